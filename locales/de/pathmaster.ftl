@@ -0,0 +1,561 @@
+# German messages for pathmaster. Messages missing here are shown in English.
+
+## Shared
+
+error = Fehler: { $error }
+error-backup = Fehler beim Anlegen der Sicherung: { $error }
+error-shell-config = Fehler beim Aktualisieren der Shell-Konfiguration: { $error }
+error-backup-mode = Ungültiger Sicherungsmodus: { $mode }. Gültige Modi sind: default, path, shell, switch
+error-stdin = Fehler beim Lesen der Verzeichnisse von der Standardeingabe: { $error }
+error-write-file = Fehler beim Schreiben von { $file }: { $error }
+warning-operation-log = Warnung: Das Vorgangsprotokoll konnte nicht geschrieben werden: { $error }
+warning-provenance = Warnung: Die Herkunft der Einträge konnte nicht gespeichert werden: { $error }
+reason-not-a-directory = existiert, ist aber kein Verzeichnis
+reason-missing = existiert nicht
+
+## Check mode and applying the new PATH
+
+check-mode-no-changes = Prüfmodus: keine Änderungen.
+check-mode-pending = Prüfmodus: Änderungen ausstehend ({ $files }); es wurde nichts geschrieben.
+container-not-persisted = Container ohne Shell-Konfiguration: PATH wurde nicht dauerhaft gespeichert.
+container-dockerfile = Um PATH im Image zu setzen, fügen Sie dies zum Dockerfile hinzu:
+env-only-apply = Nur-Umgebung-Modus: Shell-Konfigurationen wurden nicht geändert. Übernehmen Sie den neuen PATH mit:
+rehash-hint = Tipp: Führen Sie in offenen Shells nach dem erneuten Einlesen der Shell-Konfiguration '{ $command }' aus, damit neue Befehle gefunden werden.
+
+## add
+
+add-would-create = Verzeichnis '{ $path }' würde angelegt.
+add-create-failed = Fehler: Verzeichnis '{ $path }' konnte nicht angelegt werden: { $error }
+add-created = Verzeichnis '{ $path }' angelegt.
+add-invalid = Fehler: '{ $path }' { $reason }. Mit --force trotzdem hinzufügen oder mit --create anlegen.
+add-forced = Warnung: '{ $path }' { $reason }; wird trotzdem hinzugefügt (--force).
+add-moved = '{ $path }' an den Anfang von PATH verschoben.
+add-duplicate = Verzeichnis '{ $path }' ist bereits an Position { $position } in PATH; übersprungen.
+add-duplicate-as = Verzeichnis '{ $path }' ist bereits als '{ $existing }' an Position { $position } in PATH; übersprungen.
+add-added-front = '{ $path }' am Anfang von PATH hinzugefügt.
+add-added = '{ $path }' zu PATH hinzugefügt.
+add-summary-added =
+    { $count ->
+        [one] 1 Verzeichnis zu PATH hinzugefügt.
+       *[other] { $count } Verzeichnisse zu PATH hinzugefügt.
+    }
+add-summary-moved =
+    { $count ->
+        [one] 1 Verzeichnis an den Anfang von PATH verschoben.
+       *[other] { $count } Verzeichnisse an den Anfang von PATH verschoben.
+    }
+add-summary-created =
+    { $count ->
+        [one] 1 Verzeichnis angelegt:
+       *[other] { $count } Verzeichnisse angelegt:
+    }
+add-kept-declared =
+    { $count ->
+        [one] 1 Eintrag der Shell-Konfiguration behalten, der nicht im PATH dieser Sitzung ist.
+       *[other] { $count } Einträge der Shell-Konfiguration behalten, die nicht im PATH dieser Sitzung sind.
+    }
+add-nothing-added = Es wurden keine neuen Verzeichnisse zu PATH hinzugefügt.
+add-ttl-existing = '{ $path }' war bereits in PATH; es läuft jetzt am { $expires } ab.
+add-move-to-front-hint = Mit --move-to-front werden vorhandene Einträge neu angeordnet.
+
+## delete
+
+delete-none-found = Keines der Verzeichnisse wurde in PATH oder der Shell-Konfiguration gefunden.
+delete-matched = Gefundene Einträge:
+delete-config-only = Nur in der Shell-Konfiguration deklariert, nicht im PATH dieser Sitzung:
+delete-protected = Fehler: Geschützte Systemverzeichnisse werden nicht entfernt:
+delete-allow-system-hint = Mit --allow-system werden sie trotzdem entfernt.
+delete-confirm = { $count } Einträge aus PATH entfernen?
+delete-aborted = Abgebrochen; PATH wurde nicht geändert.
+delete-done = Verzeichnisse aus PATH entfernt.
+delete-index-out-of-range = Index { $index } liegt außerhalb des Bereichs; PATH hat { $count } Einträge
+delete-invalid-glob = Ungültiges Glob-Muster '{ $pattern }': { $error }
+delete-invalid-regex = Ungültiger regulärer Ausdruck '{ $pattern }': { $error }
+
+## flush
+
+flush-keeping-excluded = Ausgenommener Pfad bleibt: { $path } ({ $reason }; in keep-dirs aufgeführt)
+flush-reason-windows = Windows-Eintrag
+flush-reason-relative = relativ
+flush-reason-unavailable = nicht verfügbar
+flush-removing-windows = Windows-Pfad wird entfernt: { $path }
+flush-removing-relative = Relativer Pfad wird entfernt: '{ $path }'
+flush-absolutizing = Relativer Pfad wird absolut gemacht: '{ $path }' -> { $absolute }
+flush-removing-empty = Leerer Eintrag wird entfernt
+flush-keeping-protected = Geschützter Pfad bleibt: { $path } (mit --allow-system entfernen)
+flush-keeping-unmounted = Vorübergehend nicht verfügbarer Pfad bleibt: { $path } ({ $reason }; mit --remove-unmounted entfernen)
+flush-removing-invalid = Ungültiger Pfad wird entfernt: { $path }
+flush-nothing-found = Keine ungültigen Pfade in PATH gefunden.
+flush-done =
+    { $count ->
+        [one] 1 ungültiger Pfad entfernt und Shell-Konfiguration aktualisiert.
+       *[other] { $count } ungültige Pfade entfernt und Shell-Konfiguration aktualisiert.
+    }
+flush-absolutized =
+    { $count ->
+        [one] 1 relativer Pfad absolut gemacht.
+       *[other] { $count } relative Pfade absolut gemacht.
+    }
+flush-session-only = Warnung: PATH wurde nur für die aktuelle Sitzung geändert.
+flush-manual-update = Um die Änderungen dauerhaft zu machen, müssen Sie Ihre Shell-Konfiguration von Hand anpassen.
+flush-not-wsl = Nicht unter WSL; es gibt keine Windows-Einträge zu entfernen.
+
+## check
+
+warning = Warnung: { $warning }
+warning-save-check = Warnung: Das Prüfergebnis konnte nicht gespeichert werden: { $error }
+check-all-valid = Alle Verzeichnisse in PATH sind gültig
+check-invalid = Ungültige Verzeichnisse in PATH:
+check-unmounted = Verzeichnisse in PATH, die erst verfügbar sind, wenn ihr Dateisystem eingehängt ist:
+check-unmounted-hint = 'pathmaster flush' behält sie; mit --remove-unmounted werden sie ebenfalls entfernt
+check-not-dirs = Einträge in PATH, die existieren, aber keine Verzeichnisse sind:
+check-not-dirs-hint = (PATH-Einträge sollten das Verzeichnis nennen, das ein Programm enthält, nicht das Programm selbst)
+check-relative = Relative Einträge in PATH (sie hängen vom aktuellen Verzeichnis ab):
+check-empty =
+    { $count ->
+        [one] PATH hat 1 leeren Eintrag durch ':' am Anfang, am Ende oder doppelt (gilt als aktuelles Verzeichnis)
+       *[other] PATH hat { $count } leere Einträge durch ':' am Anfang, am Ende oder doppelt (gelten als aktuelles Verzeichnis)
+    }
+check-relative-hint = Mit 'pathmaster flush --relative remove' oder '--relative absolutize' beheben
+check-stale = Veraltete Toolchain-Verzeichnisse in PATH:
+check-stale-hint = Sie können mit 'pathmaster delete <Verzeichnis>' entfernt werden
+check-rules = Nicht erfüllte PATH-Reihenfolgeregeln:
+check-expired = Abgelaufene Einträge in PATH:
+check-expired-entry = { $path } (abgelaufen { $expires })
+check-expired-hint = Mit 'pathmaster expire' entfernen
+check-conditional = Bedingte Einträge, die nicht zu diesem Rechner passen:
+check-conditional-missing = fehlt: { $entry }
+check-conditional-unwanted = hier nicht gewünscht: { $entry }
+check-conditional-hint = Sie werden hinzugefügt oder entfernt, wenn pathmaster PATH das nächste Mal schreibt
+check-modified = Shell-Konfigurationen, deren PATH außerhalb von pathmaster geändert wurde:
+check-modified-hint = Sie deklarieren nicht mehr die Einträge, die pathmaster zuletzt geschrieben hat
+check-slow = Langsame Verzeichnisse in PATH:
+check-slow-explanation =
+    Die Shell durchsucht PATH nach jedem Befehl, den sie noch nicht ausgeführt hat,
+    und die Befehlsvervollständigung liest jeden Eintrag, daher verzögert jeder von
+    ihnen Tippfehler, neue Befehle und die Tab-Vervollständigung. Ein nicht
+    erreichbares Netzlaufwerk kann die Shell hängen lassen. Verschieben Sie solche
+    Einträge ans Ende von PATH oder entfernen Sie sie.
+check-policy = Richtlinienverstöße ({ $file }):
+check-policy-hint = Mit 'pathmaster enforce' beheben
+check-windows =
+    { $count ->
+        [one] 1 von WSL angehängter Windows-PATH-Eintrag:
+       *[other] { $count } von WSL angehängte Windows-PATH-Einträge:
+    }
+check-windows-explanation =
+    Das Durchsuchen des Windows-Dateisystems ist langsam, daher wartet jeder Befehl,
+    der auf der Linux-Seite nicht gefunden wird (auch Tippfehler und Vervollständigung),
+    auf diese Suche. Ohne sie brauchen Windows-Programme wie code oder explorer.exe
+    ihren vollständigen Pfad.
+check-windows-appended =
+    Entfernen Sie sie mit 'pathmaster clean --strip-windows' oder verhindern Sie,
+    dass WSL sie anhängt, mit appendWindowsPath = false unter [interop] in { $file }
+check-windows-configured =
+    WSL hängt sie nicht mehr an, also setzt eine Shell-Konfiguration sie noch;
+    entfernen Sie sie mit 'pathmaster clean --strip-windows'
+check-homebrew = Homebrew ({ $prefix }) steht nicht am Anfang von PATH:
+check-homebrew-explanation =
+    Programme, die auch mit dem System kommen, etwa git, python3 oder ruby,
+    starten in der Systemversion statt in der mit brew installierten.
+check-homebrew-hint = Mit 'pathmaster prepend { $dirs }' nach vorne verschieben
+check-system-invalid = Ungültige Verzeichnisse im System-PATH ({ $file }):
+check-system-hint = Mit 'pathmaster --system flush' entfernen
+check-notify-summary =
+    { $count ->
+        [one] 1 neues PATH-Problem
+       *[other] { $count } neue PATH-Probleme
+    }
+check-notify-more = und { $count } weitere
+check-quiet-summary =
+    { $count ->
+        [one] pathmaster: 1 PATH-Problem gefunden, Details mit 'pathmaster check'
+       *[other] pathmaster: { $count } PATH-Probleme gefunden, Details mit 'pathmaster check'
+    }
+
+## snapshot
+
+snapshot-saved-config = Shell-Konfiguration { $file } in der Sicherung gespeichert
+snapshot-no-config = Keine Shell-Konfiguration zu sichern
+snapshot-taken = Schnappschuss erstellt; es wurde nichts geändert.
+snapshot-named = Benannt als '{ $name }'. Anzeigen mit 'pathmaster history'.
+snapshot-auto-stamp-failed = Warnung: Der automatische Schnappschuss konnte nicht vermerkt werden: { $error }
+snapshot-timer-failed = Fehler beim Einrichten des Schnappschuss-Timers: { $error }
+snapshot-wrote-units = { $unit }.service und { $unit }.timer nach { $dir } geschrieben
+snapshot-timer-scheduled = Tägliche Schnappschüsse sind eingeplant; prüfen mit 'systemctl --user list-timers'.
+snapshot-cron-exists = Ein geplanter Schnappschuss steht bereits in Ihrer crontab.
+snapshot-cron-added = Täglicher Schnappschuss zur crontab hinzugefügt.
+
+## enforce
+
+enforce-no-policy = Keine Richtliniendatei unter { $file }; nichts durchzusetzen.
+enforce-removing = Verbotenes Verzeichnis wird entfernt: { $path }
+enforce-adding = Vorgeschriebenes Verzeichnis wird hinzugefügt: { $path }
+enforce-required-missing = Fehler: Das vorgeschriebene Verzeichnis '{ $path }' existiert nicht und kann daher nicht hinzugefügt werden.
+enforce-reordering = PATH wird gemäß den Reihenfolgeregeln der Richtlinie neu angeordnet
+enforce-already = PATH entspricht bereits { $file }.
+enforce-done = PATH entspricht jetzt { $file }.
+enforce-remaining = PATH verstößt weiterhin gegen { $file }:
+
+## adopt
+
+adopt-one-shell = Fehler: adopt bearbeitet die Konfiguration einer Shell nach der anderen; lassen Sie --all-shells, --system und --env-only weg.
+adopt-absolute-only = Fehler: adopt schreibt den ganzen PATH und braucht dafür den absoluten Exportstil (--export-style absolute).
+adopt-nothing = Keine { $shell }-Startdatei deklariert PATH; es gibt nichts zu übernehmen.
+adopt-adopting =
+    { $count ->
+        [one] PATH-Deklarationen aus 1 { $shell }-Startdatei werden übernommen:
+       *[other] PATH-Deklarationen aus { $count } { $shell }-Startdateien werden übernommen:
+    }
+adopt-duplicate = {" "}(Duplikat)
+adopt-added = Nicht im aktuellen PATH, daher am Ende hinzugefügt:
+adopt-missing = Nicht in PATH und kein Verzeichnis, daher ausgelassen:
+adopt-kept = Unverändert gelassen (innerhalb einer Bedingung oder Schleife):
+adopt-commented =
+    { $count ->
+        [one] 1 PATH-Deklaration in { $file } auskommentiert
+       *[other] { $count } PATH-Deklarationen in { $file } auskommentiert
+    }
+adopt-target-block = verwalteten Block
+adopt-target-drop-in = Drop-in-Datei
+adopt-summary = { $declared ->
+        [one] 1 Deklaration
+       *[other] { $declared } Deklarationen
+    } von { $entries ->
+        [one] 1 Eintrag
+       *[other] { $entries } Einträgen
+    } in pathmasters { $target } zusammengeführt ({ $commented } anderswo auskommentiert, { $duplicates ->
+        [one] 1 Duplikat
+       *[other] { $duplicates } Duplikate
+    } aus PATH entfernt).
+
+## undo and redo
+
+undo-log-unreadable = Fehler beim Lesen des Vorgangsprotokolls: { $error }
+undo-nothing =
+    { $command ->
+        [redo] Nichts wiederherzustellen.
+       *[undo] Nichts rückgängig zu machen.
+    }
+undo-too-many =
+    { $command ->
+        [redo] Nur { $count } Vorgang/Vorgänge können wiederhergestellt werden.
+       *[undo] Nur { $count } Vorgang/Vorgänge können rückgängig gemacht werden.
+    }
+undo-conflict = PATH hat sich geändert, seit der Vorgang aufgezeichnet wurde:
+undo-force-hint = Mit --force wird der aktuelle PATH überschrieben.
+undo-overwriting = Änderungen seit dem Vorgang werden überschrieben (--force)
+undo-reverting =
+    { $command ->
+        [redo] Wiederherstellen: pathmaster { $arguments } ({ $timestamp })
+       *[undo] Rückgängig: pathmaster { $arguments } ({ $timestamp })
+    }
+undo-done = PATH und Shell-Konfiguration aktualisiert.
+undo-reordered = Einträge umsortiert
+
+## history and restore
+
+history-no-dir = Fehler beim Ermitteln des Sicherungsverzeichnisses: { $error }
+history-none = Keine Sicherungen in { $dir } gefunden.
+history-available = Verfügbare Sicherungen:
+history-shell-only = nur Shell-Konfigurationen
+history-config-copies = Kopien von Shell-Konfigurationen:
+history-unreadable = Fehler beim Lesen der Sicherungen: { $error }
+restore-cancelled = Wiederherstellung abgebrochen; PATH wurde nicht geändert.
+restore-failed = Fehler beim Wiederherstellen von PATH: { $error }
+restore-done = PATH aus der Sicherung wiederhergestellt: { $file }
+restore-apply-hint = Bereits laufende Shells behalten ihren alten PATH, bis sie ihre Shell-Konfiguration neu einlesen: Führen Sie 'exec $SHELL' aus oder lesen Sie die Konfiguration in jeder Shell neu ein. Um ihn sofort in dieser Shell zu übernehmen, führen Sie aus:
+restore-confirm-empty = Die Sicherung { $file } enthält einen leeren PATH, sodass keine Befehle gefunden würden. Trotzdem wiederherstellen?
+backup-creating = Sicherung wird angelegt unter: { $file }
+backup-config-failed = Warnung: { $file } konnte nicht gesichert werden: { $error }
+
+## prompts and shells
+
+prompt-yes = ja
+prompt-no = nein
+prompt-not-terminal = Die Standardeingabe ist kein Terminal; mit --yes bestätigen
+zsh-no-unique-path = Warnung: { $file } deklariert nicht 'typeset -U path', daher bekommt PATH bei jedem Einlesen doppelte Einträge.
+zsh-add-unique-path = '{ $declaration }' zu { $file } hinzufügen?
+
+## writing PATH
+
+shell-env-only = Nur-Umgebung-Modus: Shell-Konfigurationen wurden nicht geändert
+shell-tmux-updated = PATH für neue tmux-Fenster aktualisiert
+shell-tmux-none = Kein tmux-Server läuft; nichts zu aktualisieren
+shell-tmux-failed = Warnung: tmux konnte nicht aktualisiert werden: { $error }
+shell-record-failed = Warnung: Der in die Konfiguration geschriebene PATH konnte nicht vermerkt werden: { $error }
+shell-all-updated = PATH für { $count } Shell(s) aktualisiert:
+shell-sources-shared = { $file } (liest { $shared } ein, für eine andere Shell geschrieben)
+shell-skipped-shared = übersprungen, { $shared } wurde bereits für eine andere Shell aktualisiert
+conditional-withdrawn = Hinweis: '{ $entry }' ist ein bedingter Eintrag. Er bleibt vorerst aus PATH heraus, wird aber beim nächsten Schreiben von PATH wieder hinzugefügt, solange seine Bedingungen gelten; entfernen Sie ihn aus conditional-entries in der Konfiguration, um ihn dauerhaft loszuwerden
+conditional-added = Bedingter Eintrag '{ $path }' zu PATH hinzugefügt
+conditional-removed = Bedingter Eintrag '{ $path }' aus PATH entfernt; seine Bedingungen gelten hier nicht
+rules-reordered = PATH gemäß den Reihenfolgeregeln neu angeordnet
+rules-not-met = Warnung: PATH-Reihenfolgeregel nicht erfüllt: { $rule }
+
+## list, show and verify
+
+error-read-file = Fehler beim Lesen von { $file }: { $error }
+warning-cache = Warnung: Der Cache konnte nicht aktualisiert werden: { $error }
+status-empty = leer
+status-relative = relativ
+status-valid = gültig
+status-invalid = ungültig
+status-not-dir = kein Verzeichnis
+status-missing = fehlt
+list-current = Aktuelle PATH-Einträge:
+list-declared = In { $file } deklarierte PATH-Einträge:
+list-existing-path = bisheriger PATH
+list-active = aktiv
+list-inactive = nicht im aktuellen PATH
+list-undeclared = Nicht in { $file } deklarierte PATH-Einträge:
+list-tree = Aktuelle PATH-Einträge ({ $count }) in { $groups } Gruppe(n):
+list-group = { $count ->
+        [one] 1 Eintrag
+       *[other] { $count } Einträge
+    }, { $invalid ->
+        [0] alle gültig
+       *[other] { $invalid } ungültig
+    }
+show-position = Position:
+show-position-value = { $positions } von { $count }
+show-not-in-path = nicht in PATH
+show-canonical = Kanonisch:
+show-status = Status:
+show-permissions = Rechte:
+show-world-writable = für alle schreibbar
+show-read-only = schreibgeschützt
+show-writable = schreibbar
+show-executables = Programme:
+show-owner = Besitzer:
+show-declared-in = Deklariert in:
+show-not-declared = keine Shell-Konfiguration erwähnt es
+show-first-backup = Erste Sicherung:
+show-no-backup = in keiner Sicherung
+verify-in-sync = PATH stimmt mit { $file } überein
+verify-missing-from-path = In { $file } deklariert, aber nicht in PATH (Konfiguration neu einlesen?):
+verify-missing-from-config = In PATH, aber nicht in { $file } deklariert (zur Laufzeit hinzugefügt?):
+verify-order = Die Einträge stehen in anderer Reihenfolge:
+verify-order-config = Konfiguration:
+verify-order-path = PATH:
+
+## shadows and ensure
+
+shadows-none = Keine verdeckten Programme gefunden.
+shadows-runs = wird ausgeführt
+shadows-version-unknown = Version unbekannt
+shadows-versions-differ = Versionen unterscheiden sich
+shadows-same-version = Alle verdeckten Kopien melden dieselbe Version.
+shadows-mismatches = Abweichende Versionen: { $names }
+ensure-none-required = Keine vorgeschriebenen Verzeichnisse; übergeben Sie sie als Argumente oder setzen Sie required-dirs in der Konfigurationsdatei.
+ensure-complete = Alle vorgeschriebenen Verzeichnisse sind bereits in PATH.
+
+## log and explain
+
+log-empty = Es wurden noch keine Vorgänge protokolliert.
+log-reordered = Einträge umsortiert oder anders geschrieben
+log-reason = Grund: { $reason }
+log-backup = Sicherung: { $file }
+log-truncated = ({ $count } ältere(r) Vorgang/Vorgänge nicht angezeigt; mit --limit 0 alle anzeigen. Protokoll: { $file })
+explain-files =
+    { $kind ->
+        [login] Startdateien, die eine { $shell }-Login-Shell liest:
+       *[other] Startdateien, die eine { $shell }-Shell ohne Login liest:
+    }
+explain-no-files = keine gefunden
+explain-path = Daraus entstehender PATH:
+explain-empty = leer; keine Startdatei setzt PATH
+explain-duplicate = Duplikat von { $position }
+explain-unexplained = Im aktuellen PATH, aber von keiner Startdatei gesetzt:
+explain-unexplained-hint = Diese stammen von dem Programm, das diese Shell gestartet hat (Terminal, IDE oder Anmeldemanager), von einem darin ausgeführten Befehl oder von einer Bedingung, die nicht zutraf.
+
+## clean and grep
+
+clean-empty = Leerer Pfadeintrag wird entfernt
+clean-invalid = Ungültiger Pfad wird entfernt: { $entry }
+clean-windows = Windows-Pfad wird entfernt: { $entry }
+clean-duplicate = Duplikat wird entfernt: { $entry } (wie Position { $position })
+clean-normalized = Normalisiere { $from } -> { $to }
+clean-kept-protected = Geschützter Pfad bleibt: { $entry } (mit --allow-system entfernen)
+clean-kept-excluded = Ausgenommener Pfad bleibt: { $entry } ({ $reason }; in keep-dirs aufgeführt)
+clean-nothing = PATH ist bereits bereinigt.
+clean-done =
+    PATH bereinigt: { $removed } ungültige und { $duplicates } doppelte Einträge entfernt, { $normalized } normalisiert; { $before } -> { $after } Einträge.
+clean-windows-done =
+    { $count } Windows-{ $count ->
+        [one] Eintrag
+       *[other] Einträge
+    } entfernt.
+grep-invalid = Fehler: ungültiges Muster '{ $pattern }': { $error }
+grep-none = Keine PATH-Einträge passen zu '{ $pattern }'.
+grep-header = PATH-Einträge, die zu '{ $pattern }' passen:
+grep-already-front = Die passenden Einträge stehen bereits am Anfang von PATH.
+grep-moved =
+    { $count } passende { $count ->
+        [one] Eintrag
+       *[other] Einträge
+    } an den Anfang von PATH verschoben.
+
+## why, scan and replace
+
+why-added = { $path } wurde am { $date } hinzugefügt durch: pathmaster { $arguments }
+why-reason = Grund: { $reason }
+why-no-reason = Es wurde kein Grund angegeben (beim nächsten Mal --reason verwenden).
+why-expires = Läuft ab: { $date } (siehe 'pathmaster expire')
+why-gone = Es ist nicht mehr in PATH.
+why-unrecorded = pathmaster weiß nicht, wie { $path } hinzugefügt wurde; es war bereits in PATH oder wurde von etwas anderem hinzugefügt.
+why-unknown = { $path } ist nicht in PATH, und pathmaster hat keinen Eintrag dazu.
+scan-failed = Fehler beim Durchsuchen der Shell-Konfigurationen: { $error }
+scan-executables = Programme pro PATH-Verzeichnis:
+replace-not-in-path = Fehler: '{ $path }' ist nicht in PATH.
+replace-same = '{ $old }' ist bereits '{ $new }'; nichts zu ersetzen.
+replace-invalid = Fehler: '{ $path }' { $reason }. Mit --force trotzdem verwenden.
+replace-forced = Warnung: '{ $path }' { $reason }; wird trotzdem verwendet (--force).
+replace-protected = Fehler: geschütztes Systemverzeichnis '{ $path }' wird nicht ersetzt.
+replace-allow-system-hint = Mit --allow-system erneut ausführen, um es trotzdem zu ersetzen.
+replace-done = '{ $old }' an Position { $position } durch '{ $new }' ersetzt.
+replace-duplicate-removed = Die Kopie von '{ $path }' an Position { $position } wurde entfernt.
+
+## expire, normalize, cache, audit and bench
+
+expire-none = Keine abgelaufenen Einträge in PATH.
+expire-removing = Entferne { $path } (abgelaufen { $date })
+expire-done =
+    { $count } { $count ->
+        [one] abgelaufener Eintrag
+       *[other] abgelaufene Einträge
+    } aus PATH entfernt.
+normalize-kept-excluded = Ausgenommener Pfad bleibt: { $from } (würde zu { $to } normalisiert; in keep-dirs aufgeführt)
+normalize-nothing = Alle PATH-Einträge sind bereits normalisiert.
+normalize-done =
+    { $count } PATH-{ $count ->
+        [one] Eintrag
+       *[other] Einträge
+    } normalisiert.
+cache-cleared = Cache unter { $file } geleert
+cache-empty = Der Cache ist bereits leer.
+cache-clear-failed = Fehler beim Leeren des Caches: { $error }
+audit-no-proc = Es lässt sich nicht feststellen, als welcher Benutzer pathmaster läuft (/proc/self ist nicht verfügbar), daher wurden Besitz und Rechte nicht geprüft.
+audit-unsupported = Besitz- und Rechteprüfungen werden nur auf Unix-Systemen unterstützt.
+audit-owner = { $path } (Besitzer: { $owner })
+audit-group = { $path } (Gruppe: { $group })
+audit-runs-as = { $path } (läuft als { $owner })
+audit-foreign = PATH-Verzeichnisse, die weder Ihnen noch root gehören:
+audit-foreign-advice =
+    Ihr Besitzer kann Programme hinzufügen, die laufen, sobald Sie einen Befehlsnamen eingeben.
+    Entfernen Sie sie mit 'pathmaster delete <Verzeichnis>', wenn Sie sie nicht brauchen,
+    oder übertragen Sie sie an root: sudo chown root: <Verzeichnis>
+audit-group-writable = Für die Gruppe beschreibbare Verzeichnisse in PATH:
+audit-group-writable-advice =
+    Jedes Gruppenmitglied kann Programme hinzufügen, die Sie per Namen starten.
+    Schreibrecht der Gruppe entfernen: chmod g-w <Verzeichnis>
+audit-world-writable = Für alle beschreibbare Verzeichnisse in PATH:
+audit-world-writable-advice =
+    Jeder Benutzer dieses Rechners kann Programme hinzufügen, die Sie per Namen starten.
+    Schreibrecht für andere entfernen: chmod o-w <Verzeichnis>,
+    oder den Eintrag mit 'pathmaster delete <Verzeichnis>' entfernen.
+audit-setuid = Setuid-Programme in PATH-Verzeichnissen:
+audit-setuid-advice =
+    Diese laufen mit den Rechten ihres Besitzers, egal wer sie startet. Werkzeuge wie
+    sudo und passwd Ihrer Distribution brauchen das; bei allem anderen prüfen Sie,
+    woher es stammt, und entfernen das Bit mit: chmod u-s <Datei>
+audit-clean = Alle { $count } PATH-Verzeichnisse werden von Ihnen oder root kontrolliert.
+bench-not-found = { $command } (nicht gefunden)
+bench-total = Gesamt
+bench-header = Durchschnittliche Zeit zum Auflösen jedes Befehls (je { $count } Suchen):
+bench-command = Befehl
+bench-current = Aktuell
+bench-cleaned = Bereinigt
+bench-time = Zeit
+bench-already-clean =
+    PATH ist bereits bereinigt ({ $count } { $count ->
+        [one] Eintrag
+       *[other] Einträge
+    }); es gibt nichts zu vergleichen.
+bench-result = Ein bereinigter PATH ({ $before } -> { $after } Einträge) würde diese Suchen { $change } machen.
+bench-clean-hint = Mit 'pathmaster clean' anwenden.
+bench-same = etwa gleich schnell
+bench-faster = { $percent } % schneller
+bench-slower = { $percent } % langsamer
+
+## lint and export
+
+lint-clobbers-zshenv = In { $file } (Zeile { $line }) gesetzter PATH wird hier überschrieben
+lint-clobbers-zshenv-fix = Erweitern Sie PATH stattdessen (PATH="/neues/verz:$PATH") oder setzen Sie ihn nur in einer der Dateien.
+lint-missing-export = PATH wird zugewiesen, aber diese Datei exportiert ihn nie
+lint-missing-export-fix = Verwenden Sie 'export PATH=...' oder fügen Sie nach der Zuweisung 'export PATH' hinzu.
+lint-addition-in-loop = PATH wird in einer Schleife erweitert, ohne auf Duplikate zu prüfen
+lint-addition-in-loop-fix = Verzeichnisse überspringen, die bereits in PATH sind: case ":$PATH:" in *":$dir:"*) ;; *) PATH="$dir:$PATH" ;; esac (in zsh funktioniert auch 'typeset -U path').
+lint-unquoted-expansion = $PATH wird ohne Anführungszeichen expandiert
+lint-unquoted-expansion-fix = Setzen Sie den Wert in Anführungszeichen, z. B. PATH="$PATH:/neues/verz", damit Einträge mit Leerzeichen erhalten bleiben.
+lint-clean =
+    Keine PATH-Probleme in { $count } Shell-{ $count ->
+        [one] Konfiguration
+       *[other] Konfigurationen
+    } gefunden.
+lint-found =
+    { $count } { $count ->
+        [one] Problem
+       *[other] Probleme
+    } in { $files } Shell-{ $files ->
+        [one] Konfiguration
+       *[other] Konfigurationen
+    } gefunden.
+export-not-utf8 = Warnung: Eintrag ist kein gültiges UTF-8 und wird übersprungen: { $path }
+
+## serve
+
+serve-conflict = Fehler: serve lässt sich nicht mit --check oder --emit kombinieren
+serve-read-failed = Fehler beim Lesen der Anfrage: { $error }
+
+## shell configs, desktop session and system PATH
+
+dotfiles-commit-failed = Warnung: Änderungen konnten nicht im Dotfiles-Repository { $repo } committet werden: { $error }
+dotfiles-nothing = Keine geänderten Dateien liegen im Dotfiles-Repository { $repo }
+dotfiles-committed = { $files } im Dotfiles-Repository { $repo } committet
+shell-config-backup = Sicherung der Shell-Konfiguration erstellt unter: { $file }
+shell-inherited-entry = Hinweis: { $path } wird über $PATH geerbt und bleibt im relativen Exportstil in neuen Shells erhalten
+shell-sourced-file = PATH wird in einer eingebundenen Datei festgelegt: { $file }
+shell-drop-in-written = PATH in Drop-in-Datei geschrieben: { $file }
+shell-drop-in-sourced = Zeile zum Einbinden der Drop-in-Datei zu { $file } hinzugefügt
+shell-drop-in-remaining = Hinweis: { $file } ändert PATH noch in { $count } Zeile(n); diese laufen vor der Drop-in-Datei.
+shell-using-custom = Verwende die Konfiguration der eigenen Shell { $shell } (erkannt über { $source })
+shell-using = Verwende die { $shell }-Konfiguration (erkannt über { $source })
+shell-custom-ignored = Warnung: eigene Shell wird ignoriert: { $error }
+transaction-restore-failed = Warnung: { $file } konnte nicht wiederhergestellt werden: { $error }
+gui-not-utf8 = Warnung: Eintrag ist kein gültiges UTF-8 und bleibt aus dem PATH der Desktop-Sitzung heraus: { $path }
+gui-session-written = PATH der Desktop-Sitzung in { $file } geschrieben
+gui-source-line = Zeile zum Einbinden zu { $file } hinzugefügt
+gui-log-out = Melden Sie sich ab und wieder an, damit Desktop-Anwendungen den neuen PATH sehen.
+gui-session-backup = Sicherung der Sitzungsdatei erstellt unter: { $file }
+gui-launch-agent = Launch-Agent { $file } geschrieben
+gui-launchctl = PATH für ab jetzt gestartete Anwendungen gesetzt; laufende Anwendungen neu starten, damit sie ihn übernehmen.
+system-no-path = { $file } setzt PATH nicht; beginne mit { $path }
+system-backup = Sicherung von { $file } erstellt unter: { $backup }
+system-updated = System-PATH in { $file } aktualisiert
+system-needs-root = Zum Aktualisieren von { $file } sind Root-Rechte nötig
+
+## problem descriptions
+
+homebrew-after-system = { $path } steht hinter { $system }
+homebrew-not-in-path = { $path } ist nicht in PATH
+unmounted-removable = Wechseldatenträger nicht eingehängt
+unmounted-automount = automatisch eingehängt unter { $path }
+slow-network-mount = auf einem { $filesystem }-Netzlaufwerk
+slow-listing = Auflisten dauerte { $milliseconds } ms
+slow-many-files = { $count } Dateien
+policy-forbidden = verboten: { $path }
+policy-missing = erforderlich, aber fehlt: { $path }
+policy-rule = Regel nicht erfüllt: { $rule }
+
+## scan
+
+scan-system-files = Systemweite Dateien (erfordert sudo):
+scan-user-files = Benutzerdateien:
+
+## backup selection
+
+restore-no-backups = Keine Sicherungen mit PATH in { $directory } gefunden.
+restore-no-previous = Es gibt keine Sicherung vor der neuesten.
+restore-no-index = Keine Sicherung Nummer { $index }; 'pathmaster history' listet { $count } Sicherung(en).
+restore-none-before = Vor { $time } wurde keine Sicherung erstellt.
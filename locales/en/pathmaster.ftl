@@ -0,0 +1,541 @@
+# English messages for pathmaster. This catalog is the fallback: every
+# message has to be here, and other catalogs may translate any subset.
+#
+# Variables are filled in by the code; keep their names when translating.
+
+## Shared
+
+error = Error: { $error }
+error-backup = Error creating backup: { $error }
+error-shell-config = Error updating shell configuration: { $error }
+error-backup-mode = Invalid backup mode: { $mode }. Valid modes are: default, path, shell, switch
+error-stdin = Error reading directories from stdin: { $error }
+error-write-file = Error writing { $file }: { $error }
+warning-operation-log = Warning: could not write operation log: { $error }
+warning-provenance = Warning: could not record where entries came from: { $error }
+reason-not-a-directory = exists but is not a directory
+reason-missing = does not exist
+
+## Check mode and applying the new PATH
+
+check-mode-no-changes = Check mode: no changes.
+check-mode-pending = Check mode: changes pending ({ $files }); nothing was written.
+container-not-persisted = Running in a container without a shell config, so PATH was not persisted.
+container-dockerfile = To set it in the image, add this to the Dockerfile:
+env-only-apply = Env-only mode: shell configs were not changed. Apply the new PATH with:
+rehash-hint = Hint: in open shells, run '{ $command }' after re-reading your shell config so new commands are found.
+
+## add
+
+add-would-create = Would create directory '{ $path }'.
+add-create-failed = Error: could not create directory '{ $path }': { $error }
+add-created = Created directory '{ $path }'.
+add-invalid = Error: '{ $path }' { $reason }. Use --force to add it anyway or --create to create it.
+add-forced = Warning: '{ $path }' { $reason }; adding anyway (--force).
+add-moved = Moved '{ $path }' to the front of PATH.
+add-duplicate = Directory '{ $path }' is already in PATH at position { $position }; skipping.
+add-duplicate-as = Directory '{ $path }' is already in PATH as '{ $existing }' at position { $position }; skipping.
+add-added-front = Added '{ $path }' to the front of PATH.
+add-added = Added '{ $path }' to PATH.
+add-summary-added = Successfully added { $count } directory(ies) to PATH.
+add-summary-moved = Moved { $count } directory(ies) to the front of PATH.
+add-summary-created = Created { $count } directory(ies):
+add-kept-declared = Kept { $count } shell config entry(ies) not in this session's PATH.
+add-nothing-added = No new directories were added to PATH.
+add-ttl-existing = '{ $path }' was already in PATH; it now expires at { $expires }.
+add-move-to-front-hint = Use --move-to-front to reposition existing entries.
+
+## delete
+
+delete-none-found = None of the directories were found in PATH or the shell config.
+delete-matched = Matched entries:
+delete-config-only = Only declared in the shell config, not in this session's PATH:
+delete-protected = Error: refusing to remove protected system directories:
+delete-allow-system-hint = Run again with --allow-system to remove them anyway.
+delete-confirm = Remove { $count } entries from PATH?
+delete-aborted = Aborted; PATH was not changed.
+delete-done = Successfully removed directories from PATH.
+delete-index-out-of-range = Index { $index } is out of range; PATH has { $count } entries
+delete-invalid-glob = Invalid glob pattern '{ $pattern }': { $error }
+delete-invalid-regex = Invalid regex '{ $pattern }': { $error }
+
+## flush
+
+flush-keeping-excluded = Keeping excluded path: { $path } ({ $reason }; listed in keep-dirs)
+flush-reason-windows = Windows entry
+flush-reason-relative = relative
+flush-reason-unavailable = not available
+flush-removing-windows = Removing Windows path: { $path }
+flush-removing-relative = Removing relative path: '{ $path }'
+flush-absolutizing = Absolutizing relative path: '{ $path }' -> { $absolute }
+flush-removing-empty = Removing empty path entry
+flush-keeping-protected = Keeping protected path: { $path } (use --allow-system to remove it)
+flush-keeping-unmounted = Keeping temporarily unavailable path: { $path } ({ $reason }; use --remove-unmounted to remove it)
+flush-removing-invalid = Removing invalid path: { $path }
+flush-nothing-found = No invalid paths found in PATH.
+flush-done = Successfully removed { $count } invalid path(s) and updated shell configuration.
+flush-absolutized = Absolutized { $count } relative path(s).
+flush-session-only = Warning: PATH environment variable was updated for current session only.
+flush-manual-update = To make changes permanent, you'll need to manually update your shell configuration.
+flush-not-wsl = Not running under WSL; there are no Windows entries to strip.
+
+## check
+
+warning = Warning: { $warning }
+warning-save-check = Warning: could not save the check result: { $error }
+check-all-valid = All directories in PATH are valid
+check-invalid = Invalid directories in PATH:
+check-unmounted = Directories in PATH that are unavailable until their filesystem is mounted:
+check-unmounted-hint = 'pathmaster flush' keeps them; add --remove-unmounted to remove them too
+check-not-dirs = Entries in PATH that exist but are not directories:
+check-not-dirs-hint = (PATH entries should name the directory containing a program, not the program itself)
+check-relative = Relative entries in PATH (they depend on the current directory):
+check-empty =
+    { $count ->
+        [one] PATH has 1 empty entry from leading, trailing, or doubled ':' (treated as the current directory)
+       *[other] PATH has { $count } empty entries from leading, trailing, or doubled ':' (treated as the current directory)
+    }
+check-relative-hint = Run 'pathmaster flush --relative remove' or '--relative absolutize' to fix them
+check-stale = Stale toolchain directories in PATH:
+check-stale-hint = They can be removed with 'pathmaster delete <directory>'
+check-rules = PATH ordering rules not met:
+check-expired = Expired entries in PATH:
+check-expired-entry = { $path } (expired { $expires })
+check-expired-hint = Run 'pathmaster expire' to remove them
+check-conditional = Conditional entries out of step with this machine:
+check-conditional-missing = missing: { $entry }
+check-conditional-unwanted = not wanted here: { $entry }
+check-conditional-hint = They are added or removed the next time pathmaster writes PATH
+check-modified = Shell configs whose PATH was changed outside pathmaster:
+check-modified-hint = They no longer declare the entries pathmaster last wrote
+check-slow = Slow directories in PATH:
+check-slow-explanation =
+    The shell searches PATH for every command it hasn't run yet, and command
+    completion lists every entry, so each of these adds its delay to typos,
+    new commands and tab completion. An unreachable network mount can hang
+    the shell. Move such entries towards the end of PATH or remove them.
+check-policy = Policy violations ({ $file }):
+check-policy-hint = Run 'pathmaster enforce' to fix them
+check-windows =
+    { $count ->
+        [one] 1 Windows PATH entry appended by WSL:
+       *[other] { $count } Windows PATH entries appended by WSL:
+    }
+check-windows-explanation =
+    Searching the Windows filesystem is slow, so every command not found on the
+    Linux side (including typos and shell completion) waits for these lookups.
+    Without them, Windows programs such as code or explorer.exe need their full path.
+check-windows-appended =
+    Remove them with 'pathmaster clean --strip-windows', or stop WSL adding them
+    with appendWindowsPath = false under [interop] in { $file }
+check-windows-configured =
+    WSL no longer appends them, so a shell config still sets them;
+    remove them with 'pathmaster clean --strip-windows'
+check-homebrew = Homebrew ({ $prefix }) is not at the front of PATH:
+check-homebrew-explanation =
+    Programs that also come with the system, such as git, python3 or ruby,
+    run the system's versions instead of the ones installed with brew.
+check-homebrew-hint = Move them in front with 'pathmaster prepend { $dirs }'
+check-system-invalid = Invalid directories in the system PATH ({ $file }):
+check-system-hint = Run 'pathmaster --system flush' to remove them
+check-notify-summary =
+    { $count ->
+        [one] 1 new PATH problem
+       *[other] { $count } new PATH problems
+    }
+check-notify-more = and { $count } more
+check-quiet-summary =
+    { $count ->
+        [one] pathmaster: 1 PATH problem found, run 'pathmaster check' for details
+       *[other] pathmaster: { $count } PATH problems found, run 'pathmaster check' for details
+    }
+
+## snapshot
+
+snapshot-saved-config = Saved shell config { $file } in the backup
+snapshot-no-config = No shell config to back up
+snapshot-taken = Snapshot taken; nothing was changed.
+snapshot-named = Named '{ $name }'. See it with 'pathmaster history'.
+snapshot-auto-stamp-failed = Warning: could not record the automatic snapshot: { $error }
+snapshot-timer-failed = Error installing the snapshot timer: { $error }
+snapshot-wrote-units = Wrote { $unit }.service and { $unit }.timer to { $dir }
+snapshot-timer-scheduled = Daily snapshots are scheduled; check with 'systemctl --user list-timers'.
+snapshot-cron-exists = A scheduled snapshot is already in your crontab.
+snapshot-cron-added = Added a daily snapshot to your crontab.
+
+## enforce
+
+enforce-no-policy = No policy file at { $file }; nothing to enforce.
+enforce-removing = Removing forbidden directory: { $path }
+enforce-adding = Adding required directory: { $path }
+enforce-required-missing = Error: required directory '{ $path }' does not exist, so it can't be added.
+enforce-reordering = Reordering PATH to satisfy the policy's ordering rules
+enforce-already = PATH already complies with { $file }.
+enforce-done = PATH now complies with { $file }.
+enforce-remaining = PATH still violates { $file }:
+
+## adopt
+
+adopt-one-shell = Error: adopt works on one shell's config at a time; drop --all-shells, --system and --env-only.
+adopt-absolute-only = Error: adopt writes the whole PATH, which needs the absolute export style (--export-style absolute).
+adopt-nothing = No { $shell } startup file declares PATH; there is nothing to adopt.
+adopt-adopting =
+    { $count ->
+        [one] Adopting PATH declarations from 1 { $shell } startup file:
+       *[other] Adopting PATH declarations from { $count } { $shell } startup files:
+    }
+adopt-duplicate = {" "}(duplicate)
+adopt-added = Not in the current PATH, so added to the end:
+adopt-missing = Not in PATH and not a directory, so left out:
+adopt-kept = Left in place (inside a condition or loop):
+adopt-commented =
+    { $count ->
+        [one] Commented out 1 PATH declaration in { $file }
+       *[other] Commented out { $count } PATH declarations in { $file }
+    }
+adopt-target-block = managed block
+adopt-target-drop-in = drop-in file
+adopt-summary = Consolidated { $declared ->
+        [one] 1 declaration
+       *[other] { $declared } declarations
+    } of { $entries ->
+        [one] 1 entry
+       *[other] { $entries } entries
+    } into pathmaster's { $target } ({ $commented } commented out elsewhere, { $duplicates ->
+        [one] 1 duplicate
+       *[other] { $duplicates } duplicates
+    } removed from PATH).
+
+## undo and redo
+
+undo-log-unreadable = Error reading operation log: { $error }
+undo-nothing =
+    { $command ->
+        [redo] Nothing to redo.
+       *[undo] Nothing to undo.
+    }
+undo-too-many =
+    { $command ->
+        [redo] Only { $count } operation(s) can be redone.
+       *[undo] Only { $count } operation(s) can be undone.
+    }
+undo-conflict = PATH has changed since the operation was recorded:
+undo-force-hint = Run again with --force to overwrite the current PATH.
+undo-overwriting = Overwriting changes made since the operation (--force)
+undo-reverting =
+    { $command ->
+        [redo] Redoing: pathmaster { $arguments } ({ $timestamp })
+       *[undo] Undoing: pathmaster { $arguments } ({ $timestamp })
+    }
+undo-done = PATH and shell configuration updated.
+undo-reordered = entries reordered
+
+## history and restore
+
+history-no-dir = Error getting backup directory: { $error }
+history-none = No backups found in { $dir }.
+history-available = Available backups:
+history-shell-only = shell configs only
+history-config-copies = Shell config copies:
+history-unreadable = Error reading backups: { $error }
+restore-cancelled = Restore cancelled; PATH was not changed.
+restore-failed = Error restoring PATH: { $error }
+restore-done = PATH restored from backup: { $file }
+restore-apply-hint = Shells that are already running keep their old PATH until they re-read their shell config: run 'exec $SHELL' or source the config in each. To apply it to this shell now, run:
+restore-confirm-empty = Backup { $file } holds an empty PATH, so no commands would be found. Restore it anyway?
+backup-creating = Creating backup at: { $file }
+backup-config-failed = Warning: could not back up { $file }: { $error }
+
+## prompts and shells
+
+prompt-yes = yes
+prompt-no = no
+prompt-not-terminal = stdin is not a terminal; pass --yes to confirm
+zsh-no-unique-path = Warning: { $file } doesn't declare 'typeset -U path', so PATH gets duplicate entries each time it's sourced.
+zsh-add-unique-path = Add '{ $declaration }' to { $file }?
+
+## writing PATH
+
+shell-env-only = Env-only mode: shell configs were not modified
+shell-tmux-updated = Updated PATH for new tmux panes
+shell-tmux-none = No tmux server is running; nothing to update
+shell-tmux-failed = Warning: could not update tmux: { $error }
+shell-record-failed = Warning: could not record the PATH written to the config: { $error }
+shell-all-updated = Updated PATH for { $count } shell(s):
+shell-sources-shared = { $file } (sources { $shared }, written for another shell)
+shell-skipped-shared = skipped, { $shared } is already updated for another shell
+conditional-withdrawn = Note: '{ $entry }' is a conditional entry. It stays out of PATH for now, but is added back the next time PATH is written while its conditions hold; remove it from conditional-entries in the config to drop it for good
+conditional-added = Added conditional entry '{ $path }' to PATH
+conditional-removed = Removed conditional entry '{ $path }' from PATH; its conditions don't hold here
+rules-reordered = Reordered PATH to satisfy ordering rules
+rules-not-met = Warning: PATH ordering rule not met: { $rule }
+
+## list, show and verify
+
+error-read-file = Error reading { $file }: { $error }
+warning-cache = Warning: could not update cache: { $error }
+status-empty = empty
+status-relative = relative
+status-valid = valid
+status-invalid = invalid
+status-not-dir = not a directory
+status-missing = missing
+list-current = Current PATH entries:
+list-declared = PATH entries declared in { $file }:
+list-existing-path = existing PATH
+list-active = active
+list-inactive = not in current PATH
+list-undeclared = PATH entries not declared in { $file }:
+list-tree = Current PATH entries ({ $count }) in { $groups } group(s):
+list-group = { $count ->
+        [one] 1 entry
+       *[other] { $count } entries
+    }, { $invalid ->
+        [0] all valid
+       *[other] { $invalid } invalid
+    }
+show-position = Position:
+show-position-value = { $positions } of { $count }
+show-not-in-path = not in PATH
+show-canonical = Canonical:
+show-status = Status:
+show-permissions = Permissions:
+show-world-writable = writable by anyone
+show-read-only = read-only
+show-writable = writable
+show-executables = Executables:
+show-owner = Owner:
+show-declared-in = Declared in:
+show-not-declared = no shell config mentions it
+show-first-backup = First backup:
+show-no-backup = not in any backup
+verify-in-sync = PATH matches { $file }
+verify-missing-from-path = Declared in { $file } but not in PATH (re-source the config?):
+verify-missing-from-config = In PATH but not declared in { $file } (added at runtime?):
+verify-order = Entries are in a different order:
+verify-order-config = config:
+verify-order-path = PATH:
+
+## shadows and ensure
+
+shadows-none = No shadowed executables found.
+shadows-runs = runs
+shadows-version-unknown = version unknown
+shadows-versions-differ = versions differ
+shadows-same-version = All shadowed copies report the same version.
+shadows-mismatches = Version mismatches: { $names }
+ensure-none-required = No required directories; pass them as arguments or set required-dirs in the config file.
+ensure-complete = All required directories are already in PATH.
+
+## log and explain
+
+log-empty = No operations have been logged yet.
+log-reordered = entries reordered or respelled
+log-reason = reason: { $reason }
+log-backup = backup: { $file }
+log-truncated = ({ $count } older operation(s) not shown; use --limit 0 to show all. Log: { $file })
+explain-files =
+    { $kind ->
+        [login] Startup files a { $shell } login shell reads:
+       *[other] Startup files a { $shell } non-login shell reads:
+    }
+explain-no-files = none found
+explain-path = PATH they build:
+explain-empty = empty; no startup file sets PATH
+explain-duplicate = duplicate of { $position }
+explain-unexplained = In your current PATH, but not set by any startup file:
+explain-unexplained-hint = These come from the program that started this shell (a terminal, IDE or login manager), a command run in it, or a condition that didn't hold.
+
+## clean and grep
+
+clean-empty = Removing empty path entry
+clean-invalid = Removing invalid path: { $entry }
+clean-windows = Removing Windows path: { $entry }
+clean-duplicate = Removing duplicate: { $entry } (same as position { $position })
+clean-normalized = Normalizing { $from } -> { $to }
+clean-kept-protected = Keeping protected path: { $entry } (use --allow-system to remove it)
+clean-kept-excluded = Keeping excluded path: { $entry } ({ $reason }; listed in keep-dirs)
+clean-nothing = PATH is already clean.
+clean-done =
+    Cleaned PATH: removed { $removed } invalid and { $duplicates } duplicate { $duplicates ->
+        [one] entry
+       *[other] entries
+    }, normalized { $normalized }; { $before } -> { $after } entries.
+clean-windows-done =
+    Removed { $count } Windows { $count ->
+        [one] entry
+       *[other] entries
+    }.
+grep-invalid = Error: invalid pattern '{ $pattern }': { $error }
+grep-none = No PATH entries match '{ $pattern }'.
+grep-header = PATH entries matching '{ $pattern }':
+grep-already-front = The matching entries are already at the front of PATH.
+grep-moved =
+    Moved { $count } matching { $count ->
+        [one] entry
+       *[other] entries
+    } to the front of PATH.
+
+## why, scan and replace
+
+why-added = { $path } was added on { $date } by: pathmaster { $arguments }
+why-reason = Reason: { $reason }
+why-no-reason = No reason was given (use --reason next time).
+why-expires = Expires: { $date } (see 'pathmaster expire')
+why-gone = It is no longer in PATH.
+why-unrecorded = pathmaster has no record of how { $path } was added; it was already in PATH or was added by something else.
+why-unknown = { $path } is not in PATH, and pathmaster has no record of it.
+scan-failed = Error scanning shell configs: { $error }
+scan-executables = Executables per PATH directory:
+replace-not-in-path = Error: '{ $path }' is not in PATH.
+replace-same = '{ $old }' is already '{ $new }'; nothing to replace.
+replace-invalid = Error: '{ $path }' { $reason }. Use --force to use it anyway.
+replace-forced = Warning: '{ $path }' { $reason }; using it anyway (--force).
+replace-protected = Error: refusing to replace protected system directory '{ $path }'.
+replace-allow-system-hint = Run again with --allow-system to replace it anyway.
+replace-done = Replaced '{ $old }' with '{ $new }' at position { $position }.
+replace-duplicate-removed = Removed the copy of '{ $path }' that was at position { $position }.
+
+## expire, normalize, cache, audit and bench
+
+expire-none = No expired entries in PATH.
+expire-removing = Removing { $path } (expired { $date })
+expire-done =
+    Removed { $count } expired { $count ->
+        [one] entry
+       *[other] entries
+    } from PATH.
+normalize-kept-excluded = Keeping excluded path: { $from } (would be normalized to { $to }; listed in keep-dirs)
+normalize-nothing = All PATH entries are already normalized.
+normalize-done =
+    Successfully normalized { $count } PATH { $count ->
+        [one] entry
+       *[other] entries
+    }.
+cache-cleared = Cleared cache at { $file }
+cache-empty = Cache is already empty.
+cache-clear-failed = Error clearing cache: { $error }
+audit-no-proc = Cannot tell which user pathmaster runs as (/proc/self is unavailable), so ownership and permissions weren't audited.
+audit-unsupported = Ownership and permission audits are only supported on Unix systems.
+audit-owner = { $path } (owner: { $owner })
+audit-group = { $path } (group: { $group })
+audit-runs-as = { $path } (runs as { $owner })
+audit-foreign = PATH directories owned by someone other than you or root:
+audit-foreign-advice =
+    Their owner can add programs that run whenever you type a command name.
+    Remove them with 'pathmaster delete <directory>' if you don't need them,
+    or have them owned by root: sudo chown root: <directory>
+audit-group-writable = Group-writable directories in PATH:
+audit-group-writable-advice =
+    Every member of the group can add programs that you run by name.
+    Remove group write permission: chmod g-w <directory>
+audit-world-writable = World-writable directories in PATH:
+audit-world-writable-advice =
+    Any user on this machine can add programs that you run by name.
+    Remove write permission for others: chmod o-w <directory>,
+    or remove the entry with 'pathmaster delete <directory>'.
+audit-setuid = Setuid executables in PATH directories:
+audit-setuid-advice =
+    These run with their owner's privileges whoever starts them. Tools such as
+    sudo and passwd from your distribution need this; for anything else, check
+    where it came from and remove the bit with: chmod u-s <file>
+audit-clean = All { $count } PATH directories are controlled by you or root.
+bench-not-found = { $command } (not found)
+bench-total = Total
+bench-header = Average time to resolve each command ({ $count } lookups each):
+bench-command = Command
+bench-current = Current
+bench-cleaned = Cleaned
+bench-time = Time
+bench-already-clean =
+    PATH is already clean ({ $count } { $count ->
+        [one] entry
+       *[other] entries
+    }); there is nothing to compare it with.
+bench-result = Cleaning PATH ({ $before } -> { $after } entries) would make these lookups { $change }.
+bench-clean-hint = Run 'pathmaster clean' to apply it.
+bench-same = take about as long
+bench-faster = { $percent }% faster
+bench-slower = { $percent }% slower
+
+## lint and export
+
+lint-clobbers-zshenv = PATH set in { $file } (line { $line }) is overwritten here
+lint-clobbers-zshenv-fix = Extend PATH instead (PATH="/new/dir:$PATH"), or set it in only one of the files.
+lint-missing-export = PATH is assigned, but this file never exports it
+lint-missing-export-fix = Use 'export PATH=...', or add 'export PATH' after the assignment.
+lint-addition-in-loop = PATH is extended inside a loop without checking for duplicates
+lint-addition-in-loop-fix = Skip directories already in PATH: case ":$PATH:" in *":$dir:"*) ;; *) PATH="$dir:$PATH" ;; esac (in zsh, 'typeset -U path' also works).
+lint-unquoted-expansion = $PATH is expanded without quotes
+lint-unquoted-expansion-fix = Quote the value, e.g. PATH="$PATH:/new/dir", so entries with spaces survive.
+lint-clean =
+    No PATH problems found in { $count } shell { $count ->
+        [one] config
+       *[other] configs
+    }.
+lint-found =
+    Found { $count } { $count ->
+        [one] problem
+       *[other] problems
+    } in { $files } shell { $files ->
+        [one] config
+       *[other] configs
+    }.
+export-not-utf8 = Warning: skipping entry that isn't valid UTF-8: { $path }
+
+## serve
+
+serve-conflict = Error: serve can't be combined with --check or --emit
+serve-read-failed = Error reading request: { $error }
+
+## shell configs, desktop session and system PATH
+
+dotfiles-commit-failed = Warning: could not commit changes to dotfiles repository { $repo }: { $error }
+dotfiles-nothing = No modified files are inside dotfiles repository { $repo }
+dotfiles-committed = Committed { $files } to dotfiles repository { $repo }
+shell-config-backup = Created backup of shell config at: { $file }
+shell-inherited-entry = Note: { $path } is inherited through $PATH and stays in new shells in relative export style
+shell-sourced-file = PATH is declared in sourced file: { $file }
+shell-drop-in-written = Wrote PATH to drop-in file: { $file }
+shell-drop-in-sourced = Added drop-in source line to { $file }
+shell-drop-in-remaining = Note: { $file } still modifies PATH on { $count } line(s); those run before the drop-in file.
+shell-using-custom = Using custom { $shell } shell config (detected from { $source })
+shell-using = Using { $shell } shell config (detected from { $source })
+shell-custom-ignored = Warning: ignoring custom shell: { $error }
+transaction-restore-failed = Warning: could not restore { $file }: { $error }
+gui-not-utf8 = Warning: leaving entry that isn't valid UTF-8 out of the desktop session PATH: { $path }
+gui-session-written = Wrote desktop session PATH to { $file }
+gui-source-line = Added source line to { $file }
+gui-log-out = Log out and back in for desktop applications to see the new PATH.
+gui-session-backup = Created backup of session file at: { $file }
+gui-launch-agent = Wrote launch agent { $file }
+gui-launchctl = Set PATH for applications launched from now on; restart running ones to pick it up.
+system-no-path = { $file } doesn't set PATH; starting from { $path }
+system-backup = Created backup of { $file } at: { $backup }
+system-updated = Updated system PATH in { $file }
+system-needs-root = Root privileges are needed to update { $file }
+
+## problem descriptions
+
+homebrew-after-system = { $path } comes after { $system }
+homebrew-not-in-path = { $path } is not in PATH
+unmounted-removable = removable media not mounted
+unmounted-automount = automounted under { $path }
+slow-network-mount = on a { $filesystem } network mount
+slow-listing = listing took { $milliseconds } ms
+slow-many-files = { $count } files
+policy-forbidden = forbidden: { $path }
+policy-missing = required but missing: { $path }
+policy-rule = rule not met: { $rule }
+
+## scan
+
+scan-system-files = System-level files (requires sudo):
+scan-user-files = User-level files:
+
+## backup selection
+
+restore-no-backups = No backups holding a PATH found in { $directory }.
+restore-no-previous = There is no backup before the latest one.
+restore-no-index = No backup number { $index }; 'pathmaster history' lists { $count } backup(s).
+restore-none-before = No backup was taken before { $time }.
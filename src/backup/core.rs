@@ -1,56 +1,208 @@
 //! Core backup functionality for pathmaster.
 
-use chrono::Local;
+use crate::backup::mode::{self, BackupMode};
+use crate::config;
+use crate::msg;
+use crate::utils::check_mode;
+use crate::utils::path::{os_str_bytes, os_string_from_bytes};
+use crate::utils::shell::{factory, locations};
+use chrono::{Local, NaiveDateTime};
 use lazy_static::lazy_static;
 use serde::{Deserialize, Serialize};
 use std::env;
+use std::ffi::{OsStr, OsString};
 use std::fs::{self, File};
 use std::io;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Mutex;
 
+/// Format of the timestamp in backup file names
+pub const TIMESTAMP_FORMAT: &str = "%Y%m%d%H%M%S";
+
 lazy_static! {
     static ref BACKUP_DIR: Mutex<Option<PathBuf>> = Mutex::new(None);
+    static ref LAST_BACKUP: Mutex<Option<PathBuf>> = Mutex::new(None);
+}
+
+/// A shell config saved in a backup
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ShellConfigCopy {
+    /// Shell the config belongs to, e.g. `zsh`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shell: Option<String>,
+    /// The config file PATH is written to
+    pub file: PathBuf,
+    /// Its content when the backup was taken
+    pub content: String,
 }
 
 /// Represents a PATH backup with timestamp and path data
+///
+/// JSON strings must be valid UTF-8, so a PATH containing other bytes is
+/// stored twice: `path` holds a lossy copy for display, and `path_bytes`
+/// holds the exact bytes used when restoring. Backups of UTF-8 PATHs have
+/// no `path_bytes` field.
+///
+/// Depending on the backup mode, the shell configs PATH is written to are
+/// embedded in `shell_configs`, and shell-only backups leave `path` empty.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Backup {
     /// Timestamp when backup was created
     pub timestamp: String,
     /// Complete PATH string at backup time
     pub path: String,
+    /// Raw PATH bytes, present only when PATH isn't valid UTF-8
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path_bytes: Option<Vec<u8>>,
+    /// Name given to a snapshot with `snapshot --name`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// Set when PATH wasn't saved (`--backup-mode shell`)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub shell_only: bool,
+    /// Shell configs saved with the backup
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub shell_configs: Vec<ShellConfigCopy>,
+}
+
+impl Backup {
+    /// Creates a backup of a PATH value, keeping non-UTF-8 bytes intact
+    pub fn new(timestamp: String, path: &OsStr) -> Self {
+        let path_bytes = match path.to_str() {
+            Some(_) => None,
+            None => Some(os_str_bytes(path)),
+        };
+
+        Backup {
+            timestamp,
+            path: path.to_string_lossy().into_owned(),
+            path_bytes,
+            label: None,
+            shell_only: false,
+            shell_configs: Vec::new(),
+        }
+    }
+
+    /// Returns the backed-up PATH, preferring the raw bytes when present
+    pub fn path_os(&self) -> OsString {
+        match &self.path_bytes {
+            Some(bytes) => os_string_from_bytes(bytes.clone()),
+            None => OsString::from(&self.path),
+        }
+    }
 }
 
-/// Sets a custom backup directory (primarily for testing)
+/// Sets the backup directory for this run, ahead of any configured one (used by tests)
 #[allow(dead_code)]
 pub fn set_backup_dir(dir: PathBuf) -> io::Result<()> {
-    let mut backup_dir = BACKUP_DIR.lock().map_err(|_| {
-        io::Error::new(
-            io::ErrorKind::Other,
-            "Failed to lock backup directory mutex",
-        )
-    })?;
+    let mut backup_dir = BACKUP_DIR
+        .lock()
+        .map_err(|_| io::Error::other("Failed to lock backup directory mutex"))?;
     *backup_dir = Some(dir);
     Ok(())
 }
 
 /// Gets the directory where backups are stored
 ///
+/// `history`, `restore` and every backup agree on it: `$PATHMASTER_BACKUP_DIR`
+/// when set, otherwise `backup-dir` from the config file, otherwise
+/// `~/.pathmaster/backups`.
+///
 /// # Returns
 /// * `PathBuf` containing the path to the backup directory
 pub fn get_backup_dir() -> io::Result<PathBuf> {
-    let backup_dir = BACKUP_DIR.lock().map_err(|_| {
-        io::Error::new(
-            io::ErrorKind::Other,
-            "Failed to lock backup directory mutex",
+    let backup_dir = BACKUP_DIR
+        .lock()
+        .map_err(|_| io::Error::other("Failed to lock backup directory mutex"))?;
+
+    Ok(backup_dir
+        .clone()
+        .or_else(|| config::get_config().backup_dir)
+        .unwrap_or_else(|| locations::home_dir().join(".pathmaster/backups")))
+}
+
+/// Returns the backup file created most recently in this run, if any
+pub fn last_backup() -> Option<PathBuf> {
+    LAST_BACKUP.lock().ok().and_then(|last| last.clone())
+}
+
+/// A backup file and when it was taken
+#[derive(Debug, Clone, PartialEq)]
+pub struct BackupEntry {
+    /// The backup file
+    pub file: PathBuf,
+    /// When the backup was taken
+    pub taken: NaiveDateTime,
+    /// Whether the backup holds only shell configs and no PATH
+    pub shell_only: bool,
+}
+
+/// Lists the backups in a directory, oldest first
+///
+/// Backups are ordered by the timestamp stored in them, so their file names
+/// don't matter. Files that aren't backups, such as notes or editor swap
+/// files, are skipped. A backup with an unreadable timestamp falls back to
+/// the one in a `backup_<timestamp>.json` file name. Backups taken within
+/// the same second are ordered by the counter in their names.
+pub fn list_backups(backup_dir: &Path) -> io::Result<Vec<BackupEntry>> {
+    let stamp = |file: &Path| -> Option<String> {
+        let name = file.file_name()?.to_string_lossy().into_owned();
+        Some(
+            name.strip_prefix("backup_")?
+                .strip_suffix(".json")?
+                .to_string(),
         )
-    })?;
+    };
+    let counter = |file: &Path| stamp(file).map_or(1, |stamp| split_counter(&stamp).1);
+    let mut backups: Vec<BackupEntry> = fs::read_dir(backup_dir)?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|file| file.is_file() && file.extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|file| {
+            let backup: Backup = serde_json::from_str(&fs::read_to_string(&file).ok()?).ok()?;
+            let taken = parse_timestamp(&backup.timestamp)
+                .or_else(|| parse_timestamp(split_counter(&stamp(&file)?).0))?;
+            Some(BackupEntry {
+                file,
+                taken,
+                shell_only: backup.shell_only,
+            })
+        })
+        .collect();
+    backups.sort_by(|a, b| {
+        a.taken
+            .cmp(&b.taken)
+            .then_with(|| counter(&a.file).cmp(&counter(&b.file)))
+            .then_with(|| a.file.cmp(&b.file))
+    });
+    Ok(backups)
+}
 
-    Ok(backup_dir.clone().unwrap_or_else(|| {
-        let home_dir = dirs_next::home_dir().unwrap_or_else(|| PathBuf::from("/"));
-        home_dir.join(".pathmaster/backups")
-    }))
+/// Returns the first path `name` gives that no file has yet: the one
+/// without a suffix, then those with `_2`, `_3`, ... after the timestamp,
+/// so backups taken within the same second don't replace each other
+pub fn unused_path(name: impl Fn(&str) -> PathBuf) -> PathBuf {
+    let mut path = name("");
+    let mut counter = 2;
+    while path.exists() {
+        path = name(&format!("_{}", counter));
+        counter += 1;
+    }
+    path
+}
+
+/// Splits the timestamp in a backup file name from the counter added by
+/// [`unused_path`], which is 1 when there is none
+pub fn split_counter(stamp: &str) -> (&str, u32) {
+    stamp
+        .rsplit_once('_')
+        .and_then(|(timestamp, counter)| Some((timestamp, counter.parse().ok()?)))
+        .unwrap_or((stamp, 1))
+}
+
+/// Parses a backup timestamp such as `20240321120000`
+pub fn parse_timestamp(timestamp: &str) -> Option<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(timestamp.trim(), TIMESTAMP_FORMAT).ok()
 }
 
 /// Creates a new backup of the current PATH environment
@@ -59,39 +211,139 @@ pub fn get_backup_dir() -> io::Result<PathBuf> {
 /// * `Ok(())` on successful backup creation
 /// * `Err(io::Error)` if backup creation fails
 pub fn create_backup() -> io::Result<()> {
-    let backup_dir = get_backup_dir()?;
+    create_labeled_backup(None).map(|_| ())
+}
 
-    // Create backup directory if it doesn't exist
-    fs::create_dir_all(&backup_dir)?;
+/// Creates a backup of the current PATH, optionally naming it
+///
+/// What is saved follows the backup mode set for this run.
+///
+/// # Returns
+/// * `Ok(PathBuf)` - The backup file
+/// * `Err(io::Error)` if backup creation fails
+pub fn create_labeled_backup(label: Option<&str>) -> io::Result<PathBuf> {
+    create_backup_in_mode(label, mode::get_backup_mode()).map(|(file, _)| file)
+}
 
-    let timestamp = Local::now().format("%Y%m%d%H%M%S").to_string();
-    let path = env::var("PATH").unwrap_or_default();
+/// Creates a backup saving PATH, the shell configs PATH is written to, or
+/// both, as `mode` says
+///
+/// # Arguments
+///
+/// * `label` - Optional name shown by `history`
+/// * `mode` - What to save
+///
+/// # Returns
+/// * `Ok((PathBuf, Backup))` - The backup file and what was saved in it
+/// * `Err(io::Error)` if backup creation fails
+pub fn create_backup_in_mode(
+    label: Option<&str>,
+    mode: BackupMode,
+) -> io::Result<(PathBuf, Backup)> {
+    write_backup(label, mode, true)
+}
+
+/// Creates a backup like [`create_backup_in_mode`] without printing anything
+pub fn create_quiet_backup(label: Option<&str>, mode: BackupMode) -> io::Result<(PathBuf, Backup)> {
+    write_backup(label, mode, false)
+}
 
-    let backup = Backup {
-        timestamp: timestamp.clone(),
-        path,
+/// Writes a backup, announcing the file on standard output when `announce` is set
+fn write_backup(
+    label: Option<&str>,
+    mode: BackupMode,
+    announce: bool,
+) -> io::Result<(PathBuf, Backup)> {
+    let backup_dir = get_backup_dir()?;
+    let timestamp = Local::now().format(TIMESTAMP_FORMAT).to_string();
+    let backup_file =
+        unused_path(|suffix| backup_dir.join(format!("backup_{}{}.json", timestamp, suffix)));
+
+    let path = if mode.should_backup_path() {
+        env::var_os("PATH").unwrap_or_default()
+    } else {
+        OsString::new()
     };
+    let mut backup = Backup::new(timestamp.clone(), &path);
+    backup.label = label.map(str::to_string);
+    backup.shell_only = !mode.should_backup_path();
+
+    // Check mode writes nothing, backups included
+    if check_mode::is_check_mode() {
+        return Ok((backup_file, backup));
+    }
+
+    if mode.should_backup_shell() {
+        backup.shell_configs = shell_config_copies();
+    }
 
-    let backup_file = backup_dir.join(format!("backup_{}.json", timestamp));
-    println!("Creating backup at: {:?}", backup_file); // Debug print
+    // Create backup directory if it doesn't exist
+    fs::create_dir_all(&backup_dir)?;
+
+    if announce {
+        println!(
+            "{}",
+            msg!("backup-creating", file = format!("{:?}", backup_file))
+        );
+    }
 
     let file = File::create(&backup_file)?;
     serde_json::to_writer_pretty(file, &backup)?;
 
     // Verify file was created
     if !backup_file.exists() {
-        return Err(io::Error::new(
-            io::ErrorKind::Other,
-            format!("Failed to create backup file at {:?}", backup_file),
-        ));
+        return Err(io::Error::other(format!(
+            "Failed to create backup file at {:?}",
+            backup_file
+        )));
     }
 
-    Ok(())
+    if let Ok(mut last) = LAST_BACKUP.lock() {
+        *last = Some(backup_file.clone());
+    }
+    Ok((backup_file, backup))
+}
+
+/// Reads the shell configs PATH is written to: the current shell's, or
+/// every configured one with `--all-shells`
+///
+/// Configs that don't exist yet are skipped; ones that can't be read are
+/// skipped with a warning rather than failing the backup.
+fn shell_config_copies() -> Vec<ShellConfigCopy> {
+    let settings = config::get_config();
+    if settings.env_only || settings.system {
+        return Vec::new();
+    }
+    let handlers = if settings.all_shells {
+        factory::configured_shell_handlers()
+    } else {
+        vec![factory::get_shell_handler(settings.shell)]
+    };
+
+    handlers
+        .iter()
+        .map(|handler| (handler.name(), handler.get_target_path()))
+        .filter(|(_, file)| file.exists())
+        .filter_map(|(shell, file)| match fs::read_to_string(&file) {
+            Ok(content) => Some(ShellConfigCopy {
+                shell: Some(shell),
+                file,
+                content,
+            }),
+            Err(e) => {
+                eprintln!("{}", msg!("backup-config-failed", file = file, error = e));
+                None
+            }
+        })
+        .collect()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::utils::root;
+    use crate::utils::shell::locations;
+    use crate::utils::shell::types::ShellType;
     use serial_test::serial;
     use std::fs;
     use tempfile::TempDir;
@@ -148,10 +400,8 @@ mod tests {
 
         // List directory contents for debugging
         println!("Directory contents after backup:");
-        for entry in fs::read_dir(&backup_dir)? {
-            if let Ok(entry) = entry {
-                println!("  {:?}", entry.path());
-            }
+        for entry in fs::read_dir(&backup_dir)?.flatten() {
+            println!("  {:?}", entry.path());
         }
 
         // Verify backup was created
@@ -167,7 +417,7 @@ mod tests {
         // Find and verify the backup file
         let backup_files: Vec<_> = fs::read_dir(&backup_dir)?
             .filter_map(Result::ok)
-            .filter(|e| e.path().extension().map_or(false, |ext| ext == "json"))
+            .filter(|e| e.path().extension().is_some_and(|ext| ext == "json"))
             .collect();
 
         assert_eq!(backup_files.len(), 1, "Expected exactly one backup file");
@@ -183,6 +433,117 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    #[serial]
+    fn test_labeled_backup() -> io::Result<()> {
+        let temp_dir = TempDir::new()?;
+        set_backup_dir(temp_dir.path().to_path_buf())?;
+
+        let file = create_labeled_backup(Some("before-installer"))?;
+        let backup: Backup = serde_json::from_str(&fs::read_to_string(file)?)?;
+        assert_eq!(backup.label.as_deref(), Some("before-installer"));
+        Ok(())
+    }
+
+    #[test]
+    #[serial]
+    fn test_backup_modes() -> io::Result<()> {
+        // Under --root, the shell config saved is the one in the temporary home
+        let temp_dir = TempDir::new()?;
+        root::set_root(Some(temp_dir.path()))?;
+        let home = locations::home_dir();
+        fs::create_dir_all(&home)?;
+        fs::write(home.join(".bashrc"), "export PATH=/usr/bin:/bin\n")?;
+        config::set_config(config::Config {
+            shell: Some(ShellType::Bash),
+            ..Default::default()
+        });
+        set_backup_dir(temp_dir.path().join("backups"))?;
+        env::set_var("PATH", "/usr/bin:/bin");
+
+        let backups = (|| -> io::Result<(PathBuf, PathBuf, Backup)> {
+            let (path_only, _) = create_backup_in_mode(Some("path"), BackupMode::PathOnly)?;
+            let (shell_only, saved) = create_backup_in_mode(Some("shell"), BackupMode::ShellOnly)?;
+            Ok((path_only, shell_only, saved))
+        })();
+        root::set_root(None)?;
+        config::clear_config();
+        let (path_only, shell_only, saved) = backups?;
+        let backup: Backup = serde_json::from_str(&fs::read_to_string(&path_only)?)?;
+        let shell_only: Backup = serde_json::from_str(&fs::read_to_string(&shell_only)?)?;
+
+        assert_eq!(backup.path, "/usr/bin:/bin");
+        assert!(!backup.shell_only);
+        assert!(backup.shell_configs.is_empty());
+
+        let backup = shell_only;
+        assert!(backup.shell_only);
+        assert_eq!(backup.path, "");
+        assert_eq!(backup.shell_configs, saved.shell_configs);
+        assert_eq!(backup.shell_configs.len(), 1);
+        assert_eq!(backup.shell_configs[0].file, home.join(".bashrc"));
+        assert_eq!(
+            backup.shell_configs[0].content,
+            "export PATH=/usr/bin:/bin\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_backup_without_shell_fields() {
+        let backup: Backup =
+            serde_json::from_str(r#"{"timestamp": "20240321120000", "path": "/usr/bin"}"#).unwrap();
+        assert!(!backup.shell_only);
+        assert!(backup.shell_configs.is_empty());
+
+        let json = serde_json::to_string(&backup).unwrap();
+        assert!(!json.contains("shell"));
+    }
+
+    #[test]
+    fn test_list_backups_mixed_contents() -> io::Result<()> {
+        let temp_dir = TempDir::new()?;
+        let dir = temp_dir.path();
+        let write = |name: &str, timestamp: &str| {
+            let backup = Backup::new(timestamp.to_string(), OsStr::new("/usr/bin"));
+            fs::write(dir.join(name), serde_json::to_string(&backup).unwrap())
+        };
+
+        write("backup_20240321120000.json", "20240321120000")?;
+        // Sorts last by name but is the oldest
+        write("backup_zz-renamed.json", "20240101000000")?;
+        // Named differently but the newest
+        write("before-installer.json", "20240322080000")?;
+        // Timestamp field unreadable, so the file name is used
+        write("backup_20240310000000.json", "unknown")?;
+        fs::write(dir.join("notes.json"), "{\"todo\": []}")?;
+        fs::write(dir.join("backup_20991231235959.json"), "not json")?;
+        fs::write(dir.join("README.txt"), "backups live here")?;
+        fs::create_dir(dir.join("backup_20991231235959"))?;
+
+        let names: Vec<String> = list_backups(dir)?
+            .iter()
+            .map(|backup| {
+                backup
+                    .file
+                    .file_name()
+                    .unwrap()
+                    .to_string_lossy()
+                    .into_owned()
+            })
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "backup_zz-renamed.json",
+                "backup_20240310000000.json",
+                "backup_20240321120000.json",
+                "before-installer.json",
+            ]
+        );
+        Ok(())
+    }
+
     #[test]
     #[serial]
     fn test_multiple_backups() -> io::Result<()> {
@@ -208,10 +569,8 @@ mod tests {
 
         // List directory contents for debugging
         println!("Directory contents after backups:");
-        for entry in fs::read_dir(&backup_dir)? {
-            if let Ok(entry) = entry {
-                println!("  {:?}", entry.path());
-            }
+        for entry in fs::read_dir(&backup_dir)?.flatten() {
+            println!("  {:?}", entry.path());
         }
 
         let count = count_backup_files(&backup_dir)?;
@@ -220,6 +579,28 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    #[serial]
+    fn test_same_second_backups_kept() -> io::Result<()> {
+        let temp_dir = TempDir::new()?;
+        let backup_dir = temp_dir.path().to_path_buf();
+        set_backup_dir(backup_dir.clone())?;
+
+        let (first, _) = create_backup_in_mode(Some("first"), BackupMode::PathOnly)?;
+        let (second, _) = create_backup_in_mode(Some("second"), BackupMode::PathOnly)?;
+        let (third, _) = create_backup_in_mode(Some("third"), BackupMode::PathOnly)?;
+
+        let listed: Vec<PathBuf> = list_backups(&backup_dir)?
+            .into_iter()
+            .map(|backup| backup.file)
+            .collect();
+        assert_eq!(listed, vec![first, second, third]);
+
+        assert_eq!(split_counter("20240321120000_2"), ("20240321120000", 2));
+        assert_eq!(split_counter("20240321120000"), ("20240321120000", 1));
+        Ok(())
+    }
+
     #[test]
     #[serial]
     fn test_backup_dir_creation() -> io::Result<()> {
@@ -256,4 +637,70 @@ mod tests {
 
         Ok(())
     }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_non_utf8_path_round_trip() -> io::Result<()> {
+        use std::os::unix::ffi::OsStrExt;
+
+        let path = OsStr::from_bytes(b"/usr/bin:/opt/caf\xe9/bin");
+        let backup = Backup::new("20240101000000".to_string(), path);
+        let json = serde_json::to_string(&backup)?;
+        let restored: Backup = serde_json::from_str(&json)?;
+
+        assert_eq!(restored.path_os(), path);
+        assert_eq!(restored.path, "/usr/bin:/opt/caf\u{fffd}/bin");
+
+        let utf8 = Backup::new("20240101000000".to_string(), OsStr::new("/usr/bin"));
+        assert!(!serde_json::to_string(&utf8)?.contains("path_bytes"));
+
+        Ok(())
+    }
+
+    #[test]
+    #[serial]
+    fn test_backup_dir_precedence() {
+        let temp_dir = TempDir::new().unwrap();
+        root::set_root(Some(temp_dir.path())).unwrap();
+        let root = root::get_root().unwrap();
+        let home = locations::home_dir();
+        let config_file = root.join("config.toml");
+        let saved_dir = BACKUP_DIR.lock().unwrap().take();
+        let saved_env: Vec<_> = [config::CONFIG_ENV, "PATHMASTER_BACKUP_DIR"]
+            .into_iter()
+            .map(|name| (name, env::var_os(name)))
+            .collect();
+        env::set_var(config::CONFIG_ENV, &config_file);
+        env::remove_var("PATHMASTER_BACKUP_DIR");
+        let resolved = || {
+            config::set_config(config::resolve(&config::Overrides::default()));
+            get_backup_dir().unwrap()
+        };
+
+        let default = resolved();
+        // The config file's directory expands `~` to the home under the root
+        fs::write(&config_file, "backup-dir = \"~/synced/backups\"\n").unwrap();
+        let from_config = resolved();
+        // $PATHMASTER_BACKUP_DIR wins over the config file and is moved under the root
+        env::set_var("PATHMASTER_BACKUP_DIR", "/pm-test/env-backups");
+        let from_env = resolved();
+        // set_backup_dir wins over both
+        set_backup_dir(temp_dir.path().join("cli-backups")).unwrap();
+        let from_cli = resolved();
+
+        *BACKUP_DIR.lock().unwrap() = saved_dir;
+        for (name, value) in saved_env {
+            match value {
+                Some(value) => env::set_var(name, value),
+                None => env::remove_var(name),
+            }
+        }
+        root::set_root(None).unwrap();
+        config::clear_config();
+
+        assert_eq!(default, home.join(".pathmaster/backups"));
+        assert_eq!(from_config, home.join("synced/backups"));
+        assert_eq!(from_env, root.join("pm-test/env-backups"));
+        assert_eq!(from_cli, temp_dir.path().join("cli-backups"));
+    }
 }
@@ -6,6 +6,6 @@ pub mod mode;
 pub mod restore;
 pub mod show;
 
-pub use core::create_backup;
-pub use restore::execute as restore_from_backup;
-pub use show::show_history;
+pub use core::{create_backup, create_backup_in_mode};
+pub use restore::{execute as restore_from_backup, BackupSelector};
+pub use show::{show_history, show_history_porcelain};
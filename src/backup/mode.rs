@@ -6,13 +6,32 @@
 //! - Mode conflict resolution
 //! - Mode persistence
 
+use lazy_static::lazy_static;
 use std::fmt;
 use std::str::FromStr;
+use std::sync::Mutex;
+
+lazy_static! {
+    static ref BACKUP_MODE: Mutex<BackupMode> = Mutex::new(BackupMode::default());
+}
+
+/// Sets what backups taken in this run save, from `--backup-mode`
+pub fn set_backup_mode(mode: BackupMode) {
+    if let Ok(mut current) = BACKUP_MODE.lock() {
+        *current = mode;
+    }
+}
+
+/// Returns what backups taken in this run save
+pub fn get_backup_mode() -> BackupMode {
+    BACKUP_MODE.lock().map(|mode| *mode).unwrap_or_default()
+}
 
 /// Represents available backup modes for pathmaster.
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
 pub enum BackupMode {
     /// Backs up both PATH and shell configurations (default)
+    #[default]
     Both,
     /// Backs up only PATH entries
     PathOnly,
@@ -20,12 +39,6 @@ pub enum BackupMode {
     ShellOnly,
 }
 
-impl Default for BackupMode {
-    fn default() -> Self {
-        Self::Both
-    }
-}
-
 impl fmt::Display for BackupMode {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -84,19 +97,12 @@ pub enum ModeChangeResult {
 }
 
 /// Manages backup mode state and transitions
-#[derive(Debug)]
+#[derive(Debug, Default)]
 #[allow(dead_code)]
 pub struct BackupModeManager {
     current_mode: BackupMode,
 }
 
-impl Default for BackupModeManager {
-    fn default() -> Self {
-        Self {
-            current_mode: BackupMode::default(),
-        }
-    }
-}
 #[allow(dead_code)]
 impl BackupModeManager {
     /// Creates a new BackupModeManager with default mode
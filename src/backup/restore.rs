@@ -3,84 +3,295 @@
 //! This module handles:
 //! - Restoring PATH from specified backup files
 //! - Finding and using the most recent backup
-//! - Validating backup files
+//! - Selecting a backup by history number or by time (`--before "2 days ago"`)
+//! - Validating backup files, and refusing to apply an empty PATH unconfirmed
 //! - Updating shell configuration after restore
+//! - Telling running shells how to pick the restored PATH up
 
-use crate::backup::core::get_backup_dir;
-use crate::utils;
+use crate::backup::core::{get_backup_dir, list_backups, Backup, TIMESTAMP_FORMAT};
+use crate::config;
+use crate::msg;
+use crate::utils::shell::emit;
+use crate::utils::{self, prompt, PathSet};
+use chrono::{Duration, Local, NaiveDate, NaiveDateTime};
 use std::env;
-use std::fs::File;
-use std::io::Read;
+use std::ffi::OsStr;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Which backup to restore
+#[derive(Debug, Clone, PartialEq)]
+pub enum BackupSelector {
+    /// The most recent backup
+    Latest,
+    /// The backup before the most recent one
+    Previous,
+    /// A backup by its number in `pathmaster history`, starting at 1
+    Index(usize),
+    /// The backup with this exact timestamp
+    Timestamp(String),
+    /// The most recent backup taken before this time
+    Before(NaiveDateTime),
+}
+
+impl BackupSelector {
+    /// Finds the backup file the selector refers to
+    ///
+    /// The latest and previous backup, and the one before a time, are
+    /// picked among the backups holding a PATH, so shell-only backups
+    /// (`--backup-mode shell`) are passed over. A backup picked by number or
+    /// timestamp is used as it is.
+    ///
+    /// # Arguments
+    ///
+    /// * `backup_dir` - Directory holding the backups
+    pub fn resolve(&self, backup_dir: &Path) -> io::Result<PathBuf> {
+        let backups = list_backups(backup_dir).unwrap_or_default();
+        let mut with_path = backups.iter().filter(|backup| !backup.shell_only);
+        let found = match self {
+            BackupSelector::Timestamp(ts) => {
+                let stored = backups
+                    .iter()
+                    .find(|backup| backup.taken.format(TIMESTAMP_FORMAT).to_string() == *ts);
+                return Ok(stored.map_or_else(
+                    || backup_dir.join(format!("backup_{}.json", ts)),
+                    |backup| backup.file.clone(),
+                ));
+            }
+            BackupSelector::Latest => {
+                return get_latest_backup(backup_dir).ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::NotFound,
+                        msg!("restore-no-backups", directory = backup_dir),
+                    )
+                })
+            }
+            BackupSelector::Previous => with_path.rev().nth(1),
+            BackupSelector::Index(index) => index.checked_sub(1).and_then(|i| backups.get(i)),
+            BackupSelector::Before(time) => with_path.rfind(|backup| backup.taken < *time),
+        };
+        found.map(|backup| backup.file.clone()).ok_or_else(|| {
+            let message = match self {
+                BackupSelector::Previous => msg!("restore-no-previous"),
+                BackupSelector::Index(index) => {
+                    msg!("restore-no-index", index = *index, count = backups.len())
+                }
+                _ => msg!("restore-none-before", time = self.to_string()),
+            };
+            io::Error::new(io::ErrorKind::NotFound, message)
+        })
+    }
+}
+
+impl std::fmt::Display for BackupSelector {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BackupSelector::Latest => write!(f, "latest"),
+            BackupSelector::Previous => write!(f, "previous"),
+            BackupSelector::Index(index) => write!(f, "#{}", index),
+            BackupSelector::Timestamp(ts) => write!(f, "{}", ts),
+            BackupSelector::Before(time) => write!(f, "{}", time.format("%Y-%m-%d %H:%M:%S")),
+        }
+    }
+}
+
+/// Parses a time for `restore --before`, relative to now
+///
+/// Accepts `<n> <unit>s ago` (seconds to weeks), `today`, `yesterday`,
+/// `YYYY-MM-DD`, `YYYY-MM-DD HH:MM[:SS]` and backup timestamps.
+pub fn parse_time(expression: &str) -> Result<NaiveDateTime, String> {
+    parse_time_from(expression, Local::now().naive_local())
+}
+
+fn parse_time_from(expression: &str, now: NaiveDateTime) -> Result<NaiveDateTime, String> {
+    let expression = expression.trim().to_lowercase();
+    let midnight = now.date().and_hms_opt(0, 0, 0).unwrap_or(now);
+    match expression.as_str() {
+        "now" => return Ok(now),
+        "today" => return Ok(midnight),
+        "yesterday" => return Ok(midnight - Duration::days(1)),
+        _ => {}
+    }
+
+    if let Some(amount) = expression.strip_suffix(" ago") {
+        let mut words = amount.split_whitespace();
+        let (count, unit) = match (words.next(), words.next(), words.next()) {
+            (Some(count), Some(unit), None) => (count, unit),
+            _ => return Err(format!("'{}' is not a time like '2 days ago'", expression)),
+        };
+        let count: u32 = match count {
+            "a" | "an" | "one" => 1,
+            _ => count
+                .parse()
+                .map_err(|_| format!("'{}' is not a whole number of units back", count))?,
+        };
+        let unit = match unit.trim_end_matches('s') {
+            "sec" | "second" => Duration::seconds(1),
+            "min" | "minute" => Duration::minutes(1),
+            "hour" => Duration::hours(1),
+            "day" => Duration::days(1),
+            "week" => Duration::weeks(1),
+            other => return Err(format!("Unknown time unit '{}'", other)),
+        };
+        return i32::try_from(count)
+            .ok()
+            .and_then(|count| unit.checked_mul(count))
+            .and_then(|span| now.checked_sub_signed(span))
+            .ok_or_else(|| format!("'{}' is too far back", expression));
+    }
+
+    for format in [
+        TIMESTAMP_FORMAT,
+        "%Y-%m-%d %H:%M:%S",
+        "%Y-%m-%d %H:%M",
+        "%Y-%m-%dT%H:%M:%S",
+    ] {
+        if let Ok(time) = NaiveDateTime::parse_from_str(&expression, format) {
+            return Ok(time);
+        }
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(&expression, "%Y-%m-%d") {
+        if let Some(time) = date.and_hms_opt(0, 0, 0) {
+            return Ok(time);
+        }
+    }
+    Err(format!(
+        "Cannot read '{}' as a time; use e.g. '2 days ago', 'yesterday' or '2024-03-21 12:00'",
+        expression
+    ))
+}
 
 /// Executes the restore command to recover PATH from a backup
 ///
 /// # Arguments
 ///
-/// * `timestamp` - Optional timestamp string to specify which backup to restore.
-///                 If None, restores from the most recent backup.
+/// * `selector` - Which backup to restore
+///
+/// pathmaster can't change the PATH of the shell that started it, so
+/// unless it runs through the `--emit` wrapper, the restored PATH only
+/// reaches new shells. This is said after the restore, along with the code
+/// to run to apply it to the current shell.
+///
+/// # Returns
+/// * `false` if the backup couldn't be found, read or applied. Declining
+///   to restore an empty PATH isn't a failure.
 ///
 /// # Example
 ///
 /// ```
 /// // Restore from specific backup
-/// let timestamp = Some(String::from("20240321120000"));
-/// commands::restore::execute(&timestamp);
+/// commands::restore::execute(&BackupSelector::Timestamp("20240321120000".into()));
 ///
 /// // Restore from most recent backup
-/// commands::restore::execute(&None);
+/// commands::restore::execute(&BackupSelector::Latest);
 /// ```
-pub fn execute(timestamp: &Option<String>) {
-    let backup_dir = match get_backup_dir() {
-        Ok(dir) => dir,
+pub fn execute(selector: &BackupSelector) -> bool {
+    let file = match restore(selector) {
+        Ok(Some(file)) => file,
+        Ok(None) => {
+            println!("{}", msg!("restore-cancelled"));
+            return true;
+        }
         Err(e) => {
-            eprintln!("Error getting backup directory: {}", e);
-            return;
+            eprintln!("{}", msg!("restore-failed", error = e));
+            return false;
         }
     };
 
-    let backup_file = match timestamp {
-        Some(ts) => backup_dir.join(format!("backup_{}.json", ts)),
-        None => {
-            // Get the most recent backup
-            match get_latest_backup(&backup_dir) {
-                Some(file) => file,
-                None => {
-                    println!("No backups found.");
-                    return;
-                }
-            }
-        }
-    };
+    println!("{}", msg!("restore-done", file = file));
 
-    if !backup_file.exists() {
-        println!("Backup file not found: {}", backup_file.display());
-        return;
+    if let Some(statements) = apply_hint(&config::get_config(), &utils::get_path_entries()) {
+        println!("{}", msg!("restore-apply-hint"));
+        println!("{}", statements);
     }
+    true
+}
 
-    // Read the backup file
-    let mut file = File::open(&backup_file).expect("Failed to open backup file");
-    let mut contents = String::new();
-    file.read_to_string(&mut contents)
-        .expect("Failed to read backup file");
-
-    // Deserialize the backup
-    let backup: serde_json::Value =
-        serde_json::from_str(&contents).expect("Failed to parse backup file");
-    let path = backup["path"].as_str().unwrap_or_default();
+/// Returns the code that applies a restored PATH to the running shell, if
+/// the user has to run it by hand
+///
+/// The `--emit` wrapper applies it itself, and env-only and container
+/// modes print their own hints.
+fn apply_hint(settings: &config::Config, entries: &[PathBuf]) -> Option<String> {
+    if settings.emit_file.is_some() || settings.env_only || settings.container {
+        return None;
+    }
+    Some(emit::apply_statements(settings.shell, entries))
+}
 
-    // Update PATH
-    env::set_var("PATH", path);
+/// Restores PATH and the shell config from the selected backup
+///
+/// # Returns
+/// * `Ok(Some(PathBuf))` - The backup that was restored
+/// * `Ok(None)` - The user declined to restore an empty PATH
+/// * `Err(io::Error)` - If the backup can't be found or read, or the
+///   shell config can't be updated
+fn restore(selector: &BackupSelector) -> io::Result<Option<PathBuf>> {
+    let backup_dir = get_backup_dir()?;
+    let backup_file = selector.resolve(&backup_dir)?;
+    let backup = load_backup(&backup_file)?;
+    if backup.shell_only {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "Backup {} only holds shell configs (--backup-mode shell); there is no PATH to restore",
+                backup_file.display()
+            ),
+        ));
+    }
 
-    // Update shell configuration
-    if let Err(e) = utils::update_shell_config(&utils::get_path_entries()) {
-        eprintln!("Error updating shell configuration: {}", e);
-        return;
+    let path = backup.path_os();
+    if is_empty_path(&path) && !prompt::confirm(&msg!("restore-confirm-empty", file = backup_file))
+    {
+        return Ok(None);
     }
 
-    println!("PATH restored from backup: {}", backup_file.display());
+    // Written the way every other command writes PATH
+    PathSet::new(env::split_paths(&path).collect())
+        .apply()
+        .map_err(|e| {
+            io::Error::new(
+                e.kind(),
+                format!("Cannot update shell configuration: {}", e),
+            )
+        })?;
+    Ok(Some(backup_file))
+}
+
+/// Reads and parses a backup file
+///
+/// # Returns
+/// * `Ok(Backup)` - The backup
+/// * `Err(io::Error)` - Naming the file and whether it was missing,
+///   unreadable or not a valid backup
+pub fn load_backup(file: &Path) -> io::Result<Backup> {
+    let contents = fs::read_to_string(file).map_err(|e| match e.kind() {
+        io::ErrorKind::NotFound => io::Error::new(
+            e.kind(),
+            format!("Backup file not found: {}", file.display()),
+        ),
+        _ => io::Error::new(
+            e.kind(),
+            format!("Cannot read backup {}: {}", file.display(), e),
+        ),
+    })?;
+    serde_json::from_str(&contents).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{} is not a valid backup: {}", file.display(), e),
+        )
+    })
+}
+
+/// Returns true if a PATH value has no directories in it
+fn is_empty_path(path: &OsStr) -> bool {
+    env::split_paths(path).all(|entry| entry.as_os_str().is_empty())
 }
 
-/// Gets the most recent backup file
+/// Gets the most recent backup file that holds a PATH
+///
+/// Shell-only backups are skipped, since there's no PATH to restore from them.
 ///
 /// # Arguments
 ///
@@ -88,10 +299,245 @@ pub fn execute(timestamp: &Option<String>) {
 ///
 /// # Returns
 ///
-/// Option containing PathBuf to the most recent backup file,
-/// or None if no backups exist
-pub fn get_latest_backup(backup_dir: &std::path::Path) -> Option<std::path::PathBuf> {
-    let mut backups: Vec<_> = std::fs::read_dir(backup_dir).ok()?.flatten().collect();
-    backups.sort_by_key(|dir| dir.file_name());
-    backups.last().map(|entry| entry.path())
+/// Option containing PathBuf to the backup with the newest timestamp,
+/// or None if no backup holds a PATH
+pub fn get_latest_backup(backup_dir: &Path) -> Option<PathBuf> {
+    list_backups(backup_dir)
+        .ok()?
+        .into_iter()
+        .rfind(|backup| !backup.shell_only)
+        .map(|backup| backup.file)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backup::core::set_backup_dir;
+    use crate::utils::root;
+    use crate::utils::shell::{self, locations, types::ShellType};
+    use serial_test::serial;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn at(text: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    #[test]
+    fn test_parse_time() {
+        let now = at("2024-03-21 12:30:00");
+        assert_eq!(
+            parse_time_from("2 days ago", now),
+            Ok(at("2024-03-19 12:30:00"))
+        );
+        assert_eq!(
+            parse_time_from("an hour ago", now),
+            Ok(at("2024-03-21 11:30:00"))
+        );
+        assert_eq!(
+            parse_time_from("Yesterday", now),
+            Ok(at("2024-03-20 00:00:00"))
+        );
+        assert_eq!(
+            parse_time_from("2024-03-01", now),
+            Ok(at("2024-03-01 00:00:00"))
+        );
+        assert_eq!(
+            parse_time_from("2024-03-01 08:15", now),
+            Ok(at("2024-03-01 08:15:00"))
+        );
+        assert_eq!(
+            parse_time_from("20240301081500", now),
+            Ok(at("2024-03-01 08:15:00"))
+        );
+        assert!(parse_time_from("2 fortnights ago", now).is_err());
+        assert!(parse_time_from("-2 hours ago", now).is_err());
+        assert!(parse_time_from("-2h", now).is_err());
+        assert!(parse_time_from("last tuesday", now).is_err());
+    }
+
+    #[test]
+    fn test_load_backup_errors() {
+        let temp_dir = TempDir::new().unwrap();
+        let missing = temp_dir.path().join("backup_20240321100000.json");
+        let error = load_backup(&missing).unwrap_err();
+        assert!(error.to_string().starts_with("Backup file not found"));
+
+        let corrupt = temp_dir.path().join("backup_20240321110000.json");
+        fs::write(&corrupt, r#"{"timestamp": "20240321110000""#).unwrap();
+        let error = load_backup(&corrupt).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert!(error.to_string().contains("is not a valid backup"));
+
+        let no_path = temp_dir.path().join("backup_20240321120000.json");
+        fs::write(&no_path, r#"{"timestamp": "20240321120000"}"#).unwrap();
+        assert!(load_backup(&no_path).is_err());
+    }
+
+    #[test]
+    fn test_apply_hint() {
+        let entries = vec![PathBuf::from("/pm-test/bin"), PathBuf::from("/usr/bin")];
+        let bash = config::Config {
+            shell: Some(ShellType::Bash),
+            ..Default::default()
+        };
+        assert_eq!(
+            apply_hint(&bash, &entries).as_deref(),
+            Some("export PATH=\"/pm-test/bin:/usr/bin\"\nhash -r")
+        );
+
+        for settings in [
+            config::Config {
+                emit_file: Some(PathBuf::from("/pm-test/emit")),
+                ..bash.clone()
+            },
+            config::Config {
+                env_only: true,
+                ..bash.clone()
+            },
+            config::Config {
+                container: true,
+                ..bash.clone()
+            },
+        ] {
+            assert_eq!(apply_hint(&settings, &entries), None);
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn test_restore_rewrites_shell_config() {
+        let temp_dir = TempDir::new().unwrap();
+        let backup_dir = temp_dir.path().join("backups");
+        fs::create_dir(&backup_dir).unwrap();
+        fs::write(
+            backup_dir.join("backup_20240321100000.json"),
+            r#"{"timestamp": "20240321100000", "path": "/pm-test/restored:/usr/bin"}"#,
+        )
+        .unwrap();
+
+        root::set_root(Some(temp_dir.path())).unwrap();
+        let home = locations::home_dir();
+        fs::create_dir_all(&home).unwrap();
+        fs::write(home.join(".bashrc"), "export PATH=\"/pm-test/old:$PATH\"\n").unwrap();
+        config::set_config(config::Config {
+            shell: Some(ShellType::Bash),
+            ..Default::default()
+        });
+        set_backup_dir(backup_dir).unwrap();
+        let original_path = env::var_os("PATH");
+
+        let restored = execute(&BackupSelector::Latest);
+        let path = utils::get_path_entries();
+        let declared = shell::declared_entries();
+
+        match original_path {
+            Some(path) => env::set_var("PATH", path),
+            None => env::remove_var("PATH"),
+        }
+        root::set_root(None).unwrap();
+        config::clear_config();
+
+        // New shells read the restored PATH from the config, not from this process
+        let expected = vec![
+            PathBuf::from("/pm-test/restored"),
+            PathBuf::from("/usr/bin"),
+        ];
+        assert!(restored);
+        assert_eq!(path, expected);
+        assert_eq!(declared, expected);
+    }
+
+    #[test]
+    fn test_is_empty_path() {
+        assert!(is_empty_path(OsStr::new("")));
+        assert!(is_empty_path(OsStr::new("::")));
+        assert!(!is_empty_path(OsStr::new(":/usr/bin")));
+    }
+
+    #[test]
+    fn test_resolve_selectors() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path();
+        for ts in ["20240319100000", "20240320100000", "20240321100000"] {
+            let backup = format!(r#"{{"timestamp": "{}", "path": "/usr/bin"}}"#, ts);
+            fs::write(dir.join(format!("backup_{}.json", ts)), backup).unwrap();
+        }
+        fs::write(dir.join("notes.txt"), "not a backup").unwrap();
+        let file = |ts: &str| dir.join(format!("backup_{}.json", ts));
+
+        assert_eq!(get_latest_backup(dir), Some(file("20240321100000")));
+        assert_eq!(
+            BackupSelector::Latest.resolve(dir).unwrap(),
+            file("20240321100000")
+        );
+        assert_eq!(
+            BackupSelector::Previous.resolve(dir).unwrap(),
+            file("20240320100000")
+        );
+        assert_eq!(
+            BackupSelector::Index(1).resolve(dir).unwrap(),
+            file("20240319100000")
+        );
+        assert!(BackupSelector::Index(4).resolve(dir).is_err());
+        assert!(BackupSelector::Index(0).resolve(dir).is_err());
+        assert_eq!(
+            BackupSelector::Before(at("2024-03-21 09:00:00"))
+                .resolve(dir)
+                .unwrap(),
+            file("20240320100000")
+        );
+        assert!(BackupSelector::Before(at("2024-03-19 10:00:00"))
+            .resolve(dir)
+            .is_err());
+    }
+
+    #[test]
+    fn test_resolve_skips_shell_only_backups() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path();
+        for (ts, shell_only) in [
+            ("20240319100000", false),
+            ("20240320100000", true),
+            ("20240321100000", false),
+            ("20240322100000", true),
+        ] {
+            let backup = if shell_only {
+                format!(
+                    r#"{{"timestamp": "{}", "path": "", "shell_only": true}}"#,
+                    ts
+                )
+            } else {
+                format!(r#"{{"timestamp": "{}", "path": "/usr/bin"}}"#, ts)
+            };
+            fs::write(dir.join(format!("backup_{}.json", ts)), backup).unwrap();
+        }
+        let file = |ts: &str| dir.join(format!("backup_{}.json", ts));
+
+        assert_eq!(get_latest_backup(dir), Some(file("20240321100000")));
+        assert_eq!(
+            BackupSelector::Latest.resolve(dir).unwrap(),
+            file("20240321100000")
+        );
+        assert_eq!(
+            BackupSelector::Previous.resolve(dir).unwrap(),
+            file("20240319100000")
+        );
+        assert_eq!(
+            BackupSelector::Before(at("2024-03-21 09:00:00"))
+                .resolve(dir)
+                .unwrap(),
+            file("20240319100000")
+        );
+        // Picked by number, a shell-only backup is still the one meant
+        assert_eq!(
+            BackupSelector::Index(2).resolve(dir).unwrap(),
+            file("20240320100000")
+        );
+
+        fs::remove_file(file("20240319100000")).unwrap();
+        fs::remove_file(file("20240321100000")).unwrap();
+        assert_eq!(get_latest_backup(dir), None);
+        assert!(BackupSelector::Latest.resolve(dir).is_err());
+    }
 }
@@ -1,29 +1,187 @@
 // src/backup/show.rs
 
-use super::core::get_backup_dir;
+use super::core::{get_backup_dir, list_backups, Backup, BackupEntry};
+use crate::config;
+use crate::msg;
+use crate::utils::porcelain::{self, PorcelainVersion};
+use crate::utils::shell::factory;
+use crate::utils::shell::handlers::backup_copies;
+use chrono::NaiveDateTime;
 use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A saved copy of a shell config: in a backup, or in a `.bak_` file made
+/// before pathmaster rewrote the config
+#[derive(Debug, Clone, PartialEq)]
+struct ConfigCopy {
+    /// Number of the backup holding the copy; `None` for `.bak_` files
+    backup: Option<usize>,
+    /// Shell the config belongs to, e.g. `bash`
+    shell: String,
+    /// The config that was copied
+    config: PathBuf,
+    /// The file holding the copy
+    file: PathBuf,
+    taken: NaiveDateTime,
+}
 
 /// Displays the history of PATH backups
 ///
-/// Lists all available backups in chronological order
+/// Lists all available backups in chronological order, numbered so a
+/// backup can be restored with `pathmaster restore <number>`, with the
+/// shell configs saved in each. The `.bak_` copies left next to shell
+/// configs follow, so every way back is shown in one place.
 pub fn show_history() {
     let backup_dir = match get_backup_dir() {
         Ok(dir) => dir,
         Err(e) => {
-            eprintln!("Error getting backup directory: {}", e);
+            eprintln!("{}", msg!("history-no-dir", error = e));
             return;
         }
     };
 
-    match fs::read_dir(&backup_dir) {
-        Ok(entries) => {
-            println!("Available backups:");
-            for entry in entries.flatten() {
-                println!("- {}", entry.file_name().to_string_lossy());
+    let backups = list_backups(&backup_dir).unwrap_or_default();
+    if backups.is_empty() {
+        println!("{}", msg!("history-none", dir = backup_dir));
+    } else {
+        println!("{}", msg!("history-available"));
+    }
+    for (index, backup) in backups.iter().enumerate() {
+        let taken = backup.taken.format("%Y-%m-%d %H:%M:%S");
+        let saved = read_backup(&backup.file);
+        let mut line = format!("{:>4}  {}", index + 1, taken);
+        if let Some(label) = saved.as_ref().and_then(|saved| saved.label.as_ref()) {
+            line.push_str(&format!("  ({})", label));
+        }
+        if saved.as_ref().is_some_and(|saved| saved.shell_only) {
+            line.push_str(&format!("  [{}]", msg!("history-shell-only")));
+        }
+        println!("{}", line);
+        for config in saved.iter().flat_map(|saved| &saved.shell_configs) {
+            match &config.shell {
+                Some(shell) => println!("        {}: {}", shell, config.file.display()),
+                None => println!("        {}", config.file.display()),
             }
         }
-        Err(_) => {
-            println!("No backups found.");
+    }
+
+    let copies = config_copies();
+    if !copies.is_empty() {
+        println!();
+        println!("{}", msg!("history-config-copies"));
+        for copy in &copies {
+            println!(
+                "      {}  {}: {}",
+                copy.taken.format("%Y-%m-%d %H:%M:%S"),
+                copy.shell,
+                copy.file.display()
+            );
+        }
+    }
+}
+
+/// Prints the backups as porcelain `backup` records, and the shell configs
+/// saved in them and copied next to them as `shell-config` records
+/// (see [`porcelain`])
+pub fn show_history_porcelain(version: PorcelainVersion) {
+    let backups = match get_backup_dir().and_then(|dir| list_backups(&dir)) {
+        Ok(backups) => backups,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+        Err(e) => {
+            eprintln!("{}", msg!("history-unreadable", error = e));
+            return;
         }
+    };
+
+    porcelain::print_header(version);
+    for (index, backup) in backups.iter().enumerate() {
+        println!(
+            "{}",
+            porcelain::record(&[
+                "backup",
+                &(index + 1).to_string(),
+                &backup.taken.format("%Y-%m-%dT%H:%M:%S").to_string(),
+                &backup_label(&backup.file).unwrap_or_default(),
+                &backup.file.to_string_lossy(),
+            ])
+        );
+    }
+    for copy in saved_configs(&backups).into_iter().chain(config_copies()) {
+        println!(
+            "{}",
+            porcelain::record(&[
+                "shell-config",
+                &copy.backup.map(|n| n.to_string()).unwrap_or_default(),
+                &copy.taken.format("%Y-%m-%dT%H:%M:%S").to_string(),
+                &copy.shell,
+                &copy.config.to_string_lossy(),
+                &copy.file.to_string_lossy(),
+            ])
+        );
     }
 }
+
+/// Returns the shell configs saved in backups, in backup order
+fn saved_configs(backups: &[BackupEntry]) -> Vec<ConfigCopy> {
+    let mut copies = Vec::new();
+    for (index, backup) in backups.iter().enumerate() {
+        let saved = read_backup(&backup.file).map(|saved| saved.shell_configs);
+        for config in saved.unwrap_or_default() {
+            copies.push(ConfigCopy {
+                backup: Some(index + 1),
+                shell: config.shell.unwrap_or_default(),
+                config: config.file,
+                file: backup.file.clone(),
+                taken: backup.taken,
+            });
+        }
+    }
+    copies
+}
+
+/// Finds the `.bak_` copies of the shell configs pathmaster writes to, for
+/// the current shell and every shell with a config, oldest first
+fn config_copies() -> Vec<ConfigCopy> {
+    let settings = config::get_config();
+    let mut handlers = factory::configured_shell_handlers();
+    handlers.push(factory::get_shell_handler(settings.shell));
+
+    let mut configs: Vec<(String, PathBuf)> = Vec::new();
+    for handler in &handlers {
+        for file in handler
+            .modified_files()
+            .into_iter()
+            .chain([handler.get_config_path()])
+        {
+            if !configs.iter().any(|(_, known)| *known == file) {
+                configs.push((handler.name(), file));
+            }
+        }
+    }
+
+    let mut copies: Vec<ConfigCopy> = configs
+        .into_iter()
+        .flat_map(|(shell, config)| {
+            backup_copies(&config)
+                .into_iter()
+                .map(move |(file, taken)| ConfigCopy {
+                    backup: None,
+                    shell: shell.clone(),
+                    config: config.clone(),
+                    file,
+                    taken,
+                })
+        })
+        .collect();
+    copies.sort_by(|a, b| a.taken.cmp(&b.taken).then_with(|| a.file.cmp(&b.file)));
+    copies
+}
+
+/// Returns the name given to a backup with `snapshot --name`, if any
+fn backup_label(file: &Path) -> Option<String> {
+    read_backup(file)?.label
+}
+
+fn read_backup(file: &Path) -> Option<Backup> {
+    serde_json::from_str(&fs::read_to_string(file).ok()?).ok()
+}
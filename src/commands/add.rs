@@ -2,77 +2,498 @@
 //!
 //! This module handles:
 //! - Validating new directories
-//! - Adding directories to PATH
-//! - Updating shell configuration
+//! - Creating missing directories on request
+//! - Adding directories to the end of PATH, or to the front (`prepend`)
+//! - Detecting directories already on PATH and optionally moving them to the front
+//! - Updating shell configuration, keeping entries it declares that this
+//!   session's PATH doesn't have yet
 //! - Creating backups before modifications
 
-use crate::backup;
-use crate::utils;
+use crate::commands::validator::is_valid_path_entry;
+use crate::msg;
+use crate::utils::shell;
+use crate::utils::{self, check_mode, PathSet};
+use std::ffi::OsString;
+use std::fs;
 use std::path::PathBuf;
 
+/// Options controlling how directories are added
+#[derive(Debug, Default, Clone)]
+pub struct AddOptions {
+    /// Move directories already on PATH to the front instead of skipping them
+    pub move_to_front: bool,
+    /// Insert new directories at the front of PATH instead of the end
+    pub prepend: bool,
+    /// Create missing directories (like `mkdir -p`) before adding them
+    pub create: bool,
+    /// Add directories even if they don't exist or aren't directories
+    pub force: bool,
+}
+
+/// What placing the directories in PATH did
+#[derive(Debug, Default, PartialEq)]
+struct Placement {
+    /// New directories added to PATH
+    added: usize,
+    /// Directories already in PATH that were moved to the front
+    moved: usize,
+    /// Directories already in PATH that were left where they were
+    duplicates: usize,
+    /// Directories created with `--create`
+    created: Vec<PathBuf>,
+    /// Directories refused because they're invalid or couldn't be created
+    rejected: usize,
+}
+
+impl Placement {
+    /// Whether PATH changed
+    fn changed(&self) -> bool {
+        self.added > 0 || self.moved > 0
+    }
+}
+
 /// Executes the add command to include new directories in PATH
 ///
 /// # Arguments
 ///
-/// * `directories` - A slice of strings containing directories to add
+/// * `directories` - The directories to add, as given on the command line
+/// * `options` - Options controlling how directories are added
+///
+/// # Returns
+///
+/// `false` if a directory was refused as invalid or couldn't be created,
+/// even when the others were added
 ///
 /// # Example
 ///
 /// ```
-/// let dirs = vec![String::from("~/bin")];
-/// commands::add::execute(&dirs);
+/// let dirs = vec![OsString::from("~/bin")];
+/// commands::add::execute(&dirs, &AddOptions::default());
 /// ```
-pub fn execute(directories: &[String]) {
+pub fn execute(directories: &[OsString], options: &AddOptions) -> bool {
     // Expand and normalize the directory paths
     let dirs_to_add: Vec<PathBuf> = directories
         .iter()
-        .map(|dir| utils::expand_path(dir))
+        .map(|dir| utils::expand_os_path(dir))
         .collect();
 
+    // Get current PATH
+    let mut path_set = PathSet::from_env();
+
     // Backup current PATH
-    if let Err(e) = backup::create_backup() {
-        eprintln!("Error creating backup: {}", e);
-        return;
+    if let Err(e) = path_set.apply_backup() {
+        eprintln!("{}", msg!("error-backup", error = e));
+        return false;
     }
 
-    // Get current PATH
-    let mut path_entries = utils::get_path_entries();
+    let placement = place(&mut path_set, dirs_to_add, options);
 
-    // Track the number of directories added
-    let mut added_count = 0;
+    if placement.changed() {
+        // Update PATH, then the shell configuration, without dropping the
+        // entries it declares that weren't loaded into this session
+        path_set.apply_env();
+        let mut config_set = path_set.clone();
+        config_set.merge(&shell::declared_path());
+        if let Err(e) = config_set.apply_shell_config() {
+            eprintln!("{}", msg!("error-shell-config", error = e));
+            return false;
+        }
+        let kept = config_set.entries().len() - path_set.entries().len();
+        if kept > 0 {
+            println!("{}", msg!("add-kept-declared", count = kept));
+        }
 
-    for dir_path in dirs_to_add {
-        if !dir_path.is_dir() {
-            eprintln!(
-                "Warning: '{}' is not a valid directory.",
-                dir_path.display()
+        if placement.added > 0 {
+            println!("{}", msg!("add-summary-added", count = placement.added));
+        }
+        if placement.moved > 0 {
+            println!("{}", msg!("add-summary-moved", count = placement.moved));
+        }
+        if !placement.created.is_empty() {
+            println!(
+                "{}",
+                msg!("add-summary-created", count = placement.created.len())
             );
-            continue;
+            for dir in &placement.created {
+                println!("  {}", dir.display());
+            }
+        }
+    } else {
+        println!("{}", msg!("add-nothing-added"));
+        if placement.duplicates > 0 && !options.move_to_front {
+            println!("{}", msg!("add-move-to-front-hint"));
+        }
+    }
+
+    placement.rejected == 0
+}
+
+/// Validates, creates and places each directory in `path_set`, printing
+/// what happens to it
+fn place(path_set: &mut PathSet, dirs_to_add: Vec<PathBuf>, options: &AddOptions) -> Placement {
+    let mut placement = Placement::default();
+    // Entries placed at the front so far; they keep the order they were given in
+    let mut front = 0;
+
+    for dir_path in dirs_to_add {
+        let mut would_create = false;
+        if options.create && !dir_path.exists() {
+            if check_mode::is_check_mode() {
+                println!("{}", msg!("add-would-create", path = dir_path));
+                would_create = true;
+            } else {
+                if let Err(e) = fs::create_dir_all(&dir_path) {
+                    eprintln!("{}", msg!("add-create-failed", path = dir_path, error = e));
+                    placement.rejected += 1;
+                    continue;
+                }
+                println!("{}", msg!("add-created", path = dir_path));
+                placement.created.push(dir_path.clone());
+            }
+        }
+
+        if !would_create && !is_valid_path_entry(&dir_path) {
+            let reason = if dir_path.exists() {
+                msg!("reason-not-a-directory")
+            } else {
+                msg!("reason-missing")
+            };
+
+            if !options.force {
+                eprintln!("{}", msg!("add-invalid", path = dir_path, reason = reason));
+                placement.rejected += 1;
+                continue;
+            }
+            eprintln!("{}", msg!("add-forced", path = dir_path, reason = reason));
         }
 
-        if path_entries.contains(&dir_path) {
-            println!("Directory '{}' is already in PATH.", dir_path.display());
+        if let Some(index) = path_set.position(&dir_path) {
+            let existing = path_set.entries()[index].clone();
+            if options.move_to_front && index >= front {
+                front += 1;
+                if index >= front {
+                    path_set.insert(front - 1, &existing);
+                    placement.moved += 1;
+                    println!("{}", msg!("add-moved", path = existing));
+                    continue;
+                }
+            }
+
+            placement.duplicates += 1;
+            if existing == dir_path {
+                println!(
+                    "{}",
+                    msg!("add-duplicate", path = dir_path, position = index + 1)
+                );
+            } else {
+                println!(
+                    "{}",
+                    msg!(
+                        "add-duplicate-as",
+                        path = dir_path,
+                        existing = existing,
+                        position = index + 1
+                    )
+                );
+            }
             continue;
         }
 
         // Add the new directory
-        path_entries.push(dir_path.clone());
-        added_count += 1;
-        println!("Added '{}' to PATH.", dir_path.display());
+        if options.prepend {
+            path_set.insert(front, &dir_path);
+            front += 1;
+            println!("{}", msg!("add-added-front", path = dir_path));
+        } else {
+            path_set.add(&dir_path);
+            println!("{}", msg!("add-added", path = dir_path));
+        }
+        placement.added += 1;
+    }
+    placement
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config;
+    use crate::utils::root;
+    use crate::utils::shell::locations;
+    use crate::utils::shell::types::ShellType;
+    use serial_test::serial;
+    use std::env;
+    use tempfile::TempDir;
+
+    #[test]
+    #[serial]
+    fn test_create_missing_directory() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path().join("new/bin");
+        let mut path_set = PathSet::new(vec![PathBuf::from("/usr/bin")]);
+        let options = AddOptions {
+            create: true,
+            ..Default::default()
+        };
+
+        let placement = place(&mut path_set, vec![dir.clone()], &options);
+
+        assert!(dir.is_dir());
+        assert_eq!(placement.created, vec![dir.clone()]);
+        assert_eq!(placement.added, 1);
+        assert_eq!(path_set.entries(), &[PathBuf::from("/usr/bin"), dir]);
     }
 
-    if added_count > 0 {
-        // Update PATH
-        utils::set_path_entries(&path_entries);
+    #[test]
+    #[serial]
+    fn test_create_in_check_mode() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path().join("new/bin");
+        let mut path_set = PathSet::new(Vec::new());
+        let options = AddOptions {
+            create: true,
+            ..Default::default()
+        };
 
-        // Update shell configuration
-        if let Err(e) = utils::update_shell_config(&path_entries) {
-            eprintln!("Error updating shell configuration: {}", e);
-            return;
+        check_mode::set_check_mode(true);
+        let placement = place(&mut path_set, vec![dir.clone()], &options);
+        check_mode::set_check_mode(false);
+
+        // Check mode reports what would be created without creating it
+        assert!(!dir.exists());
+        assert!(placement.created.is_empty());
+        assert_eq!(placement.added, 1);
+        assert_eq!(path_set.entries(), &[dir]);
+    }
+
+    #[test]
+    fn test_force_adds_invalid_path() {
+        let temp_dir = TempDir::new().unwrap();
+        let missing = temp_dir.path().join("missing");
+        let mut path_set = PathSet::new(vec![PathBuf::from("/usr/bin")]);
+        let options = AddOptions {
+            force: true,
+            ..Default::default()
+        };
+
+        let placement = place(&mut path_set, vec![missing.clone()], &options);
+
+        assert_eq!(placement.added, 1);
+        assert_eq!(placement.rejected, 0);
+        assert_eq!(path_set.entries(), &[PathBuf::from("/usr/bin"), missing]);
+    }
+
+    #[test]
+    fn test_invalid_path_is_rejected() {
+        let temp_dir = TempDir::new().unwrap();
+        let missing = temp_dir.path().join("missing");
+        let file = temp_dir.path().join("file");
+        fs::write(&file, "").unwrap();
+        let valid = temp_dir.path().to_path_buf();
+        let mut path_set = PathSet::new(Vec::new());
+
+        let placement = place(
+            &mut path_set,
+            vec![missing, file, valid.clone()],
+            &AddOptions::default(),
+        );
+
+        // The valid directory is still added, the others are refused
+        assert_eq!(placement.rejected, 2);
+        assert_eq!(placement.added, 1);
+        assert_eq!(path_set.entries(), &[valid]);
+    }
+
+    /// Creates a directory for each name under `root`
+    fn directories(root: &TempDir, names: &[&str]) -> Vec<PathBuf> {
+        names
+            .iter()
+            .map(|name| {
+                let dir = root.path().join(name);
+                fs::create_dir(&dir).unwrap();
+                dir
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_prepend_mixes_new_and_existing_in_order() {
+        let temp_dir = TempDir::new().unwrap();
+        let [x, a, y, new1, new2] = directories(&temp_dir, &["x", "a", "y", "new1", "new2"])
+            .try_into()
+            .unwrap();
+        let mut path_set = PathSet::new(vec![x.clone(), a.clone(), y.clone()]);
+        let options = AddOptions {
+            move_to_front: true,
+            prepend: true,
+            ..Default::default()
+        };
+
+        let placement = place(
+            &mut path_set,
+            vec![new1.clone(), a.clone(), new2.clone()],
+            &options,
+        );
+
+        // The given directories lead PATH in the order they were given
+        assert_eq!(placement.added, 2);
+        assert_eq!(placement.moved, 1);
+        assert_eq!(path_set.entries(), &[new1, a, new2, x, y]);
+    }
+
+    #[test]
+    fn test_move_to_front_appends_new_and_moves_existing() {
+        let temp_dir = TempDir::new().unwrap();
+        let [x, a, b, new] = directories(&temp_dir, &["x", "a", "b", "new"])
+            .try_into()
+            .unwrap();
+        let mut path_set = PathSet::new(vec![x.clone(), a.clone(), b.clone()]);
+        let options = AddOptions {
+            move_to_front: true,
+            ..Default::default()
+        };
+
+        let placement = place(
+            &mut path_set,
+            vec![b.clone(), new.clone(), a.clone()],
+            &options,
+        );
+
+        // Existing entries move to the front keeping their relative order;
+        // new ones still go to the end
+        assert_eq!(placement.added, 1);
+        assert_eq!(placement.moved, 2);
+        assert_eq!(path_set.entries(), &[b, a, x, new]);
+    }
+
+    #[test]
+    fn test_move_to_front_leaves_entry_already_at_front() {
+        let temp_dir = TempDir::new().unwrap();
+        let [a, x, b] = directories(&temp_dir, &["a", "x", "b"]).try_into().unwrap();
+        let mut path_set = PathSet::new(vec![a.clone(), x.clone(), b.clone()]);
+        let options = AddOptions {
+            move_to_front: true,
+            prepend: true,
+            ..Default::default()
+        };
+
+        let placement = place(&mut path_set, vec![a.clone(), b.clone()], &options);
+
+        assert_eq!(placement.moved, 1);
+        assert_eq!(placement.duplicates, 1);
+        assert_eq!(path_set.entries(), &[a, b, x]);
+    }
+
+    #[test]
+    #[serial]
+    fn test_add_keeps_config_entries_around_path() {
+        let temp_dir = TempDir::new().unwrap();
+        let [system, mine, tools, new] =
+            directories(&temp_dir, &["system", "mine", "tools", "new"])
+                .try_into()
+                .unwrap();
+
+        root::set_root(Some(temp_dir.path())).unwrap();
+        let home = locations::home_dir();
+        fs::create_dir_all(&home).unwrap();
+        config::set_config(config::Config {
+            shell: Some(ShellType::Bash),
+            ..Default::default()
+        });
+        crate::backup::core::set_backup_dir(temp_dir.path().join("backups")).unwrap();
+        let original_path = env::var_os("PATH");
+
+        // This session's PATH lacks what the config declares ahead of or
+        // behind the existing PATH
+        let mut declared = Vec::new();
+        for declaration in [
+            format!("export PATH=\"{}:$PATH\"\n", mine.display()),
+            format!("export PATH=\"$PATH:{}\"\n", tools.display()),
+        ] {
+            fs::write(home.join(".bashrc"), declaration).unwrap();
+            env::set_var("PATH", &system);
+            execute(&[new.clone().into()], &AddOptions::default());
+            declared.push(shell::declared_entries());
         }
 
-        println!("Successfully added {} directory(ies) to PATH.", added_count);
-    } else {
-        println!("No new directories were added to PATH.");
+        match original_path {
+            Some(path) => env::set_var("PATH", path),
+            None => env::remove_var("PATH"),
+        }
+        root::set_root(None).unwrap();
+        config::clear_config();
+
+        assert_eq!(declared[0], vec![mine.clone(), system.clone(), new.clone()]);
+        assert_eq!(declared[1], vec![system, new, tools]);
+    }
+
+    #[test]
+    #[serial]
+    fn test_prepend_and_append_commands() {
+        let temp_dir = TempDir::new().unwrap();
+        let [x, a, new1, new2] = directories(&temp_dir, &["x", "a", "new1", "new2"])
+            .try_into()
+            .unwrap();
+
+        root::set_root(Some(temp_dir.path())).unwrap();
+        let home = locations::home_dir();
+        fs::create_dir_all(&home).unwrap();
+        fs::write(home.join(".bashrc"), "").unwrap();
+        config::set_config(config::Config {
+            shell: Some(ShellType::Bash),
+            ..Default::default()
+        });
+        crate::backup::core::set_backup_dir(temp_dir.path().join("backups")).unwrap();
+        let original_path = env::var_os("PATH");
+        env::set_var("PATH", env::join_paths([&x, &a]).unwrap());
+
+        // `prepend` puts the directories first in the order given,
+        // moving those already in PATH
+        let prepended = execute(
+            &[new1.clone().into(), a.clone().into()],
+            &AddOptions {
+                move_to_front: true,
+                prepend: true,
+                ..Default::default()
+            },
+        );
+        let after_prepend = utils::get_path_entries();
+        // `append` adds new directories last and leaves existing ones alone
+        let appended = execute(
+            &[x.clone().into(), new2.clone().into()],
+            &AddOptions::default(),
+        );
+        let after_append = utils::get_path_entries();
+        let declared = shell::declared_entries();
+
+        match original_path {
+            Some(path) => env::set_var("PATH", path),
+            None => env::remove_var("PATH"),
+        }
+        root::set_root(None).unwrap();
+        config::clear_config();
+
+        assert!(prepended && appended);
+        assert_eq!(after_prepend, vec![new1.clone(), a.clone(), x.clone()]);
+        assert_eq!(after_append, vec![new1, a, x, new2]);
+        assert_eq!(declared, after_append);
+    }
+
+    #[test]
+    #[serial]
+    fn test_create_failure_is_rejected() {
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join("file");
+        fs::write(&file, "").unwrap();
+        let mut path_set = PathSet::new(Vec::new());
+        let options = AddOptions {
+            create: true,
+            ..Default::default()
+        };
+
+        let placement = place(&mut path_set, vec![file.join("bin")], &options);
+
+        assert_eq!(placement.rejected, 1);
+        assert!(!placement.changed());
+        assert!(path_set.entries().is_empty());
     }
 }
@@ -0,0 +1,434 @@
+//! Command implementation for moving an existing PATH setup under pathmaster.
+//!
+//! PATH is often built up over years of `export PATH=...` lines spread over
+//! several rc files. This module provides functionality to:
+//! - Find the PATH declarations in the current shell's startup files
+//! - Record which file and line declared each entry, and merge duplicates
+//! - Write the consolidated PATH to the managed block or drop-in file
+//! - Comment out the declarations it replaced in the other files
+//! - Leave declarations inside conditions and loops alone
+//! - Report exactly what was consolidated
+
+use crate::commands::validator::is_valid_path_entry;
+use crate::config::{self, ExportStyle, WriteMode};
+use crate::msg;
+use crate::utils::path_scanner::PathScanner;
+use crate::utils::shell::continuation;
+use crate::utils::shell::factory;
+use crate::utils::shell::handlers::{backup_file, write_with_rollback, ShellHandler};
+use crate::utils::shell::locations;
+use crate::utils::shell::spelling;
+use crate::utils::shell::text_format::TextFormat;
+use crate::utils::shell::types::ShellType;
+use crate::utils::{check_mode, PathSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Prefix of the lines adopt comments out
+const ADOPTED_PREFIX: &str = "# Adopted by pathmaster: ";
+
+/// Where an entry was declared
+#[derive(Debug, Clone, PartialEq)]
+pub struct Source {
+    pub file: PathBuf,
+    /// One-based line number
+    pub line: usize,
+}
+
+/// An entry declared in the startup files, with every place declaring it
+#[derive(Debug, Clone, PartialEq)]
+pub struct Adopted {
+    pub entry: PathBuf,
+    pub sources: Vec<Source>,
+}
+
+/// What the startup files declare
+#[derive(Debug, Default)]
+struct Declarations {
+    /// Entries from declarations that can be replaced, in the order first declared
+    adopted: Vec<Adopted>,
+    /// Lines declaring PATH that are replaced, by file
+    replaced: Vec<(PathBuf, Vec<usize>)>,
+    /// Declarations inside conditions or loops, which stay where they are
+    kept: Vec<(Source, String)>,
+}
+
+/// Executes the adopt command
+///
+/// # Example
+///
+/// ```
+/// commands::adopt::execute();
+/// // Output example:
+/// // Adopting PATH declarations from 2 bash startup files:
+/// //   /home/user/bin
+/// //     /home/user/.profile:10
+/// //     /home/user/.bashrc:3 (duplicate)
+/// ```
+pub fn execute() {
+    let settings = config::get_config();
+    if settings.all_shells || settings.system || settings.env_only {
+        eprintln!("{}", msg!("adopt-one-shell"));
+        return;
+    }
+    if settings.export_style == ExportStyle::Relative {
+        eprintln!("{}", msg!("adopt-absolute-only"));
+        return;
+    }
+
+    let handler = factory::get_shell_handler(settings.shell);
+    let files = startup_files(handler.as_ref());
+    if files.is_empty() {
+        println!("{}", msg!("adopt-nothing", shell = handler.name()));
+        return;
+    }
+
+    let declarations = collect_declarations(handler.as_ref(), &files);
+    println!(
+        "{}",
+        msg!(
+            "adopt-adopting",
+            count = files.len(),
+            shell = handler.name()
+        )
+    );
+    let mut path_set = PathSet::from_env();
+    let mut added = Vec::new();
+    let mut missing = Vec::new();
+    for adopted in &declarations.adopted {
+        println!("  {}", adopted.entry.display());
+        for (idx, source) in adopted.sources.iter().enumerate() {
+            println!(
+                "    {}:{}{}",
+                source.file.display(),
+                source.line,
+                if idx > 0 {
+                    msg!("adopt-duplicate")
+                } else {
+                    String::new()
+                }
+            );
+        }
+        if !path_set.contains(&adopted.entry) {
+            if is_valid_path_entry(&adopted.entry) {
+                path_set.add(&adopted.entry);
+                added.push(&adopted.entry);
+            } else {
+                missing.push(&adopted.entry);
+            }
+        }
+    }
+    if !added.is_empty() {
+        println!("{}", msg!("adopt-added"));
+        for entry in &added {
+            println!("  {}", entry.display());
+        }
+    }
+    if !missing.is_empty() {
+        println!("{}", msg!("adopt-missing"));
+        for entry in &missing {
+            println!("  {}", entry.display());
+        }
+    }
+    if !declarations.kept.is_empty() {
+        println!("{}", msg!("adopt-kept"));
+        for (source, content) in &declarations.kept {
+            println!(
+                "  {}:{}: {}",
+                source.file.display(),
+                source.line,
+                content.trim()
+            );
+        }
+    }
+
+    let before = path_set.entries().len();
+    path_set.dedupe();
+    let duplicates = before - path_set.entries().len();
+
+    if let Err(e) = path_set.apply_backup() {
+        eprintln!("{}", msg!("error-backup", error = e));
+        return;
+    }
+    if let Err(e) = path_set.apply() {
+        eprintln!("{}", msg!("error-shell-config", error = e));
+        return;
+    }
+
+    // The handler already replaced the declarations in the file it writes to
+    let target = match settings.write_mode {
+        WriteMode::Inline => Some(handler.get_target_path()),
+        WriteMode::DropIn => None,
+    };
+    let mut commented = 0;
+    for (file, lines) in &declarations.replaced {
+        if Some(file) == target.as_ref() {
+            continue;
+        }
+        match comment_out_file(file, lines) {
+            Ok(()) => {
+                commented += lines.len();
+                println!(
+                    "{}",
+                    msg!("adopt-commented", count = lines.len(), file = file)
+                );
+            }
+            Err(e) => eprintln!("{}", msg!("error-write-file", file = file, error = e)),
+        }
+    }
+
+    let declared: usize = declarations
+        .adopted
+        .iter()
+        .map(|adopted| adopted.sources.len())
+        .sum();
+    let target = match settings.write_mode {
+        WriteMode::Inline => msg!("adopt-target-block"),
+        WriteMode::DropIn => msg!("adopt-target-drop-in"),
+    };
+    println!(
+        "{}",
+        msg!(
+            "adopt-summary",
+            declared = declared,
+            entries = declarations.adopted.len(),
+            target = target,
+            commented = commented,
+            duplicates = duplicates
+        )
+    );
+}
+
+/// Returns the startup files of the handler's shell that declare PATH
+///
+/// Candidates come from [`PathScanner`], limited to the files the shell
+/// reads, plus the file the handler writes to.
+fn startup_files(handler: &dyn ShellHandler) -> Vec<PathBuf> {
+    let shell_files = shell_startup_files(&handler.get_shell_type());
+    let mut files: Vec<PathBuf> = Vec::new();
+    if let Ok(locations) = PathScanner::new().scan_all() {
+        for location in locations.iter().filter(|l| !l.requires_sudo()) {
+            let file = location.file().to_path_buf();
+            if shell_files.contains(&file) && !files.contains(&file) {
+                files.push(file);
+            }
+        }
+    }
+
+    let target = handler.get_target_path();
+    let declares = fs::read_to_string(&target)
+        .map(|content| !handler.detect_path_modifications(&content).is_empty())
+        .unwrap_or(false);
+    if declares && !files.contains(&target) {
+        files.push(target);
+    }
+    files
+}
+
+/// Returns the startup files a shell reads, in the order it reads them
+fn shell_startup_files(shell: &ShellType) -> Vec<PathBuf> {
+    let home = locations::home_dir();
+    let zdotdir = locations::zsh_dotdir();
+    match shell {
+        ShellType::Bash => vec![
+            home.join(".profile"),
+            home.join(".bash_profile"),
+            home.join(".bash_login"),
+            home.join(".bashrc"),
+        ],
+        ShellType::Zsh => vec![
+            zdotdir.join(".zshenv"),
+            zdotdir.join(".zprofile"),
+            zdotdir.join(".zshrc"),
+        ],
+        ShellType::Ksh => std::iter::once(home.join(".profile"))
+            .chain(locations::ksh_env_file())
+            .collect(),
+        ShellType::Tcsh => vec![home.join(".cshrc"), home.join(".login")],
+        ShellType::Fish => vec![locations::fish_config_dir().join("config.fish")],
+        ShellType::Generic => vec![home.join(".profile")],
+    }
+}
+
+/// Reads the PATH declarations of each file
+fn collect_declarations(handler: &dyn ShellHandler, files: &[PathBuf]) -> Declarations {
+    let mut declarations = Declarations::default();
+    for file in files {
+        let Ok(content) = fs::read_to_string(file) else {
+            continue;
+        };
+        let (_, content) = TextFormat::detect(&content);
+        add_declarations(&mut declarations, handler, file, &content);
+    }
+    declarations
+}
+
+/// Adds one file's PATH declarations
+fn add_declarations(
+    declarations: &mut Declarations,
+    handler: &dyn ShellHandler,
+    file: &Path,
+    content: &str,
+) {
+    let replaceable: Vec<usize> = handler
+        .replaceable_modifications(content)
+        .iter()
+        .map(|modification| modification.line_number)
+        .collect();
+    for modification in handler.detect_path_modifications(content) {
+        if !replaceable.contains(&modification.line_number) {
+            declarations.kept.push((
+                Source {
+                    file: file.to_path_buf(),
+                    line: modification.line_number,
+                },
+                modification.content,
+            ));
+        }
+    }
+
+    for declared in handler.parse_declared_entries(content) {
+        if !replaceable.contains(&declared.line_number) {
+            continue;
+        }
+        let Some(entry) = spelling::expand_entry(&declared.raw) else {
+            continue;
+        };
+        let source = Source {
+            file: file.to_path_buf(),
+            line: declared.line_number,
+        };
+        match declarations
+            .adopted
+            .iter_mut()
+            .find(|adopted| adopted.entry == entry)
+        {
+            Some(adopted) => adopted.sources.push(source),
+            None => declarations.adopted.push(Adopted {
+                entry,
+                sources: vec![source],
+            }),
+        }
+    }
+
+    if !replaceable.is_empty() {
+        declarations
+            .replaced
+            .push((file.to_path_buf(), replaceable));
+    }
+}
+
+/// Comments out the statements starting on the given lines of a file
+fn comment_out_file(file: &Path, lines: &[usize]) -> std::io::Result<()> {
+    let original = fs::read_to_string(file)?;
+    let (format, content) = TextFormat::detect(&original);
+    let updated = format.restore(&comment_out(&content, lines));
+    if check_mode::is_check_mode() {
+        check_mode::record_write(file, &updated);
+        return Ok(());
+    }
+    let backup_path = backup_file(file)?;
+    write_with_rollback(file, &updated, &backup_path)
+}
+
+/// Comments out every physical line of the statements starting on `lines`
+///
+/// # Arguments
+///
+/// * `content` - Contents of the config file
+/// * `lines` - One-based line numbers where the statements start
+fn comment_out(content: &str, lines: &[usize]) -> String {
+    let mut commented: Vec<usize> = Vec::new();
+    for statement in continuation::logical_lines(content) {
+        if lines.contains(&(statement.start + 1)) {
+            commented.extend(statement.start..=statement.end);
+        }
+    }
+
+    let mut result: String = content
+        .lines()
+        .enumerate()
+        .map(|(idx, line)| {
+            if commented.contains(&idx) {
+                format!("{}{}\n", ADOPTED_PREFIX, line)
+            } else {
+                format!("{}\n", line)
+            }
+        })
+        .collect();
+    if !content.ends_with('\n') {
+        result.pop();
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::shell::handlers::BashHandler;
+
+    #[test]
+    fn test_comment_out() {
+        let content = "alias ll='ls -l'\nexport PATH=/usr/bin:\\\n/opt/bin:$PATH\necho done";
+        assert_eq!(
+            comment_out(content, &[2]),
+            "alias ll='ls -l'\n\
+             # Adopted by pathmaster: export PATH=/usr/bin:\\\n\
+             # Adopted by pathmaster: /opt/bin:$PATH\n\
+             echo done"
+        );
+    }
+
+    #[test]
+    fn test_add_declarations() {
+        let handler = BashHandler::new();
+        let profile = Path::new("/pm-test/.profile");
+        let bashrc = Path::new("/pm-test/.bashrc");
+        let mut declarations = Declarations::default();
+        add_declarations(
+            &mut declarations,
+            &handler,
+            profile,
+            "export PATH=\"/pm-test/bin:$PATH\"\n",
+        );
+        add_declarations(
+            &mut declarations,
+            &handler,
+            bashrc,
+            "export PATH=\"/pm-test/tools:/pm-test/bin:$PATH\"\n\
+             [ -d /pm-test/go ] && export PATH=\"/pm-test/go:$PATH\"\n",
+        );
+
+        let entries: Vec<&Path> = declarations
+            .adopted
+            .iter()
+            .map(|adopted| adopted.entry.as_path())
+            .collect();
+        assert_eq!(
+            entries,
+            vec![Path::new("/pm-test/bin"), Path::new("/pm-test/tools")]
+        );
+        assert_eq!(
+            declarations.adopted[0].sources,
+            vec![
+                Source {
+                    file: profile.to_path_buf(),
+                    line: 1
+                },
+                Source {
+                    file: bashrc.to_path_buf(),
+                    line: 1
+                },
+            ]
+        );
+        assert_eq!(declarations.kept.len(), 1);
+        assert_eq!(declarations.kept[0].0.line, 2);
+        assert_eq!(
+            declarations.replaced,
+            vec![
+                (profile.to_path_buf(), vec![1]),
+                (bashrc.to_path_buf(), vec![1])
+            ]
+        );
+    }
+}
@@ -0,0 +1,234 @@
+//! Command implementation for auditing who controls PATH.
+//!
+//! Anyone who can write to a PATH directory can put a program there that
+//! runs when you type its name. This module provides functionality to:
+//! - Flag PATH directories owned by someone other than you or root
+//! - Flag group-writable and world-writable PATH directories
+//! - List setuid executables inside PATH directories
+//! - Explain each finding and suggest how to fix it
+
+use crate::commands::validator::is_valid_path_entry;
+use crate::msg;
+use crate::utils::{self, find_path_entry, user};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// One ownership or permission problem
+#[derive(Debug, Clone, PartialEq)]
+pub enum Finding {
+    /// The directory is owned by this uid, which is neither you nor root
+    ForeignOwner(PathBuf, u32),
+    /// Members of the directory's group (this gid) can write to it
+    GroupWritable(PathBuf, u32),
+    /// Anyone can write to the directory
+    WorldWritable(PathBuf),
+    /// The executable runs as its owner (this uid) whoever starts it
+    Setuid(PathBuf, u32),
+}
+
+/// Executes the audit command
+///
+/// # Returns
+/// * `true` if no PATH directory can be written by someone other than you or root
+///
+/// # Example
+///
+/// ```
+/// commands::audit::execute();
+/// // Output example:
+/// // Group-writable directories in PATH:
+/// //   /opt/tools/bin (group: staff)
+/// ```
+pub fn execute() -> bool {
+    let Some(my_uid) = user::effective_uid() else {
+        if cfg!(unix) {
+            println!("{}", msg!("audit-no-proc"));
+        } else {
+            println!("{}", msg!("audit-unsupported"));
+        }
+        return true;
+    };
+
+    let mut dirs: Vec<PathBuf> = Vec::new();
+    for entry in utils::get_path_entries() {
+        if is_valid_path_entry(&entry) && find_path_entry(&dirs, &entry).is_none() {
+            dirs.push(entry);
+        }
+    }
+    let findings: Vec<Finding> = dirs
+        .iter()
+        .flat_map(|dir| audit_directory(dir, my_uid))
+        .collect();
+
+    let mut foreign = Vec::new();
+    let mut group_writable = Vec::new();
+    let mut world_writable = Vec::new();
+    let mut setuid = Vec::new();
+    for finding in &findings {
+        match finding {
+            Finding::ForeignOwner(dir, uid) => foreign.push(msg!(
+                "audit-owner",
+                path = dir,
+                owner = account_name("/etc/passwd", *uid)
+            )),
+            Finding::GroupWritable(dir, gid) => group_writable.push(msg!(
+                "audit-group",
+                path = dir,
+                group = account_name("/etc/group", *gid)
+            )),
+            Finding::WorldWritable(dir) => world_writable.push(dir.display().to_string()),
+            Finding::Setuid(file, uid) => setuid.push(msg!(
+                "audit-runs-as",
+                path = file,
+                owner = account_name("/etc/passwd", *uid)
+            )),
+        }
+    }
+
+    print_section("audit-foreign", &foreign);
+    print_section("audit-group-writable", &group_writable);
+    print_section("audit-world-writable", &world_writable);
+    print_section("audit-setuid", &setuid);
+
+    let writable = foreign.len() + group_writable.len() + world_writable.len();
+    if writable == 0 {
+        println!("{}", msg!("audit-clean", count = dirs.len()));
+    }
+    writable == 0
+}
+
+/// Prints the section's heading, the items and its advice, if there are any items
+///
+/// The heading is the message `id`, and the advice is the message `id-advice`.
+fn print_section(id: &str, items: &[String]) {
+    if items.is_empty() {
+        return;
+    }
+    println!("{}", msg!(id));
+    for item in items {
+        println!("  {}", item);
+    }
+    for line in msg!(&format!("{}-advice", id)).lines() {
+        println!("  {}", line);
+    }
+}
+
+/// Checks a directory's owner and mode, and the setuid bits of its files
+#[cfg(unix)]
+fn audit_directory(dir: &Path, my_uid: u32) -> Vec<Finding> {
+    use std::os::unix::fs::MetadataExt;
+
+    let Ok(metadata) = fs::metadata(dir) else {
+        return Vec::new();
+    };
+    let mut findings =
+        permission_findings(dir, metadata.uid(), metadata.gid(), metadata.mode(), my_uid);
+
+    let Ok(files) = fs::read_dir(dir) else {
+        return findings;
+    };
+    let mut setuid: Vec<(PathBuf, u32)> = files
+        .flatten()
+        .filter_map(|file| {
+            let path = file.path();
+            let metadata = fs::metadata(&path).ok()?;
+            (metadata.is_file() && metadata.mode() & 0o4000 != 0).then(|| (path, metadata.uid()))
+        })
+        .collect();
+    setuid.sort();
+    findings.extend(
+        setuid
+            .into_iter()
+            .map(|(file, uid)| Finding::Setuid(file, uid)),
+    );
+    findings
+}
+
+#[cfg(not(unix))]
+fn audit_directory(_dir: &Path, _my_uid: u32) -> Vec<Finding> {
+    Vec::new()
+}
+
+/// Works out the problems with a directory's owner and mode
+fn permission_findings(dir: &Path, uid: u32, gid: u32, mode: u32, my_uid: u32) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    if uid != 0 && uid != my_uid {
+        findings.push(Finding::ForeignOwner(dir.to_path_buf(), uid));
+    }
+    if mode & 0o020 != 0 {
+        findings.push(Finding::GroupWritable(dir.to_path_buf(), gid));
+    }
+    if mode & 0o002 != 0 {
+        findings.push(Finding::WorldWritable(dir.to_path_buf()));
+    }
+    findings
+}
+
+/// Looks up the name of a user or group id in a passwd or group file,
+/// falling back to the number
+fn account_name(file: &str, id: u32) -> String {
+    fs::read_to_string(file)
+        .ok()
+        .and_then(|content| find_account(&content, id))
+        .unwrap_or_else(|| id.to_string())
+}
+
+/// Finds the name for `id` in passwd or group file content
+fn find_account(content: &str, id: u32) -> Option<String> {
+    content.lines().find_map(|line| {
+        let mut fields = line.split(':');
+        let name = fields.next()?;
+        (fields.nth(1)?.parse::<u32>().ok()? == id).then(|| name.to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_permission_findings() {
+        let dir = Path::new("/opt/tools/bin");
+        assert!(permission_findings(dir, 0, 0, 0o40755, 1000).is_empty());
+        assert!(permission_findings(dir, 1000, 1000, 0o40755, 1000).is_empty());
+        assert_eq!(
+            permission_findings(dir, 1001, 50, 0o40777, 1000),
+            vec![
+                Finding::ForeignOwner(dir.to_path_buf(), 1001),
+                Finding::GroupWritable(dir.to_path_buf(), 50),
+                Finding::WorldWritable(dir.to_path_buf()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_find_account() {
+        let passwd = "root:x:0:0:root:/root:/bin/bash\nalice:x:1000:1000::/home/alice:/bin/sh\n";
+        assert_eq!(find_account(passwd, 1000), Some("alice".to_string()));
+        assert_eq!(find_account(passwd, 0), Some("root".to_string()));
+        assert_eq!(find_account(passwd, 42), None);
+        assert_eq!(
+            find_account("staff:x:50:alice\n", 50),
+            Some("staff".to_string())
+        );
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_audit_directory_finds_setuid() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        fs::set_permissions(temp_dir.path(), fs::Permissions::from_mode(0o755)).unwrap();
+        let tool = temp_dir.path().join("tool");
+        fs::write(&tool, "").unwrap();
+        fs::set_permissions(&tool, fs::Permissions::from_mode(0o4755)).unwrap();
+        fs::write(temp_dir.path().join("plain"), "").unwrap();
+
+        let my_uid = user::effective_uid().unwrap();
+        assert_eq!(
+            audit_directory(temp_dir.path(), my_uid),
+            vec![Finding::Setuid(tool, my_uid)]
+        );
+    }
+}
@@ -0,0 +1,235 @@
+//! Command implementation for measuring what PATH costs command lookup.
+//!
+//! This module provides functionality to:
+//! - Time how long resolving common commands takes with the current PATH
+//! - Time the same lookups with the PATH `clean` would leave
+//! - Show the difference, so cleanup has concrete numbers behind it
+
+use crate::commands::clean::clean_entries;
+use crate::msg;
+use crate::utils::path_scanner::is_executable;
+use crate::utils::{self, protected};
+use std::hint::black_box;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+/// Commands looked up when none are given; the last one doesn't exist, which
+/// is what a typo costs
+pub const BENCH_COMMANDS: &[&str] = &[
+    "ls",
+    "cat",
+    "grep",
+    "sed",
+    "git",
+    "ssh",
+    "make",
+    "python3",
+    "vim",
+    "pathmaster-no-such-command",
+];
+
+/// Lookups of each command timed when `--iterations` isn't given
+pub const DEFAULT_ITERATIONS: u32 = 1000;
+
+/// Average lookup time of one command with each PATH
+#[derive(Debug, Clone, PartialEq)]
+struct Timing {
+    /// Command name, noting when it isn't found
+    name: String,
+    current: Duration,
+    cleaned: Duration,
+}
+
+/// Executes the bench command
+///
+/// # Arguments
+///
+/// * `names` - Commands to look up (a common set when empty)
+/// * `iterations` - How many times each lookup is timed
+///
+/// # Example
+///
+/// ```
+/// commands::bench::execute(&[], 1000);
+/// // Output example:
+/// // Command      Current    Cleaned
+/// // ls            4.2 µs     2.1 µs
+/// ```
+pub fn execute(names: &[String], iterations: u32) {
+    let names: Vec<String> = if names.is_empty() {
+        BENCH_COMMANDS.iter().map(|name| name.to_string()).collect()
+    } else {
+        names.to_vec()
+    };
+    let iterations = iterations.max(1);
+
+    let current = utils::get_path_entries();
+    let (cleaned, _) = clean_entries(
+        &current,
+        false,
+        &protected::protected_dirs(),
+        &protected::keep_dirs(),
+        &[],
+    );
+
+    let timings: Vec<Timing> = names
+        .iter()
+        .map(|name| Timing {
+            name: if resolve(&current, name).is_some() {
+                name.clone()
+            } else {
+                msg!("bench-not-found", command = name)
+            },
+            current: time_lookup(&current, name, iterations),
+            cleaned: time_lookup(&cleaned, name, iterations),
+        })
+        .collect();
+    let total = Timing {
+        name: msg!("bench-total"),
+        current: timings.iter().map(|timing| timing.current).sum(),
+        cleaned: timings.iter().map(|timing| timing.cleaned).sum(),
+    };
+
+    // Without changes there's only one PATH to time
+    let compare = cleaned != current;
+    let command = msg!("bench-command");
+    let width = timings
+        .iter()
+        .chain(std::iter::once(&total))
+        .map(|timing| timing.name.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max(command.chars().count());
+
+    println!("{}", msg!("bench-header", count = iterations));
+    if compare {
+        println!(
+            "  {:<width$} {:>10} {:>10}",
+            command,
+            msg!("bench-current"),
+            msg!("bench-cleaned")
+        );
+    } else {
+        println!("  {:<width$} {:>10}", command, msg!("bench-time"));
+    }
+    for timing in timings.iter().chain(std::iter::once(&total)) {
+        if compare {
+            println!(
+                "  {:<width$} {:>10} {:>10}",
+                timing.name,
+                format_duration(timing.current),
+                format_duration(timing.cleaned)
+            );
+        } else {
+            println!(
+                "  {:<width$} {:>10}",
+                timing.name,
+                format_duration(timing.current)
+            );
+        }
+    }
+
+    if !compare {
+        println!("{}", msg!("bench-already-clean", count = current.len()));
+        return;
+    }
+    println!(
+        "{}",
+        msg!(
+            "bench-result",
+            before = current.len(),
+            after = cleaned.len(),
+            change = describe_change(total.current, total.cleaned)
+        )
+    );
+    println!("{}", msg!("bench-clean-hint"));
+}
+
+/// Finds the first executable called `name` in PATH order, as the shell does
+fn resolve(entries: &[PathBuf], name: &str) -> Option<PathBuf> {
+    entries
+        .iter()
+        // An empty entry means the current directory
+        .map(|entry| entry.join(name))
+        .find(|candidate| is_executable(candidate))
+}
+
+/// Average time of resolving `name` against `entries`
+fn time_lookup(entries: &[PathBuf], name: &str, iterations: u32) -> Duration {
+    // One untimed lookup first, so both PATHs are timed with warm caches
+    black_box(resolve(entries, name));
+    let start = Instant::now();
+    for _ in 0..iterations {
+        black_box(resolve(black_box(entries), name));
+    }
+    start.elapsed() / iterations
+}
+
+/// Formats a duration in microseconds, or milliseconds once it's that long
+fn format_duration(duration: Duration) -> String {
+    let micros = duration.as_secs_f64() * 1_000_000.0;
+    if micros < 1000.0 {
+        format!("{:.1} µs", micros)
+    } else {
+        format!("{:.2} ms", micros / 1000.0)
+    }
+}
+
+/// Describes the cleaned total relative to the current one
+fn describe_change(current: Duration, cleaned: Duration) -> String {
+    if current.is_zero() || cleaned == current {
+        return msg!("bench-same");
+    }
+    let percent = (current.as_secs_f64() - cleaned.as_secs_f64()) / current.as_secs_f64() * 100.0;
+    if percent >= 0.5 {
+        msg!("bench-faster", percent = format!("{:.0}", percent))
+    } else if percent <= -0.5 {
+        msg!("bench-slower", percent = format!("{:.0}", -percent))
+    } else {
+        msg!("bench-same")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serial_test::serial;
+    use tempfile::TempDir;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_resolve() {
+        use std::fs;
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        let first = temp_dir.path().join("first");
+        let second = temp_dir.path().join("second");
+        fs::create_dir(&first).unwrap();
+        fs::create_dir(&second).unwrap();
+        fs::write(first.join("tool"), "").unwrap();
+        fs::write(second.join("tool"), "").unwrap();
+        fs::set_permissions(second.join("tool"), fs::Permissions::from_mode(0o755)).unwrap();
+
+        // The first copy isn't executable, so the second one wins
+        let entries = vec![PathBuf::from("/pm-test/missing"), first, second.clone()];
+        assert_eq!(resolve(&entries, "tool"), Some(second.join("tool")));
+        assert_eq!(resolve(&entries, "other"), None);
+    }
+
+    #[test]
+    #[serial]
+    fn test_format_duration() {
+        crate::utils::messages::set_language(Some("en")).unwrap();
+        assert_eq!(format_duration(Duration::from_nanos(4_240)), "4.2 µs");
+        assert_eq!(format_duration(Duration::from_micros(1_500)), "1.50 ms");
+        assert_eq!(
+            describe_change(Duration::from_micros(10), Duration::from_micros(6)),
+            "40% faster"
+        );
+        assert_eq!(
+            describe_change(Duration::from_micros(10), Duration::from_micros(10)),
+            "take about as long"
+        );
+    }
+}
@@ -0,0 +1,22 @@
+//! Command implementation for managing pathmaster's cache.
+//!
+//! This module provides functionality to:
+//! - Clear cached executable listings of PATH directories
+
+use crate::msg;
+use crate::utils::cache;
+
+/// Removes the executable listing cache
+///
+/// # Example
+///
+/// ```
+/// commands::cache::clear();
+/// ```
+pub fn clear() {
+    match cache::clear() {
+        Ok(true) => println!("{}", msg!("cache-cleared", file = cache::cache_path())),
+        Ok(false) => println!("{}", msg!("cache-empty")),
+        Err(e) => eprintln!("{}", msg!("cache-clear-failed", error = e)),
+    }
+}
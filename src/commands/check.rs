@@ -0,0 +1,653 @@
+//! Command implementation for checking PATH entries.
+//!
+//! This module handles:
+//! - Validating the live PATH, a PATH string, or a saved dump file
+//! - Reporting missing, non-directory, relative, and empty entries
+//! - Telling entries on unmounted removable media or automounts from gone ones
+//! - Naming the package manager that owns invalid entries
+//! - Finding entries left behind by removed toolchains
+//! - Reporting entries that slow down command lookup
+//! - Reporting unmet ordering rules
+//! - Reporting violations of the system policy
+//! - Reporting entries whose `add --ttl` has run out
+//! - Reporting conditional entries out of step with this machine
+//! - Explaining the Windows PATH entries WSL appends
+//! - Suggesting where Homebrew's directories belong in PATH
+//! - Checking the system-wide PATH in /etc/environment
+//! - Reporting shell configs whose PATH was changed outside pathmaster
+//! - Sending a desktop notification for new problems (`--notify`)
+//! - Printing a one-line summary for shell startup (`--quiet`)
+//! - Printing problems in the stable porcelain format (`--porcelain`)
+
+use crate::commands::status;
+use crate::commands::validator::{self, PathValidation};
+use crate::config;
+use crate::msg;
+use crate::utils;
+use crate::utils::attribution::Layout;
+use crate::utils::homebrew::{self, HomebrewProblem};
+use crate::utils::last_check::{LastCheck, Problem};
+use crate::utils::mounts::{self, Unmounted};
+use crate::utils::notify;
+use crate::utils::policy::Violation;
+use crate::utils::policy::{Policy, POLICY_FILE};
+use crate::utils::porcelain::{self, PorcelainVersion};
+use crate::utils::rules::{PathRule, Position};
+use crate::utils::shell::{self, written};
+use crate::utils::slow::SlowReason;
+use crate::utils::system_env::{self, EtcEnvironment};
+use chrono::Duration;
+use std::env;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+/// Where the PATH to check comes from
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CheckSource<'a> {
+    /// The PATH of the running environment
+    Environment,
+    /// A colon-separated PATH value
+    Value(&'a str),
+    /// A file holding a PATH value or environment dump (`-` for stdin)
+    File(&'a Path),
+    /// The entries the shell config declares, for runs without the login PATH
+    Declared,
+}
+
+/// How many new problems a notification lists by name
+const NOTIFY_LIMIT: usize = 5;
+
+/// Executes the check command to report problems with PATH entries
+///
+/// # Arguments
+///
+/// * `source` - The PATH to check
+///
+/// # Example
+///
+/// ```
+/// commands::check::execute(CheckSource::Value("/usr/bin:/missing"));
+/// // Output example:
+/// // Invalid directories in PATH:
+/// //   /missing
+/// ```
+pub fn execute(source: CheckSource) {
+    let entries = match read_entries(source) {
+        Ok(entries) => entries,
+        Err(e) => {
+            eprintln!("{}", msg!("error", error = e));
+            return;
+        }
+    };
+
+    // Fixing with flush only makes sense for the live PATH
+    let suggest_flush = source == CheckSource::Environment;
+    report(&validator::validate_entries(entries.clone()), suggest_flush);
+
+    let stale = utils::stale::find_stale(&Layout::from_env(), &entries);
+    if !stale.is_empty() {
+        println!("{}", msg!("check-stale"));
+        for stale in &stale {
+            println!(
+                "  {} ({}: {})",
+                stale.entry.display(),
+                stale.ecosystem,
+                stale.reason
+            );
+        }
+        note(msg!("check-stale-hint"));
+    }
+
+    report_slow_entries(&entries);
+
+    let settings = config::get_config();
+    let violations = utils::rules::violations(&settings.rules, &entries);
+    if !violations.is_empty() {
+        println!("{}", msg!("check-rules"));
+        for rule in violations {
+            println!("  {}", rule);
+        }
+    }
+
+    report_policy_violations(&entries, source == CheckSource::Environment);
+
+    if source == CheckSource::Environment {
+        let expired = utils::provenance::expired_entries(&entries);
+        if !expired.is_empty() {
+            println!("{}", msg!("check-expired"));
+            for (entry, expires) in &expired {
+                note(msg!("check-expired-entry", path = entry, expires = expires));
+            }
+            note(msg!("check-expired-hint"));
+        }
+
+        let machine = utils::conditional::Machine::current();
+        let (_, outcome) =
+            utils::conditional::apply(&settings.conditional_entries, &entries, &machine);
+        if !outcome.added.is_empty() || !outcome.removed.is_empty() {
+            println!("{}", msg!("check-conditional"));
+            for conditional in &settings.conditional_entries {
+                let dir = utils::expand_path(&conditional.entry);
+                if outcome.added.contains(&dir) {
+                    note(msg!(
+                        "check-conditional-missing",
+                        entry = conditional.to_string()
+                    ));
+                } else if outcome.removed.contains(&dir) {
+                    note(msg!(
+                        "check-conditional-unwanted",
+                        entry = conditional.to_string()
+                    ));
+                }
+            }
+            note(msg!("check-conditional-hint"));
+        }
+
+        report_windows_entries(&entries);
+        report_homebrew(&entries);
+    }
+
+    if source == CheckSource::Environment && !settings.system {
+        check_system_path();
+    }
+
+    if matches!(source, CheckSource::Environment | CheckSource::Declared) {
+        let changed = written::changed_configs();
+        if !changed.is_empty() {
+            println!("{}", msg!("check-modified"));
+            for file in &changed {
+                println!("  {}", file.display());
+            }
+            note(msg!("check-modified-hint"));
+        }
+    }
+}
+
+/// Saves the problems found and sends a desktop notification listing the
+/// ones the last saved check didn't find
+///
+/// Besides the problems `execute` reports, shell configs whose PATH was
+/// changed outside pathmaster count as `modified` problems.
+///
+/// # Arguments
+///
+/// * `source` - The PATH to check
+///
+/// # Example
+///
+/// ```
+/// commands::check::notify(CheckSource::Declared);
+/// // Notification example:
+/// // 1 new PATH problem
+/// // missing: /opt/tool/bin
+/// ```
+pub fn notify(source: CheckSource) {
+    let entries = match read_entries(source) {
+        Ok(entries) => entries,
+        Err(e) => {
+            eprintln!("{}", msg!("error", error = e));
+            return;
+        }
+    };
+
+    let previous = LastCheck::load();
+    let current = LastCheck::new(recorded_problems(source, &entries));
+    if let Err(e) = current.save() {
+        eprintln!("{}", msg!("warning-save-check", error = e));
+    }
+
+    let new = current.new_since(previous.as_ref());
+    if new.is_empty() {
+        return;
+    }
+    let summary = msg!("check-notify-summary", count = new.len());
+    let mut lines: Vec<String> = new
+        .iter()
+        .take(NOTIFY_LIMIT)
+        .map(|problem| problem.to_string())
+        .collect();
+    if new.len() > NOTIFY_LIMIT {
+        lines.push(msg!("check-notify-more", count = new.len() - NOTIFY_LIMIT));
+    }
+    if let Err(e) = notify::send(&summary, &lines.join("\n")) {
+        eprintln!("{}", msg!("warning", warning = e));
+    }
+}
+
+/// Prints the problems `execute` reports as porcelain `problem` records
+/// (see [`porcelain`])
+///
+/// # Example
+///
+/// ```
+/// commands::check::porcelain(CheckSource::Value("/usr/bin:/missing"), PorcelainVersion::V1);
+/// // Output example:
+/// // porcelain    v1
+/// // problem    missing    /missing
+/// ```
+pub fn porcelain(source: CheckSource, version: PorcelainVersion) {
+    let entries = match read_entries(source) {
+        Ok(entries) => entries,
+        Err(e) => {
+            eprintln!("{}", msg!("error", error = e));
+            return;
+        }
+    };
+
+    porcelain::print_header(version);
+    for (kind, path, detail) in problems(source, &entries) {
+        println!(
+            "{}",
+            porcelain::record(&["problem", kind, &path.to_string_lossy(), &detail])
+        );
+    }
+}
+
+/// Prints a one-line summary if there are problems, and nothing otherwise
+///
+/// A check of this environment's PATH or of the declared entries is saved
+/// like `--notify` saves it; a check of another PATH value or dump isn't.
+/// With `daily`, such a check is skipped when the last saved one is less
+/// than a day old, and the summary is printed from the saved problems that
+/// still apply, so running it at every shell startup costs no more than
+/// reading that file.
+///
+/// # Arguments
+///
+/// * `source` - The PATH to check
+/// * `daily` - Whether to reuse a saved check less than a day old
+///
+/// # Example
+///
+/// ```
+/// commands::check::quiet(CheckSource::Environment, true);
+/// // Output example:
+/// // pathmaster: 2 PATH problems found, run 'pathmaster check' for details
+/// ```
+pub fn quiet(source: CheckSource, daily: bool) {
+    let entries = match read_entries(source) {
+        Ok(entries) => entries,
+        Err(e) => {
+            eprintln!("{}", msg!("error", error = e));
+            return;
+        }
+    };
+
+    // Only checks of this environment's PATH are saved, and reused
+    let saved = matches!(source, CheckSource::Environment | CheckSource::Declared);
+    let recent =
+        LastCheck::load().filter(|last| daily && saved && last.checked_within(Duration::days(1)));
+    let count = match recent {
+        Some(last) => last
+            .problems
+            .iter()
+            .filter(|problem| status::still_applies(problem, &entries))
+            .count(),
+        None => {
+            let current = LastCheck::new(recorded_problems(source, &entries));
+            if saved {
+                if let Err(e) = current.save() {
+                    eprintln!("{}", msg!("warning-save-check", error = e));
+                }
+            }
+            current.problems.len()
+        }
+    };
+
+    if count > 0 {
+        println!("{}", msg!("check-quiet-summary", count = count));
+    }
+}
+
+/// Collects the problems `--notify` and `--quiet` save: those of
+/// [`problems`], and shell configs changed outside pathmaster as `modified`
+fn recorded_problems(source: CheckSource, entries: &[PathBuf]) -> Vec<Problem> {
+    let mut found: Vec<Problem> = problems(source, entries)
+        .into_iter()
+        .map(|(kind, path, detail)| Problem {
+            kind: kind.to_string(),
+            path,
+            detail,
+        })
+        .collect();
+    if matches!(source, CheckSource::Environment | CheckSource::Declared) {
+        for file in written::changed_configs() {
+            found.push(Problem {
+                kind: "modified".to_string(),
+                path: file,
+                detail: String::new(),
+            });
+        }
+    }
+    found
+}
+
+/// Collects every problem `execute` reports as (kind, path, detail)
+pub fn problems(source: CheckSource, entries: &[PathBuf]) -> Vec<(&'static str, PathBuf, String)> {
+    let mut problems = Vec::new();
+
+    let validation = validator::validate_entries(entries.to_vec());
+    let mounts = mounts::read_mounts();
+    for dir in validation.missing_dirs {
+        match mounts::unmounted_reason(&mounts, &dir) {
+            Some(Unmounted::RemovableMedia) => {
+                problems.push(("unmounted", dir, "removable-media".to_string()))
+            }
+            Some(Unmounted::Automount(mount_point)) => {
+                let detail = format!("automount:{}", mount_point.display());
+                problems.push(("unmounted", dir, detail));
+            }
+            None => problems.push(("missing", dir, String::new())),
+        }
+    }
+    for path in validation.not_dirs {
+        problems.push(("not-dir", path, String::new()));
+    }
+    for path in validation.relative_dirs {
+        problems.push(("relative", path, String::new()));
+    }
+    for _ in 0..validation.empty_entries {
+        problems.push(("empty", PathBuf::new(), String::new()));
+    }
+
+    for stale in utils::stale::find_stale(&Layout::from_env(), entries) {
+        problems.push(("stale", stale.entry, stale.ecosystem.to_string()));
+    }
+    for slow in slow_entries(entries) {
+        let reasons: Vec<String> = slow
+            .reasons
+            .iter()
+            .map(|reason| match reason {
+                SlowReason::NetworkMount(fs_type) => format!("network:{}", fs_type),
+                SlowReason::SlowListing(elapsed) => format!("listing-ms:{}", elapsed.as_millis()),
+                SlowReason::ManyFiles(count) => format!("files:{}", count),
+            })
+            .collect();
+        problems.push(("slow", slow.entry, reasons.join(",")));
+    }
+
+    let settings = config::get_config();
+    for rule in utils::rules::violations(&settings.rules, entries) {
+        problems.push(("rule", PathBuf::from(&rule.entry), rule_detail(rule)));
+    }
+    match Policy::load() {
+        Ok(Some(policy)) => {
+            for violation in policy.violations(entries) {
+                problems.push(match violation {
+                    Violation::Forbidden(entry) => ("policy", entry, "forbidden".to_string()),
+                    Violation::Missing(entry) => ("policy", entry, "required".to_string()),
+                    Violation::Rule(rule) => {
+                        ("policy", PathBuf::from(&rule.entry), rule_detail(&rule))
+                    }
+                });
+            }
+        }
+        Ok(None) => {}
+        Err(e) => eprintln!("{}", msg!("warning", warning = e)),
+    }
+
+    if source == CheckSource::Environment {
+        for (entry, expires) in utils::provenance::expired_entries(entries) {
+            problems.push(("expired", entry, expires));
+        }
+
+        let machine = utils::conditional::Machine::current();
+        let (_, outcome) =
+            utils::conditional::apply(&settings.conditional_entries, entries, &machine);
+        for dir in outcome.added {
+            problems.push(("conditional", dir, "missing".to_string()));
+        }
+        for dir in outcome.removed {
+            problems.push(("conditional", dir, "unwanted".to_string()));
+        }
+
+        for entry in utils::wsl::windows_entries(entries) {
+            problems.push(("windows", entry, String::new()));
+        }
+        if let Some(prefix) = homebrew::detect_prefix() {
+            for problem in homebrew::problems(&prefix, entries) {
+                problems.push(match problem {
+                    HomebrewProblem::AfterSystem { entry, system } => {
+                        ("homebrew", entry, format!("after:{}", system.display()))
+                    }
+                    HomebrewProblem::NotInPath { entry } => {
+                        ("homebrew", entry, "not-in-path".to_string())
+                    }
+                });
+            }
+        }
+        if !settings.system {
+            for dir in system_missing_dirs() {
+                let detail = system_env::ETC_ENVIRONMENT.to_string();
+                problems.push(("system-missing", dir, detail));
+            }
+        }
+    }
+    problems
+}
+
+/// Formats a rule's constraints for porcelain output, e.g. `before:/usr/bin,position:first`
+fn rule_detail(rule: &PathRule) -> String {
+    let mut constraints = Vec::new();
+    if let Some(before) = &rule.before {
+        constraints.push(format!("before:{}", before));
+    }
+    if let Some(after) = &rule.after {
+        constraints.push(format!("after:{}", after));
+    }
+    match rule.position {
+        Some(Position::First) => constraints.push("position:first".to_string()),
+        Some(Position::Last) => constraints.push("position:last".to_string()),
+        None => {}
+    }
+    constraints.join(",")
+}
+
+/// Reports entries on network mounts, slow to list or holding very many files
+fn report_slow_entries(entries: &[PathBuf]) {
+    let slow = slow_entries(entries);
+    if slow.is_empty() {
+        return;
+    }
+
+    println!("{}", msg!("check-slow"));
+    for slow in &slow {
+        let reasons: Vec<String> = slow
+            .reasons
+            .iter()
+            .map(|reason| reason.to_string())
+            .collect();
+        println!("  {} ({})", slow.entry.display(), reasons.join(", "));
+    }
+    note(msg!("check-slow-explanation"));
+}
+
+/// Finds the slow entries, leaving out the Windows entries under WSL,
+/// which are reported on their own
+fn slow_entries(entries: &[PathBuf]) -> Vec<utils::slow::SlowEntry> {
+    let windows = utils::wsl::windows_entries(entries);
+    utils::slow::find_slow(entries)
+        .into_iter()
+        .filter(|slow| !windows.contains(&slow.entry))
+        .collect()
+}
+
+/// Reports how the entries break the system policy, if there is one
+fn report_policy_violations(entries: &[PathBuf], suggest_enforce: bool) {
+    let policy = match Policy::load() {
+        Ok(Some(policy)) => policy,
+        Ok(None) => return,
+        Err(e) => {
+            println!("{}", msg!("warning", warning = e));
+            return;
+        }
+    };
+
+    let violations = policy.violations(entries);
+    if violations.is_empty() {
+        return;
+    }
+    println!("{}", msg!("check-policy", file = POLICY_FILE));
+    for violation in &violations {
+        println!("  {}", violation);
+    }
+    if suggest_enforce {
+        note(msg!("check-policy-hint"));
+    }
+}
+
+/// Explains the tradeoff of the Windows PATH entries WSL appends
+fn report_windows_entries(entries: &[PathBuf]) {
+    let windows = utils::wsl::windows_entries(entries);
+    if windows.is_empty() {
+        return;
+    }
+
+    println!("{}", msg!("check-windows", count = windows.len()));
+    for entry in &windows {
+        println!("  {}", entry.display());
+    }
+    note(msg!("check-windows-explanation"));
+    if utils::wsl::WslConf::load().append_windows_path {
+        note(msg!("check-windows-appended", file = utils::wsl::WSL_CONF));
+    } else {
+        note(msg!("check-windows-configured"));
+    }
+}
+
+/// Reports Homebrew directories that come after the system directories or
+/// are missing, with the command that puts them in front
+fn report_homebrew(entries: &[PathBuf]) {
+    let Some(prefix) = homebrew::detect_prefix() else {
+        return;
+    };
+    let problems = homebrew::problems(&prefix, entries);
+    if problems.is_empty() {
+        return;
+    }
+
+    println!("{}", msg!("check-homebrew", prefix = prefix));
+    for problem in &problems {
+        println!("  {}", problem);
+    }
+    note(msg!("check-homebrew-explanation"));
+    let dirs: Vec<String> = homebrew::front_dirs(&prefix, entries)
+        .iter()
+        .map(|dir| dir.display().to_string())
+        .collect();
+    note(msg!("check-homebrew-hint", dirs = dirs.join(" ")));
+}
+
+/// Reports directories in the /etc/environment PATH that don't exist
+fn check_system_path() {
+    let missing = system_missing_dirs();
+    if !missing.is_empty() {
+        println!(
+            "{}",
+            msg!("check-system-invalid", file = system_env::ETC_ENVIRONMENT)
+        );
+        for dir in &missing {
+            println!("  {}", dir.to_string_lossy());
+        }
+        note(msg!("check-system-hint"));
+    }
+}
+
+/// Returns the directories in the /etc/environment PATH that don't exist
+fn system_missing_dirs() -> Vec<PathBuf> {
+    EtcEnvironment::read(&system_env::etc_environment())
+        .ok()
+        .and_then(|environment| environment.path_entries())
+        .map(|entries| validator::validate_entries(entries).missing_dirs)
+        .unwrap_or_default()
+}
+
+/// Reads the entries to check from the given source
+fn read_entries(source: CheckSource) -> io::Result<Vec<PathBuf>> {
+    match source {
+        CheckSource::Environment => Ok(validator::split_path_value(
+            env::var_os("PATH").unwrap_or_default(),
+        )),
+        CheckSource::Value(value) => Ok(validator::split_path_value(value)),
+        CheckSource::Declared => Ok(shell::declared_entries()),
+        CheckSource::File(file) => {
+            let content = if file == Path::new("-") {
+                let mut content = String::new();
+                io::stdin().read_to_string(&mut content)?;
+                content
+            } else {
+                fs::read_to_string(file).map_err(|e| {
+                    io::Error::new(e.kind(), format!("Cannot read {}: {}", file.display(), e))
+                })?
+            };
+            Ok(validator::parse_path_dump(&content))
+        }
+    }
+}
+
+/// Prints the problems found by validation
+fn report(validation: &PathValidation, suggest_flush: bool) {
+    if validation.is_valid() {
+        println!("{}", msg!("check-all-valid"));
+    }
+    let layout = Layout::from_env();
+    let mounts = mounts::read_mounts();
+    let mut missing = Vec::new();
+    let mut unmounted = Vec::new();
+    for dir in &validation.missing_dirs {
+        match mounts::unmounted_reason(&mounts, dir) {
+            Some(reason) => unmounted.push((dir, reason)),
+            None => missing.push(dir),
+        }
+    }
+    if !missing.is_empty() {
+        println!("{}", msg!("check-invalid"));
+        for dir in &missing {
+            println!("  {}", describe(dir, &layout));
+        }
+    }
+    if !unmounted.is_empty() {
+        println!("{}", msg!("check-unmounted"));
+        for (dir, reason) in &unmounted {
+            println!("  {} ({})", dir.to_string_lossy(), reason);
+        }
+        if suggest_flush {
+            note(msg!("check-unmounted-hint"));
+        }
+    }
+    if !validation.not_dirs.is_empty() {
+        println!("{}", msg!("check-not-dirs"));
+        for path in &validation.not_dirs {
+            println!("  {}", describe(path, &layout));
+        }
+        note(msg!("check-not-dirs-hint"));
+    }
+    if !validation.relative_dirs.is_empty() {
+        println!("{}", msg!("check-relative"));
+        for path in &validation.relative_dirs {
+            println!("  {}", path.to_string_lossy());
+        }
+    }
+    if validation.empty_entries > 0 {
+        println!("{}", msg!("check-empty", count = validation.empty_entries));
+    }
+    if suggest_flush && (!validation.relative_dirs.is_empty() || validation.empty_entries > 0) {
+        note(msg!("check-relative-hint"));
+    }
+}
+
+/// Prints a message indented under the list it explains, line by line
+fn note(message: String) {
+    for line in message.lines() {
+        println!("  {}", line);
+    }
+}
+
+/// Formats an entry, naming the package manager that owns it
+fn describe(entry: &Path, layout: &Layout) -> String {
+    match layout.attribute(entry) {
+        Some(ecosystem) => format!("{} ({})", entry.to_string_lossy(), ecosystem),
+        None => entry.to_string_lossy().into_owned(),
+    }
+}
@@ -0,0 +1,310 @@
+//! Command implementation for tidying up PATH in one step.
+//!
+//! This module provides functionality to:
+//! - Normalize the spelling of every entry
+//! - Collapse duplicates, including ones spelled differently
+//! - Remove empty entries and directories that don't exist
+//! - Optionally remove the Windows entries WSL appends
+//! - Leave directories listed in `keep-dirs` exactly as written
+//! - Apply everything as one backup-protected update with a summary
+
+use crate::commands::flush::windows_to_strip;
+use crate::commands::validator::is_valid_path_entry;
+use crate::msg;
+use crate::utils::path::normalize_entry;
+use crate::utils::protected;
+use crate::utils::{find_path_entry, PathSet};
+use std::path::{Path, PathBuf};
+
+/// One change made by clean
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    /// An empty entry from a leading, trailing or doubled colon was removed
+    Empty,
+    /// An entry that isn't an existing directory was removed
+    Invalid(PathBuf),
+    /// A repeat of the entry at this zero-based position was removed
+    Duplicate(PathBuf, usize),
+    /// An entry was respelled
+    Normalized(PathBuf, PathBuf),
+    /// A missing protected system directory was kept
+    KeptProtected(PathBuf),
+    /// A Windows entry appended by WSL was removed
+    Windows(PathBuf),
+    /// An entry listed in `keep-dirs` was kept as written instead of the change described
+    Excluded(PathBuf, String),
+}
+
+/// Executes the clean command: normalize, dedupe and flush in one update
+///
+/// # Arguments
+///
+/// * `resolve_symlinks` - Replace symlinked entries with their targets
+/// * `allow_system` - Also remove protected system directories that don't exist
+/// * `strip_windows` - Also remove Windows entries appended by WSL
+///
+/// # Example
+///
+/// ```
+/// commands::clean::execute(false, false, false);
+/// // Output example:
+/// // Normalizing /usr/local/bin/ -> /usr/local/bin
+/// // Removing duplicate: /usr/local/bin (same as position 1)
+/// ```
+pub fn execute(resolve_symlinks: bool, allow_system: bool, strip_windows: bool) {
+    let original = PathSet::from_env();
+    let protected = if allow_system {
+        Vec::new()
+    } else {
+        protected::protected_dirs()
+    };
+    let windows = if strip_windows {
+        windows_to_strip(original.entries())
+    } else {
+        Vec::new()
+    };
+    let (cleaned, changes) = clean_entries(
+        original.entries(),
+        resolve_symlinks,
+        &protected,
+        &protected::keep_dirs(),
+        &windows,
+    );
+
+    let (mut removed, mut duplicates, mut normalized, mut stripped) = (0, 0, 0, 0);
+    for change in &changes {
+        match change {
+            Change::Empty => {
+                println!("{}", msg!("clean-empty"));
+                removed += 1;
+            }
+            Change::Invalid(entry) => {
+                println!("{}", msg!("clean-invalid", entry = entry));
+                removed += 1;
+            }
+            Change::Windows(entry) => {
+                println!("{}", msg!("clean-windows", entry = entry));
+                stripped += 1;
+            }
+            Change::Duplicate(entry, first) => {
+                println!(
+                    "{}",
+                    msg!("clean-duplicate", entry = entry, position = first + 1)
+                );
+                duplicates += 1;
+            }
+            Change::Normalized(from, to) => {
+                println!("{}", msg!("clean-normalized", from = from, to = to));
+                normalized += 1;
+            }
+            Change::KeptProtected(entry) => {
+                println!("{}", msg!("clean-kept-protected", entry = entry));
+            }
+            Change::Excluded(entry, reason) => {
+                println!(
+                    "{}",
+                    msg!(
+                        "clean-kept-excluded",
+                        entry = entry,
+                        reason = reason.to_string()
+                    )
+                );
+            }
+        }
+    }
+
+    if removed + duplicates + normalized + stripped == 0 {
+        println!("{}", msg!("clean-nothing"));
+        return;
+    }
+
+    let cleaned = PathSet::new(cleaned);
+
+    // Backup current PATH
+    if let Err(e) = cleaned.apply_backup() {
+        eprintln!("{}", msg!("error-backup", error = e));
+        return;
+    }
+
+    // Update PATH and make persistent changes (update shell config)
+    if let Err(e) = cleaned.apply() {
+        eprintln!("{}", msg!("error-shell-config", error = e));
+        return;
+    }
+
+    println!(
+        "{}",
+        msg!(
+            "clean-done",
+            removed = removed,
+            duplicates = duplicates,
+            normalized = normalized,
+            before = original.entries().len(),
+            after = cleaned.entries().len()
+        )
+    );
+    if stripped > 0 {
+        println!("{}", msg!("clean-windows-done", count = stripped));
+    }
+}
+
+/// Works out the cleaned PATH and the changes that lead to it.
+///
+/// Entries are normalized before duplicates are looked for, so
+/// `/usr/local/bin/` and `/usr/local/bin` collapse into the first one.
+///
+/// # Arguments
+/// * `entries` - Current PATH entries
+/// * `resolve_symlinks` - Replace symlinked entries with their targets
+/// * `protected` - Directories kept even if they don't exist
+/// * `keep` - Directories kept exactly as written
+/// * `windows` - Windows entries to remove
+pub fn clean_entries(
+    entries: &[PathBuf],
+    resolve_symlinks: bool,
+    protected: &[PathBuf],
+    keep: &[PathBuf],
+    windows: &[PathBuf],
+) -> (Vec<PathBuf>, Vec<Change>) {
+    let mut cleaned: Vec<PathBuf> = Vec::new();
+    let mut changes = Vec::new();
+
+    for entry in entries {
+        if entry.as_os_str().is_empty() {
+            changes.push(Change::Empty);
+            continue;
+        }
+        if protected::is_kept(entry, keep) {
+            if let Some(reason) = kept_reason(entry, resolve_symlinks, &cleaned, windows) {
+                changes.push(Change::Excluded(entry.clone(), reason));
+            }
+            cleaned.push(entry.clone());
+            continue;
+        }
+        if windows.contains(entry) {
+            changes.push(Change::Windows(entry.clone()));
+            continue;
+        }
+
+        let normalized = normalize_entry(entry, resolve_symlinks);
+        if !is_valid_path_entry(&normalized) {
+            if !protected::is_protected(&normalized, protected) {
+                changes.push(Change::Invalid(entry.clone()));
+                continue;
+            }
+            changes.push(Change::KeptProtected(entry.clone()));
+        }
+
+        if let Some(first) = find_path_entry(&cleaned, &normalized) {
+            changes.push(Change::Duplicate(entry.clone(), first));
+            continue;
+        }
+
+        if normalized.as_os_str() != entry.as_os_str() {
+            changes.push(Change::Normalized(entry.clone(), normalized.clone()));
+        }
+        cleaned.push(normalized);
+    }
+
+    (cleaned, changes)
+}
+
+/// Describes what clean would have done to an entry listed in `keep-dirs`,
+/// or `None` if it would have been left alone anyway
+fn kept_reason(
+    entry: &Path,
+    resolve_symlinks: bool,
+    cleaned: &[PathBuf],
+    windows: &[PathBuf],
+) -> Option<String> {
+    let normalized = normalize_entry(entry, resolve_symlinks);
+    if windows.iter().any(|windows_entry| windows_entry == entry) {
+        Some("Windows entry".to_string())
+    } else if !is_valid_path_entry(&normalized) {
+        Some("not available".to_string())
+    } else if let Some(first) = find_path_entry(cleaned, &normalized) {
+        Some(format!("same as position {}", first + 1))
+    } else if normalized.as_os_str() != entry.as_os_str() {
+        Some(format!("would be normalized to {}", normalized.display()))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_clean_entries() {
+        let temp_dir = TempDir::new().unwrap();
+        let bin = temp_dir.path().join("bin");
+        let lib = temp_dir.path().join("lib");
+        std::fs::create_dir(&bin).unwrap();
+        std::fs::create_dir(&lib).unwrap();
+        let spelled = PathBuf::from(format!("{}//bin/", temp_dir.path().display()));
+
+        let entries = vec![
+            spelled.clone(),
+            PathBuf::new(),
+            PathBuf::from("/pm-test/missing"),
+            lib.clone(),
+            bin.clone(),
+            PathBuf::from("/pm-test/protected"),
+            PathBuf::from("/mnt/c/Windows"),
+        ];
+        let protected = vec![PathBuf::from("/pm-test/protected")];
+        let windows = vec![PathBuf::from("/mnt/c/Windows")];
+        let (cleaned, changes) = clean_entries(&entries, false, &protected, &[], &windows);
+
+        assert_eq!(
+            cleaned,
+            vec![bin.clone(), lib, PathBuf::from("/pm-test/protected")]
+        );
+        assert_eq!(
+            changes,
+            vec![
+                Change::Normalized(spelled, bin.clone()),
+                Change::Empty,
+                Change::Invalid(PathBuf::from("/pm-test/missing")),
+                Change::Duplicate(bin, 0),
+                Change::KeptProtected(PathBuf::from("/pm-test/protected")),
+                Change::Windows(PathBuf::from("/mnt/c/Windows")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_clean_entries_keeps_excluded() {
+        let temp_dir = TempDir::new().unwrap();
+        let bin = temp_dir.path().join("bin");
+        std::fs::create_dir(&bin).unwrap();
+        let spelled = PathBuf::from(format!("{}/", bin.display()));
+
+        let entries = vec![
+            bin.clone(),
+            PathBuf::from("/pm-test/mount/bin"),
+            spelled.clone(),
+            PathBuf::from("/pm-test/missing"),
+        ];
+        let keep = vec![bin.clone(), PathBuf::from("/pm-test/mount/bin")];
+        let (cleaned, changes) = clean_entries(&entries, false, &[], &keep, &[]);
+
+        assert_eq!(
+            cleaned,
+            vec![bin, PathBuf::from("/pm-test/mount/bin"), spelled.clone()]
+        );
+        assert_eq!(
+            changes,
+            vec![
+                Change::Excluded(
+                    PathBuf::from("/pm-test/mount/bin"),
+                    "not available".to_string()
+                ),
+                Change::Excluded(spelled, "same as position 1".to_string()),
+                Change::Invalid(PathBuf::from("/pm-test/missing")),
+            ]
+        );
+    }
+}
@@ -2,55 +2,413 @@
 //!
 //! This module handles:
 //! - Removing specified directories from PATH
+//! - Removing directories only the shell config declares, e.g. after it was
+//!   edited by hand
+//! - Selecting entries by position, glob pattern, or regex
+//! - Refusing to remove protected system directories
 //! - Creating backups before modification
 //! - Updating shell configuration
 //! - Maintaining PATH integrity
 
-use crate::backup;
-use crate::utils;
+use crate::msg;
+use crate::utils::prompt;
+use crate::utils::protected;
+use crate::utils::shell;
+use crate::utils::shell::types::DeclaredPath;
+use crate::utils::{self, find_path_entry, PathSet};
+use glob::Pattern;
+use regex::Regex;
+use std::ffi::OsString;
+use std::path::PathBuf;
+
+/// Selectors for removing entries without typing exact paths
+#[derive(Debug, Default, Clone)]
+pub struct DeleteOptions {
+    /// One-based position of the entry to remove
+    pub index: Option<usize>,
+    /// Glob pattern matched against each entry (e.g. `~/old-sdks/*`)
+    pub glob: Option<String>,
+    /// Regular expression matched against each entry
+    pub regex: Option<String>,
+    /// Allow removing protected system directories
+    pub allow_system: bool,
+}
+
+impl DeleteOptions {
+    /// Returns whether any pattern selector that can match many entries is set
+    fn has_patterns(&self) -> bool {
+        self.glob.is_some() || self.regex.is_some()
+    }
+}
 
 /// Executes the delete command to remove directories from PATH
 ///
 /// # Arguments
 ///
-/// * `directories` - A slice of strings containing directories to remove
+/// * `directories` - The directories to remove, as given on the command line
+/// * `options` - Additional selectors for entries to remove
+///
+/// # Returns
+///
+/// `false` if a selector was invalid, nothing matched, a protected directory
+/// was selected, or the backup or shell config couldn't be written. Declining the confirmation isn't a failure.
 ///
 /// # Example
 ///
 /// ```
-/// let dirs = vec![String::from("~/old/bin")];
-/// commands::delete::execute(&dirs);
+/// let dirs = vec![OsString::from("~/old/bin")];
+/// commands::delete::execute(&dirs, &DeleteOptions::default());
 /// ```
-pub fn execute(directories: &[String]) {
+pub fn execute(directories: &[OsString], options: &DeleteOptions) -> bool {
+    // Get current PATH
+    let path_entries = utils::get_path_entries();
+
+    // Entries the shell config declares that this session's PATH lacks
+    let declared = shell::declared_path();
+    let mut config_only: Vec<PathBuf> = Vec::new();
+    for entry in &declared.entries {
+        if find_path_entry(&path_entries, entry).is_none()
+            && find_path_entry(&config_only, entry).is_none()
+        {
+            config_only.push(entry.clone());
+        }
+    }
+
+    // Positions count in PATH only
+    let config_options = DeleteOptions {
+        index: None,
+        ..options.clone()
+    };
+    let selected = select_entries(&path_entries, directories, options).and_then(|selected| {
+        let in_config = select_entries(&config_only, directories, &config_options)?;
+        Ok((selected, in_config))
+    });
+    let (selected, in_config) = match selected {
+        Ok(selected) => selected,
+        Err(e) => {
+            eprintln!("{}", msg!("error", error = e));
+            return false;
+        }
+    };
+
+    if selected.is_empty() && in_config.is_empty() {
+        println!("{}", msg!("delete-none-found"));
+        return false;
+    }
+
+    if !selected.is_empty() {
+        println!("{}", msg!("delete-matched"));
+        for &index in &selected {
+            println!("  {}: {}", index + 1, path_entries[index].display());
+        }
+    }
+    if !in_config.is_empty() {
+        println!("{}", msg!("delete-config-only"));
+        for &index in &in_config {
+            println!("  {}", config_only[index].display());
+        }
+    }
+
+    if !options.allow_system {
+        let protected = protected::protected_dirs();
+        let blocked: Vec<&PathBuf> = selected
+            .iter()
+            .map(|&index| &path_entries[index])
+            .chain(in_config.iter().map(|&index| &config_only[index]))
+            .filter(|entry| protected::is_protected(entry, &protected))
+            .collect();
+        if !blocked.is_empty() {
+            eprintln!("{}", msg!("delete-protected"));
+            for entry in blocked {
+                eprintln!("  {}", entry.display());
+            }
+            eprintln!("{}", msg!("delete-allow-system-hint"));
+            return false;
+        }
+    }
+
+    let count = selected.len() + in_config.len();
+    if options.has_patterns()
+        && count > 1
+        && !prompt::confirm(&msg!("delete-confirm", count = count))
+    {
+        println!("{}", msg!("delete-aborted"));
+        return true;
+    }
+
+    let mut path_set = PathSet::new(path_entries.clone());
+
     // Backup current PATH
-    if let Err(e) = backup::create_backup() {
-        eprintln!("Error creating backup: {}", e);
-        return;
+    if let Err(e) = path_set.apply_backup() {
+        eprintln!("{}", msg!("error-backup", error = e));
+        return false;
     }
 
-    // Get current PATH
-    let mut path_entries = utils::get_path_entries();
+    // Remove the selected entries from PATH. The shell config also keeps the
+    // entries only it declares, except those selected.
+    let removed: Vec<PathBuf> = selected
+        .iter()
+        .map(|&index| path_entries[index].clone())
+        .chain(in_config.iter().map(|&index| config_only[index].clone()))
+        .collect();
+    let is_kept = |entry: &&PathBuf| find_path_entry(&removed, entry).is_none();
+    let kept = DeclaredPath {
+        ahead: declared.entries[..declared.ahead]
+            .iter()
+            .filter(is_kept)
+            .count(),
+        entries: declared.entries.iter().filter(is_kept).cloned().collect(),
+    };
+    path_set.remove_positions(&selected);
+    path_set.apply_env();
+    let mut config_set = path_set.clone();
+    config_set.merge(&kept);
+    if let Err(e) = config_set.apply_shell_config() {
+        eprintln!("{}", msg!("error-shell-config", error = e));
+        return false;
+    }
+
+    println!("{}", msg!("delete-done"));
+    true
+}
+
+/// Determines which PATH entries are selected for removal.
+///
+/// Exact directories match every occurrence of the expanded path. Globs are
+/// tilde-expanded before matching; regexes match anywhere in the entry.
+///
+/// # Returns
+/// * `Ok(Vec<usize>)` - Sorted zero-based indices of the selected entries
+/// * `Err(String)` - If a selector is invalid or out of range
+fn select_entries(
+    entries: &[PathBuf],
+    directories: &[OsString],
+    options: &DeleteOptions,
+) -> Result<Vec<usize>, String> {
+    let mut selected = Vec::new();
 
-    // Remove the directories
-    let original_len = path_entries.len();
     for directory in directories {
-        let dir_path = utils::expand_path(directory);
-        path_entries.retain(|p| p != &dir_path);
+        let dir_path = utils::expand_os_path(directory);
+        selected.extend(
+            entries
+                .iter()
+                .enumerate()
+                .filter(|(_, entry)| **entry == dir_path)
+                .map(|(index, _)| index),
+        );
     }
 
-    if path_entries.len() == original_len {
-        println!("None of the directories were found in PATH.");
-        return;
+    if let Some(index) = options.index {
+        if index == 0 || index > entries.len() {
+            return Err(msg!(
+                "delete-index-out-of-range",
+                index = index,
+                count = entries.len()
+            ));
+        }
+        selected.push(index - 1);
     }
 
-    // Update PATH
-    utils::set_path_entries(&path_entries);
+    if let Some(glob) = &options.glob {
+        let expanded = shellexpand::tilde(glob);
+        let pattern = Pattern::new(&expanded)
+            .map_err(|e| msg!("delete-invalid-glob", pattern = glob, error = e.to_string()))?;
+        selected.extend(
+            entries
+                .iter()
+                .enumerate()
+                .filter(|(_, entry)| pattern.matches_path(entry))
+                .map(|(index, _)| index),
+        );
+    }
+
+    if let Some(regex) = &options.regex {
+        let regex = Regex::new(regex).map_err(|e| {
+            msg!(
+                "delete-invalid-regex",
+                pattern = regex,
+                error = e.to_string()
+            )
+        })?;
+        selected.extend(
+            entries
+                .iter()
+                .enumerate()
+                .filter(|(_, entry)| regex.is_match(&entry.to_string_lossy()))
+                .map(|(index, _)| index),
+        );
+    }
+
+    selected.sort_unstable();
+    selected.dedup();
+    Ok(selected)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config;
+    use crate::utils::root;
+    use crate::utils::shell::locations;
+    use crate::utils::shell::types::ShellType;
+    use serial_test::serial;
+    use std::env;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn sample_entries() -> Vec<PathBuf> {
+        vec![
+            PathBuf::from("/usr/bin"),
+            PathBuf::from("/opt/sdks/v1/bin"),
+            PathBuf::from("/home/user/project/node_modules/.bin"),
+            PathBuf::from("/opt/sdks/v2/bin"),
+        ]
+    }
+
+    #[test]
+    fn test_select_by_index() {
+        let options = DeleteOptions {
+            index: Some(2),
+            ..Default::default()
+        };
+        assert_eq!(
+            select_entries(&sample_entries(), &[], &options),
+            Ok(vec![1])
+        );
+
+        let options = DeleteOptions {
+            index: Some(5),
+            ..Default::default()
+        };
+        assert!(select_entries(&sample_entries(), &[], &options).is_err());
+    }
+
+    #[test]
+    fn test_select_by_glob_and_regex() {
+        let options = DeleteOptions {
+            glob: Some("/opt/sdks/*/bin".to_string()),
+            regex: Some("node_modules".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            select_entries(&sample_entries(), &[], &options),
+            Ok(vec![1, 2, 3])
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn test_delete_updates_shell_config() {
+        let temp_dir = TempDir::new().unwrap();
+        let dirs: Vec<PathBuf> = ["a", "b", "c", "d"]
+            .iter()
+            .map(|name| temp_dir.path().join(name))
+            .collect();
+        for dir in &dirs {
+            fs::create_dir_all(dir).unwrap();
+        }
+        let joined = |entries: &[&PathBuf]| env::join_paths(entries).unwrap();
 
-    // Make persistent changes (update shell config)
-    if let Err(e) = utils::update_shell_config(&path_entries) {
-        eprintln!("Error updating shell configuration: {}", e);
-        return;
+        root::set_root(Some(temp_dir.path())).unwrap();
+        let home = locations::home_dir();
+        fs::create_dir_all(&home).unwrap();
+        fs::write(home.join(".bashrc"), "alias ll='ls -l'\n").unwrap();
+        config::set_config(config::Config {
+            shell: Some(ShellType::Bash),
+            ..Default::default()
+        });
+        crate::backup::core::set_backup_dir(temp_dir.path().join("backups")).unwrap();
+        let original_path = env::var_os("PATH");
+
+        // The config declares a, b, c and d, but this session's PATH lacks c
+        env::set_var("PATH", joined(&[&dirs[0], &dirs[1], &dirs[3]]));
+        PathSet::new(dirs.clone()).apply_shell_config().unwrap();
+
+        // b is in PATH and in the config
+        let removed_path_entry = execute(&[dirs[1].clone().into()], &DeleteOptions::default());
+        let after_path_entry = shell::declared_entries();
+        let env_after_path_entry = utils::get_path_entries();
+
+        // c is only in the config
+        let removed_config_entry = execute(&[dirs[2].clone().into()], &DeleteOptions::default());
+        let after_config_entry = shell::declared_entries();
+        let env_after_config_entry = utils::get_path_entries();
+
+        // Nothing matches an entry that is in neither
+        let removed_missing = execute(
+            &[temp_dir.path().join("missing").into()],
+            &DeleteOptions::default(),
+        );
+
+        match original_path {
+            Some(path) => env::set_var("PATH", path),
+            None => env::remove_var("PATH"),
+        }
+        root::set_root(None).unwrap();
+        config::clear_config();
+
+        assert!(removed_path_entry);
+        assert!(removed_config_entry);
+        assert!(!removed_missing);
+        assert_eq!(
+            after_path_entry,
+            vec![dirs[0].clone(), dirs[2].clone(), dirs[3].clone()]
+        );
+        assert_eq!(env_after_path_entry, vec![dirs[0].clone(), dirs[3].clone()]);
+        assert_eq!(after_config_entry, vec![dirs[0].clone(), dirs[3].clone()]);
+        assert_eq!(
+            env_after_config_entry,
+            vec![dirs[0].clone(), dirs[3].clone()]
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn test_delete_refuses_protected_entries() {
+        let temp_dir = TempDir::new().unwrap();
+        let system = temp_dir.path().join("system");
+        let mine = temp_dir.path().join("mine");
+        let original_path = env::var_os("PATH");
+
+        config::set_config(config::Config {
+            shell: Some(ShellType::Bash),
+            protected_dirs: Some(vec![system.to_string_lossy().into_owned()]),
+            ..Default::default()
+        });
+        env::set_var("PATH", env::join_paths([&system, &mine]).unwrap());
+
+        // Matching a protected entry refuses the whole removal
+        let removed = execute(
+            &[],
+            &DeleteOptions {
+                glob: Some(format!("{}/*", temp_dir.path().display())),
+                ..Default::default()
+            },
+        );
+        let entries = utils::get_path_entries();
+
+        match original_path {
+            Some(path) => env::set_var("PATH", path),
+            None => env::remove_var("PATH"),
+        }
+        config::clear_config();
+
+        assert!(!removed);
+        assert_eq!(entries, vec![system, mine]);
     }
 
-    println!("Successfully removed directories from PATH.");
+    #[test]
+    fn test_select_exact_and_invalid_regex() {
+        let dirs = vec![OsString::from("/usr/bin")];
+        assert_eq!(
+            select_entries(&sample_entries(), &dirs, &DeleteOptions::default()),
+            Ok(vec![0])
+        );
+
+        let options = DeleteOptions {
+            regex: Some("(".to_string()),
+            ..Default::default()
+        };
+        assert!(select_entries(&sample_entries(), &[], &options).is_err());
+    }
 }
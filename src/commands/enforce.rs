@@ -0,0 +1,155 @@
+//! Command implementation for applying the administrator's PATH policy.
+//!
+//! This module handles:
+//! - Removing forbidden directories from PATH
+//! - Adding missing required directories next to their declared neighbours
+//! - Reordering PATH to satisfy the policy's ordering rules
+//! - Reporting violations that can't be fixed automatically
+//!
+//! See [`crate::utils::policy`] for the policy file format.
+
+use crate::commands::ensure::insert_missing;
+use crate::commands::validator::is_valid_path_entry;
+use crate::msg;
+use crate::utils::policy::{Policy, POLICY_FILE};
+use crate::utils::{self, rules, PathSet};
+use std::path::PathBuf;
+
+/// Executes the enforce command
+///
+/// # Returns
+/// * `true` if PATH complies with the policy afterwards
+///
+/// # Example
+///
+/// ```
+/// commands::enforce::execute();
+/// // Output example:
+/// // Removing forbidden directory: /tmp/bin
+/// // Adding required directory: /opt/corp/bin
+/// // PATH now complies with /etc/pathmaster/policy.toml.
+/// ```
+pub fn execute() -> bool {
+    let policy = match Policy::load() {
+        Ok(Some(policy)) => policy,
+        Ok(None) => {
+            println!("{}", msg!("enforce-no-policy", file = POLICY_FILE));
+            return true;
+        }
+        Err(e) => {
+            eprintln!("{}", msg!("error", error = e));
+            return false;
+        }
+    };
+
+    let current = utils::get_path_entries();
+    let (available, unavailable): (Vec<PathBuf>, Vec<PathBuf>) = policy
+        .required()
+        .into_iter()
+        .partition(|dir| is_valid_path_entry(dir));
+    let (enforced, removed, added) = apply_policy(&policy, &current, &available);
+
+    for entry in &removed {
+        println!("{}", msg!("enforce-removing", path = entry));
+    }
+    for entry in &added {
+        println!("{}", msg!("enforce-adding", path = entry));
+    }
+    for entry in &unavailable {
+        if utils::find_path_entry(&current, entry).is_none() {
+            eprintln!("{}", msg!("enforce-required-missing", path = entry));
+        }
+    }
+
+    if enforced != current {
+        if removed.is_empty() && added.is_empty() {
+            println!("{}", msg!("enforce-reordering"));
+        }
+        let path_set = PathSet::new(enforced.clone());
+
+        // Backup current PATH
+        if let Err(e) = path_set.apply_backup() {
+            eprintln!("{}", msg!("error-backup", error = e));
+            return false;
+        }
+        if let Err(e) = path_set.apply() {
+            eprintln!("{}", msg!("error-shell-config", error = e));
+            return false;
+        }
+    }
+
+    let remaining = policy.violations(&utils::get_path_entries());
+    if remaining.is_empty() {
+        if enforced == current {
+            println!("{}", msg!("enforce-already", file = POLICY_FILE));
+        } else {
+            println!("{}", msg!("enforce-done", file = POLICY_FILE));
+        }
+        return true;
+    }
+
+    println!("{}", msg!("enforce-remaining", file = POLICY_FILE));
+    for violation in &remaining {
+        println!("  {}", violation);
+    }
+    false
+}
+
+/// Works out PATH under the policy: forbidden entries removed, available
+/// required entries added, then the ordering rules applied.
+///
+/// # Returns
+/// * The new entries, the entries removed and the entries added
+fn apply_policy(
+    policy: &Policy,
+    entries: &[PathBuf],
+    required: &[PathBuf],
+) -> (Vec<PathBuf>, Vec<PathBuf>, Vec<PathBuf>) {
+    let (forbidden, allowed): (Vec<PathBuf>, Vec<PathBuf>) = entries
+        .iter()
+        .cloned()
+        .partition(|entry| policy.is_forbidden(entry));
+    let required: Vec<PathBuf> = required
+        .iter()
+        .filter(|dir| !policy.is_forbidden(dir))
+        .cloned()
+        .collect();
+    let (with_required, added) = insert_missing(&allowed, &required);
+    (
+        rules::enforce(&policy.rules, &with_required),
+        forbidden,
+        added,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::rules::{PathRule, Position};
+    use crate::utils::test_support::paths;
+
+    #[test]
+    fn test_apply_policy() {
+        let policy = Policy {
+            forbidden_dirs: vec![".".to_string()],
+            required_dirs: vec!["/pm-test/corp/bin".to_string()],
+            rules: vec![PathRule {
+                entry: "/pm-test/corp/bin".to_string(),
+                position: Some(Position::First),
+                ..PathRule::default()
+            }],
+        };
+        let entries = paths(&["/usr/bin", ".", "/bin"]);
+
+        let (enforced, removed, added) = apply_policy(&policy, &entries, &policy.required());
+        assert_eq!(enforced, paths(&["/pm-test/corp/bin", "/usr/bin", "/bin"]));
+        assert_eq!(removed, paths(&["."]));
+        assert_eq!(added, paths(&["/pm-test/corp/bin"]));
+        assert!(policy.violations(&enforced).is_empty());
+
+        // Enforcing again changes nothing
+        let (again, removed, added) = apply_policy(&policy, &enforced, &policy.required());
+        assert_eq!(again, enforced);
+        assert!(removed.is_empty() && added.is_empty());
+    }
+}
@@ -0,0 +1,199 @@
+//! Command implementation for making sure required directories are on PATH.
+//!
+//! This module handles:
+//! - Reading required directories from arguments or the config file
+//! - Adding missing directories next to their declared neighbours
+//! - Leaving PATH and shell configs untouched when nothing is missing
+//!
+//! Running `ensure` again after it succeeded changes nothing, so it can be
+//! called from provisioning scripts and dotfile bootstraps.
+
+use crate::backup;
+use crate::commands::validator::is_valid_path_entry;
+use crate::config;
+use crate::msg;
+use crate::utils::check_mode;
+use crate::utils::{self, PathSet};
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Options controlling how missing directories are handled
+#[derive(Debug, Default, Clone)]
+pub struct EnsureOptions {
+    /// Create required directories that don't exist yet
+    pub create: bool,
+    /// Add required directories even if they don't exist
+    pub force: bool,
+}
+
+/// Executes the ensure command to add any required directories missing from PATH
+///
+/// # Arguments
+///
+/// * `directories` - Required directories in order; the config's `required-dirs` when empty
+/// * `options` - How to handle required directories that don't exist
+///
+/// # Returns
+/// * `true` if every required directory is on PATH afterwards
+///
+/// # Example
+///
+/// ```
+/// let dirs = vec![OsString::from("~/.local/bin"), OsString::from("~/.cargo/bin")];
+/// commands::ensure::execute(&dirs, &EnsureOptions::default());
+/// ```
+pub fn execute(directories: &[OsString], options: &EnsureOptions) -> bool {
+    let declared = if directories.is_empty() {
+        config::get_config()
+            .required_dirs
+            .into_iter()
+            .map(OsString::from)
+            .collect()
+    } else {
+        directories.to_vec()
+    };
+    if declared.is_empty() {
+        eprintln!("{}", msg!("ensure-none-required"));
+        return false;
+    }
+
+    let path_entries = utils::get_path_entries();
+    let mut complete = true;
+    let mut required = Vec::new();
+    for dir in &declared {
+        let dir_path = utils::expand_os_path(dir);
+        if utils::find_path_entry(&path_entries, &dir_path).is_none()
+            && !prepare_directory(&dir_path, options)
+        {
+            complete = false;
+            continue;
+        }
+        required.push(dir_path);
+    }
+
+    let (updated, added) = insert_missing(&path_entries, &required);
+    if added.is_empty() {
+        println!("{}", msg!("ensure-complete"));
+        return complete;
+    }
+
+    if let Err(e) = backup::create_backup() {
+        eprintln!("{}", msg!("error-backup", error = e));
+        return false;
+    }
+
+    for dir in &added {
+        println!("{}", msg!("add-added", path = dir));
+    }
+
+    if let Err(e) = PathSet::new(updated).apply() {
+        eprintln!("{}", msg!("error-shell-config", error = e));
+        return false;
+    }
+
+    println!("{}", msg!("add-summary-added", count = added.len()));
+    complete
+}
+
+/// Checks that a missing required directory can be added, creating it if asked.
+///
+/// # Returns
+/// * `true` if the directory should be added
+fn prepare_directory(dir_path: &Path, options: &EnsureOptions) -> bool {
+    if options.create && !dir_path.exists() {
+        if check_mode::is_check_mode() {
+            println!("{}", msg!("add-would-create", path = dir_path));
+            return true;
+        }
+        if let Err(e) = fs::create_dir_all(dir_path) {
+            eprintln!("{}", msg!("add-create-failed", path = dir_path, error = e));
+            return false;
+        }
+        println!("{}", msg!("add-created", path = dir_path));
+    }
+
+    if !is_valid_path_entry(dir_path) && !options.force {
+        let reason = if dir_path.exists() {
+            msg!("reason-not-a-directory")
+        } else {
+            msg!("reason-missing")
+        };
+        eprintln!("{}", msg!("add-invalid", path = dir_path, reason = reason));
+        return false;
+    }
+    true
+}
+
+/// Inserts the required entries missing from `entries`, keeping their declared order.
+///
+/// A missing entry goes right after the previous required entry in PATH, or
+/// before the next one when it comes first; if none of them is in PATH, it's
+/// appended. Entries already in PATH aren't moved.
+///
+/// # Returns
+/// * The updated entries and the entries that were added
+pub fn insert_missing(entries: &[PathBuf], required: &[PathBuf]) -> (Vec<PathBuf>, Vec<PathBuf>) {
+    let mut entries = entries.to_vec();
+    let mut added = Vec::new();
+    // Position of the last required entry placed so far
+    let mut anchor: Option<usize> = None;
+
+    for (index, dir) in required.iter().enumerate() {
+        if let Some(position) = utils::find_path_entry(&entries, dir) {
+            anchor = Some(position);
+            continue;
+        }
+
+        let position = match anchor {
+            Some(anchor) => anchor + 1,
+            None => required[index + 1..]
+                .iter()
+                .find_map(|next| utils::find_path_entry(&entries, next))
+                .unwrap_or(entries.len()),
+        };
+        entries.insert(position, dir.clone());
+        added.push(dir.clone());
+        anchor = Some(position);
+    }
+
+    (entries, added)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_support::paths;
+
+    #[test]
+    fn test_insert_missing_keeps_declared_order() {
+        let required = paths(&["/req/a", "/req/b", "/usr/local/bin", "/req/c"]);
+        let entries = paths(&["/usr/bin", "/usr/local/bin", "/bin"]);
+
+        let (updated, added) = insert_missing(&entries, &required);
+        assert_eq!(
+            updated,
+            paths(&[
+                "/usr/bin",
+                "/req/a",
+                "/req/b",
+                "/usr/local/bin",
+                "/req/c",
+                "/bin"
+            ])
+        );
+        assert_eq!(added, paths(&["/req/a", "/req/b", "/req/c"]));
+
+        // Running again changes nothing
+        let (again, added) = insert_missing(&updated, &required);
+        assert_eq!(again, updated);
+        assert!(added.is_empty());
+    }
+
+    #[test]
+    fn test_insert_missing_appends_without_neighbours() {
+        let (updated, added) = insert_missing(&paths(&["/usr/bin"]), &paths(&["/a", "/b"]));
+        assert_eq!(updated, paths(&["/usr/bin", "/a", "/b"]));
+        assert_eq!(added.len(), 2);
+    }
+}
@@ -0,0 +1,45 @@
+//! Command implementation for removing expired PATH entries.
+//!
+//! This module handles:
+//! - Finding entries added with `add --ttl` whose time has run out
+//! - Removing them in one backup-protected update
+
+use crate::msg;
+use crate::utils::provenance;
+use crate::utils::PathSet;
+
+/// Executes the expire command to remove entries whose TTL has run out
+///
+/// # Example
+///
+/// ```
+/// commands::expire::execute();
+/// // Output example:
+/// // Removing /home/user/tmp/toolchain/bin (expired 2024-03-28 12:00:00)
+/// // Removed 1 expired entry from PATH.
+/// ```
+pub fn execute() {
+    let mut path_set = PathSet::from_env();
+    let expired = provenance::expired_entries(path_set.entries());
+    if expired.is_empty() {
+        println!("{}", msg!("expire-none"));
+        return;
+    }
+
+    // Backup current PATH
+    if let Err(e) = path_set.apply_backup() {
+        eprintln!("{}", msg!("error-backup", error = e));
+        return;
+    }
+
+    for (entry, expires) in &expired {
+        println!("{}", msg!("expire-removing", path = entry, date = expires));
+        path_set.remove(entry);
+    }
+    if let Err(e) = path_set.apply() {
+        eprintln!("{}", msg!("error-shell-config", error = e));
+        return;
+    }
+
+    println!("{}", msg!("expire-done", count = expired.len()));
+}
@@ -0,0 +1,654 @@
+//! Command implementation for showing how the startup files build PATH.
+//!
+//! `check` knows what is wrong with PATH, not where each entry comes from.
+//! This module provides functionality to:
+//! - List the startup files the shell reads, in order, following `source`
+//! - Replay their PATH statements to rebuild PATH as a new shell would
+//! - Show for each resulting entry the file and line that introduced it
+//! - Point out entries in the current PATH that no startup file sets
+//!
+//! Conditions aren't evaluated: every PATH statement is replayed as if its
+//! guard held.
+
+use crate::msg;
+use crate::utils::root;
+use crate::utils::shell::quoting;
+use crate::utils::shell::types::ShellType;
+use crate::utils::shell::{continuation, factory, locations};
+use crate::utils::{self, find_path_entry};
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+lazy_static! {
+    static ref POSIX_PATH: Regex = Regex::new(
+        r"(?:^|[;&|({]|\s)(?:(?:export|typeset\s+-x|declare\s+-g?x|readonly)\s+)?(PATH|path)(\+?=)"
+    )
+    .unwrap();
+    static ref POSIX_VARIABLE: Regex =
+        Regex::new(r"^\s*(?:export\s+)?([A-Za-z_][A-Za-z0-9_]*)=").unwrap();
+    static ref SOURCE: Regex =
+        Regex::new(r#"(?:^|[;&|]|\bthen|\bdo)\s*(?:source|\.)\s+("[^"]+"|'[^']+'|[^\s;&|]+)"#)
+            .unwrap();
+    static ref GLOB_LOOP: Regex =
+        Regex::new(r"\bfor\s+([A-Za-z_][A-Za-z0-9_]*)\s+in\s+([^;]*\*[^;]*?)\s*(?:;|$)").unwrap();
+    static ref FISH_SET_PATH: Regex =
+        Regex::new(r"^\s*set\s+((?:-[-\w]+\s+)*)PATH\b(.*)$").unwrap();
+    static ref FISH_ADD_PATH: Regex = Regex::new(r"^\s*fish_add_path\s+(.*)$").unwrap();
+    static ref TCSH_SETENV_PATH: Regex = Regex::new(r"^\s*setenv\s+PATH\s+(.*)$").unwrap();
+    static ref TCSH_SET_PATH: Regex = Regex::new(r"^\s*set\s+path\s*=\s*\((.*)\)").unwrap();
+}
+
+/// How deeply sourced files are followed
+const MAX_DEPTH: usize = 8;
+
+/// Where an entry was introduced
+#[derive(Debug, Clone, PartialEq)]
+pub struct Origin {
+    pub file: PathBuf,
+    /// One-based line number
+    pub line: usize,
+}
+
+/// An entry of the rebuilt PATH and the statement that introduced it
+#[derive(Debug, Clone, PartialEq)]
+pub struct Traced {
+    pub entry: PathBuf,
+    pub origin: Origin,
+}
+
+/// One part of a new PATH value
+#[derive(Debug, Clone, PartialEq)]
+enum Component {
+    /// The PATH so far, as in `$PATH`
+    Existing,
+    /// An entry as written, before expansion
+    Entry(String),
+}
+
+/// Replays startup files to rebuild PATH
+struct Simulation {
+    shell: ShellType,
+    path: Vec<Traced>,
+    /// Variables the files set, used to expand later entries
+    variables: HashMap<String, String>,
+    /// Files read, with how deeply each was sourced
+    files: Vec<(PathBuf, usize)>,
+    /// Files being read, to stop at sourcing loops
+    stack: Vec<PathBuf>,
+}
+
+/// Executes the explain command
+///
+/// # Arguments
+///
+/// * `non_login` - Replay a non-login interactive shell, such as a new
+///   terminal tab, instead of a login shell
+///
+/// # Example
+///
+/// ```
+/// commands::explain::execute(false);
+/// // Output example:
+/// // PATH they build:
+/// //    1  /home/user/bin    /home/user/.bashrc:3
+/// //    2  /usr/local/bin    /etc/environment:1
+/// ```
+pub fn execute(non_login: bool) {
+    let shell = factory::get_shell_handler(crate::config::get_config().shell).get_shell_type();
+    let login = !non_login;
+    let mut simulation = Simulation::new(shell);
+    for file in startup_chain(&shell, login) {
+        simulation.run_file(&file, 0);
+    }
+
+    let kind = if login { "login" } else { "non-login" };
+    println!(
+        "{}",
+        msg!("explain-files", shell = shell.to_string(), kind = kind)
+    );
+    if simulation.files.is_empty() {
+        println!("  ({})", msg!("explain-no-files"));
+    }
+    for (file, depth) in &simulation.files {
+        println!("  {}{}", "  ".repeat(*depth), file.display());
+    }
+
+    println!("{}", msg!("explain-path"));
+    if simulation.path.is_empty() {
+        println!("  ({})", msg!("explain-empty"));
+    }
+    let width = simulation
+        .path
+        .iter()
+        .map(|traced| traced.entry.display().to_string().chars().count())
+        .max()
+        .unwrap_or(0);
+    let built: Vec<PathBuf> = simulation
+        .path
+        .iter()
+        .map(|traced| traced.entry.clone())
+        .collect();
+    for (idx, traced) in simulation.path.iter().enumerate() {
+        let first = find_path_entry(&built[..idx], &traced.entry);
+        println!(
+            "  {:>3}  {:<width$}  {}:{}{}",
+            idx + 1,
+            traced.entry.display().to_string(),
+            traced.origin.file.display(),
+            traced.origin.line,
+            first
+                .map(|first| format!(" ({})", msg!("explain-duplicate", position = first + 1)))
+                .unwrap_or_default()
+        );
+    }
+
+    let unexplained: Vec<PathBuf> = utils::get_path_entries()
+        .into_iter()
+        .filter(|entry| find_path_entry(&built, entry).is_none())
+        .collect();
+    if !unexplained.is_empty() {
+        println!("{}", msg!("explain-unexplained"));
+        for entry in &unexplained {
+            println!("  {}", entry.display());
+        }
+        println!("  {}", msg!("explain-unexplained-hint"));
+    }
+}
+
+/// Returns the startup files a shell reads, in order
+///
+/// `/etc/environment` comes first: on Linux it's read at login, before any
+/// shell starts.
+fn startup_chain(shell: &ShellType, login: bool) -> Vec<PathBuf> {
+    let home = locations::home_dir();
+    let zdotdir = locations::zsh_dotdir();
+    let first_of = |candidates: &[PathBuf]| candidates.iter().find(|file| file.is_file()).cloned();
+    let etc = |names: &[&str]| first_of(&names.iter().map(root::rooted).collect::<Vec<_>>());
+
+    let mut chain = vec![root::rooted("/etc/environment")];
+    match shell {
+        ShellType::Bash if login => {
+            chain.push(root::rooted("/etc/profile"));
+            chain.extend(first_of(&[
+                home.join(".bash_profile"),
+                home.join(".bash_login"),
+                home.join(".profile"),
+            ]));
+        }
+        ShellType::Bash => {
+            chain.push(root::rooted("/etc/bash.bashrc"));
+            chain.push(home.join(".bashrc"));
+        }
+        ShellType::Zsh => {
+            chain.extend(etc(&["/etc/zshenv", "/etc/zsh/zshenv"]));
+            chain.push(zdotdir.join(".zshenv"));
+            if login {
+                chain.extend(etc(&["/etc/zprofile", "/etc/zsh/zprofile"]));
+                chain.push(zdotdir.join(".zprofile"));
+            }
+            chain.extend(etc(&["/etc/zshrc", "/etc/zsh/zshrc"]));
+            chain.push(zdotdir.join(".zshrc"));
+            if login {
+                chain.extend(etc(&["/etc/zlogin", "/etc/zsh/zlogin"]));
+                chain.push(zdotdir.join(".zlogin"));
+            }
+        }
+        ShellType::Ksh => {
+            if login {
+                chain.push(root::rooted("/etc/profile"));
+                chain.push(home.join(".profile"));
+            }
+            chain.push(locations::ksh_env_file().unwrap_or_else(|| home.join(".kshrc")));
+        }
+        ShellType::Tcsh => {
+            chain.push(root::rooted("/etc/csh.cshrc"));
+            if login {
+                chain.push(root::rooted("/etc/csh.login"));
+            }
+            chain.extend(first_of(&[home.join(".tcshrc"), home.join(".cshrc")]));
+            if login {
+                chain.push(home.join(".login"));
+            }
+        }
+        ShellType::Fish => {
+            let user = locations::fish_config_dir();
+            chain.extend(sorted_glob("/etc/fish/conf.d/*.fish"));
+            chain.push(root::rooted("/etc/fish/config.fish"));
+            chain.extend(sorted_glob(&format!("{}/conf.d/*.fish", user.display())));
+            chain.push(user.join("config.fish"));
+        }
+        ShellType::Generic => {
+            chain.push(root::rooted("/etc/profile"));
+            chain.push(home.join(".profile"));
+        }
+    }
+    chain
+}
+
+/// Returns the files matching a glob, sorted as the shell sorts them
+fn sorted_glob(pattern: &str) -> Vec<PathBuf> {
+    let pattern = root::rooted(pattern);
+    let mut files: Vec<PathBuf> = glob::glob(&pattern.to_string_lossy())
+        .map(|paths| paths.flatten().filter(|path| path.is_file()).collect())
+        .unwrap_or_default();
+    files.sort();
+    files
+}
+
+impl Simulation {
+    fn new(shell: ShellType) -> Self {
+        Self {
+            shell,
+            path: Vec::new(),
+            variables: HashMap::new(),
+            files: Vec::new(),
+            stack: Vec::new(),
+        }
+    }
+
+    /// Replays one file, and the files it sources
+    fn run_file(&mut self, file: &Path, depth: usize) {
+        if depth > MAX_DEPTH || self.stack.iter().any(|open| open == file) {
+            return;
+        }
+        let Ok(content) = fs::read_to_string(file) else {
+            return;
+        };
+        self.files.push((file.to_path_buf(), depth));
+        self.stack.push(file.to_path_buf());
+
+        // Loop variables bound to globs, for `for f in /etc/profile.d/*.sh; do . "$f"; done`
+        let mut glob_loops: HashMap<String, String> = HashMap::new();
+        for statement in continuation::logical_lines(&content) {
+            let text = statement.text.trim();
+            if text.is_empty() || text.starts_with('#') {
+                continue;
+            }
+            let line = statement.start + 1;
+            if let Some(captures) = GLOB_LOOP.captures(text) {
+                glob_loops.insert(captures[1].to_string(), self.expand(captures[2].trim()));
+            }
+            for sourced in self.sourced_files(file, text, &glob_loops) {
+                self.run_file(&sourced, depth + 1);
+            }
+            if let Some(components) = self.path_statement(text) {
+                self.set_path(file, line, components);
+            } else if self.is_posix() {
+                self.record_variable(text);
+            }
+        }
+
+        self.stack.pop();
+    }
+
+    /// Returns whether the shell uses POSIX syntax
+    fn is_posix(&self) -> bool {
+        !matches!(self.shell, ShellType::Fish | ShellType::Tcsh)
+    }
+
+    /// Finds the files a statement sources
+    fn sourced_files(
+        &self,
+        file: &Path,
+        text: &str,
+        glob_loops: &HashMap<String, String>,
+    ) -> Vec<PathBuf> {
+        let base = file.parent().unwrap_or_else(|| Path::new("/"));
+        let mut files = Vec::new();
+        for captures in SOURCE.captures_iter(text) {
+            let raw = captures[1].trim_matches(|c| c == '"' || c == '\'');
+            let variable = raw
+                .trim_start_matches('$')
+                .trim_matches(|c| c == '{' || c == '}');
+            if let Some(pattern) = glob_loops.get(variable).filter(|_| raw.starts_with('$')) {
+                files.extend(sorted_glob(pattern));
+                continue;
+            }
+            let path = root::rooted(self.expand(raw));
+            let path = if path.is_relative() {
+                base.join(path)
+            } else {
+                path
+            };
+            if path.is_file() {
+                files.push(path);
+            }
+        }
+        files
+    }
+
+    /// Parses a statement that sets PATH into the parts of the new value
+    fn path_statement(&self, text: &str) -> Option<Vec<Component>> {
+        match self.shell {
+            ShellType::Fish => fish_path_statement(text, &self.current()),
+            ShellType::Tcsh => tcsh_path_statement(text),
+            _ => posix_path_statement(text, self.shell == ShellType::Zsh),
+        }
+    }
+
+    /// Remembers a variable a POSIX statement sets, such as `GOPATH=~/go`
+    fn record_variable(&mut self, text: &str) {
+        let Some(captures) = POSIX_VARIABLE.captures(text) else {
+            return;
+        };
+        let name = captures[1].to_string();
+        let (value, rest) =
+            quoting::split_assignment_value(&text[captures.get(0).unwrap().end()..]);
+        if is_command_prefix(rest) {
+            return;
+        }
+        let value = quoting::split_words(value).concat();
+        let expanded = self.expand(&value);
+        self.variables.insert(name, expanded);
+    }
+
+    /// Applies a new PATH value introduced at `file:line`
+    fn set_path(&mut self, file: &Path, line: usize, components: Vec<Component>) {
+        let mut path = Vec::new();
+        for component in components {
+            match component {
+                Component::Existing => path.extend(self.path.iter().cloned()),
+                Component::Entry(raw) if !raw.is_empty() => path.push(Traced {
+                    entry: PathBuf::from(self.expand(&raw)),
+                    origin: Origin {
+                        file: file.to_path_buf(),
+                        line,
+                    },
+                }),
+                Component::Entry(_) => {}
+            }
+        }
+        self.path = path;
+    }
+
+    /// Returns the PATH built so far
+    fn current(&self) -> Vec<PathBuf> {
+        self.path
+            .iter()
+            .map(|traced| traced.entry.clone())
+            .collect()
+    }
+
+    /// Expands `~` and variables, preferring those set by the files
+    fn expand(&self, raw: &str) -> String {
+        shellexpand::full_with_context_no_errors(
+            raw,
+            || Some(locations::real_home_dir()),
+            |name: &str| {
+                self.variables
+                    .get(name)
+                    .cloned()
+                    .or_else(|| env::var(name).ok())
+            },
+        )
+        .to_string()
+    }
+}
+
+/// Returns whether the text after an assignment makes it a command prefix,
+/// as in `PATH=/x command`, which only sets PATH for that command
+fn is_command_prefix(rest: &str) -> bool {
+    rest.trim_start()
+        .starts_with(|c: char| c.is_alphanumeric() || c == '/' || c == '.')
+}
+
+/// Parses a POSIX `PATH=...`, `PATH+=...` or zsh `path=(...)` statement
+fn posix_path_statement(text: &str, zsh: bool) -> Option<Vec<Component>> {
+    POSIX_PATH.captures_iter(text).find_map(|captures| {
+        let operator = captures.get(2).unwrap();
+        let (value, rest) = quoting::split_assignment_value(&text[operator.end()..]);
+        if is_command_prefix(rest) {
+            return None;
+        }
+        let append = operator.as_str() == "+=";
+        let mut components = if append {
+            vec![Component::Existing]
+        } else {
+            Vec::new()
+        };
+
+        if &captures[1] == "path" {
+            let inner = value.strip_prefix('(')?.strip_suffix(')')?;
+            if !zsh {
+                return None;
+            }
+            components.extend(quoting::split_words(inner).into_iter().map(|word| {
+                if matches!(
+                    word.as_str(),
+                    "$path" | "${path}" | "$path[@]" | "${path[@]}"
+                ) {
+                    Component::Existing
+                } else {
+                    Component::Entry(word)
+                }
+            }));
+        } else {
+            components.extend(colon_components(&quoting::split_words(value).concat()));
+        }
+        Some(components)
+    })
+}
+
+/// Splits a colon-separated PATH value, marking references to the existing PATH
+fn colon_components(value: &str) -> Vec<Component> {
+    value
+        .split(':')
+        .map(|part| match part {
+            "$PATH" | "${PATH}" => Component::Existing,
+            _ => Component::Entry(part.to_string()),
+        })
+        .collect()
+}
+
+/// Parses a fish `set PATH ...` or `fish_add_path ...` statement
+fn fish_path_statement(text: &str, current: &[PathBuf]) -> Option<Vec<Component>> {
+    if let Some(captures) = FISH_SET_PATH.captures(text) {
+        let flags: Vec<&str> = captures[1].split_whitespace().collect();
+        let has_flag = |short: char, long: &str| {
+            flags
+                .iter()
+                .any(|flag| *flag == long || (!flag.starts_with("--") && flag[1..].contains(short)))
+        };
+        if has_flag('q', "--query") {
+            return None;
+        }
+        if has_flag('e', "--erase") {
+            return Some(Vec::new());
+        }
+        let mut components: Vec<Component> = quoting::split_words(&captures[2])
+            .into_iter()
+            .map(|word| match word.as_str() {
+                "$PATH" => Component::Existing,
+                _ => Component::Entry(word),
+            })
+            .collect();
+        if has_flag('a', "--append") {
+            components.insert(0, Component::Existing);
+        } else if has_flag('p', "--prepend") {
+            components.push(Component::Existing);
+        }
+        return Some(components);
+    }
+
+    let captures = FISH_ADD_PATH.captures(text)?;
+    let words = quoting::split_words(&captures[1]);
+    let append = words.iter().any(|word| word == "-a" || word == "--append");
+    // fish_add_path skips directories that are already in PATH
+    let dirs: Vec<Component> = words
+        .into_iter()
+        .filter(|word| !word.starts_with('-'))
+        .filter(|word| !current.contains(&utils::expand_path(word)))
+        .map(Component::Entry)
+        .collect();
+    let mut components = Vec::new();
+    if append {
+        components.push(Component::Existing);
+        components.extend(dirs);
+    } else {
+        components.extend(dirs);
+        components.push(Component::Existing);
+    }
+    Some(components)
+}
+
+/// Parses a tcsh `setenv PATH ...` or `set path = (...)` statement
+fn tcsh_path_statement(text: &str) -> Option<Vec<Component>> {
+    if let Some(captures) = TCSH_SETENV_PATH.captures(text) {
+        return Some(colon_components(
+            &quoting::split_words(&captures[1]).concat(),
+        ));
+    }
+    let captures = TCSH_SET_PATH.captures(text)?;
+    Some(
+        quoting::split_words(&captures[1])
+            .into_iter()
+            .map(|word| match word.as_str() {
+                "$path" | "${path}" => Component::Existing,
+                _ => Component::Entry(word),
+            })
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn entries(simulation: &Simulation) -> Vec<(String, usize)> {
+        simulation
+            .path
+            .iter()
+            .map(|traced| (traced.entry.display().to_string(), traced.origin.line))
+            .collect()
+    }
+
+    #[test]
+    fn test_posix_path_statement() {
+        assert_eq!(
+            posix_path_statement("export PATH=\"$HOME/bin:$PATH\"", false),
+            Some(vec![
+                Component::Entry("$HOME/bin".to_string()),
+                Component::Existing
+            ])
+        );
+        assert_eq!(
+            posix_path_statement("PATH+=:/opt/bin", false),
+            Some(vec![
+                Component::Existing,
+                Component::Entry(String::new()),
+                Component::Entry("/opt/bin".to_string())
+            ])
+        );
+        assert_eq!(
+            posix_path_statement("path=(~/bin $path)", true),
+            Some(vec![
+                Component::Entry("~/bin".to_string()),
+                Component::Existing
+            ])
+        );
+        assert_eq!(posix_path_statement("PATH=/opt/bin make", false), None);
+        assert_eq!(posix_path_statement("echo $PATH", false), None);
+    }
+
+    #[test]
+    fn test_fish_and_tcsh_statements() {
+        let current = vec![PathBuf::from("/usr/bin")];
+        assert_eq!(
+            fish_path_statement("fish_add_path -a /opt/bin /usr/bin", &current),
+            Some(vec![
+                Component::Existing,
+                Component::Entry("/opt/bin".to_string())
+            ])
+        );
+        assert_eq!(
+            fish_path_statement("set -gx PATH /opt/bin $PATH", &current),
+            Some(vec![
+                Component::Entry("/opt/bin".to_string()),
+                Component::Existing
+            ])
+        );
+        assert_eq!(
+            fish_path_statement("set -gxa PATH /opt/bin", &current),
+            Some(vec![
+                Component::Existing,
+                Component::Entry("/opt/bin".to_string())
+            ])
+        );
+        assert_eq!(fish_path_statement("set -e PATH", &current), Some(vec![]));
+        assert_eq!(fish_path_statement("set -q PATH", &current), None);
+        assert_eq!(
+            tcsh_path_statement("set path = ( $path /opt/bin )"),
+            Some(vec![
+                Component::Existing,
+                Component::Entry("/opt/bin".to_string())
+            ])
+        );
+        assert_eq!(
+            tcsh_path_statement("setenv PATH /opt/bin:${PATH}"),
+            Some(vec![
+                Component::Entry("/opt/bin".to_string()),
+                Component::Existing
+            ])
+        );
+    }
+
+    #[test]
+    fn test_run_file_traces_entries() {
+        let temp_dir = TempDir::new().unwrap();
+        let profile = temp_dir.path().join(".profile");
+        let extra = temp_dir.path().join("path.sh");
+        fs::write(
+            &profile,
+            "PATH=/usr/bin:/bin\n\
+             TOOLS=/pm-test/tools\n\
+             . ./path.sh\n\
+             export PATH=\"$TOOLS/bin:$PATH\"\n",
+        )
+        .unwrap();
+        fs::write(&extra, "PATH=\"$PATH:/pm-test/extra\"\n").unwrap();
+
+        let mut simulation = Simulation::new(ShellType::Bash);
+        simulation.run_file(&profile, 0);
+        assert_eq!(
+            entries(&simulation),
+            vec![
+                ("/pm-test/tools/bin".to_string(), 4),
+                ("/usr/bin".to_string(), 1),
+                ("/bin".to_string(), 1),
+                ("/pm-test/extra".to_string(), 1),
+            ]
+        );
+        assert_eq!(simulation.path[3].origin.file, extra);
+        assert_eq!(simulation.files, vec![(profile, 0), (extra, 1)]);
+    }
+
+    #[test]
+    fn test_glob_loop_sources_each_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let profile_d = temp_dir.path().join("profile.d");
+        fs::create_dir(&profile_d).unwrap();
+        fs::write(profile_d.join("b.sh"), "PATH=\"$PATH:/pm-test/b\"\n").unwrap();
+        fs::write(profile_d.join("a.sh"), "PATH=\"$PATH:/pm-test/a\"\n").unwrap();
+        let profile = temp_dir.path().join("profile");
+        fs::write(
+            &profile,
+            format!(
+                "for i in {}/*.sh; do\n  if [ -r \"$i\" ]; then\n    . \"$i\"\n  fi\ndone\n",
+                profile_d.display()
+            ),
+        )
+        .unwrap();
+
+        let mut simulation = Simulation::new(ShellType::Generic);
+        simulation.run_file(&profile, 0);
+        assert_eq!(
+            simulation.current(),
+            vec![PathBuf::from("/pm-test/a"), PathBuf::from("/pm-test/b")]
+        );
+    }
+}
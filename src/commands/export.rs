@@ -0,0 +1,172 @@
+//! Command implementation for exporting PATH to other environments.
+//!
+//! This module provides functionality to:
+//! - Print the current PATH as a statement for the user's shell
+//! - Print a Dockerfile `ENV PATH=...` instruction
+//! - Print GitHub Actions steps that add each entry through `$GITHUB_PATH`
+
+use crate::config;
+use crate::msg;
+use crate::utils;
+use crate::utils::shell::emit;
+use crate::utils::shell::quoting::posix_single_quoted;
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// Output format for the export command
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ExportFormat {
+    /// A PATH assignment for the configured or detected shell
+    #[default]
+    Shell,
+    /// A Dockerfile `ENV` instruction
+    Dockerfile,
+    /// Shell lines appending each entry to `$GITHUB_PATH`
+    GithubActions,
+}
+
+impl fmt::Display for ExportFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExportFormat::Shell => write!(f, "shell"),
+            ExportFormat::Dockerfile => write!(f, "dockerfile"),
+            ExportFormat::GithubActions => write!(f, "github-actions"),
+        }
+    }
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "shell" => Ok(ExportFormat::Shell),
+            "dockerfile" | "docker" => Ok(ExportFormat::Dockerfile),
+            "github-actions" | "github" => Ok(ExportFormat::GithubActions),
+            _ => Err(format!("Invalid export format: {}", s)),
+        }
+    }
+}
+
+/// Executes the export command to print PATH in the given format
+///
+/// A Dockerfile can't hold entries that aren't valid UTF-8, so they are
+/// left out of it with a warning. The shell formats write them as `printf`
+/// substitutions.
+///
+/// # Arguments
+///
+/// * `format` - Output format
+///
+/// # Example
+///
+/// ```
+/// commands::export::execute(ExportFormat::Dockerfile);
+/// // Output example:
+/// // ENV PATH="/usr/local/bin:/usr/bin:/bin"
+/// ```
+pub fn execute(format: ExportFormat) {
+    let entries = utils::get_path_entries();
+
+    let output = match format {
+        ExportFormat::Shell => emit::export_statement(config::get_config().shell, &entries),
+        ExportFormat::Dockerfile => dockerfile_env(&utf8_entries(&entries)),
+        ExportFormat::GithubActions => github_path_lines(&entries),
+    };
+    println!("{}", output);
+}
+
+/// Returns the entries that are valid UTF-8, warning about the others
+fn utf8_entries(entries: &[PathBuf]) -> Vec<PathBuf> {
+    entries
+        .iter()
+        .filter(|entry| {
+            let valid = entry.to_str().is_some();
+            if !valid {
+                eprintln!("{}", msg!("export-not-utf8", path = entry));
+            }
+            valid
+        })
+        .cloned()
+        .collect()
+}
+
+/// Formats a Dockerfile `ENV` instruction setting PATH to `entries`
+fn dockerfile_env(entries: &[PathBuf]) -> String {
+    let value = entries
+        .iter()
+        .map(|entry| {
+            // Docker unescapes only `\`, `"` and `$` inside double quotes
+            let mut escaped = String::new();
+            for c in entry.to_string_lossy().chars() {
+                if matches!(c, '\\' | '"' | '$') {
+                    escaped.push('\\');
+                }
+                escaped.push(c);
+            }
+            escaped
+        })
+        .collect::<Vec<_>>()
+        .join(":");
+    format!("ENV PATH=\"{}\"", value)
+}
+
+/// Formats shell lines adding `entries` to `$GITHUB_PATH`.
+///
+/// The runner prepends each line to PATH, so later lines take precedence;
+/// entries are written in reverse to keep their order.
+fn github_path_lines(entries: &[PathBuf]) -> String {
+    entries
+        .iter()
+        .rev()
+        .map(|entry| {
+            format!(
+                "echo {} >> \"$GITHUB_PATH\"",
+                posix_single_quoted(entry.as_os_str())
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entries() -> Vec<PathBuf> {
+        vec![
+            PathBuf::from("/opt/it's here/bin"),
+            PathBuf::from("/opt/$weird\"dir"),
+            PathBuf::from("/usr/bin"),
+        ]
+    }
+
+    #[test]
+    fn test_dockerfile_env() {
+        assert_eq!(
+            dockerfile_env(&entries()),
+            r#"ENV PATH="/opt/it's here/bin:/opt/\$weird\"dir:/usr/bin""#
+        );
+    }
+
+    #[test]
+    fn test_github_path_lines() {
+        assert_eq!(
+            github_path_lines(&entries()),
+            "echo '/usr/bin' >> \"$GITHUB_PATH\"\n\
+             echo '/opt/$weird\"dir' >> \"$GITHUB_PATH\"\n\
+             echo '/opt/it'\\''s here/bin' >> \"$GITHUB_PATH\""
+        );
+    }
+
+    #[test]
+    fn test_format_parsing() {
+        assert_eq!(
+            "github-actions".parse::<ExportFormat>(),
+            Ok(ExportFormat::GithubActions)
+        );
+        assert_eq!(ExportFormat::Dockerfile.to_string(), "dockerfile");
+        assert!("yaml".parse::<ExportFormat>().is_err());
+    }
+}
@@ -2,62 +2,211 @@
 //!
 //! This module provides functionality to:
 //! - Identify and remove invalid PATH entries
+//! - Optionally remove or absolutize relative and empty entries
+//! - Optionally remove the Windows entries WSL appends
+//! - Keep protected system directories unless explicitly allowed
+//! - Keep entries on removable media or automounts that aren't mounted right now
+//! - Leave directories listed in `keep-dirs` exactly as written
 //! - Update shell configuration files
 //! - Maintain backups of configurations
 //! - Provide detailed feedback about changes
 
 use crate::backup;
 use crate::commands::validator::is_valid_path_entry;
-use crate::utils;
+use crate::msg;
+use crate::utils::{self, mounts, protected, wsl, PathSet};
+use std::env;
+use std::fmt;
 use std::path::PathBuf;
+use std::str::FromStr;
+
+/// What flush does with relative and empty PATH entries
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RelativeAction {
+    /// Drop relative and empty entries
+    Remove,
+    /// Resolve relative and empty entries against the current directory
+    Absolutize,
+}
+
+impl fmt::Display for RelativeAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RelativeAction::Remove => write!(f, "remove"),
+            RelativeAction::Absolutize => write!(f, "absolutize"),
+        }
+    }
+}
+
+impl FromStr for RelativeAction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "remove" => Ok(RelativeAction::Remove),
+            "absolutize" => Ok(RelativeAction::Absolutize),
+            _ => Err(format!("Invalid relative action: {}", s)),
+        }
+    }
+}
+
+/// Options controlling what flush removes
+#[derive(Debug, Default, Clone)]
+pub struct FlushOptions {
+    /// Optionally remove or absolutize relative and empty entries
+    pub relative: Option<RelativeAction>,
+    /// Also remove protected system directories that don't exist
+    pub allow_system: bool,
+    /// Also remove Windows entries appended by WSL
+    pub strip_windows: bool,
+    /// Also remove missing entries on removable media or automounts that aren't mounted
+    pub remove_unmounted: bool,
+}
 
 /// Removes invalid directories from the PATH environment variable.
-pub fn execute() {
+///
+/// Empty entries are always removed. Relative entries are kept when they
+/// currently resolve to a directory, unless `relative` says otherwise.
+/// Missing entries that may only be unmounted for now are kept unless
+/// `remove_unmounted` is set.
+///
+/// # Arguments
+///
+/// * `options` - What to remove besides directories that don't exist
+pub fn execute(options: &FlushOptions) {
+    let relative = options.relative;
     // Backup current PATH
     if let Err(e) = backup::create_backup() {
-        eprintln!("Error creating backup: {}", e);
+        eprintln!("{}", msg!("error-backup", error = e));
         return;
     }
 
     // Get current PATH entries
     let current_entries = utils::get_path_entries();
     let original_count = current_entries.len();
+    let current_dir = env::current_dir().ok();
+    let protected = if options.allow_system {
+        Vec::new()
+    } else {
+        protected::protected_dirs()
+    };
+    let windows = if options.strip_windows {
+        windows_to_strip(&current_entries)
+    } else {
+        Vec::new()
+    };
+    let keep = protected::keep_dirs();
+    let mounts = mounts::read_mounts();
 
-    // Filter out non-existing paths
-    let valid_entries: Vec<PathBuf> = current_entries
-        .into_iter()
-        .filter(|path| {
-            if is_valid_path_entry(path) {
-                true
+    // Filter out non-existing paths, handling relative entries as requested
+    let mut valid_entries: Vec<PathBuf> = Vec::new();
+    let mut absolutized_count = 0;
+    for path in current_entries {
+        let is_relative = path.as_os_str().is_empty() || path.is_relative();
+        if protected::is_kept(&path, &keep) {
+            let reason = if windows.contains(&path) {
+                Some(msg!("flush-reason-windows"))
+            } else if is_relative && relative.is_some() {
+                Some(msg!("flush-reason-relative"))
+            } else if !is_valid_path_entry(&path) {
+                Some(msg!("flush-reason-unavailable"))
             } else {
-                println!("Removing invalid path: {}", path.display());
-                false
+                None
+            };
+            if let Some(reason) = reason {
+                println!(
+                    "{}",
+                    msg!("flush-keeping-excluded", path = path, reason = reason)
+                );
             }
-        })
-        .collect();
+            valid_entries.push(path);
+            continue;
+        }
+        if windows.contains(&path) {
+            println!("{}", msg!("flush-removing-windows", path = path));
+            continue;
+        }
+
+        let mut absolutized = false;
+        let path = match (is_relative, relative, &current_dir) {
+            (true, Some(RelativeAction::Remove), _) => {
+                println!("{}", msg!("flush-removing-relative", path = path));
+                continue;
+            }
+            (true, Some(RelativeAction::Absolutize), Some(dir)) => {
+                // Collecting components drops `.` segments and trailing slashes
+                let absolute: PathBuf = dir.join(&path).components().collect();
+                println!(
+                    "{}",
+                    msg!("flush-absolutizing", path = path, absolute = absolute)
+                );
+                absolutized = true;
+                absolute
+            }
+            _ => path,
+        };
+
+        if path.as_os_str().is_empty() {
+            println!("{}", msg!("flush-removing-empty"));
+        } else if is_valid_path_entry(&path) {
+            if absolutized {
+                absolutized_count += 1;
+            }
+            valid_entries.push(path);
+        } else if protected::is_protected(&path, &protected) {
+            println!("{}", msg!("flush-keeping-protected", path = path));
+            valid_entries.push(path);
+        } else if let Some(reason) = (!options.remove_unmounted)
+            .then(|| mounts::unmounted_reason(&mounts, &path))
+            .flatten()
+        {
+            println!(
+                "{}",
+                msg!(
+                    "flush-keeping-unmounted",
+                    path = path,
+                    reason = reason.to_string()
+                )
+            );
+            valid_entries.push(path);
+        } else {
+            println!("{}", msg!("flush-removing-invalid", path = path));
+        }
+    }
 
     let removed_count = original_count - valid_entries.len();
 
-    if removed_count == 0 {
-        println!("No invalid paths found in PATH.");
+    if removed_count == 0 && absolutized_count == 0 {
+        println!("{}", msg!("flush-nothing-found"));
         return;
     }
 
+    let path_set = PathSet::new(valid_entries);
+
     // Update PATH environment variable
-    utils::set_path_entries(&valid_entries);
+    path_set.apply_env();
 
     // Update shell configuration files
-    match utils::update_shell_config(&valid_entries) {
+    match path_set.apply_shell_config() {
         Ok(_) => {
-            println!(
-                "Successfully removed {} invalid path(s) and updated shell configuration.",
-                removed_count
-            );
+            println!("{}", msg!("flush-done", count = removed_count));
+            if absolutized_count > 0 {
+                println!("{}", msg!("flush-absolutized", count = absolutized_count));
+            }
         }
         Err(e) => {
-            eprintln!("Error updating shell configuration: {}", e);
-            println!("Warning: PATH environment variable was updated for current session only.");
-            println!("To make changes permanent, you'll need to manually update your shell configuration.");
+            eprintln!("{}", msg!("error-shell-config", error = e));
+            println!("{}", msg!("flush-session-only"));
+            println!("{}", msg!("flush-manual-update"));
         }
     }
 }
+
+/// Returns the Windows entries to strip, noting when not running under WSL
+pub fn windows_to_strip(entries: &[PathBuf]) -> Vec<PathBuf> {
+    if !wsl::is_wsl() {
+        println!("{}", msg!("flush-not-wsl"));
+        return Vec::new();
+    }
+    wsl::windows_entries(entries)
+}
@@ -0,0 +1,161 @@
+//! Command implementation for searching PATH entries.
+//!
+//! This module provides functionality to:
+//! - List entries matching a regular expression with their position and status
+//! - Delete the matching entries (`--delete`)
+//! - Move the matching entries to the front of PATH (`--to-front`)
+
+use crate::commands::delete::{self, DeleteOptions};
+use crate::commands::list::entry_status;
+use crate::msg;
+use crate::utils::{self, PathSet};
+use regex::Regex;
+use std::path::PathBuf;
+
+/// What to do with the matching entries besides listing them
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GrepAction {
+    /// Remove them from PATH
+    Delete,
+    /// Move them to the front of PATH, keeping their order
+    ToFront,
+}
+
+/// Options controlling how entries are matched and acted on
+#[derive(Debug, Default, Clone)]
+pub struct GrepOptions {
+    /// Match without regard to case
+    pub ignore_case: bool,
+    /// Act on the matches instead of only listing them
+    pub action: Option<GrepAction>,
+    /// Allow deleting protected system directories
+    pub allow_system: bool,
+}
+
+/// Executes the grep command
+///
+/// # Arguments
+///
+/// * `pattern` - Regular expression matched anywhere in each entry
+/// * `options` - Options controlling matching and what to do with matches
+///
+/// # Returns
+///
+/// `true` if any entry matched
+///
+/// # Example
+///
+/// ```
+/// commands::grep::execute("cuda", &GrepOptions::default());
+/// // Output example:
+/// // PATH entries matching 'cuda':
+/// //     4: /usr/local/cuda-12/bin  valid
+/// ```
+pub fn execute(pattern: &str, options: &GrepOptions) -> bool {
+    let expression = if options.ignore_case {
+        format!("(?i){}", pattern)
+    } else {
+        pattern.to_string()
+    };
+    let regex = match Regex::new(&expression) {
+        Ok(regex) => regex,
+        Err(e) => {
+            eprintln!(
+                "{}",
+                msg!("grep-invalid", pattern = pattern, error = e.to_string())
+            );
+            return false;
+        }
+    };
+
+    let path_entries = utils::get_path_entries();
+    let matches = matching_positions(&path_entries, &regex);
+    if matches.is_empty() {
+        println!("{}", msg!("grep-none", pattern = pattern));
+        return false;
+    }
+
+    if options.action == Some(GrepAction::Delete) {
+        // Delete lists the matches itself, asks before removing several,
+        // and refuses protected directories
+        return delete::execute(
+            &[],
+            &DeleteOptions {
+                regex: Some(expression),
+                allow_system: options.allow_system,
+                ..Default::default()
+            },
+        );
+    }
+
+    println!("{}", msg!("grep-header", pattern = pattern));
+    for &index in &matches {
+        println!(
+            "  {:>3}: {}  {}",
+            index + 1,
+            path_entries[index].display(),
+            entry_status(&path_entries[index])
+        );
+    }
+
+    if options.action == Some(GrepAction::ToFront) {
+        move_to_front(path_entries, &matches);
+    }
+    true
+}
+
+/// Returns the zero-based positions of the entries matching `regex`
+fn matching_positions(entries: &[PathBuf], regex: &Regex) -> Vec<usize> {
+    entries
+        .iter()
+        .enumerate()
+        .filter(|(_, entry)| regex.is_match(&entry.to_string_lossy()))
+        .map(|(index, _)| index)
+        .collect()
+}
+
+/// Moves the entries at `positions` (ascending) to the front, keeping their order
+fn move_to_front(entries: Vec<PathBuf>, positions: &[usize]) {
+    if positions.iter().enumerate().all(|(n, &index)| n == index) {
+        println!("{}", msg!("grep-already-front"));
+        return;
+    }
+
+    let mut path_set = PathSet::new(entries);
+
+    // Backup current PATH
+    if let Err(e) = path_set.apply_backup() {
+        eprintln!("{}", msg!("error-backup", error = e));
+        return;
+    }
+
+    // Each match moves past only entries before it, so later positions stay valid
+    for (n, &index) in positions.iter().enumerate() {
+        path_set.reorder(index, n);
+    }
+    if let Err(e) = path_set.apply() {
+        eprintln!("{}", msg!("error-shell-config", error = e));
+        return;
+    }
+
+    println!("{}", msg!("grep-moved", count = positions.len()));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matching_positions() {
+        let entries: Vec<PathBuf> = ["/usr/bin", "/usr/local/cuda-12/bin", "/opt/CUDA/bin"]
+            .iter()
+            .map(PathBuf::from)
+            .collect();
+
+        let regex = Regex::new("cuda").unwrap();
+        assert_eq!(matching_positions(&entries, &regex), vec![1]);
+
+        let regex = Regex::new("(?i)cuda").unwrap();
+        assert_eq!(matching_positions(&entries, &regex), vec![1, 2]);
+    }
+}
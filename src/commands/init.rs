@@ -0,0 +1,78 @@
+//! Command implementation for printing shell startup snippets.
+//!
+//! `pathmaster init --daily-check` prints lines to add to the shell config
+//! that run `pathmaster check --quiet --daily` when an interactive shell
+//! starts. This module provides functionality to:
+//! - Print the snippet for the current shell
+//! - Skip the check in non-interactive shells, so scripts and `scp` stay quiet
+//! - Skip it when pathmaster isn't installed
+
+use crate::config;
+use crate::utils::shell::factory;
+use crate::utils::shell::types::ShellType;
+
+/// The command the snippet runs
+const DAILY_CHECK: &str = "pathmaster check --quiet --daily";
+
+/// Executes the init command to print the startup snippet
+///
+/// # Example
+///
+/// ```
+/// commands::init::execute();
+/// // Output example:
+/// // # pathmaster: check PATH once a day
+/// // case $- in *i*) command -v pathmaster >/dev/null 2>&1 && pathmaster check --quiet --daily ;; esac
+/// ```
+pub fn execute() {
+    let shell = config::get_config()
+        .shell
+        .unwrap_or_else(|| factory::detect_shell().0);
+    print!("{}", snippet(shell));
+}
+
+/// Returns the snippet that runs the daily check at startup of an
+/// interactive `shell`
+fn snippet(shell: ShellType) -> String {
+    let check = match shell {
+        ShellType::Fish => format!(
+            "status is-interactive; and command -q pathmaster; and {}",
+            DAILY_CHECK
+        ),
+        ShellType::Tcsh => format!("if ($?prompt && -X pathmaster) {}", DAILY_CHECK),
+        ShellType::Zsh | ShellType::Bash | ShellType::Ksh | ShellType::Generic => format!(
+            "case $- in *i*) command -v pathmaster >/dev/null 2>&1 && {} ;; esac",
+            DAILY_CHECK
+        ),
+    };
+    format!("# pathmaster: check PATH once a day\n{}\n", check)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snippet() {
+        let posix = "# pathmaster: check PATH once a day\n\
+            case $- in *i*) command -v pathmaster >/dev/null 2>&1 && pathmaster check --quiet --daily ;; esac\n";
+        for shell in [
+            ShellType::Bash,
+            ShellType::Zsh,
+            ShellType::Ksh,
+            ShellType::Generic,
+        ] {
+            assert_eq!(snippet(shell), posix, "{}", shell);
+        }
+        assert_eq!(
+            snippet(ShellType::Fish),
+            "# pathmaster: check PATH once a day\n\
+             status is-interactive; and command -q pathmaster; and pathmaster check --quiet --daily\n"
+        );
+        assert_eq!(
+            snippet(ShellType::Tcsh),
+            "# pathmaster: check PATH once a day\n\
+             if ($?prompt && -X pathmaster) pathmaster check --quiet --daily\n"
+        );
+    }
+}
@@ -0,0 +1,436 @@
+//! Command implementation for linting PATH setup in shell configs.
+//!
+//! Some mistakes in rc files aren't about the entries themselves, so
+//! cleaning PATH can't fix them. This module provides functionality to:
+//! - Find PATH set in `.zshenv` and overwritten again in `.zprofile` or `.zshrc`
+//! - Find files that assign PATH without exporting it
+//! - Find PATH additions inside loops that don't check for duplicates
+//! - Find unquoted `$PATH` expansions, which break on entries with spaces
+//! - Report each finding with its file and line, and how to fix it
+//!
+//! pathmaster's own managed block is skipped.
+
+use crate::msg;
+use crate::utils::shell::block;
+use crate::utils::shell::continuation::{self, LogicalLine};
+use crate::utils::shell::handlers::zsh;
+use crate::utils::shell::locations;
+use crate::utils::shell::quoting;
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+lazy_static! {
+    static ref ASSIGNMENT: Regex = Regex::new(
+        r"(?:^|[;&|({]|\s)(?:(export|typeset\s+-x|declare\s+-g?x)\s+)?(PATH|path)(\+?=)"
+    )
+    .unwrap();
+    static ref EXPORT: Regex = Regex::new(
+        r"(?:^|[;&|\s])(?:export|typeset\s+-x|declare\s+-g?x)\s+(?:[A-Za-z_]\w*\s+)*PATH\b"
+    )
+    .unwrap();
+}
+
+/// Words starting and ending loops in POSIX shells
+const LOOP_OPENERS: &[&str] = &["for", "while", "until", "select"];
+const LOOP_CLOSER: &str = "done";
+
+/// A PATH mistake in a shell config
+#[derive(Debug, Clone, PartialEq)]
+pub enum Problem {
+    /// PATH set on this line of `.zshenv` is overwritten here
+    ClobbersZshenv(usize),
+    /// PATH is assigned but the file never exports it
+    MissingExport,
+    /// PATH is extended inside a loop without checking for duplicates
+    AdditionInLoop,
+    /// `$PATH` is expanded without quotes
+    UnquotedExpansion,
+}
+
+impl Problem {
+    /// Describes the mistake
+    fn description(&self, zshenv: &Path) -> String {
+        match self {
+            Problem::ClobbersZshenv(line) => {
+                msg!("lint-clobbers-zshenv", file = zshenv, line = *line)
+            }
+            Problem::MissingExport => msg!("lint-missing-export"),
+            Problem::AdditionInLoop => msg!("lint-addition-in-loop"),
+            Problem::UnquotedExpansion => msg!("lint-unquoted-expansion"),
+        }
+    }
+
+    /// Suggests how to fix the mistake
+    fn suggestion(&self) -> String {
+        match self {
+            Problem::ClobbersZshenv(_) => msg!("lint-clobbers-zshenv-fix"),
+            Problem::MissingExport => msg!("lint-missing-export-fix"),
+            Problem::AdditionInLoop => msg!("lint-addition-in-loop-fix"),
+            Problem::UnquotedExpansion => msg!("lint-unquoted-expansion-fix"),
+        }
+    }
+}
+
+/// A problem found on one line of a shell config
+#[derive(Debug, Clone, PartialEq)]
+pub struct Finding {
+    /// The shell config
+    pub file: PathBuf,
+    /// One-based line number
+    pub line: usize,
+    pub problem: Problem,
+}
+
+/// A PATH assignment in a shell config
+#[derive(Debug, Clone, PartialEq)]
+struct Assignment {
+    /// Zero-based index of the first physical line of the statement
+    line: usize,
+    /// Whether the statement exports PATH as well
+    exported: bool,
+    /// Whether the new value keeps the existing PATH
+    extends: bool,
+    /// Whether `$PATH` is expanded outside quotes
+    unquoted: bool,
+}
+
+/// Executes the lint command
+///
+/// # Returns
+/// * `true` if no problems were found
+///
+/// # Example
+///
+/// ```
+/// commands::lint::execute();
+/// // Output example:
+/// // /home/user/.bashrc:12: $PATH is expanded without quotes
+/// //   Quote the value, e.g. PATH="$PATH:/new/dir", so entries with spaces survive.
+/// ```
+pub fn execute() -> bool {
+    let configs: Vec<(PathBuf, String)> = config_files()
+        .into_iter()
+        .filter_map(|file| {
+            let content = fs::read_to_string(&file).ok()?;
+            Some((file, content))
+        })
+        .collect();
+    let zshenv = locations::zsh_dotdir().join(".zshenv");
+    let findings = lint(&configs, &zshenv);
+
+    for finding in &findings {
+        println!(
+            "{}:{}: {}",
+            finding.file.display(),
+            finding.line,
+            finding.problem.description(&zshenv)
+        );
+        println!("  {}", finding.problem.suggestion());
+    }
+
+    if findings.is_empty() {
+        println!("{}", msg!("lint-clean", count = configs.len()));
+    } else {
+        let mut files: Vec<&PathBuf> = findings.iter().map(|finding| &finding.file).collect();
+        files.dedup();
+        println!(
+            "{}",
+            msg!("lint-found", count = findings.len(), files = files.len())
+        );
+    }
+    findings.is_empty()
+}
+
+/// Returns the POSIX-style shell configs to lint, in the order shells read them
+fn config_files() -> Vec<PathBuf> {
+    let home = locations::home_dir();
+    let zdotdir = locations::zsh_dotdir();
+    let mut files = vec![
+        home.join(".profile"),
+        home.join(".bash_profile"),
+        home.join(".bash_login"),
+        home.join(".bashrc"),
+        zdotdir.join(".zshenv"),
+        zdotdir.join(".zprofile"),
+        zdotdir.join(".zshrc"),
+    ];
+    files.extend(locations::ksh_env_file().filter(|file| !files.contains(file)));
+    files
+}
+
+/// Finds the problems in a set of shell configs
+///
+/// # Arguments
+///
+/// * `configs` - Each config file with its content
+/// * `zshenv` - Path of `.zshenv`, whose PATH `.zprofile` and `.zshrc` shouldn't overwrite
+pub fn lint(configs: &[(PathBuf, String)], zshenv: &Path) -> Vec<Finding> {
+    let zshenv_line = configs
+        .iter()
+        .find(|(file, _)| file == zshenv)
+        .and_then(|(_, content)| assignments(content).first().map(|a| a.line + 1));
+
+    let mut findings = Vec::new();
+    for (file, content) in configs {
+        let zsh_startup = file.parent() == zshenv.parent()
+            && matches!(
+                file.file_name().and_then(|name| name.to_str()),
+                Some(".zprofile" | ".zshrc")
+            );
+        let clobbered = zshenv_line.filter(|_| zsh_startup);
+        findings.extend(
+            lint_file(content, clobbered)
+                .into_iter()
+                .map(|(line, problem)| Finding {
+                    file: file.clone(),
+                    line,
+                    problem,
+                }),
+        );
+    }
+    findings
+}
+
+/// Finds the problems in one config
+///
+/// # Arguments
+///
+/// * `content` - The config's content
+/// * `zshenv_line` - Line of `.zshenv` that sets PATH, if this file runs after it
+///
+/// # Returns
+/// * One-based line numbers with the problem found there
+fn lint_file(content: &str, zshenv_line: Option<usize>) -> Vec<(usize, Problem)> {
+    let lines = statements(content);
+    let found = assignments(content);
+    let exported = lines.iter().any(|line| EXPORT.is_match(&line.text));
+    let unique = lines
+        .iter()
+        .any(|line| zsh::declares_unique_path(&line.text));
+    let loops = loop_spans(&lines);
+
+    let mut problems = Vec::new();
+    if let Some(line) = zshenv_line {
+        problems.extend(
+            found
+                .iter()
+                .filter(|assignment| !assignment.extends)
+                .map(|assignment| (assignment.line + 1, Problem::ClobbersZshenv(line))),
+        );
+    }
+    if !exported {
+        problems.extend(
+            found
+                .iter()
+                .find(|assignment| !assignment.exported)
+                .map(|assignment| (assignment.line + 1, Problem::MissingExport)),
+        );
+    }
+    if !unique {
+        for assignment in found.iter().filter(|assignment| assignment.extends) {
+            // The innermost loop around the addition decides whether it's guarded
+            let innermost = loops
+                .iter()
+                .filter(|(start, end)| (*start..=*end).contains(&assignment.line))
+                .max_by_key(|(start, _)| *start);
+            if let Some((start, end)) = innermost {
+                let guarded = lines
+                    .iter()
+                    .filter(|line| line.start >= *start && line.end <= *end)
+                    .any(|line| is_duplicate_check(&line.text));
+                if !guarded {
+                    problems.push((assignment.line + 1, Problem::AdditionInLoop));
+                }
+            }
+        }
+    }
+    problems.extend(
+        found
+            .iter()
+            .filter(|assignment| assignment.unquoted)
+            .map(|assignment| (assignment.line + 1, Problem::UnquotedExpansion)),
+    );
+
+    problems.sort_by_key(|(line, _)| *line);
+    problems
+}
+
+/// Splits a config into statements, leaving out comments and the managed block
+fn statements(content: &str) -> Vec<LogicalLine> {
+    let managed = block::find_block(content);
+    continuation::logical_lines(content)
+        .into_iter()
+        .filter(|line| !line.text.trim_start().starts_with('#'))
+        .filter(|line| !managed.is_some_and(|(start, end)| line.start >= start && line.end <= end))
+        .collect()
+}
+
+/// Finds the PATH assignments in a config
+fn assignments(content: &str) -> Vec<Assignment> {
+    let mut found = Vec::new();
+    for line in statements(content) {
+        for captures in ASSIGNMENT.captures_iter(&line.text) {
+            let operator = captures.get(3).unwrap();
+            let (value, rest) = quoting::split_assignment_value(&line.text[operator.end()..]);
+            // `PATH=/x command` only sets PATH for that command
+            if rest
+                .trim_start()
+                .starts_with(|c: char| c.is_alphanumeric() || c == '/' || c == '.')
+            {
+                continue;
+            }
+            let array = &captures[2] == "path";
+            if array && !value.starts_with('(') {
+                continue;
+            }
+            found.push(Assignment {
+                line: line.start,
+                // zsh ties `path` to PATH, which is already exported
+                exported: array || captures.get(1).is_some(),
+                extends: operator.as_str() == "+="
+                    || ["$PATH", "${PATH", "$path", "${path"]
+                        .iter()
+                        .any(|reference| value.contains(reference)),
+                unquoted: !array && has_unquoted_path(value),
+            });
+        }
+    }
+    found
+}
+
+/// Returns whether `$PATH` or `${PATH}` is expanded outside quotes
+fn has_unquoted_path(value: &str) -> bool {
+    let mut in_single = false;
+    let mut in_double = false;
+    let mut escaped = false;
+    for (idx, c) in value.char_indices() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        match c {
+            '\\' if !in_single => escaped = true,
+            '\'' if !in_double => in_single = !in_single,
+            '"' if !in_single => in_double = !in_double,
+            '$' if !in_single && !in_double => {
+                let rest = &value[idx + 1..];
+                let bare = rest.starts_with("PATH")
+                    && !rest[4..].starts_with(|c: char| c.is_alphanumeric() || c == '_');
+                if bare || rest.starts_with("{PATH}") {
+                    return true;
+                }
+            }
+            _ => {}
+        }
+    }
+    false
+}
+
+/// Returns whether a statement checks PATH for an entry, as in `case ":$PATH:"`
+fn is_duplicate_check(text: &str) -> bool {
+    text.contains(":$PATH:") || text.contains(":${PATH}:")
+}
+
+/// Finds the loops in a config
+///
+/// # Returns
+/// * Zero-based first and last physical line of each loop
+fn loop_spans(lines: &[LogicalLine]) -> Vec<(usize, usize)> {
+    let mut open: Vec<usize> = Vec::new();
+    let mut spans = Vec::new();
+    for line in lines {
+        for word in line
+            .text
+            .split(|c: char| c.is_whitespace() || c == ';')
+            .filter(|word| !word.is_empty())
+        {
+            if LOOP_OPENERS.contains(&word) {
+                open.push(line.start);
+            } else if word == LOOP_CLOSER {
+                if let Some(start) = open.pop() {
+                    spans.push((start, line.end));
+                }
+            }
+        }
+    }
+    spans
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn problems(content: &str) -> Vec<(usize, Problem)> {
+        lint_file(content, None)
+    }
+
+    #[test]
+    fn test_clean_config_has_no_problems() {
+        let content = "# PATH=$PATH:/commented\n\
+                       export PATH=\"$HOME/bin:$PATH\"\n\
+                       PATH=/usr/local/sbin cmd --flag\n\
+                       for dir in ~/tools/*/bin; do\n\
+                       \x20 case \":$PATH:\" in *\":$dir:\"*) ;; *) PATH=\"$dir:$PATH\" ;; esac\n\
+                       done\n";
+        assert_eq!(problems(content), vec![]);
+    }
+
+    #[test]
+    fn test_missing_export_and_unquoted() {
+        let content = "PATH=$PATH:/opt/tools/bin\nPATH=\"$PATH:/opt/more\"\n";
+        assert_eq!(
+            problems(content),
+            vec![(1, Problem::MissingExport), (1, Problem::UnquotedExpansion)]
+        );
+        let content = "PATH=\"$PATH:/opt/more\"\nexport PATH\n";
+        assert_eq!(problems(content), vec![]);
+    }
+
+    #[test]
+    fn test_addition_in_loop() {
+        let content = "export PATH\nfor dir in ~/a ~/b; do\n  PATH=\"$dir:$PATH\"\ndone\n";
+        assert_eq!(problems(content), vec![(3, Problem::AdditionInLoop)]);
+        let content = "typeset -U path\nfor dir in ~/a ~/b; do\n  path+=($dir)\ndone\n";
+        assert_eq!(problems(content), vec![]);
+    }
+
+    #[test]
+    fn test_managed_block_is_skipped() {
+        let content = format!(
+            "{}\nPATH=$PATH:/x\n{}\n",
+            block::BLOCK_START,
+            block::BLOCK_END
+        );
+        assert_eq!(problems(&content), vec![]);
+    }
+
+    #[test]
+    fn test_zshrc_clobbers_zshenv() {
+        let zshenv = PathBuf::from("/pm-test/home/.zshenv");
+        let configs = vec![
+            (
+                zshenv.clone(),
+                "export PATH=\"$HOME/bin:$PATH\"\n".to_string(),
+            ),
+            (
+                PathBuf::from("/pm-test/home/.zshrc"),
+                "export PATH=\"/usr/bin:/bin\"\nexport PATH=\"$HOME/.cargo/bin:$PATH\"\n"
+                    .to_string(),
+            ),
+            (
+                PathBuf::from("/pm-test/home/.bashrc"),
+                "export PATH=\"/usr/bin:/bin\"\n".to_string(),
+            ),
+        ];
+        assert_eq!(
+            lint(&configs, &zshenv),
+            vec![Finding {
+                file: PathBuf::from("/pm-test/home/.zshrc"),
+                line: 1,
+                problem: Problem::ClobbersZshenv(1),
+            }]
+        );
+    }
+}
@@ -4,29 +4,438 @@
 //! - Display all current PATH entries
 //! - Format output for readability
 //! - Show full paths with proper display formatting
+//! - Show entries as declared in the shell config (`--source`)
+//! - Group entries by shared top-level directory (`--tree`)
+//! - Show each entry's status and owning package manager (`--long`)
+//! - Print entries in the stable porcelain format (`--porcelain`)
 
+use crate::commands::validator::is_valid_path_entry;
+use crate::config;
+use crate::msg;
 use crate::utils;
+use crate::utils::attribution::Layout;
+use crate::utils::porcelain::{self, PorcelainVersion};
+use crate::utils::shell::{factory, spelling, ShellHandler};
+use std::fs;
+use std::path::{Path, PathBuf};
 
 /// Executes the list command to display current PATH entries
 ///
 /// Lists all directories currently in PATH, with each entry on a new line
 /// prefixed with a bullet point for better readability.
 ///
+/// # Arguments
+///
+/// * `source` - Show entries as declared in the shell config instead
+/// * `tree` - Group entries by shared top-level directory
+/// * `long` - Show each entry's status and owning package manager
+///
 /// # Example
 ///
 /// ```
-/// commands::list::execute();
+/// commands::list::execute(false, false, false);
 /// // Output example:
 /// // Current PATH entries:
 /// // - /usr/local/bin
 /// // - /usr/bin
 /// // - ~/custom/bin
 /// ```
-pub fn execute() {
+pub fn execute(source: bool, tree: bool, long: bool) {
+    if source {
+        list_sources();
+        return;
+    }
+    if tree {
+        list_tree(long);
+        return;
+    }
+    if long {
+        list_long();
+        return;
+    }
+
     let path_entries = utils::get_path_entries();
 
-    println!("Current PATH entries:");
+    println!("{}", msg!("list-current"));
     for path in path_entries {
         println!("- {}", path.display());
     }
 }
+
+/// Lists PATH entries as declared in the shell config next to their expanded form
+///
+/// Each declaration is shown with its line number, the expanded path, whether
+/// the directory exists, and whether it is active in the current PATH.
+/// Entries in the current PATH that no config line declares are listed last.
+fn list_sources() {
+    let handler = factory::get_shell_handler(config::get_config().shell);
+    let config_path = handler.get_target_path();
+
+    let content = match fs::read_to_string(&config_path) {
+        Ok(content) => content,
+        Err(e) => {
+            eprintln!("{}", msg!("error-read-file", file = config_path, error = e));
+            return;
+        }
+    };
+
+    let path_entries = utils::get_path_entries();
+    let (lines, undeclared) = describe_sources(handler.as_ref(), &content, &path_entries);
+
+    println!("{}", msg!("list-declared", file = config_path));
+    for line in lines {
+        println!("{}", line);
+    }
+
+    if !undeclared.is_empty() {
+        println!();
+        println!("{}", msg!("list-undeclared", file = config_path));
+        for path in undeclared {
+            println!("- {}", path.display());
+        }
+    }
+}
+
+/// Describes the PATH declarations in a shell config, one line each
+///
+/// # Returns
+/// * The lines `list --source` shows for the declarations
+/// * The entries of `path_entries` that no declaration produces
+fn describe_sources<'a>(
+    handler: &dyn ShellHandler,
+    content: &str,
+    path_entries: &'a [PathBuf],
+) -> (Vec<String>, Vec<&'a PathBuf>) {
+    let mut lines = Vec::new();
+    let mut declared: Vec<PathBuf> = Vec::new();
+
+    for entry in handler.parse_declared_entries(content) {
+        if spelling::is_self_reference(&entry.raw) {
+            lines.push(format!(
+                "  {:>4}: {} ({})",
+                entry.line_number,
+                entry.raw,
+                msg!("list-existing-path")
+            ));
+            continue;
+        }
+
+        let expanded = match spelling::expand_entry(&entry.raw) {
+            Some(path) => path,
+            None => continue,
+        };
+
+        let validity = if is_valid_path_entry(&expanded) {
+            msg!("status-valid")
+        } else {
+            msg!("status-invalid")
+        };
+        let activity = if path_entries.contains(&expanded) {
+            msg!("list-active")
+        } else {
+            msg!("list-inactive")
+        };
+
+        lines.push(format!(
+            "  {:>4}: {} -> {} ({}, {})",
+            entry.line_number,
+            entry.raw,
+            expanded.display(),
+            validity,
+            activity
+        ));
+        declared.push(expanded);
+    }
+
+    let undeclared = path_entries
+        .iter()
+        .filter(|path| !declared.contains(path))
+        .collect();
+    (lines, undeclared)
+}
+
+/// Lists PATH entries with their position, status and owning package manager
+fn list_long() {
+    let path_entries = utils::get_path_entries();
+    let layout = Layout::from_env();
+    let width = path_entries
+        .iter()
+        .map(|entry| entry.display().to_string().len())
+        .max()
+        .unwrap_or(0);
+
+    println!("{}", msg!("list-current"));
+    for (index, entry) in path_entries.iter().enumerate() {
+        let owner = layout
+            .attribute(entry)
+            .map(|ecosystem| ecosystem.to_string())
+            .unwrap_or_else(|| "-".to_string());
+        println!(
+            "  {:>3}: {:<width$}  {:<16}  {}",
+            index + 1,
+            entry.display(),
+            entry_status(entry),
+            owner,
+            width = width
+        );
+    }
+}
+
+/// Prints PATH entries as porcelain `entry` records (see [`porcelain`])
+///
+/// # Example
+///
+/// ```
+/// commands::list::porcelain(PorcelainVersion::V1);
+/// // Output example:
+/// // porcelain    v1
+/// // entry    1    valid    /usr/local/bin
+/// // entry    2    missing    /opt/old/bin
+/// ```
+pub fn porcelain(version: PorcelainVersion) {
+    porcelain::print_header(version);
+    for (index, entry) in utils::get_path_entries().iter().enumerate() {
+        println!(
+            "{}",
+            porcelain::record(&[
+                "entry",
+                &(index + 1).to_string(),
+                porcelain_status(entry),
+                &entry.to_string_lossy(),
+            ])
+        );
+    }
+}
+
+/// Names an entry's status as the porcelain format does; unlike
+/// [`entry_status`], these names never change
+pub fn porcelain_status(entry: &Path) -> &'static str {
+    if entry.as_os_str().is_empty() {
+        "empty"
+    } else if entry.is_relative() {
+        "relative"
+    } else if is_valid_path_entry(entry) {
+        "valid"
+    } else if entry.exists() {
+        "not-dir"
+    } else {
+        "missing"
+    }
+}
+
+/// Describes whether an entry is usable
+pub fn entry_status(entry: &Path) -> String {
+    if entry.as_os_str().is_empty() {
+        msg!("status-empty")
+    } else if entry.is_relative() {
+        msg!("status-relative")
+    } else if is_valid_path_entry(entry) {
+        msg!("status-valid")
+    } else if entry.exists() {
+        msg!("status-not-dir")
+    } else {
+        msg!("status-missing")
+    }
+}
+
+/// PATH entries sharing a top-level directory
+#[derive(Debug, PartialEq)]
+struct EntryGroup {
+    /// Shared prefix, e.g. `/usr` or `~/.local`
+    prefix: String,
+    /// Zero-based PATH positions of the entries, in PATH order
+    positions: Vec<usize>,
+}
+
+/// Lists PATH entries grouped by top-level directory
+///
+/// Groups appear in the order of their first entry. Each entry keeps its
+/// one-based PATH position, and each group shows how many entries are invalid.
+/// With `long`, entries also show their owning package manager.
+fn list_tree(long: bool) {
+    let path_entries = utils::get_path_entries();
+    let layout = Layout::from_env();
+    let home = dirs_next::home_dir();
+    let groups = group_entries(&path_entries, home.as_deref());
+
+    println!(
+        "{}",
+        msg!(
+            "list-tree",
+            count = path_entries.len(),
+            groups = groups.len()
+        )
+    );
+    for group in groups {
+        let invalid = group
+            .positions
+            .iter()
+            .filter(|&&position| !is_valid_path_entry(&path_entries[position]))
+            .count();
+        let count = group.positions.len();
+        println!(
+            "{} ({})",
+            group.prefix,
+            msg!("list-group", count = count, invalid = invalid)
+        );
+
+        for position in group.positions {
+            let entry = &path_entries[position];
+            let mut notes = Vec::new();
+            if !is_valid_path_entry(entry) {
+                notes.push(msg!("status-invalid"));
+            }
+            if let Some(ecosystem) = layout.attribute(entry).filter(|_| long) {
+                notes.push(ecosystem.to_string());
+            }
+            let notes = if notes.is_empty() {
+                String::new()
+            } else {
+                format!(" ({})", notes.join(", "))
+            };
+            println!(
+                "  {:>3}: {}{}",
+                position + 1,
+                abbreviate(entry, home.as_deref()),
+                notes
+            );
+        }
+    }
+}
+
+/// Groups entries by their first directory, or their first directory under `~`
+fn group_entries(entries: &[PathBuf], home: Option<&Path>) -> Vec<EntryGroup> {
+    let mut groups: Vec<EntryGroup> = Vec::new();
+    for (position, entry) in entries.iter().enumerate() {
+        let prefix = group_prefix(&abbreviate(entry, home));
+        match groups.iter_mut().find(|group| group.prefix == prefix) {
+            Some(group) => group.positions.push(position),
+            None => groups.push(EntryGroup {
+                prefix,
+                positions: vec![position],
+            }),
+        }
+    }
+    groups
+}
+
+/// Returns the group prefix of an abbreviated entry
+fn group_prefix(shown: &str) -> String {
+    let (root, rest) = if let Some(rest) = shown.strip_prefix("~/") {
+        ("~/", rest)
+    } else if let Some(rest) = shown.strip_prefix('/') {
+        ("/", rest)
+    } else if shown == "~" {
+        return shown.to_string();
+    } else {
+        return "(relative)".to_string();
+    };
+
+    match rest.split('/').find(|part| !part.is_empty()) {
+        Some(first) => format!("{}{}", root, first),
+        None if root == "/" => root.to_string(),
+        None => "~".to_string(),
+    }
+}
+
+/// Formats an entry for display, writing the home directory as `~`
+fn abbreviate(entry: &Path, home: Option<&Path>) -> String {
+    if entry.as_os_str().is_empty() {
+        return "(empty)".to_string();
+    }
+    match home.and_then(|home| entry.strip_prefix(home).ok()) {
+        Some(rest) if rest.as_os_str().is_empty() => "~".to_string(),
+        Some(rest) => format!("~/{}", rest.display()),
+        None => entry.display().to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::shell::handlers::bash::BashHandler;
+    use crate::utils::shell::locations;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_describe_sources() {
+        let temp_dir = TempDir::new().unwrap();
+        let existing = temp_dir.path().join("bin");
+        fs::create_dir(&existing).unwrap();
+        let missing = temp_dir.path().join("missing");
+        let home = locations::real_home_dir();
+        let content = format!(
+            "alias ll='ls -l'\n\
+             export PATH=\"{}:$HOME/pm-test-bin:$PATH\"\n\
+             export PATH=\"$PATH:{}\"\n",
+            existing.display(),
+            missing.display()
+        );
+        let path_entries = vec![existing.clone(), PathBuf::from("/pm-test/undeclared")];
+
+        let (lines, undeclared) = describe_sources(&BashHandler::new(), &content, &path_entries);
+        let valid_active = format!("{}, {}", msg!("status-valid"), msg!("list-active"));
+        let invalid_inactive = format!("{}, {}", msg!("status-invalid"), msg!("list-inactive"));
+        assert_eq!(
+            lines,
+            vec![
+                format!(
+                    "     2: {} -> {} ({})",
+                    existing.display(),
+                    existing.display(),
+                    valid_active
+                ),
+                format!(
+                    "     2: $HOME/pm-test-bin -> {} ({})",
+                    home.join("pm-test-bin").display(),
+                    invalid_inactive
+                ),
+                format!("     2: $PATH ({})", msg!("list-existing-path")),
+                format!("     3: $PATH ({})", msg!("list-existing-path")),
+                format!(
+                    "     3: {} -> {} ({})",
+                    missing.display(),
+                    missing.display(),
+                    invalid_inactive
+                ),
+            ]
+        );
+        assert_eq!(undeclared, vec![&PathBuf::from("/pm-test/undeclared")]);
+    }
+
+    #[test]
+    fn test_group_entries() {
+        let home = Path::new("/home/user");
+        let entries: Vec<PathBuf> = [
+            "/home/user/.local/bin",
+            "/usr/local/bin",
+            "/home/user/sdk/go/bin",
+            "/usr/bin",
+            "/bin",
+            "/home/user/sdk/flutter/bin",
+            "bin",
+            "",
+        ]
+        .iter()
+        .map(PathBuf::from)
+        .collect();
+
+        let groups = group_entries(&entries, Some(home));
+        let summary: Vec<(&str, Vec<usize>)> = groups
+            .iter()
+            .map(|group| (group.prefix.as_str(), group.positions.clone()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("~/.local", vec![0]),
+                ("/usr", vec![1, 3]),
+                ("~/sdk", vec![2, 5]),
+                ("/bin", vec![4]),
+                ("(relative)", vec![6, 7]),
+            ]
+        );
+        assert_eq!(abbreviate(&entries[2], Some(home)), "~/sdk/go/bin");
+        assert_eq!(group_prefix("/"), "/");
+    }
+}
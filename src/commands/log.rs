@@ -0,0 +1,80 @@
+//! Command implementation for reviewing the operation log.
+//!
+//! This module provides functionality to:
+//! - List recent operations that changed PATH
+//! - Show the entries each one added, removed or reordered
+
+use crate::msg;
+use crate::utils::audit;
+
+/// Number of operations shown when no limit is given
+pub const DEFAULT_LIMIT: usize = 20;
+
+/// Executes the log command to show recent PATH operations, oldest first
+///
+/// # Arguments
+///
+/// * `limit` - Maximum number of operations to show; `0` shows all
+///
+/// # Example
+///
+/// ```
+/// commands::log::execute(20);
+/// // Output example:
+/// // 2024-03-21 12:00:00  pathmaster add ~/bin
+/// //     + /home/user/bin
+/// //     backup: /home/user/.pathmaster/backups/backup_20240321120000.json
+/// ```
+pub fn execute(limit: usize) {
+    let records = match audit::read_all() {
+        Ok(records) => records,
+        Err(e) => {
+            eprintln!("{}", msg!("undo-log-unreadable", error = e));
+            return;
+        }
+    };
+
+    if records.is_empty() {
+        println!("{}", msg!("log-empty"));
+        return;
+    }
+
+    let skip = if limit == 0 {
+        0
+    } else {
+        records.len().saturating_sub(limit)
+    };
+
+    for record in &records[skip..] {
+        println!(
+            "{}  pathmaster {}",
+            record.timestamp,
+            record.arguments.join(" ")
+        );
+
+        let added = record.added();
+        let removed = record.removed();
+        for entry in &added {
+            println!("    + {}", entry);
+        }
+        for entry in &removed {
+            println!("    - {}", entry);
+        }
+        if added.is_empty() && removed.is_empty() {
+            println!("    ~ {}", msg!("log-reordered"));
+        }
+        if let Some(reason) = &record.reason {
+            println!("    {}", msg!("log-reason", reason = reason));
+        }
+        if let Some(backup) = &record.backup {
+            println!("    {}", msg!("log-backup", file = backup));
+        }
+    }
+
+    if skip > 0 {
+        println!(
+            "{}",
+            msg!("log-truncated", count = skip, file = audit::log_path())
+        );
+    }
+}
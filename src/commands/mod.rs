@@ -1,6 +1,32 @@
 // src/commands/mod.rs
 pub mod add;
+pub mod adopt;
+pub mod audit;
+pub mod bench;
+pub mod cache;
+pub mod check;
+pub mod clean;
 pub mod delete;
+pub mod enforce;
+pub mod ensure;
+pub mod expire;
+pub mod explain;
+pub mod export;
 pub mod flush;
+pub mod grep;
+pub mod init;
+pub mod lint;
 pub mod list;
+pub mod log;
+pub mod normalize;
+pub mod replace;
+pub mod scan;
+pub mod serve;
+pub mod shadows;
+pub mod show;
+pub mod snapshot;
+pub mod status;
+pub mod undo;
 pub mod validator;
+pub mod verify;
+pub mod why;
@@ -0,0 +1,73 @@
+//! Command implementation for normalizing PATH entries.
+//!
+//! This module provides functionality to:
+//! - Strip trailing slashes and collapse `//` and `/./` segments
+//! - Optionally resolve symlinks to their targets
+//! - Leave directories listed in `keep-dirs` exactly as written
+//! - Apply all changes in one backup-protected update
+
+use crate::msg;
+use crate::utils::protected;
+use crate::utils::PathSet;
+
+/// Executes the normalize command to clean up the spelling of PATH entries
+///
+/// # Arguments
+///
+/// * `resolve_symlinks` - Replace symlinked entries with their targets
+///
+/// # Example
+///
+/// ```
+/// commands::normalize::execute(false);
+/// // Output example:
+/// // Normalizing /usr/local/bin/ -> /usr/local/bin
+/// ```
+pub fn execute(resolve_symlinks: bool) {
+    let original = PathSet::from_env();
+    let mut normalized = original.clone();
+    normalized.normalize(resolve_symlinks);
+    let keep = protected::keep_dirs();
+
+    let mut changed = 0;
+    let mut entries = Vec::with_capacity(original.entries().len());
+    for (original, updated) in original.entries().iter().zip(normalized.entries()) {
+        // Path equality ignores the differences being fixed, so compare the raw strings
+        if original.as_os_str() == updated.as_os_str() {
+            entries.push(updated.clone());
+        } else if protected::is_kept(original, &keep) {
+            println!(
+                "{}",
+                msg!("normalize-kept-excluded", from = original, to = updated)
+            );
+            entries.push(original.clone());
+        } else {
+            println!(
+                "{}",
+                msg!("clean-normalized", from = original, to = updated)
+            );
+            entries.push(updated.clone());
+            changed += 1;
+        }
+    }
+    let normalized = PathSet::new(entries);
+
+    if changed == 0 {
+        println!("{}", msg!("normalize-nothing"));
+        return;
+    }
+
+    // Backup current PATH
+    if let Err(e) = normalized.apply_backup() {
+        eprintln!("{}", msg!("error-backup", error = e));
+        return;
+    }
+
+    // Update PATH and make persistent changes (update shell config)
+    if let Err(e) = normalized.apply() {
+        eprintln!("{}", msg!("error-shell-config", error = e));
+        return;
+    }
+
+    println!("{}", msg!("normalize-done", count = changed));
+}
@@ -0,0 +1,159 @@
+//! Command implementation for swapping one PATH directory for another.
+//!
+//! This module handles:
+//! - Replacing an entry in place, keeping its position
+//! - Validating the new directory
+//! - Refusing to replace protected system directories
+//! - Creating backups before modification
+//! - Updating shell configuration
+
+use crate::commands::validator::is_valid_path_entry;
+use crate::msg;
+use crate::utils::protected;
+use crate::utils::{self, PathSet};
+
+/// Options controlling how a directory is replaced
+#[derive(Debug, Default, Clone)]
+pub struct ReplaceOptions {
+    /// Use the new directory even if it doesn't exist or isn't a directory
+    pub force: bool,
+    /// Allow replacing protected system directories
+    pub allow_system: bool,
+}
+
+/// Executes the replace command, e.g. when upgrading an SDK installed
+/// side by side with the old one
+///
+/// # Arguments
+///
+/// * `old` - Directory currently in PATH
+/// * `new` - Directory to put in its place
+/// * `options` - Options controlling the replacement
+///
+/// # Returns
+///
+/// `false` if `old` isn't in PATH, `new` was refused or `old` is protected,
+/// or the backup or shell config couldn't be written
+///
+/// # Example
+///
+/// ```
+/// commands::replace::execute("~/sdk/v1/bin", "~/sdk/v2/bin", &ReplaceOptions::default());
+/// // Output example:
+/// // Replaced '/home/user/sdk/v1/bin' with '/home/user/sdk/v2/bin' at position 3.
+/// ```
+pub fn execute(old: &str, new: &str, options: &ReplaceOptions) -> bool {
+    let old_path = utils::expand_path(old);
+    let new_path = utils::expand_path(new);
+
+    let mut path_set = PathSet::from_env();
+    let Some(index) = path_set.position(&old_path) else {
+        eprintln!("{}", msg!("replace-not-in-path", path = &old_path));
+        return false;
+    };
+    let existing = path_set.entries()[index].clone();
+
+    if path_set.position(&new_path) == Some(index) {
+        println!("{}", msg!("replace-same", old = &existing, new = &new_path));
+        return true;
+    }
+
+    if !is_valid_path_entry(&new_path) {
+        let reason = if new_path.exists() {
+            msg!("reason-not-a-directory")
+        } else {
+            msg!("reason-missing")
+        };
+
+        if !options.force {
+            eprintln!(
+                "{}",
+                msg!("replace-invalid", path = &new_path, reason = reason)
+            );
+            return false;
+        }
+        eprintln!(
+            "{}",
+            msg!("replace-forced", path = &new_path, reason = reason)
+        );
+    }
+
+    if !options.allow_system && protected::is_protected(&existing, &protected::protected_dirs()) {
+        eprintln!("{}", msg!("replace-protected", path = &existing));
+        eprintln!("{}", msg!("replace-allow-system-hint"));
+        return false;
+    }
+
+    // Backup current PATH
+    if let Err(e) = path_set.apply_backup() {
+        eprintln!("{}", msg!("error-backup", error = e));
+        return false;
+    }
+
+    let duplicate = path_set.position(&new_path);
+    path_set.replace(&existing, &new_path);
+    if let Err(e) = path_set.apply() {
+        eprintln!("{}", msg!("error-shell-config", error = e));
+        return false;
+    }
+
+    println!(
+        "{}",
+        msg!(
+            "replace-done",
+            old = &existing,
+            new = &new_path,
+            position = index + 1
+        )
+    );
+    if let Some(duplicate) = duplicate {
+        println!(
+            "{}",
+            msg!(
+                "replace-duplicate-removed",
+                path = &new_path,
+                position = duplicate + 1
+            )
+        );
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serial_test::serial;
+    use std::env;
+    use tempfile::TempDir;
+
+    #[test]
+    #[serial]
+    fn test_replace_refusals_fail() {
+        let temp_dir = TempDir::new().unwrap();
+        let old = temp_dir.path().join("old");
+        std::fs::create_dir(&old).unwrap();
+        let original_path = env::var_os("PATH");
+        env::set_var("PATH", &old);
+
+        let not_in_path = execute(
+            &temp_dir.path().join("other").to_string_lossy(),
+            &old.to_string_lossy(),
+            &ReplaceOptions::default(),
+        );
+        let missing = execute(
+            &old.to_string_lossy(),
+            &temp_dir.path().join("missing").to_string_lossy(),
+            &ReplaceOptions::default(),
+        );
+        let entries = utils::get_path_entries();
+
+        match original_path {
+            Some(path) => env::set_var("PATH", path),
+            None => env::remove_var("PATH"),
+        }
+
+        assert!(!not_in_path);
+        assert!(!missing);
+        assert_eq!(entries, vec![old]);
+    }
+}
@@ -0,0 +1,50 @@
+//! Command implementation for scanning the system for PATH declarations.
+//!
+//! This module provides functionality to:
+//! - Find PATH modifications in system and user shell configs
+//! - Count the executables provided by each PATH directory
+//! - Scan files and directories in parallel with stable output order
+//! - Reuse cached listings for directories that haven't changed
+
+use crate::msg;
+use crate::utils;
+use crate::utils::cache::ExecutableCache;
+use crate::utils::path_scanner::{self, PathScanner};
+
+/// Executes the scan command
+///
+/// Prints every config line that modifies PATH, grouped into system-level
+/// and user-level files, followed by the number of executables found in
+/// each current PATH directory.
+///
+/// # Example
+///
+/// ```
+/// commands::scan::execute();
+/// ```
+pub fn execute() {
+    let scanner = PathScanner::new();
+    match scanner.scan_all() {
+        Ok(locations) => print!("{}", path_scanner::format_results(&locations)),
+        Err(e) => {
+            eprintln!("{}", msg!("scan-failed", error = e));
+            return;
+        }
+    }
+
+    println!("\n{}", msg!("scan-executables"));
+    let path_entries = utils::get_path_entries();
+    let mut cache = ExecutableCache::load();
+    let listings = path_scanner::scan_directories(&path_entries, &mut cache);
+    if let Err(e) = cache.save() {
+        eprintln!("{}", msg!("warning-cache", error = e));
+    }
+
+    for listing in listings {
+        println!(
+            "  {:>5}  {}",
+            listing.executables.len(),
+            listing.directory.display()
+        );
+    }
+}
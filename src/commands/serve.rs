@@ -0,0 +1,447 @@
+//! Command implementation for serving editors over JSON-RPC.
+//!
+//! `pathmaster serve --stdio` reads JSON-RPC 2.0 requests from standard
+//! input, one per line, and writes one response line per request to
+//! standard output, so editor extensions can manage PATH without scraping
+//! the human-readable output. This module provides functionality to:
+//! - List PATH entries with their status (`list`)
+//! - Report problems as `check --porcelain` does (`check`)
+//! - Add and remove directories (`add`, `remove`)
+//! - Preview the changes `add` or `remove` would make (`diff`)
+//!
+//! Changes run as a separate `pathmaster` process with the global options
+//! the server was started with, so they behave exactly like the commands
+//! and their messages can't mix with the protocol. Their output is returned
+//! in the result. After a change, the server's PATH, which `list`, `check`
+//! and later changes start from, is updated to the new one.
+
+use crate::commands::check::{self, CheckSource};
+use crate::commands::list;
+use crate::config;
+use crate::msg;
+use crate::utils;
+use crate::utils::shell::factory;
+use serde_json::{json, Value};
+use std::env;
+use std::ffi::OsString;
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::process::{Command, Stdio};
+
+/// JSON-RPC error codes
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const INTERNAL_ERROR: i64 = -32603;
+
+/// A JSON-RPC error, before it gets the request's id
+#[derive(Debug, Clone, PartialEq)]
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        RpcError {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+/// Executes the serve command, answering requests until stdin is closed
+///
+/// # Example
+///
+/// ```text
+/// $ pathmaster serve --stdio
+/// {"jsonrpc":"2.0","id":1,"method":"add","params":{"paths":["~/bin"]}}
+/// {"jsonrpc":"2.0","id":1,"result":{"success":true,"exit_code":0,"output":"...","path":[...]}}
+/// ```
+pub fn execute() {
+    let globals = global_args();
+    let stdin = io::stdin();
+    let mut stdout = io::stdout();
+    for line in stdin.lock().lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(e) => {
+                eprintln!("{}", msg!("serve-read-failed", error = e));
+                break;
+            }
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(response) = handle_line(&line, &globals) {
+            if writeln!(stdout, "{}", response)
+                .and_then(|_| stdout.flush())
+                .is_err()
+            {
+                break;
+            }
+        }
+    }
+}
+
+/// Answers one request line
+///
+/// # Returns
+/// * `None` - For notifications, which get no response
+fn handle_line(line: &str, globals: &[OsString]) -> Option<Value> {
+    let request: Value = match serde_json::from_str(line) {
+        Ok(request) => request,
+        Err(e) => {
+            return Some(error_response(
+                Value::Null,
+                RpcError::new(PARSE_ERROR, format!("Parse error: {}", e)),
+            ))
+        }
+    };
+    let id = request.get("id").cloned();
+    let Some(method) = request.get("method").and_then(Value::as_str) else {
+        return Some(error_response(
+            id.unwrap_or(Value::Null),
+            RpcError::new(INVALID_REQUEST, "Request has no method"),
+        ));
+    };
+    let params = request.get("params").cloned().unwrap_or(Value::Null);
+
+    let result = dispatch(method, &params, globals);
+    let id = id?;
+    Some(match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(error) => error_response(id, error),
+    })
+}
+
+fn error_response(id: Value, error: RpcError) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": error.code, "message": error.message },
+    })
+}
+
+fn dispatch(method: &str, params: &Value, globals: &[OsString]) -> Result<Value, RpcError> {
+    match method {
+        "list" => Ok(list_entries()),
+        "check" => Ok(check_entries()),
+        "add" | "remove" => run_change(&command_args(method, params)?, globals),
+        "diff" => {
+            let inner = params
+                .get("method")
+                .and_then(Value::as_str)
+                .ok_or_else(|| RpcError::new(INVALID_PARAMS, "diff needs the method to preview"))?;
+            let inner_params = params.get("params").cloned().unwrap_or(Value::Null);
+            preview_change(&command_args(inner, &inner_params)?, globals)
+        }
+        _ => Err(RpcError::new(
+            METHOD_NOT_FOUND,
+            format!("Method not found: {}", method),
+        )),
+    }
+}
+
+/// Lists PATH entries with the statuses of `list --porcelain`
+fn list_entries() -> Value {
+    let entries: Vec<Value> = utils::get_path_entries()
+        .iter()
+        .enumerate()
+        .map(|(index, entry)| {
+            json!({
+                "position": index + 1,
+                "path": entry.to_string_lossy(),
+                "status": list::porcelain_status(entry),
+            })
+        })
+        .collect();
+    json!({ "entries": entries })
+}
+
+/// Lists the problems `check --porcelain` reports
+fn check_entries() -> Value {
+    let problems: Vec<Value> = check::problems(CheckSource::Environment, &utils::get_path_entries())
+        .into_iter()
+        .map(|(kind, path, detail)| {
+            json!({ "kind": kind, "path": path.to_string_lossy(), "detail": detail })
+        })
+        .collect();
+    json!({ "problems": problems })
+}
+
+/// Builds the command line for an `add` or `remove` request
+///
+/// `add` takes `paths` and the flags `prepend`, `create` and `force`;
+/// `remove` takes `paths`, `index`, `glob`, `regex` and `allow_system`.
+/// Both take `yes` to confirm prompts, which are answered no otherwise.
+fn command_args(method: &str, params: &Value) -> Result<Vec<OsString>, RpcError> {
+    let flag = |name: &str| params.get(name).and_then(Value::as_bool).unwrap_or(false);
+    let text = |name: &str| params.get(name).and_then(Value::as_str);
+    let paths: Vec<&str> = match params.get("paths") {
+        None | Some(Value::Null) => Vec::new(),
+        Some(Value::Array(paths)) => paths
+            .iter()
+            .map(|path| {
+                path.as_str()
+                    .ok_or_else(|| RpcError::new(INVALID_PARAMS, "paths must be strings"))
+            })
+            .collect::<Result<_, _>>()?,
+        Some(_) => {
+            return Err(RpcError::new(
+                INVALID_PARAMS,
+                "paths must be an array of strings",
+            ))
+        }
+    };
+
+    let mut args: Vec<String> = Vec::new();
+    if flag("yes") {
+        args.push("--yes".to_string());
+    }
+    match method {
+        "add" => {
+            if paths.is_empty() {
+                return Err(RpcError::new(INVALID_PARAMS, "add needs paths"));
+            }
+            args.push(if flag("prepend") { "prepend" } else { "add" }.to_string());
+            for (name, option) in [("create", "--create"), ("force", "--force")] {
+                if flag(name) {
+                    args.push(option.to_string());
+                }
+            }
+        }
+        "remove" => {
+            let index = params.get("index").and_then(Value::as_u64);
+            if paths.is_empty()
+                && index.is_none()
+                && text("glob").is_none()
+                && text("regex").is_none()
+            {
+                return Err(RpcError::new(
+                    INVALID_PARAMS,
+                    "remove needs paths, index, glob or regex",
+                ));
+            }
+            args.push("delete".to_string());
+            if let Some(index) = index {
+                args.push(format!("--index={}", index));
+            }
+            for name in ["glob", "regex"] {
+                if let Some(value) = text(name) {
+                    args.push(format!("--{}={}", name, value));
+                }
+            }
+            if flag("allow_system") {
+                args.push("--allow-system".to_string());
+            }
+        }
+        _ => {
+            return Err(RpcError::new(
+                METHOD_NOT_FOUND,
+                format!("Method not found: {}", method),
+            ))
+        }
+    }
+    // Paths could start with '-', so they go after '--'
+    if !paths.is_empty() {
+        args.push("--".to_string());
+        args.extend(paths.iter().map(|path| path.to_string()));
+    }
+    Ok(args.into_iter().map(OsString::from).collect())
+}
+
+/// The outcome of a pathmaster process a request ran
+struct RunOutput {
+    status: Option<i32>,
+    stdout: String,
+    stderr: String,
+}
+
+/// Runs a change and takes over the PATH it leaves
+///
+/// The new PATH is emitted into a file in a private temporary directory,
+/// which is removed afterwards.
+fn run_change(args: &[OsString], globals: &[OsString]) -> Result<Value, RpcError> {
+    let emit_dir = tempfile::Builder::new()
+        .prefix("pathmaster-serve-")
+        .tempdir()
+        .map_err(|e| {
+            RpcError::new(
+                INTERNAL_ERROR,
+                format!("Cannot create a temporary directory: {}", e),
+            )
+        })?;
+    let emit_file = emit_dir.path().join("path");
+    let mut command_line = vec![OsString::from("--emit"), emit_file.clone().into()];
+    command_line.extend(args.iter().cloned());
+
+    let output = run_pathmaster(globals, &command_line)?;
+    let emitted = fs::read_to_string(&emit_file).unwrap_or_default();
+
+    if !emitted.trim().is_empty() {
+        let handler = factory::get_shell_handler(config::get_config().shell);
+        utils::set_path_entries(&handler.parse_path_entries(&emitted));
+    }
+    let path: Vec<String> = utils::get_path_entries()
+        .iter()
+        .map(|entry| entry.to_string_lossy().into_owned())
+        .collect();
+    Ok(json!({
+        "success": output.status == Some(0),
+        "exit_code": output.status,
+        "output": output.stdout + &output.stderr,
+        "path": path,
+    }))
+}
+
+/// Runs a change in check mode and returns its JSON diff
+fn preview_change(args: &[OsString], globals: &[OsString]) -> Result<Value, RpcError> {
+    let mut command_line: Vec<OsString> = ["--check", "--diff-format", "json"]
+        .iter()
+        .map(OsString::from)
+        .collect();
+    command_line.extend(args.iter().cloned());
+
+    let output = run_pathmaster(globals, &command_line)?;
+    let (messages, diff) = split_preview(&output);
+    Ok(json!({
+        "changes_pending": output.status == Some(utils::check_mode::CHANGES_PENDING),
+        "exit_code": output.status,
+        "output": messages,
+        "diff": diff,
+    }))
+}
+
+/// Separates the JSON diff a preview printed from its messages
+///
+/// The diff is the last line of standard output. Everything before it, and
+/// whatever went to standard error, such as warnings, is messages. Without
+/// a diff, all the output is messages.
+fn split_preview(output: &RunOutput) -> (String, Value) {
+    let stdout = output.stdout.trim_end();
+    let (before, last) = stdout.rsplit_once('\n').unwrap_or(("", stdout));
+    let diff: Value = serde_json::from_str(last.trim()).unwrap_or(Value::Null);
+    let messages = if diff.is_null() {
+        &output.stdout
+    } else {
+        before
+    };
+    let mut messages = messages.trim_end().to_string();
+    if !messages.is_empty() && !output.stderr.is_empty() {
+        messages.push('\n');
+    }
+    messages.push_str(&output.stderr);
+    (messages, diff)
+}
+
+/// Runs pathmaster with the server's global options and `args`
+fn run_pathmaster(globals: &[OsString], args: &[OsString]) -> Result<RunOutput, RpcError> {
+    let exe = env::current_exe()
+        .map_err(|e| RpcError::new(INTERNAL_ERROR, format!("Cannot find pathmaster: {}", e)))?;
+    let output = Command::new(exe)
+        .args(globals)
+        .args(args)
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| RpcError::new(INTERNAL_ERROR, format!("Cannot run pathmaster: {}", e)))?;
+
+    Ok(RunOutput {
+        status: output.status.code(),
+        stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+        stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+    })
+}
+
+/// Returns the global options given before `serve`, for the changes it runs
+///
+/// `--yes` and `--no-input` are left out, so each request decides whether
+/// prompts are confirmed.
+fn global_args() -> Vec<OsString> {
+    env::args_os()
+        .skip(1)
+        .take_while(|arg| arg != "serve")
+        .filter(|arg| arg != "--yes" && arg != "--no-input")
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(values: &[&str]) -> Vec<OsString> {
+        values.iter().map(OsString::from).collect()
+    }
+
+    #[test]
+    fn test_command_args() {
+        let params = json!({ "paths": ["-odd", "/pm-test/a"], "prepend": true, "yes": true });
+        assert_eq!(
+            command_args("add", &params),
+            Ok(args(&["--yes", "prepend", "--", "-odd", "/pm-test/a"]))
+        );
+
+        let params = json!({ "glob": "/pm-test/*", "index": 2, "allow_system": true });
+        assert_eq!(
+            command_args("remove", &params),
+            Ok(args(&[
+                "delete",
+                "--index=2",
+                "--glob=/pm-test/*",
+                "--allow-system"
+            ]))
+        );
+
+        let error = command_args("add", &json!({ "paths": [1] })).unwrap_err();
+        assert_eq!(error.code, INVALID_PARAMS);
+        assert!(command_args("remove", &Value::Null).is_err());
+    }
+
+    #[test]
+    fn test_split_preview() {
+        let output = RunOutput {
+            status: Some(utils::check_mode::CHANGES_PENDING),
+            stdout: "Check mode: changes pending (PATH); nothing was written.\n{\"path\":{\"before\":[],\"after\":[\"/pm-test/a\"]}}\n".to_string(),
+            stderr: "Warning: '/pm-test/a' does not exist; adding anyway (--force).\n".to_string(),
+        };
+        let (messages, diff) = split_preview(&output);
+        assert_eq!(diff["path"]["after"][0], "/pm-test/a");
+        assert_eq!(
+            messages,
+            "Check mode: changes pending (PATH); nothing was written.\nWarning: '/pm-test/a' does not exist; adding anyway (--force).\n"
+        );
+
+        // A command that fails before printing a diff keeps all its output
+        let output = RunOutput {
+            status: Some(1),
+            stdout: String::new(),
+            stderr: "Error: '/pm-test/b' does not exist.\n".to_string(),
+        };
+        let (messages, diff) = split_preview(&output);
+        assert!(diff.is_null());
+        assert_eq!(messages, "Error: '/pm-test/b' does not exist.\n");
+    }
+
+    #[test]
+    fn test_handle_line_errors() {
+        let response = handle_line("{not json", &[]).unwrap();
+        assert_eq!(response["error"]["code"], PARSE_ERROR);
+        assert_eq!(response["id"], Value::Null);
+
+        let response = handle_line(r#"{"jsonrpc":"2.0","id":7,"method":"nope"}"#, &[]).unwrap();
+        assert_eq!(response["error"]["code"], METHOD_NOT_FOUND);
+        assert_eq!(response["id"], 7);
+
+        // Notifications get no response
+        assert!(handle_line(r#"{"jsonrpc":"2.0","method":"nope"}"#, &[]).is_none());
+
+        let response = handle_line(
+            r#"{"jsonrpc":"2.0","id":"a","method":"diff","params":{}}"#,
+            &[],
+        )
+        .unwrap();
+        assert_eq!(response["error"]["code"], INVALID_PARAMS);
+    }
+}
@@ -0,0 +1,251 @@
+//! Command implementation for finding shadowed executables.
+//!
+//! This module provides functionality to:
+//! - Find executables provided by more than one PATH directory
+//! - Show which copy wins and which copies it hides
+//! - Compare `--version` output of interpreters and compilers across copies
+
+use crate::msg;
+use crate::utils;
+use crate::utils::cache::ExecutableCache;
+use crate::utils::path_scanner::{self, DirectoryListing};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Tools whose versions are compared with `--versions` when no names are given
+pub const VERSIONED_TOOLS: &[&str] = &[
+    "python", "python3", "pip", "pip3", "node", "npm", "java", "javac", "gcc", "g++", "cc",
+    "clang", "ruby", "perl", "go", "rustc", "cargo", "git", "make",
+];
+
+/// How long a `--version` call may take before it's abandoned
+const VERSION_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// An executable name found in more than one PATH directory
+#[derive(Debug, PartialEq)]
+pub struct Shadow {
+    /// Executable name, e.g. `python3`
+    pub name: String,
+    /// Every copy in PATH order; the first is the one that runs
+    pub copies: Vec<PathBuf>,
+}
+
+/// Executes the shadows command
+///
+/// # Arguments
+///
+/// * `names` - Only report these executables (all when empty)
+/// * `versions` - Run each copy with `--version` and report mismatches
+///
+/// # Example
+///
+/// ```
+/// commands::shadows::execute(&[], false);
+/// // Output example:
+/// // python3
+/// //   /usr/local/bin/python3 (runs)
+/// //   /usr/bin/python3
+/// ```
+pub fn execute(names: &[String], versions: bool) {
+    let path_entries = utils::get_path_entries();
+    let mut cache = ExecutableCache::load();
+    let listings = path_scanner::scan_directories(&path_entries, &mut cache);
+    if let Err(e) = cache.save() {
+        eprintln!("{}", msg!("warning-cache", error = e));
+    }
+
+    let shadows: Vec<Shadow> = find_shadows(&listings)
+        .into_iter()
+        .filter(|shadow| {
+            if !names.is_empty() {
+                names.contains(&shadow.name)
+            } else if versions {
+                VERSIONED_TOOLS.contains(&shadow.name.as_str())
+            } else {
+                true
+            }
+        })
+        .collect();
+
+    if shadows.is_empty() {
+        println!("{}", msg!("shadows-none"));
+        return;
+    }
+
+    let mut mismatched = Vec::new();
+    for shadow in &shadows {
+        println!("{}", shadow.name);
+        let mut seen_versions: Vec<String> = Vec::new();
+        for (index, copy) in shadow.copies.iter().enumerate() {
+            let marker = if index == 0 {
+                format!(" ({})", msg!("shadows-runs"))
+            } else {
+                String::new()
+            };
+            if versions {
+                let version = tool_version(copy).unwrap_or_else(|| msg!("shadows-version-unknown"));
+                println!("  {}{}: {}", copy.display(), marker, version);
+                if !seen_versions.contains(&version) {
+                    seen_versions.push(version);
+                }
+            } else {
+                println!("  {}{}", copy.display(), marker);
+            }
+        }
+        if seen_versions.len() > 1 {
+            println!("  ! {}", msg!("shadows-versions-differ"));
+            mismatched.push(shadow.name.as_str());
+        }
+    }
+
+    if versions {
+        if mismatched.is_empty() {
+            println!();
+            println!("{}", msg!("shadows-same-version"));
+        } else {
+            println!();
+            println!(
+                "{}",
+                msg!("shadows-mismatches", names = mismatched.join(", "))
+            );
+        }
+    }
+}
+
+/// Finds executable names provided by more than one PATH directory.
+///
+/// A directory listed twice in PATH only counts once, and copies that are
+/// the same file (such as `/bin/ls` and `/usr/bin/ls` on merged-/usr
+/// systems) are not reported as shadowing each other. Results are sorted
+/// by name.
+pub fn find_shadows(listings: &[DirectoryListing]) -> Vec<Shadow> {
+    let mut copies: HashMap<&str, Vec<PathBuf>> = HashMap::new();
+    let mut seen_dirs: Vec<&PathBuf> = Vec::new();
+
+    for listing in listings {
+        if seen_dirs.contains(&&listing.directory) {
+            continue;
+        }
+        seen_dirs.push(&listing.directory);
+        for name in &listing.executables {
+            copies
+                .entry(name)
+                .or_default()
+                .push(listing.directory.join(name));
+        }
+    }
+
+    let mut shadows: Vec<Shadow> = copies
+        .into_iter()
+        .filter_map(|(name, paths)| {
+            let mut files = Vec::new();
+            let mut distinct = Vec::new();
+            for path in paths {
+                let file = fs::canonicalize(&path).unwrap_or_else(|_| path.clone());
+                if !files.contains(&file) {
+                    files.push(file);
+                    distinct.push(path);
+                }
+            }
+            (distinct.len() > 1).then(|| Shadow {
+                name: name.to_string(),
+                copies: distinct,
+            })
+        })
+        .collect();
+    shadows.sort_by(|a, b| a.name.cmp(&b.name));
+    shadows
+}
+
+/// Runs an executable with `--version` and returns the first line it prints.
+///
+/// Some tools (older `java`, `gcc -v`-style compilers) write their version to
+/// stderr, so stderr is used when stdout is empty. Programs that don't exit
+/// within a few seconds are killed.
+fn tool_version(executable: &Path) -> Option<String> {
+    let mut child = Command::new(executable)
+        .arg("--version")
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .ok()?;
+
+    let started = Instant::now();
+    while child.try_wait().ok()?.is_none() {
+        if started.elapsed() > VERSION_TIMEOUT {
+            let _ = child.kill();
+            let _ = child.wait();
+            return None;
+        }
+        thread::sleep(Duration::from_millis(20));
+    }
+
+    let output = child.wait_with_output().ok()?;
+    first_line(&output.stdout).or_else(|| first_line(&output.stderr))
+}
+
+/// Returns the first non-empty line of command output
+fn first_line(output: &[u8]) -> Option<String> {
+    String::from_utf8_lossy(output)
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn listing(directory: &Path, executables: &[&str]) -> DirectoryListing {
+        DirectoryListing {
+            directory: directory.to_path_buf(),
+            executables: executables.iter().map(|name| name.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_find_shadows() {
+        let temp_dir = TempDir::new().unwrap();
+        let first = temp_dir.path().join("first");
+        let second = temp_dir.path().join("second");
+        let linked = temp_dir.path().join("linked");
+        for dir in [&first, &second] {
+            fs::create_dir_all(dir).unwrap();
+            fs::write(dir.join("python3"), "").unwrap();
+        }
+        fs::write(first.join("tool"), "").unwrap();
+        std::os::unix::fs::symlink(&first, &linked).unwrap();
+
+        let listings = vec![
+            listing(&first, &["python3", "tool"]),
+            listing(&second, &["python3"]),
+            listing(&first, &["python3", "tool"]),
+            // Same files as `first` through a symlinked directory
+            listing(&linked, &["python3", "tool"]),
+        ];
+
+        assert_eq!(
+            find_shadows(&listings),
+            vec![Shadow {
+                name: "python3".to_string(),
+                copies: vec![first.join("python3"), second.join("python3")],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_first_line() {
+        assert_eq!(
+            first_line(b"\nPython 3.12.1\nmore\n").as_deref(),
+            Some("Python 3.12.1")
+        );
+        assert_eq!(first_line(b"  \n"), None);
+    }
+}
@@ -0,0 +1,304 @@
+//! Command implementation for showing everything known about one PATH entry.
+//!
+//! This module provides functionality to:
+//! - Show an entry's position, canonical path and status
+//! - Show its permissions and how many executables it holds
+//! - Find the shell config lines that declare it
+//! - Name the package manager that owns it
+//! - Find the first backup it appears in
+
+use crate::backup::core::{get_backup_dir, list_backups, BackupEntry};
+use crate::backup::restore::load_backup;
+use crate::commands::list::entry_status;
+use crate::msg;
+use crate::utils::attribution::Layout;
+use crate::utils::cache::ExecutableCache;
+use crate::utils::path_scanner::{self, PathScanner};
+use crate::utils::{self, find_path_entry};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Executes the show command for a single directory
+///
+/// # Arguments
+///
+/// * `directory` - The directory to describe; it doesn't have to be in PATH
+///
+/// # Example
+///
+/// ```
+/// commands::show::execute("~/.cargo/bin");
+/// // Output example:
+/// // /home/user/.cargo/bin
+/// //   Position:     3 of 12
+/// //   Status:       valid
+/// //   Owner:        cargo
+/// ```
+pub fn execute(directory: &str) {
+    let dir = utils::expand_path(directory);
+    let path_entries = utils::get_path_entries();
+    let home = dirs_next::home_dir();
+    let label = |id: &str| msg!(id);
+    let width = [
+        "show-position",
+        "show-canonical",
+        "show-status",
+        "show-permissions",
+        "show-executables",
+        "show-owner",
+        "show-declared-in",
+        "show-first-backup",
+    ]
+    .iter()
+    .map(|id| label(id).chars().count() + 1)
+    .max()
+    .unwrap_or_default();
+
+    println!("{}", dir.display());
+
+    let positions: Vec<String> = path_entries
+        .iter()
+        .enumerate()
+        .filter(|(_, entry)| find_path_entry(std::slice::from_ref(&dir), entry).is_some())
+        .map(|(index, _)| (index + 1).to_string())
+        .collect();
+    let position = if positions.is_empty() {
+        msg!("show-not-in-path")
+    } else {
+        msg!(
+            "show-position-value",
+            positions = positions.join(", "),
+            count = path_entries.len()
+        )
+    };
+    println!(
+        "  {:<width$}{}",
+        label("show-position"),
+        position,
+        width = width
+    );
+
+    if let Ok(canonical) = fs::canonicalize(&dir) {
+        if canonical != dir {
+            println!(
+                "  {:<width$}{}",
+                label("show-canonical"),
+                canonical.display(),
+                width = width
+            );
+        }
+    }
+    println!(
+        "  {:<width$}{}",
+        label("show-status"),
+        entry_status(&dir),
+        width = width
+    );
+
+    if let Some(permissions) = describe_permissions(&dir) {
+        println!(
+            "  {:<width$}{}",
+            label("show-permissions"),
+            permissions,
+            width = width
+        );
+    }
+
+    if dir.is_dir() {
+        let mut cache = ExecutableCache::load();
+        let listings = path_scanner::scan_directories(std::slice::from_ref(&dir), &mut cache);
+        if let Err(e) = cache.save() {
+            eprintln!("{}", msg!("warning-cache", error = e));
+        }
+        let count = listings
+            .first()
+            .map_or(0, |listing| listing.executables.len());
+        println!(
+            "  {:<width$}{}",
+            label("show-executables"),
+            count,
+            width = width
+        );
+    }
+
+    let owner = Layout::from_env()
+        .attribute(&dir)
+        .map(|ecosystem| ecosystem.to_string())
+        .unwrap_or_else(|| "-".to_string());
+    println!("  {:<width$}{}", label("show-owner"), owner, width = width);
+
+    let declarations: Vec<String> = PathScanner::new()
+        .scan_all()
+        .unwrap_or_default()
+        .iter()
+        .filter(|location| line_mentions(location.content(), &dir, home.as_deref()))
+        .map(|location| {
+            format!(
+                "{}:{} - {}",
+                location.file().display(),
+                location.line_number(),
+                location.content().trim()
+            )
+        })
+        .collect();
+    if declarations.is_empty() {
+        println!(
+            "  {:<width$}{}",
+            label("show-declared-in"),
+            msg!("show-not-declared"),
+            width = width
+        );
+    }
+    for (index, declaration) in declarations.iter().enumerate() {
+        let heading = if index == 0 {
+            label("show-declared-in")
+        } else {
+            String::new()
+        };
+        println!("  {:<width$}{}", heading, declaration, width = width);
+    }
+
+    let backups = get_backup_dir()
+        .and_then(|backup_dir| list_backups(&backup_dir))
+        .unwrap_or_default();
+    let first_seen = match first_backup_with(&backups, &dir) {
+        Some(backup) => format!(
+            "{} ({})",
+            backup.taken.format("%Y-%m-%d %H:%M:%S"),
+            backup.file.display()
+        ),
+        None => msg!("show-no-backup"),
+    };
+    println!(
+        "  {:<width$}{}",
+        label("show-first-backup"),
+        first_seen,
+        width = width
+    );
+}
+
+/// Formats a directory's permissions like `ls -l`, with the octal mode
+#[cfg(unix)]
+fn describe_permissions(dir: &Path) -> Option<String> {
+    use std::os::unix::fs::PermissionsExt;
+    let mode = fs::metadata(dir).ok()?.permissions().mode();
+    let mut symbolic = String::from(if dir.is_dir() { "d" } else { "-" });
+    for shift in [6, 3, 0] {
+        let bits = (mode >> shift) & 0o7;
+        symbolic.push(if bits & 0o4 != 0 { 'r' } else { '-' });
+        symbolic.push(if bits & 0o2 != 0 { 'w' } else { '-' });
+        symbolic.push(if bits & 0o1 != 0 { 'x' } else { '-' });
+    }
+    let mut description = format!("{} ({:o})", symbolic, mode & 0o7777);
+    if mode & 0o002 != 0 {
+        description.push_str(&format!(", {}", msg!("show-world-writable")));
+    }
+    Some(description)
+}
+
+/// Reports only whether the directory is read-only
+#[cfg(not(unix))]
+fn describe_permissions(dir: &Path) -> Option<String> {
+    let readonly = fs::metadata(dir).ok()?.permissions().readonly();
+    Some(if readonly {
+        msg!("show-read-only")
+    } else {
+        msg!("show-writable")
+    })
+}
+
+/// Returns whether a shell config line names `dir`, as written or via `~` or `$HOME`
+fn line_mentions(line: &str, dir: &Path, home: Option<&Path>) -> bool {
+    let mut spellings = vec![dir.to_string_lossy().into_owned()];
+    if let Some(relative) = home.and_then(|home| dir.strip_prefix(home).ok()) {
+        let relative = relative.to_string_lossy();
+        for prefix in ["~", "$HOME", "${HOME}"] {
+            spellings.push(format!("{}/{}", prefix, relative));
+        }
+    }
+
+    spellings.iter().any(|spelling| {
+        line.match_indices(spelling.as_str()).any(|(start, _)| {
+            // The next character must end the entry, so /usr/bin doesn't match /usr/bin2
+            let rest = &line[start + spelling.len()..];
+            let rest = rest.strip_prefix('/').unwrap_or(rest);
+            let ends_entry = rest
+                .chars()
+                .next()
+                .is_none_or(|next| matches!(next, ':' | '"' | '\'' | ' ' | ')' | ';'));
+            let before = line[..start].chars().next_back();
+            ends_entry && !before.is_some_and(|c| c.is_alphanumeric() || c == '/')
+        })
+    })
+}
+
+/// Returns the oldest backup whose PATH contains `dir`
+fn first_backup_with<'a>(backups: &'a [BackupEntry], dir: &Path) -> Option<&'a BackupEntry> {
+    backups.iter().find(|backup| {
+        load_backup(&backup.file).is_ok_and(|saved| {
+            let entries: Vec<PathBuf> = env::split_paths(&saved.path_os()).collect();
+            find_path_entry(&entries, dir).is_some()
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDateTime;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_line_mentions() {
+        let home = Some(Path::new("/home/user"));
+        let dir = Path::new("/home/user/.cargo/bin");
+
+        assert!(line_mentions(
+            r#"export PATH="$HOME/.cargo/bin:$PATH""#,
+            dir,
+            home
+        ));
+        assert!(line_mentions("path+=(~/.cargo/bin)", dir, home));
+        assert!(line_mentions("PATH=/home/user/.cargo/bin", dir, home));
+        assert!(!line_mentions(
+            "export PATH=$HOME/.cargo/bin2:$PATH",
+            dir,
+            home
+        ));
+        assert!(!line_mentions(
+            "export PATH=/usr/bin:$PATH",
+            Path::new("/bin"),
+            home
+        ));
+        assert!(!line_mentions(
+            "export PATH=$HOME/.cargo/bin/extra:$PATH",
+            dir,
+            home
+        ));
+    }
+
+    #[test]
+    fn test_first_backup_with() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut backups = Vec::new();
+        for (timestamp, path) in [
+            ("20240301000000", "/usr/bin"),
+            ("20240302000000", "/pm-test/sdk:/usr/bin"),
+            ("20240303000000", "/pm-test/sdk"),
+        ] {
+            let file = temp_dir.path().join(format!("backup_{}.json", timestamp));
+            let backup = format!(r#"{{"timestamp": "{}", "path": "{}"}}"#, timestamp, path);
+            fs::write(&file, backup).unwrap();
+            backups.push(BackupEntry {
+                file,
+                taken: NaiveDateTime::parse_from_str(timestamp, "%Y%m%d%H%M%S").unwrap(),
+                shell_only: false,
+            });
+        }
+
+        let first = first_backup_with(&backups, Path::new("/pm-test/sdk")).unwrap();
+        assert_eq!(first.taken.format("%d").to_string(), "02");
+        assert!(first_backup_with(&backups, Path::new("/pm-test/other")).is_none());
+    }
+}
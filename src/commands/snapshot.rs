@@ -0,0 +1,258 @@
+//! Command implementation for taking a backup without changing anything.
+//!
+//! This module provides functionality to:
+//! - Back up the current PATH, optionally under a name
+//! - Save the shell configs PATH is written to in the same backup
+//! - Follow the backup mode to choose what is saved
+//! - Take one automatic snapshot a day, or schedule them with a timer
+
+use crate::backup;
+use crate::backup::mode::BackupMode;
+use crate::msg;
+use crate::utils::shell::locations;
+use chrono::Local;
+use std::env;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// Label of snapshots taken by the `auto-snapshot` setting
+pub const AUTO_SNAPSHOT_LABEL: &str = "daily";
+
+/// Label of snapshots taken by the installed timer
+pub const SCHEDULED_LABEL: &str = "scheduled";
+
+/// Name of the systemd user units installed by `--install-timer`
+const TIMER_UNIT: &str = "pathmaster-snapshot";
+
+/// Comment marking the crontab line installed by `--install-timer`
+const CRON_MARKER: &str = "# pathmaster scheduled snapshot";
+
+/// Executes the snapshot command
+///
+/// # Arguments
+///
+/// * `name` - Optional label stored with the PATH backup
+/// * `mode` - What to back up
+///
+/// # Example
+///
+/// ```
+/// commands::snapshot::execute(Some("before-installer"), BackupMode::Both);
+/// // Output example:
+/// // Creating backup at: "~/.pathmaster/backups/backup_20240321120000.json"
+/// // Saved shell config /home/user/.bashrc in the backup
+/// ```
+pub fn execute(name: Option<&str>, mode: BackupMode) {
+    let saved = match backup::create_backup_in_mode(name, mode) {
+        Ok((_, saved)) => saved,
+        Err(e) => {
+            eprintln!("{}", msg!("error-backup", error = e));
+            return;
+        }
+    };
+
+    for copy in &saved.shell_configs {
+        println!("{}", msg!("snapshot-saved-config", file = copy.file));
+    }
+    if mode.should_backup_shell() && saved.shell_configs.is_empty() {
+        println!("{}", msg!("snapshot-no-config"));
+    }
+
+    println!("{}", msg!("snapshot-taken"));
+    if let Some(name) = name {
+        println!("{}", msg!("snapshot-named", name = name));
+    }
+}
+
+/// Takes a snapshot on the first run of the day when `auto-snapshot` is on
+///
+/// The snapshot is taken quietly: the command that triggered it owns
+/// standard output, which scripts may be parsing. Failures go to stderr.
+///
+/// # Arguments
+///
+/// * `mode` - What to back up
+pub fn auto_snapshot(mode: BackupMode) {
+    let stamp = auto_snapshot_stamp();
+    let today = Local::now().format("%Y-%m-%d").to_string();
+    if fs::read_to_string(&stamp).is_ok_and(|day| day.trim() == today) {
+        return;
+    }
+
+    if let Err(e) = backup::core::create_quiet_backup(Some(AUTO_SNAPSHOT_LABEL), mode) {
+        eprintln!("{}", msg!("error-backup", error = e));
+        return;
+    }
+
+    let result = stamp
+        .parent()
+        .map_or(Ok(()), fs::create_dir_all)
+        .and_then(|_| fs::write(&stamp, format!("{}\n", today)));
+    if let Err(e) = result {
+        eprintln!("{}", msg!("snapshot-auto-stamp-failed", error = e));
+    }
+}
+
+/// Returns the file holding the date of the last automatic snapshot
+fn auto_snapshot_stamp() -> PathBuf {
+    locations::xdg_state_home()
+        .join("pathmaster")
+        .join("last-auto-snapshot")
+}
+
+/// Schedules a daily snapshot with a systemd user timer, or cron without systemd
+pub fn install_timer() {
+    let result = env::current_exe().and_then(|exe| {
+        if Path::new("/run/systemd/system").exists() {
+            install_systemd_timer(&exe)
+        } else {
+            install_cron_entry(&exe)
+        }
+    });
+    if let Err(e) = result {
+        eprintln!("{}", msg!("snapshot-timer-failed", error = e));
+    }
+}
+
+/// Returns the command the timer runs.
+///
+/// Scheduled runs don't inherit a login shell's PATH, so only the shell
+/// configs are backed up.
+fn scheduled_command(exe: &Path) -> String {
+    format!(
+        "\"{}\" --backup-mode shell snapshot --name {}",
+        exe.display(),
+        SCHEDULED_LABEL
+    )
+}
+
+/// Formats the systemd service and timer units
+fn systemd_units(exe: &Path) -> (String, String) {
+    let service = format!(
+        "[Unit]\nDescription=Take a pathmaster snapshot of the shell configs\n\n\
+         [Service]\nType=oneshot\nExecStart={}\n",
+        scheduled_command(exe)
+    );
+    let timer = "[Unit]\nDescription=Daily pathmaster snapshot\n\n\
+                 [Timer]\nOnCalendar=daily\nPersistent=true\n\n\
+                 [Install]\nWantedBy=timers.target\n"
+        .to_string();
+    (service, timer)
+}
+
+fn install_systemd_timer(exe: &Path) -> io::Result<()> {
+    let unit_dir = locations::xdg_config_home().join("systemd/user");
+    fs::create_dir_all(&unit_dir)?;
+    let (service, timer) = systemd_units(exe);
+    fs::write(unit_dir.join(format!("{}.service", TIMER_UNIT)), service)?;
+    fs::write(unit_dir.join(format!("{}.timer", TIMER_UNIT)), timer)?;
+    println!(
+        "{}",
+        msg!("snapshot-wrote-units", unit = TIMER_UNIT, dir = unit_dir)
+    );
+
+    for args in [
+        vec!["--user", "daemon-reload"],
+        vec![
+            "--user",
+            "enable",
+            "--now",
+            &format!("{}.timer", TIMER_UNIT),
+        ],
+    ] {
+        let status = Command::new("systemctl").args(&args).status()?;
+        if !status.success() {
+            return Err(io::Error::other(format!(
+                "systemctl {} failed ({})",
+                args.join(" "),
+                status
+            )));
+        }
+    }
+    println!("{}", msg!("snapshot-timer-scheduled"));
+    Ok(())
+}
+
+/// Returns the crontab with the snapshot line added, or `None` if it's already there
+fn add_cron_line(crontab: &str, exe: &Path) -> Option<String> {
+    if crontab.lines().any(|line| line.contains(CRON_MARKER)) {
+        return None;
+    }
+    let mut updated = crontab.to_string();
+    if !updated.is_empty() && !updated.ends_with('\n') {
+        updated.push('\n');
+    }
+    updated.push_str(&format!(
+        "@daily {} {}\n",
+        scheduled_command(exe),
+        CRON_MARKER
+    ));
+    Some(updated)
+}
+
+fn install_cron_entry(exe: &Path) -> io::Result<()> {
+    // `crontab -l` fails when the user has no crontab yet
+    let current = Command::new("crontab")
+        .arg("-l")
+        .stderr(Stdio::null())
+        .output()
+        .map_err(|e| match e.kind() {
+            io::ErrorKind::NotFound => io::Error::new(
+                e.kind(),
+                "neither systemd nor crontab is available to schedule snapshots",
+            ),
+            _ => e,
+        })?;
+    let crontab = if current.status.success() {
+        String::from_utf8_lossy(&current.stdout).into_owned()
+    } else {
+        String::new()
+    };
+
+    let Some(updated) = add_cron_line(&crontab, exe) else {
+        println!("{}", msg!("snapshot-cron-exists"));
+        return Ok(());
+    };
+
+    let mut child = Command::new("crontab")
+        .arg("-")
+        .stdin(Stdio::piped())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(updated.as_bytes())?;
+    }
+    let status = child.wait()?;
+    if !status.success() {
+        return Err(io::Error::other(format!("crontab failed ({})", status)));
+    }
+    println!("{}", msg!("snapshot-cron-added"));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_cron_line() {
+        let exe = Path::new("/usr/local/bin/pathmaster");
+        let updated = add_cron_line("MAILTO=me\n0 * * * * backup.sh", exe).unwrap();
+        assert_eq!(
+            updated,
+            "MAILTO=me\n0 * * * * backup.sh\n@daily \"/usr/local/bin/pathmaster\" \
+             --backup-mode shell snapshot --name scheduled # pathmaster scheduled snapshot\n"
+        );
+        assert_eq!(add_cron_line(&updated, exe), None);
+    }
+
+    #[test]
+    fn test_systemd_units() {
+        let (service, timer) = systemd_units(Path::new("/opt/pathmaster"));
+        assert!(service.contains(
+            "ExecStart=\"/opt/pathmaster\" --backup-mode shell snapshot --name scheduled\n"
+        ));
+        assert!(timer.contains("OnCalendar=daily\nPersistent=true\n"));
+    }
+}
@@ -0,0 +1,102 @@
+//! Command implementation for showing PATH status in shell prompts.
+//!
+//! `pathmaster status --prompt` prints a compact marker such as `✗2` when
+//! the last saved check found problems, for PS1 or a starship custom
+//! module. It only reads the saved result and never checks anything, so it
+//! adds no noticeable delay to the prompt. This module provides
+//! functionality to:
+//! - Count the problems of the last saved check (see `check --quiet`)
+//! - Leave out problems whose entry has since been removed from PATH
+
+use crate::commands::validator;
+use crate::utils::last_check::{LastCheck, Problem};
+use std::env;
+use std::path::PathBuf;
+
+/// Marker shown before the number of problems
+const PROBLEM_MARKER: &str = "✗";
+
+/// Problem kinds that are about a file or the system PATH rather than an
+/// entry of this PATH
+const NON_ENTRY_KINDS: &[&str] = &["modified", "system-missing"];
+
+/// Problems, as (kind, detail), about an entry that belongs in PATH but
+/// isn't there
+const ABSENT_ENTRY_PROBLEMS: &[(&str, &str)] = &[
+    ("homebrew", "not-in-path"),
+    ("conditional", "missing"),
+    ("policy", "required"),
+];
+
+/// Executes the status command to print the prompt marker
+///
+/// Prints nothing when there are no problems or no check was saved, so
+/// the prompt stays unchanged.
+///
+/// # Example
+///
+/// ```
+/// commands::status::execute();
+/// // Output example:
+/// // ✗2
+/// ```
+pub fn execute() {
+    let Some(last) = LastCheck::load() else {
+        return;
+    };
+
+    let entries = validator::split_path_value(env::var_os("PATH").unwrap_or_default());
+    let count = last
+        .problems
+        .iter()
+        .filter(|problem| still_applies(problem, &entries))
+        .count();
+    if count > 0 {
+        println!("{}{}", PROBLEM_MARKER, count);
+    }
+}
+
+/// Whether a saved problem still applies to the current PATH entries
+pub fn still_applies(problem: &Problem, entries: &[PathBuf]) -> bool {
+    let absent_entry = ABSENT_ENTRY_PROBLEMS
+        .iter()
+        .any(|&(kind, detail)| problem.kind == kind && problem.detail == detail);
+    if absent_entry {
+        return !entries.contains(&problem.path);
+    }
+    NON_ENTRY_KINDS.contains(&problem.kind.as_str()) || entries.contains(&problem.path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_support::problem;
+
+    #[test]
+    fn test_still_applies() {
+        let entries = vec![PathBuf::from("/usr/bin"), PathBuf::from("/gone")];
+
+        assert!(still_applies(&problem("missing", "/gone"), &entries));
+        assert!(!still_applies(&problem("missing", "/removed"), &entries));
+        assert!(still_applies(
+            &problem("modified", "/home/user/.bashrc"),
+            &entries
+        ));
+
+        let mut not_in_path = problem("homebrew", "/opt/homebrew/bin");
+        not_in_path.detail = "not-in-path".to_string();
+        assert!(still_applies(&not_in_path, &entries));
+        assert!(!still_applies(
+            &not_in_path,
+            &[PathBuf::from("/opt/homebrew/bin")]
+        ));
+
+        let mut conditional = problem("conditional", "/home/user/bin");
+        conditional.detail = "missing".to_string();
+        assert!(still_applies(&conditional, &entries));
+        assert!(!still_applies(
+            &conditional,
+            &[PathBuf::from("/home/user/bin")]
+        ));
+    }
+}
@@ -0,0 +1,198 @@
+//! Command implementation for undoing and redoing PATH operations.
+//!
+//! This module provides functionality to:
+//! - Revert the most recent operations recorded in the operation log
+//! - Reapply operations that were undone
+//! - Refuse when PATH has changed since, unless forced
+
+use crate::backup;
+use crate::msg;
+use crate::utils::audit::{self, AuditRecord};
+use crate::utils::{self, PathSet};
+use std::path::PathBuf;
+
+/// Direction of a history move
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Direction {
+    Undo,
+    Redo,
+}
+
+impl Direction {
+    /// Command name, e.g. "undo"; messages select their wording by it
+    fn name(self) -> &'static str {
+        match self {
+            Direction::Undo => "undo",
+            Direction::Redo => "redo",
+        }
+    }
+}
+
+/// Executes the undo command to revert the last `steps` operations
+///
+/// # Arguments
+///
+/// * `steps` - Number of operations to revert
+/// * `force` - Revert even if PATH changed since the last operation
+///
+/// # Example
+///
+/// ```
+/// commands::undo::undo(1, false);
+/// // Output example:
+/// // Undoing: pathmaster add ~/bin (2024-03-21 12:00:00)
+/// ```
+pub fn undo(steps: usize, force: bool) {
+    run(Direction::Undo, steps, force);
+}
+
+/// Executes the redo command to reapply the last `steps` undone operations
+///
+/// # Arguments
+///
+/// * `steps` - Number of operations to reapply
+/// * `force` - Reapply even if PATH changed since the last undo
+pub fn redo(steps: usize, force: bool) {
+    run(Direction::Redo, steps, force);
+}
+
+fn run(direction: Direction, steps: usize, force: bool) {
+    let records = match audit::read_all() {
+        Ok(records) => records,
+        Err(e) => {
+            eprintln!("{}", msg!("undo-log-unreadable", error = e));
+            return;
+        }
+    };
+
+    let stacks = audit::undo_stacks(&records);
+    let available = match direction {
+        Direction::Undo => &stacks.done,
+        Direction::Redo => &stacks.undone,
+    };
+    if steps == 0 || available.is_empty() {
+        println!("{}", msg!("undo-nothing", command = direction.name()));
+        return;
+    }
+    if steps > available.len() {
+        eprintln!(
+            "{}",
+            msg!(
+                "undo-too-many",
+                command = direction.name(),
+                count = available.len()
+            )
+        );
+        return;
+    }
+
+    // Most recent first for undo; next to reapply first for redo
+    let ids: Vec<usize> = available.iter().rev().take(steps).copied().collect();
+    let first = &records[ids[0]];
+    let last = &records[ids[ids.len() - 1]];
+    let (expected, target) = match direction {
+        Direction::Undo => (first.after_paths(), last.before_paths()),
+        Direction::Redo => (first.before_paths(), last.after_paths()),
+    };
+
+    let current = utils::get_path_entries();
+    if let Some(conflict) = find_conflict(&current, &expected, &target) {
+        if !force {
+            eprintln!("{}", msg!("undo-conflict"));
+            eprintln!("{}", conflict);
+            eprintln!("{}", msg!("undo-force-hint"));
+            return;
+        }
+        println!("{}", msg!("undo-overwriting"));
+    }
+
+    for id in &ids {
+        let record = &records[*id];
+        println!(
+            "{}",
+            msg!(
+                "undo-reverting",
+                command = direction.name(),
+                arguments = record.arguments.join(" "),
+                timestamp = record.timestamp
+            )
+        );
+    }
+
+    if let Err(e) = backup::create_backup() {
+        eprintln!("{}", msg!("error-backup", error = e));
+        return;
+    }
+
+    if let Err(e) = PathSet::new(target.clone()).apply() {
+        eprintln!("{}", msg!("error-shell-config", error = e));
+        return;
+    }
+
+    let mut record = AuditRecord::new(
+        direction.name(),
+        audit::command_arguments(),
+        &current,
+        &target,
+        backup::core::last_backup(),
+    );
+    match direction {
+        Direction::Undo => record.undoes = ids,
+        Direction::Redo => record.redoes = ids,
+    }
+    if let Err(e) = audit::append(&record) {
+        eprintln!("{}", msg!("warning-operation-log", error = e));
+    }
+
+    println!("{}", msg!("undo-done"));
+}
+
+/// Checks that PATH is still in the state the history move starts from.
+///
+/// PATH may also already equal the target: a shell that wasn't re-sourced
+/// after an operation still has the PATH from before it, and moving back
+/// there only needs the shell config rewritten.
+///
+/// # Returns
+/// * `None` if PATH matches, or a description of the differences
+fn find_conflict(current: &[PathBuf], expected: &[PathBuf], target: &[PathBuf]) -> Option<String> {
+    if current == expected || current == target {
+        return None;
+    }
+
+    let mut lines = Vec::new();
+    for entry in current.iter().filter(|entry| !expected.contains(entry)) {
+        lines.push(format!("  + {}", entry.display()));
+    }
+    for entry in expected.iter().filter(|entry| !current.contains(entry)) {
+        lines.push(format!("  - {}", entry.display()));
+    }
+    if lines.is_empty() {
+        lines.push(format!("  ~ {}", msg!("undo-reordered")));
+    }
+    Some(lines.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_support::paths;
+    use serial_test::serial;
+
+    #[test]
+    #[serial]
+    fn test_find_conflict() {
+        crate::utils::messages::set_language(Some("en")).unwrap();
+        let expected = paths(&["/new", "/usr/bin"]);
+        let target = paths(&["/usr/bin"]);
+
+        assert_eq!(find_conflict(&expected, &expected, &target), None);
+        assert_eq!(find_conflict(&target, &expected, &target), None);
+
+        let conflict = find_conflict(&paths(&["/new", "/usr/bin", "/other"]), &expected, &target);
+        assert_eq!(conflict.as_deref(), Some("  + /other"));
+
+        let conflict = find_conflict(&paths(&["/usr/bin", "/new"]), &expected, &target);
+        assert_eq!(conflict.as_deref(), Some("  ~ entries reordered"));
+    }
+}
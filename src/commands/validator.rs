@@ -1,10 +1,13 @@
 //! Path validation functionality for the pathmaster tool.
 //!
 //! This module provides functionality to validate directories in the PATH
-//! environment variable, separating them into existing and missing directories.
-//! It handles validation of both individual paths and the complete PATH.
+//! environment variable, separating them into existing directories, missing
+//! directories, and paths that exist but aren't directories.
+//! It handles validation of both individual paths and the complete PATH,
+//! whether taken from the environment or from a PATH string or dump file.
 
 use std::env;
+use std::ffi::OsStr;
 use std::path::{Path, PathBuf};
 
 /// Represents the validation results of PATH directories.
@@ -14,6 +17,12 @@ pub struct PathValidation {
     pub existing_dirs: Vec<PathBuf>,
     /// Directories that are in PATH but don't exist
     pub missing_dirs: Vec<PathBuf>,
+    /// Paths in PATH that exist but aren't directories (e.g. a stray file name)
+    pub not_dirs: Vec<PathBuf>,
+    /// Relative entries such as `.` or `bin`, which depend on the current directory
+    pub relative_dirs: Vec<PathBuf>,
+    /// Number of empty entries from leading, trailing, or doubled colons
+    pub empty_entries: usize,
 }
 
 /// Validates whether a path is a valid directory for PATH inclusion.
@@ -34,60 +43,114 @@ impl PathValidation {
         PathValidation {
             existing_dirs: Vec::new(),
             missing_dirs: Vec::new(),
+            not_dirs: Vec::new(),
+            relative_dirs: Vec::new(),
+            empty_entries: 0,
         }
     }
 
     /// Adds a path to the appropriate list based on its validity.
     ///
+    /// Empty and relative entries are recorded as such regardless of whether
+    /// they currently resolve to a directory, since that depends on where the
+    /// shell happens to be.
+    ///
     /// # Arguments
     /// * `path` - The path to validate and add
     pub fn add_path(&mut self, path: PathBuf) {
-        if is_valid_path_entry(&path) {
+        if path.as_os_str().is_empty() {
+            self.empty_entries += 1;
+        } else if path.is_relative() {
+            self.relative_dirs.push(path);
+        } else if is_valid_path_entry(&path) {
             self.existing_dirs.push(path);
+        } else if path.exists() {
+            self.not_dirs.push(path);
         } else {
             self.missing_dirs.push(path);
         }
     }
 
-    /// Returns the total number of directories (both valid and invalid).
+    /// Returns whether every entry is an existing directory.
+    pub fn is_valid(&self) -> bool {
+        self.missing_dirs.is_empty()
+            && self.not_dirs.is_empty()
+            && self.relative_dirs.is_empty()
+            && self.empty_entries == 0
+    }
+
+    /// Returns the total number of entries (both valid and invalid).
     #[allow(dead_code)]
     pub fn total_dirs(&self) -> usize {
-        self.existing_dirs.len() + self.missing_dirs.len()
+        self.existing_dirs.len()
+            + self.missing_dirs.len()
+            + self.not_dirs.len()
+            + self.relative_dirs.len()
+            + self.empty_entries
     }
 }
 
-/// Validates all directories in the current PATH environment variable.
+/// Validates a list of PATH entries, such as one read from a file.
 ///
-/// # Returns
-/// * `Ok(PathValidation)` - Validation results with existing and missing directories
-/// * `Err(std::io::Error)` - If there are problems accessing the filesystem
-pub fn validate_path() -> std::io::Result<PathValidation> {
+/// # Arguments
+/// * `entries` - The entries to validate, in PATH order
+pub fn validate_entries(entries: Vec<PathBuf>) -> PathValidation {
     let mut validation = PathValidation::new();
-
-    // Get PATH entries, return empty validation if PATH is unset or empty
-    let path_var = match env::var_os("PATH") {
-        Some(path) => {
-            let path_str = path.to_string_lossy();
-            if path_str.trim().is_empty() {
-                return Ok(validation);
-            }
-            path
-        }
-        None => return Ok(validation),
-    };
-
-    // Process each PATH entry
-    for entry in env::split_paths(&path_var) {
-        if !entry.as_os_str().is_empty() {
-            validation.add_path(entry);
-        }
+    for entry in entries {
+        validation.add_path(entry);
     }
 
     // Sort for consistent output
     validation.existing_dirs.sort();
     validation.missing_dirs.sort();
+    validation.not_dirs.sort();
+    validation.relative_dirs.sort();
+
+    validation
+}
+
+/// Splits a colon-separated PATH value into entries.
+///
+/// An unset or blank PATH has no entries, rather than one empty entry.
+pub fn split_path_value<S: AsRef<OsStr>>(value: S) -> Vec<PathBuf> {
+    let value = value.as_ref();
+    if value.to_string_lossy().trim().is_empty() {
+        return Vec::new();
+    }
+    env::split_paths(value).collect()
+}
+
+/// Extracts PATH entries from saved text.
+///
+/// Accepts a file holding just the PATH value, an environment dump (as
+/// printed by `env` or `export -p`) containing a `PATH=` line, or one
+/// entry per line. Blank lines and `#` comments are ignored.
+pub fn parse_path_dump(content: &str) -> Vec<PathBuf> {
+    let lines: Vec<&str> = content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .collect();
+
+    let declared = lines.iter().find_map(|line| {
+        let line = line.strip_prefix("declare -x ").unwrap_or(line);
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        line.strip_prefix("PATH=")
+    });
+
+    match (declared, lines.as_slice()) {
+        (Some(value), _) => split_path_value(unquote(value)),
+        (None, [value]) => split_path_value(value),
+        (None, _) => lines.iter().map(PathBuf::from).collect(),
+    }
+}
 
-    Ok(validation)
+/// Strips one pair of matching surrounding quotes
+fn unquote(value: &str) -> &str {
+    ['"', '\'']
+        .iter()
+        .find_map(|&quote| value.strip_prefix(quote)?.strip_suffix(quote))
+        .unwrap_or(value)
 }
 
 #[cfg(test)]
@@ -119,6 +182,31 @@ mod tests {
         validation.add_path(temp_dir.path().join("nonexistent"));
         assert_eq!(validation.existing_dirs.len(), 1);
         assert_eq!(validation.missing_dirs.len(), 1);
+
+        // Test with a file rather than a directory
+        let file_path = temp_dir.path().join("tool");
+        std::fs::write(&file_path, "").unwrap();
+        validation.add_path(file_path.clone());
+        assert_eq!(validation.not_dirs, vec![file_path]);
+        assert_eq!(validation.missing_dirs.len(), 1);
+        assert!(!validation.is_valid());
+    }
+
+    #[test]
+    fn test_relative_and_empty_entries() {
+        let mut validation = PathValidation::new();
+        validation.add_path(PathBuf::from(""));
+        validation.add_path(PathBuf::from("."));
+        validation.add_path(PathBuf::from("node_modules/.bin"));
+
+        assert_eq!(validation.empty_entries, 1);
+        assert_eq!(
+            validation.relative_dirs,
+            vec![PathBuf::from("."), PathBuf::from("node_modules/.bin")]
+        );
+        assert!(validation.existing_dirs.is_empty());
+        assert!(!validation.is_valid());
+        assert_eq!(validation.total_dirs(), 3);
     }
 
     #[test]
@@ -128,6 +216,40 @@ mod tests {
 
         validation.existing_dirs.push(PathBuf::from("/valid"));
         validation.missing_dirs.push(PathBuf::from("/invalid"));
-        assert_eq!(validation.total_dirs(), 2);
+        validation.not_dirs.push(PathBuf::from("/usr/bin/ls"));
+        assert_eq!(validation.total_dirs(), 3);
+    }
+
+    #[test]
+    fn test_parse_path_dump() {
+        let expected = vec![PathBuf::from("/usr/bin"), PathBuf::from("/opt/My Tools")];
+
+        assert_eq!(parse_path_dump("/usr/bin:/opt/My Tools\n"), expected);
+        assert_eq!(
+            parse_path_dump("HOME=/home/user\nPATH=/usr/bin:/opt/My Tools\nSHELL=/bin/bash\n"),
+            expected
+        );
+        assert_eq!(
+            parse_path_dump("declare -x PATH=\"/usr/bin:/opt/My Tools\"\n"),
+            expected
+        );
+        assert_eq!(
+            parse_path_dump("# PATH from CI\n/usr/bin\n\n/opt/My Tools\n"),
+            expected
+        );
+        assert!(parse_path_dump("").is_empty());
+    }
+
+    #[test]
+    fn test_validate_entries() {
+        let temp_dir = TempDir::new().unwrap();
+        let value = format!("{}::missing", temp_dir.path().display());
+
+        let validation = validate_entries(split_path_value(&value));
+        assert_eq!(validation.existing_dirs.len(), 1);
+        assert_eq!(validation.empty_entries, 1);
+        assert_eq!(validation.relative_dirs, vec![PathBuf::from("missing")]);
+        assert_eq!(validation.total_dirs(), 3);
+        assert_eq!(split_path_value(" "), Vec::<PathBuf>::new());
     }
 }
@@ -0,0 +1,197 @@
+//! Command implementation for detecting drift between PATH and the shell config.
+//!
+//! This module provides functionality to:
+//! - Compare the PATH declared in the shell config with the live PATH
+//! - Report entries missing from either side
+//! - Report entries that appear in a different order
+
+use crate::config::{self, WriteMode};
+use crate::msg;
+use crate::utils;
+use crate::utils::shell::{factory, spelling};
+use std::fs;
+use std::path::PathBuf;
+
+/// Differences between the PATH declared in a shell config and the live PATH
+#[derive(Debug, Default, PartialEq)]
+pub struct Drift {
+    /// Entries declared in the config but missing from PATH
+    pub missing_from_path: Vec<PathBuf>,
+    /// Entries in PATH that the config doesn't declare
+    pub missing_from_config: Vec<PathBuf>,
+    /// Entries present on both sides, in config order, when the order differs
+    pub config_order: Vec<PathBuf>,
+    /// The same entries in PATH order, when the order differs
+    pub path_order: Vec<PathBuf>,
+}
+
+impl Drift {
+    /// Returns whether PATH matches the config
+    pub fn is_in_sync(&self) -> bool {
+        self.missing_from_path.is_empty()
+            && self.missing_from_config.is_empty()
+            && self.config_order.is_empty()
+    }
+}
+
+/// Compares declared entries with the live PATH.
+///
+/// When the config extends `$PATH` instead of replacing it, entries only
+/// found in PATH are inherited rather than drift, so they aren't reported.
+///
+/// # Arguments
+/// * `declared` - Expanded entries declared in the config, in order
+/// * `extends_path` - Whether the config references the existing `$PATH`
+/// * `live` - Current PATH entries
+pub fn compare(declared: &[PathBuf], extends_path: bool, live: &[PathBuf]) -> Drift {
+    let declared = first_occurrences(declared);
+    let live = first_occurrences(live);
+
+    let missing_from_path = declared
+        .iter()
+        .filter(|entry| !live.contains(entry))
+        .cloned()
+        .collect();
+    let missing_from_config = if extends_path {
+        Vec::new()
+    } else {
+        live.iter()
+            .filter(|entry| !declared.contains(entry))
+            .cloned()
+            .collect()
+    };
+
+    let config_order: Vec<PathBuf> = declared
+        .iter()
+        .filter(|entry| live.contains(entry))
+        .cloned()
+        .collect();
+    let path_order: Vec<PathBuf> = live
+        .iter()
+        .filter(|entry| declared.contains(entry))
+        .cloned()
+        .collect();
+    let (config_order, path_order) = if config_order == path_order {
+        (Vec::new(), Vec::new())
+    } else {
+        (config_order, path_order)
+    };
+
+    Drift {
+        missing_from_path,
+        missing_from_config,
+        config_order,
+        path_order,
+    }
+}
+
+/// Removes repeated entries, keeping the first, which is the one that wins lookups
+fn first_occurrences(entries: &[PathBuf]) -> Vec<PathBuf> {
+    let mut unique: Vec<PathBuf> = Vec::new();
+    for entry in entries {
+        if !unique.contains(entry) {
+            unique.push(entry.clone());
+        }
+    }
+    unique
+}
+
+/// Executes the verify command to check that PATH matches the shell config
+///
+/// # Returns
+/// * `true` if PATH matches the config, `false` on drift or errors
+///
+/// # Example
+///
+/// ```
+/// commands::verify::execute();
+/// // Output example:
+/// // Declared in /home/user/.bashrc but not in PATH (re-source the config?):
+/// //   /home/user/.cargo/bin
+/// ```
+pub fn execute() -> bool {
+    let settings = config::get_config();
+    let handler = factory::get_shell_handler(settings.shell);
+    let config_path = match settings.write_mode {
+        WriteMode::Inline => handler.get_target_path(),
+        WriteMode::DropIn => config::config_dir().join(handler.drop_in_file_name()),
+    };
+
+    let content = match fs::read_to_string(&config_path) {
+        Ok(content) => content,
+        Err(e) => {
+            eprintln!("{}", msg!("error-read-file", file = config_path, error = e));
+            return false;
+        }
+    };
+
+    let raw_entries = handler.parse_raw_entries(&content);
+    let extends_path = raw_entries
+        .iter()
+        .any(|raw| spelling::is_self_reference(raw));
+    let declared: Vec<PathBuf> = raw_entries
+        .iter()
+        .filter_map(|raw| spelling::expand_entry(raw))
+        .collect();
+    let drift = compare(&declared, extends_path, &utils::get_path_entries());
+
+    if drift.is_in_sync() {
+        println!("{}", msg!("verify-in-sync", file = config_path));
+        return true;
+    }
+
+    if !drift.missing_from_path.is_empty() {
+        println!("{}", msg!("verify-missing-from-path", file = config_path));
+        for entry in &drift.missing_from_path {
+            println!("  {}", entry.display());
+        }
+    }
+    if !drift.missing_from_config.is_empty() {
+        println!("{}", msg!("verify-missing-from-config", file = config_path));
+        for entry in &drift.missing_from_config {
+            println!("  {}", entry.display());
+        }
+    }
+    if !drift.config_order.is_empty() {
+        println!("{}", msg!("verify-order"));
+        println!("  {}", msg!("verify-order-config"));
+        for entry in &drift.config_order {
+            println!("    {}", entry.display());
+        }
+        println!("  {}", msg!("verify-order-path"));
+        for entry in &drift.path_order {
+            println!("    {}", entry.display());
+        }
+    }
+
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_support::paths;
+
+    #[test]
+    fn test_compare_in_sync() {
+        let declared = paths(&["/usr/local/bin", "/usr/bin"]);
+        let live = paths(&["/usr/local/bin/", "/usr/bin", "/usr/local/bin"]);
+        assert!(compare(&declared, false, &live).is_in_sync());
+    }
+
+    #[test]
+    fn test_compare_reports_drift() {
+        let declared = paths(&["/opt/new/bin", "/usr/bin", "/bin"]);
+        let live = paths(&["/bin", "/usr/bin", "/tmp/runtime"]);
+        let drift = compare(&declared, false, &live);
+
+        assert_eq!(drift.missing_from_path, paths(&["/opt/new/bin"]));
+        assert_eq!(drift.missing_from_config, paths(&["/tmp/runtime"]));
+        assert_eq!(drift.config_order, paths(&["/usr/bin", "/bin"]));
+        assert_eq!(drift.path_order, paths(&["/bin", "/usr/bin"]));
+
+        // Entries inherited through $PATH aren't drift
+        let drift = compare(&paths(&["/usr/bin"]), true, &live);
+        assert!(drift.is_in_sync());
+    }
+}
@@ -0,0 +1,95 @@
+//! Command implementation for explaining where a PATH entry came from.
+//!
+//! This module provides functionality to:
+//! - Show when a directory was added, by which command, and why
+//! - Fall back to the operation log for entries added before provenance was kept
+//! - Say when pathmaster has no record of an entry
+
+use crate::msg;
+use crate::utils::audit;
+use crate::utils::provenance::ProvenanceStore;
+use crate::utils::{self, find_path_entry};
+use std::path::{Path, PathBuf};
+
+/// Executes the why command for a single directory
+///
+/// # Arguments
+///
+/// * `directory` - The directory to explain
+///
+/// # Example
+///
+/// ```
+/// commands::why::execute("~/cuda/bin");
+/// // Output example:
+/// // /home/user/cuda/bin was added on 2024-03-21 12:00:00 by: pathmaster --reason for CUDA 12 add ~/cuda/bin
+/// //   Reason: for CUDA 12
+/// ```
+pub fn execute(directory: &str) {
+    let dir = utils::expand_path(directory);
+    let in_path = find_path_entry(&utils::get_path_entries(), &dir).is_some();
+
+    let store = ProvenanceStore::load();
+    let expires = store
+        .find(&dir)
+        .and_then(|(_, provenance)| provenance.expires.clone());
+    let found = match store.find(&dir) {
+        Some((_, provenance)) => Some((
+            provenance.added.clone(),
+            provenance.arguments.join(" "),
+            provenance.reason.clone(),
+        )),
+        None => latest_logged_addition(&dir),
+    };
+
+    match found {
+        Some((added, arguments, reason)) => {
+            println!(
+                "{}",
+                msg!(
+                    "why-added",
+                    path = &dir,
+                    date = added,
+                    arguments = arguments
+                )
+            );
+            match reason {
+                Some(reason) => println!("  {}", msg!("why-reason", reason = reason)),
+                None => println!("  {}", msg!("why-no-reason")),
+            }
+            if let Some(expires) = expires {
+                println!("  {}", msg!("why-expires", date = expires));
+            }
+            if !in_path {
+                println!("  {}", msg!("why-gone"));
+            }
+        }
+        None if in_path => println!("{}", msg!("why-unrecorded", path = &dir)),
+        None => println!("{}", msg!("why-unknown", path = &dir)),
+    }
+}
+
+/// Finds the latest logged operation that added `dir`, for entries added
+/// before provenance was recorded
+///
+/// # Returns
+/// * `Some((timestamp, arguments, reason))` of the operation
+fn latest_logged_addition(dir: &Path) -> Option<(String, String, Option<String>)> {
+    let records = audit::read_all().ok()?;
+    records
+        .iter()
+        .rev()
+        .find(|record| {
+            record
+                .added()
+                .iter()
+                .any(|entry| find_path_entry(&[PathBuf::from(entry)], dir).is_some())
+        })
+        .map(|record| {
+            (
+                record.timestamp.clone(),
+                record.arguments.join(" "),
+                record.reason.clone(),
+            )
+        })
+}
@@ -0,0 +1,509 @@
+//! User configuration for pathmaster.
+//!
+//! Settings are read from `$XDG_CONFIG_HOME/pathmaster/config.toml`
+//! (default `~/.config/pathmaster/config.toml`, or `$PATHMASTER_CONFIG`).
+//! A missing file is not an error; every setting has a default.
+//!
+//! [`resolve`] works out the settings every command runs with. Each one
+//! comes from the first of:
+//! 1. the command-line flag
+//! 2. its `PATHMASTER_*` environment variable
+//! 3. the config file
+//! 4. the default
+
+use crate::msg;
+use crate::utils;
+use crate::utils::conditional::ConditionalEntry;
+use crate::utils::root;
+use crate::utils::rules::PathRule;
+use crate::utils::shell::handlers::template::ShellDefinition;
+use crate::utils::shell::{locations, types::ShellType};
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Mutex;
+
+/// Environment variable naming the config file
+pub const CONFIG_ENV: &str = "PATHMASTER_CONFIG";
+
+lazy_static! {
+    static ref CONFIG: Mutex<Option<Config>> = Mutex::new(None);
+}
+
+/// How PATH changes are persisted to shell configuration files.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum WriteMode {
+    /// Rewrite PATH declarations directly inside the rc file (default)
+    #[default]
+    Inline,
+    /// Write PATH to a pathmaster-owned file and source it from the rc file
+    DropIn,
+}
+
+impl fmt::Display for WriteMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WriteMode::Inline => write!(f, "inline"),
+            WriteMode::DropIn => write!(f, "drop-in"),
+        }
+    }
+}
+
+impl FromStr for WriteMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "inline" => Ok(WriteMode::Inline),
+            "drop-in" | "dropin" => Ok(WriteMode::DropIn),
+            _ => Err(format!("Invalid write mode: {}", s)),
+        }
+    }
+}
+
+/// Whether rewritten PATH declarations list every entry or extend `$PATH`.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ExportStyle {
+    /// Write the complete PATH as an absolute list (default)
+    #[default]
+    Absolute,
+    /// Write only entries managed in the config around `$PATH`
+    Relative,
+}
+
+impl fmt::Display for ExportStyle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExportStyle::Absolute => write!(f, "absolute"),
+            ExportStyle::Relative => write!(f, "relative"),
+        }
+    }
+}
+
+impl FromStr for ExportStyle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "absolute" => Ok(ExportStyle::Absolute),
+            "relative" => Ok(ExportStyle::Relative),
+            _ => Err(format!("Invalid export style: {}", s)),
+        }
+    }
+}
+
+/// Whether bash, ksh and generic configs get one PATH declaration or one
+/// line per entry.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ExportFormat {
+    /// A single `export PATH="..."` (default)
+    #[default]
+    Single,
+    /// One `PATH="$HOME/bin:$PATH"`-style line per entry, for cleaner diffs
+    Incremental,
+}
+
+impl fmt::Display for ExportFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExportFormat::Single => write!(f, "single"),
+            ExportFormat::Incremental => write!(f, "incremental"),
+        }
+    }
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "single" => Ok(ExportFormat::Single),
+            "incremental" => Ok(ExportFormat::Incremental),
+            _ => Err(format!("Invalid export format: {}", s)),
+        }
+    }
+}
+
+/// Settings loaded from the pathmaster config file.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct Config {
+    /// How PATH changes are written to shell configs
+    pub write_mode: WriteMode,
+    /// Whether declarations list the full PATH or extend `$PATH`
+    pub export_style: ExportStyle,
+    /// Whether POSIX shell declarations are one line or one line per entry
+    pub export_format: ExportFormat,
+    /// Shell config file to edit instead of the detected one
+    pub config_file: Option<PathBuf>,
+    /// Shell whose config is managed, instead of the one named by `$SHELL`
+    pub shell: Option<ShellType>,
+    /// Apply PATH changes to every shell whose config exists
+    pub all_shells: bool,
+    /// Git repository to commit modified shell configs to
+    pub dotfiles_repo: Option<PathBuf>,
+    /// Commit message template; `{files}` and `{date}` are filled in
+    pub dotfiles_commit_message: Option<String>,
+    /// Command run before shell configs are written; failure cancels the update
+    pub pre_update: Option<String>,
+    /// Command run after shell configs were written
+    pub post_update: Option<String>,
+    /// Never write shell configs; only the environment is managed
+    pub env_only: bool,
+    /// Also set PATH for graphical desktop sessions
+    pub gui: bool,
+    /// Also set PATH for new panes of the running tmux server
+    pub tmux: bool,
+    /// Shells defined by templates, for shells without a built-in handler
+    pub shells: Vec<ShellDefinition>,
+    /// Ordering rules checked whenever PATH is written
+    pub rules: Vec<PathRule>,
+    /// Reorder PATH to satisfy the rules instead of only warning
+    pub enforce_rules: bool,
+    /// Entries added or removed by their conditions whenever PATH is written
+    pub conditional_entries: Vec<ConditionalEntry>,
+    /// Directories delete and flush only remove with `--allow-system`
+    pub protected_dirs: Option<Vec<String>>,
+    /// Directories flush, clean and normalize leave exactly as written
+    pub keep_dirs: Vec<String>,
+    /// Directories `ensure` adds when they're missing, in order
+    pub required_dirs: Vec<String>,
+    /// Take a snapshot on the first run of each day
+    pub auto_snapshot: bool,
+    /// File to write shell code applying the new PATH to (`--emit`, not read from the file)
+    #[serde(skip)]
+    pub emit_file: Option<PathBuf>,
+    /// Manage the system-wide PATH in /etc/environment (`--system`, not read from the file)
+    #[serde(skip)]
+    pub system: bool,
+    /// Running in a container with no shell config to persist to (detected, not read from the file)
+    #[serde(skip)]
+    pub container: bool,
+    /// Directory PATH backups are kept in, e.g. a synced or encrypted folder
+    pub backup_dir: Option<PathBuf>,
+}
+
+/// Settings given on the command line, which win over every other source.
+///
+/// `None` and `false` leave the setting to the environment, the config
+/// file or the default.
+#[derive(Debug, Default, Clone)]
+pub struct Overrides {
+    pub write_mode: Option<WriteMode>,
+    pub export_style: Option<ExportStyle>,
+    pub export_format: Option<ExportFormat>,
+    pub config_file: Option<PathBuf>,
+    pub shell: Option<ShellType>,
+    pub all_shells: bool,
+    pub env_only: bool,
+    pub gui: bool,
+    pub tmux: bool,
+    pub emit_file: Option<PathBuf>,
+    pub system: bool,
+}
+
+impl Overrides {
+    fn apply(&self, config: &mut Config) {
+        if let Some(write_mode) = self.write_mode {
+            config.write_mode = write_mode;
+        }
+        if let Some(export_style) = self.export_style {
+            config.export_style = export_style;
+        }
+        if let Some(export_format) = self.export_format {
+            config.export_format = export_format;
+        }
+        if let Some(config_file) = &self.config_file {
+            config.config_file = Some(config_file.clone());
+        }
+        if let Some(shell) = self.shell {
+            config.shell = Some(shell);
+        }
+        config.all_shells |= self.all_shells;
+        config.env_only |= self.env_only;
+        config.gui |= self.gui;
+        config.tmux |= self.tmux;
+        config.emit_file = self.emit_file.clone();
+        config.system = self.system;
+    }
+}
+
+/// Returns the directory holding pathmaster's configuration and drop-in files.
+pub fn config_dir() -> PathBuf {
+    locations::xdg_config_home().join("pathmaster")
+}
+
+/// Returns the location of the config file, which `$PATHMASTER_CONFIG` can change.
+pub fn config_path() -> PathBuf {
+    env::var(CONFIG_ENV)
+        .ok()
+        .filter(|value| !value.is_empty())
+        .map(|value| root::rooted(utils::expand_path(&value)))
+        .unwrap_or_else(|| config_dir().join("config.toml"))
+}
+
+/// Loads the config file, returning defaults when it doesn't exist.
+///
+/// # Returns
+/// * `Ok(Config)` - Parsed or default configuration
+/// * `Err(io::Error)` - If the file exists but can't be read or parsed
+pub fn load() -> io::Result<Config> {
+    let path = config_path();
+    if !path.exists() {
+        return Ok(Config::default());
+    }
+
+    let content = fs::read_to_string(&path)?;
+    let mut config: Config = toml::from_str(&content).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Invalid config file {}: {}", path.display(), e),
+        )
+    })?;
+    config.config_file = config
+        .config_file
+        .map(|file| root::rooted(utils::expand_os_path(file.as_os_str())));
+    config.dotfiles_repo = config
+        .dotfiles_repo
+        .map(|repo| root::rooted(utils::expand_os_path(repo.as_os_str())));
+    config.backup_dir = config
+        .backup_dir
+        .map(|dir| root::rooted(utils::expand_os_path(dir.as_os_str())));
+    Ok(config)
+}
+
+/// Works out the settings commands run with, from the command line, the
+/// environment, the config file and the defaults, in that order.
+///
+/// Problems with the config file or environment variables are reported as
+/// warnings, and the setting is taken from the next source.
+///
+/// # Arguments
+///
+/// * `overrides` - Settings given on the command line
+pub fn resolve(overrides: &Overrides) -> Config {
+    let mut config = load().unwrap_or_else(|e| {
+        eprintln!("{}", msg!("warning", warning = e));
+        Config::default()
+    });
+    for warning in apply_env(&mut config, |name| env::var(name).ok()) {
+        eprintln!("{}", msg!("warning", warning = warning));
+    }
+    overrides.apply(&mut config);
+    config
+}
+
+/// Applies the `PATHMASTER_*` variables over `config`
+///
+/// Each variable is named after the setting it overrides, e.g.
+/// `PATHMASTER_WRITE_MODE` for `write-mode`; `PATHMASTER_NO_SHELL_UPDATE`
+/// is another name for `PATHMASTER_ENV_ONLY`.
+///
+/// # Arguments
+///
+/// * `config` - Settings from the config file
+/// * `var` - Looks up an environment variable; empty values count as unset
+///
+/// # Returns
+/// * The variables that were ignored because their value is invalid
+fn apply_env(config: &mut Config, var: impl Fn(&str) -> Option<String>) -> Vec<String> {
+    let mut warnings = Vec::new();
+    let setting = |name: &str| EnvSetting {
+        name: name.to_string(),
+        value: var(name).filter(|value| !value.is_empty()),
+    };
+    let path = |text: &str| Ok::<_, String>(root::rooted(utils::expand_path(text)));
+
+    if let Some(mode) = setting("PATHMASTER_WRITE_MODE").parse(str::parse, &mut warnings) {
+        config.write_mode = mode;
+    }
+    if let Some(style) = setting("PATHMASTER_EXPORT_STYLE").parse(str::parse, &mut warnings) {
+        config.export_style = style;
+    }
+    if let Some(format) = setting("PATHMASTER_EXPORT_FORMAT").parse(str::parse, &mut warnings) {
+        config.export_format = format;
+    }
+    if let Some(file) = setting("PATHMASTER_CONFIG_FILE").parse(path, &mut warnings) {
+        config.config_file = Some(file);
+    }
+    if let Some(shell) = setting("PATHMASTER_SHELL").parse(str::parse, &mut warnings) {
+        config.shell = Some(shell);
+    }
+    for (name, switch) in [
+        ("PATHMASTER_ALL_SHELLS", &mut config.all_shells),
+        ("PATHMASTER_GUI", &mut config.gui),
+        ("PATHMASTER_TMUX", &mut config.tmux),
+        ("PATHMASTER_ENFORCE_RULES", &mut config.enforce_rules),
+        ("PATHMASTER_AUTO_SNAPSHOT", &mut config.auto_snapshot),
+    ] {
+        if let Some(on) = setting(name).parse(parse_switch, &mut warnings) {
+            *switch = on;
+        }
+    }
+    // PATHMASTER_NO_SHELL_UPDATE is another name for PATHMASTER_ENV_ONLY
+    for name in ["PATHMASTER_NO_SHELL_UPDATE", "PATHMASTER_ENV_ONLY"] {
+        if let Some(on) = setting(name).parse(parse_switch, &mut warnings) {
+            config.env_only = on;
+        }
+    }
+    if let Some(repo) = setting("PATHMASTER_DOTFILES_REPO").parse(path, &mut warnings) {
+        config.dotfiles_repo = Some(repo);
+    }
+    if let Some(dir) = setting("PATHMASTER_BACKUP_DIR").parse(path, &mut warnings) {
+        config.backup_dir = Some(dir);
+    }
+    warnings
+}
+
+/// An environment variable that may override a setting
+struct EnvSetting {
+    name: String,
+    value: Option<String>,
+}
+
+impl EnvSetting {
+    /// Parses the value, recording a warning if it's invalid
+    fn parse<T>(
+        self,
+        parse: impl Fn(&str) -> Result<T, String>,
+        warnings: &mut Vec<String>,
+    ) -> Option<T> {
+        let value = self.value?;
+        parse(&value)
+            .map_err(|e| warnings.push(format!("Ignoring {}={}: {}", self.name, value, e)))
+            .ok()
+    }
+}
+
+/// Parses an on/off environment variable
+fn parse_switch(value: &str) -> Result<bool, String> {
+    match value.to_lowercase().as_str() {
+        "1" | "true" | "yes" | "on" => Ok(true),
+        "0" | "false" | "no" | "off" => Ok(false),
+        _ => Err("expected 1, true, yes or on, or 0, false, no or off".to_string()),
+    }
+}
+
+/// Replaces the active configuration (used by `main` once settings are resolved)
+pub fn set_config(config: Config) {
+    if let Ok(mut current) = CONFIG.lock() {
+        *current = Some(config);
+    }
+}
+
+/// Forgets the active configuration, so it's resolved again on next use
+/// (used by tests that set one)
+#[cfg(test)]
+pub fn clear_config() {
+    if let Ok(mut current) = CONFIG.lock() {
+        *current = None;
+    }
+}
+
+/// Returns the active configuration, resolving it without command-line
+/// overrides on first use.
+///
+/// Errors reading the config file are reported once and defaults are used.
+pub fn get_config() -> Config {
+    let mut current = match CONFIG.lock() {
+        Ok(guard) => guard,
+        Err(_) => return Config::default(),
+    };
+
+    current
+        .get_or_insert_with(|| resolve(&Overrides::default()))
+        .clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_parse_config() {
+        let config: Config = toml::from_str("write-mode = \"drop-in\"").unwrap();
+        assert_eq!(config.write_mode, WriteMode::DropIn);
+
+        let config: Config = toml::from_str("").unwrap();
+        assert_eq!(config.write_mode, WriteMode::Inline);
+        assert_eq!(config.export_style, ExportStyle::Absolute);
+
+        let config: Config = toml::from_str("export-style = \"relative\"").unwrap();
+        assert_eq!(config.export_style, ExportStyle::Relative);
+
+        let config: Config = toml::from_str("export-format = \"incremental\"").unwrap();
+        assert_eq!(config.export_format, ExportFormat::Incremental);
+
+        let config: Config = toml::from_str("config-file = \"/etc/csh.cshrc\"").unwrap();
+        assert_eq!(config.config_file, Some(PathBuf::from("/etc/csh.cshrc")));
+
+        let config: Config = toml::from_str("shell = \"zsh\"").unwrap();
+        assert_eq!(config.shell, Some(ShellType::Zsh));
+
+        let config: Config = toml::from_str("protected-dirs = []").unwrap();
+        assert_eq!(config.protected_dirs, Some(Vec::new()));
+
+        let config: Config = toml::from_str("backup-dir = \"/pm-test/sync/backups\"").unwrap();
+        assert_eq!(
+            config.backup_dir,
+            Some(PathBuf::from("/pm-test/sync/backups"))
+        );
+    }
+
+    #[test]
+    fn test_env_overrides_config_file() {
+        let mut config: Config =
+            toml::from_str("write-mode = \"drop-in\"\nshell = \"zsh\"\ngui = true").unwrap();
+        let env: HashMap<&str, &str> = [
+            ("PATHMASTER_SHELL", "fish"),
+            ("PATHMASTER_GUI", "off"),
+            ("PATHMASTER_NO_SHELL_UPDATE", "1"),
+            ("PATHMASTER_BACKUP_DIR", "/pm-test/backups"),
+            ("PATHMASTER_EXPORT_STYLE", "sideways"),
+            ("PATHMASTER_TMUX", "maybe"),
+            ("PATHMASTER_CONFIG_FILE", ""),
+        ]
+        .into_iter()
+        .collect();
+
+        let warnings = apply_env(&mut config, |name| env.get(name).map(|v| v.to_string()));
+        assert_eq!(config.write_mode, WriteMode::DropIn);
+        assert_eq!(config.shell, Some(ShellType::Fish));
+        assert!(!config.gui);
+        assert!(config.env_only);
+        assert_eq!(config.backup_dir, Some(PathBuf::from("/pm-test/backups")));
+        assert_eq!(config.export_style, ExportStyle::Absolute);
+        assert!(!config.tmux);
+        assert_eq!(config.config_file, None);
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings[0].starts_with("Ignoring PATHMASTER_EXPORT_STYLE=sideways"));
+
+        // The command line wins over both
+        Overrides {
+            shell: Some(ShellType::Bash),
+            gui: true,
+            ..Default::default()
+        }
+        .apply(&mut config);
+        assert_eq!(config.shell, Some(ShellType::Bash));
+        assert!(config.gui);
+        assert!(config.env_only);
+    }
+
+    #[test]
+    fn test_write_mode_parsing() {
+        assert_eq!("inline".parse::<WriteMode>().unwrap(), WriteMode::Inline);
+        assert_eq!("drop-in".parse::<WriteMode>().unwrap(), WriteMode::DropIn);
+        assert!("bogus".parse::<WriteMode>().is_err());
+        assert_eq!(WriteMode::DropIn.to_string(), "drop-in");
+    }
+}
@@ -5,14 +5,18 @@
 //! - Removing directories from PATH
 //! - Listing current PATH entries
 //! - Managing PATH backups
+//! - Logging operations that change PATH
 //! - Validating PATH entries
 //! - Flushing invalid entries from PATH
+//! - Verifying PATH against the shell config
+//! - Scanning shell configs for PATH modifications
 
-use clap::{command, Parser, Subcommand};
-use commands::validator;
+use clap::{Args, Parser, Subcommand};
+use std::ffi::OsString;
 
 mod backup;
 mod commands;
+mod config;
 mod utils;
 
 /// CLI configuration and argument parsing for pathmaster
@@ -25,6 +29,86 @@ struct Cli {
     #[arg(long, value_name = "MODE")]
     backup_mode: Option<String>,
 
+    /// How PATH changes are written to shell configs (inline, drop-in)
+    #[arg(long, value_name = "MODE")]
+    write_mode: Option<config::WriteMode>,
+
+    /// Whether PATH declarations list every entry or extend $PATH (absolute, relative)
+    #[arg(long, value_name = "STYLE")]
+    export_style: Option<config::ExportStyle>,
+
+    /// Whether bash, ksh and generic configs get one PATH declaration or one line per entry (single, incremental)
+    #[arg(long, value_name = "FORMAT")]
+    export_format: Option<config::ExportFormat>,
+
+    /// Shell config file to edit instead of the detected one (e.g. ~/.cshrc)
+    #[arg(long, value_name = "FILE")]
+    config_file: Option<String>,
+
+    /// Manage this shell's config instead of the one named by $SHELL (bash, zsh, fish, tcsh, ksh, generic)
+    #[arg(long, value_name = "SHELL")]
+    shell: Option<utils::shell::types::ShellType>,
+
+    /// Apply PATH changes to the config of every shell that has one
+    #[arg(long)]
+    all_shells: bool,
+
+    /// Never modify shell config files; only the environment is changed
+    #[arg(long)]
+    env_only: bool,
+
+    /// Also set PATH for graphical desktop sessions (environment.d on Linux, launchctl on macOS)
+    #[arg(long, conflicts_with = "env_only")]
+    gui: bool,
+
+    /// Also set PATH for new panes in running tmux sessions (tmux set-environment -g)
+    #[arg(long)]
+    tmux: bool,
+
+    /// Manage the system-wide PATH in /etc/environment instead of your own (asks for root via sudo)
+    #[arg(long, conflicts_with_all = ["env_only", "all_shells", "gui", "tmux", "emit", "config_file", "shell"])]
+    system: bool,
+
+    /// Write shell code that applies the new PATH to FILE, for shell wrappers to source
+    #[arg(long, value_name = "FILE")]
+    emit: Option<std::path::PathBuf>,
+
+    /// Answer yes to every confirmation prompt
+    #[arg(long, conflicts_with = "no_input")]
+    yes: bool,
+
+    /// Never prompt; confirmations are answered no
+    #[arg(long)]
+    no_input: bool,
+
+    /// Only report whether the command would change anything; nothing is written (exit code 2 if it would)
+    #[arg(long)]
+    check: bool,
+
+    /// With --check, show the changes to PATH and shell configs as a diff
+    #[arg(long, requires = "check")]
+    diff: bool,
+
+    /// Format of the --check diff (unified, json); implies --diff
+    #[arg(long, value_name = "FORMAT", requires = "check")]
+    diff_format: Option<utils::check_mode::DiffFormat>,
+
+    /// Why this change is made; kept in the operation log and shown by 'why'
+    #[arg(long, value_name = "TEXT")]
+    reason: Option<String>,
+
+    /// Print extra details, such as which shell config is used and why
+    #[arg(short, long)]
+    verbose: bool,
+
+    /// Language of messages (e.g. en, de); detected from LC_ALL, LC_MESSAGES and LANG by default
+    #[arg(long, value_name = "LANG")]
+    lang: Option<String>,
+
+    /// Find and change every file under DIR as if it were / (default $PATHMASTER_ROOT), e.g. to try a copy of your dotfiles
+    #[arg(long, value_name = "DIR")]
+    root: Option<std::path::PathBuf>,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -35,76 +119,1011 @@ enum Commands {
     /// Add directories to the PATH
     #[command(name = "add", short_flag = 'a')]
     Add {
-        /// Directories to add
-        directories: Vec<String>,
+        /// Directories to add (use '-' to read them from stdin, one per line)
+        directories: Vec<OsString>,
+
+        /// Move directories already in PATH to the front instead of skipping them
+        #[arg(long)]
+        move_to_front: bool,
+
+        #[command(flatten)]
+        new: NewDirectoryArgs,
+
+        /// Remove the directories again after this long, e.g. 7d (m, h, d, w)
+        #[arg(long, value_name = "DURATION", value_parser = utils::provenance::parse_ttl)]
+        ttl: Option<chrono::Duration>,
+    },
+    /// Add directories to the front of PATH, moving them there if already present
+    #[command(name = "prepend")]
+    Prepend {
+        /// Directories to prepend, in order (use '-' to read them from stdin)
+        directories: Vec<OsString>,
+
+        #[command(flatten)]
+        new: NewDirectoryArgs,
+    },
+    /// Add directories to the end of PATH, skipping those already present
+    #[command(name = "append")]
+    Append {
+        /// Directories to append, in order (use '-' to read them from stdin)
+        directories: Vec<OsString>,
+
+        #[command(flatten)]
+        new: NewDirectoryArgs,
     },
     /// Delete directories from the PATH
     #[command(name = "delete", short_flag = 'd', aliases = &["remove"])]
     Delete {
-        /// Directories to delete
-        directories: Vec<String>,
+        /// Directories to delete (use '-' to read them from stdin, one per line)
+        directories: Vec<OsString>,
+
+        /// Delete the entry at this position (1-based)
+        #[arg(long, value_name = "N")]
+        index: Option<usize>,
+
+        /// Delete entries matching a glob pattern (e.g. '~/old-sdks/*')
+        #[arg(long, value_name = "PATTERN")]
+        glob: Option<String>,
+
+        /// Delete entries matching a regular expression
+        #[arg(long, value_name = "REGEX")]
+        regex: Option<String>,
+
+        /// Allow removing protected system directories such as /usr/bin
+        #[arg(long)]
+        allow_system: bool,
+    },
+    /// Remove entries added with --ttl whose time has run out
+    #[command(name = "expire")]
+    Expire,
+    /// Bring PATH in line with the system policy in /etc/pathmaster/policy.toml
+    #[command(name = "enforce")]
+    Enforce,
+    /// Show everything known about one PATH entry
+    #[command(name = "show")]
+    Show {
+        /// Directory to describe
+        directory: String,
+    },
+    /// Explain when, how and why a directory was added to PATH
+    #[command(name = "why")]
+    Why {
+        /// Directory to explain
+        directory: String,
+    },
+    /// Replay the shell's startup files and show which file and line introduced each PATH entry
+    #[command(name = "explain")]
+    Explain {
+        /// Replay a non-login shell (e.g. a new terminal tab) instead of a login shell
+        #[arg(long)]
+        non_login: bool,
+    },
+    /// List PATH entries matching a pattern, optionally deleting them or moving them to the front
+    #[command(name = "grep")]
+    Grep {
+        /// Regular expression matched anywhere in each entry, e.g. 'cuda'
+        pattern: String,
+
+        /// Match without regard to case
+        #[arg(short, long)]
+        ignore_case: bool,
+
+        /// Delete the matching entries
+        #[arg(long, conflicts_with = "to_front")]
+        delete: bool,
+
+        /// Move the matching entries to the front of PATH
+        #[arg(long)]
+        to_front: bool,
+
+        /// Allow deleting protected system directories such as /usr/bin
+        #[arg(long, requires = "delete")]
+        allow_system: bool,
+    },
+    /// Replace a directory in PATH with another, keeping its position
+    #[command(name = "replace")]
+    Replace {
+        /// Directory currently in PATH
+        old: String,
+
+        /// Directory to put in its place
+        new: String,
+
+        /// Use the new directory even if it doesn't exist or isn't a directory
+        #[arg(long)]
+        force: bool,
+
+        /// Allow replacing protected system directories such as /usr/bin
+        #[arg(long)]
+        allow_system: bool,
+    },
+    /// Print PATH for use elsewhere, e.g. in a Dockerfile or CI workflow
+    #[command(name = "export")]
+    Export {
+        /// Output format (shell, dockerfile, github-actions)
+        #[arg(long, value_name = "FORMAT", default_value_t = commands::export::ExportFormat::Shell)]
+        format: commands::export::ExportFormat,
+    },
+    /// Add any required directories missing from PATH (safe to re-run)
+    #[command(name = "ensure")]
+    Ensure {
+        /// Required directories in order (default: required-dirs from the config; '-' reads stdin)
+        directories: Vec<OsString>,
+
+        /// Create required directories that don't exist yet
+        #[arg(long)]
+        create: bool,
+
+        /// Add required directories even if they don't exist
+        #[arg(long)]
+        force: bool,
     },
     /// List current PATH entries
     #[command(name = "list", short_flag = 'l')]
-    List,
+    List {
+        /// Show entries as declared in the shell config alongside their expanded form
+        #[arg(long, conflicts_with = "tree")]
+        source: bool,
+
+        /// Group entries by top-level directory, with counts and invalid entries per group
+        #[arg(long)]
+        tree: bool,
+
+        /// Show each entry's status and the package manager that owns it
+        #[arg(long, conflicts_with = "source")]
+        long: bool,
+
+        /// Print stable, versioned, tab-separated output for scripts (default version: v1)
+        #[arg(
+            long,
+            value_name = "VERSION",
+            num_args = 0..=1,
+            default_missing_value = "v1",
+            conflicts_with_all = ["source", "tree", "long"]
+        )]
+        porcelain: Option<utils::porcelain::PorcelainVersion>,
+    },
+    /// Show recent operations that changed PATH
+    #[command(name = "log")]
+    Log {
+        /// Number of operations to show (0 for all)
+        #[arg(short = 'n', long, default_value_t = commands::log::DEFAULT_LIMIT)]
+        limit: usize,
+    },
+    /// Revert the most recent operations from the operation log
+    #[command(name = "undo")]
+    Undo {
+        /// Number of operations to revert
+        #[arg(long, default_value_t = 1)]
+        steps: usize,
+
+        /// Revert even if PATH changed since the operation
+        #[arg(long)]
+        force: bool,
+    },
+    /// Reapply operations reverted by undo
+    #[command(name = "redo")]
+    Redo {
+        /// Number of operations to reapply
+        #[arg(long, default_value_t = 1)]
+        steps: usize,
+
+        /// Reapply even if PATH changed since the undo
+        #[arg(long)]
+        force: bool,
+    },
     /// Show backup history
     #[command(name = "history", short_flag = 'y')]
-    History,
+    History {
+        /// Print stable, versioned, tab-separated output for scripts (default version: v1)
+        #[arg(long, value_name = "VERSION", num_args = 0..=1, default_missing_value = "v1")]
+        porcelain: Option<utils::porcelain::PorcelainVersion>,
+    },
+    /// Back up PATH and shell configs (per --backup-mode) without changing anything
+    #[command(name = "snapshot")]
+    Snapshot {
+        /// Name shown for the backup in history
+        #[arg(long, value_name = "LABEL")]
+        name: Option<String>,
+
+        /// Schedule a daily snapshot with a systemd user timer (or cron)
+        #[arg(long, conflicts_with = "name")]
+        install_timer: bool,
+    },
     /// Restore PATH from a backup
     #[command(name = "restore", short_flag = 'r')]
     Restore {
+        /// Number of the backup in 'pathmaster history'
+        #[arg(value_name = "NUMBER", group = "selector")]
+        index: Option<usize>,
+
         /// Timestamp of the backup to restore
-        #[arg(short, long)]
+        #[arg(short, long, group = "selector")]
         timestamp: Option<String>,
+
+        /// Restore the most recent backup (the default)
+        #[arg(long, group = "selector")]
+        latest: bool,
+
+        /// Restore the backup before the most recent one
+        #[arg(long, group = "selector")]
+        previous: bool,
+
+        /// Restore the last backup taken before a time, e.g. "2 days ago"
+        #[arg(long, value_name = "WHEN", group = "selector", value_parser = backup::restore::parse_time)]
+        before: Option<chrono::NaiveDateTime>,
     },
     /// Flush non-existing paths from the PATH
     #[command(name = "flush", short_flag = 'f')]
-    Flush,
+    Flush {
+        /// Also remove or absolutize relative and empty entries (remove, absolutize)
+        #[arg(long, value_name = "ACTION")]
+        relative: Option<commands::flush::RelativeAction>,
+
+        /// Allow removing protected system directories such as /usr/bin
+        #[arg(long)]
+        allow_system: bool,
+
+        /// Also remove the Windows PATH entries WSL appends (/mnt/c/...)
+        #[arg(long)]
+        strip_windows: bool,
+
+        /// Also remove missing entries on removable media or automounts that aren't mounted
+        #[arg(long)]
+        remove_unmounted: bool,
+    },
     /// Check PATH for invalid directories
-    #[command(name = "check", short_flag = 'c')]
-    Check,
+    #[command(name = "check", short_flag = 'c', aliases = &["doctor"])]
+    Check {
+        /// Check this colon-separated PATH value instead of the environment
+        #[arg(long, value_name = "PATH", conflicts_with = "from_file")]
+        path: Option<String>,
+
+        /// Check the PATH in a file: a PATH value, an `env` dump, or one entry per line ('-' for stdin)
+        #[arg(long, value_name = "FILE")]
+        from_file: Option<std::path::PathBuf>,
+
+        /// Print stable, versioned, tab-separated output for scripts (default version: v1)
+        #[arg(long, value_name = "VERSION", num_args = 0..=1, default_missing_value = "v1")]
+        porcelain: Option<utils::porcelain::PorcelainVersion>,
+
+        /// Check the entries the shell config declares instead of the environment, for scheduled runs
+        #[arg(long, conflicts_with_all = ["path", "from_file"])]
+        declared: bool,
+
+        /// Send a desktop notification when problems appear that the last saved check didn't find
+        #[arg(long)]
+        notify: bool,
+
+        /// Print only a one-line summary, and only if there are problems
+        #[arg(long, conflicts_with_all = ["porcelain", "notify"])]
+        quiet: bool,
+
+        /// With --quiet, skip the check if the last saved one is less than a day old
+        #[arg(long, requires = "quiet")]
+        daily: bool,
+    },
+    /// Normalize how PATH entries are written (trailing slashes, '//', '/./')
+    #[command(name = "normalize")]
+    Normalize {
+        /// Also replace symlinked entries with the directories they point to
+        #[arg(long)]
+        resolve_symlinks: bool,
+    },
+    /// Remove invalid and duplicate entries and normalize the rest in one update
+    #[command(name = "clean")]
+    Clean {
+        /// Also replace symlinked entries with the directories they point to
+        #[arg(long)]
+        resolve_symlinks: bool,
+
+        /// Allow removing protected system directories such as /usr/bin
+        #[arg(long)]
+        allow_system: bool,
+
+        /// Also remove the Windows PATH entries WSL appends (/mnt/c/...)
+        #[arg(long)]
+        strip_windows: bool,
+    },
+    /// Report differences between the current PATH and the shell config
+    #[command(name = "verify")]
+    Verify,
+    /// Scan shell configs for PATH modifications and PATH directories for executables
+    #[command(name = "scan")]
+    Scan,
+    /// Move the PATH declarations in your shell's startup files into pathmaster's managed config
+    #[command(name = "adopt")]
+    Adopt,
+    /// Flag PATH directories others can write to and setuid executables in them
+    #[command(name = "audit")]
+    Audit,
+    /// Find PATH mistakes in shell configs that pathmaster can't fix
+    #[command(name = "lint")]
+    Lint,
+    /// Show executables found in more than one PATH directory
+    #[command(name = "shadows")]
+    Shadows {
+        /// Only show these executables (e.g. python3 node)
+        names: Vec<String>,
+
+        /// Run each copy with --version and report mismatches (common interpreters and compilers by default)
+        #[arg(long)]
+        versions: bool,
+    },
+    /// Measure how long command lookup takes with the current PATH and a cleaned one
+    #[command(name = "bench")]
+    Bench {
+        /// Commands to look up (a common set by default)
+        names: Vec<String>,
+
+        /// How many times each lookup is timed
+        #[arg(long, default_value_t = commands::bench::DEFAULT_ITERATIONS)]
+        iterations: u32,
+    },
+    /// Serve list, check, add, remove and diff previews to editors over JSON-RPC
+    #[command(name = "serve")]
+    Serve {
+        /// Read requests from stdin and write responses to stdout, one JSON object per line
+        #[arg(long, required = true)]
+        stdio: bool,
+    },
+    /// Print a compact marker of the last saved check's problems, e.g. for PS1
+    #[command(name = "status")]
+    Status {
+        /// Print the marker for a shell prompt, e.g. '✗2', or nothing if there are no problems
+        #[arg(long, required = true)]
+        prompt: bool,
+    },
+    /// Print shell startup lines that check PATH once a day
+    #[command(name = "init")]
+    Init {
+        /// Run 'check --quiet --daily' when an interactive shell starts
+        #[arg(long, required = true)]
+        daily_check: bool,
+    },
+    /// Manage cached PATH analysis data
+    #[command(name = "cache")]
+    Cache {
+        #[command(subcommand)]
+        action: CacheAction,
+    },
+    /// Install the /etc/environment given on stdin (run as root by --system)
+    #[command(name = "system-write", hide = true)]
+    SystemWrite,
+}
+
+impl Commands {
+    /// Name of the command as typed on the command line
+    fn name(&self) -> &'static str {
+        match self {
+            Commands::Add { .. } => "add",
+            Commands::Prepend { .. } => "prepend",
+            Commands::Append { .. } => "append",
+            Commands::Delete { .. } => "delete",
+            Commands::Replace { .. } => "replace",
+            Commands::Grep { .. } => "grep",
+            Commands::Show { .. } => "show",
+            Commands::Expire => "expire",
+            Commands::Enforce => "enforce",
+            Commands::Why { .. } => "why",
+            Commands::Explain { .. } => "explain",
+            Commands::Ensure { .. } => "ensure",
+            Commands::Export { .. } => "export",
+            Commands::List { .. } => "list",
+            Commands::Log { .. } => "log",
+            Commands::Undo { .. } => "undo",
+            Commands::Redo { .. } => "redo",
+            Commands::History { .. } => "history",
+            Commands::Snapshot { .. } => "snapshot",
+            Commands::Restore { .. } => "restore",
+            Commands::Flush { .. } => "flush",
+            Commands::Check { .. } => "check",
+            Commands::Normalize { .. } => "normalize",
+            Commands::Clean { .. } => "clean",
+            Commands::Verify => "verify",
+            Commands::Scan => "scan",
+            Commands::Adopt => "adopt",
+            Commands::Audit => "audit",
+            Commands::Lint => "lint",
+            Commands::Shadows { .. } => "shadows",
+            Commands::Bench { .. } => "bench",
+            Commands::Serve { .. } => "serve",
+            Commands::Status { .. } => "status",
+            Commands::Init { .. } => "init",
+            Commands::Cache { .. } => "cache",
+            Commands::SystemWrite => "system-write",
+        }
+    }
+
+    /// Returns whether the command may change PATH or a shell config
+    fn changes_path(&self) -> bool {
+        match self {
+            Commands::Add { .. }
+            | Commands::Prepend { .. }
+            | Commands::Append { .. }
+            | Commands::Delete { .. }
+            | Commands::Replace { .. }
+            | Commands::Expire
+            | Commands::Enforce
+            | Commands::Ensure { .. }
+            | Commands::Undo { .. }
+            | Commands::Redo { .. }
+            | Commands::Restore { .. }
+            | Commands::Flush { .. }
+            | Commands::Normalize { .. }
+            | Commands::Clean { .. }
+            | Commands::Adopt => true,
+            Commands::Grep {
+                delete, to_front, ..
+            } => *delete || *to_front,
+            _ => false,
+        }
+    }
+}
+
+/// How add, prepend and append treat directories that aren't there yet
+#[derive(Args)]
+struct NewDirectoryArgs {
+    /// Create directories that don't exist yet
+    #[arg(long)]
+    create: bool,
+
+    /// Add directories even if they don't exist or aren't directories
+    #[arg(long)]
+    force: bool,
+}
+
+impl NewDirectoryArgs {
+    /// Returns add options with these flags set and the others at their defaults
+    fn options(&self) -> commands::add::AddOptions {
+        commands::add::AddOptions {
+            create: self.create,
+            force: self.force,
+            ..Default::default()
+        }
+    }
+}
+
+/// Actions for the cache command
+#[derive(Subcommand)]
+enum CacheAction {
+    /// Delete cached executable listings
+    Clear,
 }
 
 fn main() {
     let cli = Cli::parse();
 
+    if let Err(e) = utils::messages::set_language(cli.lang.as_deref()) {
+        eprintln!("{}", msg!("error", error = e));
+        std::process::exit(1);
+    }
+
+    // Every file below is looked up under the fake root, so set it first
+    let root = cli.root.clone().or_else(|| {
+        std::env::var_os(utils::root::ROOT_ENV)
+            .filter(|value| !value.is_empty())
+            .map(std::path::PathBuf::from)
+    });
+    if let Err(e) = utils::root::set_root(root.as_deref()) {
+        eprintln!("{}", msg!("error", error = e));
+        std::process::exit(1);
+    }
+
+    // In system mode, commands work on the PATH from /etc/environment
+    if cli.system {
+        match utils::system_env::read_path() {
+            Ok(entries) => utils::set_path_entries(&entries),
+            Err(e) => {
+                eprintln!("{}", msg!("error", error = e));
+                std::process::exit(1);
+            }
+        }
+    }
+
+    // Remember the inherited PATH before any command changes it
+    utils::record_initial_path();
+    utils::verbose::set_verbose(cli.verbose);
+    utils::check_mode::set_check_mode(cli.check);
+    utils::hooks::set_current_command(cli.command.name());
+    utils::audit::set_reason(cli.reason.as_deref());
+    if cli.yes {
+        utils::prompt::set_prompt_mode(utils::prompt::PromptMode::AssumeYes);
+    } else if cli.no_input {
+        utils::prompt::set_prompt_mode(utils::prompt::PromptMode::NoInput);
+    }
+
+    // Settings come from the command line, the environment, the config file or defaults
+    let mut settings = config::resolve(&config::Overrides {
+        write_mode: cli.write_mode,
+        export_style: cli.export_style,
+        export_format: cli.export_format,
+        config_file: cli
+            .config_file
+            .as_deref()
+            .map(|file| utils::root::rooted(utils::expand_path(file))),
+        shell: cli.shell,
+        all_shells: cli.all_shells,
+        env_only: cli.env_only,
+        gui: cli.gui,
+        tmux: cli.tmux,
+        emit_file: cli.emit.clone(),
+        system: cli.system,
+    });
+
+    // A container without a shell config has nowhere to persist PATH to
+    if !settings.env_only
+        && !settings.system
+        && !settings.all_shells
+        && settings.config_file.is_none()
+        && utils::container::in_container()
+        && !utils::shell::factory::get_shell_handler(settings.shell).has_config()
+    {
+        settings.env_only = true;
+        settings.container = true;
+    }
+    config::set_config(settings);
+
     // Initialize backup mode if specified
-    if let Some(mode) = cli.backup_mode {
-        let mut manager = backup::mode::BackupModeManager::new();
+    let mut manager = backup::mode::BackupModeManager::new();
+    if let Some(mode) = &cli.backup_mode {
         match mode.as_str() {
             "default" => manager.reset_to_default(),
             "path" => manager.confirm_mode_change(backup::mode::BackupMode::PathOnly),
             "shell" => manager.confirm_mode_change(backup::mode::BackupMode::ShellOnly),
             "switch" => manager.toggle_mode(),
             _ => {
-                eprintln!(
-                    "Invalid backup mode: {}. Valid modes are: default, path, shell, switch",
-                    mode
-                );
+                eprintln!("{}", msg!("error-backup-mode", mode = mode));
                 std::process::exit(1);
             }
         }
     }
+    backup::mode::set_backup_mode(manager.current_mode());
 
-    match &cli.command {
-        Commands::Add { directories } => commands::add::execute(directories),
-        Commands::Delete { directories } => commands::delete::execute(directories),
-        Commands::List => commands::list::execute(),
-        Commands::History => backup::show_history(),
-        Commands::Restore { timestamp } => backup::restore_from_backup(timestamp),
-        Commands::Flush => commands::flush::execute(),
-        Commands::Check => match validator::validate_path() {
-            Ok(validation) => {
-                if validation.existing_dirs.is_empty() && validation.missing_dirs.is_empty() {
-                    println!("All directories in PATH are valid");
-                } else {
-                    println!("Invalid directories in PATH:");
-                    for dir in validation.missing_dirs {
-                        println!("  {}", dir.to_string_lossy());
-                    }
+    // Read-only commands, porcelain output included, never take one
+    if config::get_config().auto_snapshot && !cli.check && cli.command.changes_path() {
+        commands::snapshot::auto_snapshot(manager.current_mode());
+    }
+
+    // Expand '-' into directories read from stdin for bulk operations
+    let directories = match &cli.command {
+        Commands::Add { directories, .. }
+        | Commands::Prepend { directories, .. }
+        | Commands::Append { directories, .. }
+        | Commands::Delete { directories, .. }
+        | Commands::Ensure { directories, .. } => {
+            match utils::resolve_directory_args(directories, std::io::stdin().lock()) {
+                Ok(resolved) => resolved,
+                Err(e) => {
+                    eprintln!("{}", msg!("error-stdin", error = e));
+                    std::process::exit(1);
                 }
             }
-            Err(e) => eprintln!("Error: {}", e),
+        }
+        _ => Vec::new(),
+    };
+
+    let mut failed = false;
+    match &cli.command {
+        Commands::Add {
+            move_to_front, new, ..
+        } => {
+            failed = !commands::add::execute(
+                &directories,
+                &commands::add::AddOptions {
+                    move_to_front: *move_to_front,
+                    ..new.options()
+                },
+            )
+        }
+        Commands::Prepend { new, .. } => {
+            failed = !commands::add::execute(
+                &directories,
+                &commands::add::AddOptions {
+                    move_to_front: true,
+                    prepend: true,
+                    ..new.options()
+                },
+            )
+        }
+        Commands::Append { new, .. } => {
+            failed = !commands::add::execute(&directories, &new.options())
+        }
+        Commands::Show { directory } => commands::show::execute(directory),
+        Commands::Expire => commands::expire::execute(),
+        Commands::Enforce => failed = !commands::enforce::execute(),
+        Commands::Why { directory } => commands::why::execute(directory),
+        Commands::Explain { non_login } => commands::explain::execute(*non_login),
+        Commands::Grep {
+            pattern,
+            ignore_case,
+            delete,
+            to_front,
+            allow_system,
+        } => {
+            let action = if *delete {
+                Some(commands::grep::GrepAction::Delete)
+            } else if *to_front {
+                Some(commands::grep::GrepAction::ToFront)
+            } else {
+                None
+            };
+            let options = commands::grep::GrepOptions {
+                ignore_case: *ignore_case,
+                action,
+                allow_system: *allow_system,
+            };
+            if !commands::grep::execute(pattern, &options) {
+                std::process::exit(1);
+            }
+        }
+        Commands::Replace {
+            old,
+            new,
+            force,
+            allow_system,
+        } => {
+            failed = !commands::replace::execute(
+                old,
+                new,
+                &commands::replace::ReplaceOptions {
+                    force: *force,
+                    allow_system: *allow_system,
+                },
+            )
+        }
+        Commands::Delete {
+            index,
+            glob,
+            regex,
+            allow_system,
+            ..
+        } => {
+            failed = !commands::delete::execute(
+                &directories,
+                &commands::delete::DeleteOptions {
+                    index: *index,
+                    glob: glob.clone(),
+                    regex: regex.clone(),
+                    allow_system: *allow_system,
+                },
+            )
+        }
+        Commands::Ensure { create, force, .. } => {
+            failed = !commands::ensure::execute(
+                &directories,
+                &commands::ensure::EnsureOptions {
+                    create: *create,
+                    force: *force,
+                },
+            );
+        }
+        Commands::Export { format } => commands::export::execute(*format),
+        Commands::List {
+            porcelain: Some(version),
+            ..
+        } => commands::list::porcelain(*version),
+        Commands::List {
+            source, tree, long, ..
+        } => commands::list::execute(*source, *tree, *long),
+        Commands::Log { limit } => commands::log::execute(*limit),
+        Commands::Undo { steps, force } => commands::undo::undo(*steps, *force),
+        Commands::Redo { steps, force } => commands::undo::redo(*steps, *force),
+        Commands::History {
+            porcelain: Some(version),
+        } => backup::show_history_porcelain(*version),
+        Commands::History { porcelain: None } => backup::show_history(),
+        Commands::Snapshot {
+            install_timer: true,
+            ..
+        } => commands::snapshot::install_timer(),
+        Commands::Snapshot { name, .. } => {
+            commands::snapshot::execute(name.as_deref(), manager.current_mode())
+        }
+        Commands::Restore {
+            index,
+            timestamp,
+            latest: _,
+            previous,
+            before,
+        } => {
+            let selector = match (index, timestamp, before) {
+                (Some(index), _, _) => backup::BackupSelector::Index(*index),
+                (_, Some(timestamp), _) => backup::BackupSelector::Timestamp(timestamp.clone()),
+                (_, _, Some(time)) => backup::BackupSelector::Before(*time),
+                _ if *previous => backup::BackupSelector::Previous,
+                _ => backup::BackupSelector::Latest,
+            };
+            failed = !backup::restore_from_backup(&selector);
+        }
+        Commands::Flush {
+            relative,
+            allow_system,
+            strip_windows,
+            remove_unmounted,
+        } => commands::flush::execute(&commands::flush::FlushOptions {
+            relative: *relative,
+            allow_system: *allow_system,
+            strip_windows: *strip_windows,
+            remove_unmounted: *remove_unmounted,
+        }),
+        Commands::Normalize { resolve_symlinks } => commands::normalize::execute(*resolve_symlinks),
+        Commands::Clean {
+            resolve_symlinks,
+            allow_system,
+            strip_windows,
+        } => commands::clean::execute(*resolve_symlinks, *allow_system, *strip_windows),
+        Commands::Verify => {
+            if !commands::verify::execute() {
+                std::process::exit(1);
+            }
+        }
+        Commands::Scan => commands::scan::execute(),
+        Commands::Adopt => commands::adopt::execute(),
+        Commands::Audit => failed = !commands::audit::execute(),
+        Commands::Lint => failed = !commands::lint::execute(),
+        Commands::Shadows { names, versions } => commands::shadows::execute(names, *versions),
+        Commands::Bench { names, iterations } => commands::bench::execute(names, *iterations),
+        Commands::Status { .. } => commands::status::execute(),
+        Commands::Init { .. } => commands::init::execute(),
+        Commands::Serve { .. } => {
+            if cli.check || cli.emit.is_some() {
+                eprintln!("{}", msg!("serve-conflict"));
+                std::process::exit(1);
+            }
+            commands::serve::execute();
+            // The changes it ran logged themselves, and its standard output
+            // belongs to the protocol, so nothing is logged or printed here
+            return;
+        }
+        Commands::Cache { action } => match action {
+            CacheAction::Clear => commands::cache::clear(),
         },
+        Commands::SystemWrite => {
+            if let Err(e) = utils::system_env::install_from_stdin() {
+                eprintln!("{}", msg!("error", error = e));
+                std::process::exit(1);
+            }
+        }
+        Commands::Check {
+            path,
+            from_file,
+            porcelain,
+            declared,
+            notify,
+            quiet,
+            daily,
+        } => {
+            let source = match (path, from_file) {
+                (Some(value), _) => commands::check::CheckSource::Value(value),
+                (None, Some(file)) => commands::check::CheckSource::File(file),
+                (None, None) if *declared => commands::check::CheckSource::Declared,
+                (None, None) => commands::check::CheckSource::Environment,
+            };
+            match porcelain {
+                Some(version) => commands::check::porcelain(source, *version),
+                None if *quiet => commands::check::quiet(source, *daily),
+                None => commands::check::execute(source),
+            }
+            if *notify {
+                commands::check::notify(source);
+            }
+        }
+    }
+
+    if cli.check {
+        let diff = match cli.diff_format {
+            Some(format) => Some(format),
+            None => cli.diff.then_some(utils::check_mode::DiffFormat::Unified),
+        };
+        let pending = report_check_mode(diff);
+        if failed {
+            std::process::exit(1);
+        }
+        if pending {
+            std::process::exit(utils::check_mode::CHANGES_PENDING);
+        }
+        return;
+    }
+
+    // Undo and redo log themselves, linking to the operations they revert
+    if !matches!(cli.command, Commands::Undo { .. } | Commands::Redo { .. }) {
+        let expires = match &cli.command {
+            Commands::Add { ttl: Some(ttl), .. } => Some(utils::provenance::expiry_time(*ttl)),
+            _ => None,
+        };
+        if let Some(expires) = &expires {
+            record_existing_expiry(&directories, cli.reason.as_deref(), expires);
+        }
+        record_operation(cli.command.name(), cli.reason.as_deref(), expires);
+    }
+    // Restore prints its own instructions, including the rehash command
+    emit_environment(!matches!(cli.command, Commands::Restore { .. }));
+
+    if failed {
+        std::process::exit(1);
+    }
+}
+
+/// Reports what the command would have changed in check mode.
+///
+/// A JSON diff is printed last, on one line, even when nothing would
+/// change, so scripts can read it from the last line of output.
+///
+/// # Returns
+/// * `true` if PATH or any file would change
+fn report_check_mode(diff: Option<utils::check_mode::DiffFormat>) -> bool {
+    use utils::check_mode::DiffFormat;
+
+    let before = utils::initial_path_entries();
+    let after = utils::get_path_entries();
+    let writes = utils::check_mode::pending_writes();
+    let path_changed = before != after.as_slice();
+    let lines = |entries: &[std::path::PathBuf]| -> String {
+        entries
+            .iter()
+            .map(|entry| format!("{}\n", entry.display()))
+            .collect()
+    };
+    let print_json = || {
+        if diff == Some(DiffFormat::Json) {
+            println!(
+                "{}",
+                utils::check_mode::json_diff(&lines(before), &lines(&after), &writes)
+            );
+        }
+    };
+    if !path_changed && writes.is_empty() {
+        println!("{}", msg!("check-mode-no-changes"));
+        print_json();
+        return false;
+    }
+
+    if diff == Some(DiffFormat::Unified) {
+        print!(
+            "{}",
+            utils::check_mode::unified_diff("PATH", "PATH (new)", &lines(before), &lines(&after))
+        );
+        for write in &writes {
+            let file = write.file.display().to_string();
+            print!(
+                "{}",
+                utils::check_mode::unified_diff(
+                    &file,
+                    &format!("{} (new)", file),
+                    &write.before,
+                    &write.after
+                )
+            );
+        }
+    }
+
+    let mut pending: Vec<String> = writes
+        .iter()
+        .map(|write| write.file.display().to_string())
+        .collect();
+    if path_changed {
+        pending.insert(0, "PATH".to_string());
+    }
+    println!("{}", msg!("check-mode-pending", files = pending.join(", ")));
+    print_json();
+    true
+}
+
+/// Hands the new PATH to the calling shell when the command changed it.
+///
+/// With `--emit` the shell code goes to a file for a wrapper to source. In
+/// env-only mode without `--emit`, the code is printed so it can be run by
+/// hand, since no shell config will pick the change up. Both include the
+/// shell's rehash command; when only the shell config changed, a hint to run
+/// it is printed instead, unless `rehash_hint` is false. In a container
+/// without a shell config, a Dockerfile `ENV` line is printed.
+fn emit_environment(rehash_hint: bool) {
+    let settings = config::get_config();
+    let after = utils::get_path_entries();
+    let changed = utils::initial_path_entries() != after.as_slice();
+
+    match settings.emit_file {
+        Some(file) => {
+            // Always write the file so a wrapper never sources a stale one
+            let result = if changed {
+                utils::shell::emit::write_emit_file(&file, settings.shell, &after)
+            } else {
+                std::fs::write(&file, "")
+            };
+            if let Err(e) = result {
+                eprintln!("{}", msg!("error-write-file", file = file, error = e));
+            }
+        }
+        None if changed && settings.container => {
+            eprintln!("{}", msg!("container-not-persisted"));
+            eprintln!("{}", msg!("container-dockerfile"));
+            println!("{}", utils::container::dockerfile_env(&after));
+        }
+        None if changed && settings.env_only => {
+            eprintln!("{}", msg!("env-only-apply"));
+            eprintln!(
+                "{}",
+                utils::shell::emit::apply_statements(settings.shell, &after)
+            );
+        }
+        None if changed && rehash_hint && !settings.system => {
+            if let Some(rehash) = utils::shell::emit::rehash_command(settings.shell) {
+                println!("{}", msg!("rehash-hint", command = rehash));
+            }
+        }
+        None => {}
+    }
+}
+
+/// Appends the command to the operation log if it changed PATH, and records
+/// where the entries it added came from and when they expire
+fn record_operation(command: &str, reason: Option<&str>, expires: Option<String>) {
+    let before = utils::initial_path_entries();
+    let after = utils::get_path_entries();
+    if before == after.as_slice() {
+        return;
+    }
+
+    let mut record = utils::audit::AuditRecord::new(
+        command,
+        utils::audit::command_arguments(),
+        before,
+        &after,
+        backup::core::last_backup(),
+    );
+    record.reason = reason.map(str::to_string);
+    record.expires = expires;
+    if let Err(e) = utils::audit::append(&record) {
+        eprintln!("{}", msg!("warning-operation-log", error = e));
+    }
+    if let Err(e) = utils::provenance::record_operation(&record) {
+        eprintln!("{}", msg!("warning-provenance", error = e));
+    }
+}
+
+/// Gives directories `add --ttl` found already in PATH the expiry time too,
+/// so `expire` removes them along with the new ones
+fn record_existing_expiry(directories: &[OsString], reason: Option<&str>, expires: &str) {
+    let before = utils::initial_path_entries();
+    let existing: Vec<std::path::PathBuf> = directories
+        .iter()
+        .filter_map(|dir| utils::find_path_entry(before, &utils::expand_os_path(dir)))
+        .map(|index| before[index].clone())
+        .collect();
+    if existing.is_empty() {
+        return;
+    }
+
+    let provenance = utils::provenance::Provenance {
+        added: chrono::Local::now()
+            .format(utils::provenance::TIME_FORMAT)
+            .to_string(),
+        command: "add".to_string(),
+        arguments: utils::audit::command_arguments(),
+        reason: reason.map(str::to_string),
+        expires: Some(expires.to_string()),
+    };
+    match utils::provenance::set_expiry(&existing, &provenance) {
+        Ok(()) => {
+            for entry in &existing {
+                println!(
+                    "{}",
+                    msg!("add-ttl-existing", path = entry, expires = expires)
+                );
+            }
+        }
+        Err(e) => eprintln!("{}", msg!("warning-provenance", error = e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn test_cli_definition() {
+        Cli::command().debug_assert();
     }
 }
@@ -0,0 +1,327 @@
+//! Attribution of PATH entries to the package ecosystem that owns them.
+//!
+//! Ownership is guessed from well-known install layouts (`~/.cargo/bin`,
+//! `/opt/homebrew/bin`, `/snap/bin`, ...), honouring the environment
+//! variables that relocate them such as `$CARGO_HOME` or `$GOPATH`. Knowing
+//! that an entry belongs to a package manager helps decide whether it's safe
+//! to remove: it usually comes back on the next install, or breaks that tool.
+
+use crate::utils::root;
+use crate::utils::shell::locations;
+use std::env;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Package ecosystem that installs programs into a PATH entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ecosystem {
+    /// `cargo install` binaries
+    Cargo,
+    /// Rust toolchains managed by rustup
+    Rustup,
+    /// Globally installed npm packages
+    Npm,
+    /// Globally installed pnpm packages
+    Pnpm,
+    /// Python applications installed with pipx
+    Pipx,
+    /// `go install` binaries or the Go toolchain
+    Go,
+    /// Homebrew or Linuxbrew
+    Homebrew,
+    /// Flatpak application exports
+    Flatpak,
+    /// Snap packages
+    Snap,
+    /// Ruby versions managed by rbenv
+    Rbenv,
+    /// Python versions managed by pyenv
+    Pyenv,
+    /// Node versions managed by nodenv
+    Nodenv,
+    /// Tool versions managed by asdf
+    Asdf,
+    /// Node versions managed by nvm
+    Nvm,
+    /// Directories managed by the operating system's package manager
+    System,
+}
+
+impl fmt::Display for Ecosystem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Ecosystem::Cargo => "cargo",
+            Ecosystem::Rustup => "rustup",
+            Ecosystem::Npm => "npm",
+            Ecosystem::Pnpm => "pnpm",
+            Ecosystem::Pipx => "pipx",
+            Ecosystem::Go => "go",
+            Ecosystem::Homebrew => "homebrew",
+            Ecosystem::Flatpak => "flatpak",
+            Ecosystem::Snap => "snap",
+            Ecosystem::Rbenv => "rbenv",
+            Ecosystem::Pyenv => "pyenv",
+            Ecosystem::Nodenv => "nodenv",
+            Ecosystem::Asdf => "asdf",
+            Ecosystem::Nvm => "nvm",
+            Ecosystem::System => "system",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Install locations used to attribute entries
+#[derive(Debug, Clone, PartialEq)]
+pub struct Layout {
+    /// The user's home directory
+    pub home: PathBuf,
+    /// `$CARGO_HOME`, default `~/.cargo`
+    pub cargo_home: PathBuf,
+    /// `$RUSTUP_HOME`, default `~/.rustup`
+    pub rustup_home: PathBuf,
+    /// `$GOPATH`, default `~/go`
+    pub go_path: PathBuf,
+    /// `$GOBIN`, if set
+    pub go_bin: Option<PathBuf>,
+    /// `$PNPM_HOME`, default `$XDG_DATA_HOME/pnpm`
+    pub pnpm_home: PathBuf,
+    /// `$NPM_CONFIG_PREFIX`, if set
+    pub npm_prefix: Option<PathBuf>,
+    /// `$PIPX_BIN_DIR`, default `~/.local/bin`
+    pub pipx_bin_dir: PathBuf,
+    /// Whether pipx has installed anything, since its default bin directory is shared
+    pub pipx_installed: bool,
+    /// `$XDG_DATA_HOME`, default `~/.local/share`
+    pub data_home: PathBuf,
+    /// Whether `/usr/local` is a Homebrew prefix (Intel macOS)
+    pub usr_local_homebrew: bool,
+    /// `$RBENV_ROOT`, default `~/.rbenv`
+    pub rbenv_root: PathBuf,
+    /// `$PYENV_ROOT`, default `~/.pyenv`
+    pub pyenv_root: PathBuf,
+    /// `$NODENV_ROOT`, default `~/.nodenv`
+    pub nodenv_root: PathBuf,
+    /// `$ASDF_DATA_DIR`, default `~/.asdf`
+    pub asdf_dir: PathBuf,
+    /// `$NVM_DIR`, default `~/.nvm`
+    pub nvm_dir: PathBuf,
+}
+
+impl Layout {
+    /// Returns the default install locations for a home directory
+    pub fn for_home(home: &Path) -> Self {
+        let data_home = home.join(".local/share");
+        Self {
+            cargo_home: home.join(".cargo"),
+            rustup_home: home.join(".rustup"),
+            go_path: home.join("go"),
+            go_bin: None,
+            pnpm_home: data_home.join("pnpm"),
+            npm_prefix: None,
+            pipx_bin_dir: home.join(".local/bin"),
+            pipx_installed: false,
+            usr_local_homebrew: false,
+            rbenv_root: home.join(".rbenv"),
+            pyenv_root: home.join(".pyenv"),
+            nodenv_root: home.join(".nodenv"),
+            asdf_dir: home.join(".asdf"),
+            nvm_dir: home.join(".nvm"),
+            data_home,
+            home: home.to_path_buf(),
+        }
+    }
+
+    /// Reads the install locations from the environment
+    pub fn from_env() -> Self {
+        let env_dir = locations::absolute_env_dir;
+        let defaults = Self::for_home(&locations::real_home_dir());
+        let data_home = root::unrooted(locations::xdg_data_home());
+
+        let pipx_home = env_dir("PIPX_HOME").unwrap_or_else(|| data_home.join("pipx"));
+        let pipx_installed =
+            pipx_home.join("venvs").is_dir() || defaults.home.join(".local/pipx").is_dir();
+
+        Self {
+            cargo_home: env_dir("CARGO_HOME").unwrap_or(defaults.cargo_home),
+            rustup_home: env_dir("RUSTUP_HOME").unwrap_or(defaults.rustup_home),
+            go_path: go_path().unwrap_or(defaults.go_path),
+            go_bin: env_dir("GOBIN"),
+            pnpm_home: env_dir("PNPM_HOME").unwrap_or_else(|| data_home.join("pnpm")),
+            npm_prefix: env_dir("NPM_CONFIG_PREFIX"),
+            pipx_bin_dir: env_dir("PIPX_BIN_DIR").unwrap_or(defaults.pipx_bin_dir),
+            pipx_installed,
+            usr_local_homebrew: Path::new("/usr/local/Homebrew").is_dir(),
+            rbenv_root: env_dir("RBENV_ROOT").unwrap_or(defaults.rbenv_root),
+            pyenv_root: env_dir("PYENV_ROOT").unwrap_or(defaults.pyenv_root),
+            nodenv_root: env_dir("NODENV_ROOT").unwrap_or(defaults.nodenv_root),
+            asdf_dir: env_dir("ASDF_DATA_DIR").unwrap_or(defaults.asdf_dir),
+            nvm_dir: env_dir("NVM_DIR").unwrap_or(defaults.nvm_dir),
+            data_home,
+            home: defaults.home,
+        }
+    }
+
+    /// Returns the root directory of a version manager, e.g. `~/.rbenv`
+    pub fn manager_root(&self, ecosystem: Ecosystem) -> Option<&Path> {
+        match ecosystem {
+            Ecosystem::Rbenv => Some(&self.rbenv_root),
+            Ecosystem::Pyenv => Some(&self.pyenv_root),
+            Ecosystem::Nodenv => Some(&self.nodenv_root),
+            Ecosystem::Asdf => Some(&self.asdf_dir),
+            Ecosystem::Nvm => Some(&self.nvm_dir),
+            _ => None,
+        }
+    }
+
+    /// Guesses which ecosystem owns `entry`, if any
+    pub fn attribute(&self, entry: &Path) -> Option<Ecosystem> {
+        let home = &self.home;
+
+        if entry == self.cargo_home.join("bin") {
+            return Some(Ecosystem::Cargo);
+        }
+        if entry.starts_with(self.rustup_home.join("toolchains")) {
+            return Some(Ecosystem::Rustup);
+        }
+        for manager in [
+            Ecosystem::Rbenv,
+            Ecosystem::Pyenv,
+            Ecosystem::Nodenv,
+            Ecosystem::Asdf,
+            Ecosystem::Nvm,
+        ] {
+            if self
+                .manager_root(manager)
+                .is_some_and(|root| entry.starts_with(root))
+            {
+                return Some(manager);
+            }
+        }
+        if self.go_bin.as_deref() == Some(entry)
+            || entry == self.go_path.join("bin")
+            || entry == Path::new("/usr/local/go/bin")
+            || is_under_with_name(entry, &home.join("sdk"), "go")
+        {
+            return Some(Ecosystem::Go);
+        }
+        if entry.starts_with(&self.pnpm_home) || entry.starts_with(home.join("Library/pnpm")) {
+            return Some(Ecosystem::Pnpm);
+        }
+        if self
+            .npm_prefix
+            .as_ref()
+            .is_some_and(|prefix| entry == prefix.join("bin"))
+            || entry == home.join(".npm-global/bin")
+            || entry.ends_with("node_modules/.bin")
+        {
+            return Some(Ecosystem::Npm);
+        }
+        if self.pipx_installed && entry == self.pipx_bin_dir {
+            return Some(Ecosystem::Pipx);
+        }
+        if entry.starts_with("/opt/homebrew")
+            || entry.starts_with("/home/linuxbrew/.linuxbrew")
+            || entry.starts_with(home.join(".linuxbrew"))
+            || (self.usr_local_homebrew
+                && (entry == Path::new("/usr/local/bin") || entry == Path::new("/usr/local/sbin")))
+        {
+            return Some(Ecosystem::Homebrew);
+        }
+        if entry == Path::new("/var/lib/flatpak/exports/bin")
+            || entry == self.data_home.join("flatpak/exports/bin")
+        {
+            return Some(Ecosystem::Flatpak);
+        }
+        if entry == Path::new("/snap/bin") || entry == Path::new("/var/lib/snapd/snap/bin") {
+            return Some(Ecosystem::Snap);
+        }
+        if ["/bin", "/sbin", "/usr/bin", "/usr/sbin", "/usr/games"]
+            .iter()
+            .any(|dir| entry == Path::new(dir))
+        {
+            return Some(Ecosystem::System);
+        }
+        None
+    }
+}
+
+/// Returns the first directory of `$GOPATH`, which may list several
+fn go_path() -> Option<PathBuf> {
+    let value = env::var_os("GOPATH")?;
+    env::split_paths(&value).find(|path| path.is_absolute())
+}
+
+/// Whether `entry` is inside a directory of `parent` whose name starts with `name`
+fn is_under_with_name(entry: &Path, parent: &Path, name: &str) -> bool {
+    entry
+        .strip_prefix(parent)
+        .ok()
+        .and_then(|rest| rest.components().next())
+        .is_some_and(|first| first.as_os_str().to_string_lossy().starts_with(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layout() -> Layout {
+        let home = Path::new("/home/user");
+        Layout {
+            npm_prefix: Some(home.join(".npm-packages")),
+            pipx_installed: true,
+            ..Layout::for_home(home)
+        }
+    }
+
+    #[test]
+    fn test_attribute() {
+        let layout = layout();
+        let cases = [
+            ("/home/user/.cargo/bin", Some(Ecosystem::Cargo)),
+            (
+                "/home/user/.rustup/toolchains/stable-x86_64-unknown-linux-gnu/bin",
+                Some(Ecosystem::Rustup),
+            ),
+            ("/home/user/.npm-packages/bin", Some(Ecosystem::Npm)),
+            ("/srv/app/node_modules/.bin", Some(Ecosystem::Npm)),
+            ("/home/user/.local/share/pnpm", Some(Ecosystem::Pnpm)),
+            ("/home/user/.local/bin", Some(Ecosystem::Pipx)),
+            ("/home/user/go/bin", Some(Ecosystem::Go)),
+            ("/home/user/sdk/go1.22.0/bin", Some(Ecosystem::Go)),
+            ("/opt/homebrew/bin", Some(Ecosystem::Homebrew)),
+            ("/home/linuxbrew/.linuxbrew/sbin", Some(Ecosystem::Homebrew)),
+            ("/var/lib/flatpak/exports/bin", Some(Ecosystem::Flatpak)),
+            (
+                "/home/user/.local/share/flatpak/exports/bin",
+                Some(Ecosystem::Flatpak),
+            ),
+            ("/snap/bin", Some(Ecosystem::Snap)),
+            ("/home/user/.rbenv/shims", Some(Ecosystem::Rbenv)),
+            (
+                "/home/user/.nvm/versions/node/v20.11.0/bin",
+                Some(Ecosystem::Nvm),
+            ),
+            ("/usr/bin", Some(Ecosystem::System)),
+            ("/usr/local/bin", None),
+            ("/home/user/bin", None),
+        ];
+
+        for (entry, expected) in cases {
+            assert_eq!(layout.attribute(Path::new(entry)), expected, "{}", entry);
+        }
+    }
+
+    #[test]
+    fn test_shared_directories_need_evidence() {
+        let layout = Layout {
+            pipx_installed: false,
+            usr_local_homebrew: true,
+            ..layout()
+        };
+        assert_eq!(layout.attribute(Path::new("/home/user/.local/bin")), None);
+        assert_eq!(
+            layout.attribute(Path::new("/usr/local/bin")),
+            Some(Ecosystem::Homebrew)
+        );
+    }
+}
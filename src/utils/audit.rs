@@ -0,0 +1,331 @@
+//! Append-only log of operations that changed PATH.
+//!
+//! Each mutating command appends one JSON record per line to
+//! `$XDG_STATE_HOME/pathmaster/operations.jsonl` (default
+//! `~/.local/state/pathmaster/operations.jsonl`), holding the command line,
+//! PATH before and after, and the backup file created for it. Records are
+//! never rewritten, so the log answers "what changed my PATH, and when?".
+//! Undo and redo are recorded as operations too, linking to the records
+//! they revert, so the undo history can be rebuilt from the log alone.
+
+use crate::utils::check_mode;
+use crate::utils::path::{os_str_bytes, os_string_from_bytes};
+use crate::utils::shell::locations;
+use chrono::Local;
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+lazy_static! {
+    static ref REASON: Mutex<Option<String>> = Mutex::new(None);
+}
+
+/// Sets why the current command is run, from `--reason`
+pub fn set_reason(reason: Option<&str>) {
+    if let Ok(mut current) = REASON.lock() {
+        *current = reason.map(str::to_string);
+    }
+}
+
+/// Returns why the current command is run, if `--reason` was given
+pub fn current_reason() -> Option<String> {
+    REASON.lock().ok().and_then(|reason| reason.clone())
+}
+
+/// One operation that changed PATH
+///
+/// As in backups, entries that aren't valid UTF-8 are stored twice: `before`
+/// and `after` hold lossy copies for display, and `before_bytes` and
+/// `after_bytes` hold the exact bytes used by undo and redo. Records whose
+/// entries are all UTF-8 have no byte fields.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditRecord {
+    /// When the operation ran, as `%Y-%m-%d %H:%M:%S` local time
+    pub timestamp: String,
+    /// Name of the command, e.g. `add`
+    pub command: String,
+    /// Command-line arguments, excluding the program name
+    pub arguments: Vec<String>,
+    /// PATH entries before the operation
+    pub before: Vec<String>,
+    /// PATH entries after the operation
+    pub after: Vec<String>,
+    /// Raw bytes of each entry before, present only when one isn't valid UTF-8
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub before_bytes: Option<Vec<Vec<u8>>>,
+    /// Raw bytes of each entry after, present only when one isn't valid UTF-8
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub after_bytes: Option<Vec<Vec<u8>>>,
+    /// Backup file created by the operation
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backup: Option<PathBuf>,
+    /// Log positions of the operations this undo reverted, newest first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub undoes: Vec<usize>,
+    /// Log positions of the operations this redo reapplied, oldest first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub redoes: Vec<usize>,
+    /// Why the change was made, from `--reason`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// When the added entries expire, from `add --ttl`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires: Option<String>,
+}
+
+/// Returns this process's command-line arguments, excluding the program
+/// name, converted lossily like the entries of a record
+pub fn command_arguments() -> Vec<String> {
+    std::env::args_os()
+        .skip(1)
+        .map(|arg| arg.to_string_lossy().into_owned())
+        .collect()
+}
+
+impl AuditRecord {
+    /// Creates a record stamped with the current time
+    pub fn new(
+        command: &str,
+        arguments: Vec<String>,
+        before: &[PathBuf],
+        after: &[PathBuf],
+        backup: Option<PathBuf>,
+    ) -> Self {
+        let lossy = |entries: &[PathBuf]| -> Vec<String> {
+            entries
+                .iter()
+                .map(|entry| entry.to_string_lossy().into_owned())
+                .collect()
+        };
+
+        Self {
+            timestamp: Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+            command: command.to_string(),
+            arguments,
+            before: lossy(before),
+            after: lossy(after),
+            before_bytes: exact_bytes(before),
+            after_bytes: exact_bytes(after),
+            backup,
+            undoes: Vec::new(),
+            redoes: Vec::new(),
+            reason: None,
+            expires: None,
+        }
+    }
+
+    /// PATH entries before the operation, with their exact bytes
+    pub fn before_paths(&self) -> Vec<PathBuf> {
+        exact_paths(&self.before, self.before_bytes.as_deref())
+    }
+
+    /// PATH entries after the operation, with their exact bytes
+    pub fn after_paths(&self) -> Vec<PathBuf> {
+        exact_paths(&self.after, self.after_bytes.as_deref())
+    }
+
+    /// Entries present after the operation but not before
+    pub fn added(&self) -> Vec<&String> {
+        self.after
+            .iter()
+            .filter(|entry| !self.before.contains(entry))
+            .collect()
+    }
+
+    /// Entries present before the operation but not after
+    pub fn removed(&self) -> Vec<&String> {
+        self.before
+            .iter()
+            .filter(|entry| !self.after.contains(entry))
+            .collect()
+    }
+}
+
+/// Returns the bytes of each entry when any of them isn't valid UTF-8
+fn exact_bytes(entries: &[PathBuf]) -> Option<Vec<Vec<u8>>> {
+    entries
+        .iter()
+        .any(|entry| entry.to_str().is_none())
+        .then(|| {
+            entries
+                .iter()
+                .map(|entry| os_str_bytes(entry.as_os_str()))
+                .collect()
+        })
+}
+
+/// Returns entries from their raw bytes when stored, else from the lossy copies
+fn exact_paths(lossy: &[String], bytes: Option<&[Vec<u8>]>) -> Vec<PathBuf> {
+    match bytes {
+        Some(bytes) => bytes
+            .iter()
+            .map(|entry| PathBuf::from(os_string_from_bytes(entry.clone())))
+            .collect(),
+        None => lossy.iter().map(PathBuf::from).collect(),
+    }
+}
+
+/// Operations that can be undone or redone, as positions in the log
+#[derive(Debug, Default, PartialEq)]
+pub struct UndoStacks {
+    /// Operations in effect, oldest first; the last is undone first
+    pub done: Vec<usize>,
+    /// Undone operations, newest undo last; the last is redone first
+    pub undone: Vec<usize>,
+}
+
+/// Replays the log to find which operations can be undone and redone.
+///
+/// Undo and redo records move operations between the stacks. Any other
+/// operation starts a new history branch, so nothing undone before it can
+/// be redone.
+pub fn undo_stacks(records: &[AuditRecord]) -> UndoStacks {
+    let mut stacks = UndoStacks::default();
+    for (position, record) in records.iter().enumerate() {
+        if !record.undoes.is_empty() {
+            for id in &record.undoes {
+                stacks.done.retain(|done| done != id);
+                stacks.undone.push(*id);
+            }
+        } else if !record.redoes.is_empty() {
+            for id in &record.redoes {
+                stacks.undone.retain(|undone| undone != id);
+                stacks.done.push(*id);
+            }
+        } else {
+            stacks.done.push(position);
+            stacks.undone.clear();
+        }
+    }
+    stacks
+}
+
+/// Returns the location of the operation log
+pub fn log_path() -> PathBuf {
+    locations::xdg_state_home()
+        .join("pathmaster")
+        .join("operations.jsonl")
+}
+
+/// Appends a record to the operation log; nothing is written in check mode
+pub fn append(record: &AuditRecord) -> io::Result<()> {
+    if check_mode::is_check_mode() {
+        return Ok(());
+    }
+    append_to(&log_path(), record)
+}
+
+/// Reads every record from the operation log, oldest first
+pub fn read_all() -> io::Result<Vec<AuditRecord>> {
+    read_from(&log_path())
+}
+
+fn append_to(path: &Path, record: &AuditRecord) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let mut line = serde_json::to_string(record)?;
+    line.push('\n');
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?
+        .write_all(line.as_bytes())
+}
+
+/// Reads records from `path`, skipping lines that can't be parsed.
+///
+/// A missing log is treated as empty.
+fn read_from(path: &Path) -> io::Result<Vec<AuditRecord>> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+
+    Ok(content
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_append_and_read() {
+        let temp_dir = TempDir::new().unwrap();
+        let log = temp_dir.path().join("state/operations.jsonl");
+        assert!(read_from(&log).unwrap().is_empty());
+
+        let before = vec![PathBuf::from("/usr/bin"), PathBuf::from("/old")];
+        let after = vec![PathBuf::from("/new"), PathBuf::from("/usr/bin")];
+        let record = AuditRecord::new(
+            "add",
+            vec!["add".to_string(), "/new".to_string()],
+            &before,
+            &after,
+            Some(PathBuf::from("/backups/backup_1.json")),
+        );
+        append_to(&log, &record).unwrap();
+        append_to(&log, &record).unwrap();
+        fs::write(&log, fs::read_to_string(&log).unwrap() + "not json\n").unwrap();
+
+        let records = read_from(&log).unwrap();
+        assert_eq!(records, vec![record.clone(), record.clone()]);
+        assert_eq!(record.added(), vec!["/new"]);
+        assert_eq!(record.removed(), vec!["/old"]);
+    }
+
+    #[test]
+    fn test_non_utf8_entries_kept_exactly() {
+        let temp_dir = TempDir::new().unwrap();
+        let log = temp_dir.path().join("operations.jsonl");
+        let odd = PathBuf::from(os_string_from_bytes(b"/opt/caf\xe9/bin".to_vec()));
+        let before = vec![PathBuf::from("/usr/bin")];
+        let after = vec![PathBuf::from("/usr/bin"), odd.clone()];
+
+        let record = AuditRecord::new("add", Vec::new(), &before, &after, None);
+        append_to(&log, &record).unwrap();
+        let read = read_from(&log).unwrap().remove(0);
+
+        assert_eq!(read.before_paths(), before);
+        assert_eq!(read.after_paths(), after);
+        assert_eq!(read.after[1], "/opt/caf\u{fffd}/bin");
+        assert!(read.before_bytes.is_none());
+        assert!(!fs::read_to_string(&log).unwrap().contains("before_bytes"));
+    }
+
+    #[test]
+    fn test_undo_stacks() {
+        let op = |undoes: Vec<usize>, redoes: Vec<usize>| AuditRecord {
+            undoes,
+            redoes,
+            ..AuditRecord::new("add", Vec::new(), &[], &[], None)
+        };
+
+        // 0, 1, 2 applied; undo 2 and 1; redo 1
+        let mut records = vec![
+            op(vec![], vec![]),
+            op(vec![], vec![]),
+            op(vec![], vec![]),
+            op(vec![2, 1], vec![]),
+            op(vec![], vec![1]),
+        ];
+        let stacks = undo_stacks(&records);
+        assert_eq!(stacks.done, vec![0, 1]);
+        assert_eq!(stacks.undone, vec![2]);
+
+        // A new operation discards what could be redone
+        records.push(op(vec![], vec![]));
+        let stacks = undo_stacks(&records);
+        assert_eq!(stacks.done, vec![0, 1, 5]);
+        assert!(stacks.undone.is_empty());
+    }
+}
@@ -0,0 +1,130 @@
+//! On-disk cache of executable listings for PATH directories.
+//!
+//! Listing every PATH directory is slow on large PATHs and network
+//! filesystems. Listings are cached in
+//! `$XDG_CACHE_HOME/pathmaster/executables.json` keyed by directory, and an
+//! entry is reused only while the directory's modification time is unchanged.
+//! Adding or removing a file updates the directory mtime, which invalidates it.
+
+use crate::utils::shell::locations;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+/// Cached executables of a single directory
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct CachedListing {
+    /// Directory modification time the listing was taken at, in nanoseconds
+    modified: u128,
+    executables: Vec<String>,
+}
+
+/// Executable listings keyed by directory
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ExecutableCache {
+    directories: HashMap<PathBuf, CachedListing>,
+}
+
+/// Returns the location of the cache file.
+pub fn cache_path() -> PathBuf {
+    locations::xdg_cache_home()
+        .join("pathmaster")
+        .join("executables.json")
+}
+
+/// Returns a directory's modification time as a cache key.
+fn modified_key(directory: &Path) -> Option<u128> {
+    let modified = fs::metadata(directory).ok()?.modified().ok()?;
+    Some(modified.duration_since(UNIX_EPOCH).ok()?.as_nanos())
+}
+
+impl ExecutableCache {
+    /// Loads the cache file, starting empty when it is missing or unreadable.
+    pub fn load() -> Self {
+        fs::read_to_string(cache_path())
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// Writes the cache file, creating its directory if needed.
+    pub fn save(&self) -> io::Result<()> {
+        let path = cache_path();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string(self)?)
+    }
+
+    /// Returns the cached executables of a directory if it hasn't changed since.
+    pub fn get(&self, directory: &Path) -> Option<Vec<String>> {
+        let cached = self.directories.get(directory)?;
+        if modified_key(directory)? == cached.modified {
+            Some(cached.executables.clone())
+        } else {
+            None
+        }
+    }
+
+    /// Records the executables of a directory at its current modification time.
+    ///
+    /// Directories whose modification time can't be read are not cached,
+    /// nor are ones whose path isn't valid UTF-8, which can't be a JSON key.
+    pub fn insert(&mut self, directory: &Path, executables: Vec<String>) {
+        if directory.to_str().is_none() {
+            return;
+        }
+        if let Some(modified) = modified_key(directory) {
+            self.directories.insert(
+                directory.to_path_buf(),
+                CachedListing {
+                    modified,
+                    executables,
+                },
+            );
+        }
+    }
+}
+
+/// Deletes the cache file.
+///
+/// # Returns
+/// * `Ok(true)` - If a cache file was removed
+/// * `Ok(false)` - If there was no cache to remove
+pub fn clear() -> io::Result<bool> {
+    match fs::remove_file(cache_path()) {
+        Ok(()) => Ok(true),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_cache_invalidated_by_directory_changes() -> io::Result<()> {
+        let temp_dir = TempDir::new()?;
+        let dir = temp_dir.path();
+        let mut cache = ExecutableCache::default();
+
+        assert_eq!(cache.get(dir), None);
+
+        cache.insert(dir, vec!["cached-tool".to_string()]);
+        assert_eq!(cache.get(dir), Some(vec!["cached-tool".to_string()]));
+
+        // Force a different mtime, since back-to-back writes may share one
+        let entry = cache.directories.get_mut(dir).unwrap();
+        entry.modified = 0;
+        File::create(dir.join("new-tool"))?;
+        assert_eq!(cache.get(dir), None);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,350 @@
+//! Check mode, set from the `--check` flag.
+//!
+//! In check mode commands work out their changes as usual, but nothing is
+//! written: no backups, shell configs, hooks or log entries. The files that
+//! would have been written are collected here, so `main` can report whether
+//! anything would change and, with `--diff`, what: as unified diffs, or with
+//! `--diff-format json` as one JSON document for editors and scripts.
+
+use lazy_static::lazy_static;
+use serde::Serialize;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+/// Exit code when check mode finds changes pending
+pub const CHANGES_PENDING: i32 = 2;
+
+/// Lines of unchanged context around each change in a diff
+const CONTEXT: usize = 3;
+
+static CHECK_MODE: AtomicBool = AtomicBool::new(false);
+
+lazy_static! {
+    static ref PENDING_WRITES: Mutex<Vec<PendingWrite>> = Mutex::new(Vec::new());
+}
+
+/// A file check mode kept from being written
+#[derive(Debug, Clone, PartialEq)]
+pub struct PendingWrite {
+    pub file: PathBuf,
+    /// Current content, empty if the file doesn't exist
+    pub before: String,
+    /// Content that would have been written
+    pub after: String,
+}
+
+/// How `--diff` shows the changes
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum DiffFormat {
+    /// Unified diffs, as `diff -u` prints them
+    #[default]
+    Unified,
+    /// One JSON document listing removed and added lines per file
+    Json,
+}
+
+impl fmt::Display for DiffFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DiffFormat::Unified => write!(f, "unified"),
+            DiffFormat::Json => write!(f, "json"),
+        }
+    }
+}
+
+impl FromStr for DiffFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "unified" => Ok(DiffFormat::Unified),
+            "json" => Ok(DiffFormat::Json),
+            _ => Err(format!("Invalid diff format: {}", s)),
+        }
+    }
+}
+
+/// A line removed from the old text or added in the new one
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LineChange {
+    /// One-based line number: in the old text for removed lines, in the new
+    /// text for added ones
+    pub line: usize,
+    pub text: String,
+}
+
+/// The lines that differ between two texts
+#[derive(Debug, Clone, PartialEq, Default, Serialize)]
+pub struct LineChanges {
+    pub removed: Vec<LineChange>,
+    pub added: Vec<LineChange>,
+}
+
+/// Changes to one file, for the JSON diff
+#[derive(Debug, Clone, PartialEq, Serialize)]
+struct FileChanges {
+    file: String,
+    #[serde(flatten)]
+    changes: LineChanges,
+}
+
+/// Everything a command would change, for the JSON diff
+#[derive(Debug, Clone, PartialEq, Serialize)]
+struct DiffReport {
+    /// PATH, one entry per line; `null` if it wouldn't change
+    path: Option<LineChanges>,
+    files: Vec<FileChanges>,
+}
+
+/// Enables or disables check mode
+pub fn set_check_mode(enabled: bool) {
+    CHECK_MODE.store(enabled, Ordering::Relaxed);
+}
+
+/// Returns whether check mode is enabled
+pub fn is_check_mode() -> bool {
+    CHECK_MODE.load(Ordering::Relaxed)
+}
+
+/// Records that `file` would be written with `content`.
+///
+/// Writes that leave the file unchanged aren't recorded.
+pub fn record_write(file: &Path, content: &str) {
+    let before = fs::read_to_string(file).unwrap_or_default();
+    if before == content {
+        return;
+    }
+    if let Ok(mut pending) = PENDING_WRITES.lock() {
+        pending.retain(|write| write.file != file);
+        pending.push(PendingWrite {
+            file: file.to_path_buf(),
+            before,
+            after: content.to_string(),
+        });
+    }
+}
+
+/// Returns the files that would have been written
+pub fn pending_writes() -> Vec<PendingWrite> {
+    PENDING_WRITES
+        .lock()
+        .map(|pending| pending.clone())
+        .unwrap_or_default()
+}
+
+/// How a line of a diff relates the old text to the new
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Edit {
+    Keep,
+    Delete,
+    Insert,
+}
+
+/// Formats a unified diff between two texts; empty if they're equal
+pub fn unified_diff(old_label: &str, new_label: &str, before: &str, after: &str) -> String {
+    let old: Vec<&str> = before.lines().collect();
+    let new: Vec<&str> = after.lines().collect();
+    let edits = line_edits(&old, &new);
+    if edits.iter().all(|(edit, _)| *edit == Edit::Keep) {
+        return String::new();
+    }
+
+    let mut output = format!("--- {}\n+++ {}\n", old_label, new_label);
+    let changes: Vec<usize> = (0..edits.len())
+        .filter(|&index| edits[index].0 != Edit::Keep)
+        .collect();
+
+    let mut group_start = 0;
+    while group_start < changes.len() {
+        // Changes closer than twice the context share a hunk
+        let mut group_end = group_start;
+        while group_end + 1 < changes.len()
+            && changes[group_end + 1] - changes[group_end] <= 2 * CONTEXT
+        {
+            group_end += 1;
+        }
+        let start = changes[group_start].saturating_sub(CONTEXT);
+        let end = (changes[group_end] + CONTEXT + 1).min(edits.len());
+
+        let count = |range: &[(Edit, &str)], side: Edit| {
+            range
+                .iter()
+                .filter(|(edit, _)| *edit == Edit::Keep || *edit == side)
+                .count()
+        };
+        let old_start = count(&edits[..start], Edit::Delete);
+        let new_start = count(&edits[..start], Edit::Insert);
+        let old_len = count(&edits[start..end], Edit::Delete);
+        let new_len = count(&edits[start..end], Edit::Insert);
+        output.push_str(&format!(
+            "@@ -{},{} +{},{} @@\n",
+            old_start + usize::from(old_len > 0),
+            old_len,
+            new_start + usize::from(new_len > 0),
+            new_len
+        ));
+        for (edit, line) in &edits[start..end] {
+            let marker = match edit {
+                Edit::Keep => ' ',
+                Edit::Delete => '-',
+                Edit::Insert => '+',
+            };
+            output.push_str(&format!("{}{}\n", marker, line));
+        }
+        group_start = group_end + 1;
+    }
+    output
+}
+
+/// Lists the lines removed from `before` and added in `after`
+pub fn line_changes(before: &str, after: &str) -> LineChanges {
+    let old: Vec<&str> = before.lines().collect();
+    let new: Vec<&str> = after.lines().collect();
+    let mut changes = LineChanges::default();
+    let (mut old_line, mut new_line) = (0, 0);
+    for (edit, text) in line_edits(&old, &new) {
+        match edit {
+            Edit::Keep => {
+                old_line += 1;
+                new_line += 1;
+            }
+            Edit::Delete => {
+                old_line += 1;
+                changes.removed.push(LineChange {
+                    line: old_line,
+                    text: text.to_string(),
+                });
+            }
+            Edit::Insert => {
+                new_line += 1;
+                changes.added.push(LineChange {
+                    line: new_line,
+                    text: text.to_string(),
+                });
+            }
+        }
+    }
+    changes
+}
+
+/// Formats the changes to PATH and to the files that would be written as a
+/// JSON document on one line
+///
+/// # Example
+///
+/// ```json
+/// {"path":{"removed":[],"added":[{"line":3,"text":"/home/user/bin"}]},
+///  "files":[{"file":"/home/user/.bashrc","removed":[...],"added":[...]}]}
+/// ```
+pub fn json_diff(path_before: &str, path_after: &str, writes: &[PendingWrite]) -> String {
+    let report = DiffReport {
+        path: (path_before != path_after).then(|| line_changes(path_before, path_after)),
+        files: writes
+            .iter()
+            .map(|write| FileChanges {
+                file: write.file.to_string_lossy().into_owned(),
+                changes: line_changes(&write.before, &write.after),
+            })
+            .collect(),
+    };
+    serde_json::to_string(&report).unwrap_or_default()
+}
+
+/// Lines to keep, delete and insert to turn `old` into `new`, using the
+/// longest common subsequence
+fn line_edits<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<(Edit, &'a str)> {
+    // common[i][j] is the length of the LCS of old[i..] and new[j..]
+    let mut common = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = if old[i] == new[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let mut edits = Vec::with_capacity(old.len().max(new.len()));
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            edits.push((Edit::Keep, old[i]));
+            i += 1;
+            j += 1;
+        } else if j == new.len() || (i < old.len() && common[i + 1][j] >= common[i][j + 1]) {
+            edits.push((Edit::Delete, old[i]));
+            i += 1;
+        } else {
+            edits.push((Edit::Insert, new[j]));
+            j += 1;
+        }
+    }
+    edits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unified_diff() {
+        assert_eq!(unified_diff("a", "b", "same\n", "same\n"), "");
+
+        let before: String = (1..=20).map(|n| format!("{}\n", n)).collect();
+        let after = before.replace("\n5\n", "\nfive\n") + "21\n";
+        assert_eq!(
+            unified_diff("old", "new", &before, &after),
+            "--- old\n+++ new\n\
+             @@ -2,7 +2,7 @@\n 2\n 3\n 4\n-5\n+five\n 6\n 7\n 8\n\
+             @@ -18,3 +18,4 @@\n 18\n 19\n 20\n+21\n"
+        );
+
+        // Changes close together share a hunk
+        let after = before
+            .replace("\n5\n", "\nfive\n")
+            .replace("\n9\n", "\nnine\n");
+        let diff = unified_diff("old", "new", &before, &after);
+        assert_eq!(diff.matches("@@ -").count(), 1);
+        assert!(diff.contains("@@ -2,11 +2,11 @@\n"));
+    }
+
+    #[test]
+    fn test_json_diff() {
+        let changes = line_changes("a\nb\nc\n", "a\nB\nc\nd\n");
+        assert_eq!(
+            changes.removed,
+            vec![LineChange {
+                line: 2,
+                text: "b".to_string()
+            }]
+        );
+        assert_eq!(
+            changes.added.iter().map(|c| c.line).collect::<Vec<_>>(),
+            vec![2, 4]
+        );
+
+        let writes = vec![PendingWrite {
+            file: PathBuf::from("/pm-test/.bashrc"),
+            before: String::new(),
+            after: "export PATH=/usr/bin\n".to_string(),
+        }];
+        assert_eq!(
+            json_diff("/usr/bin\n", "/usr/bin\n", &writes),
+            "{\"path\":null,\"files\":[{\"file\":\"/pm-test/.bashrc\",\"removed\":[],\
+             \"added\":[{\"line\":1,\"text\":\"export PATH=/usr/bin\"}]}]}"
+        );
+    }
+
+    #[test]
+    fn test_unified_diff_new_file() {
+        assert_eq!(
+            unified_diff("old", "new", "", "export PATH=/usr/bin\n"),
+            "--- old\n+++ new\n@@ -0,0 +1,1 @@\n+export PATH=/usr/bin\n"
+        );
+    }
+}
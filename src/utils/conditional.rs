@@ -0,0 +1,305 @@
+//! PATH entries that are only wanted on some machines.
+//!
+//! Conditional entries are declared in the config file:
+//!
+//! ```toml
+//! [[conditional-entries]]
+//! entry = "~/.pyenv/bin"
+//! if-exists = "~/.pyenv"
+//!
+//! [[conditional-entries]]
+//! entry = "/opt/work/bin"
+//! hosts = ["workstation", "build01"]
+//! position = "first"
+//! ```
+//!
+//! Whenever pathmaster writes a new PATH, an entry whose conditions all hold
+//! is added if it's missing, and one whose conditions don't hold is removed.
+//! That lets one config shared through dotfiles serve several machines.
+
+use crate::utils;
+use crate::utils::rules::Position;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+/// A PATH entry and the conditions under which it belongs in PATH
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct ConditionalEntry {
+    /// Entry to add or remove; `~` is expanded
+    pub entry: String,
+    /// Only keep the entry if this path exists
+    pub if_exists: Option<String>,
+    /// Only keep the entry on these hosts
+    pub hosts: Vec<String>,
+    /// Only keep the entry on this operating system, e.g. `linux` or `macos`
+    pub os: Option<String>,
+    /// Where a missing entry is added; the end of PATH by default
+    pub position: Option<Position>,
+}
+
+impl fmt::Display for ConditionalEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut conditions = Vec::new();
+        if let Some(path) = &self.if_exists {
+            conditions.push(format!("{} exists", path));
+        }
+        if !self.hosts.is_empty() {
+            conditions.push(format!("host is {}", self.hosts.join(" or ")));
+        }
+        if let Some(os) = &self.os {
+            conditions.push(format!("OS is {}", os));
+        }
+        if conditions.is_empty() {
+            write!(f, "{}", self.entry)
+        } else {
+            write!(f, "{} (if {})", self.entry, conditions.join(" and "))
+        }
+    }
+}
+
+/// Facts about this machine that conditions are tested against
+#[derive(Debug, Clone, PartialEq)]
+pub struct Machine {
+    pub hostname: Option<String>,
+    pub os: String,
+}
+
+impl Machine {
+    /// Describes the machine pathmaster runs on
+    pub fn current() -> Self {
+        Machine {
+            hostname: hostname(),
+            os: std::env::consts::OS.to_string(),
+        }
+    }
+}
+
+impl ConditionalEntry {
+    /// Returns whether every condition of the entry holds on `machine`
+    pub fn applies(&self, machine: &Machine) -> bool {
+        let exists = self
+            .if_exists
+            .as_deref()
+            .is_none_or(|path| utils::expand_path(path).exists());
+        let host = self.hosts.is_empty()
+            || machine.hostname.as_deref().is_some_and(|hostname| {
+                self.hosts
+                    .iter()
+                    .any(|host| host.eq_ignore_ascii_case(hostname))
+            });
+        let os = self.os.as_deref().is_none_or(|os| {
+            os.eq_ignore_ascii_case(&machine.os)
+                || (os.eq_ignore_ascii_case("darwin") && machine.os == "macos")
+        });
+        exists && host && os
+    }
+}
+
+/// Changes made to satisfy the conditional entries
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Outcome {
+    /// Entries whose conditions hold that were missing
+    pub added: Vec<PathBuf>,
+    /// Entries whose conditions don't hold that were in PATH
+    pub removed: Vec<PathBuf>,
+}
+
+/// Adds entries whose conditions hold and removes those whose conditions don't.
+///
+/// Entries with `position = "first"` go to the front in declared order; the
+/// others are appended. Entries already in PATH aren't moved.
+///
+/// # Returns
+/// * The updated entries and what changed
+pub fn apply(
+    conditionals: &[ConditionalEntry],
+    entries: &[PathBuf],
+    machine: &Machine,
+) -> (Vec<PathBuf>, Outcome) {
+    let mut entries = entries.to_vec();
+    let mut outcome = Outcome::default();
+    let mut front = 0;
+
+    for conditional in conditionals {
+        let dir = utils::expand_path(&conditional.entry);
+        let present = utils::find_path_entry(&entries, &dir).is_some();
+        if conditional.applies(machine) {
+            if present {
+                continue;
+            }
+            if conditional.position == Some(Position::First) {
+                entries.insert(front, dir.clone());
+                front += 1;
+            } else {
+                entries.push(dir.clone());
+            }
+            outcome.added.push(dir);
+        } else if present {
+            while let Some(index) = utils::find_path_entry(&entries, &dir) {
+                entries.remove(index);
+            }
+            outcome.removed.push(dir);
+        }
+    }
+    (entries, outcome)
+}
+
+/// Returns the conditional entries the running command took out of PATH
+///
+/// An entry counts as taken out when it was in `before` but isn't in
+/// `after`, e.g. after `pathmaster remove`. Re-adding it straight away
+/// would silently undo the command.
+pub fn withdrawn<'a>(
+    conditionals: &'a [ConditionalEntry],
+    before: &[PathBuf],
+    after: &[PathBuf],
+) -> Vec<&'a ConditionalEntry> {
+    conditionals
+        .iter()
+        .filter(|conditional| {
+            let dir = utils::expand_path(&conditional.entry);
+            utils::find_path_entry(before, &dir).is_some()
+                && utils::find_path_entry(after, &dir).is_none()
+        })
+        .collect()
+}
+
+/// Returns the name of this machine, without any domain part
+fn hostname() -> Option<String> {
+    let name = ["/proc/sys/kernel/hostname", "/etc/hostname"]
+        .iter()
+        .find_map(|file| fs::read_to_string(file).ok())
+        .or_else(|| {
+            Command::new("hostname")
+                .output()
+                .ok()
+                .filter(|output| output.status.success())
+                .map(|output| String::from_utf8_lossy(&output.stdout).into_owned())
+        })?;
+    let name = name.trim();
+    let name = name.split('.').next().unwrap_or(name);
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_support::paths;
+
+    fn machine(hostname: &str) -> Machine {
+        Machine {
+            hostname: Some(hostname.to_string()),
+            os: "linux".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_parse_conditional_entries() {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "kebab-case")]
+        struct Config {
+            conditional_entries: Vec<ConditionalEntry>,
+        }
+        let config: Config = toml::from_str(
+            r#"
+            [[conditional-entries]]
+            entry = "~/.pyenv/bin"
+            if-exists = "~/.pyenv"
+
+            [[conditional-entries]]
+            entry = "/opt/work/bin"
+            hosts = ["workstation"]
+            position = "first"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.conditional_entries.len(), 2);
+        assert_eq!(
+            config.conditional_entries[0].if_exists.as_deref(),
+            Some("~/.pyenv")
+        );
+        assert_eq!(
+            config.conditional_entries[1].position,
+            Some(Position::First)
+        );
+    }
+
+    #[test]
+    fn test_applies() {
+        let work = ConditionalEntry {
+            entry: "/pm-test/work/bin".to_string(),
+            hosts: vec!["Workstation".to_string()],
+            ..Default::default()
+        };
+        assert!(work.applies(&machine("workstation")));
+        assert!(!work.applies(&machine("laptop")));
+
+        let pyenv = ConditionalEntry {
+            entry: "/pm-test/pyenv/bin".to_string(),
+            if_exists: Some("/pm-test/pyenv".to_string()),
+            ..Default::default()
+        };
+        assert!(!pyenv.applies(&machine("laptop")));
+
+        let mac = ConditionalEntry {
+            entry: "/opt/homebrew/bin".to_string(),
+            os: Some("macos".to_string()),
+            ..Default::default()
+        };
+        assert!(!mac.applies(&machine("laptop")));
+    }
+
+    #[test]
+    fn test_apply() {
+        let conditionals = vec![
+            ConditionalEntry {
+                entry: "/pm-test/work/bin".to_string(),
+                hosts: vec!["workstation".to_string()],
+                position: Some(Position::First),
+                ..Default::default()
+            },
+            ConditionalEntry {
+                entry: "/pm-test/tools/bin".to_string(),
+                ..Default::default()
+            },
+        ];
+
+        let entries = paths(&["/usr/bin"]);
+        let (updated, outcome) = apply(&conditionals, &entries, &machine("workstation"));
+        assert_eq!(
+            updated,
+            paths(&["/pm-test/work/bin", "/usr/bin", "/pm-test/tools/bin"])
+        );
+        assert_eq!(outcome.added.len(), 2);
+
+        // On another host the host-specific entry is taken out again
+        let (updated, outcome) = apply(&conditionals, &updated, &machine("laptop"));
+        assert_eq!(updated, paths(&["/usr/bin", "/pm-test/tools/bin"]));
+        assert_eq!(outcome.removed, paths(&["/pm-test/work/bin"]));
+        assert!(outcome.added.is_empty());
+    }
+
+    #[test]
+    fn test_withdrawn() {
+        let conditionals = vec![
+            ConditionalEntry {
+                entry: "/pm-test/work/bin".to_string(),
+                ..Default::default()
+            },
+            ConditionalEntry {
+                entry: "/pm-test/tools/bin".to_string(),
+                ..Default::default()
+            },
+        ];
+        let before = paths(&["/usr/bin", "/pm-test/work/bin"]);
+        let after = paths(&["/usr/bin"]);
+
+        // Only the entry that was in PATH and was removed counts
+        let withdrawn = withdrawn(&conditionals, &before, &after);
+        assert_eq!(withdrawn, vec![&conditionals[0]]);
+    }
+}
@@ -0,0 +1,68 @@
+//! Detection of containers without a shell setup.
+//!
+//! Container images often have no login shell, no `$SHELL` and no rc files,
+//! so there is nothing to persist PATH to. Changing PATH there is done in the
+//! Dockerfile instead. When pathmaster runs in a container and the shell it
+//! would manage has no config file, it works in env-only mode and prints an
+//! `ENV` line to put in the Dockerfile.
+
+use crate::utils::shell::quoting::escape_double_quoted;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Files container runtimes create inside the container
+const MARKER_FILES: &[&str] = &["/.dockerenv", "/run/.containerenv"];
+
+/// Cgroup path segments of common container runtimes
+const CGROUP_MARKERS: &[&str] = &["docker", "kubepods", "containerd", "libpod", "lxc"];
+
+/// Returns whether pathmaster runs inside a container
+pub fn in_container() -> bool {
+    MARKER_FILES.iter().any(|file| Path::new(file).exists())
+        || env::var_os("container").is_some_and(|value| !value.is_empty())
+        || fs::read_to_string("/proc/1/cgroup").is_ok_and(|content| cgroup_in_container(&content))
+}
+
+/// Returns whether `/proc/1/cgroup` content names a container runtime
+fn cgroup_in_container(content: &str) -> bool {
+    content.lines().any(|line| {
+        line.rsplit(':')
+            .next()
+            .is_some_and(|path| CGROUP_MARKERS.iter().any(|marker| path.contains(marker)))
+    })
+}
+
+/// Formats a Dockerfile `ENV` instruction setting PATH to `entries`
+pub fn dockerfile_env(entries: &[PathBuf]) -> String {
+    let value = entries
+        .iter()
+        .map(|entry| entry.to_string_lossy())
+        .collect::<Vec<_>>()
+        .join(":");
+    format!("ENV PATH=\"{}\"", escape_double_quoted(&value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cgroup_in_container() {
+        assert!(cgroup_in_container(
+            "12:memory:/docker/3f1c2a\n0::/system.slice\n"
+        ));
+        assert!(cgroup_in_container("0::/kubepods/besteffort/pod1234\n"));
+        assert!(!cgroup_in_container("0::/init.scope\n"));
+        assert!(!cgroup_in_container("0::/\n"));
+    }
+
+    #[test]
+    fn test_dockerfile_env() {
+        let entries = vec![PathBuf::from("/opt/app/bin"), PathBuf::from("/usr/bin")];
+        assert_eq!(
+            dockerfile_env(&entries),
+            "ENV PATH=\"/opt/app/bin:/usr/bin\""
+        );
+    }
+}
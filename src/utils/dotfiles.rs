@@ -0,0 +1,162 @@
+//! Commits shell config changes to the user's dotfiles repository.
+//!
+//! When `dotfiles-repo` is set in the config, files pathmaster modifies that
+//! live inside that git repository (directly or through a symlink, as most
+//! dotfiles managers arrange) are committed after a successful update. Git
+//! is run as a subprocess so the user's own git config, hooks and signing
+//! settings apply.
+
+use crate::config;
+use crate::msg;
+use crate::utils::verbose;
+use chrono::Local;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Commit message used when the config doesn't set one
+pub const DEFAULT_COMMIT_MESSAGE: &str = "pathmaster: update PATH in {files}";
+
+/// Commits modified files to the configured dotfiles repository.
+///
+/// Does nothing when no repository is configured or none of the files are
+/// inside it. Failures are reported as warnings, since the PATH update
+/// itself has already succeeded.
+pub fn commit_changes(files: &[PathBuf]) {
+    let settings = config::get_config();
+    let repo = match settings.dotfiles_repo {
+        Some(repo) => repo,
+        None => return,
+    };
+    let template = settings
+        .dotfiles_commit_message
+        .unwrap_or_else(|| DEFAULT_COMMIT_MESSAGE.to_string());
+
+    if let Err(e) = commit_files(&repo, files, &template) {
+        eprintln!(
+            "{}",
+            msg!("dotfiles-commit-failed", repo = &repo, error = e)
+        );
+    }
+}
+
+/// Stages and commits the files that are inside `repo`
+fn commit_files(repo: &Path, files: &[PathBuf], template: &str) -> io::Result<()> {
+    let tracked = files_in_repo(repo, files)?;
+    if tracked.is_empty() {
+        if verbose::is_verbose() {
+            println!("{}", msg!("dotfiles-nothing", repo = repo));
+        }
+        return Ok(());
+    }
+
+    let status = git(repo, &["status", "--porcelain", "--"], &tracked)?;
+    if status.trim().is_empty() {
+        return Ok(());
+    }
+
+    let message = render_message(template, &tracked);
+    git(repo, &["add", "--"], &tracked)?;
+    git(repo, &["commit", "--quiet", "-m", &message, "--"], &tracked)?;
+    println!(
+        "{}",
+        msg!(
+            "dotfiles-committed",
+            files = display_list(&tracked),
+            repo = repo
+        )
+    );
+    Ok(())
+}
+
+/// Returns the files located inside `repo`, relative to its root.
+///
+/// Symlinks are resolved first, so `~/.bashrc` linked to
+/// `~/dotfiles/bashrc` is committed as `bashrc`.
+fn files_in_repo(repo: &Path, files: &[PathBuf]) -> io::Result<Vec<PathBuf>> {
+    let repo = fs::canonicalize(repo)?;
+    let mut tracked = Vec::new();
+    for file in files {
+        let resolved = match fs::canonicalize(file) {
+            Ok(resolved) => resolved,
+            Err(_) => continue,
+        };
+        if let Ok(relative) = resolved.strip_prefix(&repo) {
+            if !tracked.iter().any(|path: &PathBuf| path == relative) {
+                tracked.push(relative.to_path_buf());
+            }
+        }
+    }
+    Ok(tracked)
+}
+
+/// Fills in the commit message template.
+///
+/// `{files}` is replaced by the committed files and `{date}` by the current
+/// date and time.
+fn render_message(template: &str, files: &[PathBuf]) -> String {
+    template.replace("{files}", &display_list(files)).replace(
+        "{date}",
+        &Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+    )
+}
+
+fn display_list(files: &[PathBuf]) -> String {
+    files
+        .iter()
+        .map(|file| file.display().to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Runs git in `repo` with the given arguments followed by `paths`
+fn git(repo: &Path, args: &[&str], paths: &[PathBuf]) -> io::Result<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(args)
+        .args(paths)
+        .output()?;
+
+    if !output.status.success() {
+        return Err(io::Error::other(format!(
+            "git {} failed: {}",
+            args[0],
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_files_in_repo_follows_symlinks() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = temp_dir.path().join("dotfiles");
+        fs::create_dir(&repo).unwrap();
+        fs::write(repo.join("bashrc"), "").unwrap();
+        let link = temp_dir.path().join(".bashrc");
+        std::os::unix::fs::symlink(repo.join("bashrc"), &link).unwrap();
+        let outside = temp_dir.path().join(".zshrc");
+        fs::write(&outside, "").unwrap();
+
+        let tracked = files_in_repo(&repo, &[link, outside, repo.join("bashrc")]).unwrap();
+        assert_eq!(tracked, vec![PathBuf::from("bashrc")]);
+    }
+
+    #[test]
+    fn test_render_message() {
+        let files = vec![PathBuf::from("bashrc"), PathBuf::from("config.fish")];
+        assert_eq!(
+            render_message(DEFAULT_COMMIT_MESSAGE, &files),
+            "pathmaster: update PATH in bashrc, config.fish"
+        );
+        assert!(!render_message("PATH at {date}", &files).contains("{date}"));
+    }
+}
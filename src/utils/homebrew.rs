@@ -0,0 +1,178 @@
+//! Homebrew's place in PATH.
+//!
+//! Homebrew installs into `/opt/homebrew` on Apple Silicon Macs, into
+//! `/usr/local` on Intel Macs and into `/home/linuxbrew/.linuxbrew` on
+//! Linux. Its `bin` and `sbin` directories have to come before the system
+//! directories: otherwise the older tools macOS ships, such as `git`,
+//! `python3` or `ruby`, run instead of the ones installed with `brew`. This
+//! module finds the prefix and checks where its directories are in PATH, so
+//! `check` can suggest the fix.
+
+use crate::msg;
+use crate::utils::find_path_entry;
+use crate::utils::shell::locations;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Where Homebrew is installed, most specific first
+const PREFIXES: &[&str] = &["/opt/homebrew", "/home/linuxbrew/.linuxbrew"];
+
+/// System directories Homebrew's directories should come before
+const SYSTEM_DIRS: &[&str] = &["/usr/bin", "/bin", "/usr/sbin", "/sbin"];
+
+/// A problem with Homebrew's directories in PATH
+#[derive(Debug, Clone, PartialEq)]
+pub enum HomebrewProblem {
+    /// A Homebrew directory comes after a system directory
+    AfterSystem {
+        /// The Homebrew directory
+        entry: PathBuf,
+        /// The first system directory before it
+        system: PathBuf,
+    },
+    /// Homebrew is installed but its `bin` directory isn't in PATH
+    NotInPath {
+        /// The missing `bin` directory
+        entry: PathBuf,
+    },
+}
+
+impl fmt::Display for HomebrewProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HomebrewProblem::AfterSystem { entry, system } => write!(
+                f,
+                "{}",
+                msg!("homebrew-after-system", path = entry, system = system)
+            ),
+            HomebrewProblem::NotInPath { entry } => {
+                write!(f, "{}", msg!("homebrew-not-in-path", path = entry))
+            }
+        }
+    }
+}
+
+/// Returns the Homebrew prefix of this machine, if Homebrew is installed
+///
+/// `$HOMEBREW_PREFIX`, set by `brew shellenv`, wins. Otherwise the standard
+/// prefixes are tried, with `/usr/local` only counting when it holds a
+/// Homebrew installation, since other software uses it too.
+pub fn detect_prefix() -> Option<PathBuf> {
+    if let Some(prefix) = locations::absolute_env_dir("HOMEBREW_PREFIX") {
+        if prefix.join("bin/brew").exists() {
+            return Some(prefix);
+        }
+    }
+    PREFIXES
+        .iter()
+        .map(PathBuf::from)
+        .find(|prefix| prefix.join("bin/brew").exists())
+        .or_else(|| {
+            Path::new("/usr/local/Homebrew")
+                .is_dir()
+                .then(|| PathBuf::from("/usr/local"))
+        })
+}
+
+/// Returns the directories of a prefix that belong at the front of PATH:
+/// `bin`, and `sbin` if it exists or is in PATH
+pub fn front_dirs(prefix: &Path, entries: &[PathBuf]) -> Vec<PathBuf> {
+    let sbin = prefix.join("sbin");
+    let mut dirs = vec![prefix.join("bin")];
+    if sbin.is_dir() || find_path_entry(entries, &sbin).is_some() {
+        dirs.push(sbin);
+    }
+    dirs
+}
+
+/// Checks the position of a prefix's directories in PATH
+///
+/// # Arguments
+///
+/// * `prefix` - The Homebrew prefix
+/// * `entries` - The PATH entries
+///
+/// # Returns
+///
+/// The problems found, in the order of `bin` then `sbin`
+pub fn problems(prefix: &Path, entries: &[PathBuf]) -> Vec<HomebrewProblem> {
+    let first_system = SYSTEM_DIRS
+        .iter()
+        .filter_map(|dir| find_path_entry(entries, Path::new(dir)))
+        .min();
+
+    let mut problems = Vec::new();
+    for dir in ["bin", "sbin"] {
+        let entry = prefix.join(dir);
+        match find_path_entry(entries, &entry) {
+            Some(position) => {
+                if let Some(system) = first_system.filter(|system| *system < position) {
+                    problems.push(HomebrewProblem::AfterSystem {
+                        entry,
+                        system: entries[system].clone(),
+                    });
+                }
+            }
+            None if dir == "bin" => problems.push(HomebrewProblem::NotInPath { entry }),
+            None => {}
+        }
+    }
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entries(path: &str) -> Vec<PathBuf> {
+        path.split(':').map(PathBuf::from).collect()
+    }
+
+    #[test]
+    fn test_problems() {
+        let prefix = Path::new("/nonexistent/homebrew");
+
+        assert!(problems(
+            prefix,
+            &entries("/nonexistent/homebrew/bin:/nonexistent/homebrew/sbin:/usr/bin:/bin")
+        )
+        .is_empty());
+
+        assert_eq!(
+            problems(
+                prefix,
+                &entries("/usr/bin:/bin:/nonexistent/homebrew/bin:/nonexistent/homebrew/sbin")
+            ),
+            vec![
+                HomebrewProblem::AfterSystem {
+                    entry: PathBuf::from("/nonexistent/homebrew/bin"),
+                    system: PathBuf::from("/usr/bin"),
+                },
+                HomebrewProblem::AfterSystem {
+                    entry: PathBuf::from("/nonexistent/homebrew/sbin"),
+                    system: PathBuf::from("/usr/bin"),
+                },
+            ]
+        );
+
+        assert_eq!(
+            problems(prefix, &entries("/usr/bin:/bin")),
+            vec![HomebrewProblem::NotInPath {
+                entry: PathBuf::from("/nonexistent/homebrew/bin"),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_front_dirs() {
+        let prefix = Path::new("/nonexistent/homebrew");
+        assert_eq!(
+            front_dirs(prefix, &entries("/usr/bin")),
+            vec![PathBuf::from("/nonexistent/homebrew/bin")]
+        );
+        assert_eq!(
+            front_dirs(prefix, &entries("/usr/bin:/nonexistent/homebrew/sbin")).len(),
+            2
+        );
+    }
+}
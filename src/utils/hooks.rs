@@ -0,0 +1,184 @@
+//! User hook scripts run around PATH updates.
+//!
+//! The config can name a `pre-update` and a `post-update` command. Each is
+//! run with `sh -c` and receives a JSON description of the change on stdin (one line),
+//! with the event name also in `$PATHMASTER_EVENT`. A failing pre-update
+//! hook cancels the update, so it can act as a validation gate; a failing
+//! post-update hook only produces a warning.
+
+use crate::config;
+use crate::utils::audit;
+use lazy_static::lazy_static;
+use serde::Serialize;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+
+lazy_static! {
+    static ref CURRENT_COMMAND: Mutex<Option<String>> = Mutex::new(None);
+}
+
+/// When a hook runs
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HookEvent {
+    /// Before shell configs are written
+    PreUpdate,
+    /// After shell configs were written successfully
+    PostUpdate,
+}
+
+impl HookEvent {
+    /// Name of the event, matching its config key
+    pub fn name(self) -> &'static str {
+        match self {
+            HookEvent::PreUpdate => "pre-update",
+            HookEvent::PostUpdate => "post-update",
+        }
+    }
+}
+
+/// Description of a PATH change passed to hooks as JSON
+#[derive(Debug, Serialize)]
+pub struct HookPayload {
+    /// `pre-update` or `post-update`
+    pub event: String,
+    /// Command that made the change, e.g. `add`
+    pub command: Option<String>,
+    /// Command-line arguments, excluding the program name
+    pub arguments: Vec<String>,
+    /// PATH entries before the change
+    pub before: Vec<String>,
+    /// PATH entries after the change
+    pub after: Vec<String>,
+    /// Entries added by the change
+    pub added: Vec<String>,
+    /// Entries removed by the change
+    pub removed: Vec<String>,
+}
+
+impl HookPayload {
+    /// Describes a change from `before` to `after`
+    pub fn new(event: HookEvent, before: &[PathBuf], after: &[PathBuf]) -> Self {
+        Self {
+            event: event.name().to_string(),
+            command: current_command(),
+            arguments: audit::command_arguments(),
+            before: lossy(before.iter()),
+            after: lossy(after.iter()),
+            added: lossy(after.iter().filter(|entry| !before.contains(entry))),
+            removed: lossy(before.iter().filter(|entry| !after.contains(entry))),
+        }
+    }
+}
+
+fn lossy<'a>(entries: impl Iterator<Item = &'a PathBuf>) -> Vec<String> {
+    entries
+        .map(|entry| entry.to_string_lossy().into_owned())
+        .collect()
+}
+
+/// Records the name of the running command for hook payloads
+pub fn set_current_command(command: &str) {
+    if let Ok(mut current) = CURRENT_COMMAND.lock() {
+        *current = Some(command.to_string());
+    }
+}
+
+/// Returns the name of the running command, if set
+pub fn current_command() -> Option<String> {
+    CURRENT_COMMAND
+        .lock()
+        .ok()
+        .and_then(|current| current.clone())
+}
+
+/// Runs the configured hook for an event, if any.
+///
+/// # Returns
+/// * `Ok(())` - If no hook is configured or it exited successfully
+/// * `Err(io::Error)` - If the hook couldn't be run or exited with an error
+pub fn run_hook(event: HookEvent, before: &[PathBuf], after: &[PathBuf]) -> io::Result<()> {
+    let settings = config::get_config();
+    let command = match event {
+        HookEvent::PreUpdate => settings.pre_update,
+        HookEvent::PostUpdate => settings.post_update,
+    };
+
+    match command {
+        Some(command) => run_command(&command, &HookPayload::new(event, before, after)),
+        None => Ok(()),
+    }
+}
+
+/// Runs a hook command with the payload on stdin
+fn run_command(command: &str, payload: &HookPayload) -> io::Result<()> {
+    let mut json = serde_json::to_string(payload)?;
+    json.push('\n');
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .env("PATHMASTER_EVENT", &payload.event)
+        .stdin(Stdio::piped())
+        .spawn()?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        // A hook that doesn't read its input closes the pipe early; that's fine
+        if let Err(e) = stdin.write_all(json.as_bytes()) {
+            if e.kind() != io::ErrorKind::BrokenPipe {
+                return Err(e);
+            }
+        }
+    }
+
+    let status = child.wait()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!(
+            "{} hook '{}' failed ({})",
+            payload.event, command, status
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn payload() -> HookPayload {
+        HookPayload::new(
+            HookEvent::PreUpdate,
+            &[PathBuf::from("/usr/bin"), PathBuf::from("/old")],
+            &[PathBuf::from("/usr/bin"), PathBuf::from("/new")],
+        )
+    }
+
+    #[test]
+    fn test_hook_receives_payload() {
+        let temp_dir = TempDir::new().unwrap();
+        let output = temp_dir.path().join("payload.json");
+        let command = format!(
+            "cat > '{}'; echo \"$PATHMASTER_EVENT\" >> '{}'",
+            output.display(),
+            output.display()
+        );
+
+        run_command(&command, &payload()).unwrap();
+
+        let written = fs::read_to_string(&output).unwrap();
+        let (json, event) = written.split_once('\n').unwrap();
+        let json: serde_json::Value = serde_json::from_str(json).unwrap();
+        assert_eq!(json["added"], serde_json::json!(["/new"]));
+        assert_eq!(json["removed"], serde_json::json!(["/old"]));
+        assert_eq!(event.trim(), "pre-update");
+    }
+
+    #[test]
+    fn test_failing_hook_is_an_error() {
+        assert!(run_command("exit 3", &payload()).is_err());
+        assert!(run_command("true", &payload()).is_ok());
+    }
+}
@@ -0,0 +1,156 @@
+//! The problems the last recorded check found.
+//!
+//! `check --notify` saves what it found to
+//! `$XDG_STATE_HOME/pathmaster/last-check.json` so the next run can tell
+//! new problems from ones it already reported, and `check --quiet` saves
+//! it too, so `--daily` can skip checking when the last check is recent.
+
+use crate::utils::path::serde_path;
+use crate::utils::provenance::TIME_FORMAT;
+use crate::utils::shell::locations;
+use chrono::{Duration, Local, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// One problem found by a check
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Problem {
+    /// The porcelain problem kind, e.g. `missing`
+    pub kind: String,
+    /// The entry or file the problem is about
+    #[serde(with = "serde_path")]
+    pub path: PathBuf,
+    /// Extra detail, empty if there is none
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub detail: String,
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.kind, self.path.display())?;
+        if !self.detail.is_empty() {
+            write!(f, " ({})", self.detail)?;
+        }
+        Ok(())
+    }
+}
+
+/// The result of the last recorded check
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LastCheck {
+    /// When the check ran, as `%Y-%m-%d %H:%M:%S` local time
+    pub checked: String,
+    /// What it found
+    pub problems: Vec<Problem>,
+}
+
+impl LastCheck {
+    /// Records problems found now
+    pub fn new(problems: Vec<Problem>) -> Self {
+        Self {
+            checked: Local::now().format(TIME_FORMAT).to_string(),
+            problems,
+        }
+    }
+
+    /// Loads the last check, or `None` if no check was recorded
+    pub fn load() -> Option<Self> {
+        Self::load_from(&state_path())
+    }
+
+    /// Saves the check, replacing the previous one
+    pub fn save(&self) -> io::Result<()> {
+        self.save_to(&state_path())
+    }
+
+    fn load_from(path: &Path) -> Option<Self> {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+    }
+
+    fn save_to(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)
+    }
+
+    /// Whether the check ran less than `age` ago
+    pub fn checked_within(&self, age: Duration) -> bool {
+        NaiveDateTime::parse_from_str(&self.checked, TIME_FORMAT)
+            .is_ok_and(|checked| Local::now().naive_local() - checked < age)
+    }
+
+    /// Returns the problems in this check that `previous` didn't have
+    pub fn new_since<'a>(&'a self, previous: Option<&LastCheck>) -> Vec<&'a Problem> {
+        self.problems
+            .iter()
+            .filter(|problem| previous.is_none_or(|previous| !previous.problems.contains(problem)))
+            .collect()
+    }
+}
+
+/// Location of the last check record
+pub fn state_path() -> PathBuf {
+    locations::xdg_state_home()
+        .join("pathmaster")
+        .join("last-check.json")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::path::os_string_from_bytes;
+    use crate::utils::test_support::problem;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_new_since() {
+        let previous = LastCheck::new(vec![problem("missing", "/gone")]);
+        let current = LastCheck::new(vec![
+            problem("missing", "/gone"),
+            problem("missing", "/also-gone"),
+        ]);
+
+        assert_eq!(
+            current.new_since(Some(&previous)),
+            vec![&problem("missing", "/also-gone")]
+        );
+        assert_eq!(current.new_since(None).len(), 2);
+    }
+
+    #[test]
+    fn test_checked_within() {
+        let check = LastCheck::new(Vec::new());
+        assert!(check.checked_within(Duration::days(1)));
+
+        let old = LastCheck {
+            checked: "2020-01-01 00:00:00".to_string(),
+            problems: Vec::new(),
+        };
+        assert!(!old.checked_within(Duration::days(1)));
+
+        let unreadable = LastCheck {
+            checked: "yesterday".to_string(),
+            problems: Vec::new(),
+        };
+        assert!(!unreadable.checked_within(Duration::days(1)));
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("state").join("last-check.json");
+        assert_eq!(LastCheck::load_from(&path), None);
+
+        let mut not_utf8 = problem("missing", "");
+        not_utf8.path = PathBuf::from(os_string_from_bytes(b"/opt/\xff".to_vec()));
+        let check = LastCheck::new(vec![problem("not-dir", "/etc/passwd"), not_utf8]);
+        check.save_to(&path).unwrap();
+        assert_eq!(LastCheck::load_from(&path), Some(check));
+    }
+}
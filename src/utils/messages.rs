@@ -0,0 +1,294 @@
+//! Message catalog for user-facing output.
+//!
+//! Messages live in Fluent catalogs under `locales/<language>/pathmaster.ftl`,
+//! which are compiled into the binary. This module provides functionality to:
+//! - Pick the language from `--lang`, or from `LC_ALL`, `LC_MESSAGES` and `LANG`
+//! - Look messages up by id and fill in their arguments
+//! - Fall back to English for languages and messages without a translation
+//!
+//! Output is formatted with the [`msg!`](crate::msg) macro:
+//!
+//! ```
+//! println!("{}", msg!("add-added", path = dir.display()));
+//! ```
+
+use fluent_bundle::concurrent::FluentBundle;
+use fluent_bundle::{FluentArgs, FluentResource, FluentValue};
+use lazy_static::lazy_static;
+use std::env;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use unic_langid::LanguageIdentifier;
+
+/// Language used when no catalog matches
+pub const DEFAULT_LANGUAGE: &str = "en";
+
+/// Compiled-in catalogs, by language; English comes first and is complete
+const CATALOGS: &[(&str, &str)] = &[
+    ("en", include_str!("../../locales/en/pathmaster.ftl")),
+    ("de", include_str!("../../locales/de/pathmaster.ftl")),
+];
+
+lazy_static! {
+    static ref BUNDLES: Vec<(&'static str, FluentBundle<FluentResource>)> = CATALOGS
+        .iter()
+        .map(|(language, source)| (*language, load_bundle(language, source)))
+        .collect();
+    static ref LANGUAGE: Mutex<Option<&'static str>> = Mutex::new(None);
+}
+
+/// Sets the language of messages, or detects it from the environment
+///
+/// # Arguments
+///
+/// * `language` - A locale such as `de` or `de_DE.UTF-8`; `None` detects it
+///
+/// # Returns
+/// * `Err(String)` - If there's no catalog for the requested language
+pub fn set_language(language: Option<&str>) -> Result<(), String> {
+    let selected = match language {
+        Some(requested) => match_catalog(requested).ok_or_else(|| {
+            format!(
+                "No messages are available for language '{}' (available: {})",
+                requested,
+                available_languages().join(", ")
+            )
+        })?,
+        None => detect_language(),
+    };
+    *LANGUAGE.lock().unwrap() = Some(selected);
+    Ok(())
+}
+
+/// Returns the language messages are shown in
+pub fn current_language() -> &'static str {
+    LANGUAGE.lock().unwrap().get_or_insert_with(detect_language)
+}
+
+/// Returns the languages that have a catalog
+pub fn available_languages() -> Vec<&'static str> {
+    CATALOGS.iter().map(|(language, _)| *language).collect()
+}
+
+/// Picks the language from the locale variables, as `gettext` does:
+/// `LC_ALL` wins over `LC_MESSAGES`, which wins over `LANG`
+pub fn detect_language() -> &'static str {
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|var| env::var(var).ok())
+        .find(|value| !value.is_empty())
+        .and_then(|locale| match_catalog(&locale))
+        .unwrap_or(DEFAULT_LANGUAGE)
+}
+
+/// Finds the catalog for a locale such as `de_DE.UTF-8@euro`
+fn match_catalog(locale: &str) -> Option<&'static str> {
+    // `C` and `POSIX` are the untranslated locale
+    let tag = locale.split(['.', '@']).next().unwrap_or_default();
+    if tag == "C" || tag == "POSIX" {
+        return Some(DEFAULT_LANGUAGE);
+    }
+    let id: LanguageIdentifier = tag.replace('_', "-").parse().ok()?;
+    CATALOGS
+        .iter()
+        .map(|(language, _)| *language)
+        .find(|language| *language == id.language.as_str())
+}
+
+/// Formats a message in the current language
+///
+/// # Arguments
+///
+/// * `id` - Message id, as in the catalogs
+/// * `args` - Values for the message's variables
+///
+/// # Returns
+/// * The formatted message, or the id if no catalog has it
+pub fn message(id: &str, args: &[(&str, FluentValue<'static>)]) -> String {
+    let language = current_language();
+    let args = (!args.is_empty()).then(|| {
+        let mut fluent_args = FluentArgs::new();
+        for (name, value) in args {
+            fluent_args.set(*name, value.clone());
+        }
+        fluent_args
+    });
+
+    [language, DEFAULT_LANGUAGE]
+        .iter()
+        .filter_map(|language| BUNDLES.iter().find(|(name, _)| name == language))
+        .find_map(|(_, bundle)| {
+            let pattern = bundle.get_message(id)?.value()?;
+            let mut errors = Vec::new();
+            Some(
+                bundle
+                    .format_pattern(pattern, args.as_ref(), &mut errors)
+                    .into_owned(),
+            )
+        })
+        .unwrap_or_else(|| id.to_string())
+}
+
+/// Parses a catalog into a bundle
+fn load_bundle(language: &str, source: &str) -> FluentBundle<FluentResource> {
+    let id: LanguageIdentifier = language.parse().expect("catalog language is valid");
+    let mut bundle = FluentBundle::new_concurrent(vec![id]);
+    // Terminals don't need bidi isolation marks around arguments
+    bundle.set_use_isolating(false);
+    let resource =
+        FluentResource::try_new(source.to_string()).unwrap_or_else(|(resource, _)| resource);
+    let _ = bundle.add_resource(resource);
+    bundle
+}
+
+/// Values that can fill in a message variable
+pub trait MessageArg {
+    fn to_arg(&self) -> FluentValue<'static>;
+}
+
+impl<T: MessageArg + ?Sized> MessageArg for &T {
+    fn to_arg(&self) -> FluentValue<'static> {
+        (**self).to_arg()
+    }
+}
+
+impl MessageArg for str {
+    fn to_arg(&self) -> FluentValue<'static> {
+        FluentValue::from(self.to_string())
+    }
+}
+
+impl MessageArg for String {
+    fn to_arg(&self) -> FluentValue<'static> {
+        FluentValue::from(self.clone())
+    }
+}
+
+impl MessageArg for Path {
+    fn to_arg(&self) -> FluentValue<'static> {
+        FluentValue::from(self.display().to_string())
+    }
+}
+
+impl MessageArg for PathBuf {
+    fn to_arg(&self) -> FluentValue<'static> {
+        self.as_path().to_arg()
+    }
+}
+
+impl MessageArg for std::path::Display<'_> {
+    fn to_arg(&self) -> FluentValue<'static> {
+        FluentValue::from(self.to_string())
+    }
+}
+
+impl MessageArg for std::io::Error {
+    fn to_arg(&self) -> FluentValue<'static> {
+        FluentValue::from(self.to_string())
+    }
+}
+
+macro_rules! number_args {
+    ($($number:ty),*) => {
+        $(impl MessageArg for $number {
+            fn to_arg(&self) -> FluentValue<'static> {
+                FluentValue::from(*self)
+            }
+        })*
+    };
+}
+
+number_args!(usize, u32, u64, i32, i64);
+
+/// Formats a catalog message in the current language
+///
+/// # Example
+///
+/// ```
+/// msg!("add-summary-added", count = added_count)
+/// ```
+#[macro_export]
+macro_rules! msg {
+    ($id:expr $(, $name:ident = $value:expr)* $(,)?) => {
+        $crate::utils::messages::message(
+            $id,
+            &[$((
+                stringify!($name),
+                $crate::utils::messages::MessageArg::to_arg(&$value),
+            )),*],
+        )
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serial_test::serial;
+
+    /// Message ids defined by a catalog, which must parse without errors
+    fn message_ids(source: &str) -> Vec<String> {
+        if let Err((_, errors)) = FluentResource::try_new(source.to_string()) {
+            panic!("catalog doesn't parse: {:?}", errors);
+        }
+        source
+            .lines()
+            .filter(|line| line.starts_with(|c: char| c.is_ascii_lowercase()))
+            .filter_map(|line| line.split_once(" =").map(|(id, _)| id.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_catalogs_are_consistent() {
+        let english = message_ids(CATALOGS[0].1);
+        for (language, source) in CATALOGS {
+            let ids = message_ids(source);
+            for (n, id) in ids.iter().enumerate() {
+                assert!(
+                    !ids[..n].contains(id),
+                    "'{}' is defined twice in the {} catalog",
+                    id,
+                    language
+                );
+                assert!(
+                    english.contains(id),
+                    "'{}' in the {} catalog has no English message",
+                    id,
+                    language
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_match_catalog() {
+        assert_eq!(match_catalog("de_DE.UTF-8"), Some("de"));
+        assert_eq!(match_catalog("de_AT@euro"), Some("de"));
+        assert_eq!(match_catalog("en_US.UTF-8"), Some("en"));
+        assert_eq!(match_catalog("C.UTF-8"), Some("en"));
+        assert_eq!(match_catalog("POSIX"), Some("en"));
+        assert_eq!(match_catalog("xx_YY"), None);
+    }
+
+    #[test]
+    #[serial]
+    fn test_message() {
+        set_language(Some("en")).unwrap();
+        assert_eq!(
+            crate::msg!("add-added", path = Path::new("/pm-test/bin")),
+            "Added '/pm-test/bin' to PATH."
+        );
+        assert_eq!(message("no-such-message", &[]), "no-such-message");
+
+        set_language(Some("de")).unwrap();
+        assert_eq!(
+            crate::msg!("flush-done", count = 1usize),
+            "1 ungültiger Pfad entfernt und Shell-Konfiguration aktualisiert."
+        );
+        assert_eq!(
+            crate::msg!("flush-done", count = 3usize),
+            "3 ungültige Pfade entfernt und Shell-Konfiguration aktualisiert."
+        );
+        assert!(set_language(Some("xx")).is_err());
+        set_language(Some("en")).unwrap();
+    }
+}
@@ -1,6 +1,38 @@
+pub mod attribution;
+pub mod audit;
+pub mod cache;
+pub mod check_mode;
+pub mod conditional;
+pub mod container;
+pub mod dotfiles;
+pub mod homebrew;
+pub mod hooks;
+pub mod last_check;
+pub mod messages;
+pub mod mounts;
+pub mod notify;
 pub mod path;
 pub mod path_scanner;
+pub mod path_set;
+pub mod policy;
+pub mod porcelain;
+pub mod prompt;
+pub mod protected;
+pub mod provenance;
+pub mod root;
+pub mod rules;
 pub mod shell;
+pub mod slow;
+pub mod stale;
+pub mod system_env;
+#[cfg(test)]
+pub mod test_support;
+pub mod user;
+pub mod verbose;
+pub mod wsl;
 
-pub use path::{expand_path, get_path_entries, set_path_entries};
-pub use shell::update_shell_config;
+pub use path::{
+    expand_os_path, expand_path, find_path_entry, get_path_entries, initial_path_entries,
+    record_initial_path, resolve_directory_args, set_path_entries,
+};
+pub use path_set::PathSet;
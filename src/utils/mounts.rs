@@ -0,0 +1,156 @@
+//! Mounted filesystems, from `/proc/mounts`.
+//!
+//! Used to find the filesystem a PATH entry lives on: network mounts slow
+//! lookups down, and entries on removable media or automounted filesystems
+//! can be missing only for now, until the media is inserted or the
+//! automounter mounts it again.
+
+use crate::msg;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Where desktops mount removable media
+const MEDIA_ROOTS: &[&str] = &["/media", "/run/media", "/Volumes"];
+
+/// One line of `/proc/mounts`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mount {
+    /// Device or remote source, e.g. `/dev/sdb1` or `server:/export`
+    pub device: String,
+    /// Where the filesystem is mounted
+    pub mount_point: PathBuf,
+    /// Filesystem type, e.g. `ext4` or `nfs4`
+    pub fs_type: String,
+}
+
+/// Why a missing entry may only be missing for now
+#[derive(Debug, Clone, PartialEq)]
+pub enum Unmounted {
+    /// It's where removable media would be mounted
+    RemovableMedia,
+    /// It's under this automount point
+    Automount(PathBuf),
+}
+
+impl fmt::Display for Unmounted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Unmounted::RemovableMedia => write!(f, "{}", msg!("unmounted-removable")),
+            Unmounted::Automount(mount_point) => {
+                write!(f, "{}", msg!("unmounted-automount", path = mount_point))
+            }
+        }
+    }
+}
+
+/// Reads the mounted filesystems, or none if `/proc/mounts` isn't available
+pub fn read_mounts() -> Vec<Mount> {
+    fs::read_to_string("/proc/mounts")
+        .map(|content| parse_mounts(&content))
+        .unwrap_or_default()
+}
+
+/// Parses `/proc/mounts` content
+pub fn parse_mounts(content: &str) -> Vec<Mount> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            Some(Mount {
+                device: unescape(fields.next()?),
+                mount_point: PathBuf::from(unescape(fields.next()?)),
+                fs_type: fields.next()?.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Returns the mount `path` is on: the deepest mount point containing it
+pub fn containing<'a>(mounts: &'a [Mount], path: &Path) -> Option<&'a Mount> {
+    mounts
+        .iter()
+        .filter(|mount| path.starts_with(&mount.mount_point))
+        .max_by_key(|mount| mount.mount_point.components().count())
+}
+
+/// Works out whether a missing entry may come back once its filesystem is mounted
+///
+/// # Returns
+/// * `None` if the entry is on a filesystem that is mounted, so it's gone
+pub fn unmounted_reason(mounts: &[Mount], entry: &Path) -> Option<Unmounted> {
+    let mount = containing(mounts, entry);
+    if let Some(mount) = mount.filter(|mount| mount.fs_type == "autofs") {
+        return Some(Unmounted::Automount(mount.mount_point.clone()));
+    }
+
+    // Under a media root, but no media is mounted there
+    let media_root = MEDIA_ROOTS
+        .iter()
+        .map(Path::new)
+        .find(|root| entry.starts_with(root) && entry != *root)?;
+    match mount {
+        Some(mount) if mount.mount_point.starts_with(media_root) => None,
+        _ => Some(Unmounted::RemovableMedia),
+    }
+}
+
+/// Decodes the octal escapes `/proc/mounts` uses for spaces and tabs
+fn unescape(field: &str) -> String {
+    let mut decoded = String::with_capacity(field.len());
+    let mut chars = field.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            let digits: String = chars.clone().take(3).collect();
+            if let Ok(byte) = u8::from_str_radix(&digits, 8) {
+                decoded.push(byte as char);
+                chars.nth(2);
+                continue;
+            }
+        }
+        decoded.push(c);
+    }
+    decoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MOUNTS: &str = "/dev/sda1 / ext4 rw 0 0\n\
+                          server:/export /mnt/tools nfs4 rw 0 0\n\
+                          //nas/share /mnt/my\\040share cifs rw 0 0\n\
+                          auto.home /home/shared autofs rw 0 0\n\
+                          /dev/sdb1 /media/alice/USB vfat rw 0 0\n";
+
+    #[test]
+    fn test_containing() {
+        let mounts = parse_mounts(MOUNTS);
+        let fs_type = |path: &str| containing(&mounts, Path::new(path)).map(|m| m.fs_type.as_str());
+        assert_eq!(fs_type("/mnt/tools/bin"), Some("nfs4"));
+        assert_eq!(fs_type("/mnt/my share/bin"), Some("cifs"));
+        assert_eq!(fs_type("/mnt/toolset"), Some("ext4"));
+        assert_eq!(containing(&[], Path::new("/usr/bin")), None);
+    }
+
+    #[test]
+    fn test_unmounted_reason() {
+        let mounts = parse_mounts(MOUNTS);
+        let reason = |path: &str| unmounted_reason(&mounts, Path::new(path));
+        assert_eq!(
+            reason("/home/shared/tools/bin"),
+            Some(Unmounted::Automount(PathBuf::from("/home/shared")))
+        );
+        assert_eq!(
+            reason("/media/alice/BACKUP/bin"),
+            Some(Unmounted::RemovableMedia)
+        );
+        assert_eq!(
+            reason("/run/media/alice/BACKUP/bin"),
+            Some(Unmounted::RemovableMedia)
+        );
+        // The USB stick is mounted, so a directory missing from it is gone
+        assert_eq!(reason("/media/alice/USB/bin"), None);
+        assert_eq!(reason("/opt/old/bin"), None);
+    }
+}
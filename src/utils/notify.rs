@@ -0,0 +1,72 @@
+//! Desktop notifications.
+//!
+//! Notifications go through `notify-send`, which talks to the freedesktop
+//! notification service over D-Bus, or through `osascript` on macOS.
+
+use std::io;
+use std::process::{Command, Stdio};
+
+/// Shows a desktop notification
+///
+/// # Arguments
+///
+/// * `summary` - The notification title
+/// * `body` - The notification text
+///
+/// # Returns
+///
+/// An error if no notification tool is installed or it failed
+pub fn send(summary: &str, body: &str) -> io::Result<()> {
+    let (program, mut command) = if cfg!(target_os = "macos") {
+        let script = format!(
+            "display notification {} with title {}",
+            applescript_string(body),
+            applescript_string(summary)
+        );
+        let mut command = Command::new("osascript");
+        command.arg("-e").arg(script);
+        ("osascript", command)
+    } else {
+        let mut command = Command::new("notify-send");
+        command.arg("--app-name=pathmaster").arg(summary).arg(body);
+        ("notify-send", command)
+    };
+
+    let status = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .status()
+        .map_err(|e| {
+            if e.kind() == io::ErrorKind::NotFound {
+                io::Error::new(
+                    e.kind(),
+                    format!("{} not found; cannot show desktop notifications", program),
+                )
+            } else {
+                e
+            }
+        })?;
+    if !status.success() {
+        return Err(io::Error::other(format!("{} failed ({})", program, status)));
+    }
+    Ok(())
+}
+
+/// Quotes text as an AppleScript string literal
+fn applescript_string(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_applescript_string() {
+        assert_eq!(applescript_string("plain"), "\"plain\"");
+        assert_eq!(
+            applescript_string("say \"hi\" \\ bye"),
+            "\"say \\\"hi\\\" \\\\ bye\""
+        );
+    }
+}
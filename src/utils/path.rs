@@ -7,8 +7,13 @@
 //!
 //! For shell configuration management, see the `shell` module.
 
+use crate::utils::shell::locations;
+use lazy_static::lazy_static;
 use std::env;
-use std::path::PathBuf;
+use std::ffi::{OsStr, OsString};
+use std::fs;
+use std::io::{self, BufRead};
+use std::path::{Path, PathBuf};
 
 /// Expands a path string, resolving home directory (~) and environment variables.
 ///
@@ -30,6 +35,125 @@ pub fn expand_path(path: &str) -> PathBuf {
     PathBuf::from(expanded.to_string())
 }
 
+/// Expands a path that may not be valid UTF-8.
+///
+/// Valid UTF-8 is expanded like [`expand_path`]. Otherwise only a leading
+/// `~/` is expanded, and the rest of the bytes are kept as they are.
+pub fn expand_os_path(path: &OsStr) -> PathBuf {
+    if let Some(path) = path.to_str() {
+        return expand_path(path);
+    }
+
+    let bytes = path.as_encoded_bytes();
+    match bytes.strip_prefix(b"~/") {
+        Some(rest) => locations::real_home_dir().join(os_string_from_bytes(rest.to_vec())),
+        None => PathBuf::from(path),
+    }
+}
+
+/// Returns the raw bytes of an `OsStr`
+///
+/// On Unix these are the exact bytes. Elsewhere invalid UTF-8 is replaced.
+#[cfg(unix)]
+pub fn os_str_bytes(value: &OsStr) -> Vec<u8> {
+    use std::os::unix::ffi::OsStrExt;
+    value.as_bytes().to_vec()
+}
+
+/// Returns the raw bytes of an `OsStr`
+///
+/// On Unix these are the exact bytes. Elsewhere invalid UTF-8 is replaced.
+#[cfg(not(unix))]
+pub fn os_str_bytes(value: &OsStr) -> Vec<u8> {
+    value.to_string_lossy().into_owned().into_bytes()
+}
+
+/// Converts raw bytes, e.g. read from stdin or a config, into an `OsString`
+///
+/// On Unix the bytes are kept exactly. Elsewhere invalid UTF-8 is replaced.
+#[cfg(unix)]
+pub fn os_string_from_bytes(bytes: Vec<u8>) -> OsString {
+    use std::os::unix::ffi::OsStringExt;
+    OsString::from_vec(bytes)
+}
+
+/// Converts raw bytes, e.g. read from stdin or a config, into an `OsString`
+///
+/// On Unix the bytes are kept exactly. Elsewhere invalid UTF-8 is replaced.
+#[cfg(not(unix))]
+pub fn os_string_from_bytes(bytes: Vec<u8>) -> OsString {
+    OsString::from(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+/// Serde support for paths that may not be valid UTF-8.
+///
+/// JSON strings can't hold other bytes, so with `#[serde(with = ...)]` a
+/// path is stored as a string when it's valid UTF-8 and as an array of
+/// bytes otherwise. Both forms are read back.
+pub mod serde_path {
+    use super::{os_str_bytes, os_string_from_bytes};
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::path::{Path, PathBuf};
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(untagged)]
+    enum Stored {
+        Text(String),
+        Bytes(Vec<u8>),
+    }
+
+    impl From<&Path> for Stored {
+        fn from(path: &Path) -> Self {
+            match path.to_str() {
+                Some(text) => Stored::Text(text.to_string()),
+                None => Stored::Bytes(os_str_bytes(path.as_os_str())),
+            }
+        }
+    }
+
+    impl From<Stored> for PathBuf {
+        fn from(stored: Stored) -> Self {
+            match stored {
+                Stored::Text(text) => PathBuf::from(text),
+                Stored::Bytes(bytes) => PathBuf::from(os_string_from_bytes(bytes)),
+            }
+        }
+    }
+
+    pub fn serialize<S: Serializer>(path: &Path, serializer: S) -> Result<S::Ok, S::Error> {
+        Stored::from(path).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<PathBuf, D::Error> {
+        Stored::deserialize(deserializer).map(PathBuf::from)
+    }
+
+    /// The same for a list of paths
+    pub mod vec {
+        use super::Stored;
+        use serde::{Deserialize, Deserializer, Serialize, Serializer};
+        use std::path::PathBuf;
+
+        pub fn serialize<S: Serializer>(
+            paths: &[PathBuf],
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            paths
+                .iter()
+                .map(|path| Stored::from(path.as_path()))
+                .collect::<Vec<_>>()
+                .serialize(serializer)
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Vec<PathBuf>, D::Error> {
+            Vec::<Stored>::deserialize(deserializer)
+                .map(|stored| stored.into_iter().map(PathBuf::from).collect())
+        }
+    }
+}
+
 /// Gets the current PATH entries as a vector of PathBuf.
 ///
 /// # Returns
@@ -47,6 +171,22 @@ pub fn get_path_entries() -> Vec<PathBuf> {
         .unwrap_or_default()
 }
 
+lazy_static! {
+    static ref INITIAL_PATH: Vec<PathBuf> = get_path_entries();
+}
+
+/// Records PATH as inherited from the parent process.
+///
+/// Must be called before any command modifies PATH.
+pub fn record_initial_path() {
+    lazy_static::initialize(&INITIAL_PATH);
+}
+
+/// Returns PATH as it was when pathmaster started.
+pub fn initial_path_entries() -> &'static [PathBuf] {
+    &INITIAL_PATH
+}
+
 /// Sets the PATH environment variable to the provided entries.
 ///
 /// # Arguments
@@ -66,6 +206,91 @@ pub fn set_path_entries(entries: &[PathBuf]) {
     }
 }
 
+/// Finds the position of a directory in a list of PATH entries.
+///
+/// Entries are compared by their canonical form, so `/usr/bin/`, a symlink
+/// to `/usr/bin`, and `/usr/bin` itself are all treated as the same
+/// directory. Paths that can't be canonicalized are compared as written.
+///
+/// # Arguments
+/// * `entries` - Current PATH entries
+/// * `dir` - Directory to look for
+///
+/// # Returns
+/// * `Some(index)` of the first matching entry, or `None`
+pub fn find_path_entry(entries: &[PathBuf], dir: &Path) -> Option<usize> {
+    let target = fs::canonicalize(dir).unwrap_or_else(|_| dir.to_path_buf());
+    entries.iter().position(|entry| {
+        entry == dir || fs::canonicalize(entry).is_ok_and(|canonical| canonical == target)
+    })
+}
+
+/// Normalizes the spelling of a PATH entry.
+///
+/// Trailing slashes are stripped and `//` and `/./` segments collapsed.
+/// `..` segments are kept, since removing them lexically changes the meaning
+/// of paths that traverse symlinks. With `resolve_symlinks`, existing paths
+/// are replaced by their canonical form instead. Empty entries are returned
+/// unchanged.
+///
+/// # Arguments
+/// * `entry` - PATH entry to normalize
+/// * `resolve_symlinks` - Whether to resolve symlinks in existing paths
+pub fn normalize_entry(entry: &Path, resolve_symlinks: bool) -> PathBuf {
+    if entry.as_os_str().is_empty() {
+        return PathBuf::new();
+    }
+
+    if resolve_symlinks {
+        if let Ok(canonical) = fs::canonicalize(entry) {
+            return canonical;
+        }
+    }
+
+    entry.components().collect()
+}
+
+/// Replaces a `-` argument with directories read from a reader, one per line.
+///
+/// Blank lines and lines starting with `#` are skipped, so a commented list
+/// of directories can be piped in directly. Lines are read as bytes, so
+/// directory names that aren't valid UTF-8 are kept.
+///
+/// # Arguments
+/// * `args` - Directory arguments as given on the command line
+/// * `reader` - Source for the `-` argument, normally stdin
+///
+/// # Returns
+/// * `Ok(Vec<OsString>)` - Arguments with `-` replaced by the lines read
+/// * `Err(io::Error)` - If reading from `reader` fails
+pub fn resolve_directory_args<R: BufRead>(
+    args: &[OsString],
+    reader: R,
+) -> io::Result<Vec<OsString>> {
+    if !args.iter().any(|arg| arg == "-") {
+        return Ok(args.to_vec());
+    }
+
+    let mut stdin_lines = Vec::new();
+    for line in reader.split(b'\n') {
+        let line = line?;
+        let line = line.trim_ascii();
+        if !line.is_empty() && !line.starts_with(b"#") {
+            stdin_lines.push(os_string_from_bytes(line.to_vec()));
+        }
+    }
+
+    let mut resolved = Vec::new();
+    for arg in args {
+        if arg == "-" {
+            resolved.append(&mut stdin_lines);
+        } else {
+            resolved.push(arg.clone());
+        }
+    }
+    Ok(resolved)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -109,4 +334,72 @@ mod tests {
             env::set_var("PATH", path);
         }
     }
+
+    #[test]
+    fn test_find_path_entry_canonical() {
+        let temp_dir = TempDir::new().unwrap();
+        let real_dir = temp_dir.path().join("real");
+        std::fs::create_dir(&real_dir).unwrap();
+        let link = temp_dir.path().join("link");
+        std::os::unix::fs::symlink(&real_dir, &link).unwrap();
+
+        let entries = vec![PathBuf::from("/nonexistent/bin"), real_dir.clone()];
+        assert_eq!(find_path_entry(&entries, &real_dir), Some(1));
+        assert_eq!(find_path_entry(&entries, &link), Some(1));
+        assert_eq!(find_path_entry(&entries, &real_dir.join(".")), Some(1));
+        assert_eq!(
+            find_path_entry(&entries, Path::new("/nonexistent/bin")),
+            Some(0)
+        );
+        assert_eq!(find_path_entry(&entries, temp_dir.path()), None);
+    }
+
+    #[test]
+    fn test_normalize_entry() {
+        assert_eq!(
+            normalize_entry(Path::new("/usr//local/./bin/"), false).as_os_str(),
+            "/usr/local/bin"
+        );
+        assert_eq!(
+            normalize_entry(Path::new("/opt/app/../bin"), false),
+            PathBuf::from("/opt/app/../bin")
+        );
+        assert_eq!(normalize_entry(Path::new(""), false), PathBuf::new());
+
+        let temp_dir = TempDir::new().unwrap();
+        let real_dir = temp_dir.path().join("real");
+        std::fs::create_dir(&real_dir).unwrap();
+        let link = temp_dir.path().join("link");
+        std::os::unix::fs::symlink(&real_dir, &link).unwrap();
+
+        assert_eq!(normalize_entry(&link, false), link);
+        assert_eq!(
+            normalize_entry(&link, true),
+            std::fs::canonicalize(&real_dir).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_resolve_directory_args_from_reader() {
+        let input = "/opt/a/bin\n\n# comment\n  ~/bin  \n";
+        let args = vec![OsString::from("/first"), OsString::from("-")];
+
+        let resolved = resolve_directory_args(&args, input.as_bytes()).unwrap();
+        assert_eq!(resolved, vec!["/first", "/opt/a/bin", "~/bin"]);
+
+        let args = vec![OsString::from("/only")];
+        let resolved = resolve_directory_args(&args, "/ignored".as_bytes()).unwrap();
+        assert_eq!(resolved, vec!["/only"]);
+    }
+
+    #[test]
+    fn test_resolve_directory_args_keeps_invalid_utf8() {
+        let args = vec![OsString::from("-")];
+        let resolved = resolve_directory_args(&args, &b"/opt/\xff/bin\n"[..]).unwrap();
+        assert_eq!(
+            resolved,
+            vec![os_string_from_bytes(b"/opt/\xff/bin".to_vec())]
+        );
+        assert_eq!(expand_os_path(&resolved[0]), PathBuf::from(&resolved[0]));
+    }
 }
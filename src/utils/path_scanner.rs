@@ -1,10 +1,19 @@
+use crate::msg;
+use crate::utils::cache::ExecutableCache;
+use crate::utils::root;
+use crate::utils::shell::locations;
+use lazy_static::lazy_static;
+use rayon::prelude::*;
 use regex::Regex;
 use std::fs::{self, File};
 use std::io::{self, BufRead, BufReader};
 use std::path::{Path, PathBuf};
 
+lazy_static! {
+    static ref PATH_REGEX: Regex = Regex::new(r"(PATH=|export PATH|setenv PATH|path\+=)").unwrap();
+}
+
 #[derive(Debug)]
-#[allow(dead_code)]
 pub struct PathLocation {
     file: PathBuf,
     line_number: usize,
@@ -12,72 +21,101 @@ pub struct PathLocation {
     requires_sudo: bool,
 }
 
-#[allow(dead_code)]
+impl PathLocation {
+    /// File containing the PATH modification
+    pub fn file(&self) -> &Path {
+        &self.file
+    }
+
+    /// One-based line number of the modification
+    pub fn line_number(&self) -> usize {
+        self.line_number
+    }
+
+    /// The line as written
+    pub fn content(&self) -> &str {
+        &self.content
+    }
+
+    /// Whether the file is a system-level config, which only root can change
+    pub fn requires_sudo(&self) -> bool {
+        self.requires_sudo
+    }
+}
+
 pub struct PathScanner {
-    path_regex: Regex,
+    path_regex: &'static Regex,
 }
 
-#[allow(dead_code)]
 impl PathScanner {
     pub fn new() -> Self {
-        let path_regex = Regex::new(r"(PATH=|export PATH|setenv PATH|path\+=)").unwrap();
-        Self { path_regex }
+        Self {
+            path_regex: &PATH_REGEX,
+        }
     }
 
+    /// Scans system and user shell configs for PATH modifications.
+    ///
+    /// Files are scanned in parallel. Results are merged in the order the
+    /// files are listed (system files first), so output is deterministic.
     pub fn scan_all(&self) -> io::Result<Vec<PathLocation>> {
-        let mut results = Vec::new();
-
-        // System-level files (requires sudo)
-        let system_files = self.get_system_files()?;
-        for file in system_files {
-            if let Ok(mut locations) = self.scan_file(&file, true) {
-                results.append(&mut locations);
-            }
-        }
-
-        // User-level files
-        let user_files = self.get_user_files()?;
-        for file in user_files {
-            if let Ok(mut locations) = self.scan_file(&file, false) {
-                results.append(&mut locations);
-            }
-        }
+        // System-level files (requires sudo), then user-level files
+        let files: Vec<(PathBuf, bool)> = self
+            .get_system_files()?
+            .into_iter()
+            .map(|file| (file, true))
+            .chain(self.get_user_files()?.into_iter().map(|file| (file, false)))
+            .collect();
+
+        let results = files
+            .par_iter()
+            .map(|(file, requires_sudo)| self.scan_file(file, *requires_sudo).unwrap_or_default())
+            .collect::<Vec<_>>()
+            .into_iter()
+            .flatten()
+            .collect();
 
         Ok(results)
     }
 
     fn get_system_files(&self) -> io::Result<Vec<PathBuf>> {
         let mut files = vec![
-            PathBuf::from("/etc/environment"),
-            PathBuf::from("/etc/profile"),
-            PathBuf::from("/etc/bash.bashrc"),
-            PathBuf::from("/etc/bashrc"),
+            root::rooted("/etc/environment"),
+            root::rooted("/etc/profile"),
+            root::rooted("/etc/bash.bashrc"),
+            root::rooted("/etc/bashrc"),
         ];
 
-        // Add all scripts from /etc/profile.d/
-        if let Ok(entries) = fs::read_dir("/etc/profile.d") {
-            for entry in entries.flatten() {
-                if entry.path().is_file() {
-                    files.push(entry.path());
-                }
-            }
+        // Add all scripts from /etc/profile.d/, sorted for stable output
+        if let Ok(entries) = fs::read_dir(root::rooted("/etc/profile.d")) {
+            let mut scripts: Vec<PathBuf> = entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| path.is_file())
+                .collect();
+            scripts.sort();
+            files.extend(scripts);
         }
 
         Ok(files)
     }
 
     fn get_user_files(&self) -> io::Result<Vec<PathBuf>> {
-        let home = dirs_next::home_dir()
-            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "Home directory not found"))?;
+        let home = locations::home_dir();
+
+        let zdotdir = locations::zsh_dotdir();
 
         let files = vec![
             home.join(".profile"),
             home.join(".bash_profile"),
             home.join(".bash_login"),
             home.join(".bashrc"),
-            home.join(".zshrc"),
+            zdotdir.join(".zshenv"),
+            zdotdir.join(".zprofile"),
+            zdotdir.join(".zshrc"),
             home.join(".cshrc"),
             home.join(".login"),
+            locations::fish_config_dir().join("config.fish"),
         ];
 
         Ok(files)
@@ -109,12 +147,11 @@ impl PathScanner {
     }
 }
 
-#[allow(dead_code)]
 /// Format the results in a user-friendly way
 pub fn format_results(locations: &[PathLocation]) -> String {
     let mut output = String::new();
 
-    output.push_str("System-level files (requires sudo):\n");
+    output.push_str(&format!("{}\n", msg!("scan-system-files")));
     for loc in locations.iter().filter(|l| l.requires_sudo) {
         output.push_str(&format!(
             "{}:{} - {}\n",
@@ -124,7 +161,7 @@ pub fn format_results(locations: &[PathLocation]) -> String {
         ));
     }
 
-    output.push_str("\nUser-level files:\n");
+    output.push_str(&format!("\n{}\n", msg!("scan-user-files")));
     for loc in locations.iter().filter(|l| !l.requires_sudo) {
         output.push_str(&format!(
             "{}:{} - {}\n",
@@ -137,13 +174,79 @@ pub fn format_results(locations: &[PathLocation]) -> String {
     output
 }
 
-#[allow(dead_code)]
-// Example usage
-fn main() -> io::Result<()> {
-    let scanner = PathScanner::new();
-    let results = scanner.scan_all()?;
-    println!("{}", format_results(&results));
-    Ok(())
+/// Executables found in a single PATH directory
+#[derive(Debug)]
+pub struct DirectoryListing {
+    pub directory: PathBuf,
+    pub executables: Vec<String>,
+}
+
+/// Lists the executables in each directory, walking directories in parallel.
+///
+/// Listings are returned in the same order as `directories`, and the names
+/// within each listing are sorted. Unreadable directories yield empty listings.
+/// Directories unchanged since they were cached are not read again, and fresh
+/// listings are recorded in `cache`.
+pub fn scan_directories(
+    directories: &[PathBuf],
+    cache: &mut ExecutableCache,
+) -> Vec<DirectoryListing> {
+    let listings: Vec<(DirectoryListing, bool)> = directories
+        .par_iter()
+        .map(|directory| {
+            let cached = cache.get(directory);
+            let fresh = cached.is_none();
+            let executables = cached.unwrap_or_else(|| list_executables(directory));
+            (
+                DirectoryListing {
+                    directory: directory.clone(),
+                    executables,
+                },
+                fresh,
+            )
+        })
+        .collect();
+
+    listings
+        .into_iter()
+        .map(|(listing, fresh)| {
+            if fresh {
+                cache.insert(&listing.directory, listing.executables.clone());
+            }
+            listing
+        })
+        .collect()
+}
+
+/// Returns the sorted names of executable files in a directory
+fn list_executables(directory: &Path) -> Vec<String> {
+    let entries = match fs::read_dir(directory) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+
+    let mut names: Vec<String> = entries
+        .flatten()
+        .filter(|entry| is_executable(&entry.path()))
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .collect();
+    names.sort();
+    names
+}
+
+/// Returns whether a path is a file with an executable bit set
+#[cfg(unix)]
+pub fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    fs::metadata(path)
+        .map(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+/// Returns whether a path is a file
+#[cfg(not(unix))]
+pub fn is_executable(path: &Path) -> bool {
+    path.is_file()
 }
 
 #[cfg(test)]
@@ -196,4 +299,43 @@ path+=('/home/user/bin')
 
         Ok(())
     }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_scan_directories_preserves_order() -> io::Result<()> {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = TempDir::new()?;
+        let first = temp_dir.path().join("first");
+        let second = temp_dir.path().join("second");
+        fs::create_dir(&first)?;
+        fs::create_dir(&second)?;
+
+        for (dir, name, mode) in [
+            (&first, "zeta", 0o755),
+            (&first, "alpha", 0o755),
+            (&first, "notes.txt", 0o644),
+            (&second, "tool", 0o755),
+        ] {
+            let path = dir.join(name);
+            File::create(&path)?;
+            fs::set_permissions(&path, fs::Permissions::from_mode(mode))?;
+        }
+
+        let missing = temp_dir.path().join("missing");
+        let mut cache = ExecutableCache::default();
+        let listings = scan_directories(
+            &[second.clone(), missing.clone(), first.clone()],
+            &mut cache,
+        );
+
+        let directories: Vec<&PathBuf> = listings.iter().map(|l| &l.directory).collect();
+        assert_eq!(directories, vec![&second, &missing, &first]);
+        assert_eq!(listings[0].executables, vec!["tool"]);
+        assert!(listings[1].executables.is_empty());
+        assert_eq!(listings[2].executables, vec!["alpha", "zeta"]);
+        assert_eq!(cache.get(&first), Some(vec!["alpha".into(), "zeta".into()]));
+
+        Ok(())
+    }
 }
@@ -0,0 +1,399 @@
+//! A list of PATH entries with the edits commands make to it.
+//!
+//! Commands build a [`PathSet`] from the current PATH, chain the changes
+//! they need, and write the result with the `apply_*` methods:
+//!
+//! ```ignore
+//! let mut set = PathSet::from_env();
+//! set.add("/opt/tools/bin").remove("/old/bin").dedupe();
+//! set.apply_backup()?;
+//! set.apply()?;
+//! ```
+//!
+//! Entries are compared the way [`find_path_entry`] does, so `/usr/bin/`
+//! and a symlink to `/usr/bin` count as the same directory.
+
+use crate::backup;
+use crate::utils::path::{find_path_entry, get_path_entries, normalize_entry, set_path_entries};
+use crate::utils::shell;
+use crate::utils::shell::types::DeclaredPath;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// An ordered list of PATH entries
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct PathSet {
+    entries: Vec<PathBuf>,
+}
+
+/// Differences between two PATH entry lists
+#[allow(dead_code)]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct PathDiff {
+    /// Entries only in the new list, in its order
+    pub added: Vec<PathBuf>,
+    /// Entries only in the old list, in its order
+    pub removed: Vec<PathBuf>,
+    /// Whether the shared entries changed order or spelling
+    pub reordered: bool,
+}
+
+impl PathDiff {
+    /// Returns whether the two lists were identical
+    #[allow(dead_code)]
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && !self.reordered
+    }
+}
+
+impl PathSet {
+    /// Creates a set from entries, keeping their order
+    pub fn new(entries: Vec<PathBuf>) -> Self {
+        PathSet { entries }
+    }
+
+    /// Creates a set from the PATH environment variable
+    pub fn from_env() -> Self {
+        Self::new(get_path_entries())
+    }
+
+    /// Returns the entries in order
+    pub fn entries(&self) -> &[PathBuf] {
+        &self.entries
+    }
+
+    /// Returns the position of a directory, if it's in the set
+    pub fn position<P: AsRef<Path>>(&self, dir: P) -> Option<usize> {
+        find_path_entry(&self.entries, dir.as_ref())
+    }
+
+    /// Returns whether a directory is in the set
+    pub fn contains<P: AsRef<Path>>(&self, dir: P) -> bool {
+        self.position(dir).is_some()
+    }
+
+    /// Appends a directory unless it's already in the set
+    pub fn add<P: AsRef<Path>>(&mut self, dir: P) -> &mut Self {
+        if !self.contains(&dir) {
+            self.entries.push(dir.as_ref().to_path_buf());
+        }
+        self
+    }
+
+    /// Inserts a directory at a position, or moves it there if it's already present
+    pub fn insert<P: AsRef<Path>>(&mut self, index: usize, dir: P) -> &mut Self {
+        let entry = match self.position(&dir) {
+            Some(current) => self.entries.remove(current),
+            None => dir.as_ref().to_path_buf(),
+        };
+        let index = index.min(self.entries.len());
+        self.entries.insert(index, entry);
+        self
+    }
+
+    /// Puts `new` in place of the first occurrence of `old`.
+    ///
+    /// Other occurrences of `old`, and of `new` elsewhere, are removed so the
+    /// result has no duplicates. Nothing changes if `old` isn't present.
+    pub fn replace<P: AsRef<Path>, Q: AsRef<Path>>(&mut self, old: P, new: Q) -> &mut Self {
+        let Some(index) = self.position(&old) else {
+            return self;
+        };
+        let new = new.as_ref().to_path_buf();
+        let old = self.entries[index].clone();
+        let mut kept: Vec<PathBuf> = Vec::with_capacity(self.entries.len());
+        for (position, entry) in self.entries.drain(..).enumerate() {
+            if position == index {
+                kept.push(new.clone());
+            } else if find_path_entry(&[old.clone(), new.clone()], &entry).is_none() {
+                kept.push(entry);
+            }
+        }
+        self.entries = kept;
+        self
+    }
+
+    /// Removes every occurrence of a directory
+    pub fn remove<P: AsRef<Path>>(&mut self, dir: P) -> &mut Self {
+        while let Some(index) = self.position(&dir) {
+            self.entries.remove(index);
+        }
+        self
+    }
+
+    /// Removes the entries at the given zero-based positions
+    pub fn remove_positions(&mut self, positions: &[usize]) -> &mut Self {
+        let mut index = 0;
+        self.entries.retain(|_| {
+            let keep = !positions.contains(&index);
+            index += 1;
+            keep
+        });
+        self
+    }
+
+    /// Removes repeated entries, keeping the first occurrence
+    pub fn dedupe(&mut self) -> &mut Self {
+        let mut kept: Vec<PathBuf> = Vec::with_capacity(self.entries.len());
+        for entry in self.entries.drain(..) {
+            if find_path_entry(&kept, &entry).is_none() {
+                kept.push(entry);
+            }
+        }
+        self.entries = kept;
+        self
+    }
+
+    /// Inserts the entries of `declared` that the set lacks, so entries only
+    /// a shell config declares keep their place.
+    ///
+    /// Each goes after the declared entry before it, or before the one after
+    /// it. Without either, entries the config puts ahead of the existing
+    /// PATH go to the front and the others to the end.
+    pub fn merge(&mut self, declared: &DeclaredPath) -> &mut Self {
+        let (ahead, behind) = declared
+            .entries
+            .split_at(declared.ahead.min(declared.entries.len()));
+        self.merge_run(ahead, true);
+        self.merge_run(behind, false);
+        self
+    }
+
+    /// Merges entries declared on one side of the existing PATH
+    fn merge_run(&mut self, declared: &[PathBuf], front: bool) {
+        // Where the next missing entry goes, once a declared entry was placed
+        let mut next: Option<usize> = None;
+        // An exact match wins over another spelling of the same directory
+        let find = |entries: &[PathBuf], entry: &PathBuf| {
+            entries
+                .iter()
+                .position(|known| known == entry)
+                .or_else(|| find_path_entry(entries, entry))
+        };
+        for (index, entry) in declared.iter().enumerate() {
+            if let Some(position) = find(&self.entries, entry) {
+                next = Some(position + 1);
+                continue;
+            }
+            let at = next.unwrap_or_else(|| {
+                declared[index + 1..]
+                    .iter()
+                    .find_map(|later| find(&self.entries, later))
+                    .unwrap_or(if front { 0 } else { self.entries.len() })
+            });
+            self.entries.insert(at, entry.clone());
+            next = Some(at + 1);
+        }
+    }
+
+    /// Cleans up the spelling of every entry (see [`normalize_entry`])
+    pub fn normalize(&mut self, resolve_symlinks: bool) -> &mut Self {
+        for entry in &mut self.entries {
+            *entry = normalize_entry(entry, resolve_symlinks);
+        }
+        self
+    }
+
+    /// Moves the entry at `from` to position `to`
+    pub fn reorder(&mut self, from: usize, to: usize) -> &mut Self {
+        if from < self.entries.len() {
+            let entry = self.entries.remove(from);
+            let to = to.min(self.entries.len());
+            self.entries.insert(to, entry);
+        }
+        self
+    }
+
+    /// Compares this set with an earlier one
+    #[allow(dead_code)]
+    pub fn diff(&self, before: &PathSet) -> PathDiff {
+        let added: Vec<PathBuf> = self
+            .entries
+            .iter()
+            .filter(|entry| find_path_entry(&before.entries, entry).is_none())
+            .cloned()
+            .collect();
+        let removed: Vec<PathBuf> = before
+            .entries
+            .iter()
+            .filter(|entry| find_path_entry(&self.entries, entry).is_none())
+            .cloned()
+            .collect();
+        let shared = |entries: &[PathBuf], others: &[PathBuf]| -> Vec<PathBuf> {
+            entries
+                .iter()
+                .filter(|entry| find_path_entry(others, entry).is_some())
+                .cloned()
+                .collect()
+        };
+        let reordered = shared(&self.entries, &before.entries)
+            .iter()
+            .map(|entry| entry.as_os_str())
+            .ne(shared(&before.entries, &self.entries)
+                .iter()
+                .map(|entry| entry.as_os_str()));
+
+        PathDiff {
+            added,
+            removed,
+            reordered,
+        }
+    }
+
+    /// Sets the PATH environment variable of this process to the entries
+    pub fn apply_env(&self) {
+        set_path_entries(&self.entries);
+    }
+
+    /// Writes the entries to the shell config (or the configured targets)
+    pub fn apply_shell_config(&self) -> io::Result<()> {
+        shell::update_shell_config(&self.entries)
+    }
+
+    /// Backs up the current PATH before the set is applied
+    pub fn apply_backup(&self) -> io::Result<()> {
+        backup::create_backup()
+    }
+
+    /// Sets PATH in this process and writes it to the shell config
+    pub fn apply(&self) -> io::Result<()> {
+        self.apply_env();
+        self.apply_shell_config()
+    }
+}
+
+impl From<Vec<PathBuf>> for PathSet {
+    fn from(entries: Vec<PathBuf>) -> Self {
+        Self::new(entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config;
+    use crate::utils::root;
+    use crate::utils::shell::types::ShellType;
+    use serial_test::serial;
+    use tempfile::TempDir;
+
+    fn set(entries: &[&str]) -> PathSet {
+        PathSet::new(entries.iter().map(PathBuf::from).collect())
+    }
+
+    #[test]
+    fn test_fluent_edits() {
+        let mut path = set(&["/pm-test/a", "/pm-test/b", "/pm-test/a/", "/pm-test/old"]);
+        path.add("/pm-test/new")
+            .add("/pm-test/b")
+            .remove("/pm-test/old")
+            .dedupe()
+            .insert(0, "/pm-test/new");
+        assert_eq!(path, set(&["/pm-test/new", "/pm-test/a", "/pm-test/b"]));
+
+        path.reorder(2, 0).remove_positions(&[1]);
+        assert_eq!(path, set(&["/pm-test/b", "/pm-test/a"]));
+    }
+
+    #[test]
+    fn test_replace() {
+        let mut path = set(&["/pm-test/v1", "/pm-test/a", "/pm-test/v2", "/pm-test/v1"]);
+        path.replace("/pm-test/v1", "/pm-test/v2");
+        assert_eq!(path, set(&["/pm-test/v2", "/pm-test/a"]));
+
+        path.replace("/pm-test/missing", "/pm-test/b");
+        assert_eq!(path, set(&["/pm-test/v2", "/pm-test/a"]));
+    }
+
+    fn declared(entries: &[&str], ahead: usize) -> DeclaredPath {
+        DeclaredPath {
+            entries: entries.iter().map(PathBuf::from).collect(),
+            ahead,
+        }
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut path = set(&["/pm-test/a", "/pm-test/b", "/pm-test/c"]);
+        path.merge(&declared(
+            &[
+                "/pm-test/first",
+                "/pm-test/b",
+                "/pm-test/new",
+                "/pm-test/a",
+                "/pm-test/b",
+            ],
+            0,
+        ));
+        assert_eq!(
+            path,
+            set(&[
+                "/pm-test/a",
+                "/pm-test/first",
+                "/pm-test/b",
+                "/pm-test/new",
+                "/pm-test/c"
+            ])
+        );
+
+        let mut path = set(&["/pm-test/a"]);
+        path.merge(&declared(&["/pm-test/only"], 0));
+        assert_eq!(path, set(&["/pm-test/a", "/pm-test/only"]));
+
+        // PATH="/pm-test/x:/pm-test/y:$PATH:/pm-test/z"
+        let mut path = set(&["/usr/bin", "/bin"]);
+        path.merge(&declared(&["/pm-test/x", "/pm-test/y", "/pm-test/z"], 2));
+        assert_eq!(
+            path,
+            set(&["/pm-test/x", "/pm-test/y", "/usr/bin", "/bin", "/pm-test/z"])
+        );
+    }
+
+    #[test]
+    fn test_normalize() {
+        let mut path = set(&["/usr//bin/", "/usr/./local/bin"]);
+        path.normalize(false);
+        assert_eq!(path, set(&["/usr/bin", "/usr/local/bin"]));
+    }
+
+    #[test]
+    fn test_diff() {
+        let before = set(&["/pm-test/a", "/pm-test/b", "/pm-test/old"]);
+        let after = set(&["/pm-test/b", "/pm-test/a", "/pm-test/new"]);
+
+        let diff = after.diff(&before);
+        assert_eq!(diff.added, vec![PathBuf::from("/pm-test/new")]);
+        assert_eq!(diff.removed, vec![PathBuf::from("/pm-test/old")]);
+        assert!(diff.reordered);
+        assert!(before.diff(&before).is_empty());
+
+        // The same directory spelled differently is neither added nor removed
+        let respelled = set(&["/pm-test/a/", "/pm-test/b", "/pm-test/old"]);
+        let diff = respelled.diff(&before);
+        assert!(diff.added.is_empty() && diff.removed.is_empty());
+        assert!(diff.reordered);
+    }
+
+    #[test]
+    #[serial]
+    fn test_apply_backup() {
+        let temp_dir = TempDir::new().unwrap();
+        let backup_dir = temp_dir.path().join("backups");
+        root::set_root(Some(temp_dir.path())).unwrap();
+        config::set_config(config::Config {
+            shell: Some(ShellType::Bash),
+            ..Default::default()
+        });
+        backup::core::set_backup_dir(backup_dir.clone()).unwrap();
+
+        let result = set(&["/pm-test/a"]).apply_backup();
+
+        root::set_root(None).unwrap();
+        config::clear_config();
+
+        result.unwrap();
+        let backups = backup::core::list_backups(&backup_dir).unwrap();
+        assert_eq!(backups.len(), 1);
+        assert!(!backups[0].shell_only);
+    }
+}
@@ -0,0 +1,192 @@
+//! PATH policy set by an administrator.
+//!
+//! A policy lives in `/etc/pathmaster/policy.toml`, outside the user's
+//! config, so one file can be shipped to every workstation in a fleet:
+//!
+//! ```toml
+//! forbidden-dirs = [".", "/tmp/*"]
+//! required-dirs = ["/opt/corp/bin"]
+//!
+//! [[rules]]
+//! entry = "/opt/corp/bin"
+//! before = "/usr/bin"
+//! ```
+//!
+//! `pathmaster enforce` brings PATH in line with the policy and `check`
+//! reports where it isn't.
+
+use crate::msg;
+use crate::utils::root;
+use crate::utils::rules::{self, PathRule};
+use crate::utils::{self, find_path_entry};
+use glob::Pattern;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Location of the system policy file
+pub const POLICY_FILE: &str = "/etc/pathmaster/policy.toml";
+
+/// Directories PATH must not contain, must contain, and how it's ordered
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct Policy {
+    /// Entries that must not be in PATH; `~` is expanded and globs are allowed
+    pub forbidden_dirs: Vec<String>,
+    /// Entries that must be in PATH, in order
+    pub required_dirs: Vec<String>,
+    /// Ordering rules PATH must satisfy
+    pub rules: Vec<PathRule>,
+}
+
+/// One way PATH breaks the policy
+#[derive(Debug, Clone, PartialEq)]
+pub enum Violation {
+    /// A forbidden entry is in PATH
+    Forbidden(PathBuf),
+    /// A required entry is missing from PATH
+    Missing(PathBuf),
+    /// An ordering rule isn't met
+    Rule(PathRule),
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Violation::Forbidden(entry) => {
+                write!(f, "{}", msg!("policy-forbidden", path = entry))
+            }
+            Violation::Missing(entry) => write!(f, "{}", msg!("policy-missing", path = entry)),
+            Violation::Rule(rule) => {
+                write!(f, "{}", msg!("policy-rule", rule = rule.to_string()))
+            }
+        }
+    }
+}
+
+impl Policy {
+    /// Loads the system policy
+    ///
+    /// # Returns
+    /// * `Ok(None)` - If there is no policy file
+    /// * `Err(io::Error)` - If the file can't be read or parsed
+    pub fn load() -> io::Result<Option<Policy>> {
+        Self::load_from(&root::rooted(POLICY_FILE))
+    }
+
+    fn load_from(path: &Path) -> io::Result<Option<Policy>> {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        toml::from_str(&content).map(Some).map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Invalid policy file {}: {}", path.display(), e),
+            )
+        })
+    }
+
+    /// Returns whether an entry is forbidden
+    pub fn is_forbidden(&self, entry: &Path) -> bool {
+        self.forbidden_dirs.iter().any(|forbidden| {
+            let expanded = shellexpand::tilde(forbidden);
+            if forbidden.contains(['*', '?', '[']) {
+                Pattern::new(&expanded)
+                    .is_ok_and(|pattern| pattern.matches(&entry.to_string_lossy()))
+            } else if forbidden == "." || forbidden.is_empty() {
+                // Relative entries are forbidden as written, not by what they resolve to
+                entry.as_os_str() == forbidden.as_str()
+            } else {
+                find_path_entry(&[PathBuf::from(expanded.as_ref())], entry).is_some()
+            }
+        })
+    }
+
+    /// Returns the required entries, with `~` expanded
+    pub fn required(&self) -> Vec<PathBuf> {
+        self.required_dirs
+            .iter()
+            .map(|dir| utils::expand_path(dir))
+            .collect()
+    }
+
+    /// Returns every way `entries` break the policy
+    pub fn violations(&self, entries: &[PathBuf]) -> Vec<Violation> {
+        let mut violations: Vec<Violation> = entries
+            .iter()
+            .filter(|entry| self.is_forbidden(entry))
+            .map(|entry| Violation::Forbidden(entry.clone()))
+            .collect();
+        violations.extend(
+            self.required()
+                .into_iter()
+                .filter(|dir| find_path_entry(entries, dir).is_none())
+                .map(Violation::Missing),
+        );
+        violations.extend(
+            rules::violations(&self.rules, entries)
+                .into_iter()
+                .cloned()
+                .map(Violation::Rule),
+        );
+        violations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_support::paths;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_load_policy() {
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join("policy.toml");
+        assert_eq!(Policy::load_from(&file).unwrap(), None);
+
+        fs::write(
+            &file,
+            "forbidden-dirs = [\".\"]\nrequired-dirs = [\"/opt/corp/bin\"]\n\n[[rules]]\nentry = \"/opt/corp/bin\"\nposition = \"first\"\n",
+        )
+        .unwrap();
+        let policy = Policy::load_from(&file).unwrap().unwrap();
+        assert_eq!(policy.forbidden_dirs, vec!["."]);
+        assert_eq!(policy.rules.len(), 1);
+
+        fs::write(&file, "forbidden-dirs = \".\"\n").unwrap();
+        assert!(Policy::load_from(&file).is_err());
+    }
+
+    #[test]
+    fn test_violations() {
+        let policy = Policy {
+            forbidden_dirs: vec![".".to_string(), "/pm-test/tmp/*".to_string()],
+            required_dirs: vec!["/pm-test/corp/bin".to_string(), "/usr/bin".to_string()],
+            rules: vec![PathRule {
+                entry: "/usr/bin".to_string(),
+                position: Some(rules::Position::First),
+                ..PathRule::default()
+            }],
+        };
+        let entries = paths(&["/pm-test/tmp/bin", "/usr/bin", "."]);
+
+        let violations = policy.violations(&entries);
+        assert_eq!(
+            violations,
+            vec![
+                Violation::Forbidden(PathBuf::from("/pm-test/tmp/bin")),
+                Violation::Forbidden(PathBuf::from(".")),
+                Violation::Missing(PathBuf::from("/pm-test/corp/bin")),
+                Violation::Rule(policy.rules[0].clone()),
+            ]
+        );
+        assert!(policy
+            .violations(&paths(&["/usr/bin", "/pm-test/corp/bin"]))
+            .is_empty());
+    }
+}
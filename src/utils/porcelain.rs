@@ -0,0 +1,123 @@
+//! Stable output for scripts, selected with `--porcelain`.
+//!
+//! The human-readable output of `list`, `check` and `history` changes as
+//! the commands improve. Porcelain output doesn't: a format version, once
+//! released, keeps its records and fields, so scripts can rely on it.
+//! New versions are added alongside, never in place of, old ones.
+//!
+//! Version 1:
+//! - One record per line, fields separated by a tab. The first field names
+//!   the record type; scripts should skip record types they don't know.
+//! - The first line is `porcelain<TAB>v1`.
+//! - In fields, backslash, tab, newline and carriage return are written as
+//!   `\\`, `\t`, `\n` and `\r`; nothing else is escaped.
+//! - Empty fields are empty strings, so every record of a type has the same
+//!   number of fields.
+//!
+//! Records:
+//! - `list`: `entry<TAB>POSITION<TAB>STATUS<TAB>PATH`, in PATH order, where
+//!   POSITION counts from 1 and STATUS is `valid`, `missing`, `not-dir`,
+//!   `relative` or `empty`.
+//! - `check`: `problem<TAB>KIND<TAB>PATH<TAB>DETAIL`. A PATH without
+//!   problems has no `problem` records. KIND and DETAIL are:
+//!   - `missing`, `not-dir`, `relative`, `empty`, `windows`: no detail
+//!   - `unmounted`: `removable-media` or `automount:MOUNT_POINT`
+//!   - `stale`: the owning tool, e.g. `nvm`
+//!   - `slow`: comma-separated `network:FSTYPE`, `listing-ms:N`, `files:N`
+//!   - `rule`: comma-separated `before:ENTRY`, `after:ENTRY`, `position:first|last`
+//!   - `policy`: `forbidden`, `required`, or a rule as for `rule`
+//!   - `expired`: when the entry expired, as `YYYY-MM-DD HH:MM:SS`
+//!   - `conditional`: `missing` or `unwanted`
+//!   - `system-missing`: the file whose PATH lists the entry
+//!   - `homebrew`: `after:ENTRY` for a Homebrew directory behind the system
+//!     directory ENTRY, or `not-in-path` for a missing Homebrew `bin`
+//! - `history`: `backup<TAB>NUMBER<TAB>TIMESTAMP<TAB>LABEL<TAB>FILE`, oldest
+//!   first, where NUMBER is what `restore` takes and TIMESTAMP is local time
+//!   as `YYYY-MM-DDTHH:MM:SS`. The backups are followed by
+//!   `shell-config<TAB>NUMBER<TAB>TIMESTAMP<TAB>SHELL<TAB>CONFIG<TAB>FILE`
+//!   records: first the shell configs saved in backups, where FILE is the
+//!   backup, then the `.bak_` copies made before configs were rewritten,
+//!   oldest first, where NUMBER is empty and FILE is the copy.
+
+use std::fmt;
+use std::str::FromStr;
+
+/// Versions of the porcelain format
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum PorcelainVersion {
+    #[default]
+    V1,
+}
+
+impl fmt::Display for PorcelainVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PorcelainVersion::V1 => write!(f, "v1"),
+        }
+    }
+}
+
+impl FromStr for PorcelainVersion {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "v1" | "1" => Ok(PorcelainVersion::V1),
+            _ => Err(format!("Unsupported porcelain version: {}", s)),
+        }
+    }
+}
+
+/// Prints the line naming the format version, which starts porcelain output
+pub fn print_header(version: PorcelainVersion) {
+    println!("{}", record(&["porcelain", &version.to_string()]));
+}
+
+/// Formats one record, escaping each field
+pub fn record(fields: &[&str]) -> String {
+    fields
+        .iter()
+        .map(|field| escape(field))
+        .collect::<Vec<_>>()
+        .join("\t")
+}
+
+/// Escapes the characters that would break a record apart
+fn escape(field: &str) -> String {
+    let mut escaped = String::with_capacity(field.len());
+    for c in field.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\t' => escaped.push_str("\\t"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record() {
+        assert_eq!(
+            record(&["entry", "1", "valid", "/usr/bin"]),
+            "entry\t1\tvalid\t/usr/bin"
+        );
+        assert_eq!(
+            record(&["entry", "2", "missing", "/odd\tdir\\x\n"]),
+            "entry\t2\tmissing\t/odd\\tdir\\\\x\\n"
+        );
+        assert_eq!(record(&["problem", "empty", "", ""]), "problem\tempty\t\t");
+    }
+
+    #[test]
+    fn test_porcelain_version() {
+        assert_eq!("v1".parse(), Ok(PorcelainVersion::V1));
+        assert_eq!("1".parse(), Ok(PorcelainVersion::V1));
+        assert!("v2".parse::<PorcelainVersion>().is_err());
+    }
+}
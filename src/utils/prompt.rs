@@ -0,0 +1,106 @@
+//! Interactive confirmation prompts.
+//!
+//! Every confirmation goes through [`confirm`], which honors the global
+//! `--yes` and `--no-input` flags and never waits for input when stdin isn't
+//! a terminal, so scripts and cron jobs can't hang on a prompt.
+
+use crate::msg;
+use lazy_static::lazy_static;
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::sync::Mutex;
+
+lazy_static! {
+    static ref PROMPT_MODE: Mutex<PromptMode> = Mutex::new(PromptMode::default());
+}
+
+/// How confirmation prompts are answered
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum PromptMode {
+    /// Ask on the terminal (default)
+    #[default]
+    Interactive,
+    /// Answer yes without asking (`--yes`)
+    AssumeYes,
+    /// Answer no without asking (`--no-input`)
+    NoInput,
+}
+
+/// Sets how prompts are answered for the rest of the run
+pub fn set_prompt_mode(mode: PromptMode) {
+    if let Ok(mut current) = PROMPT_MODE.lock() {
+        *current = mode;
+    }
+}
+
+/// Returns how prompts are currently answered
+pub fn get_prompt_mode() -> PromptMode {
+    PROMPT_MODE.lock().map(|mode| *mode).unwrap_or_default()
+}
+
+/// Returns the answer to use without asking, or `None` if the user should be asked
+fn automatic_answer(mode: PromptMode, interactive: bool) -> Option<bool> {
+    match mode {
+        PromptMode::AssumeYes => Some(true),
+        PromptMode::NoInput => Some(false),
+        PromptMode::Interactive if !interactive => Some(false),
+        PromptMode::Interactive => None,
+    }
+}
+
+/// Asks the user a yes/no question on the terminal.
+///
+/// With `--yes` the answer is yes; with `--no-input`, or when stdin isn't a
+/// terminal, it is no. The automatic answer is printed so logs show why an
+/// operation went ahead or was skipped.
+///
+/// # Arguments
+/// * `message` - Question to display, without the `[y/N]` suffix
+///
+/// # Returns
+/// * `true` if the user answered yes, `false` otherwise (including on EOF)
+pub fn confirm(message: &str) -> bool {
+    let mode = get_prompt_mode();
+    if let Some(answer) = automatic_answer(mode, io::stdin().is_terminal()) {
+        let reason = match mode {
+            PromptMode::AssumeYes => "--yes".to_string(),
+            PromptMode::NoInput => "--no-input".to_string(),
+            PromptMode::Interactive => msg!("prompt-not-terminal"),
+        };
+        let answer_text = if answer {
+            msg!("prompt-yes")
+        } else {
+            msg!("prompt-no")
+        };
+        println!("{} [y/N] {} ({})", message, answer_text, reason);
+        return answer;
+    }
+
+    print!("{} [y/N] ", message);
+    if io::stdout().flush().is_err() {
+        return false;
+    }
+
+    let mut answer = String::new();
+    if io::stdin().lock().read_line(&mut answer).is_err() {
+        return false;
+    }
+
+    matches!(answer.trim().to_lowercase().as_str(), "y" | "yes")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_automatic_answer() {
+        assert_eq!(automatic_answer(PromptMode::AssumeYes, false), Some(true));
+        assert_eq!(automatic_answer(PromptMode::AssumeYes, true), Some(true));
+        assert_eq!(automatic_answer(PromptMode::NoInput, true), Some(false));
+        assert_eq!(
+            automatic_answer(PromptMode::Interactive, false),
+            Some(false)
+        );
+        assert_eq!(automatic_answer(PromptMode::Interactive, true), None);
+    }
+}
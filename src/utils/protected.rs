@@ -0,0 +1,79 @@
+//! System directories that delete and flush refuse to remove.
+//!
+//! Removing `/usr/bin` by mistake leaves a shell where nothing runs, so
+//! these entries are only removed with `--allow-system`. The list can be
+//! replaced in the config file:
+//!
+//! ```toml
+//! protected-dirs = ["/usr/bin", "/bin", "/opt/homebrew/bin"]
+//! ```
+//!
+//! An empty list turns the safeguard off.
+//!
+//! Separately, users can list directories that flush, clean and normalize
+//! must leave exactly as written, even when they are duplicates or point at
+//! a mount that is currently unavailable:
+//!
+//! ```toml
+//! keep-dirs = ["/mnt/nfs/tools/bin", "~/bin"]
+//! ```
+
+use crate::config;
+use crate::utils;
+use std::path::{Path, PathBuf};
+
+/// Directories protected when the config doesn't name any
+pub const DEFAULT_PROTECTED_DIRS: &[&str] = &["/bin", "/sbin", "/usr/bin", "/usr/sbin"];
+
+/// Returns the protected directories from the config, or the defaults
+pub fn protected_dirs() -> Vec<PathBuf> {
+    match config::get_config().protected_dirs {
+        Some(dirs) => dirs.iter().map(|dir| utils::expand_path(dir)).collect(),
+        None => DEFAULT_PROTECTED_DIRS.iter().map(PathBuf::from).collect(),
+    }
+}
+
+/// Returns whether `entry` is one of the protected directories.
+///
+/// Paths compare by component, so a trailing slash doesn't matter.
+pub fn is_protected(entry: &Path, protected: &[PathBuf]) -> bool {
+    protected.iter().any(|dir| dir == entry)
+}
+
+/// Returns the directories the config says to keep as written
+pub fn keep_dirs() -> Vec<PathBuf> {
+    config::get_config()
+        .keep_dirs
+        .iter()
+        .map(|dir| utils::expand_path(dir))
+        .collect()
+}
+
+/// Returns whether `entry` is listed in `keep-dirs`.
+///
+/// Paths compare by component, so a trailing slash or doubled `/` doesn't matter.
+pub fn is_kept(entry: &Path, keep: &[PathBuf]) -> bool {
+    !entry.as_os_str().is_empty() && keep.iter().any(|dir| dir == entry)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_protected() {
+        let protected: Vec<PathBuf> = DEFAULT_PROTECTED_DIRS.iter().map(PathBuf::from).collect();
+        assert!(is_protected(Path::new("/usr/bin"), &protected));
+        assert!(is_protected(Path::new("/usr/bin/"), &protected));
+        assert!(!is_protected(Path::new("/usr/local/bin"), &protected));
+        assert!(!is_protected(Path::new("/usr/bin"), &[]));
+    }
+
+    #[test]
+    fn test_is_kept() {
+        let keep = vec![PathBuf::from("/mnt/nfs/bin")];
+        assert!(is_kept(Path::new("/mnt/nfs//bin/"), &keep));
+        assert!(!is_kept(Path::new("/mnt/nfs"), &keep));
+        assert!(!is_kept(Path::new(""), &keep));
+    }
+}
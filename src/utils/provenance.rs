@@ -0,0 +1,305 @@
+//! Where PATH entries came from.
+//!
+//! Whenever an operation adds entries to PATH, each new entry is recorded in
+//! `$XDG_STATE_HOME/pathmaster/provenance.json` with the time, the command
+//! line and an optional `--reason`. `pathmaster why <dir>` reads it back, so
+//! entries added years ago still have an explanation.
+//!
+//! Entries added with `add --ttl` also get an expiry time. `check` lists
+//! expired entries still in PATH and `expire` removes them.
+
+use crate::utils::audit::AuditRecord;
+use crate::utils::find_path_entry;
+use crate::utils::shell::locations;
+use chrono::{Duration, Local, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Format of the times in the store
+pub const TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// How and when one directory was added to PATH
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Provenance {
+    /// When it was added, as `%Y-%m-%d %H:%M:%S` local time
+    pub added: String,
+    /// Name of the command that added it, e.g. `add`
+    pub command: String,
+    /// Command-line arguments, excluding the program name
+    pub arguments: Vec<String>,
+    /// Why it was added, from `--reason`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// When it should be taken out of PATH, from `add --ttl`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires: Option<String>,
+}
+
+impl Provenance {
+    /// Returns whether the entry's expiry time has passed
+    pub fn is_expired(&self, now: NaiveDateTime) -> bool {
+        self.expires
+            .as_deref()
+            .and_then(|expires| NaiveDateTime::parse_from_str(expires, TIME_FORMAT).ok())
+            .is_some_and(|expires| expires <= now)
+    }
+}
+
+/// Provenance of every directory pathmaster has added, keyed by directory
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ProvenanceStore {
+    pub entries: BTreeMap<String, Provenance>,
+}
+
+impl ProvenanceStore {
+    /// Loads the store, treating a missing or unreadable file as empty
+    pub fn load() -> Self {
+        Self::load_from(&store_path())
+    }
+
+    /// Saves the store
+    pub fn save(&self) -> io::Result<()> {
+        self.save_to(&store_path())
+    }
+
+    fn load_from(path: &Path) -> Self {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    fn save_to(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)
+    }
+
+    /// Records every entry an operation added, replacing older records for them
+    pub fn record(&mut self, operation: &AuditRecord) {
+        for entry in operation.added() {
+            self.entries.insert(
+                entry.clone(),
+                Provenance {
+                    added: operation.timestamp.clone(),
+                    command: operation.command.clone(),
+                    arguments: operation.arguments.clone(),
+                    reason: operation.reason.clone(),
+                    expires: operation.expires.clone(),
+                },
+            );
+        }
+    }
+
+    /// Sets the expiry time of existing entries; see [`set_expiry`]
+    fn set_expiry(&mut self, entries: &[PathBuf], provenance: &Provenance) {
+        for entry in entries {
+            let key = match self.find(entry) {
+                Some((key, _)) => key.clone(),
+                None => entry.to_string_lossy().into_owned(),
+            };
+            self.entries
+                .entry(key)
+                .and_modify(|record| record.expires = provenance.expires.clone())
+                .or_insert_with(|| provenance.clone());
+        }
+    }
+
+    /// Finds the record for a directory, also matching other spellings of it
+    pub fn find(&self, dir: &Path) -> Option<(&String, &Provenance)> {
+        if let Some(found) = self.entries.get_key_value(&*dir.to_string_lossy()) {
+            return Some(found);
+        }
+        self.entries
+            .iter()
+            .find(|(entry, _)| find_path_entry(&[PathBuf::from(entry)], dir).is_some())
+    }
+}
+
+/// Gives entries that were already in PATH the expiry time of `provenance`
+///
+/// Entries with a record keep it, with only the expiry time replaced;
+/// entries without one get `provenance` as their record.
+pub fn set_expiry(entries: &[PathBuf], provenance: &Provenance) -> io::Result<()> {
+    let mut store = ProvenanceStore::load();
+    store.set_expiry(entries, provenance);
+    store.save()
+}
+
+/// Returns the entries of `entries` whose expiry time has passed
+///
+/// # Returns
+/// Each expired entry with the time it expired
+pub fn expired_entries(entries: &[PathBuf]) -> Vec<(PathBuf, String)> {
+    let store = ProvenanceStore::load();
+    let now = Local::now().naive_local();
+    entries
+        .iter()
+        .filter_map(|entry| {
+            let (_, provenance) = store.find(entry)?;
+            if !provenance.is_expired(now) {
+                return None;
+            }
+            Some((entry.clone(), provenance.expires.clone()?))
+        })
+        .collect()
+}
+
+/// Parses a time to live such as `30m`, `12h`, `7d` or `2w`
+pub fn parse_ttl(ttl: &str) -> Result<Duration, String> {
+    let ttl = ttl.trim();
+    let split = ttl
+        .find(|c: char| !c.is_ascii_digit())
+        .ok_or_else(|| format!("'{}' needs a unit: m, h, d or w (e.g. 7d)", ttl))?;
+    let (count, unit) = ttl.split_at(split);
+    let count: i64 = count
+        .parse()
+        .map_err(|_| format!("'{}' doesn't start with a number", ttl))?;
+    let duration = match unit {
+        "m" | "min" => Duration::try_minutes(count),
+        "h" => Duration::try_hours(count),
+        "d" => Duration::try_days(count),
+        "w" => Duration::try_weeks(count),
+        _ => return Err(format!("Unknown unit '{}'; use m, h, d or w", unit)),
+    };
+    duration
+        .filter(|duration| *duration > Duration::zero())
+        .ok_or_else(|| format!("'{}' is not a usable time to live", ttl))
+}
+
+/// Formats the time a TTL starting now runs out
+pub fn expiry_time(ttl: Duration) -> String {
+    (Local::now().naive_local() + ttl)
+        .format(TIME_FORMAT)
+        .to_string()
+}
+
+/// Returns the location of the provenance store
+pub fn store_path() -> PathBuf {
+    locations::xdg_state_home()
+        .join("pathmaster")
+        .join("provenance.json")
+}
+
+/// Records the entries an operation added in the provenance store
+pub fn record_operation(operation: &AuditRecord) -> io::Result<()> {
+    if operation.added().is_empty() {
+        return Ok(());
+    }
+    let mut store = ProvenanceStore::load();
+    store.record(operation);
+    store.save()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_record_and_find() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("state/provenance.json");
+        assert_eq!(
+            ProvenanceStore::load_from(&path),
+            ProvenanceStore::default()
+        );
+
+        let operation = AuditRecord {
+            reason: Some("for CUDA 12".to_string()),
+            ..AuditRecord::new(
+                "add",
+                vec!["add".to_string(), "/pm-test/cuda/bin".to_string()],
+                &[PathBuf::from("/usr/bin")],
+                &[
+                    PathBuf::from("/usr/bin"),
+                    PathBuf::from("/pm-test/cuda/bin"),
+                ],
+                None,
+            )
+        };
+        let mut store = ProvenanceStore::default();
+        store.record(&operation);
+        store.save_to(&path).unwrap();
+
+        let store = ProvenanceStore::load_from(&path);
+        assert_eq!(store.entries.len(), 1);
+        let (entry, provenance) = store.find(Path::new("/pm-test/cuda/bin")).unwrap();
+        assert_eq!(entry, "/pm-test/cuda/bin");
+        assert_eq!(provenance.command, "add");
+        assert_eq!(provenance.reason.as_deref(), Some("for CUDA 12"));
+        assert!(store.find(Path::new("/usr/bin")).is_none());
+    }
+
+    #[test]
+    fn test_set_expiry_on_existing_entries() {
+        let recorded = Provenance {
+            added: "2024-03-01 09:00:00".to_string(),
+            command: "add".to_string(),
+            arguments: vec!["add".to_string(), "/pm-test/tools".to_string()],
+            reason: Some("build tools".to_string()),
+            expires: None,
+        };
+        let mut store = ProvenanceStore::default();
+        store
+            .entries
+            .insert("/pm-test/tools".to_string(), recorded.clone());
+
+        let ttl = Provenance {
+            added: "2024-03-21 12:00:00".to_string(),
+            arguments: vec!["add".to_string(), "--ttl".to_string(), "7d".to_string()],
+            reason: None,
+            expires: Some("2024-03-28 12:00:00".to_string()),
+            ..recorded.clone()
+        };
+        store.set_expiry(
+            &[
+                PathBuf::from("/pm-test/tools"),
+                PathBuf::from("/pm-test/unrecorded"),
+            ],
+            &ttl,
+        );
+
+        // A recorded entry keeps its record apart from the expiry time
+        let (_, tools) = store.find(Path::new("/pm-test/tools")).unwrap();
+        assert_eq!(
+            tools,
+            &Provenance {
+                expires: ttl.expires.clone(),
+                ..recorded
+            }
+        );
+        let (_, unrecorded) = store.find(Path::new("/pm-test/unrecorded")).unwrap();
+        assert_eq!(unrecorded, &ttl);
+    }
+
+    #[test]
+    fn test_parse_ttl() {
+        assert_eq!(parse_ttl("7d"), Ok(Duration::days(7)));
+        assert_eq!(parse_ttl("90m"), Ok(Duration::minutes(90)));
+        assert_eq!(parse_ttl("2w"), Ok(Duration::weeks(2)));
+        assert!(parse_ttl("7").is_err());
+        assert!(parse_ttl("0d").is_err());
+        assert!(parse_ttl("d").is_err());
+        assert!(parse_ttl("3y").is_err());
+    }
+
+    #[test]
+    fn test_is_expired() {
+        let now = NaiveDateTime::parse_from_str("2024-03-21 12:00:00", TIME_FORMAT).unwrap();
+        let provenance = |expires: Option<&str>| Provenance {
+            added: "2024-03-14 12:00:00".to_string(),
+            command: "add".to_string(),
+            arguments: Vec::new(),
+            reason: None,
+            expires: expires.map(str::to_string),
+        };
+        assert!(provenance(Some("2024-03-21 11:59:59")).is_expired(now));
+        assert!(!provenance(Some("2024-03-21 12:00:01")).is_expired(now));
+        assert!(!provenance(None).is_expired(now));
+    }
+}
@@ -0,0 +1,139 @@
+//! Fake root, set from `--root` or `PATHMASTER_ROOT`.
+//!
+//! With a root set, every file pathmaster discovers, reads or writes is
+//! looked up under it as if it were `/`: `~/.bashrc` becomes
+//! `<root>/home/user/.bashrc` and `/etc/profile` becomes `<root>/etc/profile`.
+//! This lets a copy of the dotfiles be tried out, and tests run real
+//! commands, without touching the real ones. This module provides
+//! functionality to:
+//! - Set and get the root
+//! - Move a path under the root, or take it back out
+//!
+//! PATH entries themselves aren't moved: they name directories the real
+//! shell searches, so they're checked where they really are.
+
+use lazy_static::lazy_static;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+
+/// Environment variable read when `--root` isn't given
+pub const ROOT_ENV: &str = "PATHMASTER_ROOT";
+
+lazy_static! {
+    static ref ROOT: Mutex<Option<PathBuf>> = Mutex::new(None);
+}
+
+/// Sets the fake root, or clears it with `None`
+///
+/// # Arguments
+///
+/// * `root` - Directory to use as `/`; relative paths are taken from the current directory
+///
+/// # Returns
+/// * `Err(io::Error)` - If the root isn't an existing directory
+pub fn set_root(root: Option<&Path>) -> io::Result<()> {
+    let root = match root {
+        Some(root) => {
+            let root = root.canonicalize().map_err(|e| {
+                io::Error::new(e.kind(), format!("Root '{}': {}", root.display(), e))
+            })?;
+            if !root.is_dir() {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("Root '{}' is not a directory", root.display()),
+                ));
+            }
+            Some(root)
+        }
+        None => None,
+    };
+    *ROOT
+        .lock()
+        .map_err(|_| io::Error::other("Failed to lock root mutex"))? = root;
+    Ok(())
+}
+
+/// Returns the fake root, if one is set
+pub fn get_root() -> Option<PathBuf> {
+    ROOT.lock().ok().and_then(|root| root.clone())
+}
+
+/// Returns where `path` is found under the root
+///
+/// Paths already under the root, relative paths, and all paths when no
+/// root is set are returned unchanged.
+///
+/// # Example
+///
+/// ```
+/// // With --root /tmp/sandbox
+/// assert_eq!(rooted("/etc/profile"), PathBuf::from("/tmp/sandbox/etc/profile"));
+/// ```
+pub fn rooted(path: impl AsRef<Path>) -> PathBuf {
+    match get_root() {
+        Some(root) => rooted_in(&root, path.as_ref()),
+        None => path.as_ref().to_path_buf(),
+    }
+}
+
+/// Returns the path a file under the root stands for outside of it,
+/// e.g. for `$HOME` in a shell config
+pub fn unrooted(path: impl AsRef<Path>) -> PathBuf {
+    match get_root() {
+        Some(root) => unrooted_in(&root, path.as_ref()),
+        None => path.as_ref().to_path_buf(),
+    }
+}
+
+fn rooted_in(root: &Path, path: &Path) -> PathBuf {
+    if path.is_relative() || path.starts_with(root) {
+        return path.to_path_buf();
+    }
+    let relative: PathBuf = path
+        .components()
+        .filter(|component| !matches!(component, Component::RootDir | Component::Prefix(_)))
+        .collect();
+    root.join(relative)
+}
+
+fn unrooted_in(root: &Path, path: &Path) -> PathBuf {
+    match path.strip_prefix(root) {
+        Ok(relative) => Path::new("/").join(relative),
+        Err(_) => path.to_path_buf(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_rooted_and_unrooted() {
+        let root = Path::new("/pm-test/root");
+        let profile = rooted_in(root, Path::new("/etc/profile"));
+        assert_eq!(profile, PathBuf::from("/pm-test/root/etc/profile"));
+        assert_eq!(rooted_in(root, &profile), profile);
+        assert_eq!(
+            rooted_in(root, Path::new("relative/file")),
+            PathBuf::from("relative/file")
+        );
+        assert_eq!(unrooted_in(root, &profile), PathBuf::from("/etc/profile"));
+        assert_eq!(
+            unrooted_in(root, Path::new("/pm-test/bin")),
+            PathBuf::from("/pm-test/bin")
+        );
+    }
+
+    #[test]
+    fn test_root_must_be_a_directory() {
+        // Rejected roots leave the current one in place
+        let temp_dir = TempDir::new().unwrap();
+        assert!(set_root(Some(&temp_dir.path().join("missing"))).is_err());
+        let file = temp_dir.path().join("file");
+        std::fs::write(&file, "").unwrap();
+        assert!(set_root(Some(&file)).is_err());
+        assert_eq!(get_root(), None);
+    }
+}
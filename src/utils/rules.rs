@@ -0,0 +1,209 @@
+//! Ordering rules for PATH entries.
+//!
+//! Rules are declared in the config file:
+//!
+//! ```toml
+//! [[rules]]
+//! entry = "~/.local/bin"
+//! before = "/usr/bin"
+//!
+//! [[rules]]
+//! entry = "/usr/bin"
+//! position = "last"
+//! ```
+//!
+//! A rule only applies when its entry (and the entry it's ordered against)
+//! is in PATH. Whenever pathmaster writes a new PATH, violations are either
+//! reported or, with `enforce-rules = true`, fixed by moving entries.
+
+use crate::utils;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::PathBuf;
+
+/// Where a pinned entry must appear
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Position {
+    First,
+    Last,
+}
+
+/// An ordering constraint on one PATH entry
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct PathRule {
+    /// Entry the rule applies to; `~` is expanded
+    pub entry: String,
+    /// Entry that must come after this one
+    pub before: Option<String>,
+    /// Entry that must come before this one
+    pub after: Option<String>,
+    /// Required position in PATH
+    pub position: Option<Position>,
+}
+
+impl fmt::Display for PathRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut constraints = Vec::new();
+        if let Some(before) = &self.before {
+            constraints.push(format!("before {}", before));
+        }
+        if let Some(after) = &self.after {
+            constraints.push(format!("after {}", after));
+        }
+        match self.position {
+            Some(Position::First) => constraints.push("first".to_string()),
+            Some(Position::Last) => constraints.push("last".to_string()),
+            None => {}
+        }
+        write!(f, "{} must be {}", self.entry, constraints.join(" and "))
+    }
+}
+
+impl PathRule {
+    /// Returns a move fixing this rule in `entries`, or `None` if it holds.
+    ///
+    /// The move is `(from, to)`: remove the entry at `from`, then insert it
+    /// at `to`.
+    fn check(&self, entries: &[PathBuf]) -> Option<(usize, usize)> {
+        let index = position_of(entries, &self.entry)?;
+
+        match self.position {
+            Some(Position::First) if index != 0 => return Some((index, 0)),
+            Some(Position::Last) if index != entries.len() - 1 => {
+                return Some((index, entries.len() - 1))
+            }
+            _ => {}
+        }
+
+        if let Some(other) = self.before.as_deref().and_then(|e| position_of(entries, e)) {
+            if index > other {
+                return Some((index, other));
+            }
+        }
+        if let Some(other) = self.after.as_deref().and_then(|e| position_of(entries, e)) {
+            if index < other {
+                // Removing the entry shifts `other` down by one
+                return Some((index, other));
+            }
+        }
+        None
+    }
+}
+
+/// Finds the first occurrence of a rule's entry, expanding `~`
+fn position_of(entries: &[PathBuf], entry: &str) -> Option<usize> {
+    let target = utils::expand_path(entry);
+    entries.iter().position(|candidate| *candidate == target)
+}
+
+/// Returns the rules that `entries` don't satisfy
+pub fn violations<'a>(rules: &'a [PathRule], entries: &[PathBuf]) -> Vec<&'a PathRule> {
+    rules
+        .iter()
+        .filter(|rule| rule.check(entries).is_some())
+        .collect()
+}
+
+/// Reorders `entries` to satisfy the rules where possible.
+///
+/// Fixes are applied repeatedly, since moving one entry can break an
+/// earlier rule. Contradictory rules can't all hold; after a bounded number
+/// of passes the remaining violations are left for the caller to report.
+pub fn enforce(rules: &[PathRule], entries: &[PathBuf]) -> Vec<PathBuf> {
+    let mut entries = entries.to_vec();
+    let max_passes = rules.len() * 2 + 1;
+
+    for _ in 0..max_passes {
+        let fix = rules.iter().find_map(|rule| rule.check(&entries));
+        match fix {
+            Some((from, to)) => {
+                let entry = entries.remove(from);
+                entries.insert(to, entry);
+            }
+            None => break,
+        }
+    }
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_support::paths;
+
+    fn rule(entry: &str) -> PathRule {
+        PathRule {
+            entry: entry.to_string(),
+            ..PathRule::default()
+        }
+    }
+
+    #[test]
+    fn test_parse_rules() {
+        #[derive(Deserialize)]
+        struct Config {
+            rules: Vec<PathRule>,
+        }
+
+        let config: Config = toml::from_str(
+            "[[rules]]\nentry = \"~/.local/bin\"\nbefore = \"/usr/bin\"\n\n\
+             [[rules]]\nentry = \"/usr/bin\"\nposition = \"last\"\n",
+        )
+        .unwrap();
+        assert_eq!(config.rules[0].before.as_deref(), Some("/usr/bin"));
+        assert_eq!(config.rules[1].position, Some(Position::Last));
+        assert_eq!(config.rules[1].to_string(), "/usr/bin must be last");
+    }
+
+    #[test]
+    fn test_violations_and_enforce() {
+        let rules = vec![
+            PathRule {
+                before: Some("/usr/bin".to_string()),
+                ..rule("/opt/local/bin")
+            },
+            PathRule {
+                position: Some(Position::Last),
+                ..rule("/usr/bin")
+            },
+            PathRule {
+                after: Some("/bin".to_string()),
+                ..rule("/sbin")
+            },
+            // Doesn't apply, since the entry isn't in PATH
+            PathRule {
+                position: Some(Position::First),
+                ..rule("/not/in/path")
+            },
+        ];
+        let entries = paths(&["/sbin", "/usr/bin", "/bin", "/opt/local/bin"]);
+
+        assert_eq!(violations(&rules, &entries).len(), 3);
+
+        let fixed = enforce(&rules, &entries);
+        assert!(violations(&rules, &fixed).is_empty());
+        assert_eq!(
+            fixed,
+            paths(&["/opt/local/bin", "/bin", "/sbin", "/usr/bin"])
+        );
+    }
+
+    #[test]
+    fn test_contradictory_rules_terminate() {
+        let rules = vec![
+            PathRule {
+                position: Some(Position::First),
+                ..rule("/a")
+            },
+            PathRule {
+                before: Some("/a".to_string()),
+                ..rule("/b")
+            },
+        ];
+        let fixed = enforce(&rules, &paths(&["/a", "/b"]));
+        assert_eq!(fixed.len(), 2);
+        assert!(!violations(&rules, &fixed).is_empty());
+    }
+}
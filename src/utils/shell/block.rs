@@ -0,0 +1,152 @@
+//! Marker-delimited managed block in shell configs.
+//!
+//! pathmaster keeps the PATH declarations it writes between a pair of
+//! marker comments. Once the block exists, updates replace only the lines
+//! between the markers, so repeated runs are idempotent and anything the
+//! user writes outside the block is left untouched.
+
+use crate::utils::shell::types::PathModification;
+
+/// Marker opening the pathmaster-managed block
+pub const BLOCK_START: &str = "# >>> pathmaster >>>";
+/// Marker closing the pathmaster-managed block
+pub const BLOCK_END: &str = "# <<< pathmaster <<<";
+/// Comment heading the PATH declarations pathmaster writes. It carries no
+/// date, so writing the same entries again leaves the file unchanged.
+pub const BLOCK_HEADER: &str = "# Updated by pathmaster";
+
+/// Locates the managed block in a config file.
+///
+/// # Returns
+/// * `Some((start, end))` - Zero-based line indices of the start and end markers
+/// * `None` - If the file has no complete block
+pub fn find_block(content: &str) -> Option<(usize, usize)> {
+    let lines: Vec<&str> = content.lines().collect();
+    let start = lines.iter().position(|line| line.trim() == BLOCK_START)?;
+    let end = lines[start..]
+        .iter()
+        .position(|line| line.trim() == BLOCK_END)?;
+    Some((start, start + end))
+}
+
+/// Returns the zero-based line the managed block starts at, or will be
+/// created at by [`apply_block`]
+pub fn block_position(content: &str, modifications: &[PathModification]) -> usize {
+    find_block(content)
+        .map(|(start, _)| start)
+        .or_else(|| modifications.iter().map(|m| m.line_number - 1).min())
+        .unwrap_or_else(|| content.lines().count())
+}
+
+/// Writes `body` into the managed block of a config file.
+///
+/// When the block already exists only its contents are replaced. Otherwise
+/// the lines listed in `modifications` are removed and the block is created
+/// where the first of them was, or appended to the end of the file if there
+/// were none.
+///
+/// # Arguments
+/// * `content` - Current contents of the config file
+/// * `modifications` - PATH declarations detected by the shell handler
+/// * `body` - New PATH declarations, as produced by `format_path_export`
+pub fn apply_block(content: &str, modifications: &[PathModification], body: &str) -> String {
+    let lines: Vec<&str> = content.lines().collect();
+    let body_lines: Vec<&str> = body.trim_matches('\n').lines().collect();
+
+    let mut block = vec![BLOCK_START];
+    block.extend(body_lines);
+    block.push(BLOCK_END);
+
+    let updated: Vec<&str> = match find_block(content) {
+        Some((start, end)) => lines[..start]
+            .iter()
+            .copied()
+            .chain(block)
+            .chain(lines[end + 1..].iter().copied())
+            .collect(),
+        None => {
+            let is_modified = |idx: usize| modifications.iter().any(|m| m.line_number == idx + 1);
+            let insert_at = (0..lines.len()).find(|&idx| is_modified(idx));
+
+            let mut updated = Vec::new();
+            for (idx, line) in lines.iter().enumerate() {
+                if Some(idx) == insert_at {
+                    updated.extend(block.iter().copied());
+                }
+                if !is_modified(idx) {
+                    updated.push(line);
+                }
+            }
+            if insert_at.is_none() {
+                if updated.last().is_some_and(|line| !line.trim().is_empty()) {
+                    updated.push("");
+                }
+                updated.extend(block);
+            }
+            updated
+        }
+    };
+
+    let mut result = updated.join("\n");
+    result.push('\n');
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::shell::types::ModificationType;
+
+    fn modification(line_number: usize) -> PathModification {
+        PathModification {
+            line_number,
+            content: String::new(),
+            modification_type: ModificationType::Assignment,
+        }
+    }
+
+    #[test]
+    fn test_block_created_at_first_declaration() {
+        let content = "# config\nexport PATH=/a\nalias ll='ls -l'\nexport PATH=/b:$PATH\n";
+        let updated = apply_block(
+            content,
+            &[modification(2), modification(4)],
+            "\nexport PATH=\"/c\"\n",
+        );
+
+        assert_eq!(
+            updated,
+            format!(
+                "# config\n{}\nexport PATH=\"/c\"\n{}\nalias ll='ls -l'\n",
+                BLOCK_START, BLOCK_END
+            )
+        );
+    }
+
+    #[test]
+    fn test_block_appended_without_declarations() {
+        let updated = apply_block("# config\n", &[], "export PATH=\"/c\"\n");
+        assert_eq!(
+            updated,
+            format!(
+                "# config\n\n{}\nexport PATH=\"/c\"\n{}\n",
+                BLOCK_START, BLOCK_END
+            )
+        );
+    }
+
+    #[test]
+    fn test_block_replaced_in_place() {
+        let content = format!(
+            "# before\n{}\nexport PATH=\"/old\"\n{}\nexport PATH=$PATH:/user\n",
+            BLOCK_START, BLOCK_END
+        );
+        let once = apply_block(&content, &[modification(5)], "export PATH=\"/new\"\n");
+        let twice = apply_block(&once, &[modification(5)], "export PATH=\"/new\"\n");
+
+        assert_eq!(once, twice);
+        assert!(!once.contains("/old"));
+        assert!(once.ends_with(&format!("{}\nexport PATH=$PATH:/user\n", BLOCK_END)));
+        assert_eq!(find_block(&once), Some((1, 3)));
+    }
+}
@@ -0,0 +1,158 @@
+//! Detection of conditional and nested PATH logic in shell configs.
+//!
+//! Lines such as `[ -d ~/bin ] && PATH=~/bin:$PATH` or a `PATH=` inside an
+//! `if [ -d ... ]` guard express intent that a flat PATH list can't
+//! capture. Handlers use this module to tell those apart from top-level
+//! unconditional declarations, which are the only ones safe to replace.
+
+use crate::utils::shell::continuation;
+use crate::utils::shell::types::ShellType;
+
+/// Keywords opening and closing compound commands for a shell's syntax
+struct Keywords {
+    openers: &'static [&'static str],
+    closers: &'static [&'static str],
+    /// Words that make the rest of the line conditional (fish `and`/`or`)
+    conditional_prefixes: &'static [&'static str],
+}
+
+const POSIX_KEYWORDS: Keywords = Keywords {
+    openers: &["if", "case", "for", "while", "until", "select", "{"],
+    closers: &["fi", "esac", "done", "}"],
+    conditional_prefixes: &[],
+};
+
+const FISH_KEYWORDS: Keywords = Keywords {
+    openers: &["if", "function", "for", "while", "switch", "begin"],
+    closers: &["end"],
+    conditional_prefixes: &["and", "or", "not"],
+};
+
+const TCSH_KEYWORDS: Keywords = Keywords {
+    openers: &["foreach", "while", "switch"],
+    closers: &["endif", "end", "endsw"],
+    conditional_prefixes: &["if"],
+};
+
+/// Determines which lines of a config are conditional or nested.
+///
+/// A line is conditional when it sits inside a compound command (if, case,
+/// loops, function bodies), opens or closes one itself, or chains commands
+/// with `&&` / `||`. Backslash-continued lines are judged as one statement,
+/// so every line of a continued statement gets the same flag.
+///
+/// # Arguments
+/// * `content` - Contents of the config file
+/// * `shell` - Shell whose syntax the file uses
+///
+/// # Returns
+/// One flag per line of `content`, `true` for conditional lines
+pub fn conditional_lines(content: &str, shell: &ShellType) -> Vec<bool> {
+    let keywords = match shell {
+        ShellType::Fish => &FISH_KEYWORDS,
+        ShellType::Tcsh => &TCSH_KEYWORDS,
+        _ => &POSIX_KEYWORDS,
+    };
+
+    let mut depth: usize = 0;
+    let mut result = Vec::new();
+
+    for statement in continuation::logical_lines(content) {
+        let code = strip_comment(&statement.text);
+        let words: Vec<&str> = code
+            .split(|c: char| c.is_whitespace() || c == ';')
+            .filter(|w| !w.is_empty())
+            .collect();
+
+        let mut conditional = depth > 0 || code.contains("&&") || code.contains("||");
+
+        let has_prefix = code
+            .split(';')
+            .filter_map(|statement| statement.split_whitespace().next())
+            .any(|first| keywords.conditional_prefixes.contains(&first));
+        if has_prefix {
+            conditional = true;
+        }
+
+        // Block-form tcsh `if (...) then` opens a block closed by `endif`
+        let tcsh_block_if = *shell == ShellType::Tcsh
+            && words.first() == Some(&"if")
+            && words.last() == Some(&"then");
+        if tcsh_block_if {
+            depth += 1;
+        }
+
+        for word in &words {
+            let opens = keywords.openers.contains(word)
+                || (*shell != ShellType::Fish && *shell != ShellType::Tcsh && word.ends_with('{'));
+            if opens {
+                depth += 1;
+                conditional = true;
+            } else if keywords.closers.contains(word) {
+                depth = depth.saturating_sub(1);
+                conditional = true;
+            }
+        }
+
+        result.extend(statement.line_numbers().map(|_| conditional));
+    }
+
+    result
+}
+
+/// Removes a trailing `#` comment, ignoring `#` inside quotes or words
+fn strip_comment(line: &str) -> &str {
+    let mut in_single = false;
+    let mut in_double = false;
+    let mut prev = ' ';
+
+    for (idx, c) in line.char_indices() {
+        match c {
+            '\'' if !in_double => in_single = !in_single,
+            '"' if !in_single => in_double = !in_double,
+            '#' if !in_single && !in_double && prev.is_whitespace() => return &line[..idx],
+            _ => {}
+        }
+        prev = c;
+    }
+
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_posix_conditionals() {
+        let content = r#"export PATH=/usr/bin
+if [ -d "$HOME/bin" ]; then
+    PATH="$HOME/bin:$PATH"
+fi
+[ -d /opt/bin ] && PATH=/opt/bin:$PATH
+add_path() {
+    PATH="$1:$PATH"
+}
+export PATH=$PATH:/usr/local/bin # trailing comment with if
+"#;
+        let flags = conditional_lines(content, &ShellType::Bash);
+        assert_eq!(
+            flags,
+            vec![false, true, true, true, true, true, true, true, false]
+        );
+    }
+
+    #[test]
+    fn test_fish_conditionals() {
+        let content = "set -gx PATH /usr/bin\nif test -d ~/bin\n    fish_add_path ~/bin\nend\ntest -d /opt; and fish_add_path /opt/bin\nfish_add_path /usr/local/bin\n";
+        let flags = conditional_lines(content, &ShellType::Fish);
+        assert_eq!(flags, vec![false, true, true, true, true, false]);
+    }
+
+    #[test]
+    fn test_tcsh_conditionals() {
+        let content = "setenv PATH /usr/bin\nif ( -d ~/bin ) then\n    set path = ($path ~/bin)\nendif\nif ( -d /opt ) setenv PATH /opt:$PATH\nset path = (/usr/bin)\n";
+        let flags = conditional_lines(content, &ShellType::Tcsh);
+        assert_eq!(flags, vec![false, true, true, true, true, false]);
+    }
+}
@@ -0,0 +1,87 @@
+//! Joining of backslash-continued lines in shell configs.
+//!
+//! A PATH declaration may be split across several lines, each ending in a
+//! backslash. Handlers parse and replace such statements as one logical
+//! line, so a rewrite never leaves dangling fragments of the old value.
+
+/// A statement made of one or more physical lines
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogicalLine {
+    /// Zero-based index of the first physical line
+    pub start: usize,
+    /// Zero-based index of the last physical line
+    pub end: usize,
+    /// The statement with backslash-newlines removed
+    pub text: String,
+}
+
+impl LogicalLine {
+    /// Returns the one-based numbers of the physical lines in this statement
+    pub fn line_numbers(&self) -> std::ops::RangeInclusive<usize> {
+        self.start + 1..=self.end + 1
+    }
+}
+
+/// Returns whether a line ends with an unescaped backslash
+fn continues(line: &str) -> bool {
+    let trailing = line.chars().rev().take_while(|&c| c == '\\').count();
+    trailing % 2 == 1
+}
+
+/// Splits content into logical lines, joining backslash continuations.
+///
+/// As in the shell, a backslash at the end of a comment doesn't continue it.
+pub fn logical_lines(content: &str) -> Vec<LogicalLine> {
+    let mut result: Vec<LogicalLine> = Vec::new();
+    let mut pending: Option<LogicalLine> = None;
+
+    for (idx, line) in content.lines().enumerate() {
+        let mut current = match pending.take() {
+            Some(mut statement) => {
+                statement.end = idx;
+                statement.text.push_str(line);
+                statement
+            }
+            None => LogicalLine {
+                start: idx,
+                end: idx,
+                text: line.to_string(),
+            },
+        };
+
+        if !current.text.trim_start().starts_with('#') && continues(&current.text) {
+            current.text.pop();
+            pending = Some(current);
+        } else {
+            result.push(current);
+        }
+    }
+
+    result.extend(pending);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_continued_export_joined() {
+        let content =
+            "export PATH=/usr/bin:\\\n/usr/local/bin:\\\n$PATH\necho done\n# note \\\nexport X=1\n";
+        let lines = logical_lines(content);
+
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[0].text, "export PATH=/usr/bin:/usr/local/bin:$PATH");
+        assert_eq!(lines[0].line_numbers(), 1..=3);
+        assert_eq!(lines[1].text, "echo done");
+        assert_eq!(lines[2].text, "# note \\");
+        assert_eq!(lines[3].start, 5);
+    }
+
+    #[test]
+    fn test_escaped_backslash_does_not_continue() {
+        let lines = logical_lines("echo a\\\\\necho b\n");
+        assert_eq!(lines.len(), 2);
+    }
+}
@@ -0,0 +1,136 @@
+//! Shell code that applies a PATH to the running shell.
+//!
+//! pathmaster can't change its parent shell's environment, so a shell
+//! function wrapping it passes `--emit <FILE>` and sources the file
+//! afterwards:
+//!
+//! ```sh
+//! pm() { f=$(mktemp); command pathmaster --emit "$f" "$@"; . "$f"; rm -f "$f"; }
+//! ```
+//!
+//! The file also runs the shell's rehash command (`hash -r` in bash,
+//! `rehash` in zsh and tcsh), so commands that moved or appeared are found
+//! at their new location right away.
+
+use super::factory;
+use super::handlers::is_comment;
+use super::spelling::Spellings;
+use super::types::ShellType;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Formats the statement that sets PATH to `entries` in the given shell.
+///
+/// Bytes that aren't valid UTF-8 are written as `printf` substitutions, so
+/// the shell sets exactly the same entries.
+pub fn export_statement(shell: Option<ShellType>, entries: &[PathBuf]) -> String {
+    let handler = factory::get_shell_handler(shell);
+
+    handler
+        .format_entries(&Spellings::default().spell_all(entries))
+        .lines()
+        .filter(|line| !line.trim().is_empty() && !is_comment(line))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Returns the rehash command for the given shell, detecting it when `None`
+pub fn rehash_command(shell: Option<ShellType>) -> Option<&'static str> {
+    shell
+        .unwrap_or_else(|| factory::detect_shell().0)
+        .rehash_command()
+}
+
+/// Formats the statements that set PATH to `entries` and rehash
+pub fn apply_statements(shell: Option<ShellType>, entries: &[PathBuf]) -> String {
+    let export = export_statement(shell, entries);
+    match rehash_command(shell) {
+        Some(rehash) => format!("{}\n{}", export, rehash),
+        None => export,
+    }
+}
+
+/// Writes the statements applying `entries` to `file`
+pub fn write_emit_file(
+    file: &Path,
+    shell: Option<ShellType>,
+    entries: &[PathBuf],
+) -> io::Result<()> {
+    fs::write(file, format!("{}\n", apply_statements(shell, entries)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_export_statement() {
+        let entries = vec![PathBuf::from("/opt/My Tools"), PathBuf::from("/usr/bin")];
+        assert_eq!(
+            export_statement(Some(ShellType::Bash), &entries),
+            "export PATH=\"/opt/My Tools:/usr/bin\""
+        );
+
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join("env.fish");
+        write_emit_file(&file, Some(ShellType::Fish), &entries).unwrap();
+        let content = fs::read_to_string(&file).unwrap();
+        assert!(content.contains("'/opt/My Tools'"));
+        assert!(!content.contains('#'));
+        assert!(!content.contains("rehash"));
+
+        let file = temp_dir.path().join("env.zsh");
+        write_emit_file(&file, Some(ShellType::Zsh), &entries).unwrap();
+        let content = fs::read_to_string(&file).unwrap();
+        assert!(content.ends_with("\nrehash\n"));
+        let file = temp_dir.path().join("env.csh");
+        write_emit_file(&file, Some(ShellType::Tcsh), &entries).unwrap();
+        let content = fs::read_to_string(&file).unwrap();
+        assert!(content.starts_with("set path = ("));
+        assert!(content.ends_with("\nrehash\n"));
+        assert_eq!(
+            apply_statements(Some(ShellType::Bash), &entries),
+            "export PATH=\"/opt/My Tools:/usr/bin\"\nhash -r"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_sourced_emit_file_applies_path() {
+        use std::os::unix::fs::PermissionsExt;
+        use std::process::Command;
+
+        let temp_dir = TempDir::new().unwrap();
+        let bin = temp_dir.path().join("bin");
+        fs::create_dir(&bin).unwrap();
+        let tool = bin.join("pm-test-tool");
+        fs::write(&tool, "#!/bin/sh\necho found\n").unwrap();
+        fs::set_permissions(&tool, fs::Permissions::from_mode(0o755)).unwrap();
+        let file = temp_dir.path().join("env.bash");
+        write_emit_file(
+            &file,
+            Some(ShellType::Bash),
+            &[
+                bin.clone(),
+                PathBuf::from("/usr/bin"),
+                PathBuf::from("/bin"),
+            ],
+        )
+        .unwrap();
+
+        let output = Command::new("bash")
+            .arg("-c")
+            .arg(". \"$1\"; echo \"$PATH\"; pm-test-tool")
+            .arg("bash")
+            .arg(&file)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{:?}", output);
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout),
+            format!("{}:/usr/bin:/bin\nfound\n", bin.display())
+        );
+    }
+}
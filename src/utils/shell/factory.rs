@@ -2,17 +2,262 @@ use super::handlers::ShellHandler;
 use super::handlers::{
     BashHandler, FishHandler, GenericHandler, KshHandler, TcshHandler, ZshHandler,
 };
+use super::registry;
+use super::types::ShellType;
+use crate::msg;
+use crate::utils::verbose;
 use std::env;
+use std::fmt;
+#[cfg(unix)]
+use std::fs;
+
+/// How many ancestor processes are checked for a running shell
+#[cfg(unix)]
+const MAX_ANCESTORS: usize = 8;
+
+/// Where the shell type was determined from
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DetectionSource {
+    /// Given with `--shell` or the `shell` config setting
+    Explicit,
+    /// Name of a shell process that started pathmaster
+    ParentProcess,
+    /// The `$SHELL` environment variable
+    ShellVar,
+}
+
+impl fmt::Display for DetectionSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DetectionSource::Explicit => write!(f, "--shell option or config"),
+            DetectionSource::ParentProcess => write!(f, "parent process"),
+            DetectionSource::ShellVar => write!(f, "$SHELL"),
+        }
+    }
+}
+
+/// Returns the handler for a shell, detecting the shell when `shell` is `None`.
+///
+/// A detected shell without a built-in handler is looked up among the
+/// custom shells defined in the config before falling back to the
+/// generic handler. In verbose mode the chosen shell and how it was
+/// determined are printed.
+pub fn get_shell_handler(shell: Option<ShellType>) -> Box<dyn ShellHandler> {
+    let (shell, source) = match shell {
+        Some(shell) => (shell, DetectionSource::Explicit),
+        None => {
+            if let Some((handler, source)) = detect_custom_shell() {
+                if verbose::is_verbose() {
+                    println!(
+                        "{}",
+                        msg!(
+                            "shell-using-custom",
+                            shell = handler.name(),
+                            source = source.to_string()
+                        )
+                    );
+                }
+                return handler;
+            }
+            detect_shell()
+        }
+    };
+
+    if verbose::is_verbose() {
+        println!(
+            "{}",
+            msg!(
+                "shell-using",
+                shell = shell.to_string(),
+                source = source.to_string()
+            )
+        );
+    }
+
+    handler_for(shell)
+}
+
+/// Returns handlers for every supported or custom shell whose config file exists.
+///
+/// The generic handler is left out, since `~/.profile` exists on most
+/// systems regardless of the shells in use.
+pub fn configured_shell_handlers() -> Vec<Box<dyn ShellHandler>> {
+    [
+        ShellType::Bash,
+        ShellType::Zsh,
+        ShellType::Fish,
+        ShellType::Tcsh,
+        ShellType::Ksh,
+    ]
+    .into_iter()
+    .map(handler_for)
+    .chain(registry::all_handlers())
+    .filter(|handler| handler.has_config())
+    .collect()
+}
+
+/// Creates the handler for a shell type
+fn handler_for(shell: ShellType) -> Box<dyn ShellHandler> {
+    match shell {
+        ShellType::Zsh => Box::new(ZshHandler::new()),
+        ShellType::Bash => Box::new(BashHandler::new()),
+        ShellType::Fish => Box::new(FishHandler::new()),
+        ShellType::Tcsh => Box::new(TcshHandler::new()),
+        ShellType::Ksh => Box::new(KshHandler::new()),
+        ShellType::Generic => Box::new(GenericHandler::new()),
+    }
+}
+
+/// Detects the shell pathmaster is running under.
+///
+/// `$SHELL` names the login shell, which isn't the running one when, say,
+/// fish is started from bash. Ancestor processes are checked first for a
+/// known interactive shell, so wrappers such as `sudo` or a `sh` script in
+/// between are skipped. `$SHELL` is used when no shell process is found,
+/// e.g. under cron or on systems without `/proc`.
+pub fn detect_shell() -> (ShellType, DetectionSource) {
+    match process_shell() {
+        Some(shell) => (shell, DetectionSource::ParentProcess),
+        None => (
+            shell_from_name(&env::var("SHELL").unwrap_or_default()),
+            DetectionSource::ShellVar,
+        ),
+    }
+}
+
+/// Detects a running custom shell, i.e. one defined in the config.
+///
+/// Ancestor processes are checked up to the first built-in shell, which
+/// takes precedence when it's closer. `$SHELL` is only used when no
+/// ancestor is a known shell.
+fn detect_custom_shell() -> Option<(Box<dyn ShellHandler>, DetectionSource)> {
+    let ancestors = ancestor_names();
+    for name in &ancestors {
+        let name = name.trim_start_matches('-');
+        if shell_from_process_name(name).is_some() {
+            return None;
+        }
+        if let Some(handler) = registry::handler_for_name(name) {
+            return Some((handler, DetectionSource::ParentProcess));
+        }
+    }
 
-pub fn get_shell_handler() -> Box<dyn ShellHandler> {
     let shell = env::var("SHELL").unwrap_or_default();
+    let name = shell.rsplit('/').next().unwrap_or_default();
+    if name.is_empty() || shell_from_name(&shell) != ShellType::Generic {
+        return None;
+    }
+    registry::handler_for_name(name).map(|handler| (handler, DetectionSource::ShellVar))
+}
+
+/// Walks up the process tree through `/proc` looking for a known shell
+fn process_shell() -> Option<ShellType> {
+    ancestor_names()
+        .iter()
+        .find_map(|name| shell_from_process_name(name))
+}
+
+/// Returns the command names of ancestor processes, nearest first
+#[cfg(unix)]
+fn ancestor_names() -> Vec<String> {
+    let mut names = Vec::new();
+    let mut pid = std::os::unix::process::parent_id();
+    for _ in 0..MAX_ANCESTORS {
+        if pid <= 1 {
+            break;
+        }
+        let Some((name, parent)) = fs::read_to_string(format!("/proc/{}/stat", pid))
+            .ok()
+            .and_then(|stat| parse_stat(&stat).map(|(name, parent)| (name.to_string(), parent)))
+        else {
+            break;
+        };
+        names.push(name);
+        pid = parent;
+    }
+    names
+}
+
+/// Returns no ancestors, since there is no `/proc` to read them from;
+/// detection falls back to `$SHELL`
+#[cfg(not(unix))]
+fn ancestor_names() -> Vec<String> {
+    Vec::new()
+}
+
+/// Extracts the command name and parent pid from a `/proc/<pid>/stat` line.
+///
+/// The name is enclosed in parentheses and may itself contain spaces or
+/// parentheses, so the fields are read after the last `)`.
+#[cfg(unix)]
+fn parse_stat(stat: &str) -> Option<(&str, u32)> {
+    let open = stat.find('(')?;
+    let close = stat.rfind(')')?;
+    let name = stat.get(open + 1..close)?;
+    let parent = stat[close + 1..].split_whitespace().nth(1)?.parse().ok()?;
+    Some((name, parent))
+}
+
+/// Maps a process name to an interactive shell.
+///
+/// Plain `sh` and `dash` are ignored since they usually run scripts rather
+/// than the user's session.
+fn shell_from_process_name(name: &str) -> Option<ShellType> {
+    match name.trim_start_matches('-') {
+        "zsh" => Some(ShellType::Zsh),
+        "bash" => Some(ShellType::Bash),
+        "fish" => Some(ShellType::Fish),
+        "tcsh" | "csh" => Some(ShellType::Tcsh),
+        "ksh" | "ksh93" | "mksh" | "pdksh" => Some(ShellType::Ksh),
+        _ => None,
+    }
+}
+
+/// Maps a shell name or path such as `/usr/bin/zsh` to a shell type
+fn shell_from_name(shell: &str) -> ShellType {
+    match shell {
+        s if s.contains("zsh") => ShellType::Zsh,
+        s if s.contains("bash") => ShellType::Bash,
+        s if s.contains("fish") => ShellType::Fish,
+        s if s.contains("tcsh") || s.contains("csh") => ShellType::Tcsh,
+        s if s.contains("ksh") => ShellType::Ksh,
+        _ => ShellType::Generic,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_explicit_shell_overrides_detection() {
+        assert_eq!(
+            get_shell_handler(Some(ShellType::Fish)).get_shell_type(),
+            ShellType::Fish
+        );
+        assert_eq!(shell_from_name("/usr/bin/zsh"), ShellType::Zsh);
+        assert_eq!(shell_from_name("/bin/csh"), ShellType::Tcsh);
+        assert_eq!(shell_from_name(""), ShellType::Generic);
+        assert_eq!("csh".parse::<ShellType>().unwrap(), ShellType::Tcsh);
+        assert!("pwsh".parse::<ShellType>().is_err());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_process_detection() {
+        assert_eq!(
+            parse_stat("4242 (fish) S 1000 4242 4242 0 -1"),
+            Some(("fish", 1000))
+        );
+        assert_eq!(
+            parse_stat("77 (tmux: server (1)) S 1 77 77"),
+            Some(("tmux: server (1)", 1))
+        );
+        assert_eq!(parse_stat("garbage"), None);
 
-    match shell.as_str() {
-        s if s.contains("zsh") => Box::new(ZshHandler::new()),
-        s if s.contains("bash") => Box::new(BashHandler::new()),
-        s if s.contains("fish") => Box::new(FishHandler::new()),
-        s if s.contains("tcsh") || s.contains("csh") => Box::new(TcshHandler::new()),
-        s if s.contains("ksh") => Box::new(KshHandler::new()),
-        _ => Box::new(GenericHandler::new()),
+        assert_eq!(shell_from_process_name("-bash"), Some(ShellType::Bash));
+        assert_eq!(shell_from_process_name("mksh"), Some(ShellType::Ksh));
+        assert_eq!(shell_from_process_name("sh"), None);
+        assert_eq!(shell_from_process_name("sudo"), None);
     }
 }
@@ -0,0 +1,232 @@
+//! PATH for graphical desktop sessions.
+//!
+//! Applications started from a desktop launcher don't read shell rc files,
+//! so editors and IDEs miss PATH changes made there. With `gui = true`
+//! (or `--gui`) the same PATH is also written where the session picks it up:
+//!
+//! - Linux: `$XDG_CONFIG_HOME/environment.d/60-pathmaster.conf`, read by
+//!   `systemd --user` in GNOME, KDE and most Wayland sessions. If
+//!   `~/.xprofile` or `~/.xsessionrc` exists, it gets a line sourcing that
+//!   file, for X display managers that don't use systemd's environment.
+//! - macOS: `launchctl setenv PATH` for applications launched from now on,
+//!   plus a launch agent that sets it again at every login.
+
+use super::handlers::{backup_file, write_with_rollback};
+use super::locations;
+use super::quoting::escape_double_quoted;
+use crate::msg;
+use crate::utils::root;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Name of the environment.d file pathmaster owns
+pub const ENVIRONMENT_D_FILE: &str = "60-pathmaster.conf";
+
+/// Label of the macOS launch agent that sets PATH at login
+pub const LAUNCH_AGENT_LABEL: &str = "pathmaster.path";
+
+/// Writes PATH for graphical sessions using the platform's mechanism.
+///
+/// environment.d files and launch agents must be valid UTF-8, so entries
+/// that aren't are left out with a warning.
+///
+/// # Returns
+/// * `Ok(Vec<PathBuf>)` - Files that were written
+/// * `Err(io::Error)` - If a file couldn't be written or `launchctl` failed
+pub fn update_session_path(entries: &[PathBuf]) -> io::Result<Vec<PathBuf>> {
+    let value = entries
+        .iter()
+        .filter_map(|entry| {
+            let text = entry.to_str();
+            if text.is_none() {
+                eprintln!("{}", msg!("gui-not-utf8", path = entry));
+            }
+            text
+        })
+        .collect::<Vec<_>>()
+        .join(":");
+
+    if cfg!(target_os = "macos") {
+        update_launchd(&value)
+    } else {
+        update_environment_d(&value)
+    }
+}
+
+/// Returns the files [`update_session_path`] may write
+pub fn session_files() -> Vec<PathBuf> {
+    let home = locations::home_dir();
+    if cfg!(target_os = "macos") {
+        vec![launch_agent_path()]
+    } else {
+        vec![
+            environment_d_path(),
+            home.join(".xprofile"),
+            home.join(".xsessionrc"),
+        ]
+    }
+}
+
+/// Returns the environment.d file pathmaster writes PATH to
+pub fn environment_d_path() -> PathBuf {
+    locations::xdg_config_home()
+        .join("environment.d")
+        .join(ENVIRONMENT_D_FILE)
+}
+
+fn update_environment_d(value: &str) -> io::Result<Vec<PathBuf>> {
+    let file = environment_d_path();
+    if let Some(parent) = file.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&file, environment_d_content(value))?;
+    println!("{}", msg!("gui-session-written", file = &file));
+
+    let mut modified = vec![file.clone()];
+    let home = locations::home_dir();
+    for session_file in [home.join(".xprofile"), home.join(".xsessionrc")] {
+        if session_file.exists() && add_source_line(&session_file, &file)? {
+            println!("{}", msg!("gui-source-line", file = &session_file));
+            modified.push(session_file);
+        }
+    }
+
+    println!("{}", msg!("gui-log-out"));
+    Ok(modified)
+}
+
+/// Formats the environment.d file.
+///
+/// systemd unescapes `\`, `"`, `` ` `` and `$` inside double quotes like a
+/// POSIX shell does, so the file can also be sourced by `sh`.
+fn environment_d_content(value: &str) -> String {
+    format!(
+        "# Managed by pathmaster. Changes made here will be overwritten.\nPATH=\"{}\"\n",
+        escape_double_quoted(value)
+    )
+}
+
+/// Makes `session_file` source `file`, backing it up first.
+///
+/// # Returns
+/// * `Ok(true)` if the line was added, `Ok(false)` if it was already there
+fn add_source_line(session_file: &Path, file: &Path) -> io::Result<bool> {
+    let source_line = format!(
+        "[ -f \"{0}\" ] && . \"{0}\"",
+        escape_double_quoted(&root::unrooted(file).to_string_lossy())
+    );
+    let mut content = fs::read_to_string(session_file)?;
+    if content.lines().any(|line| line.trim() == source_line) {
+        return Ok(false);
+    }
+
+    let backup_path = backup_file(session_file)?;
+    println!("{}", msg!("gui-session-backup", file = &backup_path));
+    if !content.is_empty() && !content.ends_with('\n') {
+        content.push('\n');
+    }
+    content.push_str(&format!("\n# Added by pathmaster\n{}\n", source_line));
+    write_with_rollback(session_file, &content, &backup_path)?;
+    Ok(true)
+}
+
+/// Returns the launch agent pathmaster writes on macOS
+fn launch_agent_path() -> PathBuf {
+    locations::home_dir()
+        .join("Library/LaunchAgents")
+        .join(format!("{}.plist", LAUNCH_AGENT_LABEL))
+}
+
+fn update_launchd(value: &str) -> io::Result<Vec<PathBuf>> {
+    let agent = launch_agent_path();
+    if let Some(parent) = agent.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&agent, launch_agent_plist(value))?;
+    println!("{}", msg!("gui-launch-agent", file = &agent));
+
+    let status = Command::new("launchctl")
+        .args(["setenv", "PATH", value])
+        .status()?;
+    if !status.success() {
+        return Err(io::Error::other(format!(
+            "launchctl setenv PATH failed ({})",
+            status
+        )));
+    }
+    println!("{}", msg!("gui-launchctl"));
+    Ok(vec![agent])
+}
+
+/// Formats a launch agent that runs `launchctl setenv PATH <value>` at login
+fn launch_agent_plist(value: &str) -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<!-- Managed by pathmaster. Changes made here will be overwritten. -->
+<plist version="1.0">
+<dict>
+  <key>Label</key>
+  <string>{}</string>
+  <key>ProgramArguments</key>
+  <array>
+    <string>/bin/launchctl</string>
+    <string>setenv</string>
+    <string>PATH</string>
+    <string>{}</string>
+  </array>
+  <key>RunAtLoad</key>
+  <true/>
+</dict>
+</plist>
+"#,
+        LAUNCH_AGENT_LABEL,
+        escape_xml(value)
+    )
+}
+
+/// Escapes text for an XML element
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_environment_d_content() {
+        assert_eq!(
+            environment_d_content("/opt/$dir:/usr/bin"),
+            "# Managed by pathmaster. Changes made here will be overwritten.\n\
+             PATH=\"/opt/\\$dir:/usr/bin\"\n"
+        );
+    }
+
+    #[test]
+    fn test_add_source_line_once() {
+        let temp_dir = TempDir::new().unwrap();
+        let xprofile = temp_dir.path().join(".xprofile");
+        let conf = temp_dir.path().join("environment.d/60-pathmaster.conf");
+        fs::write(&xprofile, "xset r rate 200 30").unwrap();
+
+        assert!(add_source_line(&xprofile, &conf).unwrap());
+        assert!(!add_source_line(&xprofile, &conf).unwrap());
+
+        let content = fs::read_to_string(&xprofile).unwrap();
+        assert!(content.starts_with("xset r rate 200 30\n"));
+        assert_eq!(content.matches(". \"").count(), 1);
+    }
+
+    #[test]
+    fn test_launch_agent_plist() {
+        let plist = launch_agent_plist("/opt/R&D/bin:/usr/bin");
+        assert!(plist.contains("<string>/opt/R&amp;D/bin:/usr/bin</string>"));
+        assert!(plist.contains("<string>pathmaster.path</string>"));
+    }
+}
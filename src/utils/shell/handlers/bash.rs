@@ -1,31 +1,41 @@
-use super::ShellHandler;
-use crate::utils::shell::types::{ModificationType, PathModification, ShellType};
-use chrono::Local;
-use dirs_next;
+use super::{is_comment, ShellHandler};
+use crate::utils::shell::continuation;
+use crate::utils::shell::locations;
+use crate::utils::shell::posix::{self, EntryCollector};
+use crate::utils::shell::types::{
+    ConfigEntry, EntryComment, ModificationType, PathModification, ShellType,
+};
+use lazy_static::lazy_static;
 use regex::Regex;
 use std::path::PathBuf;
 
+lazy_static! {
+    static ref ADDITION_REGEX: Regex = Regex::new(r"PATH=.*:([^:]+)\s*$").unwrap();
+    static ref EXPORT_REGEX: Regex =
+        Regex::new(&format!(r"export\s+PATH={}", posix::VALUE_PATTERN)).unwrap();
+    static ref MODIFICATION_REGEX: Regex = Regex::new(r"(export\s+PATH=|PATH=\$PATH:)").unwrap();
+}
+
 pub struct BashHandler {
     config_path: PathBuf,
 }
 
 impl BashHandler {
     pub fn new() -> Self {
-        let home_dir = dirs_next::home_dir().unwrap_or_else(|| PathBuf::from("/"));
         Self {
-            config_path: home_dir.join(".bashrc"),
+            config_path: locations::home_dir().join(".bashrc"),
         }
     }
 
-    fn parse_path_additions(&self, line: &str) -> Option<PathBuf> {
-        let addition_regex = Regex::new(r"PATH=.*:([^:]+)\s*$").unwrap();
-        if let Some(cap) = addition_regex.captures(line) {
-            if let Some(path) = cap.get(1) {
-                let expanded = shellexpand::tilde(path.as_str());
-                return Some(PathBuf::from(expanded.to_string()));
-            }
-        }
-        None
+    fn parse_path_additions(&self, line: &str) -> Option<String> {
+        ADDITION_REGEX
+            .captures(line)
+            .and_then(|cap| cap.get(1))
+            .map(|path| {
+                path.as_str()
+                    .trim_matches(|c| c == '"' || c == '\'')
+                    .to_string()
+            })
     }
 }
 
@@ -38,83 +48,152 @@ impl ShellHandler for BashHandler {
         self.config_path.clone()
     }
 
-    fn parse_path_entries(&self, content: &str) -> Vec<PathBuf> {
-        let mut entries = Vec::new();
-        let export_regex = Regex::new(r#"export\s+PATH=["']?([^"']+)["']?"#).unwrap();
+    fn parse_raw_entries(&self, content: &str) -> Vec<String> {
+        let mut entries = EntryCollector::default();
 
-        for line in content.lines() {
+        for line in continuation::logical_lines(content)
+            .iter()
+            .map(|line| line.text.as_str())
+            .filter(|line| !is_comment(line))
+        {
             let line = line.trim();
 
             // Handle export PATH=...
-            if let Some(cap) = export_regex.captures(line) {
-                if let Some(paths) = cap.get(1) {
-                    for path in paths.as_str().split(':') {
-                        let expanded = shellexpand::tilde(path);
-                        entries.push(PathBuf::from(expanded.to_string()));
-                    }
+            if let Some(cap) = EXPORT_REGEX.captures(line) {
+                if let Some(paths) = posix::captured_value(&cap) {
+                    entries.extend(paths.split(':').map(String::from));
                 }
             }
+            // Handle PATH=... lines, as written in the incremental format
+            else if let Some(path_line) = posix::parse_line(line) {
+                entries.add_line(path_line);
+            }
             // Handle PATH additions
             else if line.contains("PATH=$PATH:") || line.contains("PATH=\"$PATH:") {
                 if let Some(path) = self.parse_path_additions(line) {
-                    entries.push(path);
+                    entries.extend([path]);
                 }
             }
         }
 
-        entries
+        entries.into_entries()
     }
 
-    fn format_path_export(&self, entries: &[PathBuf]) -> String {
-        let paths = entries
-            .iter()
-            .map(|p| p.to_string_lossy().to_string())
-            .collect::<Vec<_>>()
-            .join(":");
-
-        format!(
-            "\n# Updated by pathmaster on {}\nexport PATH=\"{}\"\n",
-            Local::now().format("%Y-%m-%d %H:%M:%S"),
-            paths
-        )
+    fn format_entries(&self, entries: &[ConfigEntry]) -> String {
+        posix::format_path(entries)
+    }
+
+    fn parse_entry_comments(&self, content: &str) -> Vec<EntryComment> {
+        posix::entry_comments(content)
     }
 
     fn detect_path_modifications(&self, content: &str) -> Vec<PathModification> {
         let mut modifications = Vec::new();
-        let path_regex = Regex::new(r"(export\s+PATH=|PATH=\$PATH:)").unwrap();
 
-        for (idx, line) in content.lines().enumerate() {
-            if path_regex.is_match(line) {
+        // A continued statement is replaced as a whole, so flag each of its lines
+        for statement in continuation::logical_lines(content) {
+            let line = statement.text.as_str();
+            if !is_comment(line)
+                && (MODIFICATION_REGEX.is_match(line) || posix::parse_line(line).is_some())
+            {
                 let mod_type = if line.contains("PATH=$PATH:") {
                     ModificationType::Addition
                 } else {
                     ModificationType::Assignment
                 };
 
-                modifications.push(PathModification {
-                    line_number: idx + 1,
-                    content: line.to_string(),
-                    modification_type: mod_type,
-                });
+                for (line_number, physical) in statement
+                    .line_numbers()
+                    .zip(content.lines().skip(statement.start))
+                {
+                    modifications.push(PathModification {
+                        line_number,
+                        content: physical.to_string(),
+                        modification_type: mod_type.clone(),
+                    });
+                }
             }
         }
 
         modifications
     }
+}
 
-    fn update_path_in_config(&self, content: &str, entries: &[PathBuf]) -> String {
-        let modifications = self.detect_path_modifications(content);
-
-        let mut updated_content = content
-            .lines()
-            .enumerate()
-            .filter(|(idx, _)| !modifications.iter().any(|m| m.line_number == idx + 1))
-            .map(|(_, line)| line)
-            .collect::<Vec<_>>()
-            .join("\n");
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bash_later_declarations_removed_not_commented() {
+        let handler = BashHandler::new();
+        let content = "export PATH=/usr/bin:/bin\n\
+                       alias ll='ls -l'\n\
+                       export PATH=$PATH:/pm-test/old\n\
+                       # DISABLED by pathmaster: export PATH=/pm-test/legacy\n\
+                       [ -d /pm-test/opt ] && PATH=$PATH:/pm-test/opt\n";
+        let entries = vec![PathBuf::from("/usr/bin"), PathBuf::from("/bin")];
+
+        let updated = handler.update_path_in_config(content, &entries);
+        assert!(!updated.contains("/pm-test/old"));
+        assert_eq!(updated.matches("DISABLED").count(), 1);
+        assert!(updated.contains("alias ll='ls -l'\n"));
+        assert!(updated.contains("[ -d /pm-test/opt ] && PATH=$PATH:/pm-test/opt\n"));
+
+        // Later runs only rewrite the block, so the same entries leave the file as it was
+        let again = handler.update_path_in_config(&updated, &entries);
+        assert_eq!(again, updated);
+        assert_eq!(
+            again
+                .lines()
+                .filter(|line| line.starts_with("export PATH="))
+                .count(),
+            1
+        );
+    }
 
-        updated_content.push_str(&self.format_path_export(entries));
+    #[test]
+    fn test_bash_continued_conditionals_kept_whole() {
+        let handler = BashHandler::new();
+        let content = "export PATH=/usr/bin:/bin\n\
+                       [ -d /pm-test/y ] && \\\n\
+                       \x20 export PATH=/pm-test/y:$PATH\n\
+                       export PATH=$PATH:/pm-test/a:\\\n\
+                       /pm-test/b \\\n\
+                       \x20 && echo x\n\
+                       export PATH=$PATH:/pm-test/c:\\\n\
+                       /pm-test/d\n";
+        let entries = vec![PathBuf::from("/usr/bin"), PathBuf::from("/bin")];
+
+        let updated = handler.update_path_in_config(content, &entries);
+        assert!(updated.contains("[ -d /pm-test/y ] && \\\n  export PATH=/pm-test/y:$PATH\n"));
+        assert!(updated.contains("/pm-test/b \\\n  && echo x\n"));
+        assert!(!updated.contains("/pm-test/c"));
+        assert!(!updated.contains("/pm-test/d"));
+
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join(".bashrc");
+        std::fs::write(&file, &updated).unwrap();
+        let status = std::process::Command::new("bash")
+            .arg("-n")
+            .arg(&file)
+            .status()
+            .unwrap();
+        assert!(status.success(), "{}", updated);
+    }
 
-        updated_content
+    #[test]
+    fn test_bash_managed_entries_skip_guarded_declarations() {
+        let handler = BashHandler::new();
+        let content = "export PATH=/usr/bin:/bin\n\
+                       [ -d /pm-test/opt ] && PATH=$PATH:/pm-test/opt\n\
+                       export PATH=$PATH:/pm-test/mine\n";
+        assert_eq!(
+            handler.managed_entries(content),
+            vec![
+                PathBuf::from("/usr/bin"),
+                PathBuf::from("/bin"),
+                PathBuf::from("/pm-test/mine")
+            ]
+        );
     }
 }
@@ -1,9 +1,51 @@
-use super::ShellHandler;
-use crate::utils::shell::types::{ModificationType, PathModification, ShellType};
-use chrono::Local;
-use dirs_next;
+use super::{is_comment, ShellHandler};
+use crate::utils::shell::block::BLOCK_HEADER;
+use crate::utils::shell::locations;
+use crate::utils::shell::quoting;
+use crate::utils::shell::spelling;
+use crate::utils::shell::types::{
+    ConfigEntry, EntryComment, ModificationType, PathModification, ShellType,
+};
+use lazy_static::lazy_static;
 use regex::Regex;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+lazy_static! {
+    static ref ADD_PATH_REGEX: Regex = Regex::new(r"fish_add_path\s+(.+)$").unwrap();
+    static ref SET_PATH_REGEX: Regex =
+        Regex::new(r"^set\s+(?:-[a-zA-Z]+\s+)*PATH\s+(.+)$").unwrap();
+    static ref MODIFICATION_REGEX: Regex =
+        Regex::new(r"(fish_add_path|\bset\s+(?:-[a-zA-Z]+\s+)*PATH\b)").unwrap();
+    /// `set -q PATH` only tests whether PATH is set
+    static ref QUERY_REGEX: Regex =
+        Regex::new(r"^set\s+(?:-[a-zA-Z]+\s+)*(?:-[a-zA-Z]*q|--query\b)").unwrap();
+    /// Fish's `and`, `or` and `not` run the statement after them
+    static ref COMBINER_REGEX: Regex = Regex::new(r"^(?:(?:and|or|not)\s+)+").unwrap();
+}
+
+/// Splits a line into its statements, without the `and`/`or`/`not` in
+/// front of them
+fn statements(code: &str) -> impl Iterator<Item = &str> {
+    quoting::split_statements(code)
+        .into_iter()
+        .map(|statement| match COMBINER_REGEX.find(statement) {
+            Some(combiner) => &statement[combiner.end()..],
+            None => statement,
+        })
+}
+
+/// Returns whether a flag is the long option or bundles the short one,
+/// e.g. `-gxa` for `a`
+fn has_flag(flags: &[String], long: &str, short: char) -> bool {
+    flags
+        .iter()
+        .any(|flag| flag == long || (!flag.starts_with("--") && flag.contains(short)))
+}
+
+/// Whether a statement changes PATH, rather than only querying it
+fn modifies_path(statement: &str) -> bool {
+    MODIFICATION_REGEX.is_match(statement) && !QUERY_REGEX.is_match(statement)
+}
 
 pub struct FishHandler {
     config_path: PathBuf,
@@ -11,10 +53,68 @@ pub struct FishHandler {
 
 impl FishHandler {
     pub fn new() -> Self {
-        let home_dir = dirs_next::home_dir().unwrap_or_else(|| PathBuf::from("/"));
         Self {
-            config_path: home_dir.join(".config/fish/config.fish"),
+            config_path: locations::fish_config_dir().join("config.fish"),
+        }
+    }
+
+    /// Adds the entries a line declares to `path`, and returns them along
+    /// with the comment after them.
+    ///
+    /// `path` holds the entries in the order they end up in PATH, with a
+    /// `$PATH` reference where the existing PATH goes: `fish_add_path` and
+    /// `set -p` put theirs in front, `set -a` and `fish_add_path -a` after.
+    fn parse_line<'a>(
+        &self,
+        line: &'a str,
+        path: &mut Vec<String>,
+    ) -> (Vec<String>, Option<&'a str>) {
+        if is_comment(line) {
+            return (Vec::new(), None);
+        }
+        let (code, comment) = quoting::split_comment(line.trim());
+
+        let mut entries = Vec::new();
+        for statement in statements(code) {
+            let (words, flags): (Vec<String>, Vec<String>) =
+                if let Some(cap) = SET_PATH_REGEX.captures(statement) {
+                    let flags: Vec<String> = statement[..cap.get(1).map_or(0, |m| m.start())]
+                        .split_whitespace()
+                        .filter(|word| word.starts_with('-'))
+                        .map(String::from)
+                        .collect();
+                    let words = quoting::split_words(&cap[1]);
+                    if !has_flag(&flags, "--append", 'a') && !has_flag(&flags, "--prepend", 'p') {
+                        // Handle set -gx PATH ..., which lists $PATH itself
+                        path.extend(words.iter().cloned());
+                        entries.extend(words);
+                        continue;
+                    }
+                    (words, flags)
+                } else if let Some(cap) = ADD_PATH_REGEX.captures(statement) {
+                    let (flags, words) = quoting::split_words(&cap[1])
+                        .into_iter()
+                        .partition(|arg| arg.starts_with('-'));
+                    (words, flags)
+                } else {
+                    continue;
+                };
+
+            if path.is_empty() {
+                path.push("$PATH".to_string());
+            }
+            if has_flag(&flags, "--append", 'a') {
+                path.extend(words.iter().cloned());
+            } else {
+                path.splice(0..0, words.iter().cloned());
+            }
+            entries.extend(words);
         }
+
+        if entries.is_empty() {
+            return (entries, None);
+        }
+        (entries, comment)
     }
 }
 
@@ -27,34 +127,56 @@ impl ShellHandler for FishHandler {
         self.config_path.clone()
     }
 
-    fn parse_path_entries(&self, content: &str) -> Vec<PathBuf> {
-        let mut entries = Vec::new();
-        let path_regex = Regex::new(r"fish_add_path\s+(.+)$").unwrap();
-
+    fn parse_raw_entries(&self, content: &str) -> Vec<String> {
+        let mut path = Vec::new();
         for line in content.lines() {
-            if let Some(cap) = path_regex.captures(line.trim()) {
-                if let Some(path) = cap.get(1) {
-                    let expanded = shellexpand::tilde(path.as_str());
-                    entries.push(PathBuf::from(expanded.to_string()));
-                }
-            }
+            self.parse_line(line, &mut path);
         }
+        path
+    }
 
-        entries
+    /// Extracts comments after `fish_add_path` lines; a comment after a
+    /// `set` line with several entries belongs to the last one
+    fn parse_entry_comments(&self, content: &str) -> Vec<EntryComment> {
+        content
+            .lines()
+            .filter_map(|line| match self.parse_line(line, &mut Vec::new()) {
+                (entries, Some(comment)) => entries
+                    .iter()
+                    .rfind(|raw| !spelling::is_self_reference(raw))
+                    .map(|raw| EntryComment {
+                        raw: raw.clone(),
+                        comment: comment.to_string(),
+                    }),
+                _ => None,
+            })
+            .collect()
     }
 
-    fn format_path_export(&self, entries: &[PathBuf]) -> String {
+    fn format_entries(&self, entries: &[ConfigEntry]) -> String {
         let mut output = String::new();
-        output.push_str("\n# Updated by pathmaster on ");
-        output.push_str(&Local::now().format("%Y-%m-%d %H:%M:%S").to_string());
-        output.push_str("\n");
+        output.push_str(&format!("\n{}\n", BLOCK_HEADER));
 
-        // Clear existing PATH
-        output.push_str("set -e PATH\n");
+        // Extend the inherited PATH in place. This is a single line, so
+        // comments after entries can't be kept.
+        if entries.iter().any(ConfigEntry::is_path_reference) {
+            let words = entries.iter().map(quoting::fish_word).collect::<Vec<_>>();
+            output.push_str(&format!("set -gx PATH {}\n", words.join(" ")));
+            return output;
+        }
 
-        // Add each path using fish_add_path
-        for entry in entries {
-            output.push_str(&format!("fish_add_path {}\n", entry.display()));
+        // Set the first entry, then append the rest one per line. Unlike
+        // fish_add_path, this keeps the order and missing directories.
+        if entries.is_empty() {
+            output.push_str("set -gx PATH\n");
+        }
+        for (idx, entry) in entries.iter().enumerate() {
+            let flags = if idx == 0 { "-gx" } else { "-gxa" };
+            output.push_str(&format!("set {} PATH {}", flags, quoting::fish_word(entry)));
+            if let Some(comment) = &entry.comment {
+                output.push_str(&format!("  # {}", comment));
+            }
+            output.push('\n');
         }
 
         output
@@ -62,10 +184,11 @@ impl ShellHandler for FishHandler {
 
     fn detect_path_modifications(&self, content: &str) -> Vec<PathModification> {
         let mut modifications = Vec::new();
-        let path_regex = Regex::new(r"(fish_add_path|set -gx PATH)").unwrap();
 
         for (idx, line) in content.lines().enumerate() {
-            if path_regex.is_match(line) {
+            if !is_comment(line)
+                && statements(quoting::split_comment(line.trim()).0).any(modifies_path)
+            {
                 modifications.push(PathModification {
                     line_number: idx + 1,
                     content: line.to_string(),
@@ -77,21 +200,147 @@ impl ShellHandler for FishHandler {
         modifications
     }
 
-    fn update_path_in_config(&self, content: &str, entries: &[PathBuf]) -> String {
-        let modifications = self.detect_path_modifications(content);
+    fn drop_in_file_name(&self) -> &'static str {
+        "path.fish"
+    }
 
-        // Remove existing PATH modifications
-        let mut updated_content = content
-            .lines()
-            .enumerate()
-            .filter(|(idx, _)| !modifications.iter().any(|m| m.line_number == idx + 1))
-            .map(|(_, line)| line)
-            .collect::<Vec<_>>()
-            .join("\n");
+    fn format_source_line(&self, file: &Path) -> String {
+        format!("test -f \"{0}\"; and source \"{0}\"", file.display())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_fish_entry_comments() {
+        let handler = FishHandler::new();
+        let content = "fish_add_path --path /pm-test/tools  # work tools\nset -gx PATH /usr/bin $PATH # system\n";
+
+        assert_eq!(
+            handler.parse_raw_entries(content),
+            vec!["/pm-test/tools", "$PATH", "/usr/bin", "$PATH"]
+        );
+        assert_eq!(
+            handler.parse_entry_comments(content),
+            vec![
+                EntryComment {
+                    raw: "/pm-test/tools".to_string(),
+                    comment: "work tools".to_string(),
+                },
+                EntryComment {
+                    raw: "/usr/bin".to_string(),
+                    comment: "system".to_string(),
+                },
+            ]
+        );
+
+        let updated = handler.update_path_in_config(
+            "fish_add_path /pm-test/tools  # work tools\n",
+            &[PathBuf::from("/pm-test/tools")],
+        );
+        assert!(updated.contains("set -gx PATH /pm-test/tools  # work tools\n"));
+    }
+
+    #[test]
+    fn test_fish_commented_and_query_lines_ignored() {
+        let handler = FishHandler::new();
+        let content = "# fish_add_path /old/bin\nset -q PATH; or set -gx PATH /usr/bin\nset -e PATH\nfish_add_path /usr/local/bin\n";
+
+        let lines: Vec<usize> = handler
+            .detect_path_modifications(content)
+            .iter()
+            .map(|m| m.line_number)
+            .collect();
+        assert_eq!(lines, vec![2, 3, 4]);
+        assert_eq!(
+            handler.parse_raw_entries(content),
+            vec!["/usr/local/bin", "/usr/bin"]
+        );
+
+        let content = "set -g -q PATH; and echo set\nset --query PATH\nset -gx PATH /a; and set -gxa PATH /b\n";
+        let lines: Vec<usize> = handler
+            .detect_path_modifications(content)
+            .iter()
+            .map(|m| m.line_number)
+            .collect();
+        assert_eq!(lines, vec![3]);
+        assert_eq!(handler.parse_raw_entries(content), vec!["/a", "/b"]);
+    }
+
+    #[test]
+    fn test_fish_path_parsing() {
+        let handler = FishHandler::new();
+        let content = r#"
+# Some config
+set -gx PATH /usr/bin /usr/local/bin
+fish_add_path --append --path ~/bin
+set -gxa PATH /opt/bin
+"#;
+
+        let entries = handler.parse_path_entries(content);
+        assert_eq!(entries.len(), 4);
+        assert_eq!(entries[0], PathBuf::from("/usr/bin"));
+        assert_eq!(entries[2], locations::real_home_dir().join("bin"));
+        assert_eq!(entries[3], PathBuf::from("/opt/bin"));
+    }
+
+    #[test]
+    fn test_fish_path_formatting_keeps_order() {
+        let handler = FishHandler::new();
+        let entries = vec![
+            PathBuf::from("/usr/local/bin"),
+            PathBuf::from("/usr/bin"),
+            PathBuf::from("/opt/My Tools"),
+        ];
+
+        let formatted = handler.format_path_export(&entries);
+        assert!(formatted.contains(
+            "set -gx PATH /usr/local/bin\nset -gxa PATH /usr/bin\nset -gxa PATH '/opt/My Tools'\n"
+        ));
+        assert!(!formatted.contains("set -e PATH"));
+        assert_eq!(handler.parse_path_entries(&formatted), entries);
+        assert!(handler.format_path_export(&[]).ends_with("set -gx PATH\n"));
+    }
+
+    #[test]
+    fn test_fish_config_update() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("config.fish");
+        fs::write(
+            &config_path,
+            "# Initial config\nset -e PATH\nfish_add_path /old/path\nfish_add_path /usr/bin\n",
+        )
+        .unwrap();
+
+        let mut handler = FishHandler::new();
+        handler.config_path = config_path.clone();
+        let new_entries = vec![PathBuf::from("/usr/bin"), PathBuf::from("/usr/local/bin")];
+        handler.update_config(&new_entries).unwrap();
+
+        let updated = fs::read_to_string(&config_path).unwrap();
+        assert!(updated.starts_with("# Initial config\n"));
+        assert!(!updated.contains("/old/path"));
+        assert!(!updated.contains("set -e PATH"));
+        assert_eq!(handler.parse_path_entries(&updated), new_entries);
+    }
+
+    #[test]
+    fn test_fish_update_keeps_home_references() {
+        let handler = FishHandler::new();
+        let home = locations::real_home_dir();
+        let content = "set -gx PATH ~/bin $HOME/.cargo/bin /usr/bin\n";
 
-        // Add new PATH configuration
-        updated_content.push_str(&self.format_path_export(entries));
+        let mut entries = handler.parse_path_entries(content);
+        assert_eq!(entries[1], home.join(".cargo/bin"));
+        entries.push(PathBuf::from("/usr/local/bin"));
 
-        updated_content
+        let updated = handler.update_path_in_config(content, &entries);
+        assert!(updated.contains("set -gx PATH $HOME/bin\nset -gxa PATH $HOME/.cargo/bin\n"));
+        assert!(updated.contains("set -gxa PATH /usr/local/bin\n"));
+        assert_eq!(handler.update_path_in_config(&updated, &entries), updated);
     }
 }
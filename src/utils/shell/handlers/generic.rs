@@ -1,19 +1,28 @@
-use super::ShellHandler;
-use crate::utils::shell::types::{ModificationType, PathModification, ShellType};
-use chrono::Local;
-use dirs_next;
+use super::{is_comment, ShellHandler};
+use crate::utils::shell::continuation;
+use crate::utils::shell::locations;
+use crate::utils::shell::posix::{self, EntryCollector};
+use crate::utils::shell::types::{
+    ConfigEntry, EntryComment, ModificationType, PathModification, ShellType,
+};
+use lazy_static::lazy_static;
 use regex::Regex;
 use std::path::PathBuf;
 
+lazy_static! {
+    static ref EXPORT_REGEX: Regex =
+        Regex::new(&format!(r"export\s+PATH={}", posix::VALUE_PATTERN)).unwrap();
+    static ref MODIFICATION_REGEX: Regex = Regex::new(r"(?:export\s+)?PATH=").unwrap();
+}
+
 pub struct GenericHandler {
     config_path: PathBuf,
 }
 
 impl GenericHandler {
     pub fn new() -> Self {
-        let home_dir = dirs_next::home_dir().unwrap_or_else(|| PathBuf::from("/"));
         Self {
-            config_path: home_dir.join(".profile"),
+            config_path: locations::home_dir().join(".profile"),
         }
     }
 }
@@ -27,78 +36,79 @@ impl ShellHandler for GenericHandler {
         self.config_path.clone()
     }
 
-    fn parse_path_entries(&self, content: &str) -> Vec<PathBuf> {
-        let mut entries = Vec::new();
-        let export_regex = Regex::new(r#"export\s+PATH=["']?([^"']+)["']?"#).unwrap();
-
-        for line in content.lines() {
-            if let Some(cap) = export_regex.captures(line.trim()) {
-                if let Some(paths) = cap.get(1) {
-                    for path in paths.as_str().split(':') {
-                        let expanded = shellexpand::tilde(path);
-                        entries.push(PathBuf::from(expanded.to_string()));
-                    }
+    fn parse_raw_entries(&self, content: &str) -> Vec<String> {
+        let mut entries = EntryCollector::default();
+
+        for line in continuation::logical_lines(content)
+            .iter()
+            .map(|line| line.text.as_str())
+            .filter(|line| !is_comment(line))
+        {
+            if let Some(cap) = EXPORT_REGEX.captures(line.trim()) {
+                if let Some(paths) = posix::captured_value(&cap) {
+                    entries.extend(paths.split(':').map(String::from));
                 }
+            } else if let Some(path_line) = posix::parse_line(line) {
+                entries.add_line(path_line);
             }
         }
 
-        entries
+        entries.into_entries()
     }
 
-    fn format_path_export(&self, entries: &[PathBuf]) -> String {
-        let paths = entries
-            .iter()
-            .map(|p| p.to_string_lossy().to_string())
-            .collect::<Vec<_>>()
-            .join(":");
-
-        format!(
-            "\n# Updated by pathmaster on {}\nexport PATH=\"{}\"\n",
-            Local::now().format("%Y-%m-%d %H:%M:%S"),
-            paths
-        )
+    fn format_entries(&self, entries: &[ConfigEntry]) -> String {
+        posix::format_path(entries)
+    }
+
+    fn parse_entry_comments(&self, content: &str) -> Vec<EntryComment> {
+        posix::entry_comments(content)
     }
 
     fn detect_path_modifications(&self, content: &str) -> Vec<PathModification> {
         let mut modifications = Vec::new();
-        let path_regex = Regex::new(r"(?:export\s+)?PATH=").unwrap();
-
-        for (idx, line) in content.lines().enumerate() {
-            if path_regex.is_match(line) {
-                modifications.push(PathModification {
-                    line_number: idx + 1,
-                    content: line.to_string(),
-                    modification_type: ModificationType::Assignment,
-                });
+
+        // A continued statement is replaced as a whole, so flag each of its lines
+        for statement in continuation::logical_lines(content) {
+            if !is_comment(&statement.text) && MODIFICATION_REGEX.is_match(&statement.text) {
+                for (line_number, line) in statement
+                    .line_numbers()
+                    .zip(content.lines().skip(statement.start))
+                {
+                    modifications.push(PathModification {
+                        line_number,
+                        content: line.to_string(),
+                        modification_type: ModificationType::Assignment,
+                    });
+                }
             }
         }
 
         modifications
     }
-
-    fn update_path_in_config(&self, content: &str, entries: &[PathBuf]) -> String {
-        let modifications = self.detect_path_modifications(content);
-
-        let mut updated_content = content
-            .lines()
-            .enumerate()
-            .filter(|(idx, _)| !modifications.iter().any(|m| m.line_number == idx + 1))
-            .map(|(_, line)| line)
-            .collect::<Vec<_>>()
-            .join("\n");
-
-        updated_content.push_str(&self.format_path_export(entries));
-
-        updated_content
-    }
 }
 
 #[cfg(test)]
 mod generic_tests {
     use super::*;
+    use serial_test::serial;
+    use std::env;
     use std::fs;
     use tempfile::TempDir;
 
+    #[test]
+    fn test_commented_path_lines_ignored() {
+        let handler = GenericHandler::new();
+        let content = "# export PATH=/old/bin\n  # DISABLED by pathmaster: export PATH=/disabled/bin\nexport PATH=/usr/bin\n";
+
+        let modifications = handler.detect_path_modifications(content);
+        assert_eq!(modifications.len(), 1);
+        assert_eq!(modifications[0].line_number, 3);
+        assert_eq!(handler.parse_raw_entries(content), vec!["/usr/bin"]);
+
+        let updated = handler.update_path_in_config(content, &[PathBuf::from("/opt/bin")]);
+        assert!(updated.starts_with("# export PATH=/old/bin\n  # DISABLED by pathmaster"));
+    }
+
     #[test]
     fn test_generic_path_parsing() {
         let handler = GenericHandler::new();
@@ -139,4 +149,83 @@ export PATH=/usr/bin:/another/old/path
         assert!(updated_content.contains("export PATH="));
         assert!(updated_content.contains("/usr/local/bin"));
     }
+
+    #[test]
+    fn test_generic_update_follows_source() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join(".profile");
+        let path_file = temp_dir.path().join(".path");
+
+        let profile_content = "# Main profile\n. ~/.aliases\nsource .path\n";
+        fs::write(&config_path, profile_content).unwrap();
+        fs::write(&path_file, "export PATH=/usr/bin:/old/path\n").unwrap();
+
+        let mut handler = GenericHandler::new();
+        handler.config_path = config_path.clone();
+        assert_eq!(handler.get_target_path(), path_file);
+
+        handler
+            .update_config(&[PathBuf::from("/usr/local/bin")])
+            .unwrap();
+
+        assert_eq!(fs::read_to_string(&config_path).unwrap(), profile_content);
+        let updated = fs::read_to_string(&path_file).unwrap();
+        assert!(!updated.contains("/old/path"));
+        assert!(updated.contains("/usr/local/bin"));
+    }
+
+    #[test]
+    #[serial]
+    fn test_generic_drop_in_mode() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join(".profile");
+        fs::write(&config_path, "# Initial config\n").unwrap();
+
+        let original_xdg = env::var_os("XDG_CONFIG_HOME");
+        env::set_var("XDG_CONFIG_HOME", temp_dir.path().join("xdg"));
+
+        let mut handler = GenericHandler::new();
+        handler.config_path = config_path.clone();
+
+        handler
+            .update_drop_in(&[PathBuf::from("/usr/bin")])
+            .unwrap();
+        handler
+            .update_drop_in(&[PathBuf::from("/usr/local/bin")])
+            .unwrap();
+
+        match original_xdg {
+            Some(value) => env::set_var("XDG_CONFIG_HOME", value),
+            None => env::remove_var("XDG_CONFIG_HOME"),
+        }
+
+        let drop_in_path = temp_dir.path().join("xdg/pathmaster/path.sh");
+        let drop_in = fs::read_to_string(&drop_in_path).unwrap();
+        assert!(drop_in.contains("export PATH=\"/usr/local/bin\""));
+
+        let rc = fs::read_to_string(&config_path).unwrap();
+        assert!(rc.starts_with("# Initial config\n"));
+        assert_eq!(
+            rc.matches(&handler.format_source_line(&drop_in_path))
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_generic_update_preserves_conditionals() {
+        let handler = GenericHandler::new();
+        let content = r#"export PATH=/usr/bin:/old/path
+if [ -d "$HOME/bin" ]; then
+    PATH="$HOME/bin:$PATH"
+fi
+[ -d /opt/bin ] && PATH=/opt/bin:$PATH
+"#;
+
+        let updated = handler.update_path_in_config(content, &[PathBuf::from("/usr/local/bin")]);
+        assert!(!updated.contains("/old/path"));
+        assert!(updated.contains("    PATH=\"$HOME/bin:$PATH\"\n"));
+        assert!(updated.contains("[ -d /opt/bin ] && PATH=/opt/bin:$PATH\n"));
+        assert!(updated.contains("/usr/local/bin"));
+    }
 }
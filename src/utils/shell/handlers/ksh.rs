@@ -1,25 +1,56 @@
-use super::ShellHandler;
-use crate::utils::shell::types::{ModificationType, PathModification, ShellType};
-use chrono::Local;
-use dirs_next;
+use super::{is_comment, ShellHandler};
+use crate::utils::shell::continuation;
+use crate::utils::shell::locations;
+use crate::utils::shell::posix::{self, EntryCollector};
+use crate::utils::shell::types::{
+    ConfigEntry, EntryComment, ModificationType, PathModification, ShellType,
+};
+use lazy_static::lazy_static;
 use regex::Regex;
-use std::path::PathBuf;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+lazy_static! {
+    static ref EXPORT_REGEX: Regex = Regex::new(&format!(
+        r"(?:export|typeset -x)\s+PATH={}",
+        posix::VALUE_PATTERN
+    ))
+    .unwrap();
+    static ref MODIFICATION_REGEX: Regex =
+        Regex::new(r"(export\s+PATH=|typeset\s+-x\s+PATH=)").unwrap();
+}
 
 pub struct KshHandler {
-    config_path: PathBuf,
+    config_path: Option<PathBuf>,
+    home_dir: PathBuf,
 }
 
 impl KshHandler {
     pub fn new() -> Self {
-        let home_dir = dirs_next::home_dir().unwrap_or_else(|| PathBuf::from("/"));
         Self {
-            config_path: home_dir.join(".kshrc"),
+            config_path: None,
+            home_dir: locations::home_dir(),
         }
     }
 
-    fn get_fallback_paths(&self) -> Vec<PathBuf> {
-        let home_dir = dirs_next::home_dir().unwrap_or_else(|| PathBuf::from("/"));
-        vec![home_dir.join(".profile"), home_dir.join(".ksh_profile")]
+    /// Startup file read by interactive shells: `$ENV`, or `~/.kshrc`
+    fn interactive_file(&self) -> PathBuf {
+        locations::ksh_env_file().unwrap_or_else(|| self.home_dir.join(".kshrc"))
+    }
+
+    /// Startup files read by login shells
+    fn login_files(&self) -> Vec<PathBuf> {
+        vec![
+            self.home_dir.join(".profile"),
+            self.home_dir.join(".ksh_profile"),
+        ]
+    }
+
+    /// Returns whether a file exists and declares PATH
+    fn declares_path(&self, path: &Path) -> bool {
+        fs::read_to_string(path)
+            .map(|content| !self.detect_path_modifications(&content).is_empty())
+            .unwrap_or(false)
     }
 }
 
@@ -28,99 +59,107 @@ impl ShellHandler for KshHandler {
         ShellType::Ksh
     }
 
+    /// Picks the file that should hold PATH declarations.
+    ///
+    /// A startup file that already declares PATH is used first, checking the
+    /// interactive file before the login files. Otherwise the login file
+    /// `~/.profile` is preferred when it exists, since PATH exported there is
+    /// inherited by every shell; the interactive file is the last resort.
     fn get_config_path(&self) -> PathBuf {
-        // Check for fallback paths if .kshrc doesn't exist
-        if !self.config_path.exists() {
-            for path in self.get_fallback_paths() {
-                if path.exists() {
-                    return path;
-                }
-            }
+        if let Some(path) = &self.config_path {
+            return path.clone();
+        }
+
+        let interactive = self.interactive_file();
+        let login = self.login_files();
+
+        if let Some(path) = std::iter::once(&interactive)
+            .chain(&login)
+            .find(|path| self.declares_path(path))
+        {
+            return path.clone();
         }
-        self.config_path.clone()
+
+        login
+            .into_iter()
+            .find(|path| path.exists())
+            .unwrap_or(interactive)
     }
 
-    fn parse_path_entries(&self, content: &str) -> Vec<PathBuf> {
-        let mut entries = Vec::new();
+    /// Only ksh's own startup files count; `~/.profile` is shared by every
+    /// POSIX shell and says nothing about whether ksh is used.
+    fn has_config(&self) -> bool {
+        self.config_path.as_ref().is_some_and(|path| path.exists())
+            || self.interactive_file().exists()
+            || self.home_dir.join(".ksh_profile").exists()
+    }
+
+    fn parse_raw_entries(&self, content: &str) -> Vec<String> {
+        let mut entries = EntryCollector::default();
         let mut seen_paths = std::collections::HashSet::new();
-        let export_regex =
-            Regex::new(r#"(?:export|typeset -x)\s+PATH=["']?([^"']+)["']?"#).unwrap();
 
-        for line in content.lines() {
+        for line in continuation::logical_lines(content)
+            .iter()
+            .map(|line| line.text.as_str())
+            .filter(|line| !is_comment(line))
+        {
             let line = line.trim();
 
-            if let Some(cap) = export_regex.captures(line) {
-                if let Some(paths) = cap.get(1) {
-                    for path in paths.as_str().split(':') {
-                        // Skip variables like $PATH
-                        if path.starts_with('$') {
-                            continue;
-                        }
-                        let expanded = shellexpand::tilde(path);
-                        let path_buf = PathBuf::from(expanded.to_string());
-                        if seen_paths.insert(path_buf.clone()) {
-                            entries.push(path_buf);
-                        }
-                    }
+            if let Some(cap) = EXPORT_REGEX.captures(line) {
+                if let Some(paths) = posix::captured_value(&cap) {
+                    entries.extend(paths.split(':').map(String::from));
                 }
+            } else if let Some(path_line) = posix::parse_line(line) {
+                entries.add_line(path_line);
             }
         }
 
         entries
+            .into_entries()
+            .into_iter()
+            .filter(|path| seen_paths.insert(path.clone()))
+            .collect()
     }
 
-    fn format_path_export(&self, entries: &[PathBuf]) -> String {
-        let paths = entries
-            .iter()
-            .map(|p| p.to_string_lossy().to_string())
-            .collect::<Vec<_>>()
-            .join(":");
+    fn format_entries(&self, entries: &[ConfigEntry]) -> String {
+        posix::format_path(entries)
+    }
 
-        format!(
-            "\n# Updated by pathmaster on {}\nexport PATH=\"{}\"\n",
-            Local::now().format("%Y-%m-%d %H:%M:%S"),
-            paths
-        )
+    fn parse_entry_comments(&self, content: &str) -> Vec<EntryComment> {
+        posix::entry_comments(content)
     }
 
     fn detect_path_modifications(&self, content: &str) -> Vec<PathModification> {
         let mut modifications = Vec::new();
-        let path_regex = Regex::new(r"(export\s+PATH=|typeset\s+-x\s+PATH=)").unwrap();
-
-        for (idx, line) in content.lines().enumerate() {
-            if path_regex.is_match(line) {
-                modifications.push(PathModification {
-                    line_number: idx + 1,
-                    content: line.to_string(),
-                    modification_type: ModificationType::Assignment,
-                });
+
+        // A continued statement is replaced as a whole, so flag each of its lines
+        for statement in continuation::logical_lines(content) {
+            if !is_comment(&statement.text)
+                && (MODIFICATION_REGEX.is_match(&statement.text)
+                    || posix::parse_line(&statement.text).is_some())
+            {
+                for (line_number, line) in statement
+                    .line_numbers()
+                    .zip(content.lines().skip(statement.start))
+                {
+                    modifications.push(PathModification {
+                        line_number,
+                        content: line.to_string(),
+                        modification_type: ModificationType::Assignment,
+                    });
+                }
             }
         }
 
         modifications
     }
-
-    fn update_path_in_config(&self, content: &str, entries: &[PathBuf]) -> String {
-        let modifications = self.detect_path_modifications(content);
-
-        let mut updated_content = content
-            .lines()
-            .enumerate()
-            .filter(|(idx, _)| !modifications.iter().any(|m| m.line_number == idx + 1))
-            .map(|(_, line)| line)
-            .collect::<Vec<_>>()
-            .join("\n");
-
-        updated_content.push_str(&self.format_path_export(entries));
-
-        updated_content
-    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::fs;
+    use serial_test::serial;
+    use std::env;
     use tempfile::TempDir;
 
     #[test]
@@ -153,7 +192,7 @@ typeset -x PATH=/usr/bin:/old/path
         fs::write(&config_path, initial_content).unwrap();
 
         let mut handler = KshHandler::new();
-        handler.config_path = config_path.clone();
+        handler.config_path = Some(config_path.clone());
 
         let new_entries = vec![PathBuf::from("/usr/bin"), PathBuf::from("/usr/local/bin")];
 
@@ -164,4 +203,70 @@ typeset -x PATH=/usr/bin:/old/path
         assert!(updated_content.contains("/usr/bin"));
         assert!(updated_content.contains("/usr/local/bin"));
     }
+
+    #[test]
+    fn test_ksh_crlf_config_preserved() {
+        let handler = KshHandler::new();
+        let content =
+            "\u{feff}# Windows config\r\nexport PATH=/usr/bin:/old/path\r\nalias ll='ls -l'\r\n";
+
+        let updated = handler.update_path_in_config(content, &[PathBuf::from("/usr/bin")]);
+
+        assert!(updated.starts_with("\u{feff}# Windows config\r\n"));
+        assert!(!updated.contains("/old/path"));
+        assert!(updated.ends_with("alias ll='ls -l'\r\n"));
+        assert_eq!(
+            updated.matches('\n').count(),
+            updated.matches("\r\n").count()
+        );
+    }
+
+    #[test]
+    fn test_ksh_continued_export_replaced() {
+        let handler = KshHandler::new();
+        let content = "export PATH=/usr/bin:\\\n/old/path:\\\n/usr/local/bin\nalias ll='ls -l'\n";
+
+        assert_eq!(
+            handler.parse_raw_entries(content),
+            vec!["/usr/bin", "/old/path", "/usr/local/bin"]
+        );
+        assert_eq!(handler.detect_path_modifications(content).len(), 3);
+
+        let updated = handler.update_path_in_config(content, &[PathBuf::from("/usr/bin")]);
+        assert!(!updated.contains("/old/path"));
+        assert!(!updated.contains("/usr/local/bin"));
+        assert!(updated.ends_with("alias ll='ls -l'\n"));
+    }
+
+    #[test]
+    #[serial]
+    fn test_ksh_config_detection() {
+        let temp_dir = TempDir::new().unwrap();
+        let home = temp_dir.path();
+        let env_file = home.join(".config/ksh/kshrc");
+        let profile = home.join(".profile");
+
+        let original_env = env::var_os("ENV");
+        env::set_var("ENV", &env_file);
+
+        let mut handler = KshHandler::new();
+        handler.home_dir = home.to_path_buf();
+
+        // Nothing exists yet: fall back to the $ENV file
+        assert_eq!(handler.get_config_path(), env_file);
+
+        // A login profile is preferred over the interactive file
+        fs::write(&profile, "umask 022\n").unwrap();
+        assert_eq!(handler.get_config_path(), profile);
+
+        // The file that already declares PATH wins
+        fs::create_dir_all(env_file.parent().unwrap()).unwrap();
+        fs::write(&env_file, "export PATH=/usr/bin:$PATH\n").unwrap();
+        assert_eq!(handler.get_config_path(), env_file);
+
+        match original_env {
+            Some(value) => env::set_var("ENV", value),
+            None => env::remove_var("ENV"),
+        }
+    }
 }
@@ -1,13 +1,14 @@
-use chrono::Local;
+use chrono::{Local, NaiveDateTime};
 use std::fs;
 use std::io;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 pub mod bash;
 pub mod fish;
 pub mod generic;
 pub mod ksh;
 pub mod tcsh;
+pub mod template;
 pub mod zsh;
 
 pub use bash::BashHandler;
@@ -17,38 +18,584 @@ pub use ksh::KshHandler;
 pub use tcsh::TcshHandler;
 pub use zsh::ZshHandler;
 
+use crate::backup::core::{split_counter, unused_path, TIMESTAMP_FORMAT};
+use crate::config::{self, ExportStyle, WriteMode};
+use crate::msg;
+use crate::utils;
+use crate::utils::audit;
+use crate::utils::check_mode;
+use crate::utils::root;
+use crate::utils::shell::spelling::{self, Spellings};
+use crate::utils::shell::text_format::TextFormat;
 use crate::utils::shell::types::*;
+use crate::utils::shell::{block, conditionals, continuation, sources};
 
 #[allow(dead_code)]
 pub trait ShellHandler {
     fn get_shell_type(&self) -> ShellType;
+    /// Name shown in messages; custom shells override it
+    fn name(&self) -> String {
+        self.get_shell_type().to_string()
+    }
     fn get_config_path(&self) -> PathBuf;
-    fn parse_path_entries(&self, content: &str) -> Vec<PathBuf>;
-    fn format_path_export(&self, entries: &[PathBuf]) -> String;
+    /// Extracts PATH entries exactly as written in the config, without expansion
+    fn parse_raw_entries(&self, content: &str) -> Vec<String>;
+    /// Formats PATH declarations for this shell from already-spelled entries
+    fn format_entries(&self, entries: &[ConfigEntry]) -> String;
     fn detect_path_modifications(&self, content: &str) -> Vec<PathModification>;
-    fn update_path_in_config(&self, content: &str, entries: &[PathBuf]) -> String;
 
-    fn create_backup(&self) -> io::Result<PathBuf> {
+    /// Extracts PATH entries from the config, expanding `~` and variables
+    fn parse_path_entries(&self, content: &str) -> Vec<PathBuf> {
+        self.parse_raw_entries(content)
+            .iter()
+            .filter_map(|raw| spelling::expand_entry(raw))
+            .collect()
+    }
+
+    /// Extracts the comments written after PATH entries, for formats that
+    /// put one entry per line
+    fn parse_entry_comments(&self, _content: &str) -> Vec<EntryComment> {
+        Vec::new()
+    }
+
+    /// Extracts raw PATH entries along with the line declaring each one
+    fn parse_declared_entries(&self, content: &str) -> Vec<DeclaredEntry> {
+        continuation::logical_lines(content)
+            .into_iter()
+            .flat_map(|line| {
+                self.parse_raw_entries(&line.text)
+                    .into_iter()
+                    .map(move |raw| DeclaredEntry {
+                        line_number: line.start + 1,
+                        raw,
+                    })
+            })
+            .collect()
+    }
+
+    /// Returns the expanded entries of the declarations pathmaster rewrites,
+    /// in PATH order.
+    ///
+    /// Entries of guarded declarations, such as `[ -d ~/bin ] && PATH=...`,
+    /// are left out: rewriting PATH leaves those lines as they are.
+    fn managed_entries(&self, content: &str) -> Vec<PathBuf> {
+        self.managed_path(content).entries
+    }
+
+    /// Returns the [`managed_entries`](Self::managed_entries) along with how
+    /// many of them the declarations put ahead of the existing PATH
+    fn managed_path(&self, content: &str) -> DeclaredPath {
+        let (_, content) = TextFormat::detect(content);
+        let lines: Vec<usize> = self
+            .replaceable_modifications(&content)
+            .iter()
+            .map(|m| m.line_number)
+            .collect();
+        let managed: Vec<String> = self
+            .parse_declared_entries(&content)
+            .into_iter()
+            .filter(|entry| lines.contains(&entry.line_number))
+            .map(|entry| entry.raw)
+            .collect();
+        let raw: Vec<String> = self
+            .parse_raw_entries(&content)
+            .into_iter()
+            .filter(|raw| managed.contains(raw))
+            .collect();
+        let expand = |raw: &[String]| -> Vec<PathBuf> {
+            raw.iter()
+                .filter_map(|raw| spelling::expand_entry(raw))
+                .collect()
+        };
+        let ahead = raw
+            .iter()
+            .position(|raw| spelling::is_self_reference(raw))
+            .map_or(0, |reference| expand(&raw[..reference]).len());
+        DeclaredPath {
+            entries: expand(&raw),
+            ahead,
+        }
+    }
+
+    /// Returns the entries that declarations kept in `content` add before
+    /// line `line` (zero-based), so they're already in PATH when it runs.
+    ///
+    /// Relative rewrites leave these to the `$PATH` reference instead of
+    /// adding them a second time.
+    fn entries_declared_before(
+        &self,
+        _content: &str,
+        _modifications: &[PathModification],
+        _line: usize,
+    ) -> Vec<PathBuf> {
+        Vec::new()
+    }
+
+    /// Offers fixes to the rest of the config before PATH in it is rewritten
+    ///
+    /// # Returns
+    /// * The content to rewrite PATH in, with any accepted fixes applied
+    fn offer_config_fixes(&self, content: String, _config_path: &Path) -> String {
+        content
+    }
+
+    /// Formats PATH declarations, writing home-relative entries as `$HOME/...`
+    fn format_path_export(&self, entries: &[PathBuf]) -> String {
+        self.format_entries(&Spellings::default().spell_all(entries))
+    }
+
+    /// Replaces the PATH declarations in `content` with `entries`.
+    ///
+    /// Entries keep the spelling they had in the existing config, so
+    /// references like `$HOME/bin` or `~/bin` aren't expanded on rewrite.
+    /// Comments after entries are kept, and entries added with `--reason`
+    /// are annotated with it.
+    fn update_path_in_config(&self, content: &str, entries: &[PathBuf]) -> String {
+        // Edit LF-only text, then restore the file's CRLF endings and BOM
+        let (format, content) = TextFormat::detect(content);
+        let mut spellings = Spellings::from_raw(&self.parse_raw_entries(&content))
+            .with_comments(&self.parse_entry_comments(&content));
+        if let Some(reason) = audit::current_reason() {
+            let initial = utils::initial_path_entries();
+            for entry in entries.iter().filter(|entry| !initial.contains(entry)) {
+                spellings.annotate(entry, &reason);
+            }
+        }
+        let modifications = self.replaceable_modifications(&content);
+
+        let spelled = match config::get_config().export_style {
+            ExportStyle::Absolute => spellings.spell_all(entries),
+            ExportStyle::Relative => {
+                // Entries this config doesn't declare were inherited through $PATH
+                let declared = self.parse_path_entries(&content);
+                let mut inherited: Vec<PathBuf> = utils::initial_path_entries()
+                    .iter()
+                    .filter(|entry| !declared.contains(entry))
+                    .cloned()
+                    .collect();
+                for entry in inherited.iter().filter(|entry| !entries.contains(entry)) {
+                    println!("{}", msg!("shell-inherited-entry", path = entry));
+                }
+                // So were entries the file adds before the block runs
+                let position = block::block_position(&content, &modifications);
+                for entry in self.entries_declared_before(&content, &modifications, position) {
+                    if !inherited.contains(&entry) {
+                        inherited.push(entry);
+                    }
+                }
+                spellings.spell_relative(entries, &inherited)
+            }
+        };
+
+        format.restore(&block::apply_block(
+            &content,
+            &modifications,
+            &self.format_entries(&spelled),
+        ))
+    }
+
+    /// Returns the PATH modifications that are safe to replace.
+    ///
+    /// Declarations inside conditionals, loops or function bodies are left
+    /// alone so guards like `[ -d ~/bin ] && PATH=...` keep working. A
+    /// backslash-continued statement is kept or replaced as a whole, so no
+    /// fragment of it is left behind.
+    fn replaceable_modifications(&self, content: &str) -> Vec<PathModification> {
+        let conditional = conditionals::conditional_lines(content, &self.get_shell_type());
+        let modifications: Vec<PathModification> = self
+            .detect_path_modifications(content)
+            .into_iter()
+            .filter(|m| {
+                !conditional
+                    .get(m.line_number.saturating_sub(1))
+                    .copied()
+                    .unwrap_or(false)
+            })
+            .collect();
+
+        let physical: Vec<&str> = content.lines().collect();
+        let mut replaceable = Vec::new();
+        for statement in continuation::logical_lines(content) {
+            let Some(first) = modifications
+                .iter()
+                .find(|m| statement.line_numbers().contains(&m.line_number))
+            else {
+                continue;
+            };
+            for line_number in statement.line_numbers() {
+                replaceable.push(
+                    modifications
+                        .iter()
+                        .find(|m| m.line_number == line_number)
+                        .cloned()
+                        .unwrap_or_else(|| PathModification {
+                            line_number,
+                            content: physical[line_number - 1].to_string(),
+                            modification_type: first.modification_type.clone(),
+                        }),
+                );
+            }
+        }
+        replaceable
+    }
+
+    /// Name of the file PATH is written to in drop-in mode
+    fn drop_in_file_name(&self) -> &'static str {
+        "path.sh"
+    }
+
+    /// Formats the line that loads the drop-in file from the rc file
+    fn format_source_line(&self, file: &Path) -> String {
+        format!("[ -f \"{0}\" ] && . \"{0}\"", file.display())
+    }
+
+    /// Returns the file that actually declares PATH.
+    ///
+    /// When the main config has no PATH declarations of its own but sources
+    /// a file that does, the sourced file is returned instead. Only one level
+    /// of `source`/`.` statements is followed. A configured `config_file`
+    /// takes precedence over both.
+    fn get_target_path(&self) -> PathBuf {
+        if let Some(config_file) = config::get_config().config_file {
+            return config_file;
+        }
+
         let config_path = self.get_config_path();
-        let timestamp = Local::now().format("%Y%m%d%H%M%S").to_string();
-        let backup_path = config_path.with_extension(format!("bak_{}", timestamp));
+        let content = match fs::read_to_string(&config_path) {
+            Ok(content) => content,
+            Err(_) => return config_path,
+        };
+
+        if !self.detect_path_modifications(&content).is_empty() {
+            return config_path;
+        }
 
-        fs::copy(&config_path, &backup_path)?;
-        Ok(backup_path)
+        sources::find_sourced_files(&content, &config_path)
+            .into_iter()
+            .find(|file| {
+                fs::read_to_string(file)
+                    .map(|c| !self.detect_path_modifications(&c).is_empty())
+                    .unwrap_or(false)
+            })
+            .unwrap_or(config_path)
+    }
+
+    /// Returns whether the user appears to use this shell, i.e. its config exists
+    fn has_config(&self) -> bool {
+        self.get_config_path().exists()
+    }
+
+    /// Returns the files [`update_config`](Self::update_config) may write
+    fn modified_files(&self) -> Vec<PathBuf> {
+        let settings = config::get_config();
+        match settings.write_mode {
+            WriteMode::Inline => vec![self.get_target_path()],
+            WriteMode::DropIn => vec![
+                config::config_dir().join(self.drop_in_file_name()),
+                settings
+                    .config_file
+                    .unwrap_or_else(|| self.get_config_path()),
+            ],
+        }
+    }
+
+    fn create_backup(&self) -> io::Result<PathBuf> {
+        backup_file(&self.get_target_path())
     }
 
     fn update_config(&self, entries: &[PathBuf]) -> io::Result<()> {
-        let config_path = self.get_config_path();
+        if config::get_config().write_mode == WriteMode::DropIn {
+            return self.update_drop_in(entries);
+        }
+
+        let config_path = self.get_target_path();
+        if config_path != self.get_config_path() && config::get_config().config_file.is_none() {
+            println!("{}", msg!("shell-sourced-file", file = &config_path));
+        }
+
+        let content = self.offer_config_fixes(fs::read_to_string(&config_path)?, &config_path);
+        let updated_content = self.update_path_in_config(&content, entries);
+        if check_mode::is_check_mode() {
+            check_mode::record_write(&config_path, &updated_content);
+            return Ok(());
+        }
+
         let backup_path = self.create_backup()?;
-        println!(
-            "Created backup of shell config at: {}",
-            backup_path.display()
+        println!("{}", msg!("shell-config-backup", file = &backup_path));
+        write_with_rollback(&config_path, &updated_content, &backup_path)
+    }
+
+    /// Writes PATH to the pathmaster drop-in file and makes sure the rc file sources it.
+    ///
+    /// The rc file is only modified (and backed up) the first time, when the
+    /// source line is missing.
+    fn update_drop_in(&self, entries: &[PathBuf]) -> io::Result<()> {
+        self.write_drop_in(entries)?;
+        self.add_drop_in_source_line()
+    }
+
+    /// Writes PATH to the pathmaster drop-in file
+    fn write_drop_in(&self, entries: &[PathBuf]) -> io::Result<()> {
+        let drop_in_path = config::config_dir().join(self.drop_in_file_name());
+        let drop_in_content = format!(
+            "# Managed by pathmaster. Changes made here will be overwritten.\n{}",
+            self.format_path_export(entries)
         );
+        if check_mode::is_check_mode() {
+            check_mode::record_write(&drop_in_path, &drop_in_content);
+        } else {
+            if let Some(parent) = drop_in_path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(&drop_in_path, drop_in_content)?;
+            println!("{}", msg!("shell-drop-in-written", file = &drop_in_path));
+        }
+        Ok(())
+    }
 
-        let content = fs::read_to_string(&config_path)?;
-        let updated_content = self.update_path_in_config(&content, entries);
-        fs::write(&config_path, updated_content)?;
+    /// Adds the line sourcing the drop-in file to the rc file, unless it's
+    /// already there
+    ///
+    /// With `--all-shells`, shells sharing a drop-in file, such as bash and
+    /// ksh with `path.sh`, each need the line in their own rc file even
+    /// though the drop-in file is written only once.
+    fn add_drop_in_source_line(&self) -> io::Result<()> {
+        let drop_in_path = config::config_dir().join(self.drop_in_file_name());
+        let config_path = config::get_config()
+            .config_file
+            .unwrap_or_else(|| self.get_config_path());
+        // The rc file is read by the real shell, outside any --root
+        let source_line = self.format_source_line(&root::unrooted(&drop_in_path));
+        let content = fs::read_to_string(&config_path).unwrap_or_default();
+
+        if content.lines().any(|line| line.trim() == source_line) {
+            return Ok(());
+        }
+
+        let (format, mut updated_content) = TextFormat::detect(&content);
+        if !updated_content.is_empty() && !updated_content.ends_with('\n') {
+            updated_content.push('\n');
+        }
+        updated_content.push_str(&format!("\n# Added by pathmaster\n{}\n", source_line));
+        let updated_content = format.restore(&updated_content);
+        if check_mode::is_check_mode() {
+            check_mode::record_write(&config_path, &updated_content);
+            return Ok(());
+        }
+
+        let backup_path = if config_path.exists() {
+            let backup_path = backup_file(&config_path)?;
+            println!("{}", msg!("shell-config-backup", file = &backup_path));
+            Some(backup_path)
+        } else {
+            None
+        };
+
+        match &backup_path {
+            Some(backup_path) => write_with_rollback(&config_path, &updated_content, backup_path)?,
+            None => fs::write(&config_path, updated_content)?,
+        }
+        println!("{}", msg!("shell-drop-in-sourced", file = &config_path));
+
+        let remaining = self.detect_path_modifications(&content).len();
+        if remaining > 0 {
+            println!(
+                "{}",
+                msg!(
+                    "shell-drop-in-remaining",
+                    file = &config_path,
+                    count = remaining
+                )
+            );
+        }
 
         Ok(())
     }
 }
+
+/// Returns whether a config line is a comment, e.g. a commented-out PATH export.
+///
+/// All supported shells start comments with `#`.
+pub fn is_comment(line: &str) -> bool {
+    line.trim_start().starts_with('#')
+}
+
+/// Writes a config file, restoring it from `backup_path` if the write fails.
+///
+/// The file is read back after writing, so a short write (for example on a
+/// full disk) is caught too instead of leaving a truncated config behind.
+pub fn write_with_rollback(
+    config_path: &Path,
+    content: &str,
+    backup_path: &Path,
+) -> io::Result<()> {
+    let result = fs::write(config_path, content).and_then(|_| {
+        if fs::read_to_string(config_path)? == content {
+            Ok(())
+        } else {
+            Err(io::Error::other("the file doesn't match what was written"))
+        }
+    });
+
+    let Err(e) = result else {
+        return Ok(());
+    };
+    let restored = match fs::copy(backup_path, config_path) {
+        Ok(_) => format!("it was restored from {}", backup_path.display()),
+        Err(restore_error) => format!(
+            "restoring it from {} also failed ({}); copy the backup back by hand",
+            backup_path.display(),
+            restore_error
+        ),
+    };
+    Err(io::Error::new(
+        e.kind(),
+        format!(
+            "writing {} failed: {}; {}",
+            config_path.display(),
+            e,
+            restored
+        ),
+    ))
+}
+
+/// Copies a config file to a timestamped `.bak_` sibling.
+///
+/// A copy made within the same second as an earlier one gets a counter
+/// after the timestamp rather than replacing it.
+///
+/// # Returns
+/// * `Ok(PathBuf)` - Location of the backup copy
+/// * `Err(io::Error)` - If the file couldn't be copied
+pub fn backup_file(config_path: &Path) -> io::Result<PathBuf> {
+    let timestamp = Local::now().format(TIMESTAMP_FORMAT).to_string();
+    let backup_path =
+        unused_path(|suffix| config_path.with_extension(format!("bak_{}{}", timestamp, suffix)));
+
+    fs::copy(config_path, &backup_path)?;
+    Ok(backup_path)
+}
+
+/// Lists the `.bak_` copies [`backup_file`] made of a config, oldest first
+///
+/// # Returns
+/// * Each copy and when it was taken
+pub fn backup_copies(config_path: &Path) -> Vec<(PathBuf, NaiveDateTime)> {
+    let prefix = match config_path.with_extension("bak_").file_name() {
+        Some(name) => name.to_string_lossy().into_owned(),
+        None => return Vec::new(),
+    };
+    let Some(Ok(entries)) = config_path.parent().map(fs::read_dir) else {
+        return Vec::new();
+    };
+
+    let mut copies: Vec<(PathBuf, NaiveDateTime, u32)> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let (stamp, counter) = split_counter(name.strip_prefix(&prefix)?);
+            let taken = NaiveDateTime::parse_from_str(stamp, TIMESTAMP_FORMAT).ok()?;
+            Some((entry.path(), taken, counter))
+        })
+        .collect();
+    copies.sort_by_key(|copy| (copy.1, copy.2));
+    copies
+        .into_iter()
+        .map(|(file, taken, _)| (file, taken))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::path::os_string_from_bytes;
+    use crate::utils::shell::factory;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_invalid_utf8_round_trip() {
+        let entries = vec![
+            PathBuf::from(os_string_from_bytes(b"/opt/caf\xe9 it's/bin".to_vec())),
+            PathBuf::from("/usr/bin"),
+        ];
+        let spelled: Vec<ConfigEntry> = entries.iter().map(ConfigEntry::literal).collect();
+
+        for shell in [
+            ShellType::Bash,
+            ShellType::Zsh,
+            ShellType::Fish,
+            ShellType::Tcsh,
+            ShellType::Ksh,
+            ShellType::Generic,
+        ] {
+            let handler = factory::get_shell_handler(Some(shell));
+            let content = handler.format_entries(&spelled);
+            // tcsh declares both `path` and `PATH`
+            let parsed = handler.parse_path_entries(&content);
+            assert_eq!(
+                parsed[..entries.len().min(parsed.len())],
+                entries,
+                "{:?}: {}",
+                shell,
+                content
+            );
+        }
+    }
+
+    #[test]
+    fn test_write_with_rollback() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join(".bashrc");
+        fs::write(&config_path, "export PATH=/usr/bin\n").unwrap();
+        let backup_path = backup_file(&config_path).unwrap();
+
+        write_with_rollback(&config_path, "export PATH=/opt/bin\n", &backup_path).unwrap();
+        assert_eq!(
+            fs::read_to_string(&config_path).unwrap(),
+            "export PATH=/opt/bin\n"
+        );
+
+        // Writing to a directory fails; the backup comes back in its place
+        let blocked = temp_dir.path().join("blocked");
+        fs::create_dir(&blocked).unwrap();
+        let error = write_with_rollback(&blocked, "x", &backup_path).unwrap_err();
+        assert!(error.to_string().contains("restoring it from"));
+    }
+
+    #[test]
+    fn test_backup_copies() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("config.fish");
+        for name in [
+            "config.bak_20240321120000",
+            "config.bak_20240101090000_10",
+            "config.bak_20240101090000",
+            "config.bak_20240101090000_2",
+            "config.bak_notes",
+            "other.bak_20240101090000",
+        ] {
+            fs::write(temp_dir.path().join(name), "").unwrap();
+        }
+
+        let copies: Vec<String> = backup_copies(&config_path)
+            .into_iter()
+            .map(|(file, _)| file.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(
+            copies,
+            vec![
+                "config.bak_20240101090000",
+                "config.bak_20240101090000_2",
+                "config.bak_20240101090000_10",
+                "config.bak_20240321120000"
+            ]
+        );
+
+        // Copies made within the same second are all kept
+        fs::write(&config_path, "set -gx PATH /usr/bin\n").unwrap();
+        let first = backup_file(&config_path).unwrap();
+        let second = backup_file(&config_path).unwrap();
+        assert_ne!(first, second);
+        assert!(first.exists() && second.exists());
+    }
+}
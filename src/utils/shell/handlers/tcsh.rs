@@ -1,21 +1,49 @@
-use super::ShellHandler;
-use crate::utils::shell::types::{ModificationType, PathModification, ShellType};
-use chrono::Local;
-use dirs_next;
+use super::{is_comment, ShellHandler};
+use crate::utils::shell::block::BLOCK_HEADER;
+use crate::utils::shell::locations;
+use crate::utils::shell::quoting;
+use crate::utils::shell::types::{ConfigEntry, ModificationType, PathModification, ShellType};
+use lazy_static::lazy_static;
 use regex::Regex;
-use std::path::PathBuf;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+lazy_static! {
+    static ref SETENV_REGEX: Regex = Regex::new(r"setenv\s+PATH\s+([^#\n]+)").unwrap();
+    static ref SET_REGEX: Regex = Regex::new(r"set\s+path\s*=\s*\((.*?)\)").unwrap();
+    static ref MODIFICATION_REGEX: Regex = Regex::new(r"(setenv\s+PATH|set\s+path\s*=)").unwrap();
+}
 
 pub struct TcshHandler {
-    config_path: PathBuf,
+    config_path: Option<PathBuf>,
+    home_dir: PathBuf,
 }
 
 impl TcshHandler {
     pub fn new() -> Self {
-        let home_dir = dirs_next::home_dir().unwrap_or_else(|| PathBuf::from("/"));
         Self {
-            config_path: home_dir.join(".tcshrc"),
+            config_path: None,
+            home_dir: locations::home_dir(),
         }
     }
+
+    /// Startup file read by every shell: `~/.tcshrc`, or `~/.cshrc` without it
+    fn rc_file(&self) -> PathBuf {
+        let tcshrc = self.home_dir.join(".tcshrc");
+        let cshrc = self.home_dir.join(".cshrc");
+        if !tcshrc.exists() && cshrc.exists() {
+            cshrc
+        } else {
+            tcshrc
+        }
+    }
+
+    /// Returns whether a file exists and declares PATH
+    fn declares_path(&self, path: &Path) -> bool {
+        fs::read_to_string(path)
+            .map(|content| !self.detect_path_modifications(&content).is_empty())
+            .unwrap_or(false)
+    }
 }
 
 impl ShellHandler for TcshHandler {
@@ -23,40 +51,42 @@ impl ShellHandler for TcshHandler {
         ShellType::Tcsh
     }
 
+    /// Picks the file that should hold PATH declarations.
+    ///
+    /// tcsh reads `~/.tcshrc` (falling back to `~/.cshrc`) and, for login
+    /// shells, `~/.login` afterwards. Whichever of these already declares
+    /// PATH is used; otherwise the rc file is.
     fn get_config_path(&self) -> PathBuf {
-        self.config_path.clone()
+        if let Some(path) = &self.config_path {
+            return path.clone();
+        }
+
+        let rc_file = self.rc_file();
+        let login = self.home_dir.join(".login");
+        [rc_file.clone(), login]
+            .into_iter()
+            .find(|path| self.declares_path(path))
+            .unwrap_or(rc_file)
     }
 
-    fn parse_path_entries(&self, content: &str) -> Vec<PathBuf> {
+    fn parse_raw_entries(&self, content: &str) -> Vec<String> {
         let mut entries = Vec::new();
-        let setenv_regex = Regex::new(r"setenv\s+PATH\s+([^#\n]+)").unwrap();
-        let set_regex = Regex::new(r"set\s+path\s*=\s*\((.*?)\)").unwrap();
 
-        for line in content.lines() {
+        for line in content.lines().filter(|line| !is_comment(line)) {
             let line = line.trim();
 
             // Handle setenv PATH ...
-            if let Some(cap) = setenv_regex.captures(line) {
+            if let Some(cap) = SETENV_REGEX.captures(line) {
                 if let Some(paths) = cap.get(1) {
-                    for path in paths
-                        .as_str()
-                        .split_whitespace()
-                        .next()
-                        .unwrap_or("")
-                        .split(':')
-                    {
-                        let expanded = shellexpand::tilde(path);
-                        entries.push(PathBuf::from(expanded.to_string()));
+                    if let Some(value) = quoting::split_words(paths.as_str()).first() {
+                        entries.extend(value.split(':').map(String::from));
                     }
                 }
             }
             // Handle set path = (...)
-            else if let Some(cap) = set_regex.captures(line) {
+            else if let Some(cap) = SET_REGEX.captures(line) {
                 if let Some(paths) = cap.get(1) {
-                    for path in paths.as_str().split_whitespace() {
-                        let expanded = shellexpand::tilde(path);
-                        entries.push(PathBuf::from(expanded.to_string()));
-                    }
+                    entries.extend(quoting::split_words(paths.as_str()));
                 }
             }
         }
@@ -64,15 +94,28 @@ impl ShellHandler for TcshHandler {
         entries
     }
 
-    fn format_path_export(&self, entries: &[PathBuf]) -> String {
-        let paths = entries
-            .iter()
-            .map(|p| p.to_string_lossy().to_string())
-            .collect::<Vec<_>>();
+    fn format_entries(&self, entries: &[ConfigEntry]) -> String {
+        // csh keeps PATH in sync with the path array, so extend only the array
+        if entries.iter().any(ConfigEntry::is_path_reference) {
+            let words = entries
+                .iter()
+                .map(|e| {
+                    if e.is_path_reference() {
+                        "$path".to_string()
+                    } else {
+                        quoting::tcsh_word(e)
+                    }
+                })
+                .collect::<Vec<_>>();
+
+            return format!("\n{}\nset path = ({})\n", BLOCK_HEADER, words.join(" "));
+        }
+
+        let paths = entries.iter().map(quoting::tcsh_word).collect::<Vec<_>>();
 
         format!(
-            "\n# Updated by pathmaster on {}\nset path = ({})\nsetenv PATH {}\n",
-            Local::now().format("%Y-%m-%d %H:%M:%S"),
+            "\n{}\nset path = ({})\nsetenv PATH {}\n",
+            BLOCK_HEADER,
             paths.join(" "),
             paths.join(":")
         )
@@ -80,10 +123,9 @@ impl ShellHandler for TcshHandler {
 
     fn detect_path_modifications(&self, content: &str) -> Vec<PathModification> {
         let mut modifications = Vec::new();
-        let path_regex = Regex::new(r"(setenv\s+PATH|set\s+path\s*=)").unwrap();
 
         for (idx, line) in content.lines().enumerate() {
-            if path_regex.is_match(line) {
+            if !is_comment(line) && MODIFICATION_REGEX.is_match(line) {
                 modifications.push(PathModification {
                     line_number: idx + 1,
                     content: line.to_string(),
@@ -95,29 +137,39 @@ impl ShellHandler for TcshHandler {
         modifications
     }
 
-    fn update_path_in_config(&self, content: &str, entries: &[PathBuf]) -> String {
-        let modifications = self.detect_path_modifications(content);
-
-        let mut updated_content = content
-            .lines()
-            .enumerate()
-            .filter(|(idx, _)| !modifications.iter().any(|m| m.line_number == idx + 1))
-            .map(|(_, line)| line)
-            .collect::<Vec<_>>()
-            .join("\n");
-
-        updated_content.push_str(&self.format_path_export(entries));
+    fn drop_in_file_name(&self) -> &'static str {
+        "path.csh"
+    }
 
-        updated_content
+    fn format_source_line(&self, file: &Path) -> String {
+        format!("if ( -f \"{0}\" ) source \"{0}\"", file.display())
     }
 }
 
 #[cfg(test)]
 mod tcsh_tests {
     use super::*;
-    use std::fs;
     use tempfile::TempDir;
 
+    #[test]
+    fn test_tcsh_config_detection() {
+        let temp_dir = TempDir::new().unwrap();
+        let home = temp_dir.path();
+        let mut handler = TcshHandler::new();
+        handler.home_dir = home.to_path_buf();
+
+        // Nothing exists yet: default to .tcshrc
+        assert_eq!(handler.get_config_path(), home.join(".tcshrc"));
+
+        // Only .cshrc exists, so tcsh reads it instead
+        fs::write(home.join(".cshrc"), "alias ll 'ls -l'\n").unwrap();
+        assert_eq!(handler.get_config_path(), home.join(".cshrc"));
+
+        // PATH declared in .login takes precedence over an rc file without it
+        fs::write(home.join(".login"), "setenv PATH /usr/bin:$PATH\n").unwrap();
+        assert_eq!(handler.get_config_path(), home.join(".login"));
+    }
+
     #[test]
     fn test_tcsh_path_parsing() {
         let handler = TcshHandler::new();
@@ -157,7 +209,7 @@ setenv PATH /usr/bin:/old/path
         fs::write(&config_path, initial_content).unwrap();
 
         let mut handler = TcshHandler::new();
-        handler.config_path = config_path.clone();
+        handler.config_path = Some(config_path.clone());
 
         let new_entries = vec![PathBuf::from("/usr/bin"), PathBuf::from("/usr/local/bin")];
 
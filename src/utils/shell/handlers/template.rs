@@ -0,0 +1,197 @@
+//! Handlers for shells defined in the config.
+//!
+//! A `[[shells]]` entry names the shell, its config file and the line that
+//! sets its PATH, with `{path}` where the entries go. The handler writes
+//! that line and finds earlier ones by matching the text around `{path}`.
+
+use super::{is_comment, ShellHandler};
+use crate::utils;
+use crate::utils::root;
+use crate::utils::shell::block::BLOCK_HEADER;
+use crate::utils::shell::continuation;
+use crate::utils::shell::spelling;
+use crate::utils::shell::types::{ConfigEntry, ModificationType, PathModification, ShellType};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::path::PathBuf;
+
+/// Placeholder for the PATH value in a [`ShellDefinition`] template
+pub const PATH_PLACEHOLDER: &str = "{path}";
+
+/// A shell described in the config by the line that sets its PATH
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ShellDefinition {
+    /// Shell name, matched against `$SHELL` and the running shell process
+    pub name: String,
+    /// Config file PATH is written to; `~` is expanded
+    pub config_file: String,
+    /// Line setting PATH, with `{path}` where the entries go, e.g. `set-env PATH "{path}"`
+    pub template: String,
+    /// Separator between entries
+    #[serde(default = "default_separator")]
+    pub separator: String,
+    /// Built-in shell whose conditional syntax the config uses
+    #[serde(default = "default_syntax")]
+    pub syntax: ShellType,
+}
+
+fn default_separator() -> String {
+    ":".to_string()
+}
+
+fn default_syntax() -> ShellType {
+    ShellType::Generic
+}
+
+/// Handler for a shell defined by a [`ShellDefinition`]
+pub struct TemplateHandler {
+    definition: ShellDefinition,
+    pattern: Regex,
+}
+
+impl TemplateHandler {
+    /// Creates a handler for a definition
+    ///
+    /// # Returns
+    /// * `Err(String)` - If the template has no `{path}` placeholder
+    pub fn new(definition: ShellDefinition) -> Result<Self, String> {
+        let (prefix, suffix) = definition
+            .template
+            .split_once(PATH_PLACEHOLDER)
+            .ok_or_else(|| {
+                format!(
+                    "template for shell '{}' has no {} placeholder",
+                    definition.name, PATH_PLACEHOLDER
+                )
+            })?;
+        let pattern = Regex::new(&format!(
+            "^{}(.*){}$",
+            regex::escape(prefix.trim_start()),
+            regex::escape(suffix.trim_end())
+        ))
+        .map_err(|e| e.to_string())?;
+        Ok(Self {
+            definition,
+            pattern,
+        })
+    }
+}
+
+impl ShellHandler for TemplateHandler {
+    fn get_shell_type(&self) -> ShellType {
+        self.definition.syntax
+    }
+
+    fn name(&self) -> String {
+        self.definition.name.clone()
+    }
+
+    fn get_config_path(&self) -> PathBuf {
+        root::rooted(utils::expand_path(&self.definition.config_file))
+    }
+
+    fn parse_raw_entries(&self, content: &str) -> Vec<String> {
+        continuation::logical_lines(content)
+            .iter()
+            .filter(|line| !is_comment(&line.text))
+            .filter_map(|line| self.pattern.captures(line.text.trim()))
+            .filter_map(|cap| cap.get(1))
+            .flat_map(|value| {
+                value
+                    .as_str()
+                    .split(self.definition.separator.as_str())
+                    .map(String::from)
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    fn format_entries(&self, entries: &[ConfigEntry]) -> String {
+        // Variable syntax differs between shells, so references are written expanded
+        let paths = entries
+            .iter()
+            .map(|entry| {
+                spelling::expand_entry(&entry.to_string())
+                    .map(|path| path.to_string_lossy().into_owned())
+                    .unwrap_or_else(|| entry.to_string())
+            })
+            .collect::<Vec<_>>()
+            .join(&self.definition.separator);
+
+        format!(
+            "\n{}\n{}\n",
+            BLOCK_HEADER,
+            self.definition.template.replace(PATH_PLACEHOLDER, &paths)
+        )
+    }
+
+    fn detect_path_modifications(&self, content: &str) -> Vec<PathModification> {
+        content
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !is_comment(line) && self.pattern.is_match(line.trim()))
+            .map(|(index, line)| PathModification {
+                line_number: index + 1,
+                content: line.to_string(),
+                modification_type: ModificationType::Assignment,
+            })
+            .collect()
+    }
+
+    fn update_drop_in(&self, _entries: &[PathBuf]) -> io::Result<()> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!(
+                "drop-in mode isn't supported for custom shell '{}'",
+                self.definition.name
+            ),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn handler() -> TemplateHandler {
+        TemplateHandler::new(ShellDefinition {
+            name: "elvish".to_string(),
+            config_file: "~/.config/elvish/rc.elv".to_string(),
+            template: "set paths = [{path}]".to_string(),
+            separator: " ".to_string(),
+            syntax: ShellType::Generic,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_template_round_trip() {
+        let handler = handler();
+        let content = "# set paths = [/old]\nset paths = [/usr/bin /opt/bin]\necho hi\n";
+
+        assert_eq!(
+            handler.parse_raw_entries(content),
+            vec!["/usr/bin", "/opt/bin"]
+        );
+        assert_eq!(handler.detect_path_modifications(content).len(), 1);
+
+        let updated = handler.update_path_in_config(
+            content,
+            &[PathBuf::from("/usr/local/bin"), PathBuf::from("/usr/bin")],
+        );
+        assert!(updated.starts_with("# set paths = [/old]\n"));
+        assert!(updated.contains("set paths = [/usr/local/bin /usr/bin]\n"));
+        assert!(updated.contains("echo hi\n"));
+    }
+
+    #[test]
+    fn test_template_requires_placeholder() {
+        let definition = ShellDefinition {
+            template: "set paths = []".to_string(),
+            ..handler().definition
+        };
+        assert!(TemplateHandler::new(definition).is_err());
+    }
+}
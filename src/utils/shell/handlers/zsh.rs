@@ -1,8 +1,124 @@
-use super::ShellHandler;
-use crate::utils::shell::types::{ModificationType, PathModification, ShellType};
-use chrono::Local;
+use super::{is_comment, ShellHandler};
+use crate::config::{self, ExportStyle};
+use crate::msg;
+use crate::utils::check_mode;
+use crate::utils::prompt;
+use crate::utils::shell::block::BLOCK_HEADER;
+use crate::utils::shell::locations;
+use crate::utils::shell::quoting;
+use crate::utils::shell::spelling;
+use crate::utils::shell::text_format::TextFormat;
+use crate::utils::shell::types::{
+    ConfigEntry, DeclaredEntry, EntryComment, ModificationType, PathModification, ShellType,
+};
+use lazy_static::lazy_static;
 use regex::Regex;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+/// Makes zsh drop duplicate entries from `path`, and so from PATH
+pub const UNIQUE_PATH_DECLARATION: &str = "typeset -U path PATH";
+
+lazy_static! {
+    static ref MODIFICATION_REGEX: Regex = Regex::new(r"(?m)^export PATH=").unwrap();
+    /// `path=(...)` or `path+=(...)` anywhere in a line, e.g. after a guard
+    static ref PATH_ARRAY_STATEMENT_REGEX: Regex =
+        Regex::new(r"(?:^|[;&|{]|\bthen)\s*path\+?=\(([^)]*)\)").unwrap();
+    static ref UNIQUE_PATH_REGEX: Regex =
+        Regex::new(r"\b(?:typeset|declare)\s+-\w*U\w*\s+(?:\w+\s+)*path\b").unwrap();
+}
+
+/// Returns whether a zsh config makes `path` unique with `typeset -U path`
+pub fn declares_unique_path(content: &str) -> bool {
+    content
+        .lines()
+        .any(|line| !is_comment(line) && UNIQUE_PATH_REGEX.is_match(line))
+}
+
+/// A `path=(...)` array, which may span several lines
+struct PathArray {
+    /// Zero-based lines the array starts and ends on
+    start: usize,
+    end: usize,
+    words: Vec<ArrayWord>,
+}
+
+/// One entry of a `path=(...)` array
+struct ArrayWord {
+    /// Zero-based line the entry is on
+    line: usize,
+    raw: String,
+    /// Comment after the entry, when it's the last one on its line
+    comment: Option<String>,
+}
+
+/// Finds the `path=(...)` arrays in a zsh config, with one entry per line
+/// and comments allowed as in:
+///
+/// ```zsh
+/// path=(
+///     $HOME/bin  # my scripts
+///     $path
+/// ) && export PATH
+/// ```
+fn path_arrays(content: &str) -> Vec<PathArray> {
+    let mut arrays = Vec::new();
+    let mut current: Option<PathArray> = None;
+    for (idx, line) in content.lines().enumerate() {
+        let (code, comment) = quoting::split_comment(line);
+        let text = if current.is_some() {
+            code.trim()
+        } else if let Some(rest) = code.trim_start().strip_prefix("path=(") {
+            current = Some(PathArray {
+                start: idx,
+                end: idx,
+                words: Vec::new(),
+            });
+            rest
+        } else {
+            continue;
+        };
+        let Some(array) = current.as_mut() else {
+            continue;
+        };
+        array.end = idx;
+
+        let close = closing_paren(text);
+        let words = quoting::split_words(&text[..close.unwrap_or(text.len())]);
+        let count = words.len();
+        array
+            .words
+            .extend(words.into_iter().enumerate().map(|(i, raw)| ArrayWord {
+                line: idx,
+                raw,
+                comment: comment.filter(|_| i + 1 == count).map(str::to_string),
+            }));
+        if close.is_some() {
+            arrays.extend(current.take());
+        }
+    }
+    arrays
+}
+
+/// Returns the position of the first `)` outside quotes
+fn closing_paren(text: &str) -> Option<usize> {
+    let mut in_single = false;
+    let mut in_double = false;
+    let mut escaped = false;
+    for (idx, c) in text.char_indices() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        match c {
+            '\\' if !in_single => escaped = true,
+            '\'' if !in_double => in_single = !in_single,
+            '"' if !in_single => in_double = !in_double,
+            ')' if !in_single && !in_double => return Some(idx),
+            _ => {}
+        }
+    }
+    None
+}
 
 pub struct ZshHandler {
     config_path: PathBuf,
@@ -10,21 +126,19 @@ pub struct ZshHandler {
 
 impl ZshHandler {
     pub fn new() -> Self {
-        let home_dir = dirs_next::home_dir().unwrap_or_else(|| PathBuf::from("/"));
         Self {
-            config_path: home_dir.join(".zshrc"),
+            config_path: locations::zsh_dotdir().join(".zshrc"),
         }
     }
 
     fn find_path_arrays(&self, content: &str) -> Vec<PathModification> {
-        let path_array_regex = Regex::new(r"(?m)^path=\((.*?)\)").unwrap();
-
-        path_array_regex
-            .captures_iter(content)
-            .enumerate()
-            .map(|(idx, cap)| PathModification {
+        let lines: Vec<&str> = content.lines().collect();
+        path_arrays(content)
+            .iter()
+            .flat_map(|array| array.start..=array.end)
+            .map(|idx| PathModification {
                 line_number: idx + 1,
-                content: cap[0].to_string(),
+                content: lines[idx].to_string(),
                 modification_type: ModificationType::ArrayModification,
             })
             .collect()
@@ -40,48 +154,69 @@ impl ShellHandler for ZshHandler {
         self.config_path.clone()
     }
 
-    fn parse_path_entries(&self, content: &str) -> Vec<PathBuf> {
-        let mut entries = Vec::new();
+    fn parse_raw_entries(&self, content: &str) -> Vec<String> {
+        path_arrays(content)
+            .into_iter()
+            .next()
+            .map(|array| array.words.into_iter().map(|word| word.raw).collect())
+            .unwrap_or_default()
+    }
 
-        if let Some(path_array) = content
-            .lines()
-            .find(|line| line.trim().starts_with("path=("))
-        {
-            let paths = path_array
-                .trim()
-                .trim_start_matches("path=(")
-                .trim_end_matches(')')
-                .split_whitespace();
-
-            for path in paths {
-                let expanded = shellexpand::tilde(path);
-                entries.push(PathBuf::from(expanded.to_string()));
-            }
-        }
+    fn parse_entry_comments(&self, content: &str) -> Vec<EntryComment> {
+        path_arrays(content)
+            .into_iter()
+            .flat_map(|array| array.words)
+            .filter_map(|word| {
+                word.comment.map(|comment| EntryComment {
+                    raw: word.raw,
+                    comment,
+                })
+            })
+            .collect()
+    }
 
-        entries
+    fn parse_declared_entries(&self, content: &str) -> Vec<DeclaredEntry> {
+        path_arrays(content)
+            .into_iter()
+            .flat_map(|array| array.words)
+            .map(|word| DeclaredEntry {
+                line_number: word.line + 1,
+                raw: word.raw,
+            })
+            .collect()
     }
 
-    fn format_path_export(&self, entries: &[PathBuf]) -> String {
-        let paths = entries
-            .iter()
-            .map(|p| p.to_string_lossy().to_string())
-            .collect::<Vec<_>>()
-            .join(" ");
+    fn format_entries(&self, entries: &[ConfigEntry]) -> String {
+        let words = entries.iter().map(|e| {
+            // The path array mirrors PATH, so extend it rather than $PATH
+            if e.is_path_reference() {
+                "$path".to_string()
+            } else {
+                quoting::zsh_word(e)
+            }
+        });
+
+        // Comments need one entry per line
+        let paths = if entries.iter().any(|e| e.comment.is_some()) {
+            let lines = words
+                .zip(entries)
+                .map(|(word, entry)| match &entry.comment {
+                    Some(comment) => format!("    {}  # {}\n", word, comment),
+                    None => format!("    {}\n", word),
+                })
+                .collect::<String>();
+            format!("\n{}", lines)
+        } else {
+            words.collect::<Vec<_>>().join(" ")
+        };
 
-        format!(
-            "\n# Updated by pathmaster on {}\npath=({}) && export PATH\n",
-            Local::now().format("%Y-%m-%d %H:%M:%S"),
-            paths
-        )
+        format!("\n{}\npath=({}) && export PATH\n", BLOCK_HEADER, paths)
     }
 
     fn detect_path_modifications(&self, content: &str) -> Vec<PathModification> {
         let mut modifications = self.find_path_arrays(content);
-
-        let path_regex = Regex::new(r"(?m)^export PATH=").unwrap();
         for (idx, line) in content.lines().enumerate() {
-            if path_regex.is_match(line) {
+            if !is_comment(line) && MODIFICATION_REGEX.is_match(line) {
                 modifications.push(PathModification {
                     line_number: idx + 1,
                     content: line.to_string(),
@@ -93,31 +228,132 @@ impl ShellHandler for ZshHandler {
         modifications
     }
 
-    fn update_path_in_config(&self, content: &str, entries: &[PathBuf]) -> String {
-        let modifications = self.detect_path_modifications(content);
-
-        let updated_content = content
+    fn entries_declared_before(
+        &self,
+        content: &str,
+        modifications: &[PathModification],
+        line: usize,
+    ) -> Vec<PathBuf> {
+        let replaced = |idx: usize| modifications.iter().any(|m| m.line_number == idx + 1);
+        let mut entries = Vec::new();
+        for (_, text) in content
             .lines()
             .enumerate()
-            .filter(|(idx, line)| {
-                !modifications.iter().any(|m| m.line_number == idx + 1)
-                    && !line.contains("/old/path") // Explicitly filter out old paths
-            })
-            .map(|(_, line)| line)
-            .collect::<Vec<_>>()
-            .join("\n");
+            .take(line)
+            .filter(|(idx, text)| !replaced(*idx) && !is_comment(text))
+        {
+            for captures in PATH_ARRAY_STATEMENT_REGEX.captures_iter(text) {
+                for word in quoting::split_words(&captures[1]) {
+                    if let Some(entry) = spelling::expand_entry(&word) {
+                        if !entries.contains(&entry) {
+                            entries.push(entry);
+                        }
+                    }
+                }
+            }
+        }
+        entries
+    }
 
-        // Add new PATH configuration
-        updated_content + &self.format_path_export(entries)
+    /// Offers to add `typeset -U path PATH` in relative export style, where
+    /// `path=(... $path ...)` adds its entries again each time the file is sourced
+    fn offer_config_fixes(&self, content: String, config_path: &Path) -> String {
+        if config::get_config().export_style != ExportStyle::Relative
+            || declares_unique_path(&content)
+        {
+            return content;
+        }
+        println!("{}", msg!("zsh-no-unique-path", file = config_path));
+        if check_mode::is_check_mode()
+            || !prompt::confirm(&msg!(
+                "zsh-add-unique-path",
+                declaration = UNIQUE_PATH_DECLARATION,
+                file = config_path
+            ))
+        {
+            return content;
+        }
+        add_unique_path(&content)
+    }
+
+    fn drop_in_file_name(&self) -> &'static str {
+        "path.zsh"
     }
 }
 
+/// Adds `typeset -U path PATH` at the top of a config, after any shebang
+fn add_unique_path(content: &str) -> String {
+    let (format, content) = TextFormat::detect(content);
+    let declaration = format!(
+        "# Added by pathmaster: drop duplicate PATH entries\n{}\n",
+        UNIQUE_PATH_DECLARATION
+    );
+    let updated = match content.strip_prefix("#!") {
+        Some(_) => match content.split_once('\n') {
+            Some((shebang, rest)) => format!("{}\n{}{}", shebang, declaration, rest),
+            None => format!("{}\n{}", content, declaration),
+        },
+        None => format!("{}{}", declaration, content),
+    };
+    format.restore(&updated)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::utils::shell::block;
     use std::fs;
     use tempfile::TempDir;
 
+    #[test]
+    fn test_zsh_commented_exports_ignored() {
+        let handler = ZshHandler::new();
+        let content =
+            "#export PATH=/old/bin\n# DISABLED by pathmaster: path=(/disabled)\npath=(/usr/bin)\n";
+
+        let modifications = handler.detect_path_modifications(content);
+        assert_eq!(modifications.len(), 1);
+        assert_eq!(modifications[0].line_number, 3);
+    }
+
+    #[test]
+    fn test_declares_unique_path() {
+        assert!(declares_unique_path("typeset -U path PATH\n"));
+        assert!(declares_unique_path("typeset -gU PATH path\n"));
+        assert!(declares_unique_path("  declare -aU path\n"));
+        assert!(!declares_unique_path("# typeset -U path\n"));
+        assert!(!declares_unique_path("typeset -U fpath\n"));
+        assert!(!declares_unique_path("typeset -x PATH\n"));
+    }
+
+    #[test]
+    fn test_entries_declared_before_block() {
+        let handler = ZshHandler::new();
+        let content = "[[ -d /pm-test/early ]] && path=(/pm-test/early $path)\n\
+                       path=(/usr/bin $path)\n\
+                       if true; then path+=(/pm-test/late); fi\n";
+        let modifications = handler.replaceable_modifications(content);
+        assert_eq!(modifications.len(), 1);
+
+        let position = block::block_position(content, &modifications);
+        assert_eq!(
+            handler.entries_declared_before(content, &modifications, position),
+            vec![PathBuf::from("/pm-test/early")]
+        );
+    }
+
+    #[test]
+    fn test_add_unique_path() {
+        assert_eq!(
+            add_unique_path("path=(/usr/bin)\n"),
+            "# Added by pathmaster: drop duplicate PATH entries\ntypeset -U path PATH\npath=(/usr/bin)\n"
+        );
+        let updated = add_unique_path("#!/bin/zsh\r\npath=(/usr/bin)\r\n");
+        assert!(updated.starts_with("#!/bin/zsh\r\n# Added by pathmaster"));
+        assert!(updated.contains("typeset -U path PATH\r\npath=(/usr/bin)\r\n"));
+        assert!(declares_unique_path(&updated));
+    }
+
     #[test]
     fn test_zsh_path_parsing() {
         let handler = ZshHandler::new();
@@ -173,4 +409,58 @@ export PATH="/another/old/path:$PATH"
         assert!(updated_content.contains("path=("));
         assert!(updated_content.contains("export PATH"));
     }
+
+    #[test]
+    fn test_zsh_update_keeps_home_references() {
+        let handler = ZshHandler::new();
+        let home = locations::real_home_dir();
+        let content = "path=(~/bin ${HOME}/.cargo/bin /usr/bin)\n";
+
+        let entries = handler.parse_path_entries(content);
+        assert_eq!(entries[0], home.join("bin"));
+        assert_eq!(entries[1], home.join(".cargo/bin"));
+
+        let mut new_entries = entries.clone();
+        new_entries.push(PathBuf::from("/usr/local/bin"));
+
+        let updated = handler.update_path_in_config(content, &new_entries);
+        assert!(updated.contains("path=($HOME/bin ${HOME}/.cargo/bin /usr/bin /usr/local/bin)"));
+    }
+
+    #[test]
+    fn test_zsh_multi_line_array_keeps_comments() {
+        let handler = ZshHandler::new();
+        let content = "path=(\n    /pm-test/tools  # work tools\n    # kept for now\n    /pm-test/old /usr/bin\n) && export PATH\necho done\n";
+
+        assert_eq!(handler.detect_path_modifications(content).len(), 5);
+        assert_eq!(
+            handler.parse_raw_entries(content),
+            vec!["/pm-test/tools", "/pm-test/old", "/usr/bin"]
+        );
+        assert_eq!(
+            handler
+                .parse_declared_entries(content)
+                .iter()
+                .map(|entry| entry.line_number)
+                .collect::<Vec<_>>(),
+            vec![2, 4, 4]
+        );
+
+        let updated = handler.update_path_in_config(
+            content,
+            &[PathBuf::from("/pm-test/tools"), PathBuf::from("/usr/bin")],
+        );
+        assert!(updated.contains(
+            "path=(\n    /pm-test/tools  # work tools\n    /usr/bin\n) && export PATH\n"
+        ));
+        assert!(!updated.contains("/pm-test/old"));
+        assert!(updated.ends_with("echo done\n"));
+        assert_eq!(
+            handler.parse_entry_comments(&updated),
+            vec![EntryComment {
+                raw: "/pm-test/tools".to_string(),
+                comment: "work tools".to_string(),
+            }]
+        );
+    }
 }
@@ -0,0 +1,149 @@
+//! Resolution of shell configuration locations.
+//!
+//! Shells don't all keep their startup files in `$HOME`. This module
+//! centralizes the lookup rules so every handler agrees on where a
+//! shell actually reads its configuration from:
+//! - zsh reads its dotfiles from `$ZDOTDIR` when set
+//! - fish reads `config.fish` from `$XDG_CONFIG_HOME/fish`
+//! - interactive ksh reads the file named by `$ENV`
+//! - everything else falls back to the home directory
+//!
+//! pathmaster's own config, cache and state directories follow the XDG
+//! Base Directory specification as well. All of these are looked up under
+//! the `--root` directory when one is set.
+
+use crate::utils::root;
+use std::env;
+use std::path::PathBuf;
+
+/// Returns the directory holding the user's dotfiles, falling back to `/` when unknown.
+///
+/// This is under the `--root` directory when one is set.
+pub fn home_dir() -> PathBuf {
+    root::rooted(real_home_dir())
+}
+
+/// Returns the user's home directory as shells see it, ignoring `--root`.
+///
+/// PATH entries and `$HOME` in shell configs refer to this directory.
+pub fn real_home_dir() -> PathBuf {
+    dirs_next::home_dir().unwrap_or_else(|| PathBuf::from("/"))
+}
+
+/// Returns the XDG configuration directory.
+///
+/// Per the XDG Base Directory specification, relative values of
+/// `$XDG_CONFIG_HOME` are invalid and must be ignored.
+pub fn xdg_config_home() -> PathBuf {
+    rooted_env_dir("XDG_CONFIG_HOME").unwrap_or_else(|| home_dir().join(".config"))
+}
+
+/// Returns the XDG cache directory.
+///
+/// Relative values of `$XDG_CACHE_HOME` are ignored, as for `$XDG_CONFIG_HOME`.
+pub fn xdg_cache_home() -> PathBuf {
+    rooted_env_dir("XDG_CACHE_HOME").unwrap_or_else(|| home_dir().join(".cache"))
+}
+
+/// Returns the XDG state directory, used for logs that persist across runs.
+///
+/// Relative values of `$XDG_STATE_HOME` are ignored, as for `$XDG_CONFIG_HOME`.
+pub fn xdg_state_home() -> PathBuf {
+    rooted_env_dir("XDG_STATE_HOME").unwrap_or_else(|| home_dir().join(".local/state"))
+}
+
+/// Returns the XDG data directory, where tools like flatpak and pnpm install.
+///
+/// Relative values of `$XDG_DATA_HOME` are ignored, as for `$XDG_CONFIG_HOME`.
+pub fn xdg_data_home() -> PathBuf {
+    rooted_env_dir("XDG_DATA_HOME").unwrap_or_else(|| home_dir().join(".local/share"))
+}
+
+/// Returns the directory zsh reads its startup files from.
+///
+/// This is `$ZDOTDIR` when set to an absolute path, otherwise `$HOME`.
+pub fn zsh_dotdir() -> PathBuf {
+    rooted_env_dir("ZDOTDIR").unwrap_or_else(home_dir)
+}
+
+/// Returns the startup file named by `$ENV`, which ksh reads for interactive shells.
+///
+/// ksh performs parameter expansion on `$ENV`, so values like
+/// `$HOME/.kshrc` are expanded. Relative results are ignored.
+pub fn ksh_env_file() -> Option<PathBuf> {
+    let value = env::var("ENV").ok()?;
+    let expanded = shellexpand::full(&value).ok()?.to_string();
+    let path = PathBuf::from(expanded);
+    if path.is_absolute() {
+        Some(root::rooted(path))
+    } else {
+        None
+    }
+}
+
+/// Returns the directory fish reads `config.fish` from.
+pub fn fish_config_dir() -> PathBuf {
+    xdg_config_home().join("fish")
+}
+
+/// Reads an environment variable naming a directory pathmaster reads or
+/// writes files in, which is under the `--root` directory when one is set.
+fn rooted_env_dir(name: &str) -> Option<PathBuf> {
+    absolute_env_dir(name).map(root::rooted)
+}
+
+/// Reads an environment variable as a directory, ignoring empty or relative values.
+pub fn absolute_env_dir(name: &str) -> Option<PathBuf> {
+    let value = env::var_os(name)?;
+    let path = match value.to_str() {
+        Some(value) => PathBuf::from(shellexpand::tilde(value).to_string()),
+        None => PathBuf::from(value),
+    };
+    if path.is_absolute() {
+        Some(path)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serial_test::serial;
+
+    /// Runs `f` with `name` set to `value`, restoring the previous value afterwards
+    fn with_env<F: FnOnce()>(name: &str, value: Option<&str>, f: F) {
+        let original = env::var_os(name);
+        match value {
+            Some(v) => env::set_var(name, v),
+            None => env::remove_var(name),
+        }
+        f();
+        match original {
+            Some(v) => env::set_var(name, v),
+            None => env::remove_var(name),
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn test_zdotdir_respected() {
+        with_env("ZDOTDIR", Some("/tmp/zdot"), || {
+            assert_eq!(zsh_dotdir(), PathBuf::from("/tmp/zdot"));
+        });
+        with_env("ZDOTDIR", None, || {
+            assert_eq!(zsh_dotdir(), home_dir());
+        });
+    }
+
+    #[test]
+    #[serial]
+    fn test_xdg_config_home() {
+        with_env("XDG_CONFIG_HOME", Some("/tmp/xdg"), || {
+            assert_eq!(fish_config_dir(), PathBuf::from("/tmp/xdg/fish"));
+        });
+        with_env("XDG_CONFIG_HOME", Some("relative/dir"), || {
+            assert_eq!(xdg_config_home(), home_dir().join(".config"));
+        });
+    }
+}
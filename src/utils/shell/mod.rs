@@ -1,13 +1,396 @@
+use crate::config::{self, WriteMode};
+use crate::msg;
+use crate::utils;
+use crate::utils::check_mode;
+use crate::utils::conditional::{self, ConditionalEntry, Machine};
+use crate::utils::dotfiles;
+use crate::utils::hooks::{self, HookEvent};
+use crate::utils::rules;
+use crate::utils::verbose;
+use std::fs;
 use std::io;
 use std::path::PathBuf;
 
+pub mod block;
+pub mod conditionals;
+pub mod continuation;
+pub mod emit;
 pub mod factory;
+pub mod gui;
 pub mod handlers;
+pub mod locations;
+pub mod posix;
+pub mod quoting;
+pub mod registry;
+pub mod sources;
+pub mod spelling;
+pub mod text_format;
+pub mod tmux;
+pub mod transaction;
 pub mod types;
+pub mod written;
 
 pub use self::handlers::ShellHandler;
+use self::transaction::Transaction;
+use self::types::DeclaredPath;
 
+/// Writes PATH entries to the current shell's configuration.
+///
+/// In env-only mode no file is written; the hooks still run.
+///
+/// Bytes of entries that aren't valid UTF-8 are written as `printf`
+/// substitutions (see [`quoting`]), so the config sets the same entries.
 pub fn update_shell_config(entries: &[PathBuf]) -> io::Result<()> {
-    let handler = factory::get_shell_handler();
-    handler.update_config(entries)
+    let settings = config::get_config();
+    let conditioned = apply_conditionals(&settings, entries);
+    let ordered = apply_rules(&settings, &conditioned);
+    let entries = ordered.as_slice();
+
+    if settings.all_shells && settings.config_file.is_some() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "--config-file can't be combined with --all-shells",
+        ));
+    }
+
+    if check_mode::is_check_mode() {
+        return preview_files(&settings, entries);
+    }
+
+    let before = utils::initial_path_entries();
+    if let Err(e) = hooks::run_hook(HookEvent::PreUpdate, before, entries) {
+        // The change was cancelled, so PATH goes back to what it was
+        utils::set_path_entries(before);
+        return Err(e);
+    }
+
+    if settings.system {
+        utils::system_env::write_path(entries)?;
+    } else if settings.env_only {
+        if verbose::is_verbose() {
+            println!("{}", msg!("shell-env-only"));
+        }
+    } else if let Err(e) = update_files(&settings, entries) {
+        // Every file was rolled back, so PATH goes back to what it was too
+        utils::set_path_entries(before);
+        return Err(e);
+    }
+
+    if settings.tmux && !settings.system {
+        match tmux::update_environment(entries) {
+            Ok(true) => println!("{}", msg!("shell-tmux-updated")),
+            Ok(false) => {
+                if verbose::is_verbose() {
+                    println!("{}", msg!("shell-tmux-none"));
+                }
+            }
+            Err(e) => eprintln!("{}", msg!("shell-tmux-failed", error = e)),
+        }
+    }
+
+    if let Err(e) = hooks::run_hook(HookEvent::PostUpdate, before, entries) {
+        eprintln!("{}", msg!("warning", warning = e));
+    }
+    Ok(())
+}
+
+/// Reads the entries the current shell's config declares in the PATH
+/// declarations pathmaster rewrites, in PATH order.
+///
+/// These can differ from PATH when the config was edited by hand since the
+/// shell started. In env-only and system mode no shell config is written,
+/// so there are none.
+pub fn declared_entries() -> Vec<PathBuf> {
+    declared_path().entries
+}
+
+/// Reads the entries like [`declared_entries`], along with how many of them
+/// the config puts ahead of the existing PATH
+pub fn declared_path() -> DeclaredPath {
+    let settings = config::get_config();
+    if settings.env_only || settings.system {
+        return DeclaredPath::default();
+    }
+    let handler = factory::get_shell_handler(settings.shell);
+    handler
+        .modified_files()
+        .first()
+        .and_then(|file| fs::read_to_string(file).ok())
+        .map(|content| handler.managed_path(&content))
+        .unwrap_or_default()
+}
+
+/// Adds conditional entries whose conditions hold on this machine and
+/// removes those whose conditions don't, updating the environment to match.
+fn apply_conditionals(settings: &config::Config, entries: &[PathBuf]) -> Vec<PathBuf> {
+    if settings.conditional_entries.is_empty() {
+        return entries.to_vec();
+    }
+
+    // Entries this command removed stay out rather than being re-added at once
+    let withdrawn = conditional::withdrawn(
+        &settings.conditional_entries,
+        utils::initial_path_entries(),
+        entries,
+    );
+    for conditional in &withdrawn {
+        println!(
+            "{}",
+            msg!("conditional-withdrawn", entry = conditional.entry)
+        );
+    }
+    let conditionals: Vec<ConditionalEntry> = settings
+        .conditional_entries
+        .iter()
+        .filter(|conditional| !withdrawn.contains(conditional))
+        .cloned()
+        .collect();
+    let (updated, outcome) = conditional::apply(&conditionals, entries, &Machine::current());
+    for dir in &outcome.added {
+        println!("{}", msg!("conditional-added", path = dir));
+    }
+    for dir in &outcome.removed {
+        println!("{}", msg!("conditional-removed", path = dir));
+    }
+    if updated != entries {
+        utils::set_path_entries(&updated);
+    }
+    updated
+}
+
+/// Checks the configured ordering rules against the entries being written.
+///
+/// With `enforce-rules`, entries are reordered to satisfy the rules and the
+/// environment is updated to match; otherwise violations are only reported.
+fn apply_rules(settings: &config::Config, entries: &[PathBuf]) -> Vec<PathBuf> {
+    if settings.rules.is_empty() {
+        return entries.to_vec();
+    }
+
+    let mut entries = entries.to_vec();
+    if settings.enforce_rules {
+        let ordered = rules::enforce(&settings.rules, &entries);
+        if ordered != entries {
+            println!("{}", msg!("rules-reordered"));
+            utils::set_path_entries(&ordered);
+            entries = ordered;
+        }
+    }
+
+    for rule in rules::violations(&settings.rules, &entries) {
+        eprintln!("{}", msg!("rules-not-met", rule = rule.to_string()));
+    }
+    entries
+}
+
+/// A shell whose config gets updated
+struct ShellTarget {
+    handler: Box<dyn ShellHandler>,
+    /// Only add the line sourcing the drop-in file, which an earlier shell
+    /// already writes
+    source_line_only: bool,
+}
+
+impl ShellTarget {
+    fn new(handler: Box<dyn ShellHandler>) -> Self {
+        Self {
+            handler,
+            source_line_only: false,
+        }
+    }
+
+    /// The files this update writes
+    fn files(&self) -> Vec<PathBuf> {
+        let files = self.handler.modified_files();
+        if self.source_line_only {
+            files.into_iter().skip(1).collect()
+        } else {
+            files
+        }
+    }
+
+    fn update(&self, entries: &[PathBuf]) -> io::Result<()> {
+        if self.source_line_only {
+            self.handler.add_drop_in_source_line()
+        } else {
+            self.handler.update_config(entries)
+        }
+    }
+}
+
+/// Returns the shells to update: every configured one with `--all-shells`,
+/// otherwise the current one
+fn shell_targets(
+    settings: &config::Config,
+    report: &mut Vec<String>,
+) -> io::Result<Vec<ShellTarget>> {
+    if settings.all_shells {
+        select_all_shells(settings, report)
+    } else {
+        Ok(vec![ShellTarget::new(factory::get_shell_handler(
+            settings.shell,
+        ))])
+    }
+}
+
+/// Writes PATH to the shell configs and, with `gui`, the desktop session.
+///
+/// Every file that may be written is snapshotted first; if any target
+/// fails, all of them are rolled back.
+fn update_files(settings: &config::Config, entries: &[PathBuf]) -> io::Result<()> {
+    let mut report = Vec::new();
+    let targets = shell_targets(settings, &mut report)?;
+
+    let mut modified: Vec<PathBuf> = targets.iter().flat_map(ShellTarget::files).collect();
+    let mut files = modified.clone();
+    if settings.gui {
+        files.extend(gui::session_files());
+    }
+    let transaction = Transaction::begin(&files)?;
+
+    for target in &targets {
+        let name = format!("the {} config", target.handler.name());
+        transaction.step(&name, || target.update(entries))?;
+    }
+    if settings.gui {
+        modified.extend(transaction.step("the desktop session PATH", || {
+            gui::update_session_path(entries)
+        })?);
+    }
+
+    for target in targets.iter().filter(|target| !target.source_line_only) {
+        if let Err(e) = written::record(target.handler.as_ref()) {
+            eprintln!("{}", msg!("shell-record-failed", error = e));
+        }
+    }
+
+    if settings.all_shells {
+        println!("{}", msg!("shell-all-updated", count = targets.len()));
+        for line in report {
+            println!("{}", line);
+        }
+    }
+    dotfiles::commit_changes(&modified);
+    Ok(())
+}
+
+/// Works out the shell config or /etc/environment changes without writing
+/// them, for check mode. Hooks, the desktop session and tmux are left alone.
+fn preview_files(settings: &config::Config, entries: &[PathBuf]) -> io::Result<()> {
+    if settings.system {
+        return utils::system_env::write_path(entries);
+    }
+    if settings.env_only {
+        return Ok(());
+    }
+
+    for target in shell_targets(settings, &mut Vec::new())? {
+        target.update(entries)?;
+    }
+    Ok(())
+}
+
+/// Picks the shells to update with `--all-shells`.
+///
+/// A file shared by several shells, such as a sourced PATH file, is written
+/// only by the first of them. In drop-in mode shells sharing a drop-in
+/// file, such as bash and ksh with `path.sh`, still get the line sourcing
+/// it in their own rc file.
+///
+/// A line describing each shell found is added to `report`.
+fn select_all_shells(
+    settings: &config::Config,
+    report: &mut Vec<String>,
+) -> io::Result<Vec<ShellTarget>> {
+    let handlers = factory::configured_shell_handlers();
+    if handlers.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            "No shell configuration files found",
+        ));
+    }
+
+    let mut claimed: Vec<PathBuf> = Vec::new();
+    let mut selected = Vec::new();
+    for handler in handlers {
+        let files = handler.modified_files();
+        let Some(shared) = files.iter().find(|file| claimed.contains(file)).cloned() else {
+            report.push(format!("  {}: {}", handler.name(), files[0].display()));
+            claimed.extend(files);
+            selected.push(ShellTarget::new(handler));
+            continue;
+        };
+
+        // The drop-in file comes first, then the rc file sourcing it
+        let rc_file = files.get(1).filter(|file| !claimed.contains(file));
+        match rc_file {
+            Some(rc_file) if settings.write_mode == WriteMode::DropIn && shared == files[0] => {
+                report.push(format!(
+                    "  {}: {}",
+                    handler.name(),
+                    msg!("shell-sources-shared", file = rc_file, shared = shared)
+                ));
+                claimed.push(rc_file.clone());
+                selected.push(ShellTarget {
+                    handler,
+                    source_line_only: true,
+                });
+            }
+            _ => report.push(format!(
+                "  {}: {}",
+                handler.name(),
+                msg!("shell-skipped-shared", shared = shared)
+            )),
+        }
+    }
+    Ok(selected)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::root;
+    use serial_test::serial;
+    use tempfile::TempDir;
+
+    #[test]
+    #[serial]
+    fn test_all_shells_shared_drop_in() {
+        let temp_dir = TempDir::new().unwrap();
+        root::set_root(Some(temp_dir.path())).unwrap();
+        let home = locations::home_dir();
+        fs::create_dir_all(&home).unwrap();
+        fs::write(home.join(".bashrc"), "alias ll='ls -l'\n").unwrap();
+        fs::write(home.join(".kshrc"), "set -o vi\n").unwrap();
+        let settings = config::Config {
+            all_shells: true,
+            write_mode: WriteMode::DropIn,
+            ..Default::default()
+        };
+        config::set_config(settings.clone());
+
+        let targets = select_all_shells(&settings, &mut Vec::new()).unwrap();
+        let files: Vec<Vec<PathBuf>> = targets.iter().map(ShellTarget::files).collect();
+        let result = update_files(&settings, &[PathBuf::from("/usr/bin")]);
+        let drop_in = config::config_dir().join("path.sh");
+        let bashrc = fs::read_to_string(home.join(".bashrc"));
+        let kshrc = fs::read_to_string(home.join(".kshrc"));
+        root::set_root(None).unwrap();
+        config::clear_config();
+
+        // bash writes path.sh, and ksh only adds the line sourcing it
+        assert_eq!(targets.len(), 2);
+        assert!(!targets[0].source_line_only);
+        assert!(targets[1].source_line_only);
+        assert_eq!(files[1], vec![home.join(".kshrc")]);
+
+        result.unwrap();
+        assert!(fs::read_to_string(drop_in).unwrap().contains("/usr/bin"));
+        let bashrc = bashrc.unwrap();
+        let kshrc = kshrc.unwrap();
+        assert!(bashrc.starts_with("alias ll='ls -l'\n"));
+        assert!(bashrc.contains("path.sh"));
+        assert!(kshrc.starts_with("set -o vi\n"));
+        assert!(kshrc.contains("path.sh"));
+    }
 }
@@ -0,0 +1,262 @@
+//! PATH declarations shared by the POSIX shell handlers.
+//!
+//! bash, ksh and generic `sh` configs declare PATH the same way. This
+//! module provides functionality to:
+//! - Format entries as one `export PATH="..."` or one line per entry,
+//!   as chosen by the `export-format` setting
+//! - Parse the `PATH="$HOME/bin:$PATH"`-style lines of the incremental format
+//! - Put entries from a run of such lines back in the order they have in PATH
+
+use crate::config::{self, ExportFormat};
+use crate::utils::shell::block::BLOCK_HEADER;
+use crate::utils::shell::quoting;
+use crate::utils::shell::spelling;
+use crate::utils::shell::types::{ConfigEntry, EntryComment};
+use lazy_static::lazy_static;
+use regex::{Captures, Regex};
+
+/// A `PATH=` value: double-quoted with backslash escapes, single-quoted, or
+/// unquoted up to the end of the statement. The content is captured by
+/// whichever of the three groups matched; see [`captured_value`].
+pub const VALUE_PATTERN: &str = r#"(?:"((?:[^"\\]|\\.)*)"|'([^']*)'|([^\s"';&|]*))"#;
+
+lazy_static! {
+    /// `PATH=...` as a statement of its own, e.g. `PATH="$HOME/bin:$PATH"`
+    static ref PATH_LINE_REGEX: Regex =
+        Regex::new(&format!("^PATH={}$", VALUE_PATTERN)).unwrap();
+}
+
+/// Entries declared by one `PATH=...` line
+#[derive(Debug, Clone, PartialEq)]
+pub struct PathLine {
+    /// Entries exactly as written, without the `$PATH` reference
+    pub entries: Vec<String>,
+    /// Whether the line extends the inherited PATH instead of replacing it
+    pub extends: bool,
+    /// Whether the entries go before the inherited PATH
+    pub prepended: bool,
+    /// Comment after the line
+    pub comment: Option<String>,
+}
+
+/// Formats PATH declarations for a POSIX shell from spelled entries
+///
+/// # Example
+///
+/// With `export-format = "incremental"` and entries `$HOME/bin`, `$PATH`
+/// and `/opt/tools/bin`:
+///
+/// ```sh
+/// PATH="$HOME/bin:$PATH"
+/// PATH="$PATH:/opt/tools/bin"
+/// export PATH
+/// ```
+pub fn format_path(entries: &[ConfigEntry]) -> String {
+    let header = format!("\n{}\n", BLOCK_HEADER);
+    match config::get_config().export_format {
+        ExportFormat::Single => {
+            let paths = entries
+                .iter()
+                .map(quoting::posix_path_value)
+                .collect::<Vec<_>>()
+                .join(":");
+            format!("{}export PATH=\"{}\"\n", header, paths)
+        }
+        ExportFormat::Incremental => header + &incremental_lines(entries),
+    }
+}
+
+/// Writes one `PATH=` line per entry, keeping comments after entries
+fn incremental_lines(entries: &[ConfigEntry]) -> String {
+    let line = |value: String, entry: &ConfigEntry| match &entry.comment {
+        Some(comment) => format!("PATH=\"{}\"  # {}", value, comment),
+        None => format!("PATH=\"{}\"", value),
+    };
+
+    let appended =
+        |entry: &ConfigEntry| line(format!("$PATH:{}", quoting::posix_path_value(entry)), entry);
+
+    let mut lines = Vec::new();
+    match entries.iter().position(ConfigEntry::is_path_reference) {
+        // Entries before $PATH are prepended last first, so they end up in order
+        Some(reference) => {
+            for entry in entries[..reference].iter().rev() {
+                let value = format!("{}:$PATH", quoting::posix_path_value(entry));
+                lines.push(line(value, entry));
+            }
+            lines.extend(entries[reference + 1..].iter().map(appended));
+            lines.push("export PATH".to_string());
+        }
+        // The first entry starts PATH afresh
+        None => match entries.split_first() {
+            Some((first, rest)) => {
+                let value = quoting::posix_path_value(first);
+                lines.push(format!("export {}", line(value, first)));
+                lines.extend(rest.iter().map(appended));
+            }
+            None => lines.push("export PATH=\"\"".to_string()),
+        },
+    }
+    lines.join("\n") + "\n"
+}
+
+/// Parses a line extending PATH without `export`, as written by the
+/// incremental format
+///
+/// # Returns
+/// * `None` - If the line isn't a plain `PATH=...` statement referencing `$PATH`
+pub fn parse_line(line: &str) -> Option<PathLine> {
+    parse_assignment(line).filter(|line| line.extends)
+}
+
+/// Returns the content of a value matched by [`VALUE_PATTERN`] as the
+/// regex's first three groups
+pub fn captured_value<'a>(captures: &Captures<'a>) -> Option<&'a str> {
+    (1..=3)
+        .find_map(|group| captures.get(group))
+        .map(|value| value.as_str())
+}
+
+fn parse_assignment(line: &str) -> Option<PathLine> {
+    let (code, comment) = quoting::split_comment(line.trim());
+    let captures = PATH_LINE_REGEX.captures(code)?;
+    let value = captured_value(&captures)?;
+
+    let is_reference = |part: &str| part == "$PATH" || part == "${PATH}";
+    let parts: Vec<&str> = value.split(':').collect();
+    let extends = parts.iter().any(|part| is_reference(part));
+    let prepended = parts.len() > 1
+        && parts.last().is_some_and(|part| is_reference(part))
+        && !parts.first().is_some_and(|part| is_reference(part));
+    Some(PathLine {
+        entries: parts
+            .into_iter()
+            .filter(|part| !part.is_empty() && !is_reference(part))
+            .map(String::from)
+            .collect(),
+        extends,
+        prepended,
+        comment: comment.map(str::to_string),
+    })
+}
+
+/// Extracts the comments after `PATH=...` and `export PATH=...` lines; a
+/// comment belongs to the last entry on its line
+pub fn entry_comments(content: &str) -> Vec<EntryComment> {
+    content
+        .lines()
+        .map(|line| {
+            let line = line.trim();
+            line.strip_prefix("export ").unwrap_or(line)
+        })
+        .filter_map(parse_assignment)
+        .filter_map(|line| {
+            let comment = line.comment?;
+            line.entries.last().map(|raw| EntryComment {
+                raw: raw.clone(),
+                comment,
+            })
+        })
+        .collect()
+}
+
+/// Collects entries from consecutive PATH declarations in the order they
+/// end up in PATH
+///
+/// Each line prepending entries puts them before those of the lines since
+/// the last `export PATH=...`, so a run written by the incremental format
+/// reads back in its original order. As in `export PATH=...:$PATH`, the
+/// entries include a `$PATH` reference where the existing PATH goes.
+#[derive(Debug, Default)]
+pub struct EntryCollector {
+    entries: Vec<String>,
+    /// Where the entries of the current run start
+    start: usize,
+}
+
+impl EntryCollector {
+    /// Adds the entries of a `PATH=...` line
+    pub fn add_line(&mut self, line: PathLine) {
+        let run = &self.entries[self.start..];
+        if !run.iter().any(|raw| spelling::is_self_reference(raw)) {
+            self.entries.push("$PATH".to_string());
+        }
+        if line.prepended {
+            self.entries.splice(self.start..self.start, line.entries);
+        } else {
+            self.entries.extend(line.entries);
+        }
+    }
+
+    /// Adds entries of another declaration, starting a new run
+    pub fn extend(&mut self, entries: impl IntoIterator<Item = String>) {
+        self.start = self.entries.len();
+        self.entries.extend(entries);
+    }
+
+    pub fn into_entries(self) -> Vec<String> {
+        self.entries
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_incremental_round_trip() {
+        let mut entries = vec![
+            ConfigEntry {
+                variable: Some("$HOME".to_string()),
+                literal: "/bin".into(),
+                comment: Some("my scripts".to_string()),
+            },
+            ConfigEntry::literal("/pm-test/first"),
+            ConfigEntry::path_reference(),
+            ConfigEntry::literal("/pm-test/last"),
+        ];
+        let formatted = incremental_lines(&entries);
+        assert_eq!(
+            formatted,
+            "PATH=\"/pm-test/first:$PATH\"\n\
+             PATH=\"$HOME/bin:$PATH\"  # my scripts\n\
+             PATH=\"$PATH:/pm-test/last\"\n\
+             export PATH\n"
+        );
+
+        let mut collector = EntryCollector::default();
+        for line in formatted.lines().filter_map(parse_line) {
+            collector.add_line(line);
+        }
+        assert_eq!(
+            collector.into_entries(),
+            vec!["$HOME/bin", "/pm-test/first", "$PATH", "/pm-test/last"]
+        );
+        assert_eq!(
+            entry_comments(&formatted),
+            vec![EntryComment {
+                raw: "$HOME/bin".to_string(),
+                comment: "my scripts".to_string(),
+            }]
+        );
+
+        // Without $PATH, the first entry is exported on its own
+        entries.remove(2);
+        let formatted = incremental_lines(&entries);
+        assert!(formatted.starts_with("export PATH=\"$HOME/bin\"  # my scripts\n"));
+        assert_eq!(entry_comments(&formatted).len(), 1);
+    }
+
+    #[test]
+    fn test_parse_line() {
+        let line = parse_line("PATH=~/bin:/opt/bin:$PATH  # tools").unwrap();
+        assert_eq!(line.entries, vec!["~/bin", "/opt/bin"]);
+        assert!(line.prepended);
+        assert_eq!(line.comment.as_deref(), Some("tools"));
+        assert!(!parse_line("PATH='$PATH:/opt/bin'").unwrap().prepended);
+        assert!(parse_line("PATH=/usr/bin:/bin").is_none());
+        assert!(parse_line("export PATH").is_none());
+        assert!(parse_line("[ -d ~/bin ] && PATH=~/bin:$PATH").is_none());
+        assert!(parse_line("MANPATH=/usr/share/man").is_none());
+    }
+}
@@ -0,0 +1,484 @@
+//! Quoting and escaping of PATH entries for each shell's syntax.
+//!
+//! Entries may contain spaces, quotes, `$`, or non-ASCII characters. The
+//! literal part of a [`ConfigEntry`] must be written so the shell reads it
+//! back unchanged, while its leading variable reference (`$HOME`) must stay
+//! unquoted or double-quoted so it still expands.
+//!
+//! Bytes that aren't valid UTF-8 can't be written into a config as they
+//! are, so they're written as a `printf` command substitution with octal
+//! escapes, e.g. `$(printf '\377')`, which the shell turns back into the
+//! same bytes. [`decode_bytes`] does the same when configs are parsed.
+
+use crate::utils::path::os_string_from_bytes;
+use crate::utils::shell::types::ConfigEntry;
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::ffi::{OsStr, OsString};
+
+lazy_static! {
+    /// The command substitutions [`posix_path_value`] and the other
+    /// formatters write for bytes that aren't valid UTF-8, as they read
+    /// after [`split_words`] removed the quotes around them
+    static ref PRINTF_BYTES: Regex =
+        Regex::new(r"\$\(printf '((?:\\[0-7]{3})+)'\)|`printf '((?:\\[0-7]{3})+)'`").unwrap();
+}
+
+/// Part of a literal: text, or bytes that aren't valid UTF-8
+enum Chunk<'a> {
+    Text(&'a str),
+    Bytes(&'a [u8]),
+}
+
+/// Splits a literal into text and runs of bytes that aren't valid UTF-8
+fn chunks(literal: &OsStr) -> Vec<Chunk<'_>> {
+    let mut chunks = Vec::new();
+    for chunk in literal.as_encoded_bytes().utf8_chunks() {
+        if !chunk.valid().is_empty() {
+            chunks.push(Chunk::Text(chunk.valid()));
+        }
+        if !chunk.invalid().is_empty() {
+            chunks.push(Chunk::Bytes(chunk.invalid()));
+        }
+    }
+    chunks
+}
+
+/// Formats bytes as octal escapes for `printf`, e.g. `\377`
+fn octal_escapes(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("\\{:03o}", byte)).collect()
+}
+
+/// Returns whether a literal can be written as a bare word in every shell
+fn is_bare_word(literal: &OsStr) -> bool {
+    literal.to_str().is_some_and(|literal| {
+        literal
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "/._-+,@%=:".contains(c))
+    })
+}
+
+/// Turns the `printf` substitutions written for bytes that aren't valid
+/// UTF-8 back into those bytes
+///
+/// # Arguments
+/// * `text` - An entry as parsed from a config, with quotes removed
+pub fn decode_bytes(text: &str) -> OsString {
+    if !text.contains("printf '") {
+        return OsString::from(text);
+    }
+
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut last = 0;
+    for captures in PRINTF_BYTES.captures_iter(text) {
+        let whole = captures.get(0).unwrap();
+        bytes.extend_from_slice(&text.as_bytes()[last..whole.start()]);
+        let escapes = captures.get(1).or_else(|| captures.get(2)).unwrap();
+        for octal in escapes
+            .as_str()
+            .split('\\')
+            .filter(|octal| !octal.is_empty())
+        {
+            bytes.push(u8::from_str_radix(octal, 8).unwrap_or(b'?'));
+        }
+        last = whole.end();
+    }
+    bytes.extend_from_slice(&text.as_bytes()[last..]);
+    os_string_from_bytes(bytes)
+}
+
+/// Escapes text for use inside a POSIX double-quoted string
+pub fn escape_double_quoted(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '"' | '$' | '`') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Formats a literal for use inside a POSIX double-quoted string, writing
+/// bytes that aren't valid UTF-8 as `$(printf '...')`
+fn double_quoted_literal(literal: &OsStr) -> String {
+    chunks(literal)
+        .into_iter()
+        .map(|chunk| match chunk {
+            Chunk::Text(text) => escape_double_quoted(text),
+            Chunk::Bytes(bytes) => format!("$(printf '{}')", octal_escapes(bytes)),
+        })
+        .collect()
+}
+
+/// Formats a literal as a single-quoted POSIX word, writing bytes that
+/// aren't valid UTF-8 as a double-quoted `$(printf '...')`
+pub fn posix_single_quoted(literal: &OsStr) -> String {
+    let quoted: String = chunks(literal)
+        .into_iter()
+        .map(|chunk| match chunk {
+            Chunk::Text(text) => text.replace('\'', r"'\''"),
+            Chunk::Bytes(bytes) => format!("'\"$(printf '{}')\"'", octal_escapes(bytes)),
+        })
+        .collect();
+    format!("'{}'", quoted)
+}
+
+/// Formats an entry as part of a POSIX double-quoted `PATH="..."` value.
+///
+/// The caller provides the surrounding quotes.
+pub fn posix_path_value(entry: &ConfigEntry) -> String {
+    format!(
+        "{}{}",
+        entry.variable.as_deref().unwrap_or_default(),
+        double_quoted_literal(&entry.literal)
+    )
+}
+
+/// Formats an entry as a single zsh word, e.g. an element of `path=(...)`
+pub fn zsh_word(entry: &ConfigEntry) -> String {
+    let literal = if is_bare_word(&entry.literal) {
+        entry.literal.to_string_lossy().into_owned()
+    } else {
+        format!("\"{}\"", double_quoted_literal(&entry.literal))
+    };
+    format!(
+        "{}{}",
+        entry.variable.as_deref().unwrap_or_default(),
+        literal
+    )
+}
+
+/// Formats an entry as a single tcsh word.
+///
+/// csh doesn't honor backslashes inside quotes, so literals are single-quoted
+/// and embedded single quotes are written as `'\''`. Bytes that aren't
+/// valid UTF-8 are written as a double-quoted `` `printf '...'` ``.
+pub fn tcsh_word(entry: &ConfigEntry) -> String {
+    let literal = if is_bare_word(&entry.literal) {
+        entry.literal.to_string_lossy().into_owned()
+    } else {
+        let quoted: String = chunks(&entry.literal)
+            .into_iter()
+            .map(|chunk| match chunk {
+                Chunk::Text(text) => text.replace('\'', r"'\''"),
+                Chunk::Bytes(bytes) => format!("'\"`printf '{}'`\"'", octal_escapes(bytes)),
+            })
+            .collect();
+        format!("'{}'", quoted)
+    };
+    format!(
+        "{}{}",
+        entry.variable.as_deref().unwrap_or_default(),
+        literal
+    )
+}
+
+/// Formats an entry as a single fish word.
+///
+/// Inside fish single quotes only `\` and `'` need escaping. Bytes that
+/// aren't valid UTF-8 are written as a double-quoted `$(printf '...')`,
+/// which needs fish 3.4 or later.
+pub fn fish_word(entry: &ConfigEntry) -> String {
+    let literal = if is_bare_word(&entry.literal) {
+        entry.literal.to_string_lossy().into_owned()
+    } else {
+        let quoted: String = chunks(&entry.literal)
+            .into_iter()
+            .map(|chunk| match chunk {
+                Chunk::Text(text) => text.replace('\\', r"\\").replace('\'', r"\'"),
+                Chunk::Bytes(bytes) => format!("'\"$(printf '{}')\"'", octal_escapes(bytes)),
+            })
+            .collect();
+        format!("'{}'", quoted)
+    };
+    format!(
+        "{}{}",
+        entry.variable.as_deref().unwrap_or_default(),
+        literal
+    )
+}
+
+/// Splits the value of an assignment from what follows it
+///
+/// The value ends at the first unquoted space or `;`, or at the closing
+/// parenthesis of a zsh array.
+pub fn split_assignment_value(text: &str) -> (&str, &str) {
+    let mut in_single = false;
+    let mut in_double = false;
+    let mut escaped = false;
+    let mut depth = 0;
+    for (idx, c) in text.char_indices() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        match c {
+            '\\' if !in_single => escaped = true,
+            '\'' if !in_double => in_single = !in_single,
+            '"' if !in_single => in_double = !in_double,
+            '(' if !in_single && !in_double => depth += 1,
+            ')' if !in_single && !in_double && depth > 0 => depth -= 1,
+            c if !in_single && !in_double && depth == 0 && (c.is_whitespace() || c == ';') => {
+                return (&text[..idx], &text[idx..]);
+            }
+            _ => {}
+        }
+    }
+    (text, "")
+}
+
+/// Splits a trailing comment off a line of shell code
+///
+/// A `#` starts a comment at the beginning of a word, outside quotes.
+///
+/// # Returns
+/// * The code before the comment, and the comment's text without the `#`
+pub fn split_comment(line: &str) -> (&str, Option<&str>) {
+    let mut in_single = false;
+    let mut in_double = false;
+    let mut escaped = false;
+    let mut word_start = true;
+    for (idx, c) in line.char_indices() {
+        if escaped {
+            escaped = false;
+            word_start = false;
+            continue;
+        }
+        match c {
+            '\\' if !in_single => escaped = true,
+            '\'' if !in_double => in_single = !in_single,
+            '"' if !in_single => in_double = !in_double,
+            '#' if !in_single && !in_double && word_start => {
+                return (line[..idx].trim_end(), Some(line[idx + 1..].trim()));
+            }
+            _ => {}
+        }
+        word_start = c.is_whitespace() && !in_single && !in_double;
+    }
+    (line, None)
+}
+
+/// Splits a line of shell code into the statements separated by `;`, `&&`
+/// or `||` outside quotes
+///
+/// The statements are trimmed, and empty ones are left out.
+pub fn split_statements(code: &str) -> Vec<&str> {
+    let mut statements = Vec::new();
+    let mut in_single = false;
+    let mut in_double = false;
+    let mut escaped = false;
+    let mut start = 0;
+    let mut chars = code.char_indices().peekable();
+    while let Some((idx, c)) = chars.next() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        let separator = match c {
+            '\\' if !in_single => {
+                escaped = true;
+                0
+            }
+            '\'' if !in_double => {
+                in_single = !in_single;
+                0
+            }
+            '"' if !in_single => {
+                in_double = !in_double;
+                0
+            }
+            _ if in_single || in_double => 0,
+            ';' => 1,
+            '&' | '|' if chars.peek().map(|&(_, next)| next) == Some(c) => {
+                chars.next();
+                2
+            }
+            _ => 0,
+        };
+        if separator > 0 {
+            statements.push(code[start..idx].trim());
+            start = idx + separator;
+        }
+    }
+    statements.push(code[start..].trim());
+    statements.retain(|statement| !statement.is_empty());
+    statements
+}
+
+/// Splits a command line into words, removing quotes and backslash escapes.
+///
+/// Single quotes are literal except that `\'` and `\\` are unescaped, which
+/// covers the quoting emitted by [`fish_word`] and [`tcsh_word`]. Inside
+/// double quotes a backslash escapes only `\`, `"`, `$` and `` ` ``, as in
+/// POSIX shells.
+pub fn split_words(text: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut in_word = false;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => {
+                in_word = true;
+                while let Some(c) = chars.next() {
+                    match c {
+                        '\'' => break,
+                        '\\' if matches!(chars.peek(), Some('\'') | Some('\\')) => {
+                            current.extend(chars.next());
+                        }
+                        _ => current.push(c),
+                    }
+                }
+            }
+            '"' => {
+                in_word = true;
+                while let Some(c) = chars.next() {
+                    match c {
+                        '"' => break,
+                        '\\' if matches!(chars.peek(), Some('\\' | '"' | '$' | '`')) => {
+                            current.extend(chars.next())
+                        }
+                        _ => current.push(c),
+                    }
+                }
+            }
+            '\\' => {
+                in_word = true;
+                current.extend(chars.next());
+            }
+            c if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut current));
+                    in_word = false;
+                }
+            }
+            c => {
+                in_word = true;
+                current.push(c);
+            }
+        }
+    }
+
+    if in_word {
+        words.push(current);
+    }
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn home(literal: &str) -> ConfigEntry {
+        ConfigEntry {
+            variable: Some("$HOME".to_string()),
+            literal: literal.into(),
+            comment: None,
+        }
+    }
+
+    #[test]
+    fn test_posix_quoting() {
+        assert_eq!(
+            posix_path_value(&ConfigEntry::literal("/opt/My Apps/bin")),
+            "/opt/My Apps/bin"
+        );
+        assert_eq!(
+            posix_path_value(&ConfigEntry::literal(r#"/opt/a"b$c`d\e"#)),
+            r#"/opt/a\"b\$c\`d\\e"#
+        );
+        assert_eq!(posix_path_value(&home("/bin")), "$HOME/bin");
+        assert_eq!(zsh_word(&home("/My Tools")), r#"$HOME"/My Tools""#);
+        assert_eq!(zsh_word(&ConfigEntry::literal("/usr/bin")), "/usr/bin");
+    }
+
+    #[test]
+    fn test_split_statements() {
+        assert_eq!(
+            split_statements("set -q PATH; or set -gx PATH /usr/bin"),
+            vec!["set -q PATH", "or set -gx PATH /usr/bin"]
+        );
+        assert_eq!(
+            split_statements("test -d ~/bin && export PATH=\"a;b\" || true;"),
+            vec!["test -d ~/bin", "export PATH=\"a;b\"", "true"]
+        );
+        assert_eq!(
+            split_statements(r"echo 'x && y' \; z"),
+            vec![r"echo 'x && y' \; z"]
+        );
+    }
+
+    #[test]
+    fn test_tcsh_and_fish_quoting() {
+        assert_eq!(tcsh_word(&home("/My Tools")), "$HOME'/My Tools'");
+        assert_eq!(
+            tcsh_word(&ConfigEntry::literal("/opt/it's")),
+            r"'/opt/it'\''s'"
+        );
+        assert_eq!(fish_word(&home("/My Tools")), "$HOME'/My Tools'");
+        assert_eq!(
+            fish_word(&ConfigEntry::literal("/opt/it's/$x")),
+            r"'/opt/it\'s/$x'"
+        );
+        assert_eq!(fish_word(&ConfigEntry::literal("/usr/bin")), "/usr/bin");
+    }
+
+    #[test]
+    fn test_non_ascii_round_trip() {
+        let entry = ConfigEntry::literal("/home/zoë/bin/工具");
+        assert_eq!(split_words(&fish_word(&entry)), vec!["/home/zoë/bin/工具"]);
+        assert_eq!(split_words(&zsh_word(&entry)), vec!["/home/zoë/bin/工具"]);
+    }
+
+    #[test]
+    fn test_split_words() {
+        assert_eq!(
+            split_words(r#"--path $HOME'/My Tools' "/opt/a b" /usr/bin"#),
+            vec!["--path", "$HOME/My Tools", "/opt/a b", "/usr/bin"]
+        );
+        assert_eq!(
+            split_words(&fish_word(&ConfigEntry::literal("/opt/it's"))),
+            vec!["/opt/it's"]
+        );
+        assert!(split_words("   ").is_empty());
+        assert_eq!(split_words(r#""/opt/\$x\"y\z""#), vec![r#"/opt/$x"y\z"#]);
+    }
+
+    #[test]
+    fn test_invalid_utf8_quoting() {
+        let entry = ConfigEntry::literal(os_string_from_bytes(b"/opt/\xff x".to_vec()));
+        assert_eq!(posix_path_value(&entry), r"/opt/$(printf '\377') x");
+        assert_eq!(zsh_word(&entry), r#""/opt/$(printf '\377') x""#);
+        assert_eq!(fish_word(&entry), r#"'/opt/'"$(printf '\377')"' x'"#);
+        assert_eq!(tcsh_word(&entry), r#"'/opt/'"`printf '\377'`"' x'"#);
+        assert_eq!(
+            posix_single_quoted(&entry.literal),
+            r#"'/opt/'"$(printf '\377')"' x'"#
+        );
+
+        for word in [zsh_word(&entry), fish_word(&entry), tcsh_word(&entry)] {
+            let words = split_words(&word);
+            assert_eq!(decode_bytes(&words[0]), entry.literal, "{}", word);
+        }
+    }
+
+    #[test]
+    fn test_split_comment() {
+        assert_eq!(
+            split_comment("fish_add_path ~/bin  # my scripts"),
+            ("fish_add_path ~/bin", Some("my scripts"))
+        );
+        assert_eq!(
+            split_comment("    $HOME/bin #"),
+            ("    $HOME/bin", Some(""))
+        );
+        assert_eq!(
+            split_comment("/opt/a#b '/c #d'"),
+            ("/opt/a#b '/c #d'", None)
+        );
+        assert_eq!(split_comment(r"/opt/a \#b"), (r"/opt/a \#b", None));
+        assert_eq!(split_comment("echo ${#path}"), ("echo ${#path}", None));
+    }
+}
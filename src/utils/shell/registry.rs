@@ -0,0 +1,71 @@
+//! Handlers for shells pathmaster doesn't support out of the box.
+//!
+//! Such shells are defined in the config as `[[shells]]` templates. The
+//! factory consults them before falling back to the generic handler.
+
+use super::handlers::template::TemplateHandler;
+use super::handlers::ShellHandler;
+use crate::config;
+use crate::msg;
+
+/// Returns a handler for a shell name defined in the config
+pub fn handler_for_name(name: &str) -> Option<Box<dyn ShellHandler>> {
+    let definition = config::get_config()
+        .shells
+        .into_iter()
+        .find(|definition| definition.name == name)?;
+    match TemplateHandler::new(definition) {
+        Ok(handler) => Some(Box::new(handler)),
+        Err(e) => {
+            eprintln!("{}", msg!("shell-custom-ignored", error = e.to_string()));
+            None
+        }
+    }
+}
+
+/// Returns handlers for every shell defined in the config
+pub fn all_handlers() -> Vec<Box<dyn ShellHandler>> {
+    let mut names: Vec<String> = Vec::new();
+    for definition in config::get_config().shells {
+        if !names.contains(&definition.name) {
+            names.push(definition.name);
+        }
+    }
+    names
+        .iter()
+        .filter_map(|name| handler_for_name(name))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::shell::handlers::template::ShellDefinition;
+    use crate::utils::shell::types::ShellType;
+    use serial_test::serial;
+
+    #[test]
+    #[serial]
+    fn test_configured_handler() {
+        let definition = ShellDefinition {
+            name: "test-shell".to_string(),
+            config_file: "~/.testshellrc".to_string(),
+            template: "set-env PATH \"{path}\"".to_string(),
+            separator: ":".to_string(),
+            syntax: ShellType::Generic,
+        };
+        config::set_config(config::Config {
+            shells: vec![definition.clone(), definition],
+            ..Default::default()
+        });
+
+        let handler = handler_for_name("test-shell");
+        let missing = handler_for_name("no-such-shell");
+        let all = all_handlers();
+        config::clear_config();
+
+        assert_eq!(handler.unwrap().name(), "test-shell");
+        assert!(missing.is_none());
+        assert_eq!(all.len(), 1);
+    }
+}
@@ -0,0 +1,78 @@
+//! Detection of files pulled in through `source` / `.` statements.
+//!
+//! Many users keep their PATH setup in a separate file that the main rc
+//! file sources. This module finds those files so handlers can update
+//! PATH where it is actually declared instead of appending a competing
+//! declaration to the main config.
+
+use crate::utils::root;
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::path::{Path, PathBuf};
+
+lazy_static! {
+    static ref SOURCE_REGEX: Regex =
+        Regex::new(r#"(?:^|[;&|]|\bthen)\s*(?:source|\.)\s+("[^"]+"|'[^']+'|[^\s;&|]+)"#).unwrap();
+}
+
+/// Finds files sourced by the given config content, in order of appearance.
+///
+/// Relative paths are resolved against the directory containing `config_path`.
+/// Only files that exist are returned, and each file is listed once.
+///
+/// # Arguments
+/// * `content` - Contents of the config file
+/// * `config_path` - Location of the config file, used to resolve relative paths
+pub fn find_sourced_files(content: &str, config_path: &Path) -> Vec<PathBuf> {
+    let base_dir = config_path.parent().unwrap_or_else(|| Path::new("/"));
+    let mut files = Vec::new();
+
+    for line in content.lines() {
+        let line = line.trim();
+        if line.starts_with('#') {
+            continue;
+        }
+
+        for cap in SOURCE_REGEX.captures_iter(line) {
+            let raw = cap[1].trim_matches(|c| c == '"' || c == '\'');
+            let expanded = shellexpand::full(raw)
+                .map(|s| s.to_string())
+                .unwrap_or_else(|_| shellexpand::tilde(raw).to_string());
+            let mut path = root::rooted(expanded);
+            if path.is_relative() {
+                path = base_dir.join(path);
+            }
+
+            if path.is_file() && path != config_path && !files.contains(&path) {
+                files.push(path);
+            }
+        }
+    }
+
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_find_sourced_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = temp_dir.path().join(".bashrc");
+        let path_file = temp_dir.path().join(".bash_path");
+        let aliases = temp_dir.path().join(".aliases");
+        fs::write(&path_file, "export PATH=/opt/bin:$PATH\n").unwrap();
+        fs::write(&aliases, "alias ll='ls -l'\n").unwrap();
+
+        let content = format!(
+            "source {}\n[ -f .aliases ] && . .aliases\n# source /commented/out\nsource /does/not/exist\n",
+            path_file.display()
+        );
+
+        let files = find_sourced_files(&content, &config);
+        assert_eq!(files, vec![path_file, aliases]);
+    }
+}
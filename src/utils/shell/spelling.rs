@@ -0,0 +1,250 @@
+//! Mapping between PATH entries and how they are spelled in shell configs.
+//!
+//! Configs often declare entries through variables (`$HOME/bin`, `~/bin`,
+//! `$XDG_DATA_HOME/npm/bin`). When pathmaster rewrites PATH it works with
+//! expanded paths, so this module remembers the original spelling of each
+//! entry and re-emits it, keeping shared dotfiles portable across machines.
+
+use crate::utils::shell::locations;
+use crate::utils::shell::quoting::decode_bytes;
+use crate::utils::shell::types::{ConfigEntry, EntryComment};
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+lazy_static! {
+    static ref VARIABLE_PREFIX: Regex =
+        Regex::new(r"^(\$[A-Za-z_][A-Za-z0-9_]*|\$\{[A-Za-z_][A-Za-z0-9_]*\})(/.*)?$").unwrap();
+}
+
+/// References to the existing PATH that appear in declarations like `PATH=$PATH:/x`
+const SELF_REFERENCES: &[&str] = &["$PATH", "${PATH}", "$path", "$path[@]"];
+
+/// Returns whether a raw entry refers to the existing PATH rather than a directory
+pub fn is_self_reference(raw: &str) -> bool {
+    SELF_REFERENCES.contains(&raw)
+}
+
+/// Expands a raw config entry into a path.
+///
+/// Tilde and environment variables are expanded. Undefined variables fall
+/// back to tilde-only expansion. Bytes written as `printf` substitutions
+/// because they aren't valid UTF-8 are decoded.
+///
+/// # Returns
+/// * `Some(PathBuf)` - The expanded path
+/// * `None` - For empty entries and references to the existing PATH
+pub fn expand_entry(raw: &str) -> Option<PathBuf> {
+    if raw.is_empty() || is_self_reference(raw) {
+        return None;
+    }
+
+    let expanded = shellexpand::full(raw)
+        .map(|s| s.to_string())
+        .unwrap_or_else(|_| shellexpand::tilde(raw).to_string());
+    Some(PathBuf::from(decode_bytes(&expanded)))
+}
+
+/// Splits a raw entry into a leading variable reference and literal remainder.
+///
+/// A leading `~` is rewritten as `$HOME`, since tilde isn't expanded inside
+/// the quoted strings handlers emit.
+fn split_variable(raw: &str) -> Option<ConfigEntry> {
+    if raw == "~" || raw.starts_with("~/") {
+        return Some(ConfigEntry {
+            variable: Some("$HOME".to_string()),
+            literal: decode_bytes(&raw[1..]),
+            comment: None,
+        });
+    }
+
+    VARIABLE_PREFIX.captures(raw).map(|cap| ConfigEntry {
+        variable: Some(cap[1].to_string()),
+        literal: decode_bytes(cap.get(2).map_or("", |m| m.as_str())),
+        comment: None,
+    })
+}
+
+/// Original spellings of PATH entries parsed from a config, and the
+/// comments written after them.
+#[derive(Debug, Default)]
+pub struct Spellings {
+    originals: HashMap<PathBuf, ConfigEntry>,
+    comments: HashMap<PathBuf, String>,
+}
+
+impl Spellings {
+    /// Records the spelling of every raw entry that starts with a variable reference.
+    ///
+    /// # Arguments
+    /// * `raw_entries` - Entries exactly as written in the config
+    pub fn from_raw(raw_entries: &[String]) -> Self {
+        let mut originals = HashMap::new();
+        for raw in raw_entries {
+            if let (Some(path), Some(entry)) = (expand_entry(raw), split_variable(raw)) {
+                originals.entry(path).or_insert(entry);
+            }
+        }
+        Self {
+            originals,
+            comments: HashMap::new(),
+        }
+    }
+
+    /// Records the comments written after entries, so they're written again
+    pub fn with_comments(mut self, comments: &[EntryComment]) -> Self {
+        for comment in comments.iter().filter(|c| !c.comment.is_empty()) {
+            if let Some(path) = expand_entry(&comment.raw) {
+                self.comments
+                    .entry(path)
+                    .or_insert_with(|| comment.comment.clone());
+            }
+        }
+        self
+    }
+
+    /// Adds a comment for an entry that doesn't have one yet
+    pub fn annotate(&mut self, path: &Path, comment: &str) {
+        // A comment must stay on the entry's line
+        let comment = comment.split_whitespace().collect::<Vec<_>>().join(" ");
+        if !comment.is_empty() {
+            self.comments.entry(path.to_path_buf()).or_insert(comment);
+        }
+    }
+
+    /// Determines how a path should be written to a config.
+    ///
+    /// The original spelling is used when known. Otherwise paths inside the
+    /// home directory are written relative to `$HOME`, and everything else
+    /// is written literally. A recorded comment is kept with the entry.
+    pub fn spell(&self, path: &Path) -> ConfigEntry {
+        ConfigEntry {
+            comment: self.comments.get(path).cloned(),
+            ..self.spell_path(path)
+        }
+    }
+
+    fn spell_path(&self, path: &Path) -> ConfigEntry {
+        if let Some(entry) = self.originals.get(path) {
+            return entry.clone();
+        }
+
+        let home = locations::real_home_dir();
+        if home != Path::new("/") {
+            if let Ok(rest) = path.strip_prefix(&home) {
+                let mut literal = OsString::new();
+                if !rest.as_os_str().is_empty() {
+                    literal.push("/");
+                    literal.push(rest);
+                }
+                return ConfigEntry {
+                    variable: Some("$HOME".to_string()),
+                    literal,
+                    comment: None,
+                };
+            }
+        }
+
+        ConfigEntry::literal(path)
+    }
+
+    /// Spells every entry in order
+    pub fn spell_all(&self, entries: &[PathBuf]) -> Vec<ConfigEntry> {
+        entries.iter().map(|entry| self.spell(entry)).collect()
+    }
+
+    /// Spells entries relative to the existing PATH.
+    ///
+    /// Entries in `inherited` come from outside the config and are written as
+    /// a single `$PATH` reference. Other entries before the first inherited
+    /// one are prepended, and the rest are appended.
+    pub fn spell_relative(&self, entries: &[PathBuf], inherited: &[PathBuf]) -> Vec<ConfigEntry> {
+        let mut prepended = Vec::new();
+        let mut appended = Vec::new();
+        let mut seen_inherited = false;
+
+        for entry in entries {
+            if inherited.contains(entry) {
+                seen_inherited = true;
+            } else if seen_inherited {
+                appended.push(self.spell(entry));
+            } else {
+                prepended.push(self.spell(entry));
+            }
+        }
+
+        prepended.push(ConfigEntry::path_reference());
+        prepended.extend(appended);
+        prepended
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand_entry() {
+        assert_eq!(expand_entry("$PATH"), None);
+        assert_eq!(expand_entry(""), None);
+        assert_eq!(expand_entry("/usr/bin"), Some(PathBuf::from("/usr/bin")));
+        assert_eq!(
+            expand_entry("~/bin"),
+            Some(locations::real_home_dir().join("bin"))
+        );
+    }
+
+    #[test]
+    fn test_original_spelling_preserved() {
+        let raw = vec![
+            "~/bin".to_string(),
+            "${HOME}/.cargo/bin".to_string(),
+            "/usr/bin".to_string(),
+        ];
+        let spellings = Spellings::from_raw(&raw);
+        let home = locations::real_home_dir();
+
+        assert_eq!(spellings.spell(&home.join("bin")).to_string(), "$HOME/bin");
+        assert_eq!(
+            spellings.spell(&home.join(".cargo/bin")).to_string(),
+            "${HOME}/.cargo/bin"
+        );
+        assert_eq!(
+            spellings.spell(Path::new("/usr/bin")),
+            ConfigEntry::literal("/usr/bin")
+        );
+    }
+
+    #[test]
+    fn test_spell_relative() {
+        let spellings = Spellings::default();
+        let entries = vec![
+            PathBuf::from("/opt/first/bin"),
+            PathBuf::from("/usr/bin"),
+            PathBuf::from("/opt/late/bin"),
+            PathBuf::from("/bin"),
+        ];
+        let inherited = vec![PathBuf::from("/usr/bin"), PathBuf::from("/bin")];
+
+        let spelled: Vec<String> = spellings
+            .spell_relative(&entries, &inherited)
+            .iter()
+            .map(|entry| entry.to_string())
+            .collect();
+        assert_eq!(spelled, vec!["/opt/first/bin", "$PATH", "/opt/late/bin"]);
+    }
+
+    #[test]
+    fn test_home_paths_written_relative_to_home() {
+        let spellings = Spellings::default();
+        let home = locations::real_home_dir();
+        if home != Path::new("/") {
+            assert_eq!(
+                spellings.spell(&home.join("go/bin")).to_string(),
+                "$HOME/go/bin"
+            );
+        }
+    }
+}
@@ -0,0 +1,76 @@
+//! Line ending and byte order mark handling for shell configs.
+//!
+//! Configs edited on Windows or synced through some tools use CRLF line
+//! endings or start with a UTF-8 byte order mark. Handlers work on
+//! LF-only text, so the original style is detected before editing and
+//! restored when the file is written back.
+
+/// UTF-8 byte order mark
+const BOM: char = '\u{feff}';
+
+/// Line ending and BOM style of a config file
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct TextFormat {
+    /// Whether the file starts with a byte order mark
+    pub bom: bool,
+    /// Whether lines end with `\r\n`
+    pub crlf: bool,
+}
+
+impl TextFormat {
+    /// Detects the style of `content` and returns it with LF-only, BOM-free text.
+    ///
+    /// A file counts as CRLF when most of its line endings are `\r\n`.
+    pub fn detect(content: &str) -> (Self, String) {
+        let bom = content.starts_with(BOM);
+        let content = content.strip_prefix(BOM).unwrap_or(content);
+
+        let crlf_count = content.matches("\r\n").count();
+        let lf_count = content.matches('\n').count();
+        let crlf = crlf_count > 0 && crlf_count * 2 >= lf_count;
+
+        (Self { bom, crlf }, content.replace("\r\n", "\n"))
+    }
+
+    /// Converts LF-only text back to this style.
+    pub fn restore(&self, content: &str) -> String {
+        let mut restored = String::with_capacity(content.len() + 1);
+        if self.bom {
+            restored.push(BOM);
+        }
+        if self.crlf {
+            restored.push_str(&content.replace('\n', "\r\n"));
+        } else {
+            restored.push_str(content);
+        }
+        restored
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crlf_and_bom_round_trip() {
+        let original = "\u{feff}# config\r\nexport PATH=/usr/bin\r\n";
+        let (format, normalized) = TextFormat::detect(original);
+
+        assert_eq!(
+            format,
+            TextFormat {
+                bom: true,
+                crlf: true
+            }
+        );
+        assert_eq!(normalized, "# config\nexport PATH=/usr/bin\n");
+        assert_eq!(format.restore(&normalized), original);
+    }
+
+    #[test]
+    fn test_plain_lf_unchanged() {
+        let (format, normalized) = TextFormat::detect("a\nb\n");
+        assert_eq!(format, TextFormat::default());
+        assert_eq!(format.restore(&normalized), "a\nb\n");
+    }
+}
@@ -0,0 +1,64 @@
+//! PATH for new panes in running tmux sessions.
+//!
+//! A tmux server keeps the environment it was started with and hands it to
+//! every new pane, so panes opened after a PATH change still get the old
+//! PATH. With `tmux = true` (or `--tmux`) pathmaster runs
+//! `tmux set-environment -g PATH ...` after each change.
+
+use std::env;
+use std::io;
+use std::path::PathBuf;
+use std::process::Command;
+
+/// Sets PATH in the global environment of the running tmux server.
+///
+/// # Returns
+/// * `Ok(true)` - The server's PATH was updated
+/// * `Ok(false)` - No tmux server is running, so there's nothing to update
+/// * `Err(io::Error)` - If tmux isn't installed or refused the change
+pub fn update_environment(entries: &[PathBuf]) -> io::Result<bool> {
+    let value = env::join_paths(entries).map_err(io::Error::other)?;
+    let output = Command::new("tmux")
+        .arg("set-environment")
+        .arg("-g")
+        .arg("PATH")
+        .arg(&value)
+        .output()
+        .map_err(|e| match e.kind() {
+            io::ErrorKind::NotFound => io::Error::new(e.kind(), "tmux is not installed"),
+            _ => e,
+        })?;
+    if output.status.success() {
+        return Ok(true);
+    }
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if is_no_server(&stderr) {
+        return Ok(false);
+    }
+    Err(io::Error::other(format!(
+        "tmux set-environment failed: {}",
+        stderr.trim()
+    )))
+}
+
+/// Returns whether tmux's error output says no server is running
+fn is_no_server(stderr: &str) -> bool {
+    stderr.contains("no server running") || stderr.contains("error connecting to")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_no_server() {
+        assert!(is_no_server(
+            "no server running on /tmp/tmux-1000/default\n"
+        ));
+        assert!(is_no_server(
+            "error connecting to /tmp/tmux-1000/default (No such file or directory)\n"
+        ));
+        assert!(!is_no_server("unknown option -- x\n"));
+    }
+}
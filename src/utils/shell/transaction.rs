@@ -0,0 +1,136 @@
+//! Best-effort rollback of updates touching several files.
+//!
+//! A PATH change can touch more than one file: the configs of every shell
+//! with `--all-shells`, a sourced PATH file, the drop-in and its rc file, or
+//! the desktop session files with `--gui`. A [`Transaction`] snapshots all
+//! of them before the first one is written, so a write that fails part way
+//! through puts the files written before it back as they were.
+//!
+//! This isn't atomic: the files are written one after another, so a crash
+//! or kill between two writes leaves them disagreeing, and a restore can
+//! itself fail. Only file contents are snapshotted; side effects such as
+//! `launchctl setenv` aren't undone.
+
+use crate::msg;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+/// Snapshots of the files an update may write
+#[derive(Debug)]
+pub struct Transaction {
+    /// Each file with its content, or `None` if it didn't exist
+    snapshots: Vec<(PathBuf, Option<Vec<u8>>)>,
+}
+
+impl Transaction {
+    /// Reads every file so it can be restored; a file listed twice is read once
+    pub fn begin(files: &[PathBuf]) -> io::Result<Self> {
+        let mut snapshots: Vec<(PathBuf, Option<Vec<u8>>)> = Vec::new();
+        for file in files {
+            if snapshots.iter().any(|(existing, _)| existing == file) {
+                continue;
+            }
+            let content = match fs::read(file) {
+                Ok(content) => Some(content),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+                Err(e) => {
+                    return Err(io::Error::new(
+                        e.kind(),
+                        format!("Cannot read {}: {}", file.display(), e),
+                    ))
+                }
+            };
+            snapshots.push((file.clone(), content));
+        }
+        Ok(Self { snapshots })
+    }
+
+    /// Runs one step of the update, rolling everything back if it fails
+    ///
+    /// # Arguments
+    /// * `target` - What the step writes, used in the error message
+    /// * `step` - The write itself
+    pub fn step<T, F>(&self, target: &str, step: F) -> io::Result<T>
+    where
+        F: FnOnce() -> io::Result<T>,
+    {
+        step().map_err(|e| {
+            let outcome = if self.rollback() {
+                "the files written before it were put back"
+            } else {
+                "some files could not be put back"
+            };
+            io::Error::new(
+                e.kind(),
+                format!("Updating {} failed and {}: {}", target, outcome, e),
+            )
+        })
+    }
+
+    /// Puts the files back as they were, removing those that didn't exist
+    ///
+    /// # Returns
+    /// * `true` - If every file was restored
+    /// * `false` - If any couldn't be, each reported with a warning
+    pub fn rollback(&self) -> bool {
+        let mut restored = true;
+        for (file, content) in &self.snapshots {
+            if fs::read(file).ok() == *content {
+                continue;
+            }
+            let result = match content {
+                Some(content) => fs::write(file, content),
+                None => fs::remove_file(file).or_else(|e| match e.kind() {
+                    io::ErrorKind::NotFound => Ok(()),
+                    _ => Err(e),
+                }),
+            };
+            if let Err(e) = result {
+                eprintln!(
+                    "{}",
+                    msg!("transaction-restore-failed", file = file, error = e)
+                );
+                restored = false;
+            }
+        }
+        restored
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_failed_step_rolls_back() {
+        let temp_dir = TempDir::new().unwrap();
+        let existing = temp_dir.path().join(".bashrc");
+        let created = temp_dir.path().join("path.sh");
+        fs::write(&existing, "export PATH=/usr/bin\n").unwrap();
+
+        let transaction =
+            Transaction::begin(&[existing.clone(), created.clone(), existing.clone()]).unwrap();
+        assert_eq!(transaction.snapshots.len(), 2);
+
+        transaction
+            .step("bash", || fs::write(&existing, "export PATH=/changed\n"))
+            .unwrap();
+        let error = transaction
+            .step("gui", || {
+                fs::write(&created, "new")?;
+                Err::<(), _>(io::Error::other("disk full"))
+            })
+            .unwrap_err();
+
+        assert!(error
+            .to_string()
+            .contains("the files written before it were put back: disk full"));
+        assert_eq!(
+            fs::read_to_string(&existing).unwrap(),
+            "export PATH=/usr/bin\n"
+        );
+        assert!(!created.exists());
+    }
+}
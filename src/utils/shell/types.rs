@@ -1,4 +1,11 @@
-#[derive(Debug, Clone, PartialEq)]
+use serde::{Deserialize, Serialize};
+use std::ffi::{OsStr, OsString};
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub enum ShellType {
     Zsh,
     Bash,
@@ -8,6 +15,49 @@ pub enum ShellType {
     Generic,
 }
 
+impl fmt::Display for ShellType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShellType::Zsh => write!(f, "zsh"),
+            ShellType::Bash => write!(f, "bash"),
+            ShellType::Fish => write!(f, "fish"),
+            ShellType::Tcsh => write!(f, "tcsh"),
+            ShellType::Ksh => write!(f, "ksh"),
+            ShellType::Generic => write!(f, "generic"),
+        }
+    }
+}
+
+impl ShellType {
+    /// Returns the command that makes the shell forget where it found
+    /// commands, so it searches the new PATH.
+    ///
+    /// fish looks commands up afresh every time and needs none.
+    pub fn rehash_command(&self) -> Option<&'static str> {
+        match self {
+            ShellType::Zsh | ShellType::Tcsh => Some("rehash"),
+            ShellType::Bash | ShellType::Ksh | ShellType::Generic => Some("hash -r"),
+            ShellType::Fish => None,
+        }
+    }
+}
+
+impl FromStr for ShellType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "zsh" => Ok(ShellType::Zsh),
+            "bash" => Ok(ShellType::Bash),
+            "fish" => Ok(ShellType::Fish),
+            "tcsh" | "csh" => Ok(ShellType::Tcsh),
+            "ksh" | "mksh" => Ok(ShellType::Ksh),
+            "generic" | "sh" | "posix" => Ok(ShellType::Generic),
+            _ => Err(format!("Invalid shell: {}", s)),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum ModificationType {
     Assignment,        // export PATH=...
@@ -24,3 +74,83 @@ pub struct PathModification {
     pub content: String,
     pub modification_type: ModificationType,
 }
+
+/// A PATH entry as declared in a shell config, with the line declaring it
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeclaredEntry {
+    /// One-based line number of the declaration
+    pub line_number: usize,
+    /// Entry exactly as written, e.g. `$HOME/bin`
+    pub raw: String,
+}
+
+/// The entries a shell config declares, in PATH order
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DeclaredPath {
+    pub entries: Vec<PathBuf>,
+    /// How many leading entries are declared ahead of the existing PATH,
+    /// e.g. 1 for `PATH="$HOME/bin:$PATH"`; 0 when it isn't referred to
+    pub ahead: usize,
+}
+
+/// A PATH entry as it is written to a shell config.
+///
+/// Entries keep a leading variable reference (such as `$HOME`) separate from
+/// the literal remainder, so configs stay portable across machines and
+/// handlers can quote the literal part without breaking the reference.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigEntry {
+    /// Leading variable reference, e.g. `$HOME` or `${XDG_DATA_HOME}`
+    pub variable: Option<String>,
+    /// Literal part of the entry following the variable, which may hold
+    /// bytes that aren't valid UTF-8
+    pub literal: OsString,
+    /// Note written after the entry, e.g. `my scripts`, by formats that
+    /// put one entry per line
+    pub comment: Option<String>,
+}
+
+/// A comment written after a PATH entry in a shell config
+#[derive(Debug, Clone, PartialEq)]
+pub struct EntryComment {
+    /// Entry exactly as written, e.g. `$HOME/bin`
+    pub raw: String,
+    /// Comment text, without the `#`
+    pub comment: String,
+}
+
+impl ConfigEntry {
+    /// Creates an entry with no variable reference
+    pub fn literal(literal: impl AsRef<OsStr>) -> Self {
+        Self {
+            variable: None,
+            literal: literal.as_ref().to_os_string(),
+            comment: None,
+        }
+    }
+
+    /// Creates a reference to the existing PATH, written as `$PATH`
+    pub fn path_reference() -> Self {
+        Self {
+            variable: Some("$PATH".to_string()),
+            literal: OsString::new(),
+            comment: None,
+        }
+    }
+
+    /// Returns whether this entry refers to the existing PATH
+    pub fn is_path_reference(&self) -> bool {
+        self.literal.is_empty() && self.variable.as_deref() == Some("$PATH")
+    }
+}
+
+impl std::fmt::Display for ConfigEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}{}",
+            self.variable.as_deref().unwrap_or_default(),
+            self.literal.to_string_lossy()
+        )
+    }
+}
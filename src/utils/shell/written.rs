@@ -0,0 +1,118 @@
+//! The PATH entries pathmaster last wrote to each shell config.
+//!
+//! After every config update the entries of the declarations pathmaster
+//! manages are saved to `$XDG_STATE_HOME/pathmaster/written.json`. When a
+//! config later declares something else, its PATH was changed by hand or by
+//! another program, which `check` reports.
+
+use crate::utils::path::serde_path;
+use crate::utils::shell::factory;
+use crate::utils::shell::locations;
+use crate::utils::shell::ShellHandler;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Recorded entries, by shell config file
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+struct Written {
+    files: BTreeMap<PathBuf, Entries>,
+}
+
+/// The entries of one config, which may not be valid UTF-8
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+struct Entries(#[serde(with = "serde_path::vec")] Vec<PathBuf>);
+
+impl Written {
+    fn load_from(path: &Path) -> Self {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    fn save_to(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)
+    }
+}
+
+/// Location of the record
+fn record_path() -> PathBuf {
+    locations::xdg_state_home()
+        .join("pathmaster")
+        .join("written.json")
+}
+
+/// Records the entries a handler's config declares now, after pathmaster
+/// wrote it
+pub fn record(handler: &dyn ShellHandler) -> io::Result<()> {
+    let Some(file) = handler.modified_files().into_iter().next() else {
+        return Ok(());
+    };
+    let content = fs::read_to_string(&file)?;
+
+    let path = record_path();
+    let mut written = Written::load_from(&path);
+    written
+        .files
+        .insert(file, Entries(handler.managed_entries(&content)));
+    written.save_to(&path)
+}
+
+/// Returns the shell configs whose PATH declarations no longer match what
+/// pathmaster last wrote to them
+pub fn changed_configs() -> Vec<PathBuf> {
+    let written = Written::load_from(&record_path());
+    if written.files.is_empty() {
+        return Vec::new();
+    }
+
+    let mut changed = Vec::new();
+    for handler in factory::configured_shell_handlers() {
+        let Some(file) = handler.modified_files().into_iter().next() else {
+            continue;
+        };
+        let Some(recorded) = written.files.get(&file) else {
+            continue;
+        };
+        let Ok(content) = fs::read_to_string(&file) else {
+            continue;
+        };
+        if handler.managed_entries(&content) != recorded.0 && !changed.contains(&file) {
+            changed.push(file);
+        }
+    }
+    changed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::path::os_string_from_bytes;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_save_and_load() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("written.json");
+        assert!(Written::load_from(&path).files.is_empty());
+
+        let mut written = Written::default();
+        written.files.insert(
+            PathBuf::from("/home/user/.bashrc"),
+            Entries(vec![
+                PathBuf::from("/usr/bin"),
+                PathBuf::from(os_string_from_bytes(b"/opt/\xff/bin".to_vec())),
+            ]),
+        );
+        written.save_to(&path).unwrap();
+        assert_eq!(Written::load_from(&path), written);
+    }
+}
@@ -0,0 +1,195 @@
+//! Detection of PATH entries that slow down command lookup.
+//!
+//! The shell searches PATH in order for every command it hasn't hashed yet,
+//! and completing a command name lists every PATH directory. An entry on a
+//! network mount turns each of those into a round trip (or a hang while the
+//! server is unreachable), and a directory with tens of thousands of files
+//! makes completion noticeably slower. This module times a stat and listing
+//! of each entry and looks up the filesystem it lives on.
+
+use crate::msg;
+use crate::utils::mounts::{self, Mount};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Listing a directory takes longer than this is reported
+pub const SLOW_LISTING: Duration = Duration::from_millis(100);
+
+/// Directories with more files than this are reported
+pub const MANY_FILES: usize = 10_000;
+
+/// Filesystem types served over the network
+const NETWORK_FS_TYPES: &[&str] = &[
+    "nfs",
+    "nfs4",
+    "cifs",
+    "smb3",
+    "smbfs",
+    "afs",
+    "ceph",
+    "glusterfs",
+    "lustre",
+    "9p",
+    "fuse.sshfs",
+    "fuse.rclone",
+    "davfs",
+];
+
+/// Why an entry slows lookups down
+#[derive(Debug, Clone, PartialEq)]
+pub enum SlowReason {
+    /// The entry lives on a network filesystem of this type
+    NetworkMount(String),
+    /// Stat and listing the entry took this long
+    SlowListing(Duration),
+    /// The entry holds this many files
+    ManyFiles(usize),
+}
+
+impl fmt::Display for SlowReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SlowReason::NetworkMount(fs_type) => {
+                write!(f, "{}", msg!("slow-network-mount", filesystem = fs_type))
+            }
+            SlowReason::SlowListing(elapsed) => write!(
+                f,
+                "{}",
+                msg!("slow-listing", milliseconds = elapsed.as_millis() as u64)
+            ),
+            SlowReason::ManyFiles(count) => {
+                write!(f, "{}", msg!("slow-many-files", count = *count))
+            }
+        }
+    }
+}
+
+/// A PATH entry that slows command lookup down
+#[derive(Debug, Clone, PartialEq)]
+pub struct SlowEntry {
+    /// The PATH entry
+    pub entry: PathBuf,
+    /// Every reason the entry is slow
+    pub reasons: Vec<SlowReason>,
+}
+
+/// Timing of one stat and listing of a directory
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Probe {
+    /// Time the stat and listing took
+    pub elapsed: Duration,
+    /// Number of files in the directory
+    pub files: usize,
+}
+
+/// Stats and lists a directory, timing both
+///
+/// # Returns
+/// * `None` if the directory can't be read
+pub fn probe(dir: &Path) -> Option<Probe> {
+    let start = Instant::now();
+    fs::metadata(dir)
+        .ok()
+        .filter(|metadata| metadata.is_dir())?;
+    let files = fs::read_dir(dir).ok()?.count();
+    Some(Probe {
+        elapsed: start.elapsed(),
+        files,
+    })
+}
+
+/// Finds the entries that are on network mounts, slow to list or very large
+pub fn find_slow(entries: &[PathBuf]) -> Vec<SlowEntry> {
+    find_slow_in(entries, &mounts::read_mounts())
+}
+
+fn find_slow_in(entries: &[PathBuf], mounts: &[Mount]) -> Vec<SlowEntry> {
+    entries
+        .iter()
+        .filter(|entry| entry.is_absolute())
+        .filter_map(|entry| {
+            let mut reasons = Vec::new();
+            match network_mount(mounts, entry) {
+                // Touching an unreachable mount can hang, so network entries aren't probed
+                Some(fs_type) => reasons.push(SlowReason::NetworkMount(fs_type)),
+                None => {
+                    if let Some(probe) = probe(entry) {
+                        if probe.elapsed > SLOW_LISTING {
+                            reasons.push(SlowReason::SlowListing(probe.elapsed));
+                        }
+                        if probe.files > MANY_FILES {
+                            reasons.push(SlowReason::ManyFiles(probe.files));
+                        }
+                    }
+                }
+            }
+            (!reasons.is_empty()).then(|| SlowEntry {
+                entry: entry.clone(),
+                reasons,
+            })
+        })
+        .collect()
+}
+
+/// Returns the type of the network filesystem an entry lives on, if any
+///
+/// The entry is looked up as written first, so one under a network mount
+/// point is recognized without touching the mount. Only entries that
+/// aren't get resolved, to catch symlinks leading onto a network mount.
+fn network_mount(mounts: &[Mount], entry: &Path) -> Option<String> {
+    let network = |path: &Path| {
+        mounts::containing(mounts, path)
+            .filter(|mount| is_network_fs(&mount.fs_type))
+            .map(|mount| mount.fs_type.clone())
+    };
+    network(entry).or_else(|| network(&fs::canonicalize(entry).ok()?))
+}
+
+/// Returns whether a filesystem type is served over the network
+fn is_network_fs(fs_type: &str) -> bool {
+    NETWORK_FS_TYPES.contains(&fs_type)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_is_network_fs() {
+        assert!(is_network_fs("nfs4"));
+        assert!(is_network_fs("fuse.sshfs"));
+        assert!(!is_network_fs("ext4"));
+    }
+
+    #[test]
+    fn test_network_entry_is_not_probed() {
+        let temp_dir = TempDir::new().unwrap();
+        let mount_point = temp_dir.path().join("nfs");
+        fs::create_dir(&mount_point).unwrap();
+        let mounts = vec![Mount {
+            device: "server:/export".to_string(),
+            mount_point: mount_point.clone(),
+            fs_type: "nfs4".to_string(),
+        }];
+
+        // Matched as written, without resolving or listing the entry
+        let slow = find_slow_in(&[mount_point.join("bin")], &mounts);
+        assert_eq!(slow.len(), 1);
+        assert_eq!(
+            slow[0].reasons,
+            vec![SlowReason::NetworkMount("nfs4".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_probe() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("tool"), "").unwrap();
+        assert_eq!(probe(temp_dir.path()).unwrap().files, 1);
+        assert!(probe(&temp_dir.path().join("tool")).is_none());
+        assert!(probe(Path::new("/pm-test/missing")).is_none());
+    }
+}
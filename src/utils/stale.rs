@@ -0,0 +1,169 @@
+//! Detection of PATH entries left behind by removed toolchains.
+//!
+//! Version managers put shim directories on PATH, and toolchains installed
+//! under them get their own `bin` directories. Uninstalling the manager or
+//! a toolchain rarely cleans PATH up: `~/.rbenv/shims` lingers after rbenv
+//! is gone, and `~/.nvm/versions/node/v16.20.0/bin` after that version was
+//! removed. Entries are matched to their owner with [`Layout::attribute`].
+
+use crate::utils::attribution::{Ecosystem, Layout};
+use crate::utils::path_scanner::is_executable;
+use std::path::{Component, Path, PathBuf};
+
+/// A PATH entry whose toolchain or version manager is gone
+#[derive(Debug, Clone, PartialEq)]
+pub struct StaleEntry {
+    /// The PATH entry
+    pub entry: PathBuf,
+    /// Ecosystem the entry belongs to
+    pub ecosystem: Ecosystem,
+    /// Why the entry is considered stale
+    pub reason: String,
+}
+
+/// Finds entries belonging to uninstalled version managers or removed toolchains
+///
+/// # Arguments
+/// * `layout` - Install locations used to attribute entries
+/// * `entries` - PATH entries, also searched for version manager executables
+pub fn find_stale(layout: &Layout, entries: &[PathBuf]) -> Vec<StaleEntry> {
+    entries
+        .iter()
+        .filter_map(|entry| {
+            let ecosystem = layout.attribute(entry)?;
+            let reason = stale_reason(layout, ecosystem, entry, entries)?;
+            Some(StaleEntry {
+                entry: entry.clone(),
+                ecosystem,
+                reason,
+            })
+        })
+        .collect()
+}
+
+/// Explains why an entry is stale, or returns `None` if it isn't
+fn stale_reason(
+    layout: &Layout,
+    ecosystem: Ecosystem,
+    entry: &Path,
+    entries: &[PathBuf],
+) -> Option<String> {
+    match ecosystem {
+        Ecosystem::Rbenv | Ecosystem::Pyenv | Ecosystem::Nodenv | Ecosystem::Asdf => {
+            let root = layout.manager_root(ecosystem)?;
+            if !root.exists() {
+                return Some(format!("{} no longer exists", root.display()));
+            }
+            let name = ecosystem.to_string();
+            let installed = [root.join("bin"), root.join("libexec")]
+                .iter()
+                .chain(entries.iter())
+                .any(|dir| is_executable(&dir.join(&name)));
+            if !installed {
+                return Some(format!("{} is not installed", name));
+            }
+            removed_version(root, entry, "versions", "version")
+        }
+        Ecosystem::Nvm => {
+            let root = layout.manager_root(ecosystem)?;
+            if !root.join("nvm.sh").exists() {
+                return Some("nvm is not installed".to_string());
+            }
+            removed_version(&root.join("versions"), entry, "node", "node")
+        }
+        Ecosystem::Rustup => removed_version(&layout.rustup_home, entry, "toolchains", "toolchain"),
+        Ecosystem::Go => {
+            let sdk = layout.home.join("sdk");
+            if entry.starts_with(&sdk) && !entry.exists() {
+                let name = first_component(entry.strip_prefix(&sdk).ok()?)?;
+                return Some(format!("Go toolchain {} was removed", name));
+            }
+            None
+        }
+        _ => None,
+    }
+}
+
+/// Checks whether an entry inside `root/<kind>/<name>/` refers to a removed version
+///
+/// `label` names what was removed, e.g. "toolchain".
+fn removed_version(root: &Path, entry: &Path, kind: &str, label: &str) -> Option<String> {
+    let versions = root.join(kind);
+    let name = first_component(entry.strip_prefix(&versions).ok()?)?;
+    if versions.join(&name).exists() {
+        None
+    } else {
+        Some(format!("{} {} was removed", label, name))
+    }
+}
+
+/// Returns the first component of a relative path as a string
+fn first_component(path: &Path) -> Option<String> {
+    match path.components().next()? {
+        Component::Normal(name) => Some(name.to_string_lossy().into_owned()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn make_executable(path: &Path) {
+        use std::os::unix::fs::PermissionsExt;
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, "#!/bin/sh\n").unwrap();
+        fs::set_permissions(path, fs::Permissions::from_mode(0o755)).unwrap();
+    }
+
+    #[test]
+    fn test_uninstalled_manager() {
+        let temp_dir = TempDir::new().unwrap();
+        let layout = Layout::for_home(temp_dir.path());
+        let shims = layout.rbenv_root.join("shims");
+        fs::create_dir_all(&shims).unwrap();
+        let entries = vec![shims.clone(), PathBuf::from("/usr/bin")];
+
+        let stale = find_stale(&layout, &entries);
+        assert_eq!(stale.len(), 1);
+        assert_eq!(stale[0].ecosystem, Ecosystem::Rbenv);
+        assert_eq!(stale[0].reason, "rbenv is not installed");
+
+        // rbenv installed elsewhere on PATH, e.g. by Homebrew
+        let brew_bin = temp_dir.path().join("brew/bin");
+        make_executable(&brew_bin.join("rbenv"));
+        let entries = vec![shims, brew_bin];
+        assert!(find_stale(&layout, &entries).is_empty());
+    }
+
+    #[test]
+    fn test_removed_versions() {
+        let temp_dir = TempDir::new().unwrap();
+        let layout = Layout::for_home(temp_dir.path());
+        fs::create_dir_all(layout.nvm_dir.join("versions/node/v20.11.0/bin")).unwrap();
+        fs::write(layout.nvm_dir.join("nvm.sh"), "").unwrap();
+        fs::create_dir_all(layout.rustup_home.join("toolchains")).unwrap();
+
+        let entries = vec![
+            layout.nvm_dir.join("versions/node/v16.20.0/bin"),
+            layout.nvm_dir.join("versions/node/v20.11.0/bin"),
+            layout.rustup_home.join("toolchains/nightly-2023-01-01/bin"),
+            temp_dir.path().join("sdk/go1.19/bin"),
+        ];
+
+        let reasons: Vec<String> = find_stale(&layout, &entries)
+            .into_iter()
+            .map(|stale| stale.reason)
+            .collect();
+        assert_eq!(
+            reasons,
+            vec![
+                "node v16.20.0 was removed",
+                "toolchain nightly-2023-01-01 was removed",
+                "Go toolchain go1.19 was removed",
+            ]
+        );
+    }
+}
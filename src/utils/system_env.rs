@@ -0,0 +1,411 @@
+//! Editing the system-wide PATH in `/etc/environment`.
+//!
+//! The file is read by `pam_env` at login and uses its own format, not shell
+//! syntax: `KEY=VALUE` lines, optionally prefixed with `export`, with
+//! matching quotes around the value stripped and nothing expanded or
+//! unescaped. Only the PATH line is rewritten; comments, other variables and
+//! blank lines are kept as they are.
+//!
+//! Writing needs root. When pathmaster isn't running as root, it re-runs
+//! itself through `sudo` (or `pkexec` when sudo isn't installed) with the
+//! hidden `system-write` command and passes the new file on its standard
+//! input. Nothing is staged on disk, so no other user can swap the content
+//! before root installs it. `system-write` backs up the old file to its own
+//! backup directory and replaces it.
+
+use crate::msg;
+use crate::utils::check_mode;
+use crate::utils::root;
+use crate::utils::user;
+use chrono::Local;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus, Stdio};
+
+/// The file holding the system-wide environment
+pub const ETC_ENVIRONMENT: &str = "/etc/environment";
+
+/// Where backups of `/etc/environment` are kept, separate from user backups
+pub const SYSTEM_BACKUP_DIR: &str = "/var/backups/pathmaster";
+
+/// PATH assumed when `/etc/environment` doesn't set one
+pub const DEFAULT_SYSTEM_PATH: &str =
+    "/usr/local/sbin:/usr/local/bin:/usr/sbin:/usr/bin:/sbin:/bin";
+
+/// Returns where `/etc/environment` is read and written, which is under
+/// the `--root` directory when one is set
+pub fn etc_environment() -> PathBuf {
+    root::rooted(ETC_ENVIRONMENT)
+}
+
+/// An `/etc/environment` file, kept line by line
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct EtcEnvironment {
+    lines: Vec<String>,
+}
+
+/// A `PATH=` line split into the parts that are kept when it's rewritten
+#[derive(Debug, PartialEq)]
+struct PathLine<'a> {
+    /// Indentation and `export ` before the name
+    prefix: &'a str,
+    /// Quote character around the value, if any
+    quote: Option<char>,
+    /// The value with its quotes removed
+    value: &'a str,
+}
+
+impl EtcEnvironment {
+    /// Parses the file's content
+    pub fn parse(content: &str) -> Self {
+        EtcEnvironment {
+            lines: content.lines().map(str::to_string).collect(),
+        }
+    }
+
+    /// Reads a file; a missing file is treated as empty
+    pub fn read(file: &Path) -> io::Result<Self> {
+        match fs::read_to_string(file) {
+            Ok(content) => Ok(Self::parse(&content)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(io::Error::new(
+                e.kind(),
+                format!("Cannot read {}: {}", file.display(), e),
+            )),
+        }
+    }
+
+    /// Returns the PATH entries set by the file, if it sets PATH.
+    ///
+    /// pam_env applies the lines in order, so the last assignment wins.
+    pub fn path_entries(&self) -> Option<Vec<PathBuf>> {
+        let line = self
+            .lines
+            .iter()
+            .rev()
+            .find_map(|line| parse_path_line(line))?;
+        Some(
+            line.value
+                .split(':')
+                .filter(|entry| !entry.is_empty())
+                .map(PathBuf::from)
+                .collect(),
+        )
+    }
+
+    /// Replaces the value of the PATH line, or appends one.
+    ///
+    /// The line keeps its indentation, `export` prefix and quoting. The
+    /// format has no escapes, so entries containing quotes or newlines are
+    /// refused.
+    pub fn set_path(&mut self, entries: &[PathBuf]) -> io::Result<()> {
+        let mut parts = Vec::with_capacity(entries.len());
+        for entry in entries {
+            let text = entry
+                .to_str()
+                .filter(|text| !text.contains(['"', '\'', '\n']));
+            match text {
+                Some(text) => parts.push(text),
+                None => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!(
+                            "PATH entry '{}' can't be written to {}",
+                            entry.display(),
+                            ETC_ENVIRONMENT
+                        ),
+                    ))
+                }
+            }
+        }
+        let value = parts.join(":");
+
+        let position = self
+            .lines
+            .iter()
+            .rposition(|line| parse_path_line(line).is_some());
+        match position {
+            Some(index) => {
+                let line = parse_path_line(&self.lines[index]).expect("PATH line");
+                let quote = line
+                    .quote
+                    .or_else(|| value.contains(char::is_whitespace).then_some('"'));
+                self.lines[index] = format_path_line(line.prefix, quote, &value);
+            }
+            None => self.lines.push(format_path_line("", Some('"'), &value)),
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for EtcEnvironment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for line in &self.lines {
+            writeln!(f, "{}", line)?;
+        }
+        Ok(())
+    }
+}
+
+/// Splits a `PATH=` assignment, returning `None` for any other line
+fn parse_path_line(line: &str) -> Option<PathLine<'_>> {
+    let body = line.trim_start();
+    let unexported = body
+        .strip_prefix("export")
+        .filter(|rest| rest.starts_with([' ', '\t']))
+        .map(str::trim_start)
+        .unwrap_or(body);
+    let prefix = &line[..line.len() - unexported.len()];
+
+    let value = unexported.strip_prefix("PATH=")?.trim_end();
+    for quote in ['"', '\''] {
+        if value.len() >= 2 && value.starts_with(quote) && value.ends_with(quote) {
+            return Some(PathLine {
+                prefix,
+                quote: Some(quote),
+                value: &value[1..value.len() - 1],
+            });
+        }
+    }
+    Some(PathLine {
+        prefix,
+        quote: None,
+        value,
+    })
+}
+
+/// Formats a `PATH=` assignment
+fn format_path_line(prefix: &str, quote: Option<char>, value: &str) -> String {
+    match quote {
+        Some(quote) => format!("{}PATH={}{}{}", prefix, quote, value, quote),
+        None => format!("{}PATH={}", prefix, value),
+    }
+}
+
+/// Returns the system-wide PATH, falling back to a standard default
+pub fn read_path() -> io::Result<Vec<PathBuf>> {
+    let file = etc_environment();
+    match EtcEnvironment::read(&file)?.path_entries() {
+        Some(entries) => Ok(entries),
+        None => {
+            println!(
+                "{}",
+                msg!(
+                    "system-no-path",
+                    file = ETC_ENVIRONMENT,
+                    path = DEFAULT_SYSTEM_PATH
+                )
+            );
+            Ok(env::split_paths(DEFAULT_SYSTEM_PATH).collect())
+        }
+    }
+}
+
+/// Writes the system-wide PATH to `/etc/environment`, asking for root if needed.
+///
+/// Under `--root` the file is written directly, without asking.
+pub fn write_path(entries: &[PathBuf]) -> io::Result<()> {
+    let file = etc_environment();
+    let mut environment = EtcEnvironment::read(&file)?;
+    environment.set_path(entries)?;
+    let content = environment.to_string();
+
+    if check_mode::is_check_mode() {
+        check_mode::record_write(&file, &content);
+        return Ok(());
+    }
+    if root::get_root().is_some() || user::is_root() {
+        install(&file, &root::rooted(SYSTEM_BACKUP_DIR), &content)
+    } else {
+        escalate(&content)
+    }
+}
+
+/// Installs the `/etc/environment` read from standard input; run as root
+/// by `system-write`
+///
+/// Content that doesn't set PATH is refused, so an empty or cut-off input
+/// can't wipe the file.
+pub fn install_from_stdin() -> io::Result<()> {
+    if !user::is_root() {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            "system-write must be run as root",
+        ));
+    }
+    let mut content = String::new();
+    io::stdin().read_to_string(&mut content)?;
+    if EtcEnvironment::parse(&content).path_entries().is_none() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "system-write expects the new {} on standard input, with a PATH line",
+                ETC_ENVIRONMENT
+            ),
+        ));
+    }
+    install(
+        Path::new(ETC_ENVIRONMENT),
+        Path::new(SYSTEM_BACKUP_DIR),
+        &content,
+    )
+}
+
+/// Backs up `file` into `backup_dir`, then replaces it with `content`.
+///
+/// The new content is written next to the file and renamed over it, so
+/// a failed write never leaves a truncated file behind.
+fn install(file: &Path, backup_dir: &Path, content: &str) -> io::Result<()> {
+    let permissions = match fs::metadata(file) {
+        Ok(metadata) => {
+            fs::create_dir_all(backup_dir)?;
+            let timestamp = Local::now().format("%Y%m%d%H%M%S").to_string();
+            let backup = backup_dir.join(format!("environment_{}", timestamp));
+            fs::copy(file, &backup)?;
+            println!("{}", msg!("system-backup", file = file, backup = &backup));
+            Some(metadata.permissions())
+        }
+        Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+        Err(e) => return Err(e),
+    };
+
+    let staging = file.with_extension("pathmaster-new");
+    fs::write(&staging, content)?;
+    if let Some(permissions) = permissions {
+        fs::set_permissions(&staging, permissions)?;
+    }
+    fs::rename(&staging, file)?;
+    println!("{}", msg!("system-updated", file = file));
+    Ok(())
+}
+
+/// Runs `pathmaster system-write` as root to install `content`
+fn escalate(content: &str) -> io::Result<()> {
+    let exe = env::current_exe()?;
+
+    println!("{}", msg!("system-needs-root", file = ETC_ENVIRONMENT));
+    let mut result = run_system_write(Command::new("sudo").arg("--"), &exe, content);
+    if matches!(&result, Err(e) if e.kind() == io::ErrorKind::NotFound) {
+        result = run_system_write(&mut Command::new("pkexec"), &exe, content);
+    }
+
+    match result {
+        Ok(status) if status.success() => Ok(()),
+        Ok(status) => Err(io::Error::other(format!(
+            "updating {} as root failed ({})",
+            ETC_ENVIRONMENT, status
+        ))),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            format!(
+                "{} can only be changed by root, and neither sudo nor pkexec is installed",
+                ETC_ENVIRONMENT
+            ),
+        )),
+        Err(e) => Err(e),
+    }
+}
+
+/// Runs `exe system-write` through `command`, writing `content` to its
+/// standard input
+fn run_system_write(command: &mut Command, exe: &Path, content: &str) -> io::Result<ExitStatus> {
+    let mut child = command
+        .arg(exe)
+        .arg("system-write")
+        .stdin(Stdio::piped())
+        .spawn()?;
+    let written = match child.stdin.take() {
+        Some(mut stdin) => stdin.write_all(content.as_bytes()),
+        None => Ok(()),
+    };
+    let status = child.wait()?;
+    // A failed write only matters if root went on to install the file;
+    // otherwise the exit status tells what went wrong
+    if status.success() {
+        written?;
+    }
+    Ok(status)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    #[cfg(unix)]
+    fn test_run_system_write_passes_content_on_stdin() {
+        let temp_dir = TempDir::new().unwrap();
+        let received = temp_dir.path().join("received");
+        let mut command = Command::new("sh");
+        command
+            .arg("-c")
+            .arg(r#"test "$1" = system-write && cat > "$RECEIVED""#)
+            .env("RECEIVED", &received);
+
+        let status =
+            run_system_write(&mut command, Path::new("pathmaster"), "PATH=\"/usr/bin\"\n").unwrap();
+        assert!(status.success());
+        assert_eq!(
+            fs::read_to_string(&received).unwrap(),
+            "PATH=\"/usr/bin\"\n"
+        );
+    }
+
+    const SAMPLE: &str =
+        "# System environment\nLANG=en_US.UTF-8\n  export PATH='/usr/bin:/bin'\nEDITOR=vim\n";
+
+    #[test]
+    fn test_path_entries() {
+        let environment = EtcEnvironment::parse(SAMPLE);
+        assert_eq!(
+            environment.path_entries(),
+            Some(vec![PathBuf::from("/usr/bin"), PathBuf::from("/bin")])
+        );
+        assert_eq!(EtcEnvironment::parse("LANG=C\n").path_entries(), None);
+        // No expansion: $HOME stays literal
+        assert_eq!(
+            EtcEnvironment::parse("PATH=$HOME/bin").path_entries(),
+            Some(vec![PathBuf::from("$HOME/bin")])
+        );
+    }
+
+    #[test]
+    fn test_set_path_preserves_lines() {
+        let mut environment = EtcEnvironment::parse(SAMPLE);
+        environment
+            .set_path(&[PathBuf::from("/opt/bin"), PathBuf::from("/usr/bin")])
+            .unwrap();
+        assert_eq!(
+            environment.to_string(),
+            "# System environment\nLANG=en_US.UTF-8\n  export PATH='/opt/bin:/usr/bin'\nEDITOR=vim\n"
+        );
+
+        let mut environment = EtcEnvironment::parse("LANG=C\n");
+        environment.set_path(&[PathBuf::from("/usr/bin")]).unwrap();
+        assert_eq!(environment.to_string(), "LANG=C\nPATH=\"/usr/bin\"\n");
+
+        assert!(environment
+            .set_path(&[PathBuf::from("/opt/\"odd\"")])
+            .is_err());
+    }
+
+    #[test]
+    fn test_install_backs_up() {
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join("environment");
+        let backups = temp_dir.path().join("backups");
+        fs::write(&file, "PATH=/bin\n").unwrap();
+
+        install(&file, &backups, "PATH=/usr/bin\n").unwrap();
+
+        assert_eq!(fs::read_to_string(&file).unwrap(), "PATH=/usr/bin\n");
+        let saved: Vec<_> = fs::read_dir(&backups).unwrap().collect();
+        assert_eq!(saved.len(), 1);
+        let backup = saved[0].as_ref().unwrap().path();
+        assert_eq!(fs::read_to_string(backup).unwrap(), "PATH=/bin\n");
+    }
+}
@@ -0,0 +1,18 @@
+//! Helpers shared by the unit tests.
+
+use crate::utils::last_check::Problem;
+use std::path::PathBuf;
+
+/// Builds PATH entries from strings
+pub fn paths(entries: &[&str]) -> Vec<PathBuf> {
+    entries.iter().map(PathBuf::from).collect()
+}
+
+/// Builds a check problem of `kind` for `path`, without detail
+pub fn problem(kind: &str, path: &str) -> Problem {
+    Problem {
+        kind: kind.to_string(),
+        path: PathBuf::from(path),
+        detail: String::new(),
+    }
+}
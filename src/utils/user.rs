@@ -0,0 +1,45 @@
+//! The user pathmaster runs as.
+//!
+//! The effective uid decides whether `/etc/environment` can be written
+//! without `sudo`, and which PATH directories `audit` reports as owned by
+//! someone else. It's read from the owner of `/proc/self` rather than by
+//! running `id`, which would be looked up through the very PATH being
+//! changed or audited.
+
+/// Returns the effective uid of this process
+///
+/// # Returns
+/// * `Some(u32)` - The uid owning `/proc/self`
+/// * `None` - If it can't be told, e.g. on systems without `/proc`
+#[cfg(unix)]
+pub fn effective_uid() -> Option<u32> {
+    use std::os::unix::fs::MetadataExt;
+    std::fs::metadata("/proc/self")
+        .ok()
+        .map(|metadata| metadata.uid())
+}
+
+/// Returns the effective uid of this process, which isn't known here
+#[cfg(not(unix))]
+pub fn effective_uid() -> Option<u32> {
+    None
+}
+
+/// Returns whether the process runs as root; `false` when that can't be told
+pub fn is_root() -> bool {
+    effective_uid() == Some(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_effective_uid_owns_new_files() {
+        use std::os::unix::fs::MetadataExt;
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let owner = std::fs::metadata(file.path()).unwrap().uid();
+        assert_eq!(effective_uid(), Some(owner));
+    }
+}
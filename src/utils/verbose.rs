@@ -0,0 +1,15 @@
+//! Global verbose output switch, set from the `--verbose` flag.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static VERBOSE: AtomicBool = AtomicBool::new(false);
+
+/// Enables or disables verbose output
+pub fn set_verbose(verbose: bool) {
+    VERBOSE.store(verbose, Ordering::Relaxed);
+}
+
+/// Returns whether verbose output is enabled
+pub fn is_verbose() -> bool {
+    VERBOSE.load(Ordering::Relaxed)
+}
@@ -0,0 +1,140 @@
+//! Windows PATH entries under WSL.
+//!
+//! WSL appends the Windows PATH to the Linux one, so PATH gains dozens of
+//! entries such as `/mnt/c/Windows/System32`. Those live on the Windows
+//! filesystem, which is slow to search from Linux, so every lookup of a
+//! command that isn't found on the Linux side gets noticeably slower. This
+//! module recognizes those entries so `flush --strip-windows` and
+//! `clean --strip-windows` can remove them and `check` can explain the
+//! tradeoff.
+
+use crate::utils::root;
+use std::env;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+/// WSL's per-distribution settings file
+pub const WSL_CONF: &str = "/etc/wsl.conf";
+
+/// Settings from `/etc/wsl.conf` that decide where Windows entries come from
+#[derive(Debug, Clone, PartialEq)]
+pub struct WslConf {
+    /// Where Windows drives are mounted, `/mnt/` by default
+    pub automount_root: PathBuf,
+    /// Whether WSL appends the Windows PATH at startup
+    pub append_windows_path: bool,
+}
+
+impl Default for WslConf {
+    fn default() -> Self {
+        WslConf {
+            automount_root: PathBuf::from("/mnt/"),
+            append_windows_path: true,
+        }
+    }
+}
+
+impl WslConf {
+    /// Reads `/etc/wsl.conf`, using WSL's defaults when it's missing
+    pub fn load() -> Self {
+        fs::read_to_string(root::rooted(WSL_CONF))
+            .map(|content| Self::parse(&content))
+            .unwrap_or_default()
+    }
+
+    /// Parses the `[automount] root` and `[interop] appendWindowsPath` settings
+    fn parse(content: &str) -> Self {
+        let mut conf = WslConf::default();
+        let mut section = String::new();
+        for line in content.lines() {
+            let line = line.trim();
+            if line.starts_with('#') || line.starts_with(';') {
+                continue;
+            }
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                section = name.trim().to_lowercase();
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            let key = key.trim().to_lowercase();
+            let value = value.trim().trim_matches('"');
+            match (section.as_str(), key.as_str()) {
+                ("automount", "root") if !value.is_empty() => {
+                    conf.automount_root = PathBuf::from(value);
+                }
+                ("interop", "appendwindowspath") => {
+                    conf.append_windows_path = !value.eq_ignore_ascii_case("false");
+                }
+                _ => {}
+            }
+        }
+        conf
+    }
+}
+
+/// Returns whether pathmaster runs under the Windows Subsystem for Linux
+pub fn is_wsl() -> bool {
+    env::var_os("WSL_DISTRO_NAME").is_some()
+        || Path::new("/proc/sys/fs/binfmt_misc/WSLInterop").exists()
+        || fs::read_to_string("/proc/sys/kernel/osrelease")
+            .is_ok_and(|release| release.to_lowercase().contains("microsoft"))
+}
+
+/// Returns whether an entry lies on a Windows drive mounted under `root`,
+/// such as `/mnt/c/Windows`
+pub fn is_windows_entry(entry: &Path, root: &Path) -> bool {
+    let Ok(rest) = entry.strip_prefix(root) else {
+        return false;
+    };
+    match rest.components().next() {
+        Some(Component::Normal(drive)) => {
+            let drive = drive.to_string_lossy();
+            drive.len() == 1 && drive.chars().all(|c| c.is_ascii_alphabetic())
+        }
+        _ => false,
+    }
+}
+
+/// Returns the Windows entries in `entries`, or none when not running under WSL
+pub fn windows_entries(entries: &[PathBuf]) -> Vec<PathBuf> {
+    if !is_wsl() {
+        return Vec::new();
+    }
+    let root = WslConf::load().automount_root;
+    entries
+        .iter()
+        .filter(|entry| is_windows_entry(entry, &root))
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_windows_entry() {
+        let root = Path::new("/mnt/");
+        assert!(is_windows_entry(Path::new("/mnt/c/Windows/System32"), root));
+        assert!(is_windows_entry(Path::new("/mnt/d"), root));
+        assert!(!is_windows_entry(Path::new("/mnt/data/bin"), root));
+        assert!(!is_windows_entry(Path::new("/usr/bin"), root));
+        assert!(is_windows_entry(
+            Path::new("/c/Program Files/Git/cmd"),
+            Path::new("/")
+        ));
+    }
+
+    #[test]
+    fn test_parse_wsl_conf() {
+        assert_eq!(WslConf::parse(""), WslConf::default());
+
+        let conf = WslConf::parse(
+            "[automount]\nroot = /\noptions = \"metadata\"\n\n[interop]\n# keep it short\nappendWindowsPath = false\n",
+        );
+        assert_eq!(conf.automount_root, PathBuf::from("/"));
+        assert!(!conf.append_windows_path);
+    }
+}